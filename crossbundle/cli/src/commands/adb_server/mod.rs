@@ -0,0 +1,53 @@
+use crate::error::Result;
+use clap::Parser;
+use crossbundle_tools::{
+    commands::android::{AdbServer, AdbServerStatus},
+    types::{AndroidSdk, Config},
+};
+
+#[derive(Parser, Clone, Debug)]
+pub enum AdbServerCommand {
+    /// Starts the adb server if it's not running and restarts it if its version doesn't
+    /// match the adb client
+    Start,
+    /// Kills the adb server
+    Stop,
+    /// Prints the address and version of the adb server and the adb client
+    Status,
+}
+
+impl AdbServerCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        let sdk = AndroidSdk::from_env()?;
+        let server = AdbServer::from_env()?;
+        match self {
+            Self::Start => match server.ensure_running(&sdk)? {
+                AdbServerStatus::Running => config.status_message("Running adb server", &server)?,
+                AdbServerStatus::Started => config.status_message("Started adb server", &server)?,
+                AdbServerStatus::Restarted => {
+                    config.status_message("Restarted adb server", &server)?
+                }
+            },
+            Self::Stop => {
+                server.kill(&sdk)?;
+                config.status_message("Stopped adb server", &server)?;
+            }
+            Self::Status => {
+                let client_version = AdbServer::client_version(&sdk)?;
+                config.status_message("adb client", format!("version {}", client_version))?;
+                match server.server_version() {
+                    Some(version) if version == client_version => config
+                        .status_message("adb server", format!("{} version {}", server, version))?,
+                    Some(version) => config.shell().warn(format!(
+                        "adb server {} version {} doesn't match the client",
+                        server, version
+                    ))?,
+                    None => config
+                        .shell()
+                        .warn(format!("adb server {} is not running", server))?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use crate::error::*;
+use clap::Parser;
+use crossbundle_tools::{
+    commands::{android::*, ByteSize},
+    types::{AndroidSdk, Config},
+};
+use std::path::PathBuf;
+
+#[derive(Parser, Clone, Debug)]
+pub struct AnalyzeCommand {
+    /// APK to analyze
+    pub apk: PathBuf,
+    /// Number of the largest files to print
+    #[clap(long, default_value = "20")]
+    pub top: usize,
+    /// Print the full breakdown as JSON, e.g. to check size budgets on CI
+    #[clap(long)]
+    pub json: bool,
+}
+
+impl AnalyzeCommand {
+    /// Prints sizes of the APK categories and the largest files sorted by download size.
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        let sdk = AndroidSdk::from_env()?;
+        let analysis = ApkAnalysis::read(&sdk, &self.apk)?;
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&analysis)?);
+            return Ok(());
+        }
+        config.status_message("Analyzed", self.apk.display())?;
+        println!(
+            "{} {} ({})",
+            analysis.package.as_deref().unwrap_or("unknown package"),
+            analysis.version_name.as_deref().unwrap_or_default(),
+            analysis
+                .version_code
+                .map(|code| code.to_string())
+                .unwrap_or_default()
+        );
+        println!(
+            "File size: {}, download size: {}, install size: {}",
+            ByteSize(analysis.file_size),
+            ByteSize(analysis.download_size),
+            ByteSize(analysis.install_size)
+        );
+        println!("Categories:");
+        for category in &analysis.categories {
+            println!(
+                "    {:>12} {:>12} {:>6}%  {} ({} files)",
+                ByteSize(category.download_size).to_string(),
+                ByteSize(category.install_size).to_string(),
+                percent(category.download_size, analysis.download_size),
+                category.category.name(),
+                category.files
+            );
+        }
+        if !analysis.resource_types.is_empty() {
+            println!("Resources:");
+            for (resource_type, count) in &analysis.resource_types {
+                println!("    {:>6}  {}", count, resource_type);
+            }
+        }
+        println!("Largest files:");
+        for entry in analysis.entries.iter().take(self.top) {
+            println!(
+                "    {:>12} {:>12} {:>6}%  {}",
+                ByteSize(entry.download_size).to_string(),
+                ByteSize(entry.install_size).to_string(),
+                percent(entry.download_size, analysis.download_size),
+                entry.path
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Formats the share of the total with one decimal.
+fn percent(size: u64, total: u64) -> String {
+    if total == 0 {
+        return "0.0".to_owned();
+    }
+    format!("{:.1}", size as f64 * 100.0 / total as f64)
+}
@@ -0,0 +1,163 @@
+#[cfg(feature = "android")]
+use super::build::android::AndroidBuildCommand;
+#[cfg(feature = "apple")]
+use super::build::apple::IosBuildCommand;
+use super::build::BuildContext;
+use crate::error::*;
+use clap::Parser;
+#[cfg(feature = "apple")]
+use crossbundle_tools::commands::apple;
+use crossbundle_tools::types::Config;
+#[cfg(feature = "android")]
+use crossbundle_tools::{
+    commands::android::*,
+    types::{AndroidSdk, AndroidStrategy},
+};
+use std::path::PathBuf;
+
+#[derive(Parser, Clone, Debug)]
+pub struct AppCommand {
+    #[clap(subcommand)]
+    pub action: AppAction,
+    /// Package name or bundle identifier. By default it's read from the project metadata
+    #[clap(long, global = true)]
+    pub package: Option<String>,
+    /// Serial of the Android device or emulator. Required if several devices are
+    /// connected
+    #[clap(long, short = 'd', global = true)]
+    pub device: Option<String>,
+    /// UDID of the Apple simulator or `booted`. The action is performed on the simulator
+    /// instead of the Android device
+    #[clap(long, global = true, conflicts_with = "device")]
+    pub simulator: Option<String>,
+}
+
+#[derive(Parser, Clone, Debug)]
+pub enum AppAction {
+    /// Deletes all data of the application. On Android runtime permissions are reset too
+    ClearData,
+    /// Grants the permission to the application. Android permissions can be short like
+    /// `CAMERA`, simulator permissions are services like `camera`, `photos` or `location`
+    Grant {
+        /// Permission or privacy service
+        permission: String,
+    },
+    /// Revokes the permission from the application
+    Revoke {
+        /// Permission or privacy service
+        permission: String,
+    },
+    /// Opens the URL in the application
+    Deeplink {
+        /// URL like `https://crossbow.example/level/1` or `game://level/1`
+        url: String,
+    },
+    /// Saves data of the application into the tar archive, e.g. to attach save games to
+    /// bug reports. On Android the application must be debuggable
+    Backup {
+        /// Path of the archive
+        output: PathBuf,
+    },
+    /// Restores data of the application from the archive created by `backup`, e.g. on
+    /// another device. On Android the application must be debuggable
+    Restore {
+        /// Path of the archive
+        input: PathBuf,
+    },
+}
+
+impl AppCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        match self.simulator.as_deref() {
+            #[cfg(feature = "apple")]
+            Some(udid) => self.run_apple(config, udid),
+            #[cfg(feature = "android")]
+            None => self.run_android(config),
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::InvalidMetadata(anyhow::anyhow!(
+                "the platform is not supported by this build of crossbundle"
+            ))),
+        }
+    }
+
+    #[cfg(feature = "android")]
+    fn run_android(&self, config: &Config) -> Result<()> {
+        let package = match &self.package {
+            Some(package) => package.clone(),
+            None => {
+                let context = BuildContext::new(config, None)?;
+                AndroidBuildCommand::get_android_manifest(&context, AndroidStrategy::NativeApk)?
+                    .package
+            }
+        };
+        let sdk = AndroidSdk::from_env()?;
+        let device = select_android_device(&sdk, self.device.as_deref())?;
+        match &self.action {
+            AppAction::ClearData => {
+                clear_app_data(&sdk, &package, Some(&device))?;
+                config.status_message("Cleared", format!("data of {}", package))?;
+            }
+            AppAction::Grant { permission } => {
+                grant_permission(&sdk, &package, permission, Some(&device))?;
+                config.status_message("Granted", qualified_permission(permission))?;
+            }
+            AppAction::Revoke { permission } => {
+                revoke_permission(&sdk, &package, permission, Some(&device))?;
+                config.status_message("Revoked", qualified_permission(permission))?;
+            }
+            AppAction::Deeplink { url } => {
+                open_deeplink(&sdk, url, Some(&package), Some(&device))?;
+                config.status_message("Opened", url)?;
+            }
+            AppAction::Backup { output } => {
+                backup_app_data(&sdk, &package, output, Some(&device))?;
+                config.status_message("Saved", output.display())?;
+            }
+            AppAction::Restore { input } => {
+                restore_app_data(&sdk, &package, input, Some(&device))?;
+                config.status_message("Restored", format!("data of {}", package))?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "apple")]
+    fn run_apple(&self, config: &Config, udid: &str) -> Result<()> {
+        let bundle_id = match &self.package {
+            Some(bundle_id) => bundle_id.clone(),
+            None => {
+                let context = BuildContext::new(config, None)?;
+                IosBuildCommand::gen_info_plist(&context, &context.package_name())?
+                    .identification
+                    .bundle_identifier
+            }
+        };
+        match &self.action {
+            AppAction::ClearData => {
+                apple::clear_simulator_app_data(udid, &bundle_id)?;
+                config.status_message("Cleared", format!("data of {}", bundle_id))?;
+            }
+            AppAction::Grant { permission } => {
+                apple::set_simulator_privacy(udid, &bundle_id, permission, true)?;
+                config.status_message("Granted", permission)?;
+            }
+            AppAction::Revoke { permission } => {
+                apple::set_simulator_privacy(udid, &bundle_id, permission, false)?;
+                config.status_message("Revoked", permission)?;
+            }
+            AppAction::Deeplink { url } => {
+                apple::open_simulator_url(udid, url)?;
+                config.status_message("Opened", url)?;
+            }
+            AppAction::Backup { output } => {
+                apple::backup_simulator_app_data(udid, &bundle_id, output)?;
+                config.status_message("Saved", output.display())?;
+            }
+            AppAction::Restore { input } => {
+                apple::restore_simulator_app_data(udid, &bundle_id, input)?;
+                config.status_message("Restored", format!("data of {}", bundle_id))?;
+            }
+        }
+        Ok(())
+    }
+}
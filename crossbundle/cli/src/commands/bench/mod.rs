@@ -0,0 +1,162 @@
+use super::build::{android::AndroidBuildCommand, BuildContext};
+use crate::error::*;
+use clap::Parser;
+use crossbundle_tools::{
+    commands::android::*,
+    types::{AndroidTarget, Config},
+};
+use std::{collections::BTreeMap, path::PathBuf};
+
+#[derive(Parser, Clone, Debug)]
+pub struct BenchCommand {
+    /// Only run benchmarks whose ID contains the filter
+    pub filter: Option<String>,
+    /// Name of the bench target to build. By default all bench targets are built
+    #[clap(long)]
+    pub bench: Option<String>,
+    /// Space or comma separated list of features to activate
+    #[clap(long)]
+    pub features: Vec<String>,
+    /// Serial of the Android device or emulator. Required if several devices are
+    /// connected
+    #[clap(long, short = 'd')]
+    pub device: Option<String>,
+    /// Baseline the results are compared with. Default is
+    /// `target/crossbundle/bench/baseline.json`
+    #[clap(long)]
+    pub baseline: Option<PathBuf>,
+    /// Save the results as the new baseline
+    #[clap(long)]
+    pub save_baseline: bool,
+    /// Slowdown in percent relative to the baseline that fails the command
+    #[clap(long, default_value = "5.0")]
+    pub threshold: f64,
+    /// Don't switch CPU governors to `performance` while the benchmarks run
+    #[clap(long)]
+    pub no_governor: bool,
+    /// Print the comparison as JSON
+    #[clap(long)]
+    pub json: bool,
+}
+
+impl BenchCommand {
+    /// Builds criterion benchmarks for the ABI of the device, runs them on the device
+    /// and compares the results with the baseline.
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        let context = BuildContext::new(config, None)?;
+        let (sdk, ndk) = AndroidBuildCommand::android_toolchain(&context)?;
+        let device = select_android_device(&sdk, self.device.as_deref())?;
+        let abi = device_property(&sdk, &device, "ro.product.cpu.abi")?.unwrap_or_default();
+        let target = AndroidTarget::from_android_abi(&abi)?;
+        // Benchmarks are linked against the platform of the device, so they run there
+        let platform = device_property(&sdk, &device, "ro.build.version.sdk")?
+            .and_then(|api| api.parse().ok())
+            .unwrap_or(21);
+        let project_path = context.package_manifest_path.parent().unwrap();
+
+        config.status_message("Building", format!("benchmarks for {}", abi))?;
+        let executables = build_android_benches(
+            &ndk,
+            target,
+            platform,
+            project_path,
+            self.bench.as_deref(),
+            &self.features,
+        )?;
+        if executables.is_empty() {
+            config
+                .shell()
+                .warn("no bench targets found, add them to `[[bench]]` in Cargo.toml")?;
+            return Ok(());
+        }
+
+        let governors = match self.no_governor {
+            true => None,
+            false => {
+                let governors = CpuGovernors::lock_performance(&sdk, &device)?;
+                if governors.is_none() {
+                    config.shell().warn(
+                        "failed to switch CPU governors to `performance`, it requires root. \
+                         Results may be noisy because of frequency scaling",
+                    )?;
+                }
+                governors
+            }
+        };
+        let mut estimates = Vec::new();
+        let mut result = Ok(());
+        for executable in &executables {
+            config.status_message("Running", executable.display())?;
+            match run_android_bench(&sdk, &device, executable, self.filter.as_deref()) {
+                Ok((bench_estimates, _)) => estimates.extend(bench_estimates),
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+        }
+        // The governors are restored even if a benchmark failed
+        if let Some(governors) = &governors {
+            governors.restore(&sdk, &device)?;
+        }
+        result?;
+
+        let baseline_path = self.baseline.clone().unwrap_or_else(|| {
+            context
+                .target_dir
+                .join("crossbundle")
+                .join("bench")
+                .join("baseline.json")
+        });
+        let baseline: BTreeMap<String, f64> = match baseline_path.exists() {
+            true => serde_json::from_str(&std::fs::read_to_string(&baseline_path)?)?,
+            false => BTreeMap::new(),
+        };
+        let comparisons = compare_bench_results(&estimates, &baseline, self.threshold);
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&comparisons)?);
+        } else {
+            for comparison in &comparisons {
+                let change = match comparison.change_percent {
+                    Some(change) => format!("{:+.2}%", change),
+                    None => "new".to_owned(),
+                };
+                let marker = if comparison.regressed {
+                    "  REGRESSED"
+                } else {
+                    ""
+                };
+                println!(
+                    "{:<48} {:>14.1} ns {:>10}{}",
+                    comparison.id, comparison.mean_ns, change, marker
+                );
+            }
+        }
+        if self.save_baseline {
+            let baseline: BTreeMap<&str, f64> = estimates
+                .iter()
+                .map(|estimate| (estimate.id.as_str(), estimate.mean_ns))
+                .collect();
+            if let Some(parent) = baseline_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&baseline_path, serde_json::to_string_pretty(&baseline)?)?;
+            config.status_message("Saved baseline", baseline_path.display())?;
+            return Ok(());
+        }
+        let regressed: Vec<&str> = comparisons
+            .iter()
+            .filter(|comparison| comparison.regressed)
+            .map(|comparison| comparison.id.as_str())
+            .collect();
+        if !regressed.is_empty() {
+            return Err(crossbundle_tools::error::Error::BenchRegression(format!(
+                "{} slower by more than {}%",
+                regressed.join(", "),
+                self.threshold
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
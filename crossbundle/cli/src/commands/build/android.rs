@@ -1,14 +1,24 @@
-use super::{BuildContext, SharedBuildCommand};
+use super::{
+    check_size_budgets, ensure_rust_targets, record_audit_event, run_hooks, save_output_manifest,
+    sign_provenance, warn_api_levels, BuildContext, SharedBuildCommand,
+};
 use crate::{error::*, types::CrossbowMetadata};
 use android_manifest::AndroidManifest;
 use android_tools::java_tools::{JarSigner, Key};
 use clap::Parser;
+use crossbow::ContentManifest;
 use crossbundle_tools::{
-    commands::{android::*, combine_folders},
+    commands::{
+        add_capabilities_to_manifest, android::*, combine_folders, merge_library_dirs, ApiPlatform,
+        BuildCache, Fingerprint, LibraryResources, VersionCodeRecord,
+    },
     error::CommandExt,
     types::*,
 };
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
 
 /// Specifies flags and options needed to build application
 #[derive(Parser, Clone, Debug, Default)]
@@ -39,6 +49,43 @@ pub struct AndroidBuildCommand {
     /// Signing key alias.
     #[clap(long)]
     pub sign_key_alias: Option<String>,
+    /// Align uncompressed native libraries on 16KB page boundaries. Required to run on
+    /// devices with 16KB memory pages. Needs build-tools 35.0.0 or higher.
+    #[clap(long)]
+    pub page_align_16kb: bool,
+    /// Sign the APK or AAB and the output manifest with cosign. Signatures are saved
+    /// into `<artifact>.sigstore.json` bundles. Not supported with `gradle-apk` strategy
+    #[clap(long)]
+    pub sign_provenance: bool,
+    /// Build one APK per target ABI with `native-apk` strategy. Version codes of split
+    /// APKs are `abi_code * 100000000 + version_code`, where abi_code is 1 for
+    /// armeabi-v7a, 2 for arm64-v8a, 3 for x86 and 4 for x86_64
+    #[clap(long)]
+    pub split_per_abi: bool,
+    /// Run aapt2 link, dexing and signing even if their inputs didn't change since the
+    /// previous build. Cached outputs are stored in `target/crossbundle/cache`
+    #[clap(long)]
+    pub no_build_cache: bool,
+    /// Also build the universal APK with libraries for all target ABIs
+    #[clap(long, requires = "split-per-abi")]
+    pub universal_apk: bool,
+    /// Path to the cosign private key. Keyless signing is used if not specified
+    #[clap(long, requires = "sign-provenance")]
+    pub cosign_key: Option<PathBuf>,
+    /// Number of Android targets compiled in parallel. Every target gets its own cargo
+    /// target directory, so dependencies are compiled once per target
+    #[clap(long, short = 'j', default_value = "1")]
+    pub jobs: usize,
+    /// Build profile from `package.metadata.android.profiles`, e.g. `staging`. Overrides
+    /// the package name, version name, signing key, manifest placeholders and cargo
+    /// profile
+    #[clap(long)]
+    pub profile: Option<String>,
+    /// Generate pseudo-localized strings into `values-en-rXA` with accented and longer
+    /// text and `values-ar-rXB` with right-to-left text to find truncated, hardcoded
+    /// and not mirrored UI without real translations
+    #[clap(long)]
+    pub pseudo_locale: bool,
 }
 
 impl AndroidBuildCommand {
@@ -49,19 +96,39 @@ impl AndroidBuildCommand {
                 .shell()
                 .warn("You provided a signing key but not password - set password please by providing `sign_key_pass` flag")?;
         }
-        let context = BuildContext::new(config, self.shared.target_dir.clone())?;
+        let context = self.build_context(config)?;
+        let manifest = Self::get_android_manifest(&context, self.strategy)?;
+        let min_sdk_version = Self::min_sdk_version(&manifest).to_string();
+        warn_api_levels(config, &context, ApiPlatform::Android, &min_sdk_version)?;
+        let hooks = &context.config.hooks;
+        run_hooks(config, &context, &hooks.pre_build, self.shared.trust)?;
         if let Some(name) = &self.lib {
             self.build_rust_lib(config, &context, name, None)?;
-            return Ok(());
+            return run_hooks(config, &context, &hooks.post_build, self.shared.trust);
         }
-        match &self.strategy {
+        let (artifacts, version_code) = match &self.strategy {
+            AndroidStrategy::NativeApk if self.split_per_abi => {
+                let (manifest, _, apks) = self.execute_split_apks(config, &context)?;
+                self.archive_mappings(config, &context, &manifest)?;
+                let artifacts = apks.into_iter().map(|(_, path)| path).collect();
+                (artifacts, manifest.version_code)
+            }
             AndroidStrategy::NativeApk => {
-                self.execute_apk(config, &context)?;
+                let (manifest, _, apk_path) = self.execute_apk(config, &context)?;
+                self.archive_mappings(config, &context, &manifest)?;
+                (vec![apk_path], manifest.version_code)
             }
             AndroidStrategy::NativeAab => {
-                self.execute_aab(config, &context)?;
+                let (manifest, _, aab_path, _, _) = self.execute_aab(config, &context)?;
+                self.archive_mappings(config, &context, &manifest)?;
+                (vec![aab_path], manifest.version_code)
             }
             AndroidStrategy::GradleApk => {
+                if self.sign_provenance {
+                    config
+                        .shell()
+                        .warn("Provenance signing is not supported with gradle-apk strategy")?;
+                }
                 let (_, _, gradle_project_path) =
                     self.build_gradle(config, &context, &self.export_path)?;
                 config.status("Building Gradle project")?;
@@ -71,9 +138,60 @@ impl AndroidBuildCommand {
                     .arg("-p")
                     .arg(dunce::simplified(&gradle_project_path));
                 gradle.output_err(true)?;
+                (Vec::new(), None)
+            }
+        };
+        check_size_budgets(config, &context, &artifacts)?;
+        let version_code = version_code.map(|version_code| VersionCodeRecord {
+            strategy: context.config.android.version_code_strategy.to_string(),
+            version_code,
+            abi_offsets: self.split_per_abi,
+        });
+        if let Some(manifest_path) = save_output_manifest(&artifacts, version_code)? {
+            if self.sign_provenance {
+                sign_provenance(
+                    config,
+                    &artifacts,
+                    &manifest_path,
+                    self.cosign_key.as_deref(),
+                )?;
             }
         }
-        Ok(())
+        run_hooks(config, &context, &hooks.post_build, self.shared.trust)
+    }
+
+    /// Creates the build context, evaluates the build script and selects the build
+    /// profile. The profile selected by the build script overrides `--profile` flag.
+    pub fn build_context(&self, config: &Config) -> Result<BuildContext> {
+        let mut context = BuildContext::new(config, self.shared.target_dir.clone())?;
+        context.eval_build_script(
+            config,
+            "android",
+            self.shared.release,
+            self.profile.as_deref(),
+        )?;
+        let profile = context
+            .build_script
+            .build_profile
+            .clone()
+            .or_else(|| self.profile.clone());
+        if let Some(profile) = &profile {
+            context.select_android_profile(profile)?;
+            config.status_message("Selected build profile", profile)?;
+        }
+        Ok(context)
+    }
+
+    /// Returns cargo profile: `release` if `--release` flag is specified, otherwise the
+    /// one of the build profile.
+    pub fn cargo_profile(&self, context: &BuildContext) -> Profile {
+        if self.shared.release {
+            return Profile::Release;
+        }
+        context
+            .android_build_profile()
+            .and_then(|profile| profile.cargo_profile)
+            .unwrap_or_default()
     }
 
     /// Compile rust code as a dynamic library, generate Gradle project.
@@ -101,8 +219,12 @@ impl AndroidBuildCommand {
         }
 
         config.status("Preparing resources and assets")?;
-        let (assets, resources) =
-            Self::prepare_assets_and_resources(&context.config, &android_build_dir)?;
+        let (assets, resources) = Self::prepare_assets_and_resources(
+            &context.config,
+            &context.library_resources()?,
+            &android_build_dir,
+            self.pseudo_locale,
+        )?;
         config.status_message("Reading", "AndroidManifest.xml")?;
         let manifest = Self::get_android_manifest(context, AndroidStrategy::GradleApk)?;
 
@@ -141,11 +263,11 @@ impl AndroidBuildCommand {
         lib_name: &str,
         export_path: Option<PathBuf>,
     ) -> Result<()> {
-        let profile = self.shared.profile();
+        let profile = self.cargo_profile(context);
         let example = self.shared.example.as_ref();
         let (project_path, target_dir, package_name) = Self::needed_project_dirs(example, context)?;
         config.status_message("Starting lib build process", &package_name)?;
-        let (sdk, ndk) = Self::android_toolchain()?;
+        let (sdk, ndk) = Self::android_toolchain(context)?;
 
         let android_build_dir = if let Some(export_path) = export_path {
             export_path
@@ -193,26 +315,63 @@ impl AndroidBuildCommand {
         config: &Config,
         context: &BuildContext,
     ) -> Result<(AndroidManifest, AndroidSdk, PathBuf)> {
-        let profile = self.shared.profile();
+        let (manifest, sdk, mut apks) = self.build_native_apks(config, context, false, true)?;
+        let (_, apk_path) = apks.remove(0);
+        Ok((manifest, sdk, apk_path))
+    }
+
+    /// Builds one APK per ABI and the universal APK if `--universal-apk` flag is
+    /// specified. Returns APK paths with their ABIs, `None` for the universal APK.
+    pub fn execute_split_apks(
+        &self,
+        config: &Config,
+        context: &BuildContext,
+    ) -> Result<(
+        AndroidManifest,
+        AndroidSdk,
+        Vec<(Option<AndroidTarget>, PathBuf)>,
+    )> {
+        self.build_native_apks(config, context, true, self.universal_apk)
+    }
+
+    fn build_native_apks(
+        &self,
+        config: &Config,
+        context: &BuildContext,
+        split_per_abi: bool,
+        universal: bool,
+    ) -> Result<(
+        AndroidManifest,
+        AndroidSdk,
+        Vec<(Option<AndroidTarget>, PathBuf)>,
+    )> {
+        let profile = self.cargo_profile(context);
         let example = self.shared.example.as_ref();
         let (project_path, target_dir, package_name) = Self::needed_project_dirs(example, context)?;
         config.status_message("Starting apk build process", &package_name)?;
-        let (sdk, ndk) = Self::android_toolchain()?;
+        let (sdk, ndk) = Self::android_toolchain(context)?;
 
         let android_build_dir = target_dir.join("android").join(&package_name);
         let native_build_dir = android_build_dir.join("native").join("apk");
-        let outputs_build_dir = android_build_dir.join("outputs");
+        let outputs_build_dir = Self::outputs_build_dir(context, &android_build_dir);
         if !outputs_build_dir.exists() {
             std::fs::create_dir_all(&outputs_build_dir)?;
         }
 
         config.status_message("Reading", "AndroidManifest.xml")?;
         let manifest = Self::get_android_manifest(context, AndroidStrategy::NativeApk)?;
-        config.status_message("Generating", "AndroidManifest.xml")?;
-        let manifest_path = save_android_manifest(&native_build_dir, &manifest)?;
+        if context.config.android.stable_ids.is_some() {
+            config.shell().warn(
+                "`stable_ids` is applied only with `native-aab` strategy, APKs are linked with aapt",
+            )?;
+        }
         config.status("Preparing resources and assets")?;
-        let (assets, resources) =
-            Self::prepare_assets_and_resources(&context.config, &android_build_dir)?;
+        let (assets, resources) = Self::prepare_assets_and_resources(
+            &context.config,
+            &context.library_resources()?,
+            &android_build_dir,
+            self.pseudo_locale,
+        )?;
 
         config.status_message("Compiling", "lib")?;
         let target_sdk_version = Self::target_sdk_version(&manifest, &sdk);
@@ -228,49 +387,189 @@ impl AndroidBuildCommand {
             &target_dir,
             config,
         )?;
-
-        config.status_message("Generating", "unaligned APK file")?;
-        let unaligned_apk_path = gen_unaligned_apk(
+        let dex_dir = Self::compile_dex(
+            config,
+            context,
             &sdk,
-            &project_path,
-            &native_build_dir,
-            &manifest_path,
-            &assets,
-            &resources,
-            &package_name,
+            &manifest,
+            profile,
             target_sdk_version,
+            &native_build_dir,
+            &outputs_build_dir,
+            &self.build_cache(context),
         )?;
 
-        config.status("Adding libs into APK file")?;
-        for (compiled_lib, build_target) in compiled_libs {
-            add_libs_into_apk(
-                &sdk,
-                &ndk,
-                &unaligned_apk_path,
-                &compiled_lib,
-                build_target,
-                profile,
-                Self::min_sdk_version(&manifest),
-                &android_build_dir,
-                &target_dir,
-            )?;
+        // Universal APK contains libraries for all ABIs and keeps the version code
+        let mut apks = Vec::new();
+        if universal {
+            apks.push((
+                None,
+                package_name.clone(),
+                manifest.clone(),
+                compiled_libs.clone(),
+            ));
+        }
+        if split_per_abi {
+            for (compiled_lib, build_target) in &compiled_libs {
+                let mut split_manifest = manifest.clone();
+                split_manifest.version_code =
+                    Some(build_target.split_version_code(manifest.version_code.unwrap_or(1))?);
+                apks.push((
+                    Some(*build_target),
+                    format!("{}-{}", package_name, build_target.android_abi()),
+                    split_manifest,
+                    vec![(compiled_lib.clone(), *build_target)],
+                ));
+            }
         }
 
-        config.status("Aligning APK file")?;
-        let aligned_apk_path =
-            align_apk(&sdk, &unaligned_apk_path, &package_name, &outputs_build_dir)?;
+        let mut apk_paths = Vec::new();
+        for (build_target, apk_name, apk_manifest, libs) in apks {
+            config.status_message("Packaging", &apk_name)?;
+            let apk_build_dir = match build_target {
+                Some(build_target) => native_build_dir.join(build_target.android_abi()),
+                None => native_build_dir.clone(),
+            };
+            config.status_message("Generating", "AndroidManifest.xml")?;
+            let manifest_path = save_android_manifest(&apk_build_dir, &apk_manifest)?;
 
-        config.status_message("Generating", "debug signing key")?;
-        let key = Self::find_keystore(
-            self.sign_key_path.clone(),
-            self.sign_key_pass.clone(),
-            self.sign_key_alias.clone(),
-        )?;
+            config.status_message("Generating", "debug signing key")?;
+            let key = self.signing_key(context)?;
+            if apk_paths.is_empty() {
+                Self::gen_assetlinks(config, &manifest, &key, &outputs_build_dir)?;
+            }
 
-        config.status("Signing APK file")?;
-        sign_apk(&sdk, &aligned_apk_path, key)?;
+            let page_size = self.page_align_16kb.then_some(PAGE_SIZE_16KB);
+            let aligned_apk_path = outputs_build_dir.join(format!("{}.apk", apk_name));
+            let shorten_resource_paths =
+                profile == Profile::Release && context.config.android.shorten_resource_paths;
+            let collapse_resource_names =
+                profile == Profile::Release && context.config.android.collapse_resource_names;
+            let resource_map_path = outputs_build_dir.join(RESOURCE_MAP_FILE_NAME);
+            let symbols_dir = self.symbols_dir(context, profile, &manifest);
+            let mut fingerprint = Fingerprint::new("apk");
+            fingerprint
+                .value("ndk", ndk.ndk_path().display())
+                .value("build_tools", sdk.build_deps_version())
+                .value("target_sdk_version", target_sdk_version)
+                .value("profile", profile.to_string())
+                .value("page_size", format!("{:?}", page_size))
+                .value("shorten_resource_paths", shorten_resource_paths)
+                .value("collapse_resource_names", collapse_resource_names)
+                .value(
+                    "no_collapse_resources",
+                    context.config.android.no_collapse_resources.join(","),
+                )
+                .value("strip_symbols", symbols_dir.is_some())
+                .path("manifest", &manifest_path)?
+                .optional_path("assets", assets.as_deref())?
+                .optional_path("resources", resources.as_deref())?
+                .optional_path("dex", dex_dir.as_deref())?;
+            for (compiled_lib, build_target) in &libs {
+                fingerprint
+                    .value("abi", build_target.android_abi())
+                    .path("lib", compiled_lib)?;
+            }
+            Self::signing_fingerprint(&mut fingerprint, &key)?;
+
+            let key_id = format!("{}:{}", key.key_path.display(), key.key_alias);
+            let mut outputs: Vec<&Path> = vec![&aligned_apk_path];
+            if shorten_resource_paths {
+                outputs.push(&resource_map_path);
+            }
+            // Split APKs are cached per ABI, so they don't evict each other
+            let cache_step = match build_target {
+                Some(build_target) => format!("apk-{}", build_target.android_abi()),
+                None => "apk".to_owned(),
+            };
+            let restored =
+                self.build_cache(context)
+                    .cached(&cache_step, &fingerprint, &outputs, || {
+                        config.status_message("Generating", "unaligned APK file")?;
+                        let unaligned_apk_path = gen_unaligned_apk(
+                            &sdk,
+                            &project_path,
+                            &apk_build_dir,
+                            &manifest_path,
+                            &assets,
+                            &resources,
+                            &apk_name,
+                            target_sdk_version,
+                        )?;
+
+                        config.status("Adding libs into APK file")?;
+                        for (compiled_lib, build_target) in &libs {
+                            let stripper = symbols_dir
+                                .as_ref()
+                                .map(|dir| SymbolStripper::new(&ndk, *build_target, dir))
+                                .transpose()?;
+                            add_libs_into_apk(
+                                &sdk,
+                                &ndk,
+                                &unaligned_apk_path,
+                                compiled_lib,
+                                *build_target,
+                                profile,
+                                Self::min_sdk_version(&manifest),
+                                &android_build_dir,
+                                &target_dir,
+                                stripper.as_ref(),
+                            )?;
+                        }
+
+                        if let Some(dex_dir) = &dex_dir {
+                            config.status("Adding DEX files into APK file")?;
+                            add_dex_into_apk(&sdk, &unaligned_apk_path, dex_dir)?;
+                        }
+
+                        if shorten_resource_paths || collapse_resource_names {
+                            config.status("Optimizing resources")?;
+                            let optimized_apk_path = apk_build_dir.join("optimized.apk");
+                            let mut aapt2_optimize =
+                                Aapt2OptimizeCommand::new(&unaligned_apk_path, &optimized_apk_path);
+                            if shorten_resource_paths {
+                                aapt2_optimize
+                                    .shorten_resource_paths(true)
+                                    .resource_path_shortening_map(&resource_map_path);
+                            }
+                            if collapse_resource_names {
+                                let resources_config = Aapt2OptimizeCommand::save_resources_config(
+                                    &apk_build_dir.join("resources-config.txt"),
+                                    &context.config.android.no_collapse_resources,
+                                )?;
+                                aapt2_optimize
+                                    .collapse_resource_names(true)
+                                    .resources_config(&resources_config);
+                            }
+                            aapt2_optimize.run(&sdk)?;
+                            std::fs::rename(&optimized_apk_path, &unaligned_apk_path)?;
+                        }
+
+                        config.status("Aligning APK file")?;
+                        align_apk(
+                            &sdk,
+                            &unaligned_apk_path,
+                            &apk_name,
+                            &outputs_build_dir,
+                            page_size,
+                        )?;
+
+                        config.status("Signing APK file")?;
+                        sign_apk(&sdk, &aligned_apk_path, key)?;
+                        Ok(())
+                    })?;
+            if restored {
+                config.status_message("Restored from cache", aligned_apk_path.display())?;
+            } else {
+                record_audit_event(config, "sign_apk", &aligned_apk_path, &key_id)?;
+            }
+            apk_paths.push((build_target, aligned_apk_path));
+        }
+        if let Some(symbols_dir) = self.symbols_dir(context, profile, &manifest) {
+            config.status_message("Unstripped libraries saved to", symbols_dir.display())?;
+        }
         config.status("Build finished successfully")?;
-        Ok((manifest, sdk, aligned_apk_path))
+        Ok((manifest, sdk, apk_paths))
     }
 
     /// Builds AAB with aapt2 tool and signs it with jarsigner.
@@ -279,15 +578,15 @@ impl AndroidBuildCommand {
         config: &Config,
         context: &BuildContext,
     ) -> Result<(AndroidManifest, AndroidSdk, PathBuf, String, Key)> {
-        let profile = self.shared.profile();
+        let profile = self.cargo_profile(context);
         let example = self.shared.example.as_ref();
         let (project_path, target_dir, package_name) = Self::needed_project_dirs(example, context)?;
         config.status_message("Starting aab build process", &package_name)?;
-        let (sdk, ndk) = Self::android_toolchain()?;
+        let (sdk, ndk) = Self::android_toolchain(context)?;
 
         let android_build_dir = target_dir.join("android").join(&package_name);
         let native_build_dir = android_build_dir.join("native").join("aab");
-        let outputs_build_dir = android_build_dir.join("outputs");
+        let outputs_build_dir = Self::outputs_build_dir(context, &android_build_dir);
         if !outputs_build_dir.exists() {
             std::fs::create_dir_all(&outputs_build_dir)?;
         }
@@ -297,8 +596,12 @@ impl AndroidBuildCommand {
         config.status_message("Generating", "AndroidManifest.xml")?;
         let manifest_path = save_android_manifest(&native_build_dir, &manifest)?;
         config.status("Preparing resources and assets")?;
-        let (assets, resources) =
-            Self::prepare_assets_and_resources(&context.config, &android_build_dir)?;
+        let (assets, resources) = Self::prepare_assets_and_resources(
+            &context.config,
+            &context.library_resources()?,
+            &android_build_dir,
+            self.pseudo_locale,
+        )?;
 
         config.status_message("Compiling", "lib")?;
         let target_sdk_version = Self::target_sdk_version(&manifest, &sdk);
@@ -322,37 +625,98 @@ impl AndroidBuildCommand {
             if !compiled_res_path.exists() {
                 std::fs::create_dir_all(&compiled_res_path)?;
             }
-            let aapt2_compile = sdk.aapt2()?.compile_incremental(
+            // The daemon is kept alive while changed resource files are compiled one by one
+            let mut aapt2_daemon = Aapt2Daemon::start(&sdk)?;
+            let stats = compile_res_incremental(
+                &mut aapt2_daemon,
                 dunce::simplified(res),
                 dunce::simplified(&compiled_res_path),
-            );
-            let compiled_res = aapt2_compile.run()?;
-            Some(compiled_res)
+            )?;
+            config.status_message(
+                "Compiled resources",
+                format!(
+                    "{} changed, {} unchanged, {} removed",
+                    stats.compiled, stats.unchanged, stats.removed
+                ),
+            )?;
+            Some(compiled_res_path)
         } else {
             None
         };
 
         let apk_path = native_build_dir.join(format!("{}_module.apk", package_name));
-        let mut aapt2_link =
-            sdk.aapt2()?
-                .link_compiled_res(compiled_res, &apk_path, &manifest_path);
-        if let Some(assets) = &assets {
-            aapt2_link.assets(assets.clone())
-        } else {
-            &mut aapt2_link
+        let android_jar = sdk.android_jar(target_sdk_version)?;
+        let stable_ids = context
+            .config
+            .android
+            .stable_ids
+            .as_ref()
+            .map(|path| context.project_path.join(path));
+        let emitted_ids_path = native_build_dir.join("emitted-ids.txt");
+        let mut fingerprint = Fingerprint::new("aab_link");
+        fingerprint
+            .value("android_jar", android_jar.display())
+            .value("build_tools", sdk.build_deps_version())
+            .path("manifest", &manifest_path)?
+            .optional_path("compiled_res", compiled_res.as_deref())?
+            .optional_path("assets", assets.as_deref())?
+            .optional_path(
+                "stable_ids",
+                stable_ids.as_deref().filter(|path| path.exists()),
+            )?;
+        let mut outputs: Vec<&Path> = vec![&apk_path];
+        if stable_ids.is_some() {
+            outputs.push(&emitted_ids_path);
+        }
+        let restored =
+            self.build_cache(context)
+                .cached("aab_link", &fingerprint, &outputs, || {
+                    let mut aapt2_link =
+                        sdk.aapt2()?
+                            .link_compiled_res(compiled_res, &apk_path, &manifest_path);
+                    if let Some(assets) = &assets {
+                        aapt2_link.assets(assets.clone());
+                    }
+                    if let Some(stable_ids) = &stable_ids {
+                        if stable_ids.exists() {
+                            aapt2_link.stable_ids(stable_ids.clone());
+                        }
+                        aapt2_link.emit_ids(emitted_ids_path.clone());
+                    }
+                    aapt2_link
+                        .android_jar(android_jar.clone())
+                        .proto_format(true)
+                        .auto_add_overlay(true)
+                        .run()?;
+                    Ok(())
+                })?;
+        if restored {
+            config.status_message("Restored from cache", "proto format APK file")?;
+        }
+        if let Some(stable_ids) = &stable_ids {
+            if update_stable_ids(stable_ids, &emitted_ids_path)? {
+                config.status_message("Updated stable resource IDs", stable_ids.display())?;
+            }
+        }
+
+        // Feature modules reference their titles from the base module, which aapt2 can
+        // only include in binary format
+        let dynamic_features = &context.config.android.dynamic_features;
+        let base_res_apk = native_build_dir.join(format!("{}_base_res.apk", package_name));
+        if !dynamic_features.is_empty() {
+            config.status_message("Converting", "base module resources to binary format")?;
+            Aapt2ConvertCommand::new(&apk_path, &base_res_apk, Aapt2OutputFormat::Binary)
+                .run(&sdk)?;
         }
-        .android_jar(sdk.android_jar(target_sdk_version)?)
-        .proto_format(true)
-        .auto_add_overlay(true)
-        .run()?;
 
         config.status("Extracting apk files")?;
         let output_dir = native_build_dir.join("extracted_apk_files");
         let extracted_apk_path = extract_archive(&apk_path, &output_dir)?;
 
         config.status("Adding libs")?;
+        let symbols_dir = self.symbols_dir(context, profile, &manifest);
         for (compiled_lib, build_target) in compiled_libs {
-            add_libs_into_aapt2(
+            let libs_dir = add_libs_into_aapt2(
                 &ndk,
                 &compiled_lib,
                 build_target,
@@ -362,6 +726,38 @@ impl AndroidBuildCommand {
                 &target_dir,
                 &package_name,
             )?;
+            if let Some(symbols_dir) = &symbols_dir {
+                let stripper = SymbolStripper::new(&ndk, build_target, symbols_dir)?;
+                for lib_name in get_libs_in_dir(&libs_dir)? {
+                    stripper.strip(&libs_dir.join(lib_name))?;
+                }
+            }
+        }
+        if let Some(symbols_dir) = &symbols_dir {
+            config.status_message("Unstripped libraries saved to", symbols_dir.display())?;
+        }
+
+        let dex_dir = Self::compile_dex(
+            config,
+            context,
+            &sdk,
+            &manifest,
+            profile,
+            target_sdk_version,
+            &native_build_dir,
+            &outputs_build_dir,
+            &self.build_cache(context),
+        )?;
+        if let Some(dex_dir) = &dex_dir {
+            config.status("Adding DEX files")?;
+            let module_dex_dir = extracted_apk_path.join("dex");
+            std::fs::create_dir_all(&module_dex_dir)?;
+            for dex_file in dex_files(dex_dir)? {
+                std::fs::copy(
+                    &dex_file,
+                    module_dex_dir.join(dex_file.file_name().unwrap()),
+                )?;
+            }
         }
 
         config.status("Generating ZIP module from extracted files")?;
@@ -376,25 +772,71 @@ impl AndroidBuildCommand {
             }
         }
 
+        let mut module_names = std::collections::HashSet::new();
+        let asset_packs = &context.config.android.asset_packs;
+        for name in dynamic_features
+            .iter()
+            .map(|feature| &feature.name)
+            .chain(asset_packs.iter().map(|asset_pack| &asset_pack.name))
+        {
+            if !module_names.insert(name) {
+                return Err(Error::InvalidMetadata(anyhow::anyhow!(
+                    "module name `{}` is used by several dynamic features or asset packs",
+                    name
+                )));
+            }
+        }
+        let mut zip_modules = vec![gen_zip_modules];
+        for (index, feature) in dynamic_features.iter().enumerate() {
+            config.status_message("Generating feature module", &feature.name)?;
+            zip_modules.push(gen_feature_module(
+                &sdk,
+                feature,
+                index,
+                &manifest.package,
+                target_sdk_version,
+                &base_res_apk,
+                &native_build_dir,
+            )?);
+        }
+        for asset_pack in asset_packs {
+            config.status_message("Generating asset pack", &asset_pack.name)?;
+            zip_modules.push(gen_asset_pack_module(
+                &sdk,
+                asset_pack,
+                &manifest.package,
+                target_sdk_version,
+                &native_build_dir,
+            )?);
+        }
+
         config.status("Generating aab from modules")?;
-        let aab_path = gen_aab_from_modules(&package_name, &[gen_zip_modules], &outputs_build_dir)?;
+        let aab_path = gen_aab_from_modules(&package_name, &zip_modules, &outputs_build_dir)?;
 
         config.status_message("Generating", "debug signing key")?;
-        let key = Self::find_keystore(
-            self.sign_key_path.clone(),
-            self.sign_key_pass.clone(),
-            self.sign_key_alias.clone(),
-        )?;
+        let key = self.signing_key(context)?;
 
-        config.status_message("Signing", "debug signing key")?;
-        JarSigner::new(&aab_path, &key.key_alias)
-            .keystore(&key.key_path)
-            .storepass(key.key_pass.to_string())
-            .verbose(true)
-            .sigalg("SHA256withRSA".to_string())
-            .digestalg("SHA-256".to_string())
-            .run()?;
+        let mut fingerprint = Fingerprint::new("aab_sign");
+        fingerprint.path("aab", &aab_path)?;
+        Self::signing_fingerprint(&mut fingerprint, &key)?;
+        let restored =
+            self.build_cache(context)
+                .cached("aab_sign", &fingerprint, &[&aab_path], || {
+                    config.status_message("Signing", "debug signing key")?;
+                    JarSigner::new(&aab_path, &key.key_alias)
+                        .keystore(&key.key_path)
+                        .storepass(key.key_pass.to_string())
+                        .verbose(true)
+                        .sigalg("SHA256withRSA".to_string())
+                        .digestalg("SHA-256".to_string())
+                        .run()?;
+                    Ok(())
+                })?;
+        if restored {
+            config.status_message("Restored from cache", "signed AAB file")?;
+        }
 
+        Self::gen_assetlinks(config, &manifest, &key, &outputs_build_dir)?;
         let signed_aab = android_build_dir.join(format!("{}_signed.aab", package_name));
         std::fs::rename(&aab_path, &signed_aab)?;
         let output_aab = signed_aab.file_name().unwrap().to_str().unwrap();
@@ -402,6 +844,12 @@ impl AndroidBuildCommand {
         let mut options = fs_extra::file::CopyOptions::new();
         options.overwrite = true;
         fs_extra::file::move_file(&signed_aab, &outputs_build_dir.join(output_aab), &options)?;
+        record_audit_event(
+            config,
+            "sign_aab",
+            &aab_output_path,
+            &format!("{}:{}", key.key_path.display(), key.key_alias),
+        )?;
         config.status("Build finished successfully")?;
         Ok((manifest, sdk, aab_output_path, package_name, key))
     }
@@ -422,12 +870,162 @@ impl AndroidBuildCommand {
     }
 
     /// Specifies path to Android SDK and Android NDK.
-    pub fn android_toolchain() -> Result<(AndroidSdk, AndroidNdk)> {
+    /// NDK of the version pinned with `ndk_version` is used if specified.
+    pub fn android_toolchain(context: &BuildContext) -> Result<(AndroidSdk, AndroidNdk)> {
+        let android_config = &context.config.android;
         let sdk = AndroidSdk::from_env()?;
-        let ndk = AndroidNdk::from_env(sdk.sdk_path())?;
+        let ndk = AndroidNdk::from_env_with_requirements(
+            sdk.sdk_path(),
+            android_config.ndk_version.as_deref(),
+            android_config.min_ndk_version.as_deref(),
+        )?;
         Ok((sdk, ndk))
     }
 
+    /// Specifies path to Android SDK and Android NDK for commands which also work
+    /// outside of a project. NDK of the version pinned by the project in the current
+    /// directory is used if there is one.
+    pub fn project_android_toolchain(
+        config: &Config,
+        target_dir: Option<PathBuf>,
+    ) -> Result<(AndroidSdk, AndroidNdk)> {
+        match BuildContext::find(config, target_dir)? {
+            Some(context) => Self::android_toolchain(&context),
+            None => {
+                let sdk = AndroidSdk::from_env()?;
+                let ndk = AndroidNdk::from_env(sdk.sdk_path())?;
+                Ok((sdk, ndk))
+            }
+        }
+    }
+
+    /// Returns directory of the build artifacts. Artifacts of build profiles are placed
+    /// into `outputs/<profile>` so they don't overwrite each other.
+    pub fn outputs_build_dir(context: &BuildContext, android_build_dir: &Path) -> PathBuf {
+        let outputs_build_dir = android_build_dir.join("outputs");
+        match &context.android_profile {
+            Some(profile) => outputs_build_dir.join(profile),
+            None => outputs_build_dir,
+        }
+    }
+
+    /// Stores R8 and resource path mappings and unstripped libraries of the release
+    /// build in the artifact storage, `target/crossbundle` by default, so
+    /// `crossbundle retrace` and `crossbundle symbolicate` can resolve reports of the
+    /// published version.
+    fn archive_mappings(
+        &self,
+        config: &Config,
+        context: &BuildContext,
+        manifest: &AndroidManifest,
+    ) -> Result<()> {
+        if self.cargo_profile(context) != Profile::Release {
+            return Ok(());
+        }
+        let example = self.shared.example.as_ref();
+        let (_, target_dir, package_name) = Self::needed_project_dirs(example, context)?;
+        let outputs_build_dir =
+            Self::outputs_build_dir(context, &target_dir.join("android").join(&package_name));
+        let release = release_id(manifest.version_name.as_deref(), manifest.version_code);
+        let files = [
+            outputs_build_dir.join(R8_MAPPING_FILE_NAME),
+            outputs_build_dir.join(RESOURCE_MAP_FILE_NAME),
+        ];
+        let storage = context.storage()?;
+        for location in archive_mappings(storage.as_ref(), &release, &files)? {
+            config.status_message("Archived", location)?;
+        }
+        // Unstripped libraries are written into the local storage right away
+        let profile = self.cargo_profile(context);
+        if let Some(symbols_dir) = self.symbols_dir(context, profile, manifest) {
+            if context.config.storage.is_some() && symbols_dir.exists() {
+                for abi_dir in std::fs::read_dir(&symbols_dir)? {
+                    let abi_dir = abi_dir?.path();
+                    let abi = abi_dir.file_name().unwrap().to_string_lossy().to_string();
+                    for lib_name in get_libs_in_dir(&abi_dir)? {
+                        let key = format!("symbols/{}/{}/{}", release, abi, lib_name);
+                        let location = storage.put(&key, &abi_dir.join(&lib_name))?;
+                        config.status_message("Archived", location)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns `target/crossbundle/symbols/<version>` for unstripped libraries if
+    /// `strip_symbols` is enabled for the release build.
+    fn symbols_dir(
+        &self,
+        context: &BuildContext,
+        profile: Profile,
+        manifest: &AndroidManifest,
+    ) -> Option<PathBuf> {
+        if profile != Profile::Release || !context.config.android.strip_symbols {
+            return None;
+        }
+        let release = release_id(manifest.version_name.as_deref(), manifest.version_code);
+        Some(symbols_dir(&context.target_dir, &release))
+    }
+
+    /// Returns the cache of packaging steps, disabled with `--no-build-cache` flag.
+    fn build_cache(&self, context: &BuildContext) -> BuildCache {
+        let mut cache = BuildCache::new(&context.target_dir);
+        cache.disabled(self.no_build_cache);
+        cache
+    }
+
+    /// Adds the signing key to the fingerprint. The password is not added, the keystore
+    /// file changes with the key anyway.
+    fn signing_fingerprint(fingerprint: &mut Fingerprint, key: &Key) -> Result<()> {
+        fingerprint
+            .value("key_alias", &key.key_alias)
+            .path("keystore", &key.key_path)?;
+        Ok(())
+    }
+
+    /// Returns signing key from the command flags or from the build profile.
+    fn signing_key(&self, context: &BuildContext) -> Result<Key> {
+        let build_profile = context
+            .android_build_profile()
+            .filter(|_| self.sign_key_path.is_none());
+        let build_profile = match build_profile {
+            Some(build_profile) if build_profile.sign_key_path.is_some() => build_profile,
+            _ => {
+                return Self::find_keystore(
+                    self.sign_key_path.clone(),
+                    self.sign_key_pass.clone(),
+                    self.sign_key_alias.clone(),
+                )
+            }
+        };
+        let sign_key_alias = build_profile.sign_key_alias.clone().ok_or_else(|| {
+            Error::InvalidMetadata(anyhow::anyhow!(
+                "`sign_key_alias` of the build profile must be specified with `sign_key_path`"
+            ))
+        })?;
+        let sign_key_pass = match (&self.sign_key_pass, &build_profile.sign_key_pass_env) {
+            (Some(sign_key_pass), _) => sign_key_pass.clone(),
+            (None, Some(env)) => std::env::var(env).map_err(|_| {
+                Error::InvalidMetadata(anyhow::anyhow!(
+                    "environment variable `{}` with the signing key password is not set",
+                    env
+                ))
+            })?,
+            (None, None) => {
+                return Err(Error::InvalidMetadata(anyhow::anyhow!(
+                    "`sign_key_pass_env` of the build profile or `--sign-key-pass` flag must \
+                     be specified"
+                )))
+            }
+        };
+        Self::find_keystore(
+            build_profile.sign_key_path.clone(),
+            Some(sign_key_pass),
+            Some(sign_key_alias),
+        )
+    }
+
     /// Find keystore for signing application or create it.
     pub fn find_keystore(
         sign_key_path: Option<PathBuf>,
@@ -464,6 +1062,36 @@ impl AndroidBuildCommand {
         Ok(key)
     }
 
+    /// Generates `.well-known/assetlinks.json` with the signing certificate fingerprint if
+    /// the manifest declares App Links.
+    pub fn gen_assetlinks(
+        config: &Config,
+        manifest: &AndroidManifest,
+        key: &Key,
+        outputs_build_dir: &Path,
+    ) -> Result<()> {
+        let hosts = app_link_hosts(manifest);
+        if hosts.is_empty() {
+            return Ok(());
+        }
+        let sha256 =
+            inspect_keystore(&key.key_path, &key.key_pass, &key.key_alias).map(|info| info.sha256);
+        match sha256 {
+            Ok(Some(sha256)) => {
+                let path = save_assetlinks(outputs_build_dir, &manifest.package, &[sha256])?;
+                config.status_message("Generated", path.display())?;
+                config.shell().note(format!(
+                    "Publish it at https://<host>/.well-known/assetlinks.json for: {}",
+                    hosts.join(", ")
+                ))?;
+            }
+            _ => config.shell().warn(
+                "Failed to read signing certificate fingerprint, assetlinks.json is not generated",
+            )?,
+        }
+        Ok(())
+    }
+
     /// Compiling libs for architecture and write out it in vector.
     pub fn build_target(
         &self,
@@ -477,12 +1105,35 @@ impl AndroidBuildCommand {
         target_dir: &Path,
         config: &Config,
     ) -> Result<Vec<(PathBuf, AndroidTarget)>> {
+        let rust_triples: Vec<&str> = build_targets.iter().map(|t| t.rust_triple()).collect();
+        ensure_rust_targets(config, &rust_triples, self.shared.yes)?;
+        let lib_name = format!("lib{}.so", package_name.replace('-', "_"));
+        if self.jobs > 1 && build_targets.len() > 1 {
+            self.build_targets_parallel(
+                context,
+                &build_targets,
+                &lib_name,
+                ndk,
+                project_path,
+                profile,
+                target_sdk_version,
+                target_dir,
+                config,
+            )?;
+            return Ok(build_targets
+                .into_iter()
+                .map(|build_target| {
+                    let out_dir = target_dir.join(build_target.rust_triple()).join(profile);
+                    (out_dir.join(&lib_name), build_target)
+                })
+                .collect());
+        }
         let mut libs = Vec::new();
         for build_target in build_targets {
-            let lib_name = format!("lib{}.so", package_name.replace('-', "_"));
             let rust_triple = build_target.rust_triple();
 
             config.status_message("Compiling for architecture", rust_triple)?;
+            set_rust_compile_env(ndk, build_target, project_path, profile, target_sdk_version)?;
             // Compile rust code for android depending on application wrapper
             rust_compile(
                 ndk,
@@ -495,15 +1146,106 @@ impl AndroidBuildCommand {
                 target_sdk_version,
                 &lib_name,
                 context.config.android.app_wrapper,
+                None,
+                self.shared.coverage,
             )?;
 
             let out_dir = target_dir.join(build_target.rust_triple()).join(profile);
-            let compiled_lib = out_dir.join(lib_name);
+            let compiled_lib = out_dir.join(&lib_name);
             libs.push((compiled_lib, build_target));
         }
         Ok(libs)
     }
 
+    /// Compiles targets in parallel, at most `--jobs` at once. Every target is compiled in
+    /// `target/parallel/<rust triple>` cargo target directory, so builds don't wait for
+    /// the lock of the shared one. Libraries are written to the usual locations.
+    fn build_targets_parallel(
+        &self,
+        context: &BuildContext,
+        build_targets: &[AndroidTarget],
+        lib_name: &str,
+        ndk: &AndroidNdk,
+        project_path: &Path,
+        profile: Profile,
+        target_sdk_version: u32,
+        target_dir: &Path,
+        config: &Config,
+    ) -> Result<()> {
+        let triples: Vec<&str> = build_targets
+            .iter()
+            .map(|build_target| build_target.rust_triple())
+            .collect();
+        config.status_message("Compiling for architectures", triples.join(", "))?;
+        // The process environment must not be changed by the compilation threads
+        for build_target in build_targets {
+            set_rust_compile_env(
+                ndk,
+                *build_target,
+                project_path,
+                profile,
+                target_sdk_version,
+            )?;
+        }
+        let queue = Arc::new(Mutex::new(build_targets.to_vec()));
+        let mut workers = Vec::new();
+        for _ in 0..self.jobs.min(build_targets.len()) {
+            let queue = queue.clone();
+            let ndk = ndk.clone();
+            let project_path = project_path.to_owned();
+            let parallel_dir = target_dir.join("parallel");
+            let lib_name = lib_name.to_owned();
+            let features = self.shared.features.clone();
+            let all_features = self.shared.all_features;
+            let no_default_features = self.shared.no_default_features;
+            let app_wrapper = context.config.android.app_wrapper;
+            let coverage = self.shared.coverage;
+            // Errors of crossbundle-tools are not `Send`, so only messages are returned
+            workers.push(std::thread::spawn(
+                move || -> std::result::Result<(), String> {
+                    loop {
+                        let build_target = match queue.lock().unwrap().pop() {
+                            Some(build_target) => build_target,
+                            None => return Ok(()),
+                        };
+                        let rust_triple = build_target.rust_triple();
+                        rust_compile(
+                            &ndk,
+                            build_target,
+                            &project_path,
+                            profile,
+                            features.clone(),
+                            all_features,
+                            no_default_features,
+                            target_sdk_version,
+                            &lib_name,
+                            app_wrapper,
+                            Some(&parallel_dir.join(rust_triple)),
+                            coverage,
+                        )
+                        .map_err(|error| format!("{}: {}", rust_triple, error))?;
+                    }
+                },
+            ));
+        }
+        let mut errors = Vec::new();
+        for worker in workers {
+            match worker.join() {
+                Ok(Ok(())) => {}
+                Ok(Err(error)) => errors.push(error),
+                Err(_) => errors.push("compilation thread panicked".to_owned()),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(crossbundle_tools::error::Error::from(anyhow::anyhow!(
+                "failed to compile targets:\n{}",
+                errors.join("\n")
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
     /// Get target sdk version from cargo manifest
     pub fn target_sdk_version(android_manifest: &AndroidManifest, sdk: &AndroidSdk) -> u32 {
         if let Some(target_sdk_version) = android_manifest
@@ -550,48 +1292,312 @@ impl AndroidBuildCommand {
         strategy: AndroidStrategy,
     ) -> Result<AndroidManifest> {
         if let Some(manifest_path) = &context.config.android.manifest_path {
-            return Ok(read_android_manifest(manifest_path)?);
+            let mut manifest = read_android_manifest(manifest_path)?;
+            Self::apply_version_code_strategy(context, &mut manifest)?;
+            Self::apply_build_script(context, &mut manifest);
+            Self::apply_build_profile(context, &mut manifest)?;
+            return Ok(manifest);
         }
         let mut manifest = if let Some(manifest) = &context.config.android.manifest {
             manifest.clone()
         } else {
             AndroidManifest::default()
         };
+        let has_app_name_string = context
+            .config
+            .android
+            .strings
+            .values()
+            .any(|strings| strings.contains_key("app_name"));
+        if has_app_name_string && manifest.application.label.is_none() {
+            manifest.application.label = Some(android_manifest::StringResourceOrString::resource(
+                "app_name", None,
+            ));
+        }
+        // Splash screen theme doesn't override the theme specified by the user
+        if context.config.android.has_splash_screen() && manifest.application.theme.is_none() {
+            manifest.application.theme = Some(android_manifest::Resource::new_with_package(
+                SPLASH_THEME_NAME,
+                None,
+            ));
+        }
+        if context.config.android.has_java_code() && manifest.application.has_code.is_none() {
+            manifest.application.has_code = Some(true);
+        }
         update_android_manifest_with_default(
             &mut manifest,
             context.config.app_name.clone(),
             context.package_name().as_str(),
             strategy,
         );
+        if let Some(multi_window) = &context.config.android.multi_window {
+            multi_window.apply(&mut manifest);
+        }
+        if context.config.secure_window {
+            add_secure_window_meta_data(&mut manifest);
+        }
         context.config.permissions.iter().for_each(|permission| {
             permission.update_manifest(&mut manifest);
         });
-        if context.config.icon.is_some() {
+        add_capabilities_to_manifest(&mut manifest, &context.capabilities()?);
+        if context.config.icon.is_some() || context.config.android.adaptive_icon.is_some() {
             manifest.application.icon = Some(android_manifest::MipmapOrDrawableResource::mipmap(
                 "ic_launcher",
                 None,
             ));
         }
+        Self::apply_version_code_strategy(context, &mut manifest)?;
+        Self::apply_build_script(context, &mut manifest);
+        Self::apply_build_profile(context, &mut manifest)?;
         Ok(manifest)
     }
 
+    /// Applies the package name, version code and version name computed by the build
+    /// script.
+    fn apply_build_script(context: &BuildContext, manifest: &mut AndroidManifest) {
+        let build_script = &context.build_script;
+        if let Some(package_id) = &build_script.package_id {
+            manifest.package = package_id.clone();
+        }
+        if let Some(version_code) = build_script.version_code {
+            manifest.version_code = Some(version_code);
+        }
+        if let Some(version_name) = &build_script.version_name {
+            manifest.version_name = Some(version_name.clone());
+        }
+    }
+
+    /// Applies manifest placeholders and package and version name suffixes of the
+    /// selected build profile.
+    fn apply_build_profile(context: &BuildContext, manifest: &mut AndroidManifest) -> Result<()> {
+        let build_profile = match context.android_build_profile() {
+            Some(build_profile) => build_profile,
+            None => return Ok(()),
+        };
+        *manifest = apply_manifest_placeholders(manifest, &build_profile.manifest_placeholders)?;
+        if let Some(suffix) = &build_profile.application_id_suffix {
+            manifest.package.push_str(suffix);
+        }
+        if let Some(suffix) = &build_profile.version_name_suffix {
+            let version_name = manifest
+                .version_name
+                .get_or_insert_with(|| context.package_version());
+            version_name.push_str(suffix);
+        }
+        Ok(())
+    }
+
+    /// Replaces version code of the manifest with the one generated by
+    /// `version_code_strategy` metadata.
+    fn apply_version_code_strategy(
+        context: &BuildContext,
+        manifest: &mut AndroidManifest,
+    ) -> Result<()> {
+        let version_name = manifest
+            .version_name
+            .clone()
+            .unwrap_or_else(|| context.package_version());
+        let strategy = context.config.android.version_code_strategy;
+        if let Some(version_code) = strategy.version_code(&version_name, &context.project_path)? {
+            manifest.version_code = Some(version_code);
+        }
+        Ok(())
+    }
+
+    /// Compiles Kotlin and Java sources and Java bytecode from `java_inputs` metadata
+    /// into DEX files with d8, or with R8 in release builds if `shrink_code` is enabled.
+    /// Returns directory with `classes.dex` files or `None` if there is no Java code.
+    fn compile_dex(
+        config: &Config,
+        context: &BuildContext,
+        sdk: &AndroidSdk,
+        manifest: &AndroidManifest,
+        profile: Profile,
+        target_sdk_version: u32,
+        build_dir: &Path,
+        outputs_dir: &Path,
+        build_cache: &BuildCache,
+    ) -> Result<Option<PathBuf>> {
+        let android_config = &context.config.android;
+        if !android_config.has_java_code() {
+            return Ok(None);
+        }
+        let android_jar = sdk.android_jar(target_sdk_version)?;
+        let java_sources = source_files(&android_config.java_sources, "java")?;
+        let kotlin_sources = source_files(&android_config.kotlin_sources, "kt")?;
+        let shrink_code = profile == Profile::Release && android_config.shrink_code;
+        let dex_dir = build_dir.join("dex");
+        let mapping_path = outputs_dir.join(R8_MAPPING_FILE_NAME);
+
+        let mut fingerprint = Fingerprint::new("dex");
+        fingerprint
+            .value("android_jar", android_jar.display())
+            .value("build_tools", sdk.build_deps_version())
+            .value("min_sdk_version", Self::min_sdk_version(manifest))
+            .value("profile", profile.to_string())
+            .value("shrink_code", shrink_code);
+        for path in android_config
+            .java_inputs
+            .iter()
+            .chain(&java_sources)
+            .chain(&kotlin_sources)
+            .chain(&android_config.proguard_files)
+        {
+            fingerprint.path(&path.to_string_lossy(), path)?;
+        }
+        for rule in &android_config.keep_rules {
+            fingerprint.value("keep_rule", rule);
+        }
+        let outputs: &[&Path] = if shrink_code {
+            &[&dex_dir, &mapping_path]
+        } else {
+            &[&dex_dir]
+        };
+        let restored = build_cache.cached("dex", &fingerprint, outputs, || {
+            Self::run_dex_compilers(
+                config,
+                context,
+                sdk,
+                manifest,
+                profile,
+                &android_jar,
+                &java_sources,
+                &kotlin_sources,
+                build_dir,
+                &dex_dir,
+                shrink_code.then_some(&mapping_path),
+            )
+        })?;
+        if restored {
+            config.status_message("Restored from cache", "DEX files")?;
+        }
+        Ok(Some(dex_dir))
+    }
+
+    /// Compiles Java and Kotlin sources and dexes all bytecode into `dex_dir`. R8 is used
+    /// if `mapping_path` is set.
+    fn run_dex_compilers(
+        config: &Config,
+        context: &BuildContext,
+        sdk: &AndroidSdk,
+        manifest: &AndroidManifest,
+        profile: Profile,
+        android_jar: &Path,
+        java_sources: &[PathBuf],
+        kotlin_sources: &[PathBuf],
+        build_dir: &Path,
+        dex_dir: &Path,
+        mapping_path: Option<&PathBuf>,
+    ) -> Result<()> {
+        let android_config = &context.config.android;
+        let mut inputs = java_bytecode_inputs(&android_config.java_inputs)?;
+        // Archives from `java_inputs` are libraries the sources can reference
+        let mut classpath: Vec<PathBuf> = inputs
+            .iter()
+            .filter(|input| input.extension().map_or(false, |ext| ext == "jar"))
+            .cloned()
+            .collect();
+        let classes_dir = build_dir.join("classes");
+        std::fs::remove_dir_all(&classes_dir).ok();
+
+        if !kotlin_sources.is_empty() {
+            config.status_message("Compiling", "Kotlin sources")?;
+            let mut kotlinc = Kotlinc::new(kotlin_sources, &classes_dir.join("kotlin.jar"));
+            kotlinc
+                .classpath(android_jar)
+                .java_sources(java_sources)
+                .include_runtime(true)
+                .no_jdk(true);
+            for library in &classpath {
+                kotlinc.classpath(library);
+            }
+            let kotlin_jar = kotlinc.run()?;
+            classpath.push(kotlin_jar.clone());
+            inputs.push(kotlin_jar);
+        }
+        if !java_sources.is_empty() {
+            config.status_message("Compiling", "Java sources")?;
+            let mut javac = Javac::new(java_sources, &classes_dir.join("java"));
+            javac.bootclasspath(android_jar);
+            for library in &classpath {
+                javac.classpath(library);
+            }
+            let java_classes = javac.run()?;
+            inputs.extend(java_bytecode_inputs(&[java_classes])?);
+        }
+
+        std::fs::remove_dir_all(dex_dir).ok();
+        if let Some(mapping_path) = mapping_path {
+            config.status_message("Shrinking", "Java bytecode into DEX with R8")?;
+            let keep_rules_path = build_dir.join("keep-rules.pro");
+            let mut keep_rules = DEFAULT_KEEP_RULES.to_owned();
+            for rule in &android_config.keep_rules {
+                keep_rules.push_str(rule);
+                keep_rules.push('\n');
+            }
+            std::fs::write(&keep_rules_path, keep_rules)?;
+            let mut r8 = R8::new(&inputs, dex_dir);
+            r8.min_api(Self::min_sdk_version(manifest))
+                .lib(android_jar)
+                .pg_conf(&keep_rules_path)
+                .pg_map_output(mapping_path);
+            for proguard_file in &android_config.proguard_files {
+                r8.pg_conf(proguard_file);
+            }
+            r8.run(sdk)?;
+            config.status_message("Saved mapping", mapping_path.display())?;
+        } else {
+            config.status_message("Compiling", "Java bytecode into DEX")?;
+            D8::new(&inputs, dex_dir)
+                .release(profile == Profile::Release)
+                .min_api(Self::min_sdk_version(manifest))
+                .lib(android_jar)
+                .run(sdk)?;
+        }
+        Ok(())
+    }
+
     /// Prepare assets and resources for the application.
     ///
     /// Also, this function will generate mipmap icon resources if specified in the
-    /// CrossbowMetadata config.
+    /// CrossbowMetadata config and pseudo-localized strings if `pseudo_locale` is set.
     pub fn prepare_assets_and_resources(
         config: &CrossbowMetadata,
+        libraries: &[LibraryResources],
         out_dir: &Path,
+        pseudo_locale: bool,
     ) -> Result<(Option<PathBuf>, Option<PathBuf>)> {
-        let res = config.get_android_resources();
-        let gen_resources = if res.is_empty() && config.icon.is_none() {
+        let library_res: Vec<_> = libraries
+            .iter()
+            .map(|library| {
+                (
+                    library.package.as_str(),
+                    library.android_resources.as_slice(),
+                )
+            })
+            .collect();
+        let res = &merge_library_dirs(config.get_android_resources(), &library_res)?;
+        let adaptive_icon = config.android.adaptive_icon.as_ref();
+        let mut strings = config.android.strings_with_feature_titles();
+        let gen_resources = if res.is_empty()
+            && config.icon.is_none()
+            && adaptive_icon.is_none()
+            && !config.android.has_splash_screen()
+            && strings.is_empty()
+        {
             None
         } else {
             let path = out_dir.join("gen_resources");
             std::fs::remove_dir_all(&path).ok();
             combine_folders(res, &path)?;
 
-            if let Some(icon) = &config.icon {
+            // Legacy icons are generated from the adaptive icon foreground if the global
+            // icon is not specified
+            let icon = config
+                .icon
+                .clone()
+                .or_else(|| adaptive_icon.and_then(|icon| icon.foreground.clone()));
+            if let Some(icon) = &icon {
                 ImageGeneration {
                     icon_path: icon.to_owned(),
                     out_icon_name: "ic_launcher.png".to_owned(),
@@ -600,14 +1606,73 @@ impl AndroidBuildCommand {
                 }
                 .gen_mipmap_res_from_icon()?;
             }
+            if let Some(adaptive_icon) = adaptive_icon {
+                let foreground = icon.ok_or_else(|| {
+                    Error::InvalidMetadata(anyhow::anyhow!(
+                        "`adaptive_icon.foreground` or `icon` must be specified"
+                    ))
+                })?;
+                let foreground = adaptive_icon.foreground.clone().unwrap_or(foreground);
+                AdaptiveIconGeneration {
+                    foreground,
+                    background: AdaptiveIconBackground::parse(
+                        adaptive_icon.background.as_deref().unwrap_or("#FFFFFF"),
+                    )?,
+                    monochrome: adaptive_icon.monochrome.clone(),
+                    out_icon_name: "ic_launcher".to_owned(),
+                    output_path: path.clone(),
+                }
+                .gen_adaptive_icon_res()?;
+            }
+            if !strings.is_empty() {
+                // Localized application name requires the default one
+                if strings
+                    .values()
+                    .any(|strings| strings.contains_key("app_name"))
+                {
+                    strings
+                        .entry(DEFAULT_STRINGS_LOCALE.to_owned())
+                        .or_default()
+                        .entry("app_name".to_owned())
+                        .or_insert_with(|| {
+                            config
+                                .app_name
+                                .clone()
+                                .unwrap_or_else(|| "Crossbow".to_owned())
+                        });
+                }
+                gen_strings_res(&strings, &path)?;
+            }
+            if pseudo_locale {
+                gen_pseudo_locale_res(&path)?;
+            }
+            if config.android.has_splash_screen() {
+                SplashScreenGeneration {
+                    image: config.android.splash_image.clone(),
+                    color: config
+                        .android
+                        .splash_color
+                        .clone()
+                        .unwrap_or_else(|| "#000000".to_owned()),
+                    output_path: path.clone(),
+                }
+                .gen_splash_screen_res()?;
+            }
             Some(path)
         };
 
-        let assets = config.get_android_assets();
-        let gen_assets = if !res.is_empty() {
+        let library_assets: Vec<_> = libraries
+            .iter()
+            .map(|library| (library.package.as_str(), library.get_android_assets()))
+            .collect();
+        let assets = merge_library_dirs(config.get_android_assets(), &library_assets)?;
+        let gen_assets = if !assets.is_empty() {
             let path = out_dir.join("gen_assets");
             std::fs::remove_dir_all(&path).ok();
-            combine_folders(assets, &path)?;
+            combine_folders(&assets, &path)?;
+            if config.content_manifest {
+                ContentManifest::from_dir(&path)?.save(&path)?;
+            }
             Some(path)
         } else {
             None
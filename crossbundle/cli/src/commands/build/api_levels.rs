@@ -0,0 +1,22 @@
+use super::BuildContext;
+use crate::error::*;
+use crossbundle_tools::{
+    commands::{check_api_levels, ApiPlatform},
+    types::Config,
+};
+
+/// Warns about crossbow plugins and permissions used by the crate that need a higher
+/// platform version than `configured_min`. Such calls fail only at runtime on old devices
+/// with errors like "class not found" or "unrecognized selector".
+pub fn warn_api_levels(
+    config: &Config,
+    context: &BuildContext,
+    platform: ApiPlatform,
+    configured_min: &str,
+) -> Result<()> {
+    let used_apis = context.used_apis()?;
+    for warning in check_api_levels(&used_apis, platform, configured_min) {
+        config.shell().warn(warning)?;
+    }
+    Ok(())
+}
@@ -1,13 +1,24 @@
-use super::{BuildContext, SharedBuildCommand};
+use super::{
+    ensure_build_std, ensure_rust_targets, run_hooks, warn_api_levels, BuildContext,
+    SharedBuildCommand,
+};
 use crate::{error::*, types::CrossbowMetadata};
 use apple_bundle::prelude::InfoPlist;
 use clap::Parser;
+use crossbow::ContentManifest;
 use crossbundle_tools::{
-    commands::{apple, combine_folders},
+    commands::{
+        apple, apply_usage_descriptions, combine_folders, merge_library_dirs, ApiPlatform,
+        LibraryResources,
+    },
     types::*,
 };
 use std::path::{Path, PathBuf};
 
+/// Minimum macOS version of Mac Catalyst apps if it's not specified in
+/// `apple.macos.minimum_system_version`.
+const CATALYST_MINIMUM_SYSTEM_VERSION: &str = "10.15";
+
 #[derive(Parser, Clone, Debug)]
 pub struct IosBuildCommand {
     #[clap(flatten)]
@@ -17,7 +28,12 @@ pub struct IosBuildCommand {
     pub bin: Option<String>,
     /// Build for the given apple architecture.
     /// Supported targets are: `aarch64-apple-ios`, `aarch64-apple-ios-sim`,
-    /// `armv7-apple-ios`, `armv7s-apple-ios`, `i386-apple-ios`, `x86_64-apple-ios`
+    /// `armv7-apple-ios`, `armv7s-apple-ios`, `i386-apple-ios`, `x86_64-apple-ios`,
+    /// tvOS `aarch64-apple-tvos`, `aarch64-apple-tvos-sim`, `x86_64-apple-tvos` and
+    /// watchOS `arm64_32-apple-watchos`, `armv7k-apple-watchos`,
+    /// `aarch64-apple-watchos-sim`, `x86_64-apple-watchos-sim` and Mac Catalyst
+    /// `aarch64-apple-ios-macabi`, `x86_64-apple-ios-macabi`.
+    /// Several device or simulator targets are merged into one universal app
     #[clap(long, short, multiple_values = true)]
     pub target: Vec<IosTarget>,
     /// Build strategy specifies what and how to build iOS application: with help of
@@ -34,21 +50,42 @@ pub struct IosBuildCommand {
     /// The team identifier of your signing identity.
     #[clap(long)]
     pub team_identifier: Option<String>,
-    /// The id of the identity used for signing. It won't start the signing process until
-    /// you provide this flag.
+    /// The id of the identity used for signing. Builds for devices are signed with a
+    /// development or, on release, distribution identity from the keychain if it's not
+    /// specified. Simulator builds are signed only with this flag.
     #[clap(long)]
     pub identity: Option<String>,
+    /// Path to the entitlements plist added to the signature. Overrides
+    /// `apple.entitlements` of the config
+    #[clap(long)]
+    pub entitlements: Option<PathBuf>,
 }
 
 impl IosBuildCommand {
     pub fn run(&self, config: &Config) -> Result<()> {
-        let context = BuildContext::new(config, self.shared.target_dir.clone())?;
+        let context = self.build_context(config)?;
+        let info_plist = Self::gen_info_plist(&context, &context.package_name())?;
+        if let Some(minimum_os_version) = serde_json::to_value(&info_plist)?
+            .get("MinimumOSVersion")
+            .and_then(|version| version.as_str())
+        {
+            warn_api_levels(config, &context, ApiPlatform::Ios, minimum_os_version)?;
+        }
+        let hooks = &context.config.hooks;
+        run_hooks(config, &context, &hooks.pre_build, self.shared.trust)?;
         match &self.strategy {
             IosStrategy::NativeIpa => {
                 self.execute(config, &context)?;
             }
         };
-        Ok(())
+        run_hooks(config, &context, &hooks.post_build, self.shared.trust)
+    }
+
+    /// Creates the build context and evaluates the build script.
+    pub fn build_context(&self, config: &Config) -> Result<BuildContext> {
+        let mut context = BuildContext::new(config, self.shared.target_dir.clone())?;
+        context.eval_build_script(config, "ios", self.shared.release, None)?;
+        Ok(context)
     }
 
     pub fn execute(
@@ -69,14 +106,25 @@ impl IosBuildCommand {
         config.status_message("Starting build process", &package_name)?;
         config.status("Compiling app")?;
         let build_targets = Self::apple_build_targets(context, profile, &self.target);
+        // Standard library of tvOS and watchOS targets is built from source
+        let rust_triples: Vec<&str> = build_targets
+            .iter()
+            .filter(|t| !t.requires_build_std())
+            .map(|t| t.rust_triple())
+            .collect();
+        ensure_rust_targets(config, &rust_triples, self.shared.yes)?;
+        if build_targets.iter().any(|t| t.requires_build_std()) {
+            ensure_build_std(config, self.shared.yes)?;
+        }
         let mut app_paths = vec![];
-        for build_target in build_targets {
+        // Device and simulator targets are merged into universal binaries separately
+        for build_targets in apple::ios_universal_groups(&build_targets) {
             let app_path = self.build_app(
                 config,
                 context,
                 target.clone(),
                 project_path,
-                build_target,
+                &build_targets,
                 &properties,
                 profile,
                 &package_name,
@@ -92,44 +140,79 @@ impl IosBuildCommand {
         context: &BuildContext,
         target: Target,
         project_path: &Path,
-        build_target: IosTarget,
+        build_targets: &[IosTarget],
         properties: &InfoPlist,
         profile: Profile,
         name: &str,
     ) -> Result<PathBuf> {
-        let rust_triple = build_target.rust_triple();
-        config.status_message("Compiling for architecture", rust_triple)?;
-        apple::compile_rust_for_ios(
-            target,
-            build_target,
-            project_path,
-            profile,
-            self.shared.features.clone(),
-            self.shared.all_features,
-            self.shared.no_default_features,
-            &[],
-        )?;
-        let out_dir = context.target_dir.join(rust_triple).join(profile);
-        let bin_path = out_dir.join(name);
+        let mut bin_paths = vec![];
+        for build_target in build_targets {
+            let rust_triple = build_target.rust_triple();
+            config.status_message("Compiling for architecture", rust_triple)?;
+            apple::compile_rust_for_ios(
+                target.clone(),
+                *build_target,
+                project_path,
+                profile,
+                self.shared.features.clone(),
+                self.shared.all_features,
+                self.shared.no_default_features,
+                &[],
+                self.shared.coverage,
+            )?;
+            let out_dir = context.target_dir.join(rust_triple).join(profile);
+            bin_paths.push(out_dir.join(name));
+        }
+        let build_target = build_targets[0];
 
         config.status("Generating app folder")?;
         let apple_target_dir = &context
             .target_dir
             .join("apple")
-            .join(rust_triple)
+            .join(apple::ios_build_dir_name(build_targets))
             .join(profile);
 
         config.status("Preparing resources and assets")?;
-        let (assets, resources) =
-            Self::prepare_assets_and_resources(&context.config, apple_target_dir)?;
+        let (assets, resources) = Self::prepare_assets_and_resources(
+            &context.config,
+            &context.library_resources()?,
+            apple_target_dir,
+        )?;
 
+        if build_target.platform() == ApplePlatform::MacCatalyst {
+            let app_path = apple::gen_macos_app_folder(apple_target_dir, name, assets, resources)?;
+            return self.package_catalyst_app(
+                config,
+                context,
+                build_target,
+                properties,
+                &bin_paths,
+                &app_path,
+                name,
+            );
+        }
         let app_path = apple::gen_apple_app_folder(apple_target_dir, name, assets, resources)?;
-        config.status("Copying binary to app folder")?;
-        std::fs::copy(&bin_path, &app_path.join(name)).unwrap();
+        Self::copy_binary(config, &bin_paths, &app_path.join(name))?;
         config.status_message("Generating", "Info.plist")?;
-        apple::save_info_plist(&app_path, properties, false).unwrap();
+        let mut info_plist_extra = apple::platform_info_plist_keys(build_target);
+        for (key, value) in context.config.apple.info_plist_settings.to_dictionary() {
+            info_plist_extra.insert(key, value);
+        }
+        if context.config.secure_window {
+            info_plist_extra.insert(apple::SECURE_WINDOW_INFO_PLIST_KEY.to_owned(), true.into());
+        }
+        apple::save_info_plist(&app_path, properties, &info_plist_extra, false)?;
+        let on_demand_resources = &context.config.apple.on_demand_resources;
+        if !on_demand_resources.is_empty() {
+            config.status("Generating on-demand resources")?;
+            apple::gen_on_demand_resources(
+                &app_path,
+                &properties.identification.bundle_identifier,
+                on_demand_resources,
+            )?;
+        }
 
-        if self.identity.is_some() {
+        if let Some(identity) = self.signing_identity(config, build_target)? {
             config.status("Starting code signing process")?;
             apple::copy_profile(
                 &app_path,
@@ -146,10 +229,26 @@ impl IosBuildCommand {
                 &properties.identification.bundle_identifier,
                 false,
             )?;
+            let entitlements =
+                self.entitlements
+                    .as_ref()
+                    .or(context.config.apple.entitlements.as_ref());
+            if let Some(entitlements) = entitlements {
+                apple::merge_entitlements(&xcent_path, &context.project_path.join(entitlements))?;
+            }
             config.status("Signing the binary")?;
-            apple::codesign(&app_path.join(name), true, self.identity.clone(), None)?;
+            apple::Codesign::new(&app_path.join(name))
+                .identity(&identity)
+                .force(true)
+                .run()?;
             config.status("Signing the bundle itself")?;
-            apple::codesign(&app_path, true, self.identity.clone(), Some(xcent_path))?;
+            apple::Codesign::new(&app_path)
+                .identity(&identity)
+                .entitlements(&xcent_path)
+                .force(true)
+                .run()?;
+            apple::verify_codesign(&app_path, true)?;
+            super::record_audit_event(config, "codesign", &app_path.join(name), &identity)?;
             config.status("Code signing process finished")?;
         }
 
@@ -159,6 +258,123 @@ impl IosBuildCommand {
         Ok(app_path)
     }
 
+    /// Copies the binary into the app, or merges binaries of several architectures into
+    /// a universal one.
+    fn copy_binary(config: &Config, bin_paths: &[PathBuf], app_bin_path: &Path) -> Result<()> {
+        if bin_paths.len() > 1 {
+            config.status("Creating universal binary")?;
+            apple::lipo_create(bin_paths, app_bin_path)?;
+            let archs = apple::lipo_archs(app_bin_path)?;
+            config.status_message("Architectures", archs.join(", "))?;
+        } else {
+            config.status("Copying binary to app folder")?;
+            std::fs::copy(&bin_paths[0], app_bin_path)?;
+        }
+        Ok(())
+    }
+
+    /// Finishes the Mac Catalyst app generated with the macOS bundle layout. It's
+    /// configured with `apple.macos` like macOS apps and signed like them, without a
+    /// provisioning profile.
+    fn package_catalyst_app(
+        &self,
+        config: &Config,
+        context: &BuildContext,
+        build_target: IosTarget,
+        properties: &InfoPlist,
+        bin_paths: &[PathBuf],
+        app_path: &Path,
+        name: &str,
+    ) -> Result<PathBuf> {
+        let macos = &context.config.apple.macos;
+        let contents_path = app_path.join("Contents");
+        let app_bin_path = contents_path.join("MacOS").join(name);
+        Self::copy_binary(config, bin_paths, &app_bin_path)?;
+        let icon_file = match &context.config.icon {
+            Some(icon) => {
+                config.status_message("Generating", "AppIcon.icns")?;
+                apple::gen_icns(icon, &contents_path.join("Resources").join("AppIcon.icns"))?;
+                Some("AppIcon")
+            }
+            None => None,
+        };
+        config.status_message("Generating", "Info.plist")?;
+        let mut info_plist_extra = apple::macos_info_plist_keys(
+            macos
+                .minimum_system_version
+                .as_deref()
+                .unwrap_or(CATALYST_MINIMUM_SYSTEM_VERSION),
+            macos.category.as_deref(),
+            icon_file,
+        );
+        let platform_keys = apple::platform_info_plist_keys(build_target);
+        let settings = context.config.apple.info_plist_settings.to_dictionary();
+        for (key, value) in platform_keys.into_iter().chain(settings) {
+            info_plist_extra.insert(key, value);
+        }
+        if context.config.secure_window {
+            info_plist_extra.insert(apple::SECURE_WINDOW_INFO_PLIST_KEY.to_owned(), true.into());
+        }
+        apple::save_info_plist(&contents_path, properties, &info_plist_extra, false)?;
+
+        if let Some(identity) = &self.identity {
+            config.status("Starting code signing process")?;
+            let release = self.shared.release;
+            let mut codesign = apple::Codesign::new(app_path);
+            codesign
+                .identity(identity)
+                .force(true)
+                .hardened_runtime(release)
+                .timestamp(release);
+            let entitlements = self
+                .entitlements
+                .as_ref()
+                .or(context.config.apple.entitlements.as_ref())
+                .map(|entitlements| context.project_path.join(entitlements));
+            if let Some(entitlements) = &entitlements {
+                codesign.entitlements(entitlements);
+            }
+            codesign.run()?;
+            apple::verify_codesign(app_path, true)?;
+            super::record_audit_event(config, "codesign", &app_bin_path, identity)?;
+            config.status("Code signing process finished")?;
+        }
+        config.status("Build finished successfully")?;
+        Ok(app_path.to_owned())
+    }
+
+    /// Returns the identity to sign the build with. Without `--identity` builds for
+    /// devices are signed with an identity from the keychain if the provisioning profile
+    /// and the team identifier are specified.
+    fn signing_identity(&self, config: &Config, build_target: IosTarget) -> Result<Option<String>> {
+        if let Some(identity) = &self.identity {
+            return Ok(Some(identity.clone()));
+        }
+        if !build_target.is_device() {
+            return Ok(None);
+        }
+        let identities = apple::find_codesign_identities().unwrap_or_default();
+        let identity = match apple::select_codesign_identity(&identities, self.shared.release) {
+            Some(identity) => identity,
+            None => {
+                config.shell().warn(
+                    "no codesigning identity found in the keychain, the application can't be installed on devices",
+                )?;
+                return Ok(None);
+            }
+        };
+        let has_profile = self.profile_name.is_some() || self.profile_path.is_some();
+        if !has_profile || self.team_identifier.is_none() {
+            config.shell().warn(format!(
+                "specify `--profile-name` or `--profile-path` and `--team-identifier` to sign the application with `{}`",
+                identity.name
+            ))?;
+            return Ok(None);
+        }
+        config.status_message("Selected identity", &identity.name)?;
+        Ok(Some(identity.hash.clone()))
+    }
+
     /// Get apple build targets from cargo manifest
     pub fn apple_build_targets(
         context: &BuildContext,
@@ -180,8 +396,17 @@ impl IosBuildCommand {
     /// Get info plist from the path in cargo manifest or generate it with the given
     /// configuration
     pub fn gen_info_plist(context: &BuildContext, package_name: &str) -> Result<InfoPlist> {
+        let capabilities = context.capabilities()?;
+        let usage_descriptions = &context.config.apple.usage_descriptions;
         if let Some(info_plist_path) = &context.config.apple.info_plist_path {
-            return Ok(apple::read_info_plist(info_plist_path)?);
+            let mut info_plist = apple::read_info_plist(info_plist_path)?;
+            context
+                .config
+                .apple
+                .info_plist_settings
+                .apply(&mut info_plist);
+            apply_usage_descriptions(&mut info_plist, &capabilities, usage_descriptions)?;
+            return Ok(info_plist);
         }
         let mut info_plist = if let Some(info_plist) = &context.config.apple.info_plist {
             info_plist.clone()
@@ -196,31 +421,60 @@ impl IosBuildCommand {
         context.config.permissions.iter().for_each(|permission| {
             permission.update_info_plist(&mut info_plist);
         });
+        context
+            .config
+            .apple
+            .info_plist_settings
+            .apply(&mut info_plist);
+        let build_script = &context.build_script;
+        if let Some(package_id) = &build_script.package_id {
+            info_plist.identification.bundle_identifier = package_id.clone();
+        }
+        if let Some(version_code) = build_script.version_code {
+            info_plist.bundle_version.bundle_version = Some(version_code.to_string());
+        }
+        if let Some(version_name) = &build_script.version_name {
+            info_plist.bundle_version.bundle_short_version_string = Some(version_name.clone());
+        }
+        apply_usage_descriptions(&mut info_plist, &capabilities, usage_descriptions)?;
         Ok(info_plist)
     }
 
     /// Prepare assets and resources for the application.
     pub fn prepare_assets_and_resources(
         config: &CrossbowMetadata,
+        libraries: &[LibraryResources],
         out_dir: &Path,
     ) -> Result<(Option<PathBuf>, Option<PathBuf>)> {
-        let res = config.get_apple_resources();
+        let library_res: Vec<_> = libraries
+            .iter()
+            .map(|library| (library.package.as_str(), library.apple_resources.as_slice()))
+            .collect();
+        let res = &merge_library_dirs(config.get_apple_resources(), &library_res)?;
         let gen_resources = if res.is_empty() && config.icon.is_none() {
             None
         } else {
             let path = out_dir.join("gen_resources");
             std::fs::remove_dir_all(&path).ok();
             combine_folders(res, &path)?;
+            apple::compile_xcstrings_dir(&path)?;
 
             // TODO: Generate icons
             Some(path)
         };
 
-        let assets = config.get_apple_assets();
-        let gen_assets = if !res.is_empty() {
+        let library_assets: Vec<_> = libraries
+            .iter()
+            .map(|library| (library.package.as_str(), library.get_apple_assets()))
+            .collect();
+        let assets = merge_library_dirs(config.get_apple_assets(), &library_assets)?;
+        let gen_assets = if !assets.is_empty() {
             let path = out_dir.join("gen_assets");
             std::fs::remove_dir_all(&path).ok();
-            combine_folders(assets, &path)?;
+            combine_folders(&assets, &path)?;
+            if config.content_manifest {
+                ContentManifest::from_dir(&path)?.save(&path)?;
+            }
             Some(path)
         } else {
             None
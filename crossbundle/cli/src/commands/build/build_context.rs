@@ -1,6 +1,7 @@
+use super::{eval_build_script, BuildScriptInput, BuildScriptOutput, BUILD_SCRIPT_NAME};
 use crate::{error::*, types::*};
 use crossbundle_tools::{commands::*, types::Config};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub struct BuildContext {
     // Paths
@@ -11,6 +12,11 @@ pub struct BuildContext {
     // Configurations
     pub manifest: cargo::core::Manifest,
     pub config: CrossbowMetadata,
+    /// Configuration computed by `crossbow.build.rhai` script.
+    pub build_script: BuildScriptOutput,
+    /// Name of the Android build profile selected with `--profile` flag.
+    #[cfg(feature = "android")]
+    pub android_profile: Option<String>,
 }
 
 impl BuildContext {
@@ -23,7 +29,18 @@ impl BuildContext {
             target_dir.unwrap_or_else(|| workspace_manifest_path.parent().unwrap().join("target"));
         info!("Parsing Cargo.toml");
         let manifest = parse_manifest(&package_manifest_path)?;
-        let crossbow_metadata = if let Some(cargo_metadata) = manifest.custom_metadata() {
+        let config_file = CrossbowMetadata::find_config_file(&project_path);
+        let crossbow_metadata = if let Some(config_file) = config_file {
+            if manifest.custom_metadata().is_some() {
+                return Err(Error::InvalidMetadata(anyhow::anyhow!(
+                    "configuration is specified both in {} and in `[package.metadata]` of \
+                     Cargo.toml, keep only one of them",
+                    config_file.display()
+                )));
+            }
+            info!("Parsing {}", config_file.display());
+            CrossbowMetadata::from_file(&config_file)?
+        } else if let Some(cargo_metadata) = manifest.custom_metadata() {
             cargo_metadata
                 .clone()
                 .try_into::<CrossbowMetadata>()
@@ -38,9 +55,36 @@ impl BuildContext {
             target_dir,
             config: crossbow_metadata,
             manifest,
+            build_script: BuildScriptOutput::default(),
+            #[cfg(feature = "android")]
+            android_profile: None,
         })
     }
 
+    /// Creates build context of the project in the current directory, or returns `None`
+    /// outside of a Cargo project.
+    pub fn find(config: &Config, target_dir: Option<PathBuf>) -> Result<Option<Self>> {
+        match Self::new(config, target_dir) {
+            Ok(context) => Ok(Some(context)),
+            Err(Error::CrossbundleTools(
+                crossbundle_tools::error::Error::FailedToFindCargoManifest(_),
+            )) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Returns NDK version pinned with `ndk_version` and the minimal version from
+    /// `min_ndk_version` by the project in the current directory.
+    #[cfg_attr(not(feature = "android"), allow(unused_variables))]
+    pub fn ndk_versions(config: &Config) -> Result<(Option<String>, Option<String>)> {
+        #[cfg(feature = "android")]
+        if let Some(context) = Self::find(config, None)? {
+            let android_config = context.config.android;
+            return Ok((android_config.ndk_version, android_config.min_ndk_version));
+        }
+        Ok((None, None))
+    }
+
     /// Get package name from cargo manifest
     pub fn package_name(&self) -> String {
         self.manifest.summary().name().to_string()
@@ -50,4 +94,117 @@ impl BuildContext {
     pub fn package_version(&self) -> String {
         self.manifest.summary().version().to_string()
     }
+
+    /// Returns resources and assets declared by library crates the package depends on.
+    pub fn library_resources(&self) -> Result<Vec<LibraryResources>> {
+        Ok(find_library_resources(&self.package_manifest_path)?)
+    }
+
+    /// Returns names of the crate dependencies and crossbow permissions, e.g.
+    /// `play-billing` or `camera`.
+    pub fn used_apis(&self) -> Result<Vec<String>> {
+        let mut used_apis: Vec<String> = self
+            .manifest
+            .dependencies()
+            .iter()
+            .map(|dependency| dependency.package_name().to_string())
+            .collect();
+        for permission in &self.config.permissions {
+            if let serde_json::Value::String(name) = serde_json::to_value(permission)? {
+                used_apis.push(name);
+            }
+        }
+        Ok(used_apis)
+    }
+
+    /// Returns storage of archived artifacts selected in the configuration.
+    pub fn storage(&self) -> Result<Box<dyn ArtifactStorage>> {
+        Ok(StorageConfig::storage(
+            self.config.storage.as_ref(),
+            &self.target_dir,
+        )?)
+    }
+
+    /// Returns storage of archived artifacts of the project in the current directory,
+    /// or the local storage in `target_dir` outside of a project.
+    pub fn artifact_storage(
+        config: &Config,
+        target_dir: &Path,
+    ) -> Result<Box<dyn ArtifactStorage>> {
+        match Self::find(config, Some(target_dir.to_owned()))? {
+            Some(context) => context.storage(),
+            None => Ok(StorageConfig::storage(None, target_dir)?),
+        }
+    }
+
+    /// Returns capabilities listed in the configuration and inferred from crossbow
+    /// plugins and permissions.
+    pub fn capabilities(&self) -> Result<Vec<&'static Capability>> {
+        Ok(enabled_capabilities(
+            &self.used_apis()?,
+            &self.config.capabilities,
+        )?)
+    }
+
+    /// Evaluates `crossbow.build.rhai` script of the project. Steps registered by the
+    /// script are appended to the build hooks, the application name is overridden right
+    /// away, other values are applied to the manifest or Info.plist.
+    pub fn eval_build_script(
+        &mut self,
+        config: &Config,
+        platform: &str,
+        release: bool,
+        build_profile: Option<&str>,
+    ) -> Result<()> {
+        let input = BuildScriptInput {
+            package_name: self.package_name(),
+            package_version: self.package_version(),
+            platform: platform.to_owned(),
+            release,
+            build_profile: build_profile.map(ToOwned::to_owned),
+        };
+        let output = match eval_build_script(&self.project_path, &input)? {
+            Some(output) => output,
+            None => return Ok(()),
+        };
+        config.status_message("Evaluated", BUILD_SCRIPT_NAME)?;
+        if output.app_name.is_some() {
+            self.config.app_name = output.app_name.clone();
+        }
+        let hooks = &mut self.config.hooks;
+        hooks.pre_build.extend(output.pre_build.iter().cloned());
+        hooks.post_build.extend(output.post_build.iter().cloned());
+        self.build_script = output;
+        Ok(())
+    }
+
+    /// Selects the Android build profile from `profiles` metadata.
+    #[cfg(feature = "android")]
+    pub fn select_android_profile(&mut self, name: &str) -> Result<()> {
+        if !self.config.android.profiles.contains_key(name) {
+            let available: Vec<&str> = self
+                .config
+                .android
+                .profiles
+                .keys()
+                .map(String::as_str)
+                .collect();
+            return Err(Error::InvalidMetadata(anyhow::anyhow!(
+                "build profile `{}` is not found in `package.metadata.android.profiles`, \
+                 available profiles: [{}]",
+                name,
+                available.join(", ")
+            )));
+        }
+        self.android_profile = Some(name.to_owned());
+        Ok(())
+    }
+
+    /// Returns overrides of the selected Android build profile.
+    #[cfg(feature = "android")]
+    pub fn android_build_profile(&self) -> Option<&AndroidBuildProfile> {
+        self.android_profile
+            .as_ref()
+            .and_then(|name| self.config.android.profiles.get(name))
+    }
 }
@@ -0,0 +1,219 @@
+use crate::error::*;
+use crossbundle_tools::commands::Hook;
+use rhai::{Array, Engine, Map, Scope};
+use serde::{Deserialize, Serialize};
+use std::{cell::RefCell, path::Path, process::Command, rc::Rc};
+
+/// Name of the build script in the project directory.
+pub const BUILD_SCRIPT_NAME: &str = "crossbow.build.rhai";
+
+/// Information about the build passed to the build script as variables.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct BuildScriptInput {
+    pub package_name: String,
+    pub package_version: String,
+    /// `android` or `ios`.
+    pub platform: String,
+    pub release: bool,
+    /// Name of the build profile selected with `--profile` flag.
+    pub build_profile: Option<String>,
+}
+
+/// Configuration computed by the build script. `None` values are not changed by the
+/// script.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct BuildScriptOutput {
+    pub version_code: Option<u32>,
+    pub version_name: Option<String>,
+    /// Package name on Android and bundle identifier on iOS.
+    pub package_id: Option<String>,
+    pub app_name: Option<String>,
+    /// Android build profile selected by the script.
+    pub build_profile: Option<String>,
+    /// Steps registered with `pre_build` function.
+    pub pre_build: Vec<Hook>,
+    /// Steps registered with `post_build` function.
+    pub post_build: Vec<Hook>,
+}
+
+#[derive(Default)]
+struct ScriptSteps {
+    pre_build: Vec<Hook>,
+    post_build: Vec<Hook>,
+}
+
+/// Evaluates `crossbow.build.rhai` of the project if it exists.
+///
+/// The script has no access to the file system or processes. It reads the build
+/// information from `package_name`, `package_version`, `platform`, `release`,
+/// `build_profile`, `git_branch` and `git_commit_count` variables and `env(name)`
+/// function, and writes the configuration into `config` map. Extra steps are registered
+/// with `pre_build(command)` and `post_build(command)` and run like build hooks.
+pub fn eval_build_script(
+    project_path: &Path,
+    input: &BuildScriptInput,
+) -> Result<Option<BuildScriptOutput>> {
+    let script_path = project_path.join(BUILD_SCRIPT_NAME);
+    if !script_path.exists() {
+        return Ok(None);
+    }
+    let script = std::fs::read_to_string(&script_path)?;
+    let script_error = |error: String| {
+        Error::InvalidMetadata(anyhow::anyhow!("{}: {}", script_path.display(), error))
+    };
+
+    let mut engine = Engine::new();
+    // Limits protect the build from scripts that never finish
+    engine
+        .set_max_operations(1_000_000)
+        .set_max_call_levels(32)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(64 * 1024)
+        .set_max_array_size(10_000)
+        .set_max_map_size(10_000);
+    engine.register_fn("env", |name: &str| std::env::var(name).unwrap_or_default());
+    let steps = Rc::new(RefCell::new(ScriptSteps::default()));
+    let pre_build = steps.clone();
+    engine.register_fn("pre_build", move |command: Array| {
+        pre_build.borrow_mut().pre_build.push(script_hook(command));
+    });
+    let post_build = steps.clone();
+    engine.register_fn("post_build", move |command: Array| {
+        post_build
+            .borrow_mut()
+            .post_build
+            .push(script_hook(command));
+    });
+
+    let mut scope = Scope::new();
+    scope
+        .push_constant("package_name", input.package_name.clone())
+        .push_constant("package_version", input.package_version.clone())
+        .push_constant("platform", input.platform.clone())
+        .push_constant("release", input.release)
+        .push_constant(
+            "build_profile",
+            input.build_profile.clone().unwrap_or_default(),
+        )
+        .push_constant("git_branch", git_branch(project_path))
+        .push_constant("git_commit_count", git_commit_count(project_path))
+        .push("config", Map::new());
+    engine
+        .run_with_scope(&mut scope, &script)
+        .map_err(|error| script_error(error.to_string()))?;
+
+    let config = scope.get_value::<Map>("config").unwrap_or_default();
+    let string = |key: &str| -> Result<Option<String>> {
+        match config.get(key) {
+            None => Ok(None),
+            Some(value) if value.is_unit() => Ok(None),
+            Some(value) if value.is_string() => Ok(Some(value.to_string())),
+            Some(value) => Err(script_error(format!(
+                "`config.{}` must be a string, got {}",
+                key,
+                value.type_name()
+            ))),
+        }
+    };
+    let version_code = match config.get("version_code") {
+        None => None,
+        Some(value) if value.is_unit() => None,
+        Some(value) => {
+            let version_code = value.as_int().map_err(|type_name| {
+                script_error(format!(
+                    "`config.version_code` must be an integer, got {}",
+                    type_name
+                ))
+            })?;
+            let version_code = u32::try_from(version_code)
+                .ok()
+                .filter(|version_code| *version_code > 0)
+                .ok_or_else(|| {
+                    script_error(format!("invalid `config.version_code` {}", version_code))
+                })?;
+            Some(version_code)
+        }
+    };
+    let steps = steps.replace(ScriptSteps::default());
+    Ok(Some(BuildScriptOutput {
+        version_code,
+        version_name: string("version_name")?,
+        package_id: string("package_id")?,
+        app_name: string("app_name")?,
+        build_profile: string("build_profile")?,
+        pre_build: steps.pre_build,
+        post_build: steps.post_build,
+    }))
+}
+
+/// Creates the hook with default restrictions from the program and its arguments.
+fn script_hook(command: Array) -> Hook {
+    Hook {
+        command: command.into_iter().map(|arg| arg.to_string()).collect(),
+        sandbox: Default::default(),
+    }
+}
+
+/// Returns the current git branch or an empty string outside of git repository.
+fn git_branch(project_path: &Path) -> String {
+    git_output(project_path, &["rev-parse", "--abbrev-ref", "HEAD"]).unwrap_or_default()
+}
+
+/// Returns number of commits in the current git branch or `0` outside of git repository.
+fn git_commit_count(project_path: &Path) -> i64 {
+    git_output(project_path, &["rev-list", "--count", "HEAD"])
+        .and_then(|count| count.parse().ok())
+        .unwrap_or_default()
+}
+
+fn git_output(project_path: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(project_path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eval_build_script() {
+        let tempdir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tempdir.path().join(BUILD_SCRIPT_NAME),
+            r#"
+            if release {
+                config.version_code = 1000 + package_version.len();
+            }
+            config.package_id = "com.crossbow." + package_name + "." + platform;
+            post_build(["python3", "scripts/upload.py"]);
+            "#,
+        )
+        .unwrap();
+        let input = BuildScriptInput {
+            package_name: "game".to_owned(),
+            package_version: "0.1.0".to_owned(),
+            platform: "android".to_owned(),
+            release: true,
+            build_profile: None,
+        };
+        let output = eval_build_script(tempdir.path(), &input).unwrap().unwrap();
+        assert_eq!(output.version_code, Some(1005));
+        assert_eq!(
+            output.package_id.as_deref(),
+            Some("com.crossbow.game.android")
+        );
+        assert_eq!(output.version_name, None);
+        assert!(output.pre_build.is_empty());
+        assert_eq!(
+            output.post_build[0].command,
+            ["python3", "scripts/upload.py"]
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use super::BuildContext;
+use crate::error::*;
+use crossbundle_tools::{
+    commands::{Hook, HookTrustStore, SandboxBackend},
+    error::CommandExt,
+    types::Config,
+};
+use std::io::{BufRead, Write};
+
+/// Runs build hooks of the project. Hooks that weren't trusted before are trusted with
+/// `trust` flag or after confirmation in the interactive prompt.
+pub fn run_hooks(
+    config: &Config,
+    context: &BuildContext,
+    hooks: &[Hook],
+    trust: bool,
+) -> Result<()> {
+    if hooks.is_empty() {
+        return Ok(());
+    }
+    let trust_store = HookTrustStore::from_home_dir()?;
+    let backend = SandboxBackend::detect();
+    for hook in hooks {
+        let fingerprint = hook.fingerprint(&context.project_path)?;
+        if !trust_store.is_trusted(&fingerprint)? {
+            if !trust && !confirm_hook(config, hook)? {
+                return Err(
+                    crossbundle_tools::error::Error::UntrustedHook(hook.display_command()).into(),
+                );
+            }
+            trust_store.trust(&fingerprint)?;
+        }
+        if !hook.sandbox.unrestricted && backend == SandboxBackend::Environment {
+            config
+                .shell()
+                .warn("OS sandbox is not available, hook runs only with constrained environment")?;
+        }
+        config.status_message("Running hook", hook.display_command())?;
+        hook.command(&context.project_path, &backend)?
+            .output_err(true)?;
+    }
+    Ok(())
+}
+
+/// Asks the user whether to trust the hook. Returns `false` if stdin is not a terminal.
+fn confirm_hook(config: &Config, hook: &Hook) -> Result<bool> {
+    if !atty::is(atty::Stream::Stdin) || !config.shell().is_err_tty() {
+        return Ok(false);
+    }
+    {
+        let mut shell = config.shell();
+        shell.warn(format!(
+            "Hook `{}` is running for the first time with restrictions: {:?}",
+            hook.display_command(),
+            hook.sandbox
+        ))?;
+        write!(shell.err(), "Trust this hook? [y/N] ")?;
+        shell.err().flush()?;
+    }
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
@@ -0,0 +1,267 @@
+use super::{
+    apple::IosBuildCommand, ensure_rust_targets, record_audit_event, run_hooks, BuildContext,
+    SharedBuildCommand,
+};
+use crate::{
+    commands::upload::apple::{API_ISSUER_ID_ENV, API_KEY_ID_ENV},
+    error::*,
+};
+use clap::Parser;
+use crossbundle_tools::{commands::apple, types::*};
+use std::path::PathBuf;
+
+/// Minimum macOS version if it's not specified in `apple.macos.minimum_system_version`.
+const DEFAULT_MINIMUM_SYSTEM_VERSION: &str = "10.13";
+
+#[derive(Parser, Clone, Debug)]
+pub struct MacosBuildCommand {
+    #[clap(flatten)]
+    pub shared: SharedBuildCommand,
+    /// Specify custom cargo binary.
+    #[clap(long, conflicts_with = "example")]
+    pub bin: Option<String>,
+    /// Build for the given macOS architecture. Supported targets are:
+    /// `aarch64-apple-darwin`, `x86_64-apple-darwin`. Several targets are merged into one
+    /// universal app
+    #[clap(long, short, multiple_values = true)]
+    pub target: Vec<MacosTarget>,
+    /// The id of the identity used for signing, e.g. `Developer ID Application: Name
+    /// (TEAM)`. Release builds are signed with the hardened runtime and a secure
+    /// timestamp, as required for notarization. The app is not signed without it.
+    #[clap(long)]
+    pub identity: Option<String>,
+    /// Path to the entitlements plist added to the signature. Overrides
+    /// `apple.entitlements` of the config
+    #[clap(long)]
+    pub entitlements: Option<PathBuf>,
+    /// Wrap the app into a DMG with `/Applications` symlink
+    #[clap(long)]
+    pub dmg: bool,
+    /// Submit the signed app to the Apple notary service and staple the ticket. The app
+    /// is signed with the hardened runtime and a secure timestamp
+    #[clap(long, requires = "identity")]
+    pub notarize: bool,
+    /// Keychain profile with the notary credentials stored by `xcrun notarytool
+    /// store-credentials`. Without it the App Store Connect API key from
+    /// `APP_STORE_CONNECT_KEY_ID` and `APP_STORE_CONNECT_ISSUER_ID` environment variables
+    /// is used, read from `~/.appstoreconnect/private_keys/AuthKey_<key id>.p8`
+    #[clap(long)]
+    pub notary_profile: Option<String>,
+}
+
+impl MacosBuildCommand {
+    pub fn run(&self, config: &Config) -> Result<()> {
+        let mut context = BuildContext::new(config, self.shared.target_dir.clone())?;
+        context.eval_build_script(config, "macos", self.shared.release, None)?;
+        let hooks = &context.config.hooks;
+        run_hooks(config, &context, &hooks.pre_build, self.shared.trust)?;
+        self.execute(config, &context)?;
+        run_hooks(config, &context, &hooks.post_build, self.shared.trust)
+    }
+
+    /// Builds the app, a universal one if several targets are specified. Returns paths to
+    /// the apps.
+    pub fn execute(&self, config: &Config, context: &BuildContext) -> Result<Vec<PathBuf>> {
+        let (target, name) = if let Some(example) = &self.shared.example {
+            (Target::Example(example.clone()), example.clone())
+        } else if let Some(bin) = &self.bin {
+            (Target::Bin(bin.clone()), bin.clone())
+        } else {
+            (Target::Bin(context.package_name()), context.package_name())
+        };
+        config.status_message("Starting build process", &name)?;
+        let build_targets = match self.target.is_empty() {
+            true if context.config.apple.macos.build_targets.is_empty() => {
+                vec![MacosTarget::host()]
+            }
+            true => context.config.apple.macos.build_targets.clone(),
+            false => self.target.clone(),
+        };
+        let rust_triples: Vec<&str> = build_targets.iter().map(|t| t.rust_triple()).collect();
+        ensure_rust_targets(config, &rust_triples, self.shared.yes)?;
+        // Several architectures are merged into one universal app
+        let app_path = self.build_app(config, context, target, &build_targets, &name)?;
+        Ok(vec![app_path])
+    }
+
+    fn build_app(
+        &self,
+        config: &Config,
+        context: &BuildContext,
+        target: Target,
+        build_targets: &[MacosTarget],
+        name: &str,
+    ) -> Result<PathBuf> {
+        let profile = self.shared.profile();
+        let macos = &context.config.apple.macos;
+        let mut bin_paths = vec![];
+        for build_target in build_targets {
+            let rust_triple = build_target.rust_triple();
+            config.status_message("Compiling for architecture", rust_triple)?;
+            apple::compile_rust_for_macos(
+                target.clone(),
+                *build_target,
+                &context.project_path,
+                profile,
+                self.shared.features.clone(),
+                self.shared.all_features,
+                self.shared.no_default_features,
+                self.shared.coverage,
+            )?;
+            let mut out_dir = context.target_dir.join(rust_triple).join(profile);
+            if let Target::Example(_) = target {
+                out_dir = out_dir.join("examples");
+            }
+            bin_paths.push(out_dir.join(name));
+        }
+        let build_dir_name = match build_targets {
+            [build_target] => build_target.rust_triple(),
+            _ => apple::UNIVERSAL_MACOS_DIR,
+        };
+        let macos_target_dir = context
+            .target_dir
+            .join("macos")
+            .join(build_dir_name)
+            .join(profile);
+
+        config.status("Preparing resources and assets")?;
+        let (assets, resources) = IosBuildCommand::prepare_assets_and_resources(
+            &context.config,
+            &context.library_resources()?,
+            &macos_target_dir,
+        )?;
+        config.status("Generating app folder")?;
+        let app_path = apple::gen_macos_app_folder(&macos_target_dir, name, assets, resources)?;
+        let contents_path = app_path.join("Contents");
+        let app_bin_path = contents_path.join("MacOS").join(name);
+        if bin_paths.len() > 1 {
+            config.status("Creating universal binary")?;
+            apple::lipo_create(&bin_paths, &app_bin_path)?;
+            let archs = apple::lipo_archs(&app_bin_path)?;
+            config.status_message("Architectures", archs.join(", "))?;
+        } else {
+            std::fs::copy(&bin_paths[0], &app_bin_path)?;
+        }
+        let frameworks: Vec<PathBuf> = macos
+            .frameworks
+            .iter()
+            .map(|framework| context.project_path.join(framework))
+            .collect();
+        let frameworks = apple::embed_macos_frameworks(&app_path, &frameworks)?;
+        if !frameworks.is_empty() {
+            config.status_message("Embedded", format!("{} frameworks", frameworks.len()))?;
+            apple::add_frameworks_rpath(&app_bin_path)?;
+        }
+
+        let icon_file = match &context.config.icon {
+            Some(icon) => {
+                config.status_message("Generating", "AppIcon.icns")?;
+                apple::gen_icns(icon, &contents_path.join("Resources").join("AppIcon.icns"))?;
+                Some("AppIcon")
+            }
+            None => None,
+        };
+        config.status_message("Generating", "Info.plist")?;
+        let mut properties = IosBuildCommand::gen_info_plist(context, name)?;
+        // macOS apps have no launch screen
+        properties.launch_interface.launch_storyboard_name = None;
+        let mut info_plist_extra = apple::macos_info_plist_keys(
+            macos
+                .minimum_system_version
+                .as_deref()
+                .unwrap_or(DEFAULT_MINIMUM_SYSTEM_VERSION),
+            macos.category.as_deref(),
+            icon_file,
+        );
+        for (key, value) in context.config.apple.info_plist_settings.to_dictionary() {
+            info_plist_extra.insert(key, value);
+        }
+        apple::save_info_plist(&contents_path, &properties, &info_plist_extra, false)?;
+
+        if let Some(identity) = &self.identity {
+            config.status("Starting code signing process")?;
+            // Notarization requires the hardened runtime and the secure timestamp
+            let release = self.shared.release || self.notarize;
+            // Nested code is signed before the app, `--deep` doesn't apply the options
+            for framework in &frameworks {
+                apple::Codesign::new(framework)
+                    .identity(identity)
+                    .force(true)
+                    .hardened_runtime(release)
+                    .timestamp(release)
+                    .run()?;
+            }
+            let mut codesign = apple::Codesign::new(&app_path);
+            codesign
+                .identity(identity)
+                .force(true)
+                .hardened_runtime(release)
+                .timestamp(release);
+            let entitlements = self
+                .entitlements
+                .as_ref()
+                .or(context.config.apple.entitlements.as_ref())
+                .map(|entitlements| context.project_path.join(entitlements));
+            if let Some(entitlements) = &entitlements {
+                codesign.entitlements(entitlements);
+            }
+            codesign.run()?;
+            apple::verify_codesign(&app_path, true)?;
+            super::record_audit_event(config, "codesign", &app_bin_path, identity)?;
+            config.status("Code signing process finished")?;
+        }
+
+        if self.notarize {
+            config.status("Notarizing the app")?;
+            let auth = self.notary_auth()?;
+            let key = auth.key().to_owned();
+            apple::Notarize::new(&app_path, auth)
+                .run(|status| {
+                    config.status_message("Notarization", status).ok();
+                })
+                .map_err(Error::CrossbundleTools)?;
+            config.status("Notarization ticket stapled")?;
+            record_audit_event(config, "notarize", &app_bin_path, &key)?;
+        }
+
+        if self.dmg {
+            config.status("Generating dmg file")?;
+            let mut dmg =
+                apple::GenDmg::new(&app_path, &macos_target_dir.join(format!("{}.dmg", name)));
+            if let Some(app_name) = &context.config.app_name {
+                dmg.volume_name(app_name);
+            }
+            if let Some(background) = &macos.dmg_background {
+                dmg.background(&context.project_path.join(background));
+            }
+            let dmg_path = dmg.run()?;
+            config.status_message("Generated", dmg_path.display())?;
+        }
+        config.status("Build finished successfully")?;
+        Ok(app_path)
+    }
+
+    fn notary_auth(&self) -> Result<apple::NotaryAuth> {
+        if let Some(profile) = &self.notary_profile {
+            return Ok(apple::NotaryAuth::KeychainProfile(profile.clone()));
+        }
+        let key_id = std::env::var(API_KEY_ID_ENV).ok();
+        let issuer_id = std::env::var(API_ISSUER_ID_ENV).ok();
+        match (key_id, issuer_id, dirs::home_dir()) {
+            (Some(key_id), Some(issuer_id), Some(home)) => Ok(apple::NotaryAuth::ApiKey {
+                key_path: home
+                    .join(".appstoreconnect")
+                    .join("private_keys")
+                    .join(format!("AuthKey_{}.p8", key_id)),
+                key_id,
+                issuer_id,
+            }),
+            _ => Err(Error::InvalidMetadata(anyhow::anyhow!(
+                "specify `--notary-profile` or set `{}` and `{}` environment variables to \
+                 authenticate with the notary service",
+                API_KEY_ID_ENV,
+                API_ISSUER_ID_ENV
+            ))),
+        }
+    }
+}
@@ -1,20 +1,60 @@
 #[cfg(feature = "android")]
 pub mod android;
+mod api_levels;
 #[cfg(feature = "apple")]
 pub mod apple;
 mod build_context;
+mod build_script;
+mod hooks;
+#[cfg(feature = "apple")]
+pub mod macos;
+mod provenance;
+mod rust_targets;
+mod size_budget;
+#[cfg(feature = "windows")]
+pub mod windows;
 
+pub use api_levels::*;
 pub use build_context::*;
+pub use build_script::*;
+pub use hooks::*;
+pub use provenance::*;
+pub use rust_targets::*;
+pub use size_budget::*;
 
 #[cfg(feature = "android")]
 use android::AndroidBuildCommand;
 #[cfg(feature = "apple")]
 use apple::IosBuildCommand;
+#[cfg(feature = "apple")]
+use macos::MacosBuildCommand;
+#[cfg(feature = "windows")]
+use windows::WindowsBuildCommand;
 
 use crate::error::Result;
 use clap::Parser;
-use crossbundle_tools::types::{Config, Profile};
-use std::path::PathBuf;
+use crossbundle_tools::{
+    commands::{AuditEvent, AuditLog},
+    types::{Config, Profile},
+};
+use std::path::{Path, PathBuf};
+
+/// Records signing operation in the audit log if `CROSSBUNDLE_AUDIT_LOG` is set.
+pub fn record_audit_event(
+    config: &Config,
+    operation: &str,
+    artifact: &Path,
+    key: &str,
+) -> Result<()> {
+    if let Some(audit_log) = AuditLog::from_env() {
+        audit_log.record(&AuditEvent::new(operation, artifact, key)?)?;
+        config.status_message(
+            "Recorded",
+            format!("{} in {}", operation, audit_log.path().display()),
+        )?;
+    }
+    Ok(())
+}
 
 #[derive(Parser, Clone, Debug)]
 pub enum BuildCommand {
@@ -24,16 +64,26 @@ pub enum BuildCommand {
     /// Starts the process of building/packaging/signing of the rust crate for iOS
     #[cfg(feature = "apple")]
     Ios(IosBuildCommand),
+    /// Starts the process of building/packaging/signing of the rust crate for macOS
+    #[cfg(feature = "apple")]
+    Macos(MacosBuildCommand),
+    /// Starts the process of building/packaging/signing of the rust crate for Windows
+    #[cfg(feature = "windows")]
+    Windows(WindowsBuildCommand),
 }
 
 impl BuildCommand {
     pub fn handle_command(&self, config: &Config) -> Result<()> {
-        #[cfg(any(feature = "android", feature = "apple"))]
+        #[cfg(any(feature = "android", feature = "apple", feature = "windows"))]
         match &self {
             #[cfg(feature = "android")]
             Self::Android(cmd) => cmd.run(config)?,
             #[cfg(feature = "apple")]
             Self::Ios(cmd) => cmd.run(config)?,
+            #[cfg(feature = "apple")]
+            Self::Macos(cmd) => cmd.run(config)?,
+            #[cfg(feature = "windows")]
+            Self::Windows(cmd) => cmd.run(config)?,
         }
         Ok(())
     }
@@ -62,6 +112,16 @@ pub struct SharedBuildCommand {
     /// Directory for generated artifact and intermediate files
     #[clap(long)]
     pub target_dir: Option<PathBuf>,
+    /// Trust build hooks of the project that run for the first time without prompting
+    #[clap(long)]
+    pub trust: bool,
+    /// Install missing rust targets with rustup without prompting
+    #[clap(long)]
+    pub yes: bool,
+    /// Instrument the crate with `-C instrument-coverage`. The application writes
+    /// coverage profiles that `crossbundle coverage` collects into an lcov report
+    #[clap(long)]
+    pub coverage: bool,
 }
 
 impl SharedBuildCommand {
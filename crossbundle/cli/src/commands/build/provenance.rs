@@ -0,0 +1,48 @@
+use crate::error::*;
+use crossbundle_tools::{
+    commands::{cosign_sign_blob, OutputManifest, VersionCodeRecord, OUTPUT_MANIFEST_NAME},
+    types::Config,
+};
+use std::path::{Path, PathBuf};
+
+/// Saves the output manifest next to the artifacts. Returns path of the manifest or
+/// `None` if there are no artifacts.
+pub fn save_output_manifest(
+    artifacts: &[PathBuf],
+    version_code: Option<VersionCodeRecord>,
+) -> Result<Option<PathBuf>> {
+    let out_dir = match artifacts.first().and_then(|artifact| artifact.parent()) {
+        Some(out_dir) => out_dir,
+        None => return Ok(None),
+    };
+    let manifest_path = out_dir.join(OUTPUT_MANIFEST_NAME);
+    let mut manifest = OutputManifest::new(artifacts)?;
+    manifest.version_code = version_code;
+    manifest.save(&manifest_path)?;
+    Ok(Some(manifest_path))
+}
+
+/// Signs the artifacts and the output manifest saved with [`save_output_manifest`] with
+/// cosign. Keyless signing is used if `key` is not specified.
+pub fn sign_provenance(
+    config: &Config,
+    artifacts: &[PathBuf],
+    manifest_path: &Path,
+    key: Option<&Path>,
+) -> Result<()> {
+    for artifact in artifacts
+        .iter()
+        .map(|a| a.as_path())
+        .chain(std::iter::once(manifest_path))
+    {
+        config.status_message("Signing provenance", artifact.display())?;
+        let bundle = cosign_sign_blob(artifact, key)?;
+        config.status_message("Saved", bundle.display())?;
+        let key_id = match key {
+            Some(key) => key.display().to_string(),
+            None => "keyless".to_owned(),
+        };
+        super::record_audit_event(config, "cosign_sign", artifact, &key_id)?;
+    }
+    Ok(())
+}
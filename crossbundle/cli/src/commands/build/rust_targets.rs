@@ -0,0 +1,64 @@
+use crate::error::*;
+use crossbundle_tools::{
+    commands::{
+        missing_build_std_components, missing_rust_targets, rustup_install_build_std,
+        rustup_target_add,
+    },
+    types::Config,
+};
+use std::io::{BufRead, Write};
+
+/// Installs rust targets missing in the rustup toolchain before compilation. Targets are
+/// installed with `yes` flag or after confirmation in the interactive prompt. Nothing
+/// is checked if rustup is not available.
+pub fn ensure_rust_targets(config: &Config, rust_triples: &[&str], yes: bool) -> Result<()> {
+    let missing = match missing_rust_targets(rust_triples) {
+        Some(missing) if !missing.is_empty() => missing,
+        _ => return Ok(()),
+    };
+    let targets = missing.join(" ");
+    let warning = format!("Rust targets `{}` are not installed", targets);
+    if !yes && !confirm_install(config, &warning, "rustup target add")? {
+        return Err(crossbundle_tools::error::Error::RustTargetsNotInstalled(targets).into());
+    }
+    config.status_message("Installing rust targets", &targets)?;
+    rustup_target_add(&missing)?;
+    Ok(())
+}
+
+/// Installs the nightly toolchain and its `rust-src` component, which are required to
+/// build the standard library from source, like [`ensure_rust_targets`].
+pub fn ensure_build_std(config: &Config, yes: bool) -> Result<()> {
+    let missing = match missing_build_std_components() {
+        Some(missing) if !missing.is_empty() => missing,
+        _ => return Ok(()),
+    };
+    let components = missing.join(" and ");
+    let warning = format!(
+        "Rust {} required to build the standard library from source is not installed",
+        components
+    );
+    if !yes && !confirm_install(config, &warning, "rustup")? {
+        return Err(crossbundle_tools::error::Error::BuildStdNotInstalled(components).into());
+    }
+    config.status_message("Installing", &components)?;
+    rustup_install_build_std(&missing)?;
+    Ok(())
+}
+
+/// Asks the user whether to install missing toolchain parts. Returns `false` if stdin
+/// is not a terminal.
+fn confirm_install(config: &Config, warning: &str, tool: &str) -> Result<bool> {
+    if !atty::is(atty::Stream::Stdin) || !config.shell().is_err_tty() {
+        return Ok(false);
+    }
+    {
+        let mut shell = config.shell();
+        shell.warn(warning)?;
+        write!(shell.err(), "Install them with `{}`? [Y/n] ", tool)?;
+        shell.err().flush()?;
+    }
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "" | "y" | "Y" | "yes"))
+}
@@ -0,0 +1,54 @@
+use super::BuildContext;
+use crate::error::*;
+use crossbundle_tools::{
+    commands::{ArtifactSizes, SizeBudgets, SizeReport, SIZE_BUDGETS_FILE},
+    error::Error as ToolsError,
+    types::Config,
+};
+use std::path::PathBuf;
+
+/// Measures the artifacts, prints size changes against the previous build and fails if
+/// any budget from `budgets.toml` in the package directory is exceeded.
+pub fn check_size_budgets(
+    config: &Config,
+    context: &BuildContext,
+    artifacts: &[PathBuf],
+) -> Result<()> {
+    let budgets_path = context
+        .package_manifest_path
+        .parent()
+        .unwrap()
+        .join(SIZE_BUDGETS_FILE);
+    let budgets = if budgets_path.exists() {
+        Some(SizeBudgets::read(&budgets_path)?)
+    } else {
+        None
+    };
+    let mut exceeded = Vec::new();
+    for artifact in artifacts {
+        let name = artifact.file_name().unwrap().to_string_lossy().to_string();
+        let sizes = ArtifactSizes::measure(artifact)?;
+        let report_path = SizeReport::path(artifact);
+        let mut report = SizeReport::read_or_default(&report_path)?;
+        for line in sizes.diff(report.artifacts.get(&name)) {
+            config.status_message("Size", format!("{} {}", name, line))?;
+        }
+        if let Some(budgets) = &budgets {
+            exceeded.extend(
+                budgets
+                    .check(&sizes)
+                    .into_iter()
+                    .map(|reason| format!("{}: {}", name, reason)),
+            );
+        }
+        report.artifacts.insert(name, sizes);
+        report.save(&report_path)?;
+    }
+    if !exceeded.is_empty() {
+        for reason in &exceeded {
+            config.shell().error(reason)?;
+        }
+        return Err(ToolsError::SizeBudgetExceeded(exceeded.join("; ")).into());
+    }
+    Ok(())
+}
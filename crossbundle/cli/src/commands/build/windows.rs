@@ -0,0 +1,279 @@
+use super::{ensure_rust_targets, run_hooks, BuildContext, SharedBuildCommand};
+use crate::{error::*, types::WindowsConfig};
+use clap::Parser;
+use crossbow::ContentManifest;
+use crossbundle_tools::{
+    commands::{add_capabilities_to_appx_manifest, combine_folders, merge_library_dirs, windows},
+    error::WindowsError,
+    types::*,
+};
+use std::path::{Path, PathBuf};
+
+/// Publisher if it's not specified in `windows.publisher`. Matches self-signed test
+/// certificates created with `New-SelfSignedCertificate -Subject "CN=Crossbow"`.
+const DEFAULT_PUBLISHER: &str = "CN=Crossbow";
+
+#[derive(Parser, Clone, Debug)]
+pub struct WindowsBuildCommand {
+    #[clap(flatten)]
+    pub shared: SharedBuildCommand,
+    /// Specify custom cargo binary.
+    #[clap(long, conflicts_with = "example")]
+    pub bin: Option<String>,
+    /// Build for the given Windows architecture. Supported targets are:
+    /// `x86_64-pc-windows-msvc`, `aarch64-pc-windows-msvc`. Every target is packaged
+    /// into its own MSIX package
+    #[clap(long, short, multiple_values = true)]
+    pub target: Vec<WindowsTarget>,
+    /// Path to the `.pfx` certificate the package is signed with. Overrides
+    /// `windows.certificate` of the config. The package is not signed without a
+    /// certificate or `--subject-name`
+    #[clap(long)]
+    pub certificate: Option<PathBuf>,
+    /// Password of the `.pfx` certificate
+    #[clap(long)]
+    pub certificate_password: Option<String>,
+    /// Sign with the certificate from the certificate store with the given subject name
+    #[clap(long, conflicts_with = "certificate")]
+    pub subject_name: Option<String>,
+}
+
+impl WindowsBuildCommand {
+    pub fn run(&self, config: &Config) -> Result<()> {
+        let mut context = BuildContext::new(config, self.shared.target_dir.clone())?;
+        context.eval_build_script(config, "windows", self.shared.release, None)?;
+        let hooks = &context.config.hooks;
+        run_hooks(config, &context, &hooks.pre_build, self.shared.trust)?;
+        self.execute(config, &context)?;
+        run_hooks(config, &context, &hooks.post_build, self.shared.trust)
+    }
+
+    /// Builds MSIX packages for all targets. Returns paths to the packages.
+    pub fn execute(&self, config: &Config, context: &BuildContext) -> Result<Vec<PathBuf>> {
+        let (target, name) = if let Some(example) = &self.shared.example {
+            (Target::Example(example.clone()), example.clone())
+        } else if let Some(bin) = &self.bin {
+            (Target::Bin(bin.clone()), bin.clone())
+        } else {
+            (Target::Bin(context.package_name()), context.package_name())
+        };
+        config.status_message("Starting build process", &name)?;
+        let build_targets = match self.target.is_empty() {
+            true if context.config.windows.build_targets.is_empty() => {
+                vec![WindowsTarget::host()]
+            }
+            true => context.config.windows.build_targets.clone(),
+            false => self.target.clone(),
+        };
+        let rust_triples: Vec<&str> = build_targets.iter().map(|t| t.rust_triple()).collect();
+        ensure_rust_targets(config, &rust_triples, self.shared.yes)?;
+        let mut packages = vec![];
+        for build_target in build_targets {
+            packages.push(self.build_package(
+                config,
+                context,
+                target.clone(),
+                build_target,
+                &name,
+            )?);
+        }
+        Ok(packages)
+    }
+
+    fn build_package(
+        &self,
+        config: &Config,
+        context: &BuildContext,
+        target: Target,
+        build_target: WindowsTarget,
+        name: &str,
+    ) -> Result<PathBuf> {
+        let profile = self.shared.profile();
+        let windows_config = &context.config.windows;
+        // Logos are required, fail before compiling
+        let icon =
+            context.config.icon.as_ref().ok_or_else(|| {
+                crossbundle_tools::error::Error::from(WindowsError::IconNotSpecified)
+            })?;
+        let rust_triple = build_target.rust_triple();
+        config.status_message("Compiling for architecture", rust_triple)?;
+        windows::compile_rust_for_windows(
+            target.clone(),
+            build_target,
+            &context.project_path,
+            profile,
+            self.shared.features.clone(),
+            self.shared.all_features,
+            self.shared.no_default_features,
+            self.shared.coverage,
+        )?;
+        let mut exe_dir = context.target_dir.join(rust_triple).join(profile);
+        if let Target::Example(_) = target {
+            exe_dir = exe_dir.join("examples");
+        }
+        let exe_path = exe_dir.join(format!("{}.exe", name));
+        let windows_target_dir = context
+            .target_dir
+            .join("windows")
+            .join(rust_triple)
+            .join(profile);
+
+        config.status("Preparing assets")?;
+        let assets = Self::prepare_assets(context, &windows_target_dir)?;
+        config.status("Generating package layout")?;
+        let layout_path = windows::gen_appx_folder(&windows_target_dir, name, &exe_path, assets)?;
+        config.status("Generating logos")?;
+        windows::gen_appx_logos(
+            &context.project_path.join(icon),
+            &layout_path.join(windows::APPX_IMAGES_DIR),
+        )?;
+
+        config.status_message("Generating", APPX_MANIFEST_FILE)?;
+        let manifest = Self::gen_appx_manifest(context, build_target, name)?;
+        manifest.save(&layout_path)?;
+        config.status_message("Generating", "resources.pri")?;
+        windows::gen_resources_pri(&layout_path)?;
+
+        config.status("Packing MSIX package")?;
+        let package_path = windows::MakeAppx::new(
+            &layout_path,
+            &windows_target_dir.join(format!("{}.msix", name)),
+        )
+        .overwrite(true)
+        .run()?;
+
+        let certificate = self
+            .certificate
+            .as_ref()
+            .or(windows_config.certificate.as_ref())
+            .map(|certificate| context.project_path.join(certificate));
+        // The certificate file or the subject name identifies the key in the audit log
+        let signing_key = certificate
+            .as_ref()
+            .map(|certificate| certificate.display().to_string())
+            .or_else(|| self.subject_name.clone());
+        if let Some(signing_key) = signing_key {
+            config.status("Signing the package")?;
+            let mut signtool = windows::SignTool::new(&package_path);
+            if let Some(certificate) = &certificate {
+                signtool.certificate(certificate);
+            }
+            if let Some(password) = self.certificate_password(windows_config)? {
+                signtool.password(&password);
+            }
+            if let Some(subject_name) = &self.subject_name {
+                signtool.subject_name(subject_name);
+            }
+            if let Some(timestamp_url) = &windows_config.timestamp_url {
+                signtool.timestamp_url(timestamp_url);
+            }
+            signtool.run()?;
+            super::record_audit_event(config, "signtool", &package_path, &signing_key)?;
+            config.status("Signing finished")?;
+        }
+        config.status_message("Generated", package_path.display())?;
+        config.status("Build finished successfully")?;
+        Ok(package_path)
+    }
+
+    /// Generates the package manifest from the shared metadata and `windows` config.
+    pub fn gen_appx_manifest(
+        context: &BuildContext,
+        build_target: WindowsTarget,
+        name: &str,
+    ) -> Result<AppxManifest> {
+        let windows_config = &context.config.windows;
+        let identity_name = windows_config
+            .identity_name
+            .clone()
+            .or_else(|| context.build_script.package_id.clone())
+            .unwrap_or_else(|| format!("com.crossbow.{}", name.replace('_', "-")));
+        let version = context
+            .build_script
+            .version_name
+            .clone()
+            .unwrap_or_else(|| context.package_version());
+        let display_name = context
+            .config
+            .app_name
+            .clone()
+            .unwrap_or_else(|| name.to_owned());
+        let mut manifest = AppxManifest::new(
+            &identity_name,
+            windows_config
+                .publisher
+                .as_deref()
+                .unwrap_or(DEFAULT_PUBLISHER),
+            &appx_version(&version)?,
+            build_target,
+            &display_name,
+            &format!("{}.exe", name),
+        );
+        let optional_fields = [
+            (
+                &mut manifest.publisher_display_name,
+                &windows_config.publisher_display_name,
+            ),
+            (&mut manifest.description, &windows_config.description),
+            (
+                &mut manifest.background_color,
+                &windows_config.background_color,
+            ),
+            (&mut manifest.min_version, &windows_config.min_version),
+            (
+                &mut manifest.max_version_tested,
+                &windows_config.max_version_tested,
+            ),
+        ];
+        for (field, value) in optional_fields {
+            if let Some(value) = value {
+                *field = value.clone();
+            }
+        }
+        add_capabilities_to_appx_manifest(&mut manifest, &context.capabilities()?);
+        for capability in &windows_config.capabilities {
+            if !manifest.capabilities.contains(capability) {
+                manifest.capabilities.push(capability.clone());
+            }
+        }
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Combines assets of the application and its libraries. Returns `None` if there are
+    /// no assets.
+    fn prepare_assets(context: &BuildContext, out_dir: &Path) -> Result<Option<PathBuf>> {
+        let libraries = context.library_resources()?;
+        let library_assets: Vec<_> = libraries
+            .iter()
+            .map(|library| (library.package.as_str(), library.assets.as_slice()))
+            .collect();
+        let assets = merge_library_dirs(context.config.get_windows_assets(), &library_assets)?;
+        if assets.is_empty() {
+            return Ok(None);
+        }
+        let path = out_dir.join("gen_assets");
+        std::fs::remove_dir_all(&path).ok();
+        combine_folders(&assets, &path)?;
+        if context.config.content_manifest {
+            ContentManifest::from_dir(&path)?.save(&path)?;
+        }
+        Ok(Some(path))
+    }
+
+    fn certificate_password(&self, windows_config: &WindowsConfig) -> Result<Option<String>> {
+        match (
+            &self.certificate_password,
+            &windows_config.certificate_password_env,
+        ) {
+            (Some(password), _) => Ok(Some(password.clone())),
+            (None, Some(env)) => std::env::var(env).map(Some).map_err(|_| {
+                Error::InvalidMetadata(anyhow::anyhow!(
+                    "environment variable `{}` with the certificate password is not set",
+                    env
+                ))
+            }),
+            (None, None) => Ok(None),
+        }
+    }
+}
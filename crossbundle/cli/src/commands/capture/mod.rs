@@ -0,0 +1,111 @@
+use crate::error::*;
+use clap::Parser;
+#[cfg(feature = "apple")]
+use crossbundle_tools::commands::apple;
+use crossbundle_tools::types::Config;
+#[cfg(feature = "android")]
+use crossbundle_tools::{commands::android::*, types::AndroidSdk};
+use std::path::PathBuf;
+
+/// Device to capture the screen of.
+#[derive(Parser, Clone, Debug)]
+pub struct CaptureTarget {
+    /// Serial of the Android device or emulator. Required if several devices are
+    /// connected
+    #[clap(long, short = 'd')]
+    pub device: Option<String>,
+    /// UDID of the Apple simulator or `booted`. The screen of the simulator is captured
+    /// instead of the Android device
+    #[clap(long, conflicts_with = "device")]
+    pub simulator: Option<String>,
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct ScreenshotCommand {
+    #[clap(flatten)]
+    pub target: CaptureTarget,
+    /// Path of the PNG file
+    #[clap(default_value = "screenshot.png")]
+    pub output: PathBuf,
+}
+
+impl ScreenshotCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        match self.target.simulator.as_deref() {
+            #[cfg(feature = "apple")]
+            Some(udid) => apple::simulator_screenshot(udid, &self.output)?,
+            #[cfg(feature = "android")]
+            None => {
+                let sdk = AndroidSdk::from_env()?;
+                let device = select_android_device(&sdk, self.target.device.as_deref())?;
+                android_screenshot(&sdk, Some(&device), &self.output)?;
+            }
+            #[allow(unreachable_patterns)]
+            _ => {
+                return Err(Error::InvalidMetadata(anyhow::anyhow!(
+                    "the platform is not supported by this build of crossbundle"
+                )))
+            }
+        }
+        config.status_message("Saved screenshot", self.output.display())?;
+        Ok(())
+    }
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct RecordCommand {
+    #[clap(flatten)]
+    pub target: CaptureTarget,
+    /// Recording duration in seconds. Android devices record at most 180 seconds
+    #[clap(long, short = 't', default_value = "30")]
+    pub time_limit: u32,
+    /// Video bit rate in bits per second, e.g. `8000000`. Android only
+    #[clap(long)]
+    pub bit_rate: Option<u32>,
+    /// Video size like `1280x720`. By default the native resolution is used. Android
+    /// only
+    #[clap(long)]
+    pub size: Option<String>,
+    /// Path of the MP4 file
+    #[clap(default_value = "recording.mp4")]
+    pub output: PathBuf,
+}
+
+impl RecordCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        config.status_message(
+            "Recording",
+            format!("{} seconds of the screen", self.time_limit),
+        )?;
+        match self.target.simulator.as_deref() {
+            #[cfg(feature = "apple")]
+            Some(udid) => apple::simulator_record_video(
+                udid,
+                &self.output,
+                std::time::Duration::from_secs(self.time_limit as u64),
+            )?,
+            #[cfg(feature = "android")]
+            None => {
+                let sdk = AndroidSdk::from_env()?;
+                let device = select_android_device(&sdk, self.target.device.as_deref())?;
+                let mut record = ScreenRecord::default();
+                record.time_limit(self.time_limit);
+                if let Some(bit_rate) = self.bit_rate {
+                    record.bit_rate(bit_rate);
+                }
+                if let Some(size) = &self.size {
+                    record.size(size);
+                }
+                record.run(&sdk, Some(&device), &self.output)?;
+            }
+            #[allow(unreachable_patterns)]
+            _ => {
+                return Err(Error::InvalidMetadata(anyhow::anyhow!(
+                    "the platform is not supported by this build of crossbundle"
+                )))
+            }
+        }
+        config.status_message("Saved recording", self.output.display())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+#[cfg(feature = "android")]
+use super::build::android::AndroidBuildCommand;
+#[cfg(feature = "apple")]
+use super::build::apple::IosBuildCommand;
+use super::build::BuildContext;
+use crate::error::*;
+use clap::Parser;
+#[cfg(feature = "apple")]
+use crossbundle_tools::commands::{apple, find_profraw_files};
+#[cfg(feature = "android")]
+use crossbundle_tools::{
+    commands::android::*,
+    types::{AndroidSdk, AndroidStrategy},
+};
+use crossbundle_tools::{
+    commands::{export_lcov, merge_profraw},
+    types::{Config, Profile},
+};
+use std::path::{Path, PathBuf};
+
+#[derive(Parser, Clone, Debug)]
+pub struct CoverageCommand {
+    /// Instrumented Android library or iOS executable. By default binaries of the
+    /// project in `target/<rust triple>/<profile>` are used
+    #[clap(long)]
+    pub object: Vec<PathBuf>,
+    /// Use binaries of the `release` profile
+    #[clap(long)]
+    pub release: bool,
+    /// Path of the lcov report. The merged `.profdata` profile is saved next to it
+    #[clap(long, short, default_value = "target/coverage/lcov.info")]
+    pub output: PathBuf,
+    /// Package name or bundle identifier. By default it's read from the project metadata
+    #[clap(long)]
+    pub package: Option<String>,
+    /// Serial of the Android device or emulator. Required if several devices are
+    /// connected
+    #[clap(long, short = 'd')]
+    pub device: Option<String>,
+    /// UDID of the Apple simulator or `booted`. Profiles are collected from the
+    /// simulator instead of the Android device
+    #[clap(long, conflicts_with = "device")]
+    pub simulator: Option<String>,
+    /// Delete the profiles from the device or simulator after collecting them
+    #[clap(long)]
+    pub clear: bool,
+}
+
+impl CoverageCommand {
+    /// Collects coverage profiles written by the instrumented application, merges them
+    /// with `llvm-profdata` and exports the lcov report with `llvm-cov`.
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        let report_dir = self.output.parent().unwrap_or_else(|| Path::new("."));
+        let profraw_dir = report_dir.join("profraw");
+        // Profiles of previous runs must not be merged into the new report
+        if profraw_dir.exists() {
+            std::fs::remove_dir_all(&profraw_dir)?;
+        }
+        let (profraw, android): (Vec<PathBuf>, bool) = match self.simulator.as_deref() {
+            #[cfg(feature = "apple")]
+            Some(udid) => (self.collect_apple(config, udid, &profraw_dir)?, false),
+            #[cfg(feature = "android")]
+            None => (self.collect_android(config, &profraw_dir)?, true),
+            #[allow(unreachable_patterns)]
+            _ => {
+                return Err(Error::InvalidMetadata(anyhow::anyhow!(
+                    "the platform is not supported by this build of crossbundle"
+                )))
+            }
+        };
+        config.status_message("Collected", format!("{} coverage profiles", profraw.len()))?;
+        let objects = match self.object.is_empty() {
+            true => self.project_objects(config, android)?,
+            false => self.object.clone(),
+        };
+        let profdata = self.output.with_extension("profdata");
+        merge_profraw(&profraw, &profdata)?;
+        export_lcov(&profdata, &objects, &self.output)?;
+        config.status_message("Generated", self.output.display())?;
+        Ok(())
+    }
+
+    #[cfg(feature = "android")]
+    fn collect_android(&self, config: &Config, profraw_dir: &Path) -> Result<Vec<PathBuf>> {
+        let package = match &self.package {
+            Some(package) => package.clone(),
+            None => {
+                let context = BuildContext::new(config, None)?;
+                AndroidBuildCommand::get_android_manifest(&context, AndroidStrategy::NativeApk)?
+                    .package
+            }
+        };
+        let sdk = AndroidSdk::from_env()?;
+        let device = select_android_device(&sdk, self.device.as_deref())?;
+        let profraw = pull_coverage_profiles(&sdk, &package, profraw_dir, Some(&device))?;
+        if self.clear {
+            clear_coverage_profiles(&sdk, &package, Some(&device))?;
+        }
+        Ok(profraw)
+    }
+
+    #[cfg(feature = "apple")]
+    fn collect_apple(
+        &self,
+        config: &Config,
+        udid: &str,
+        profraw_dir: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        let bundle_id = match &self.package {
+            Some(bundle_id) => bundle_id.clone(),
+            None => {
+                let context = BuildContext::new(config, None)?;
+                IosBuildCommand::gen_info_plist(&context, &context.package_name())?
+                    .identification
+                    .bundle_identifier
+            }
+        };
+        let coverage_dir = apple::simulator_coverage_dir(udid, &bundle_id)?;
+        std::fs::create_dir_all(profraw_dir)?;
+        let mut profraw = Vec::new();
+        for file in find_profraw_files(&coverage_dir)? {
+            let copy = profraw_dir.join(file.file_name().unwrap());
+            std::fs::copy(&file, &copy)?;
+            if self.clear {
+                std::fs::remove_file(&file)?;
+            }
+            profraw.push(copy);
+        }
+        Ok(profraw)
+    }
+
+    /// Returns instrumented binaries of the project built for Android or iOS targets.
+    fn project_objects(&self, config: &Config, android: bool) -> Result<Vec<PathBuf>> {
+        let context = BuildContext::new(config, None)?;
+        let profile = match self.release {
+            true => Profile::Release,
+            false => Profile::Debug,
+        };
+        let package_name = context.package_name();
+        let mut objects = Vec::new();
+        if context.target_dir.exists() {
+            for entry in std::fs::read_dir(&context.target_dir)? {
+                let triple_dir = entry?.path();
+                let triple = triple_dir
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string();
+                let object = match android {
+                    true if triple.ends_with("android") || triple.ends_with("androideabi") => {
+                        triple_dir
+                            .join(profile)
+                            .join(format!("lib{}.so", package_name.replace('-', "_")))
+                    }
+                    false if triple.contains("apple-ios") => {
+                        triple_dir.join(profile).join(&package_name)
+                    }
+                    _ => continue,
+                };
+                if object.exists() {
+                    objects.push(object);
+                }
+            }
+        }
+        if objects.is_empty() {
+            return Err(crossbundle_tools::error::Error::CoverageDataNotFound(
+                "no instrumented binaries found in the target directory, specify them with `--object`".to_owned(),
+            )
+            .into());
+        }
+        Ok(objects)
+    }
+}
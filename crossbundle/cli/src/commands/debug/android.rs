@@ -0,0 +1,96 @@
+use crate::commands::build::android::AndroidBuildCommand;
+use crate::error::*;
+use clap::Parser;
+use crossbundle_tools::{
+    commands::android::*,
+    types::{AndroidSdk, AndroidStrategy, AndroidTarget, Config},
+};
+use std::{process::Command, time::Duration};
+
+/// Host port forwarded to the JDWP agent of the application.
+const JDWP_PORT: u16 = 8700;
+
+#[derive(Parser, Clone, Debug)]
+pub struct AndroidDebugCommand {
+    #[clap(flatten)]
+    pub build_command: AndroidBuildCommand,
+    /// Serial of the device or emulator to debug the application on. Required if several
+    /// devices are connected
+    #[clap(long, short = 'd')]
+    pub device: Option<String>,
+    /// Sets a breakpoint on the function before the application continues, e.g.
+    /// `--break android_main` or `--break rust_panic`
+    #[clap(long = "break")]
+    pub breakpoints: Vec<String>,
+}
+
+impl AndroidDebugCommand {
+    /// Installs the debuggable APK, starts it with `am start -D` and attaches lldb with
+    /// symbols from the unstripped libraries.
+    pub fn run(&self, config: &Config) -> Result<()> {
+        if self.build_command.strategy != AndroidStrategy::NativeApk {
+            return Err(Error::InvalidMetadata(anyhow::anyhow!(
+                "debugging is supported only with `native-apk` strategy, add `-s native-apk`"
+            )));
+        }
+        if self.build_command.shared.release {
+            return Err(Error::InvalidMetadata(anyhow::anyhow!(
+                "release builds are not debuggable, remove the `--release` flag"
+            )));
+        }
+        let context = self.build_command.build_context(config)?;
+        let (manifest, sdk, apk_path) = self.build_command.execute_apk(config, &context)?;
+        let (_, ndk) = AndroidBuildCommand::android_toolchain(&context)?;
+        let package = manifest.package;
+
+        let device = select_android_device(&sdk, self.device.as_deref())?;
+        config.status_message("Selected device", &device)?;
+        let abi = device_property(&sdk, &device, "ro.product.cpu.abi")?.unwrap_or_default();
+        let target = AndroidTarget::from_android_abi(&abi)?;
+        let lldb_server = find_lldb_server(&ndk, target)?;
+        let lldb = find_lldb(&ndk)?;
+
+        config.status("Installing APK file")?;
+        install_apk(&sdk, &apk_path, Some(&device))?;
+        force_stop_app(&sdk, &package, Some(&device))?;
+        config.status("Starting application waiting for debugger")?;
+        start_app_for_debugger(&sdk, &package, "android.app.NativeActivity", Some(&device))?;
+        let pid = wait_for_app_pid(&sdk, &package, Some(&device), Duration::from_secs(10))?;
+        config.status_message("Application started with PID", pid)?;
+
+        config.status_message("Starting", lldb_server.display())?;
+        let mut lldb_server = start_lldb_server(&sdk, &package, &lldb_server, Some(&device))?;
+        let profile = self.build_command.cargo_profile(&context);
+        let symbol_dirs = unstripped_lib_dirs(&context.target_dir, target, &profile);
+        let commands = lldb_attach_commands(&package, pid, &symbol_dirs, &self.breakpoints);
+        let commands_path = context.target_dir.join("android").join("lldb-attach.txt");
+        std::fs::create_dir_all(commands_path.parent().unwrap())?;
+        std::fs::write(&commands_path, commands)?;
+
+        // The application waits for the Java debugger, so it's resumed once lldb attaches
+        let resume_device = device.clone();
+        let resume_package = package.clone();
+        let resume = std::thread::spawn(move || -> std::result::Result<(), String> {
+            let sdk = AndroidSdk::from_env().map_err(|e| e.to_string())?;
+            let device = Some(resume_device.as_str());
+            wait_for_native_debugger(&sdk, &resume_package, pid, device, Duration::from_secs(60))
+                .map_err(|e| e.to_string())?;
+            resume_java_debugger(&sdk, pid, JDWP_PORT, device).map_err(|e| e.to_string())
+        });
+
+        config.status_message("Attaching", lldb.display())?;
+        let status = Command::new(&lldb)
+            .arg("--source")
+            .arg(&commands_path)
+            .env("ANDROID_SERIAL", &device)
+            .status();
+        lldb_server.kill().ok();
+        if let Ok(Err(error)) = resume.join() {
+            config
+                .shell()
+                .warn(format!("Failed to resume the application: {}", error))?;
+        }
+        status?;
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+#[cfg(feature = "android")]
+mod android;
+
+use crate::error::Result;
+use clap::Parser;
+use crossbundle_tools::types::Config;
+
+#[derive(Parser, Clone, Debug)]
+pub enum DebugCommand {
+    /// Builds and installs the application, starts it waiting for the debugger and
+    /// attaches lldb to the process on the Android device/emulator
+    #[cfg(feature = "android")]
+    Android(android::AndroidDebugCommand),
+}
+
+impl DebugCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        #[cfg(feature = "android")]
+        match &self {
+            Self::Android(cmd) => cmd.run(config)?,
+        }
+        Ok(())
+    }
+}
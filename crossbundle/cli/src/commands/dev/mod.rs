@@ -0,0 +1,244 @@
+#[cfg(feature = "android")]
+use super::build::android::AndroidBuildCommand;
+use super::build::BuildContext;
+use crate::error::*;
+use clap::Parser;
+#[cfg(feature = "android")]
+use crossbundle_tools::{
+    commands::android::*,
+    types::{AndroidSdk, AndroidTarget, IntoRustTriple},
+};
+use crossbundle_tools::{
+    commands::{
+        build_hot_reload_library, dev_channel_token, hot_reload_remote_path, latest_modification,
+        DevChannelClient, DEFAULT_DEV_CHANNEL_PORT,
+    },
+    types::{Config, Profile},
+};
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Parser, Clone, Debug)]
+pub struct DevCommand {
+    #[clap(subcommand)]
+    pub action: DevAction,
+    /// Port of the development channel started by the game
+    #[clap(long, global = true, default_value_t = DEFAULT_DEV_CHANNEL_PORT)]
+    pub port: u16,
+    /// Session token the game started the development channel with. By default it's
+    /// read from `CROSSBOW_DEV_TOKEN`
+    #[clap(long, global = true)]
+    pub token: Option<String>,
+    /// Serial of the Android device or emulator. Required if several devices are
+    /// connected
+    #[clap(long, short = 'd', global = true)]
+    pub device: Option<String>,
+    /// Don't forward the port to the Android device, e.g. to connect to the iOS simulator
+    /// or the desktop build
+    #[clap(long, global = true)]
+    pub no_forward: bool,
+}
+
+#[derive(Parser, Clone, Debug)]
+pub enum DevAction {
+    /// Pushes the file or the directory into the channel root of the running game
+    Push {
+        /// Path of the local file or directory
+        local: PathBuf,
+        /// Path relative to the channel root. By default it's the name of the local file
+        remote: Option<String>,
+    },
+    /// Pulls the file from the channel root of the running game, e.g. profiling dumps
+    Pull {
+        /// Path relative to the channel root
+        remote: String,
+        /// Path of the local file. By default it's the name of the remote file
+        local: Option<PathBuf>,
+    },
+    /// Shows or hides the debug overlay of the game built with `debug-overlay` feature
+    Overlay {
+        /// `on`, `off`, `toggle`, `status` to print the overlay text or `stats` to print the
+        /// frame rate and the thermal state
+        #[clap(default_value = "toggle", possible_values = ["on", "off", "toggle", "status", "stats"])]
+        state: String,
+    },
+    /// Runs the console command in the running game and prints its output
+    Cmd {
+        /// Command and its arguments
+        #[clap(required = true)]
+        command: Vec<String>,
+    },
+    /// Builds the game logic library and pushes it to the running game built with
+    /// `hot-reload` feature, which loads it without restarting. Experimental
+    HotReload {
+        /// Package of the game logic library with `crate-type = ["cdylib"]`
+        #[clap(long, short)]
+        package: String,
+        /// Rust target of the library, e.g. `aarch64-apple-ios-sim`. By default it's the
+        /// target of the selected Android device
+        #[clap(long, short)]
+        target: Option<String>,
+        /// Build the library with the release profile
+        #[clap(long)]
+        release: bool,
+        /// Rebuild and push the library every time files of the directory change, e.g.
+        /// `game-logic/src`
+        #[clap(long, short)]
+        watch: Option<PathBuf>,
+    },
+}
+
+impl DevCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        #[cfg(feature = "android")]
+        if !self.no_forward {
+            let sdk = AndroidSdk::from_env()?;
+            let device = select_android_device(&sdk, self.device.as_deref())?;
+            forward_tcp_port(&sdk, self.port, Some(&device))?;
+        }
+        let token = dev_channel_token(self.token.as_deref())?;
+        let client = DevChannelClient::new(self.port, &token);
+        match &self.action {
+            DevAction::Push { local, remote } => {
+                if !local.exists() {
+                    return Err(Error::PathNotFound(local.clone()));
+                }
+                let remote = match remote {
+                    Some(remote) => remote.clone(),
+                    None => file_name(local)?,
+                };
+                push_recursive(config, &client, local, &remote)?;
+            }
+            DevAction::Pull { remote, local } => {
+                let local = match local {
+                    Some(local) => local.clone(),
+                    None => PathBuf::from(file_name(Path::new(remote))?),
+                };
+                client.pull(remote, &local)?;
+                config.status_message("Pulled", local.display())?;
+            }
+            DevAction::Overlay { state } => {
+                let output = client.command(&format!("overlay {}", state))?;
+                println!("{}", output);
+            }
+            DevAction::Cmd { command } => {
+                let output = client.command(&command.join(" "))?;
+                println!("{}", output);
+            }
+            DevAction::HotReload {
+                package,
+                target,
+                release,
+                watch,
+            } => {
+                let profile = match *release {
+                    true => Profile::Release,
+                    false => Profile::Debug,
+                };
+                let context = BuildContext::new(config, None)?;
+                let (rust_triple, envs) = match target {
+                    Some(target) => (target.clone(), Vec::new()),
+                    None => self.android_target_envs(&context)?,
+                };
+                let hot_reload = || -> Result<()> {
+                    config.status_message("Compiling", package)?;
+                    let library_path = build_hot_reload_library(
+                        &context.project_path,
+                        &context.target_dir,
+                        package,
+                        &rust_triple,
+                        profile,
+                        &envs,
+                    )?;
+                    let version = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_millis();
+                    let remote = hot_reload_remote_path(&library_path, version);
+                    client.push(&library_path, &remote)?;
+                    config.status_message("Pushed", remote)?;
+                    Ok(())
+                };
+                let watch = match watch {
+                    Some(watch) => watch,
+                    None => return hot_reload(),
+                };
+                config.status_message("Watching", watch.display())?;
+                let mut last_modification = None;
+                loop {
+                    let modification = latest_modification(watch)?;
+                    if last_modification != Some(modification) {
+                        last_modification = Some(modification);
+                        // Compilation errors must not stop watching
+                        if let Err(error) = hot_reload() {
+                            config.shell().error(error)?;
+                        }
+                    }
+                    std::thread::sleep(Duration::from_millis(500));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the Rust target of the selected Android device and cargo environment to
+    /// link the library with the NDK of the project for the API level of the device.
+    #[cfg(feature = "android")]
+    fn android_target_envs(
+        &self,
+        context: &BuildContext,
+    ) -> Result<(String, Vec<(String, PathBuf)>)> {
+        let (sdk, ndk) = AndroidBuildCommand::android_toolchain(context)?;
+        let device = select_android_device(&sdk, self.device.as_deref())?;
+        let abi = device_property(&sdk, &device, "ro.product.cpu.abi")?.unwrap_or_default();
+        let build_target = AndroidTarget::from_android_abi(&abi)?;
+        let api_level = device_property(&sdk, &device, "ro.build.version.sdk")?
+            .and_then(|api_level| api_level.parse().ok())
+            .unwrap_or_else(|| sdk.default_platform());
+        let (clang, _) = ndk.clang(build_target, api_level)?;
+        let rust_triple = build_target.rust_triple();
+        let envs = vec![
+            (cargo_env_target_cfg("LINKER", rust_triple), clang.clone()),
+            (format!("CC_{}", rust_triple), clang),
+        ];
+        Ok((rust_triple.to_owned(), envs))
+    }
+
+    #[cfg(not(feature = "android"))]
+    fn android_target_envs(
+        &self,
+        _context: &BuildContext,
+    ) -> Result<(String, Vec<(String, PathBuf)>)> {
+        Err(Error::InvalidMetadata(anyhow::anyhow!(
+            "specify `--target` of the game logic library"
+        )))
+    }
+}
+
+/// Pushes the file or all files of the directory keeping the directory structure.
+fn push_recursive(
+    config: &Config,
+    client: &DevChannelClient,
+    local: &Path,
+    remote: &str,
+) -> Result<()> {
+    if local.is_dir() {
+        for entry in std::fs::read_dir(local)? {
+            let path = entry?.path();
+            let remote = format!("{}/{}", remote.trim_end_matches('/'), file_name(&path)?);
+            push_recursive(config, client, &path, &remote)?;
+        }
+    } else {
+        client.push(local, remote)?;
+        config.status_message("Pushed", remote)?;
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> Result<String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| Error::PathNotFound(path.to_owned()))
+}
@@ -0,0 +1,145 @@
+use crate::error::*;
+use clap::Parser;
+use crossbundle_tools::{
+    commands::android::*,
+    types::{AndroidSdk, Config},
+};
+use std::path::PathBuf;
+
+#[derive(Parser, Clone, Debug)]
+pub enum DeviceProfileCommand {
+    /// Saves the current device state and applies the profile
+    Apply(DeviceProfileApplyCommand),
+    /// Restores the device state saved by `apply`
+    Restore(DeviceProfileRestoreCommand),
+}
+
+impl DeviceProfileCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        match self {
+            Self::Apply(cmd) => cmd.run(config),
+            Self::Restore(cmd) => cmd.run(config),
+        }
+    }
+}
+
+/// Device state applied before tests to make them deterministic.
+#[derive(Parser, Clone, Debug, Default)]
+pub struct DeviceProfileArgs {
+    /// Turn off window, transition and animator animations
+    #[clap(long)]
+    pub disable_animations: bool,
+    /// Set the time zone, e.g. `UTC` or `Europe/Berlin`
+    #[clap(long)]
+    pub timezone: Option<String>,
+    /// Set the device time in `YYYY-MM-DDTHH:MM:SSZ` format
+    #[clap(long)]
+    pub fixed_time: Option<String>,
+    /// Enable demo mode status bar: fixed clock, full battery and signal, no
+    /// notification icons
+    #[clap(long)]
+    pub demo_mode: bool,
+    /// Turn airplane mode on or off
+    #[clap(long)]
+    pub airplane_mode: Option<bool>,
+}
+
+impl DeviceProfileArgs {
+    /// Returns the profile or `None` if no options are specified.
+    pub fn to_profile(&self) -> Option<DeviceProfile> {
+        let profile = DeviceProfile {
+            disable_animations: self.disable_animations,
+            timezone: self.timezone.clone(),
+            time: self.fixed_time.clone(),
+            demo_mode: self.demo_mode,
+            airplane_mode: self.airplane_mode,
+        };
+        Some(profile).filter(|profile| !profile.is_empty())
+    }
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct DeviceProfileApplyCommand {
+    /// Serial of the Android device or emulator. Required if several devices are
+    /// connected
+    #[clap(long, short = 'd')]
+    pub device: Option<String>,
+    #[clap(flatten)]
+    pub profile: DeviceProfileArgs,
+}
+
+impl DeviceProfileApplyCommand {
+    pub fn run(&self, config: &Config) -> Result<()> {
+        let profile = self.profile.to_profile().ok_or_else(|| {
+            Error::InvalidMetadata(anyhow::anyhow!("no device profile options specified"))
+        })?;
+        let sdk = AndroidSdk::from_env()?;
+        let device = select_android_device(&sdk, self.device.as_deref())?;
+        let snapshot_path = snapshot_path(&device)?;
+        // Keep the state saved by the first `apply` so `restore` returns to it
+        if !snapshot_path.exists() {
+            DeviceStateSnapshot::capture(&sdk, Some(&device))?.save(&snapshot_path)?;
+        }
+        apply_device_profile(&sdk, Some(&device), &profile)?;
+        config.status_message("Applied", format!("device profile on {}", device))?;
+        Ok(())
+    }
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct DeviceProfileRestoreCommand {
+    /// Serial of the Android device or emulator. Required if several devices are
+    /// connected
+    #[clap(long, short = 'd')]
+    pub device: Option<String>,
+}
+
+impl DeviceProfileRestoreCommand {
+    pub fn run(&self, config: &Config) -> Result<()> {
+        let sdk = AndroidSdk::from_env()?;
+        let device = select_android_device(&sdk, self.device.as_deref())?;
+        let snapshot_path = snapshot_path(&device)?;
+        if !snapshot_path.exists() {
+            return Err(Error::PathNotFound(snapshot_path));
+        }
+        DeviceStateSnapshot::read(&snapshot_path)?.restore(&sdk, Some(&device))?;
+        std::fs::remove_file(&snapshot_path)?;
+        config.status_message("Restored", format!("device state of {}", device))?;
+        Ok(())
+    }
+}
+
+/// Returns path of the device state saved by `apply` in
+/// `~/.crossbundle/device_state/<serial>.json`.
+fn snapshot_path(device: &str) -> Result<PathBuf> {
+    let file_name = device.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "_");
+    Ok(dirs::home_dir()
+        .ok_or(Error::HomeDirNotFound)?
+        .join(".crossbundle")
+        .join("device_state")
+        .join(format!("{}.json", file_name)))
+}
+
+/// Applies the profile, runs `f` and restores the device state even if `f` failed.
+pub fn with_device_profile<T>(
+    config: &Config,
+    sdk: &AndroidSdk,
+    device: &str,
+    profile: Option<DeviceProfile>,
+    f: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    let profile = match profile {
+        Some(profile) => profile,
+        None => return f(),
+    };
+    let snapshot = DeviceStateSnapshot::capture(sdk, Some(device))?;
+    config.status_message("Applying", format!("device profile on {}", device))?;
+    let result = apply_device_profile(sdk, Some(device), &profile)
+        .map_err(Error::from)
+        .and_then(|_| f());
+    let restored = snapshot.restore(sdk, Some(device));
+    let value = result?;
+    restored?;
+    config.status_message("Restored", format!("device state of {}", device))?;
+    Ok(value)
+}
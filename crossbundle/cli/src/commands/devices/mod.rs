@@ -0,0 +1,155 @@
+use crate::error::*;
+use clap::Parser;
+use crossbundle_tools::types::Config;
+
+#[derive(Parser, Clone, Debug)]
+pub struct DevicesCommand {
+    /// Show only Android devices and emulators
+    #[clap(long, conflicts_with = "apple")]
+    pub android: bool,
+    /// Show only Apple simulators and connected devices
+    #[clap(long)]
+    pub apple: bool,
+    #[clap(subcommand)]
+    pub subcommand: Option<DevicesSubcommand>,
+}
+
+#[derive(Parser, Clone, Debug)]
+pub enum DevicesSubcommand {
+    /// Pairs the Android device with Wireless debugging enabled. The address and the
+    /// pairing code are shown in `Pair device with pairing code` dialog of the device
+    #[cfg(feature = "android")]
+    Pair {
+        /// Pairing address of the device, e.g. `192.168.1.5:37215`
+        address: String,
+        /// Six-digit pairing code
+        code: String,
+    },
+    /// Connects to the paired Android device over WiFi and remembers it, so `run` and
+    /// `install` reconnect to it automatically
+    #[cfg(feature = "android")]
+    Connect {
+        /// Address of the device from Wireless debugging settings, e.g.
+        /// `192.168.1.5:41005`
+        address: String,
+    },
+    /// Disconnects the Android device connected over WiFi
+    #[cfg(feature = "android")]
+    Disconnect {
+        /// Address of the connected device
+        address: String,
+        /// Don't reconnect to the device automatically anymore
+        #[clap(long)]
+        forget: bool,
+    },
+}
+
+impl DevicesCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        #[cfg(feature = "android")]
+        if let Some(subcommand) = &self.subcommand {
+            return Self::handle_wireless(config, subcommand);
+        }
+        #[cfg(feature = "android")]
+        if !self.apple {
+            if let Err(err) = Self::print_android_devices(config) {
+                config
+                    .shell()
+                    .warn(format!("Failed to list Android devices: {}", err))?;
+            }
+        }
+        #[cfg(feature = "apple")]
+        if !self.android && cfg!(target_os = "macos") {
+            if let Err(err) = Self::print_apple_devices(config) {
+                config
+                    .shell()
+                    .warn(format!("Failed to list Apple devices: {}", err))?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "android")]
+    fn handle_wireless(config: &Config, subcommand: &DevicesSubcommand) -> Result<()> {
+        use crossbundle_tools::{commands::android::*, types::AndroidSdk};
+
+        let sdk = AndroidSdk::from_env()?;
+        let store = PairedDevices::from_home_dir()?;
+        match subcommand {
+            DevicesSubcommand::Pair { address, code } => {
+                adb_pair(&sdk, address, code)?;
+                config.status_message("Paired", address)?;
+                config.shell().note(
+                    "Connect to the device with the address from Wireless debugging settings",
+                )?;
+            }
+            DevicesSubcommand::Connect { address } => {
+                adb_connect(&sdk, address)?;
+                store.remember(address)?;
+                config.status_message("Connected to", address)?;
+            }
+            DevicesSubcommand::Disconnect { address, forget } => {
+                adb_disconnect(&sdk, address)?;
+                if *forget {
+                    store.forget(address)?;
+                }
+                config.status_message("Disconnected", address)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "android")]
+    fn print_android_devices(config: &Config) -> Result<()> {
+        use crossbundle_tools::{commands::android::android_devices, types::AndroidSdk};
+
+        let sdk = AndroidSdk::from_env()?;
+        let devices = android_devices(&sdk)?;
+        config.shell().status_header("Android devices")?;
+        if devices.is_empty() {
+            config
+                .shell()
+                .note("No Android devices or emulators connected")?;
+        }
+        for device in devices {
+            let online = device.is_online();
+            let mut details = vec![
+                device.model.unwrap_or_else(|| "unknown model".to_owned()),
+                device.connection.to_string(),
+            ];
+            if let Some(api_level) = device.api_level {
+                details.push(format!("API {}", api_level));
+            }
+            if let Some(abi) = device.abi {
+                details.push(abi);
+            }
+            if !online {
+                details.push(device.state);
+            }
+            config.status_message(device.serial, details.join(", "))?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "apple")]
+    fn print_apple_devices(config: &Config) -> Result<()> {
+        use crossbundle_tools::commands::apple::{apple_connected_devices, apple_simulators};
+
+        let mut devices = apple_connected_devices().unwrap_or_default();
+        devices.extend(apple_simulators()?);
+        config.shell().status_header("Apple devices")?;
+        for device in devices {
+            let kind = if device.simulator {
+                "simulator"
+            } else {
+                "device"
+            };
+            let mut details = vec![device.name, kind.to_owned(), device.state];
+            if let Some(os_version) = device.os_version {
+                details.push(os_version);
+            }
+            config.status_message(device.udid, details.join(", "))?;
+        }
+        Ok(())
+    }
+}
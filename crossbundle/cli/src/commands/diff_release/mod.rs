@@ -0,0 +1,179 @@
+use crate::{commands::build::BuildContext, error::*};
+use clap::Parser;
+use crossbundle_tools::{
+    commands::{android::*, find_workspace_cargo_manifest_path, ByteSize},
+    error::AndroidError,
+    types::{AndroidSdk, Config},
+};
+use std::path::{Path, PathBuf};
+
+#[derive(Parser, Clone, Debug)]
+pub struct DiffReleaseCommand {
+    /// New APK or AAB to compare with the published release
+    pub artifact: PathBuf,
+    /// Published APK or AAB. By default the latest archived release with the same
+    /// extension is used
+    #[clap(long)]
+    pub previous: Option<PathBuf>,
+    /// Don't fail if the new build adds permissions
+    #[clap(long)]
+    pub allow_new_permissions: bool,
+    /// Archive the artifact as the published release after the comparison
+    #[clap(long)]
+    pub archive: bool,
+    /// Number of archive entries with the largest size changes to print
+    #[clap(long, default_value = "10")]
+    pub top: usize,
+    /// Directory for generated artifact and intermediate files
+    #[clap(long)]
+    pub target_dir: Option<PathBuf>,
+}
+
+impl DiffReleaseCommand {
+    /// Reports manifest, permission, signature and size differences between the
+    /// published release and the new build. Fails if permissions are added.
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        let sdk = AndroidSdk::from_env()?;
+        let target_dir = match &self.target_dir {
+            Some(target_dir) => target_dir.clone(),
+            None => find_workspace_cargo_manifest_path(config.current_dir())?
+                .parent()
+                .unwrap()
+                .join("target"),
+        };
+        let storage = BuildContext::artifact_storage(config, &target_dir)?;
+        let new = ArtifactSummary::read(&sdk, &self.artifact)?;
+        let extension = self
+            .artifact
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("apk");
+        let previous_path = match &self.previous {
+            Some(previous) => Some(previous.clone()),
+            None => latest_released_artifact(storage.as_ref(), &target_dir, extension)?,
+        };
+        match previous_path {
+            Some(previous_path) => {
+                config.status_message("Comparing with", previous_path.display())?;
+                let previous = ArtifactSummary::read(&sdk, &previous_path)?;
+                let mut diff = ReleaseDiff::new(&previous, &new);
+                let is_apk = |path: &Path| path.extension().map_or(false, |ext| ext == "apk");
+                // aapt2 compares resource tables of the same package only
+                if is_apk(previous_path.as_path())
+                    && is_apk(self.artifact.as_path())
+                    && previous.package == new.package
+                {
+                    diff.resources = Aapt2Diff::new(&previous_path, &self.artifact).run(&sdk)?;
+                }
+                self.report(config, &diff)?;
+            }
+            None => config.shell().warn(
+                "No published release found, pass it with `--previous` or archive it with \
+                 `--archive`",
+            )?,
+        };
+        if self.archive {
+            let release = release_id(new.version_name.as_deref(), new.version_code);
+            let location = archive_release(storage.as_ref(), &release, &self.artifact)?;
+            config.status_message("Archived release", location)?;
+        }
+        Ok(())
+    }
+
+    fn report(&self, config: &Config, diff: &ReleaseDiff) -> Result<()> {
+        println!("Manifest:");
+        if diff.manifest.is_empty() {
+            println!("    no changes");
+        }
+        for (name, previous, new) in &diff.manifest {
+            println!("    {}: {} -> {}", name, previous, new);
+        }
+        println!("Permissions:");
+        if diff.added_permissions.is_empty() && diff.removed_permissions.is_empty() {
+            println!("    no changes");
+        }
+        for permission in &diff.added_permissions {
+            println!("    + {}", permission);
+        }
+        for permission in &diff.removed_permissions {
+            println!("    - {}", permission);
+        }
+        println!("Features:");
+        if diff.added_features.is_empty() && diff.removed_features.is_empty() {
+            println!("    no changes");
+        }
+        for feature in &diff.added_features {
+            println!("    + {}", feature);
+        }
+        for feature in &diff.removed_features {
+            println!("    - {}", feature);
+        }
+        println!("Signature:");
+        match &diff.signer {
+            Some((previous, new)) => println!("    certificate changed: {} -> {}", previous, new),
+            None => println!("    no changes"),
+        }
+        let (previous_size, new_size) = diff.size;
+        println!(
+            "Size: {} -> {} ({})",
+            ByteSize(previous_size),
+            ByteSize(new_size),
+            signed_size(new_size as i64 - previous_size as i64)
+        );
+        let mut entries = diff.entries.clone();
+        entries.sort_by_key(|(_, previous, new)| {
+            std::cmp::Reverse((*new as i64 - *previous as i64).abs())
+        });
+        for (path, previous, new) in entries.iter().take(self.top) {
+            println!(
+                "    {} {}",
+                signed_size(*new as i64 - *previous as i64),
+                path
+            );
+        }
+        if !diff.resources.is_empty() {
+            let count = |kind| {
+                diff.resources
+                    .iter()
+                    .filter(|resource| resource.kind == kind)
+                    .count()
+            };
+            println!(
+                "Resources: {} added, {} removed, {} changed",
+                count(ResourceChangeKind::Added),
+                count(ResourceChangeKind::Removed),
+                count(ResourceChangeKind::Changed)
+            );
+            for resource in diff.resources.iter().take(self.top) {
+                let sign = match resource.kind {
+                    ResourceChangeKind::Added => "+",
+                    ResourceChangeKind::Removed => "-",
+                    ResourceChangeKind::Changed => "~",
+                };
+                match &resource.config {
+                    Some(config) if !config.is_empty() => {
+                        println!("    {} {} [{}]", sign, resource.resource, config)
+                    }
+                    _ => println!("    {} {}", sign, resource.resource),
+                }
+            }
+        }
+        if diff.signer.is_some() {
+            config
+                .shell()
+                .warn("The signing certificate changed, the store will reject the update")?;
+        }
+        if !diff.added_permissions.is_empty() && !self.allow_new_permissions {
+            return Err(Error::CrossbundleTools(
+                AndroidError::NewPermissions(diff.added_permissions.join(", ")).into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Formats the size change with a sign, e.g. `+1.50 MB`.
+fn signed_size(change: i64) -> String {
+    let sign = if change < 0 { "-" } else { "+" };
+    format!("{}{}", sign, ByteSize(change.unsigned_abs()))
+}
@@ -0,0 +1,56 @@
+use crate::{commands::build::BuildContext, error::*};
+use clap::Parser;
+use crossbundle_tools::{
+    commands::{doctor, CheckStatus, DoctorReport},
+    types::Config,
+};
+
+#[derive(Parser, Clone, Debug)]
+pub struct DoctorCommand {
+    /// Print the report as JSON to stdout. Every check has a stable `code` and a
+    /// `severity` (ok, warning or error)
+    #[clap(long)]
+    pub json: bool,
+}
+
+impl DoctorCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        let (ndk_version, min_ndk_version) = BuildContext::ndk_versions(config)?;
+        if self.json {
+            let report = doctor(ndk_version.as_deref(), min_ndk_version.as_deref());
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            return Self::check_failed(&report);
+        }
+        config.status("Checking environment")?;
+        let report = doctor(ndk_version.as_deref(), min_ndk_version.as_deref());
+        for check in &report.checks {
+            match check.status {
+                CheckStatus::Ok => config.status_message(&check.name, &check.details)?,
+                CheckStatus::Warning => config
+                    .shell()
+                    .warn(format!("{}: {}", check.name, check.details))?,
+                CheckStatus::Error => config
+                    .shell()
+                    .error(format!("{}: {}", check.name, check.details))?,
+            }
+            if let Some(fix) = &check.fix {
+                config.shell().note(fix)?;
+            }
+        }
+        Self::check_failed(&report)?;
+        config.status("Environment is ready")?;
+        Ok(())
+    }
+
+    fn check_failed(report: &DoctorReport) -> Result<()> {
+        let failed = report
+            .checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Error)
+            .count();
+        if failed > 0 {
+            return Err(Error::DoctorChecksFailed(failed));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,289 @@
+use crate::{
+    commands::build::{android::AndroidBuildCommand, BuildContext},
+    error::Result,
+};
+use clap::Parser;
+use crossbundle_tools::{
+    commands::android::*,
+    types::{AndroidSdk, AndroidStrategy, Config},
+};
+use std::{collections::BTreeMap, time::Duration};
+
+#[derive(Parser, Clone, Debug)]
+pub enum EmulatorCommand {
+    /// Creates an AVD for the minimum SDK version of the project and the ABI of the host
+    Create(EmulatorCreateCommand),
+    /// Boots the AVD and waits until it's ready
+    Start(EmulatorStartCommand),
+    /// Stops the running emulator
+    Stop(EmulatorStopCommand),
+    /// Saves, loads, deletes and lists snapshots of the AVD
+    #[clap(subcommand)]
+    Snapshot(EmulatorSnapshotCommand),
+    /// Lists created AVDs
+    List,
+    /// Checks whether the emulator can use hardware acceleration: KVM, HVF or WHPX
+    CheckAccel,
+}
+
+impl EmulatorCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        match self {
+            Self::Create(cmd) => cmd.run(config),
+            Self::Start(cmd) => cmd.run(config).map(|_| ()),
+            Self::Stop(cmd) => cmd.run(config),
+            Self::Snapshot(cmd) => cmd.run(config),
+            Self::List => {
+                let sdk = AndroidSdk::from_env()?;
+                for avd in Emulator::new(&sdk)?.list_avds()? {
+                    println!("{}", avd);
+                }
+                Ok(())
+            }
+            Self::CheckAccel => {
+                let sdk = AndroidSdk::from_env()?;
+                let acceleration = check_emulator_acceleration(&sdk)?;
+                acceleration.require()?;
+                config.status_message(
+                    format!("{} is available", acceleration.hypervisor),
+                    &acceleration.details,
+                )?;
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Parser, Clone, Debug, Default)]
+pub struct EmulatorCreateCommand {
+    /// Name of the AVD. By default, `crossbow_<api level>_<abi>`
+    #[clap(long, short)]
+    pub name: Option<String>,
+    /// API level of the system image. By default, `minSdkVersion` from the project
+    /// manifest is used
+    #[clap(long)]
+    pub api_level: Option<u32>,
+    /// ABI of the system image. By default, the ABI that runs without translation on
+    /// this host: `x86_64` or `arm64-v8a`
+    #[clap(long)]
+    pub abi: Option<String>,
+    /// Tag of the system image, e.g. `default`, `google_apis` or `google_apis_playstore`
+    #[clap(long, default_value = "google_apis")]
+    pub tag: String,
+    /// Hardware profile of the AVD, e.g. `pixel_6`. Can be found with
+    /// `avdmanager list device`
+    #[clap(long)]
+    pub device: Option<String>,
+    /// Overwrite the AVD if it already exists
+    #[clap(long, short)]
+    pub force: bool,
+}
+
+impl EmulatorCreateCommand {
+    /// Installs the system image if it's missing and creates the AVD.
+    pub fn run(&self, config: &Config) -> Result<()> {
+        let sdk = AndroidSdk::from_env()?;
+        let api_level = match self.api_level {
+            Some(api_level) => api_level,
+            None => project_min_sdk_version(config).unwrap_or(DEFAULT_PLATFORM),
+        };
+        let abi = self.abi.as_deref().unwrap_or_else(host_emulator_abi);
+        let package = system_image_package(api_level, &self.tag, abi);
+        if !is_system_image_installed(sdk.sdk_path(), api_level, &self.tag, abi) {
+            config.status_message("Installing", &package)?;
+            let mut sdkmanager = SdkManager::new(sdk.sdk_path())?;
+            sdkmanager.accept_licenses()?;
+            sdkmanager.package("emulator").package(&package).install()?;
+        }
+        let name = self
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("crossbow_{}_{}", api_level, abi.replace('-', "_")));
+        AvdManager::new(sdk.sdk_path())?.create(
+            &name,
+            &package,
+            self.device.as_deref(),
+            self.force,
+        )?;
+        config.status_message("Created AVD", &name)?;
+        Ok(())
+    }
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct EmulatorStartCommand {
+    /// Name of the AVD to start
+    pub avd: String,
+    /// Run the emulator without window, audio and boot animation
+    #[clap(long)]
+    pub headless: bool,
+    /// Console port of the emulator. By default, the first free port from 5554 is used
+    #[clap(long)]
+    pub port: Option<u16>,
+    /// Reset user data of the AVD
+    #[clap(long)]
+    pub wipe_data: bool,
+    /// Seconds to wait until the emulator finishes booting
+    #[clap(long, default_value = "300")]
+    pub timeout: u64,
+    /// Boot from the named snapshot if it was saved before. Otherwise the AVD is cold
+    /// booted
+    #[clap(long)]
+    pub snapshot: Option<String>,
+    /// Don't save the state into the quick boot snapshot on exit, so every run starts
+    /// from the same state
+    #[clap(long)]
+    pub no_snapshot_save: bool,
+    /// Emulator profile from `package.metadata.android.emulator_profiles` or the
+    /// built-in `ci` and `host` profiles
+    #[clap(long)]
+    pub profile: Option<String>,
+    /// GPU emulation mode: `auto`, `host`, `swiftshader_indirect`, `angle_indirect` or
+    /// `guest`. Overrides the mode of the profile
+    #[clap(long)]
+    pub gpu: Option<EmulatorGpu>,
+}
+
+impl EmulatorStartCommand {
+    /// Starts the AVD unless it's already running and returns serial of the emulator.
+    pub fn run(&self, config: &Config) -> Result<String> {
+        let sdk = AndroidSdk::from_env()?;
+        if let Some(serial) = running_emulator(&sdk, &self.avd)? {
+            config.status_message("Emulator is already running", &serial)?;
+            return Ok(serial);
+        }
+        let mut emulator = Emulator::new(&sdk)?;
+        emulator.headless(self.headless).wipe_data(self.wipe_data);
+        let profile = match &self.profile {
+            Some(name) => EmulatorProfile::find(&project_emulator_profiles(config), name)?,
+            None => EmulatorProfile::default(),
+        };
+        emulator.profile(&profile);
+        if let Some(gpu) = self.gpu {
+            emulator.gpu(gpu);
+        }
+        // Without acceleration the emulator exits right away or never finishes booting,
+        // so fail early with the fix
+        if profile.accel != Some(EmulatorAccel::Off) {
+            check_emulator_acceleration(&sdk)?.require()?;
+        }
+        if let Some(port) = self.port {
+            emulator.port(port);
+        }
+        emulator.no_snapshot_save(self.no_snapshot_save);
+        if let Some(snapshot) = &self.snapshot {
+            if avd_snapshots(&self.avd)?.contains(snapshot) {
+                emulator.snapshot(snapshot);
+            } else {
+                config.shell().note(format!(
+                    "Snapshot `{}` doesn't exist yet, the AVD is cold booted",
+                    snapshot
+                ))?;
+            }
+        }
+        let (mut process, serial) = emulator.start(&sdk, &self.avd)?;
+        config.status_message(format!("Booting {} as", self.avd), &serial)?;
+        wait_for_boot(
+            &sdk,
+            &serial,
+            Some(&mut process),
+            Duration::from_secs(self.timeout),
+        )?;
+        config.status_message("Emulator is ready", &serial)?;
+        Ok(serial)
+    }
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct EmulatorStopCommand {
+    /// Serial of the emulator, e.g. `emulator-5554`, or name of the AVD
+    pub emulator: String,
+}
+
+impl EmulatorStopCommand {
+    pub fn run(&self, config: &Config) -> Result<()> {
+        let sdk = AndroidSdk::from_env()?;
+        let serial = emulator_serial(&sdk, &self.emulator)?;
+        kill_emulator(&sdk, &serial)?;
+        config.status_message("Stopped emulator", &serial)?;
+        Ok(())
+    }
+}
+
+#[derive(Parser, Clone, Debug)]
+pub enum EmulatorSnapshotCommand {
+    /// Saves the state of the running emulator into the snapshot
+    Save {
+        /// Serial of the emulator or name of the AVD
+        emulator: String,
+        /// Name of the snapshot
+        name: String,
+    },
+    /// Restores the state of the running emulator from the snapshot
+    Load {
+        /// Serial of the emulator or name of the AVD
+        emulator: String,
+        /// Name of the snapshot
+        name: String,
+    },
+    /// Deletes the snapshot of the running emulator
+    Delete {
+        /// Serial of the emulator or name of the AVD
+        emulator: String,
+        /// Name of the snapshot
+        name: String,
+    },
+    /// Lists saved snapshots of the AVD
+    List {
+        /// Name of the AVD
+        avd: String,
+    },
+}
+
+impl EmulatorSnapshotCommand {
+    pub fn run(&self, config: &Config) -> Result<()> {
+        if let Self::List { avd } = self {
+            for snapshot in avd_snapshots(avd)? {
+                println!("{}", snapshot);
+            }
+            return Ok(());
+        }
+        let sdk = AndroidSdk::from_env()?;
+        match self {
+            Self::Save { emulator, name } => {
+                save_snapshot(&sdk, &emulator_serial(&sdk, emulator)?, name)?;
+                config.status_message("Saved snapshot", name)?;
+            }
+            Self::Load { emulator, name } => {
+                load_snapshot(&sdk, &emulator_serial(&sdk, emulator)?, name)?;
+                config.status_message("Loaded snapshot", name)?;
+            }
+            Self::Delete { emulator, name } => {
+                delete_snapshot(&sdk, &emulator_serial(&sdk, emulator)?, name)?;
+                config.status_message("Deleted snapshot", name)?;
+            }
+            Self::List { .. } => {}
+        }
+        Ok(())
+    }
+}
+
+/// Returns serial of the running emulator of the AVD or the given serial.
+fn emulator_serial(sdk: &AndroidSdk, emulator: &str) -> Result<String> {
+    Ok(running_emulator(sdk, emulator)?.unwrap_or_else(|| emulator.to_owned()))
+}
+
+/// Returns emulator profiles of the project in the current directory.
+fn project_emulator_profiles(config: &Config) -> BTreeMap<String, EmulatorProfile> {
+    BuildContext::new(config, None)
+        .map(|context| context.config.android.emulator_profiles)
+        .unwrap_or_default()
+}
+
+/// Returns `minSdkVersion` of the project in the current directory.
+fn project_min_sdk_version(config: &Config) -> Option<u32> {
+    let context = BuildContext::new(config, None).ok()?;
+    let manifest =
+        AndroidBuildCommand::get_android_manifest(&context, AndroidStrategy::NativeApk).ok()?;
+    Some(AndroidBuildCommand::min_sdk_version(&manifest))
+}
@@ -0,0 +1,102 @@
+use super::{command_line_tools::CommandLineToolsInstallCommand, DownloadOptions};
+use crate::{
+    commands::build::{android::AndroidBuildCommand, BuildContext},
+    error::Result,
+};
+use clap::Parser;
+use crossbundle_tools::{
+    commands::android::*,
+    types::{android_sdk_path, AndroidStrategy, Config},
+};
+use std::path::PathBuf;
+
+#[derive(Parser, Clone, Debug, Default)]
+pub struct AndroidInstallCommand {
+    /// Android platform to install. By default, `targetSdkVersion` from the project
+    /// manifest is used
+    #[clap(long)]
+    pub platform: Option<u32>,
+    /// Build tools version to install. By default, `build_tools_version` from the
+    /// project metadata is used
+    #[clap(long)]
+    pub build_tools_version: Option<String>,
+    /// NDK version to install. By default, `ndk_version` from the project metadata is
+    /// used
+    #[clap(long)]
+    pub ndk_version: Option<String>,
+    /// Path to the Android SDK. By default, `ANDROID_SDK_ROOT` or the default SDK
+    /// installation path is used
+    #[clap(long)]
+    pub sdk_root: Option<PathBuf>,
+    /// Force install command line tools even if found or corrupted
+    #[clap(long, short)]
+    pub force: bool,
+    /// Number of parallel connections used to download command line tools
+    #[clap(long)]
+    pub connections: Option<usize>,
+    /// Expected SHA-256 hash of the command line tools archive. Required on hosts
+    /// without a pinned hash
+    #[clap(long)]
+    pub sha256: Option<String>,
+}
+
+impl AndroidInstallCommand {
+    /// Downloads command line tools, accepts licenses and installs platform, build tools
+    /// and NDK required by the project
+    pub fn install(&self, config: &Config) -> Result<()> {
+        let sdk_root = match &self.sdk_root {
+            Some(sdk_root) => sdk_root.clone(),
+            None => android_sdk_path()?,
+        };
+        if find_sdkmanager(&sdk_root).is_err() || self.force {
+            CommandLineToolsInstallCommand {
+                install_path: Some(sdk_root.clone()),
+                force: self.force,
+                download: DownloadOptions {
+                    connections: self.connections,
+                    sha256: self.sha256.clone(),
+                },
+            }
+            .install(config)?;
+        }
+
+        let packages = self.required_packages(config);
+        let mut sdkmanager = SdkManager::new(&sdk_root)?;
+        config.status("Accepting Android SDK licenses")?;
+        sdkmanager.accept_licenses()?;
+        config.status_message("Installing", packages.join(", "))?;
+        sdkmanager.packages(&packages).install()?;
+        config.status_message("Android SDK installed into", sdk_root.display())?;
+        Ok(())
+    }
+
+    /// Resolves required packages from command line arguments, project metadata or
+    /// defaults.
+    fn required_packages(&self, config: &Config) -> Vec<String> {
+        // Installation should work outside of a project as well
+        let context = BuildContext::new(config, None).ok();
+        let metadata = context.as_ref().map(|context| &context.config.android);
+        let platform = self.platform.unwrap_or_else(|| {
+            context
+                .as_ref()
+                .and_then(|context| {
+                    AndroidBuildCommand::get_android_manifest(context, AndroidStrategy::NativeApk)
+                        .ok()
+                })
+                .and_then(|manifest| manifest.uses_sdk)
+                .and_then(|uses_sdk| uses_sdk.target_sdk_version)
+                .unwrap_or(DEFAULT_PLATFORM)
+        });
+        let build_tools_version = self
+            .build_tools_version
+            .clone()
+            .or_else(|| metadata.and_then(|m| m.build_tools_version.clone()))
+            .unwrap_or_else(|| DEFAULT_BUILD_TOOLS_VERSION.to_owned());
+        let ndk_version = self
+            .ndk_version
+            .clone()
+            .or_else(|| metadata.and_then(|m| m.ndk_version.clone()))
+            .unwrap_or_else(|| DEFAULT_NDK_VERSION.to_owned());
+        required_sdk_packages(platform, &build_tools_version, &ndk_version)
+    }
+}
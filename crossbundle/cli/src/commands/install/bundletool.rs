@@ -6,6 +6,9 @@ use std::path::PathBuf;
 
 const BUNDLETOOL_JAR_FILE_DOWNLOAD_URL: &str =
     "https://github.com/google/bundletool/releases/download";
+/// SHA-256 hashes of the bundletool jars published on the releases page. Versions
+/// without a pinned hash are only downloaded if `--sha256` is passed.
+const BUNDLETOOL_SHA256: &[(&str, &str)] = &[];
 
 #[derive(Parser, Clone, Debug, Default)]
 pub struct BundletoolInstallCommand {
@@ -20,6 +23,8 @@ pub struct BundletoolInstallCommand {
     /// Force install bundletool even if found.
     #[clap(long, short)]
     pub force: bool,
+    #[clap(flatten)]
+    pub download: DownloadOptions,
 }
 
 impl BundletoolInstallCommand {
@@ -42,6 +47,7 @@ impl BundletoolInstallCommand {
             .join(self.version.clone())
             .join(self.file_name());
         let download_url_str = String::from(download_url.to_str().unwrap());
+        let pinned = pinned_sha256(BUNDLETOOL_SHA256, &self.file_name());
 
         if let Some(install_path) = &self.path {
             config.status_message(
@@ -49,14 +55,16 @@ impl BundletoolInstallCommand {
                 install_path.to_string_lossy(),
             )?;
             let jar_path = install_path.join(self.file_name());
-            download_to_file(&download_url_str, &jar_path)?;
+            self.download
+                .download_to_file(config, &download_url_str, &jar_path, pinned)?;
         } else {
             config.status_message(
                 format!("{} installing into", self.file_name()),
                 home_dir.to_string_lossy(),
             )?;
             let default_jar_path = default_file_path(self.file_name())?;
-            download_to_file(&download_url_str, &default_jar_path)?;
+            self.download
+                .download_to_file(config, &download_url_str, &default_jar_path, pinned)?;
         };
         config.status("Bundletool was installed successfully")?;
         Ok(())
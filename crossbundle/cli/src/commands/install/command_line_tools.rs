@@ -13,6 +13,12 @@ const OS_TAG: &str = "mac";
 const OS_TAG: &str = "linux";
 
 const COMMAND_LINE_TOOLS_DOWNLOAD_URL: &str = "https://dl.google.com/android/repository/";
+/// SHA-256 hashes of the command line tools archives published by Google. Archives
+/// without a pinned hash are only downloaded if `--sha256` is passed.
+const COMMAND_LINE_TOOLS_SHA256: &[(&str, &str)] = &[(
+    "commandlinetools-linux-8512546_latest.zip",
+    "2ccbda4302db862a28ada25aa7425d99dce9462046003c1714b059b5c47970d8",
+)];
 
 #[derive(Parser, Clone, Debug, Default)]
 pub struct CommandLineToolsInstallCommand {
@@ -22,6 +28,8 @@ pub struct CommandLineToolsInstallCommand {
     /// Force install command line tools even if found or corrupted.
     #[clap(long, short)]
     pub force: bool,
+    #[clap(flatten)]
+    pub download: DownloadOptions,
 }
 
 impl CommandLineToolsInstallCommand {
@@ -48,7 +56,7 @@ impl CommandLineToolsInstallCommand {
             format!("Downloading {} into", self.file_name()),
             file_path.parent().unwrap().to_str().unwrap(),
         )?;
-        self.download_and_save_file(command_line_tools_download_url, &file_path)?;
+        self.download_and_save_file(config, command_line_tools_download_url, &file_path)?;
 
         if let Some(path) = &self.install_path {
             config.status_message(
@@ -77,7 +85,12 @@ impl CommandLineToolsInstallCommand {
 
     /// Check home directory for zip file. If it doesn't exists download zip file and save
     /// it in the directory
-    pub fn download_and_save_file(&self, download_url: PathBuf, file_path: &Path) -> Result<()> {
+    pub fn download_and_save_file(
+        &self,
+        config: &Config,
+        download_url: PathBuf,
+        file_path: &Path,
+    ) -> Result<()> {
         remove(vec![file_path.to_path_buf()])?;
         for dir in std::fs::read_dir(file_path.parent().unwrap())? {
             let zip_path = dir?.path();
@@ -86,7 +99,9 @@ impl CommandLineToolsInstallCommand {
             }
         }
         let url = download_url.to_str().unwrap();
-        download_to_file(url, file_path)?;
+        let pinned = pinned_sha256(COMMAND_LINE_TOOLS_SHA256, &self.file_name());
+        self.download
+            .download_to_file(config, url, file_path, pinned)?;
         Ok(())
     }
 }
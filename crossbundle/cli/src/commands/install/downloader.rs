@@ -0,0 +1,498 @@
+use crate::error::*;
+use crossbundle_tools::{commands::sha256_file, types::Config};
+use std::{
+    collections::HashSet,
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+/// Number of parallel connections used by default.
+pub const DEFAULT_CONNECTIONS: usize = 4;
+/// Size of the range requested by a single connection.
+const CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+/// Number of attempts to download a single chunk.
+const CHUNK_ATTEMPTS: usize = 3;
+
+/// File to download with an optional expected SHA-256 hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Download {
+    url: String,
+    path: PathBuf,
+    sha256: Option<String>,
+}
+
+impl Download {
+    pub fn new(url: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            url: url.into(),
+            path: path.into(),
+            sha256: None,
+        }
+    }
+
+    /// Hex-encoded SHA-256 hash the downloaded file must match.
+    pub fn sha256(&mut self, sha256: Option<String>) -> &mut Self {
+        self.sha256 = sha256.map(|sha256| sha256.to_lowercase());
+        self
+    }
+
+    /// Partially downloaded file. Renamed to the destination after verification.
+    fn part_path(&self) -> PathBuf {
+        append_extension(&self.path, "part")
+    }
+
+    /// List of downloaded chunks of the partially downloaded file.
+    fn state_path(&self) -> PathBuf {
+        append_extension(&self.path, "part.state")
+    }
+}
+
+/// Downloads files in parallel chunks with HTTP range requests. Partially downloaded
+/// files are resumed from the last completed chunk on the next run.
+pub struct Downloader {
+    connections: usize,
+}
+
+impl Default for Downloader {
+    fn default() -> Self {
+        Self::new(DEFAULT_CONNECTIONS)
+    }
+}
+
+/// Download with its chunks resolved from the response of the server.
+struct PlannedDownload<'a> {
+    download: &'a Download,
+    /// `None` if server doesn't report the size or doesn't support range requests.
+    size: Option<u64>,
+    /// Sent with range requests, so chunks of a changed file aren't mixed with old ones.
+    etag: Option<String>,
+    chunks: Vec<(u64, u64)>,
+    completed: Mutex<HashSet<usize>>,
+}
+
+impl Downloader {
+    pub fn new(connections: usize) -> Self {
+        Self {
+            connections: connections.max(1),
+        }
+    }
+
+    /// Downloads files, shows aggregated progress and verifies their hashes.
+    pub fn download_all(&self, config: &Config, downloads: &[Download]) -> Result<()> {
+        let mut planned = Vec::new();
+        for download in downloads {
+            if let Some(parent) = download.path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            planned.push(plan(download)?);
+        }
+        let total: u64 = planned.iter().filter_map(|p| p.size).sum();
+        let downloaded = AtomicU64::new(
+            planned
+                .iter()
+                .map(|p| {
+                    let completed = p.completed.lock().unwrap();
+                    completed
+                        .iter()
+                        .map(|i| p.chunks[*i].1 - p.chunks[*i].0 + 1)
+                        .sum::<u64>()
+                })
+                .sum(),
+        );
+        let mut jobs = Vec::new();
+        for (file, p) in planned.iter().enumerate() {
+            let completed = p.completed.lock().unwrap();
+            if p.size.is_none() {
+                jobs.push((file, None));
+            } else {
+                for chunk in 0..p.chunks.len() {
+                    if !completed.contains(&chunk) {
+                        jobs.push((file, Some(chunk)));
+                    }
+                }
+            }
+        }
+        jobs.reverse();
+        let jobs = Mutex::new(jobs);
+        let failed = AtomicBool::new(false);
+        let finished = AtomicUsize::new(0);
+        let workers = self.connections.min(jobs.lock().unwrap().len());
+        let result = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let result = run_worker(&planned, &jobs, &downloaded, &failed);
+                        finished.fetch_add(1, Ordering::SeqCst);
+                        result
+                    })
+                })
+                .collect();
+            while finished.load(Ordering::SeqCst) < workers {
+                show_progress(config, downloaded.load(Ordering::Relaxed), total)?;
+                std::thread::sleep(Duration::from_millis(200));
+            }
+            config.shell().err_erase_line();
+            let mut result = Ok(());
+            for handle in handles {
+                if let Err(err) = handle.join().unwrap() {
+                    result = Err(err);
+                }
+            }
+            Ok::<_, Error>(result)
+        })?;
+        if let Err((url, cause)) = result {
+            return Err(Error::ChunkDownloadFailed { url, cause });
+        }
+        for p in &planned {
+            finish(p.download)?;
+        }
+        Ok(())
+    }
+}
+
+/// Requests the size of the file and splits it into chunks if the server supports
+/// range requests. Reads completed chunks of the previous run if the file on the server
+/// has the same size and ETag.
+fn plan(download: &Download) -> Result<PlannedDownload> {
+    let response = ureq::head(&download.url)
+        .call()
+        .map_err(Error::DownloadFailed)?;
+    let accepts_ranges = response.header("Accept-Ranges") == Some("bytes");
+    let size = response
+        .header("Content-Length")
+        .and_then(|len| len.parse::<u64>().ok())
+        .filter(|len| accepts_ranges && *len > 0);
+    let etag = response.header("ETag").map(String::from);
+    let chunks = match size {
+        Some(size) => split_into_chunks(size, CHUNK_SIZE),
+        None => Vec::new(),
+    };
+    let mut completed = HashSet::new();
+    if let Some(size) = size {
+        let part_path = download.part_path();
+        let state = std::fs::read_to_string(download.state_path()).unwrap_or_default();
+        let mut lines = state.lines();
+        let header = state_header(size, etag.as_deref());
+        if part_path.exists() && lines.next() == Some(header.as_str()) {
+            completed.extend(lines.filter_map(|line| line.parse::<usize>().ok()));
+            completed.retain(|chunk| *chunk < chunks.len());
+        } else {
+            let part = File::create(&part_path)?;
+            part.set_len(size)?;
+            std::fs::write(download.state_path(), format!("{}\n", header))?;
+        }
+    }
+    Ok(PlannedDownload {
+        download,
+        size,
+        etag,
+        chunks,
+        completed: Mutex::new(completed),
+    })
+}
+
+/// First line of the state file identifying the version of the file on the server.
+fn state_header(size: u64, etag: Option<&str>) -> String {
+    match etag {
+        Some(etag) => format!("{} {}", size, etag),
+        None => size.to_string(),
+    }
+}
+
+/// Splits file of the given size into inclusive byte ranges.
+fn split_into_chunks(size: u64, chunk_size: u64) -> Vec<(u64, u64)> {
+    (0..size)
+        .step_by(chunk_size as usize)
+        .map(|start| (start, (start + chunk_size).min(size) - 1))
+        .collect()
+}
+
+/// Takes jobs from the queue until it's empty or another worker failed.
+fn run_worker(
+    planned: &[PlannedDownload],
+    jobs: &Mutex<Vec<(usize, Option<usize>)>>,
+    downloaded: &AtomicU64,
+    failed: &AtomicBool,
+) -> std::result::Result<(), (String, std::io::Error)> {
+    loop {
+        if failed.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        let job = jobs.lock().unwrap().pop();
+        let (file, chunk) = match job {
+            Some(job) => job,
+            None => return Ok(()),
+        };
+        let p = &planned[file];
+        let result = match chunk {
+            Some(chunk) => download_chunk(p, chunk, downloaded),
+            None => download_whole(p.download, downloaded),
+        };
+        if let Err(err) = result {
+            failed.store(true, Ordering::SeqCst);
+            return Err((p.download.url.clone(), err));
+        }
+    }
+}
+
+/// Downloads the chunk into its range of the partial file and records it as completed.
+fn download_chunk(
+    p: &PlannedDownload,
+    chunk: usize,
+    downloaded: &AtomicU64,
+) -> std::io::Result<()> {
+    let (start, end) = p.chunks[chunk];
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let mut written = 0;
+        let result = (|| {
+            let mut request =
+                ureq::get(&p.download.url).set("Range", &format!("bytes={}-{}", start, end));
+            // The server sends the whole file instead if it changed since the first chunk
+            if let Some(etag) = &p.etag {
+                request = request.set("If-Range", etag);
+            }
+            let response = request.call().map_err(to_io_error)?;
+            if response.status() != 206 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    format!(
+                        "server ignored range request with status {}",
+                        response.status()
+                    ),
+                ));
+            }
+            let mut part = OpenOptions::new()
+                .write(true)
+                .open(p.download.part_path())?;
+            part.seek(SeekFrom::Start(start))?;
+            let mut reader = response.into_reader().take(end - start + 1);
+            copy_with_progress(&mut reader, &mut part, downloaded, &mut written)?;
+            if written != end - start + 1 {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+            part.sync_data()
+        })();
+        match result {
+            Ok(()) => break,
+            Err(err) => {
+                downloaded.fetch_sub(written, Ordering::Relaxed);
+                if attempt >= CHUNK_ATTEMPTS {
+                    return Err(err);
+                }
+            }
+        }
+    }
+    let mut completed = p.completed.lock().unwrap();
+    completed.insert(chunk);
+    let mut state = OpenOptions::new()
+        .append(true)
+        .open(p.download.state_path())?;
+    writeln!(state, "{}", chunk)
+}
+
+/// Downloads the file with a single request if the server doesn't support ranges.
+fn download_whole(download: &Download, downloaded: &AtomicU64) -> std::io::Result<()> {
+    let response = ureq::get(&download.url).call().map_err(to_io_error)?;
+    let mut part = File::create(download.part_path())?;
+    copy_with_progress(&mut response.into_reader(), &mut part, downloaded, &mut 0)?;
+    part.sync_data()
+}
+
+fn copy_with_progress(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    downloaded: &AtomicU64,
+    written: &mut u64,
+) -> std::io::Result<()> {
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let len = match reader.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(len) => len,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        writer.write_all(&buf[..len])?;
+        *written += len as u64;
+        downloaded.fetch_add(len as u64, Ordering::Relaxed);
+    }
+}
+
+/// Verifies hash of the partial file and moves it to the destination.
+fn finish(download: &Download) -> Result<()> {
+    let part_path = download.part_path();
+    if let Some(expected) = &download.sha256 {
+        let actual = sha256_file(&part_path)?;
+        if &actual != expected {
+            std::fs::remove_file(&part_path)?;
+            let _ = std::fs::remove_file(download.state_path());
+            return Err(Error::ChecksumMismatch {
+                path: download.path.clone(),
+                expected: expected.clone(),
+                actual,
+            });
+        }
+    }
+    std::fs::rename(&part_path, &download.path)?;
+    let _ = std::fs::remove_file(download.state_path());
+    Ok(())
+}
+
+/// Redraws the aggregated progress bar of all downloads.
+fn show_progress(config: &Config, downloaded: u64, total: u64) -> Result<()> {
+    let mut shell = config.shell();
+    if !shell.is_err_tty() {
+        return Ok(());
+    }
+    let line = if total > 0 {
+        let width = 30;
+        let filled = (downloaded.min(total) * width / total) as usize;
+        format!(
+            "[{}{}] {:>3}% {}/{} MiB",
+            "=".repeat(filled),
+            " ".repeat(width as usize - filled),
+            downloaded.min(total) * 100 / total,
+            downloaded / (1024 * 1024),
+            total / (1024 * 1024)
+        )
+    } else {
+        format!("{} MiB", downloaded / (1024 * 1024))
+    };
+    let status = format!("{:>12} {}\r", "Downloading", line);
+    shell.err().write_all(status.as_bytes())?;
+    shell.err().flush()?;
+    shell.set_needs_clear(true);
+    Ok(())
+}
+
+fn append_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    PathBuf::from(path)
+}
+
+fn to_io_error(err: ureq::Error) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_into_chunks() {
+        assert_eq!(split_into_chunks(10, 4), vec![(0, 3), (4, 7), (8, 9)]);
+        assert_eq!(split_into_chunks(8, 4), vec![(0, 3), (4, 7)]);
+        assert_eq!(
+            append_extension(Path::new("/tmp/ndk.zip"), "part"),
+            PathBuf::from("/tmp/ndk.zip.part")
+        );
+    }
+
+    /// Serves the body with range requests until the test finishes.
+    fn serve(body: &'static [u8], etag: &'static str) -> String {
+        use std::{
+            io::{BufRead, BufReader},
+            net::TcpListener,
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/file.zip", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    request.push(line.trim().to_owned());
+                }
+                let header = |name: &str| {
+                    request
+                        .iter()
+                        .find_map(|line| line.strip_prefix(name).map(str::to_owned))
+                };
+                let range = header("Range: bytes=")
+                    .filter(|_| header("If-Range: ").map_or(true, |if_range| if_range == etag));
+                let (status, content) = match range {
+                    Some(range) => {
+                        let (start, end) = range.split_once('-').unwrap();
+                        let (start, end): (usize, usize) =
+                            (start.parse().unwrap(), end.parse().unwrap());
+                        ("206 Partial Content", &body[start..=end])
+                    }
+                    None => ("200 OK", body),
+                };
+                let mut response = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nAccept-Ranges: bytes\r\nETag: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    content.len(),
+                    etag
+                )
+                .into_bytes();
+                if !request[0].starts_with("HEAD") {
+                    response.extend_from_slice(content);
+                }
+                stream.write_all(&response).unwrap();
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn test_resume_download() {
+        let dir = tempfile::tempdir().unwrap();
+        let download = Download::new(serve(b"0123456789", "\"v1\""), dir.path().join("file.zip"));
+        let planned = plan(&download).unwrap();
+        assert!(planned.completed.lock().unwrap().is_empty());
+        download_chunk(&planned, 0, &AtomicU64::new(0)).unwrap();
+        // Completed chunks are reused while the file on the server is the same
+        let planned = plan(&download).unwrap();
+        assert_eq!(planned.completed.into_inner().unwrap(), HashSet::from([0]));
+        // ...and downloaded again if its ETag changed
+        let mut changed = download.clone();
+        changed.url = serve(b"9876543210", "\"v2\"");
+        let planned = plan(&changed).unwrap();
+        assert!(planned.completed.lock().unwrap().is_empty());
+        // Chunks of a file changed after planning are rejected
+        let mut stale = planned;
+        stale.etag = Some("\"v1\"".to_owned());
+        assert!(download_chunk(&stale, 0, &AtomicU64::new(0)).is_err());
+    }
+
+    #[test]
+    fn test_checksum_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut download =
+            Download::new(serve(b"0123456789", "\"v1\""), dir.path().join("file.zip"));
+        download.sha256(Some("0".repeat(64)));
+        let planned = plan(&download).unwrap();
+        download_chunk(&planned, 0, &AtomicU64::new(0)).unwrap();
+        let actual = sha256_file(&download.part_path()).unwrap();
+        assert!(matches!(
+            finish(&download),
+            Err(Error::ChecksumMismatch { actual: ref hash, .. }) if *hash == actual
+        ));
+        assert!(!download.part_path().exists());
+        assert!(!download.state_path().exists());
+        assert!(!download.path.exists());
+
+        download.sha256(Some(actual.to_uppercase()));
+        let planned = plan(&download).unwrap();
+        download_chunk(&planned, 0, &AtomicU64::new(0)).unwrap();
+        finish(&download).unwrap();
+        assert_eq!(std::fs::read(&download.path).unwrap(), b"0123456789");
+    }
+}
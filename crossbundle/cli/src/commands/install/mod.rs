@@ -1,18 +1,22 @@
 #[cfg(feature = "android")]
+pub mod android;
+#[cfg(feature = "android")]
 pub mod bundletool;
 #[cfg(feature = "android")]
 pub mod command_line_tools;
+pub mod downloader;
 #[cfg(feature = "android")]
 pub mod sdkmanager;
 
 use crate::error::*;
 use clap::Parser;
 use crossbundle_tools::types::Config;
+use downloader::{Download, Downloader, DEFAULT_CONNECTIONS};
 
 #[cfg(feature = "android")]
 use self::{
-    bundletool::BundletoolInstallCommand, command_line_tools::CommandLineToolsInstallCommand,
-    sdkmanager::SdkManagerInstallCommand,
+    android::AndroidInstallCommand, bundletool::BundletoolInstallCommand,
+    command_line_tools::CommandLineToolsInstallCommand, sdkmanager::SdkManagerInstallCommand,
 };
 
 #[derive(Parser, Clone, Debug)]
@@ -27,6 +31,10 @@ pub struct InstallCommand {
 
 #[derive(Parser, Clone, Debug)]
 pub enum InstallCommandSubcommand {
+    /// Download command line tools, accept licenses and install the platform, build-tools
+    /// and NDK required by the project
+    #[cfg(feature = "android")]
+    Android(AndroidInstallCommand),
     /// Install bundletool. You can specify version of bundletool. By default, we have
     /// 1.8.2 bundletool version in usage
     #[cfg(feature = "android")]
@@ -64,6 +72,8 @@ impl InstallCommand {
         if let Some(subcommand) = &self.subcommand {
             #[cfg(feature = "android")]
             match subcommand {
+                #[cfg(feature = "android")]
+                InstallCommandSubcommand::Android(cmd) => cmd.install(config)?,
                 #[cfg(feature = "android")]
                 InstallCommandSubcommand::Bundletool(cmd) => cmd.install(config)?,
                 #[cfg(feature = "android")]
@@ -76,23 +86,51 @@ impl InstallCommand {
     }
 }
 
-/// Download from url and saves it in specified file
-pub fn download_to_file(download_url: &str, file_path: &std::path::Path) -> Result<()> {
-    let response = ureq::get(download_url)
-        .call()
-        .map_err(Error::DownloadFailed)?;
-    let mut out =
-        std::fs::File::create(file_path).map_err(|cause| Error::JarFileCreationFailed {
-            path: file_path.to_path_buf(),
-            cause,
-        })?;
-    std::io::copy(&mut response.into_reader(), &mut out).map_err(|cause| {
-        Error::CopyToFileFailed {
-            path: file_path.to_path_buf(),
-            cause,
-        }
-    })?;
-    Ok(())
+/// Options of downloads made by install commands.
+#[derive(Parser, Clone, Debug, Default)]
+pub struct DownloadOptions {
+    /// Number of parallel connections used to download the file. By default, 4
+    /// connections are used
+    #[clap(long)]
+    pub connections: Option<usize>,
+    /// Expected SHA-256 hash of the downloaded file. The file is deleted if the hash
+    /// doesn't match. Overrides the hash pinned for the default version and is required
+    /// for downloads without a pinned hash
+    #[clap(long)]
+    pub sha256: Option<String>,
+}
+
+impl DownloadOptions {
+    /// Downloads from url and saves it in specified file. Interrupted downloads are
+    /// resumed. The file is verified against `--sha256` or the `pinned` hash, and isn't
+    /// downloaded if neither is known
+    pub fn download_to_file(
+        &self,
+        config: &Config,
+        download_url: &str,
+        file_path: &std::path::Path,
+        pinned: Option<&str>,
+    ) -> Result<()> {
+        let sha256 = match self.sha256.clone().or_else(|| pinned.map(String::from)) {
+            Some(sha256) => sha256,
+            None => {
+                return Err(Error::UnknownChecksum {
+                    url: download_url.to_owned(),
+                })
+            }
+        };
+        let mut download = Download::new(download_url, file_path);
+        download.sha256(Some(sha256));
+        Downloader::new(self.connections.unwrap_or(DEFAULT_CONNECTIONS))
+            .download_all(config, &[download])
+    }
+}
+
+/// Returns the hash pinned for the file name in the table of known downloads.
+fn pinned_sha256(pins: &[(&str, &'static str)], file_name: &str) -> Option<&'static str> {
+    pins.iter()
+        .find(|(name, _)| *name == file_name)
+        .map(|(_, sha256)| *sha256)
 }
 
 /// Using default file path related on $HOME path for all installed commands
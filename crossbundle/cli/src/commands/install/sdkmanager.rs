@@ -1,8 +1,8 @@
 use clap::Parser;
 use crossbundle_tools::{
+    commands::android::find_sdkmanager,
     error::{CommandExt, Result},
     types::{android_sdk_path, Config},
-    EXECUTABLE_SUFFIX_BAT,
 };
 use std::path::Path;
 
@@ -165,25 +165,8 @@ impl SdkManagerInstallCommand {
     /// Run sdkmanager command with specified flags and options
     pub fn run(&self, _config: &Config) -> Result<()> {
         let sdk_path = android_sdk_path()?;
-        // Android Studio installs cmdline-tools into
-        // $ANDROID_SDK_ROOT/cmdline-tools/<version>/bin. Crossbundle install command
-        // ignores <version> directory so we need convert cmd-line-tools path to Option<T> to
-        // avoid confusion
-        let cmdline_tools_path = std::path::PathBuf::from(&sdk_path)
-            .join("cmdline-tools")
-            .join("latest")
-            .join("bin");
-        if cmdline_tools_path.exists() {
-            let sdkmanager_path =
-                cmdline_tools_path.join(format!("sdkmanager{}", EXECUTABLE_SUFFIX_BAT));
-            self.sdkmanager_command(&sdkmanager_path, Path::new(&sdk_path))?;
-        } else {
-            let sdkmanager_path = std::path::PathBuf::from(&sdk_path)
-                .join("cmdline-tools")
-                .join("bin")
-                .join(format!("sdkmanager{}", EXECUTABLE_SUFFIX_BAT));
-            self.sdkmanager_command(&sdkmanager_path, Path::new(&sdk_path))?;
-        };
+        let sdkmanager_path = find_sdkmanager(&sdk_path)?;
+        self.sdkmanager_command(&sdkmanager_path, &sdk_path)?;
         Ok(())
     }
 
@@ -0,0 +1,195 @@
+use crate::error::Result;
+use android_tools::java_tools::Key;
+use clap::Parser;
+use crossbundle_tools::{commands::android::*, types::Config};
+use std::path::PathBuf;
+
+#[derive(Parser, Clone, Debug)]
+pub enum KeystoreCommand {
+    /// Generates a new debug or release keystore
+    Create(KeystoreCreateCommand),
+    /// Lists aliases stored in the keystore
+    List(KeystoreListCommand),
+    /// Prints certificate information and fingerprints of the keystore entry
+    Inspect(KeystoreInspectCommand),
+    /// Generates `.well-known/assetlinks.json` for App Links verification
+    Assetlinks(KeystoreAssetlinksCommand),
+}
+
+impl KeystoreCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        match self {
+            Self::Create(cmd) => cmd.run(config),
+            Self::List(cmd) => cmd.run(config),
+            Self::Inspect(cmd) => cmd.run(config),
+            Self::Assetlinks(cmd) => cmd.run(config),
+        }
+    }
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct KeystoreCreateCommand {
+    /// Generate the default debug keystore in `~/.android/debug.keystore` with
+    /// `androiddebugkey` alias and `android` password.
+    #[clap(long, conflicts_with_all = &["keystore", "alias", "password"])]
+    pub debug: bool,
+    /// Path to the keystore that will be generated.
+    #[clap(long, short, required_unless_present = "debug")]
+    pub keystore: Option<PathBuf>,
+    /// Alias of the generated key.
+    #[clap(long, short, required_unless_present = "debug")]
+    pub alias: Option<String>,
+    /// Password of the keystore and the generated key.
+    #[clap(long, short, required_unless_present = "debug")]
+    pub password: Option<String>,
+    /// Distinguished name of the certificate owner.
+    #[clap(long, default_value = DEBUG_KEY_DNAME)]
+    pub dname: String,
+    /// Validity of the certificate in days.
+    #[clap(long, default_value = "10000")]
+    pub validity: u32,
+    /// Overwrite the keystore if it already exists.
+    #[clap(long, short)]
+    pub force: bool,
+}
+
+impl KeystoreCreateCommand {
+    pub fn run(&self, config: &Config) -> Result<()> {
+        let key = if self.debug {
+            Key::new_default()?
+        } else {
+            Key {
+                key_path: self.keystore.clone().unwrap(),
+                key_pass: self.password.clone().unwrap(),
+                key_alias: self.alias.clone().unwrap(),
+            }
+        };
+        if key.key_path.exists() {
+            if !self.force {
+                config.shell().warn(format!(
+                    "Keystore {:?} already exists. Use `--force` flag to overwrite it",
+                    key.key_path
+                ))?;
+                return Ok(());
+            }
+            std::fs::remove_file(&key.key_path)?;
+        }
+        config.status_message("Generating keystore", key.key_path.to_string_lossy())?;
+        create_keystore(&key, &self.dname, self.validity)?;
+        config.status("Keystore generated successfully")?;
+        Ok(())
+    }
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct KeystoreListCommand {
+    /// Path to the keystore. By default the debug keystore is used.
+    #[clap(long, short, requires = "password")]
+    pub keystore: Option<PathBuf>,
+    /// Password of the keystore.
+    #[clap(long, short)]
+    pub password: Option<String>,
+}
+
+impl KeystoreListCommand {
+    pub fn run(&self, config: &Config) -> Result<()> {
+        let key = keystore_or_debug(&self.keystore, &self.password, &None)?;
+        let entries = list_keystore(&key.key_path, &key.key_pass)?;
+        config.status_message("Keystore", key.key_path.to_string_lossy())?;
+        for entry in entries {
+            config.status_message(entry.entry_type, entry.alias)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct KeystoreInspectCommand {
+    /// Path to the keystore. By default the debug keystore is used.
+    #[clap(long, short, requires_all = &["password", "alias"])]
+    pub keystore: Option<PathBuf>,
+    /// Password of the keystore.
+    #[clap(long, short)]
+    pub password: Option<String>,
+    /// Alias of the entry to inspect.
+    #[clap(long, short)]
+    pub alias: Option<String>,
+}
+
+impl KeystoreInspectCommand {
+    pub fn run(&self, config: &Config) -> Result<()> {
+        let key = keystore_or_debug(&self.keystore, &self.password, &self.alias)?;
+        let info = inspect_keystore(&key.key_path, &key.key_pass, &key.key_alias)?;
+        config.status_message("Alias", &info.alias)?;
+        let fields = [
+            ("Owner", info.owner),
+            ("Issuer", info.issuer),
+            ("Valid from", info.valid),
+            ("Algorithm", info.signature_algorithm),
+            ("SHA1", info.sha1),
+            ("SHA256", info.sha256),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                config.status_message(name, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct KeystoreAssetlinksCommand {
+    /// Package name of the application, e.g. `com.crossbow.game`.
+    #[clap(long)]
+    pub package: String,
+    /// Path to the keystore. By default the debug keystore is used.
+    #[clap(long, short, requires_all = &["password", "alias"])]
+    pub keystore: Option<PathBuf>,
+    /// Password of the keystore.
+    #[clap(long, short)]
+    pub password: Option<String>,
+    /// Alias of the signing key.
+    #[clap(long, short)]
+    pub alias: Option<String>,
+    /// Additional SHA-256 fingerprints, e.g. of the Play App Signing key.
+    #[clap(long)]
+    pub fingerprint: Vec<String>,
+    /// Directory to save `.well-known/assetlinks.json` into.
+    #[clap(long, short, default_value = ".")]
+    pub output: PathBuf,
+}
+
+impl KeystoreAssetlinksCommand {
+    pub fn run(&self, config: &Config) -> Result<()> {
+        let key = keystore_or_debug(&self.keystore, &self.password, &self.alias)?;
+        let info = inspect_keystore(&key.key_path, &key.key_pass, &key.key_alias)?;
+        let fingerprints: Vec<String> = info
+            .sha256
+            .into_iter()
+            .chain(self.fingerprint.iter().cloned())
+            .collect();
+        let path = save_assetlinks(&self.output, &self.package, &fingerprints)?;
+        config.status_message("Generated", path.display())?;
+        Ok(())
+    }
+}
+
+/// Returns key with given credentials or the default debug key.
+fn keystore_or_debug(
+    keystore: &Option<PathBuf>,
+    password: &Option<String>,
+    alias: &Option<String>,
+) -> Result<Key> {
+    let mut key = Key::new_default()?;
+    if let Some(keystore) = keystore {
+        key.key_path = keystore.clone();
+    }
+    if let Some(password) = password {
+        key.key_pass = password.clone();
+    }
+    if let Some(alias) = alias {
+        key.key_alias = alias.clone();
+    }
+    Ok(key)
+}
@@ -0,0 +1,115 @@
+use crate::error::*;
+use clap::Parser;
+use crossbundle_tools::{
+    commands::android::{
+        diagnose_logcat_message, wait_for_app_pid, DeviceDiagnosis, LogPriority, Logcat, LogcatLine,
+    },
+    types::{AndroidSdk, Config},
+};
+use std::{collections::HashSet, time::Duration};
+
+#[derive(Parser, Clone, Debug)]
+pub struct LogCommand {
+    /// Package name of the installed application, e.g. `com.crossbow.game`
+    pub package: String,
+    /// Serial of the device or emulator. Can be found with `crossbundle devices` command
+    #[clap(long, short = 'd')]
+    pub device: Option<String>,
+    /// Show only messages with given tag. Can be specified several times
+    #[clap(long, short = 't')]
+    pub tag: Vec<String>,
+    /// Show only messages with given or higher priority: V, D, I, W, E or F
+    #[clap(long, short = 'p')]
+    pub priority: Option<LogPriority>,
+    /// Show messages since given time. Supports relative durations like `30s`, `5m`,
+    /// `1h` and logcat time formats like `'10-17 12:00:00.000'`
+    #[clap(long)]
+    pub since: Option<String>,
+    /// How many seconds to wait for the application to start
+    #[clap(long, default_value = "10")]
+    pub wait: u64,
+    /// Detect common on-device failures like missing native libraries, W^X violations
+    /// and scoped storage denials, and explain how to fix them
+    #[clap(long)]
+    pub diagnose: bool,
+}
+
+impl LogCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        let sdk = AndroidSdk::from_env()?;
+        let pid = wait_for_app_pid(
+            &sdk,
+            &self.package,
+            self.device.as_deref(),
+            Duration::from_secs(self.wait),
+        )?;
+        config.status_message(format!("Attached to {}", self.package), pid)?;
+        let mut logcat = Logcat::new();
+        logcat.pid(pid);
+        if let Some(device) = &self.device {
+            logcat.device(device);
+        }
+        for tag in &self.tag {
+            logcat.tag(tag);
+        }
+        if let Some(priority) = self.priority {
+            logcat.priority(priority);
+        }
+        if let Some(since) = &self.since {
+            logcat.since(since);
+        }
+        let mut diagnostics = Diagnostics::new(self.diagnose);
+        logcat.stream(&sdk, |line| {
+            match line {
+                Ok(line) => {
+                    line.print(&mut config.shell())?;
+                    diagnostics.check(config, &line)?;
+                }
+                Err(raw) => config.shell().note(raw)?,
+            }
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+/// Explains known on-device failures found in logcat output. Every failure is explained
+/// only once.
+pub struct Diagnostics {
+    enabled: bool,
+    reported: HashSet<&'static str>,
+}
+
+impl Diagnostics {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            reported: HashSet::new(),
+        }
+    }
+
+    pub fn check(
+        &mut self,
+        config: &Config,
+        line: &LogcatLine,
+    ) -> crossbundle_tools::error::Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if let Some(diagnosis) = diagnose_logcat_message(&line.message) {
+            if self.reported.insert(diagnosis.code) {
+                print_diagnosis(config, diagnosis)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn print_diagnosis(
+    config: &Config,
+    diagnosis: &DeviceDiagnosis,
+) -> crossbundle_tools::error::Result<()> {
+    let mut shell = config.shell();
+    shell.warn(format!("[{}] {}", diagnosis.code, diagnosis.problem))?;
+    shell.note(format!("Fix: {}", diagnosis.fix))
+}
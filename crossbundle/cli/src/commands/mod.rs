@@ -1,8 +1,46 @@
+#[cfg(feature = "android")]
+pub mod adb_server;
+#[cfg(feature = "android")]
+pub mod analyze;
+pub mod app;
+#[cfg(feature = "android")]
+pub mod bench;
 pub mod build;
+pub mod capture;
+pub mod coverage;
+pub mod debug;
+pub mod dev;
+#[cfg(feature = "android")]
+pub mod device_profile;
+pub mod devices;
+#[cfg(feature = "android")]
+pub mod diff_release;
+pub mod doctor;
+#[cfg(feature = "android")]
+pub mod emulator;
 pub mod install;
+#[cfg(feature = "android")]
+pub mod keystore;
+#[cfg(feature = "android")]
+pub mod log;
 pub mod new;
+pub mod profile;
+#[cfg(feature = "android")]
+pub mod retrace;
 pub mod run;
+#[cfg(feature = "apple")]
+pub mod simulator_profile;
+pub mod soak;
+#[cfg(feature = "android")]
+pub mod symbolicate;
+pub mod targets;
 pub mod update;
+#[cfg(feature = "apple")]
+pub mod upload;
+pub mod verify_launch;
+pub mod verify_provenance;
+#[cfg(feature = "apple")]
+pub mod xcresult;
 
 use crate::error::Result;
 use clap::Parser;
@@ -10,6 +48,22 @@ use crossbundle_tools::types::Config;
 
 #[derive(Parser, Clone, Debug)]
 pub enum Commands {
+    /// Starts, stops and checks the adb server. `ADB_SERVER_SOCKET` or
+    /// `ANDROID_ADB_SERVER_PORT` select a custom or remote server
+    #[cfg(feature = "android")]
+    #[clap(subcommand)]
+    AdbServer(adb_server::AdbServerCommand),
+    /// Breaks down the APK size by files and categories: native libraries, dex,
+    /// resources and assets
+    #[cfg(feature = "android")]
+    Analyze(analyze::AnalyzeCommand),
+    /// Clears, backs up and restores data, grants and revokes permissions and opens deep
+    /// links of the installed application
+    App(app::AppCommand),
+    /// Builds criterion benchmarks, runs them on the Android device and compares the
+    /// results with the stored baseline
+    #[cfg(feature = "android")]
+    Bench(bench::BenchCommand),
     /// Starts the process of building/packaging/signing of the rust crate
     #[clap(subcommand)]
     Build(build::BuildCommand),
@@ -17,13 +71,85 @@ pub enum Commands {
     /// device/emulator
     #[clap(subcommand)]
     Run(run::RunCommand),
+    /// Records the screen of the Android device or the Apple simulator into MP4 file
+    Record(capture::RecordCommand),
+    /// Saves screenshot of the Android device or the Apple simulator into PNG file
+    Screenshot(capture::ScreenshotCommand),
+    /// Collects coverage profiles of the application built with `--coverage` from the
+    /// Android device or the Apple simulator and generates the lcov report
+    Coverage(coverage::CoverageCommand),
     /// Creates a new Cargo package in the given directory. Project will be ready to build
     /// with `crossbundle`
     New(new::NewCommand),
+    /// Builds the application and attaches the native debugger to it on the device
+    #[clap(subcommand)]
+    Debug(debug::DebugCommand),
+    /// Pushes files, pulls files and runs console commands in the running development
+    /// build of the game
+    Dev(dev::DevCommand),
+    /// Applies a deterministic state to the Android device and restores the previous one
+    #[cfg(feature = "android")]
+    #[clap(subcommand)]
+    DeviceProfile(device_profile::DeviceProfileCommand),
+    /// Lists connected Android devices and emulators, Apple simulators and devices
+    Devices(devices::DevicesCommand),
+    /// Compares the new APK or AAB with the published release: manifest, permissions,
+    /// signature and size
+    #[cfg(feature = "android")]
+    DiffRelease(diff_release::DiffReleaseCommand),
+    /// Verifies the environment: rust targets, Android SDK/NDK, build tools, JDK and Xcode
+    Doctor(doctor::DoctorCommand),
+    /// Creates, starts and stops Android emulators
+    #[cfg(feature = "android")]
+    #[clap(subcommand)]
+    Emulator(emulator::EmulatorCommand),
     /// Installs bundletool and Android Studio's sdkmanager
     Install(install::InstallCommand),
+    /// Creates, lists and inspects keystores used to sign Android applications
+    #[cfg(feature = "android")]
+    #[clap(subcommand)]
+    Keystore(keystore::KeystoreCommand),
+    /// Streams colorized logcat output of the running Android application
+    #[cfg(feature = "android")]
+    Log(log::LogCommand),
+    /// Records a CPU profile of the running application and converts it into a
+    /// flamegraph report or a pprof file
+    #[clap(subcommand)]
+    Profile(profile::ProfileCommand),
+    /// De-obfuscates stack traces and resource paths of the Android release with the
+    /// archived R8 and resource mappings
+    #[cfg(feature = "android")]
+    Retrace(retrace::RetraceCommand),
+    /// Applies a status bar, locale and appearance profile to the Apple simulator and
+    /// restores the previous state
+    #[cfg(feature = "apple")]
+    #[clap(subcommand)]
+    SimulatorProfile(simulator_profile::SimulatorProfileCommand),
+    /// Monitors the frame rate and the thermal status of the running game for a long
+    /// time and writes the throttling report
+    Soak(soak::SoakCommand),
+    /// Resolves native backtraces of the Android crash with unstripped libraries of the
+    /// project
+    #[cfg(feature = "android")]
+    Symbolicate(symbolicate::SymbolicateCommand),
+    /// Lists platforms and architectures the environment can build, with installed rust
+    /// targets, toolchains and signing
+    Targets(targets::TargetsCommand),
     /// Updates or checks for new version of Crossbundle
     Update(update::UpdateCommand),
+    /// Uploads the built application to App Store Connect
+    #[cfg(feature = "apple")]
+    #[clap(subcommand)]
+    Upload(upload::UploadCommand),
+    /// Installs and launches the application on a device, emulator or simulator and
+    /// fails if it crashes or stops responding
+    VerifyLaunch(verify_launch::VerifyLaunchCommand),
+    /// Verifies cosign signatures of the artifact and the output manifest
+    VerifyProvenance(verify_provenance::VerifyProvenanceCommand),
+    /// Prints test results, attachments and coverage of the `.xcresult` bundle written
+    /// by `xcodebuild test`
+    #[cfg(feature = "apple")]
+    Xcresult(xcresult::XcresultCommand),
 }
 
 impl Commands {
@@ -35,11 +161,50 @@ impl Commands {
             }
         }
         match self {
+            #[cfg(feature = "android")]
+            Commands::AdbServer(cmd) => cmd.handle_command(config),
+            #[cfg(feature = "android")]
+            Commands::Analyze(cmd) => cmd.handle_command(config),
+            Commands::App(cmd) => cmd.handle_command(config),
+            #[cfg(feature = "android")]
+            Commands::Bench(cmd) => cmd.handle_command(config),
             Commands::Build(cmd) => cmd.handle_command(config),
             Commands::Run(cmd) => cmd.handle_command(config),
+            Commands::Record(cmd) => cmd.handle_command(config),
+            Commands::Screenshot(cmd) => cmd.handle_command(config),
+            Commands::Coverage(cmd) => cmd.handle_command(config),
             Commands::New(cmd) => cmd.handle_command(config),
+            Commands::Debug(cmd) => cmd.handle_command(config),
+            Commands::Dev(cmd) => cmd.handle_command(config),
+            #[cfg(feature = "android")]
+            Commands::DeviceProfile(cmd) => cmd.handle_command(config),
+            Commands::Devices(cmd) => cmd.handle_command(config),
+            #[cfg(feature = "android")]
+            Commands::DiffRelease(cmd) => cmd.handle_command(config),
+            Commands::Doctor(cmd) => cmd.handle_command(config),
+            #[cfg(feature = "android")]
+            Commands::Emulator(cmd) => cmd.handle_command(config),
             Commands::Install(cmd) => cmd.handle_command(config),
+            #[cfg(feature = "android")]
+            Commands::Keystore(cmd) => cmd.handle_command(config),
+            #[cfg(feature = "android")]
+            Commands::Log(cmd) => cmd.handle_command(config),
+            Commands::Profile(cmd) => cmd.handle_command(config),
+            #[cfg(feature = "android")]
+            Commands::Retrace(cmd) => cmd.handle_command(config),
+            #[cfg(feature = "apple")]
+            Commands::SimulatorProfile(cmd) => cmd.handle_command(config),
+            Commands::Soak(cmd) => cmd.handle_command(config),
+            #[cfg(feature = "android")]
+            Commands::Symbolicate(cmd) => cmd.handle_command(config),
+            Commands::Targets(cmd) => cmd.handle_command(config),
             Commands::Update(cmd) => cmd.handle_command(config),
+            #[cfg(feature = "apple")]
+            Commands::Upload(cmd) => cmd.handle_command(config),
+            Commands::VerifyLaunch(cmd) => cmd.handle_command(config),
+            Commands::VerifyProvenance(cmd) => cmd.handle_command(config),
+            #[cfg(feature = "apple")]
+            Commands::Xcresult(cmd) => cmd.handle_command(config),
         }
     }
 }
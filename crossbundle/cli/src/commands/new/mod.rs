@@ -1,7 +1,10 @@
 use crate::error::Result;
 use clap::Parser;
 use crossbundle_tools::{
-    commands::{check_cargo_generate, create_project},
+    commands::{
+        check_cargo_generate, create_project, MinimalProject, MinimalProjectPlatform,
+        MinimalProjectTemplate,
+    },
     types::Config,
 };
 
@@ -13,8 +16,16 @@ pub struct NewCommand {
     /// converted to kebab-case unless `--force` is given.
     pub name: String,
     /// Name of the template to create.
-    #[clap(long, short)]
+    #[clap(long, short, conflicts_with = "minimal")]
     pub template: Option<String>,
+    /// Generate a minimal project locally without `cargo-generate`. Possible values:
+    /// `empty`, `macroquad`, `winit`, `wgpu-triangle`, `bevy-sprite`.
+    #[clap(long)]
+    pub minimal: Option<MinimalProjectTemplate>,
+    /// Platforms to generate the minimal project for. Possible values: `all`,
+    /// `android`, `apple`.
+    #[clap(long, requires = "minimal")]
+    pub platform: Option<MinimalProjectPlatform>,
     /// Don't convert the project name to kebab-case before creating the directory.
     /// Note that cargo generate won't overwrite an existing directory, even if `--force`
     /// is given.
@@ -24,6 +35,16 @@ pub struct NewCommand {
 
 impl NewCommand {
     pub fn handle_command(&self, config: &Config) -> Result<()> {
+        if let Some(template) = self.minimal {
+            let project_path = config.current_dir().join(&self.name);
+            std::fs::create_dir_all(&project_path)?;
+            MinimalProject::new(template)
+                .name(&self.name)
+                .platform(self.platform.unwrap_or_default())
+                .generate(&project_path)?;
+            config.status_message("Generated", project_path.display())?;
+            return Ok(());
+        }
         if !check_cargo_generate() {
             config
                 .shell()
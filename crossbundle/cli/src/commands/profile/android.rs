@@ -0,0 +1,116 @@
+use crate::commands::build::android::AndroidBuildCommand;
+use crate::error::*;
+use clap::Parser;
+use crossbundle_tools::{
+    commands::{android::*, find_workspace_cargo_manifest_path},
+    types::{AndroidTarget, Config, Profile},
+};
+use std::path::PathBuf;
+
+#[derive(Parser, Clone, Debug)]
+pub struct AndroidProfileCommand {
+    /// Package name of the running application, e.g. `com.crossbow.game`
+    pub package: String,
+    /// Serial of the device or emulator. Required if several devices are connected
+    #[clap(long, short = 'd')]
+    pub device: Option<String>,
+    /// Duration of the recording in seconds
+    #[clap(long, default_value = "10")]
+    pub duration: u32,
+    /// Number of samples per second
+    #[clap(long, default_value = "1000")]
+    pub frequency: u32,
+    /// Call graph recording method: `dwarf` or `fp`. `fp` is cheaper, but requires
+    /// `-C force-frame-pointers=yes`
+    #[clap(long, default_value = "dwarf", possible_values = ["dwarf", "fp"])]
+    pub call_graph: String,
+    /// Format of the converted profile: `html` report with flamegraphs, `pprof` file or
+    /// `folded` stacks for flamegraph.pl and inferno
+    #[clap(long, short, default_value = "html")]
+    pub format: PerfFormat,
+    /// Copy the converted profile to the given path
+    #[clap(long, short)]
+    pub output: Option<PathBuf>,
+    /// Look up libraries built with the release profile
+    #[clap(long, short)]
+    pub release: bool,
+    /// Directory for generated artifact and intermediate files
+    #[clap(long)]
+    pub target_dir: Option<PathBuf>,
+    /// Directory with unstripped `.so` libraries. By default cargo output directory of
+    /// the device target is used
+    #[clap(long)]
+    pub symbols: Option<PathBuf>,
+}
+
+impl AndroidProfileCommand {
+    /// Records the profile with `app_profiler.py`, pulls it with symbols of the unstripped
+    /// libraries and converts it with simpleperf scripts.
+    pub fn run(&self, config: &Config) -> Result<()> {
+        let (sdk, ndk) =
+            AndroidBuildCommand::project_android_toolchain(config, self.target_dir.clone())?;
+        let simpleperf = find_simpleperf(&ndk)?;
+        let device = select_android_device(&sdk, self.device.as_deref())?;
+        config.status_message("Selected device", &device)?;
+
+        let target_dir = match &self.target_dir {
+            Some(target_dir) => target_dir.clone(),
+            None => find_workspace_cargo_manifest_path(config.current_dir())?
+                .parent()
+                .unwrap()
+                .join("target"),
+        };
+        let symbol_dir = match &self.symbols {
+            Some(symbols) => Some(symbols.clone()),
+            None => {
+                let abi = device_property(&sdk, &device, "ro.product.cpu.abi")?.unwrap_or_default();
+                let target = AndroidTarget::from_android_abi(&abi)?;
+                let profile = if self.release {
+                    Profile::Release
+                } else {
+                    Profile::Debug
+                };
+                unstripped_lib_dirs(&target_dir, target, &profile)
+                    .into_iter()
+                    .next()
+            }
+        };
+        match &symbol_dir {
+            Some(symbol_dir) => {
+                config.status_message("Using symbols from", symbol_dir.display())?
+            }
+            None => config
+                .shell()
+                .warn("Unstripped libraries not found, Rust frames may be unresolved")?,
+        }
+
+        let work_dir = target_dir.join("android").join("profile");
+        let mut app_profiler = AppProfiler::new(&simpleperf, &self.package, &work_dir);
+        app_profiler
+            .duration(self.duration)
+            .frequency(self.frequency)
+            .call_graph(&self.call_graph)
+            .device(&device)
+            .adb_dir(&sdk.sdk_path().join("platform-tools"));
+        if let Some(symbol_dir) = &symbol_dir {
+            app_profiler.symbol_dir(symbol_dir);
+        }
+        config.status_message(
+            format!("Recording {} for", self.package),
+            format!("{} seconds", self.duration),
+        )?;
+        let perf_data = app_profiler.run()?;
+        config.status_message("Recorded", perf_data.display())?;
+
+        let mut report = convert_perf_data(&simpleperf, &perf_data, self.format)?;
+        if let Some(output) = &self.output {
+            if let Some(parent) = output.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(&report, output)?;
+            report = output.clone();
+        }
+        config.status_message("Generated", report.display())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+#[cfg(feature = "android")]
+mod android;
+
+use crate::error::Result;
+use clap::Parser;
+use crossbundle_tools::types::Config;
+
+#[derive(Parser, Clone, Debug)]
+pub enum ProfileCommand {
+    /// Records a CPU profile of the running application on the Android device/emulator
+    /// with simpleperf and converts it into a flamegraph report or a pprof file
+    #[cfg(feature = "android")]
+    Android(android::AndroidProfileCommand),
+}
+
+impl ProfileCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        #[cfg(feature = "android")]
+        match &self {
+            Self::Android(cmd) => cmd.run(config)?,
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{commands::build::BuildContext, error::*};
+use clap::Parser;
+use crossbundle_tools::{
+    commands::{android::*, find_workspace_cargo_manifest_path},
+    types::Config,
+};
+use std::{io::Read, path::PathBuf};
+
+#[derive(Parser, Clone, Debug)]
+pub struct RetraceCommand {
+    /// File with the stack trace or the report to de-obfuscate. Reads standard input if
+    /// not specified
+    pub input: Option<PathBuf>,
+    /// R8 mapping file. By default `mapping.txt` of the archived release is used
+    #[clap(long)]
+    pub mapping: Option<PathBuf>,
+    /// Resource path shortening map. By default `resources-map.txt` of the archived
+    /// release is used
+    #[clap(long)]
+    pub resource_map: Option<PathBuf>,
+    /// Release to take the archived mappings from, e.g. `1.2.0-42`. By default the
+    /// latest archived release is used
+    #[clap(long)]
+    pub release: Option<String>,
+    /// Directory for generated artifact and intermediate files
+    #[clap(long)]
+    pub target_dir: Option<PathBuf>,
+}
+
+impl RetraceCommand {
+    /// Replaces obfuscated classes, methods and resource paths of the report with the
+    /// original ones.
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        let report = if let Some(input) = &self.input {
+            std::fs::read_to_string(input)?
+        } else {
+            let mut report = String::new();
+            std::io::stdin().read_to_string(&mut report)?;
+            report
+        };
+        let archive = self.archived_mappings_dir(config)?;
+        let mapping_path = self
+            .mapping
+            .clone()
+            .or_else(|| archive.as_ref().map(|dir| dir.join(R8_MAPPING_FILE_NAME)))
+            .filter(|path| path.exists());
+        let resource_map_path = self
+            .resource_map
+            .clone()
+            .or_else(|| archive.as_ref().map(|dir| dir.join(RESOURCE_MAP_FILE_NAME)))
+            .filter(|path| path.exists());
+        if mapping_path.is_none() && resource_map_path.is_none() {
+            return Err(Error::InvalidMetadata(anyhow::anyhow!(
+                "mappings not found, build the release with `shrink_code` or \
+                 `shorten_resource_paths` or specify them with `--mapping` and `--resource-map`"
+            )));
+        }
+        let mapping = match &mapping_path {
+            Some(path) => {
+                config.status_message("Using mapping", path.display())?;
+                Some(ProguardMapping::parse(&std::fs::read_to_string(path)?))
+            }
+            None => None,
+        };
+        let resources = match &resource_map_path {
+            Some(path) => {
+                config.status_message("Using resource map", path.display())?;
+                Some(ResourcePathMap::parse(&std::fs::read_to_string(path)?))
+            }
+            None => None,
+        };
+        print!("{}", retrace(&report, mapping.as_ref(), resources.as_ref()));
+        Ok(())
+    }
+
+    /// Directory with mappings of the requested or the latest archived release.
+    fn archived_mappings_dir(&self, config: &Config) -> Result<Option<PathBuf>> {
+        let target_dir = match &self.target_dir {
+            Some(target_dir) => target_dir.clone(),
+            None => find_workspace_cargo_manifest_path(config.current_dir())?
+                .parent()
+                .unwrap()
+                .join("target"),
+        };
+        let storage = BuildContext::artifact_storage(config, &target_dir)?;
+        Ok(match &self.release {
+            Some(release) => Some(fetch_mappings(storage.as_ref(), &target_dir, release)?),
+            None => latest_mappings_dir(storage.as_ref(), &target_dir)?,
+        })
+    }
+}
@@ -1,30 +1,83 @@
-use crate::commands::build::{android::AndroidBuildCommand, BuildContext};
+use super::AppearanceArgs;
+use crate::commands::build::{android::AndroidBuildCommand, run_hooks, BuildContext};
+use crate::commands::emulator::EmulatorStartCommand;
+use crate::commands::log::Diagnostics;
 use crate::error::*;
 use clap::Parser;
 use crossbundle_tools::{
-    commands::android::*,
-    error::CommandExt,
+    commands::{android::*, dev_channel_token, DevChannelClient, DEFAULT_DEV_CHANNEL_PORT},
+    error::{AndroidError, CommandExt},
     types::Config,
-    types::{AndroidStrategy, BuildApks, InstallApks},
+    types::{AndroidSdk, AndroidStrategy, BuildApks, InstallApks},
 };
+use std::time::Duration;
+
+/// Time to wait until the Android container starts and finishes booting.
+const CONTAINER_TIMEOUT: Duration = Duration::from_secs(120);
 
 #[derive(Parser, Clone, Debug)]
 pub struct AndroidRunCommand {
     #[clap(flatten)]
     pub build_command: AndroidBuildCommand,
-    /// Enable logging attach after run.
+    /// Enable logging attach after run. Logcat output of the application process is
+    /// streamed to the terminal until Ctrl-C is pressed.
     #[clap(long)]
     pub log: bool,
+    /// Serial of the device or emulator to run the application on. Can be found with
+    /// `crossbundle devices` command. Required if several devices are connected.
+    #[clap(long, short = 'd')]
+    pub device: Option<String>,
+    /// Name of the AVD to run the application on. The emulator is booted unless it's
+    /// already running. AVDs can be created with `crossbundle emulator create`
+    #[clap(long, conflicts_with = "device")]
+    pub emulator: Option<String>,
+    /// Address of the Android container to run the application on, e.g.
+    /// `192.168.240.112:5555`, or `waydroid` to start the Waydroid session and connect
+    /// to it
+    #[clap(long, conflicts_with_all = &["device", "emulator"])]
+    pub container: Option<String>,
+    /// Boot the emulator without window, audio and boot animation
+    #[clap(long, requires = "emulator")]
+    pub headless: bool,
+    /// Boot the emulator from the named snapshot. If it doesn't exist, the emulator is
+    /// cold booted and the snapshot is saved after the application is installed
+    #[clap(long, requires = "emulator")]
+    pub snapshot: Option<String>,
+    /// Emulator profile with GPU and acceleration settings, e.g. the built-in `ci`
+    /// profile
+    #[clap(long, requires = "emulator")]
+    pub emulator_profile: Option<String>,
+    /// Additional logcat filter specs applied to the application output,
+    /// e.g. `--log-filter RustStdoutStderr:D --log-filter *:S`.
+    #[clap(long, requires = "log")]
+    pub log_filter: Vec<String>,
+    /// Stream application logs and explain common on-device failures like missing native
+    /// libraries, W^X violations and scoped storage denials
+    #[clap(long, conflicts_with = "log-filter")]
+    pub diagnose: bool,
+    #[clap(flatten)]
+    pub appearance: AppearanceArgs,
+    /// Show the debug overlay of the game after launch. The game must be built with
+    /// `debug-overlay` feature of crossbow and start the development channel on the
+    /// default port
+    #[clap(long)]
+    pub debug_overlay: bool,
 }
 
 impl AndroidRunCommand {
     /// Deployes and runs application in AAB or APK format on your device or emulator
     pub fn run(&self, config: &Config) -> Result<()> {
-        let context = BuildContext::new(config, self.build_command.shared.target_dir.clone())?;
+        let context = self.build_command.build_context(config)?;
         if self.build_command.lib.is_some() {
             config.status("Can not run dynamic library")?;
             return Ok(());
         }
+        run_hooks(
+            config,
+            &context,
+            &context.config.hooks.pre_build,
+            self.build_command.shared.trust,
+        )?;
         match self.build_command.strategy {
             AndroidStrategy::NativeApk => {
                 self.run_native_apk(config, &context)?;
@@ -42,6 +95,7 @@ impl AndroidRunCommand {
     pub fn run_native_aab(&self, config: &Config, context: &BuildContext) -> Result<()> {
         let (android_manifest, sdk, aab_path, package_name, key) =
             self.build_command.execute_aab(config, context)?;
+        self.run_post_build_hooks(config, context)?;
         config.status("Generating apks")?;
         let apks = aab_path
             .parent()
@@ -54,39 +108,75 @@ impl AndroidRunCommand {
             .ks_key_alias(key.key_alias)
             .run()?;
         config.status("Starting run process")?;
+        let device = self.select_device(config, &sdk)?;
         config.status("Installing APKs file")?;
-        InstallApks::new(&apks_path).run()?;
-        config.status("Starting APK file")?;
-        start_app(
+        let mut install_apks = InstallApks::new(&apks_path);
+        if let Some(device) = &device {
+            install_apks.device_id(device);
+        }
+        AdbServer::from_env()?.retry(&sdk, || Ok(install_apks.run()?))?;
+        self.launch(
+            config,
             &sdk,
             &android_manifest.package,
             "android.app.NativeActivity",
+            device.as_deref(),
         )?;
-        if self.log {
-            config.status("Attaching logger")?;
-            std::thread::sleep(std::time::Duration::from_secs(2));
-            attach_logger_only_app(&sdk)?;
-        }
         config.status("Run finished successfully")?;
         Ok(())
     }
 
     pub fn run_native_apk(&self, config: &Config, context: &BuildContext) -> Result<()> {
+        if self.build_command.split_per_abi {
+            return self.run_split_apks(config, context);
+        }
         let (android_manifest, sdk, apk_path) = self.build_command.execute_apk(config, context)?;
+        self.run_post_build_hooks(config, context)?;
         config.status("Starting run process")?;
+        let device = self.select_device(config, &sdk)?;
         config.status("Installing APK file")?;
-        install_apk(&sdk, &apk_path)?;
-        config.status("Starting APK file")?;
-        start_app(
+        AdbServer::from_env()?.retry(&sdk, || install_apk(&sdk, &apk_path, device.as_deref()))?;
+        self.launch(
+            config,
             &sdk,
             &android_manifest.package,
             "android.app.NativeActivity",
+            device.as_deref(),
+        )?;
+        config.status("Run finished successfully")?;
+        Ok(())
+    }
+
+    /// Builds split APKs and installs the one that matches the most preferred ABI of
+    /// the device. Falls back to the universal APK.
+    pub fn run_split_apks(&self, config: &Config, context: &BuildContext) -> Result<()> {
+        let (android_manifest, sdk, apks) =
+            self.build_command.execute_split_apks(config, context)?;
+        self.run_post_build_hooks(config, context)?;
+        config.status("Starting run process")?;
+        let device = self.select_device(config, &sdk)?;
+        let serial = device.as_deref().unwrap_or_default();
+        let abi_list = device_property(&sdk, serial, "ro.product.cpu.abilist")?.unwrap_or_default();
+        let apk_path = abi_list
+            .split(',')
+            .find_map(|abi| {
+                apks.iter()
+                    .find(|(target, _)| target.map(|target| target.android_abi()) == Some(abi))
+            })
+            .or_else(|| apks.iter().find(|(target, _)| target.is_none()))
+            .map(|(_, path)| path)
+            .ok_or_else(|| {
+                Error::CrossbundleTools(AndroidError::NoApkForDeviceAbi(abi_list.clone()).into())
+            })?;
+        config.status_message("Installing", apk_path.display())?;
+        AdbServer::from_env()?.retry(&sdk, || install_apk(&sdk, apk_path, device.as_deref()))?;
+        self.launch(
+            config,
+            &sdk,
+            &android_manifest.package,
+            "android.app.NativeActivity",
+            device.as_deref(),
         )?;
-        if self.log {
-            config.status("Attaching logger")?;
-            std::thread::sleep(std::time::Duration::from_secs(2));
-            attach_logger_only_app(&sdk)?;
-        }
         config.status("Run finished successfully")?;
         Ok(())
     }
@@ -95,21 +185,206 @@ impl AndroidRunCommand {
         let (_, sdk, gradle_project_path) =
             self.build_command
                 .build_gradle(config, context, &self.build_command.export_path)?;
+        self.run_post_build_hooks(config, context)?;
+        let device = self.select_device(config, &sdk)?;
         config.status("Installing APK file on device")?;
         let mut gradle = gradle_init()?;
         gradle
             .arg("installDebug")
             .arg("-p")
             .arg(dunce::simplified(&gradle_project_path));
+        // Gradle installs application on all connected devices unless ANDROID_SERIAL is set
+        if let Some(device) = &device {
+            gradle.env("ANDROID_SERIAL", device);
+        }
         gradle.output_err(true)?;
+        self.launch(
+            config,
+            &sdk,
+            "com.crossbow.game",
+            ".CrossbowApp",
+            device.as_deref(),
+        )?;
+        config.status("Run finished successfully")?;
+        Ok(())
+    }
+
+    /// Runs `post_build` hooks once the application is built, before it's installed.
+    fn run_post_build_hooks(&self, config: &Config, context: &BuildContext) -> Result<()> {
+        run_hooks(
+            config,
+            context,
+            &context.config.hooks.post_build,
+            self.build_command.shared.trust,
+        )
+    }
+
+    /// Returns serial of the device to run the application on. If `--device` is not
+    /// specified, the only online device is used.
+    fn select_device(&self, config: &Config, sdk: &AndroidSdk) -> Result<Option<String>> {
+        // The server could be stopped or replaced by another adb version during the build
+        let adb_server = AdbServer::from_env()?;
+        match adb_server.ensure_running(sdk)? {
+            AdbServerStatus::Started => config.status_message("Started adb server", &adb_server)?,
+            AdbServerStatus::Restarted => config.shell().warn(format!(
+                "adb server {} had another version and was restarted",
+                adb_server
+            ))?,
+            AdbServerStatus::Running => {}
+        }
+        // Devices connected over WiFi before are disconnected when the server restarts
+        let paired_devices = PairedDevices::from_home_dir()?;
+        for address in reconnect_paired_devices(sdk, &paired_devices, self.device.as_deref())? {
+            config.status_message("Reconnected to", address)?;
+        }
+        if self.device.is_some() {
+            return Ok(self.device.clone());
+        }
+        if let Some(avd) = &self.emulator {
+            let serial = EmulatorStartCommand {
+                avd: avd.clone(),
+                headless: self.headless,
+                port: None,
+                wipe_data: false,
+                timeout: 300,
+                snapshot: self.snapshot.clone(),
+                no_snapshot_save: self.snapshot.is_some(),
+                profile: self.emulator_profile.clone(),
+                gpu: None,
+            }
+            .run(config)?;
+            return Ok(Some(serial));
+        }
+        if let Some(container) = &self.container {
+            let serial = match container.as_str() {
+                "waydroid" => {
+                    config.status("Starting Waydroid session")?;
+                    let serial = start_waydroid_session(CONTAINER_TIMEOUT)?;
+                    connect_android_container(sdk, &serial, CONTAINER_TIMEOUT)?
+                }
+                address => connect_android_container(sdk, address, CONTAINER_TIMEOUT)?,
+            };
+            config.status_message("Connected to container", &serial)?;
+            return Ok(Some(serial));
+        }
+        // Linux developers without devices and emulators often test on Waydroid
+        let no_devices = !android_devices(sdk)?.iter().any(|d| d.is_online());
+        if no_devices && is_waydroid_installed() {
+            let status = waydroid_status()?;
+            if let (true, Some(serial)) = (status.session_running, status.serial()) {
+                let serial = connect_android_container(sdk, &serial, CONTAINER_TIMEOUT)?;
+                config.status_message("Connected to running Waydroid session", &serial)?;
+                return Ok(Some(serial));
+            }
+        }
+        let device = select_android_device(sdk, None)?;
+        config.status_message("Selected device", &device)?;
+        Ok(Some(device))
+    }
+
+    /// Starts the application with the requested appearance and attaches the logger.
+    /// With several appearances captures a screenshot of each one instead.
+    fn launch(
+        &self,
+        config: &Config,
+        sdk: &AndroidSdk,
+        package: &str,
+        activity: &str,
+        device: Option<&str>,
+    ) -> Result<()> {
+        self.save_emulator_snapshot(config, sdk, device)?;
         config.status("Starting APK file")?;
-        start_app(&sdk, "com.crossbow.game", ".CrossbowApp")?;
-        if self.log {
-            config.status("Attaching logger")?;
-            std::thread::sleep(std::time::Duration::from_secs(2));
-            attach_logger_only_app(&sdk)?;
+        // Pseudo-localized strings are shown only in their locale
+        let mut appearance = self.appearance.clone();
+        if self.build_command.pseudo_locale && appearance.locale.is_empty() {
+            appearance.locale.push(PSEUDO_ACCENTED_LOCALE.to_owned());
         }
-        config.status("Run finished successfully")?;
+        let captured = appearance.launch_matrix(
+            config,
+            |appearance| {
+                apply_android_appearance(sdk, package, device, appearance)?;
+                force_stop_app(sdk, package, device)?;
+                start_app(sdk, package, activity, device)?;
+                Ok(())
+            },
+            |output| Ok(android_screenshot(sdk, device, output)?),
+        )?;
+        if self.debug_overlay {
+            self.show_debug_overlay(config, sdk, device)?;
+        }
+        if !captured {
+            self.attach_logger(config, sdk, package, device)?;
+        }
+        Ok(())
+    }
+
+    /// Saves the `--snapshot` of the emulator with the installed application if the
+    /// emulator was cold booted, so the next run boots from it.
+    fn save_emulator_snapshot(
+        &self,
+        config: &Config,
+        sdk: &AndroidSdk,
+        device: Option<&str>,
+    ) -> Result<()> {
+        let (avd, snapshot, serial) = match (&self.emulator, &self.snapshot, device) {
+            (Some(avd), Some(snapshot), Some(serial)) => (avd, snapshot, serial),
+            _ => return Ok(()),
+        };
+        if avd_snapshots(avd)?.contains(snapshot) {
+            return Ok(());
+        }
+        save_snapshot(sdk, serial, snapshot)?;
+        config.status_message("Saved snapshot", snapshot)?;
+        Ok(())
+    }
+
+    /// Shows the debug overlay through the development channel once the game starts it.
+    fn show_debug_overlay(
+        &self,
+        config: &Config,
+        sdk: &AndroidSdk,
+        device: Option<&str>,
+    ) -> Result<()> {
+        forward_tcp_port(sdk, DEFAULT_DEV_CHANNEL_PORT, device)?;
+        let token = dev_channel_token(None)?;
+        let client = DevChannelClient::new(DEFAULT_DEV_CHANNEL_PORT, &token);
+        client.wait(std::time::Duration::from_secs(10))?;
+        let output = client.command("overlay on")?;
+        config.status_message("Debug overlay", output)?;
+        Ok(())
+    }
+
+    /// Streams logcat output of the application process until Ctrl-C is pressed.
+    fn attach_logger(
+        &self,
+        config: &Config,
+        sdk: &AndroidSdk,
+        package: &str,
+        device: Option<&str>,
+    ) -> Result<()> {
+        if !self.log && !self.diagnose {
+            return Ok(());
+        }
+        config.status("Attaching logger")?;
+        let pid = wait_for_app_pid(sdk, package, device, std::time::Duration::from_secs(10))?;
+        config.status_message("Application started with PID", pid)?;
+        if !self.diagnose {
+            attach_logger_to_pid(sdk, pid, device, &self.log_filter)?;
+            return Ok(());
+        }
+        let mut logcat = Logcat::new();
+        logcat.pid(pid);
+        if let Some(device) = device {
+            logcat.device(device);
+        }
+        let mut diagnostics = Diagnostics::new(true);
+        logcat.stream(sdk, |line| {
+            if let Ok(line) = line {
+                line.print(&mut config.shell())?;
+                diagnostics.check(config, &line)?;
+            }
+            Ok(())
+        })?;
         Ok(())
     }
 }
@@ -0,0 +1,77 @@
+use crate::error::*;
+use clap::Parser;
+use crossbundle_tools::{commands::DeviceAppearance, types::Config};
+use std::{path::PathBuf, time::Duration};
+
+/// Locale, font scale and dark mode the application is launched with. If several values
+/// are specified, the application is relaunched with every combination and screenshots
+/// are saved into `--screenshot-dir`.
+#[derive(Parser, Clone, Debug, Default)]
+pub struct AppearanceArgs {
+    /// Launch the application in the locale like `fr-FR`. Can be specified several times
+    #[clap(long)]
+    pub locale: Vec<String>,
+    /// Launch the application with the font scale like `1.3`. Can be specified several
+    /// times
+    #[clap(long)]
+    pub font_scale: Vec<f32>,
+    /// Launch the application in dark (`true`) or light (`false`) mode. Can be specified
+    /// several times
+    #[clap(long, parse(try_from_str))]
+    pub dark_mode: Vec<bool>,
+    /// Directory to save screenshots of every appearance into, e.g.
+    /// `fr-FR_1.3x_dark.png`
+    #[clap(long)]
+    pub screenshot_dir: Option<PathBuf>,
+    /// Seconds to wait after launch before taking the screenshot
+    #[clap(long, default_value = "5")]
+    pub settle_time: u64,
+}
+
+impl AppearanceArgs {
+    /// Returns all combinations of the specified appearance values.
+    pub fn matrix(&self) -> Result<Vec<DeviceAppearance>> {
+        let matrix = DeviceAppearance::matrix(&self.locale, &self.font_scale, &self.dark_mode);
+        if matrix.len() > 1 && self.screenshot_dir.is_none() {
+            return Err(Error::ScreenshotDirRequired);
+        }
+        Ok(matrix)
+    }
+
+    /// Launches the application with every appearance of the matrix. `apply` sets the
+    /// appearance and relaunches the application, `screenshot` saves the screen into the
+    /// file. Returns `true` if the screenshots were captured and the run should stop.
+    pub fn launch_matrix<A, S>(
+        &self,
+        config: &Config,
+        mut apply: A,
+        mut screenshot: S,
+    ) -> Result<bool>
+    where
+        A: FnMut(&DeviceAppearance) -> Result<()>,
+        S: FnMut(&std::path::Path) -> Result<()>,
+    {
+        let matrix = self.matrix()?;
+        let screenshot_dir = match &self.screenshot_dir {
+            Some(screenshot_dir) => screenshot_dir,
+            None => {
+                let appearance = &matrix[0];
+                if *appearance != DeviceAppearance::default() {
+                    config.status_message("Applying", appearance)?;
+                }
+                apply(appearance)?;
+                return Ok(false);
+            }
+        };
+        std::fs::create_dir_all(screenshot_dir)?;
+        for appearance in &matrix {
+            config.status_message("Launching with", appearance)?;
+            apply(appearance)?;
+            std::thread::sleep(Duration::from_secs(self.settle_time));
+            let output = screenshot_dir.join(format!("{}.png", appearance.file_stem()));
+            screenshot(&output)?;
+            config.status_message("Saved screenshot", output.display())?;
+        }
+        Ok(true)
+    }
+}
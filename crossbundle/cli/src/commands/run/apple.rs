@@ -1,59 +1,317 @@
-use crate::commands::build::{apple::IosBuildCommand, BuildContext};
+use super::AppearanceArgs;
+use crate::commands::build::{apple::IosBuildCommand, run_hooks};
+use crate::commands::simulator_profile::find_simulator_profile;
 use crate::error::*;
 use clap::Parser;
-use crossbundle_tools::{commands::apple, types::Config, types::*};
-use std::path::PathBuf;
+use crossbundle_tools::{
+    commands::{
+        apple, dev_channel_token, DevChannelClient, DeviceAppearance, DEFAULT_DEV_CHANNEL_PORT,
+    },
+    error::AppleError,
+    types::Config,
+    types::*,
+};
+use std::{
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+};
 
 #[derive(Parser, Clone, Debug)]
 pub struct IosRunCommand {
     #[clap(flatten)]
     pub build_command: IosBuildCommand,
-    /// Simulator device name
+    /// Name or UDID of the simulator. If several simulators have the name, the booted
+    /// one or the one with the newest runtime is used
     #[clap(short, long, default_value = "iPhone 13")]
     pub simulator_name: String,
+    /// Launch the application on the simulator without streaming its console output
+    #[clap(long)]
+    pub no_console: bool,
     /// Run in debug mode
     #[clap(short, long)]
     pub debug: bool,
     /// Install and launch on the connected device
     #[clap(short, long, conflicts_with = "target")]
     pub device: bool,
-    /// Connected device id
+    /// Connected device id. Required if several devices are connected
     #[clap(short = 'D', long, conflicts_with = "device_name")]
     pub device_id: Option<String>,
+    /// Also stream syslog messages of the application from the connected device.
+    /// Requires libimobiledevice
+    #[clap(long, requires = "device")]
+    pub syslog: bool,
+    /// Locale, font scale and dark mode of the simulator
+    #[clap(flatten)]
+    pub appearance: AppearanceArgs,
+    /// Apply the simulator profile from `package.metadata.apple.simulator_profiles` or
+    /// the built-in `store` profile: status bar, locale, dark mode and push notification
+    #[clap(long)]
+    pub simulator_profile: Option<String>,
+    /// Show the debug overlay of the game after launch. The game must be built with
+    /// `debug-overlay` feature of crossbow and start the development channel on the
+    /// default port. Connected devices require libimobiledevice
+    #[clap(long)]
+    pub debug_overlay: bool,
 }
 
 impl IosRunCommand {
     pub fn run(&self, config: &Config) -> Result<()> {
-        let build_command = self.build_command.clone();
-        // if self.device && build_command.target.is_empty() {
-        //     build_command.target = vec![IosTarget::Aarch64];
-        // } else if build_command.target.is_empty() {
-        //     if cfg!(target_arch = "aarch64") {
-        //         build_command.target = vec![IosTarget::Aarch64];
-        //     } else {
-        //         build_command.target = vec![IosTarget::X86_64];
-        //     }
-        // }
-        let context = BuildContext::new(config, build_command.shared.target_dir.clone())?;
+        let mut build_command = self.build_command.clone();
+        if self.device && build_command.target.is_empty() {
+            build_command.target = vec![IosTarget::Aarch64];
+        }
+        let context = build_command.build_context(config)?;
+        run_hooks(
+            config,
+            &context,
+            &context.config.hooks.pre_build,
+            build_command.shared.trust,
+        )?;
         let (info_plist, app_paths) = build_command.execute(config, &context)?;
+        run_hooks(
+            config,
+            &context,
+            &context.config.hooks.post_build,
+            build_command.shared.trust,
+        )?;
         config.status("Starting run process")?;
         let bundle_id = &info_plist.identification.bundle_identifier;
         let app_path = self.get_app_path(&app_paths)?;
         if self.device {
-            config.shell().status("Launching app on connected device")?;
-            apple::run_and_debug(&app_path, self.debug, false, false, self.device_id.as_ref())?;
+            if self.appearance.matrix()? != [DeviceAppearance::default()] {
+                config
+                    .shell()
+                    .warn("Appearance options are supported only on simulators")?;
+            }
+            if self.simulator_profile.is_some() {
+                config
+                    .shell()
+                    .warn("Simulator profiles are supported only on simulators")?;
+            }
+            if self.build_command.shared.coverage {
+                config.shell().warn(
+                    "Coverage profiles are collected only from simulators and Android devices",
+                )?;
+            }
+            self.run_on_device(config, &app_path, bundle_id)?;
         } else {
-            config.status("Installing and launching application on simulator")?;
-            apple::launch_apple_app(&app_path, &self.simulator_name, bundle_id, true)?;
+            let device = Self::find_simulator(&self.simulator_name)?;
+            if !device.is_booted() {
+                config.status_message("Booting simulator", &device.name)?;
+                apple::boot_simulator(&device.udid)?;
+            }
             crossbundle_tools::types::simctl::Simctl::new()
                 .open()
                 .map_err(|err| Error::CrossbundleTools(err.into()))?;
+            config.status_message("Installing application on", &device.name)?;
+            apple::install_simulator_app(&device.udid, &app_path)?;
+            if self.build_command.shared.coverage {
+                let coverage_dir = apple::enable_simulator_coverage(&device.udid, bundle_id)?;
+                config.status_message("Writing coverage", coverage_dir.display())?;
+            }
+            let mut launch_args = Vec::new();
+            let mut push = None;
+            if let Some(name) = &self.simulator_profile {
+                let profile = find_simulator_profile(Some(&context), name)?;
+                config.status_message("Applying", format!("simulator profile {}", name))?;
+                apple::apply_simulator_profile(&device.udid, &profile)?;
+                // The locale is read by the application on launch only
+                let appearance = DeviceAppearance {
+                    locale: profile.locale.clone(),
+                    ..Default::default()
+                };
+                launch_args = apple::simulator_launch_args(&appearance);
+                push = profile.push.clone();
+            }
+            let screenshots = self.appearance.screenshot_dir.is_some();
+            let captured = self.appearance.launch_matrix(
+                config,
+                |appearance| {
+                    apple::apply_simulator_appearance(&device.udid, appearance)?;
+                    if appearance.locale.is_some() {
+                        launch_args = apple::simulator_launch_args(appearance);
+                    }
+                    // Without screenshots the application is launched once below
+                    if screenshots {
+                        apple::launch_simulator_app(&device.udid, bundle_id, &launch_args)?;
+                    }
+                    Ok(())
+                },
+                |output| Ok(apple::simulator_screenshot(&device.udid, output)?),
+            )?;
+            if !captured {
+                self.launch_on_simulator(config, &device, bundle_id, &launch_args, push)?;
+            } else if self.debug_overlay {
+                self.show_debug_overlay(config)?;
+            }
         }
         config.status("Run finished successfully")?;
         Ok(())
     }
 
+    /// Installs the application on the connected device, launches it and streams its
+    /// console output and syslog until it exits.
+    fn run_on_device(&self, config: &Config, app_path: &Path, bundle_id: &str) -> Result<()> {
+        let tool = apple::AppleDeviceTool::find()?;
+        let udid = match &self.device_id {
+            Some(udid) => udid.clone(),
+            None => {
+                let devices = apple::apple_connected_devices()?;
+                match devices.as_slice() {
+                    [device] => {
+                        config.status_message("Selected device", &device.name)?;
+                        device.udid.clone()
+                    }
+                    [] => {
+                        return Err(Error::CrossbundleTools(
+                            AppleError::ConnectedDeviceNotFound(
+                                "No connected device found, connect it with a cable and trust \
+                                 the computer"
+                                    .to_owned(),
+                            )
+                            .into(),
+                        ))
+                    }
+                    devices => {
+                        let devices: Vec<String> = devices
+                            .iter()
+                            .map(|device| format!("{} ({})", device.name, device.udid))
+                            .collect();
+                        return Err(Error::CrossbundleTools(
+                            AppleError::ConnectedDeviceNotFound(format!(
+                                "Several devices are connected, select one with `--device-id`: {}",
+                                devices.join(", ")
+                            ))
+                            .into(),
+                        ));
+                    }
+                }
+            }
+        };
+        config.status("Installing application on connected device")?;
+        apple::install_on_device(tool, &udid, app_path)?;
+        config.status("Launching application on connected device")?;
+        let mut app = apple::launch_on_device(tool, &udid, app_path, bundle_id, self.debug)?;
+        let process = app_path
+            .file_stem()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut syslog = match self.syslog {
+            true => apple::device_syslog(&udid, &process)?,
+            false => None,
+        };
+        if self.syslog && syslog.is_none() {
+            config
+                .shell()
+                .warn("Install libimobiledevice to stream the device syslog")?;
+        }
+        let mut port_forwarding = match self.debug_overlay {
+            true => apple::forward_device_port(&udid, DEFAULT_DEV_CHANNEL_PORT)?,
+            false => None,
+        };
+        if port_forwarding.is_some() {
+            self.show_debug_overlay(config)?;
+        } else if self.debug_overlay {
+            config
+                .shell()
+                .warn("Install libimobiledevice to show the debug overlay on the device")?;
+        }
+        app.wait()?;
+        if let Some(syslog) = &mut syslog {
+            syslog.kill().ok();
+        }
+        if let Some(port_forwarding) = &mut port_forwarding {
+            port_forwarding.kill().ok();
+        }
+        Ok(())
+    }
+
+    /// Shows the debug overlay through the development channel once the game starts it.
+    /// Simulators share the network of the host, so the channel is reached directly.
+    fn show_debug_overlay(&self, config: &Config) -> Result<()> {
+        let token = dev_channel_token(None)?;
+        let client = DevChannelClient::new(DEFAULT_DEV_CHANNEL_PORT, &token);
+        client.wait(std::time::Duration::from_secs(10))?;
+        let output = client.command("overlay on")?;
+        config.status_message("Debug overlay", output)?;
+        Ok(())
+    }
+
+    /// Finds the simulator by name or UDID. Fails with the list of available simulators
+    /// if there is no such one.
+    fn find_simulator(name_or_udid: &str) -> Result<apple::SimulatorDevice> {
+        let devices = apple::simulator_devices()?;
+        match apple::find_simulator(&devices, name_or_udid) {
+            Some(device) => Ok(device.clone()),
+            None => {
+                let mut names: Vec<&str> = devices
+                    .iter()
+                    .filter(|device| device.is_available)
+                    .map(|device| device.name.as_str())
+                    .collect();
+                names.sort_unstable();
+                names.dedup();
+                Err(Error::CrossbundleTools(
+                    AppleError::SimulatorNotFound(name_or_udid.to_owned(), names.join(", ")).into(),
+                ))
+            }
+        }
+    }
+
+    /// Launches the application and streams its stdout and stderr until it exits or the
+    /// run is interrupted. With `--no-console` only launches it.
+    fn launch_on_simulator(
+        &self,
+        config: &Config,
+        device: &apple::SimulatorDevice,
+        bundle_id: &str,
+        args: &[String],
+        push: Option<PathBuf>,
+    ) -> Result<()> {
+        config.status_message("Launching application on", &device.name)?;
+        if self.no_console {
+            apple::launch_simulator_app(&device.udid, bundle_id, args)?;
+            if let Some(push) = &push {
+                apple::send_simulator_push(&device.udid, bundle_id, push)?;
+            }
+            if self.debug_overlay {
+                self.show_debug_overlay(config)?;
+            }
+            return Ok(());
+        }
+        let mut console = apple::launch_simulator_console(&device.udid, bundle_id, args)?;
+        if let Some(push) = &push {
+            apple::send_simulator_push(&device.udid, bundle_id, push)?;
+        }
+        if self.debug_overlay {
+            self.show_debug_overlay(config)?;
+        }
+        config.status("Streaming console output, press Ctrl+C to stop")?;
+        if let Some(stdout) = console.stdout.take() {
+            for line in BufReader::new(stdout).lines() {
+                println!("{}", line?);
+            }
+        }
+        console.wait()?;
+        Ok(())
+    }
+
     fn get_app_path(&self, app_paths: &[PathBuf]) -> Result<PathBuf> {
+        let universal_dir = match self.device {
+            true => apple::UNIVERSAL_IOS_DIR,
+            false => apple::UNIVERSAL_IOS_SIM_DIR,
+        };
+        // Apps are built into `<target dir>/apple/<triple or universal dir>/<profile>`
+        let universal_app_path = app_paths.iter().find(|app_path| {
+            app_path
+                .parent()
+                .and_then(Path::parent)
+                .and_then(Path::file_name)
+                .map_or(false, |dir| dir == universal_dir)
+        });
+        if let Some(app_path) = universal_app_path {
+            return Ok(app_path.clone());
+        }
         if self.device || cfg!(target_arch = "aarch64") {
             Self::get_app_path_by_target(app_paths, IosTarget::Aarch64)
         } else {
@@ -1,8 +1,11 @@
 #[cfg(feature = "android")]
 mod android;
+mod appearance;
 #[cfg(feature = "apple")]
 mod apple;
 
+pub use appearance::*;
+
 use crate::error::Result;
 use clap::Parser;
 use crossbundle_tools::types::Config;
@@ -0,0 +1,112 @@
+use crate::{commands::build::BuildContext, error::*};
+use clap::Parser;
+use crossbundle_tools::{
+    commands::apple::{apply_simulator_profile, SimulatorProfile, SimulatorStateSnapshot},
+    types::Config,
+};
+use std::path::PathBuf;
+
+#[derive(Parser, Clone, Debug)]
+pub enum SimulatorProfileCommand {
+    /// Saves the current simulator state and applies the profile
+    Apply(SimulatorProfileApplyCommand),
+    /// Restores the simulator state saved by `apply` and clears the status bar override
+    Restore(SimulatorProfileRestoreCommand),
+}
+
+impl SimulatorProfileCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        match self {
+            Self::Apply(cmd) => cmd.run(config),
+            Self::Restore(cmd) => cmd.run(config),
+        }
+    }
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct SimulatorProfileApplyCommand {
+    /// Name of the profile from `package.metadata.apple.simulator_profiles` or the
+    /// built-in `store` profile
+    pub profile: String,
+    /// UDID of the simulator or `booted`
+    #[clap(long, default_value = "booted")]
+    pub simulator: String,
+}
+
+impl SimulatorProfileApplyCommand {
+    pub fn run(&self, config: &Config) -> Result<()> {
+        // Built-in profiles are available outside of the project too
+        let context = BuildContext::new(config, None).ok();
+        let profile = find_simulator_profile(context.as_ref(), &self.profile)?;
+        let snapshot_path = snapshot_path(&self.simulator)?;
+        // Keep the state saved by the first `apply` so `restore` returns to it
+        if !snapshot_path.exists() {
+            let snapshot = SimulatorStateSnapshot::capture(&self.simulator)?;
+            if let Some(parent) = snapshot_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&snapshot_path, serde_json::to_string_pretty(&snapshot)?)?;
+        }
+        apply_simulator_profile(&self.simulator, &profile)?;
+        if profile.push.is_some() {
+            config
+                .shell()
+                .note("Push notifications are sent only by `crossbundle run ios`")?;
+        }
+        config.status_message(
+            "Applied",
+            format!("simulator profile {} on {}", self.profile, self.simulator),
+        )?;
+        Ok(())
+    }
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct SimulatorProfileRestoreCommand {
+    /// UDID of the simulator or `booted`
+    #[clap(long, default_value = "booted")]
+    pub simulator: String,
+}
+
+impl SimulatorProfileRestoreCommand {
+    pub fn run(&self, config: &Config) -> Result<()> {
+        let snapshot_path = snapshot_path(&self.simulator)?;
+        if !snapshot_path.exists() {
+            return Err(Error::PathNotFound(snapshot_path));
+        }
+        let snapshot: SimulatorStateSnapshot =
+            serde_json::from_slice(&std::fs::read(&snapshot_path)?)?;
+        snapshot.restore(&self.simulator)?;
+        std::fs::remove_file(&snapshot_path)?;
+        config.status_message("Restored", format!("simulator state of {}", self.simulator))?;
+        Ok(())
+    }
+}
+
+/// Returns the profile from the project or the built-in one. Relative push payload
+/// paths are resolved against the package directory.
+pub fn find_simulator_profile(
+    context: Option<&BuildContext>,
+    name: &str,
+) -> Result<SimulatorProfile> {
+    let profiles = context
+        .map(|context| context.config.apple.simulator_profiles.clone())
+        .unwrap_or_default();
+    let mut profile = SimulatorProfile::find(&profiles, name)?;
+    if let (Some(push), Some(context)) = (&profile.push, context) {
+        let package_dir = context.package_manifest_path.parent().unwrap();
+        profile.push = Some(package_dir.join(push));
+    }
+    Ok(profile)
+}
+
+/// Returns path of the simulator state saved by `apply` in
+/// `~/.crossbundle/simulator_state/<udid>.json`.
+fn snapshot_path(simulator: &str) -> Result<PathBuf> {
+    let file_name = simulator.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "_");
+    Ok(dirs::home_dir()
+        .ok_or(Error::HomeDirNotFound)?
+        .join(".crossbundle")
+        .join("simulator_state")
+        .join(format!("{}.json", file_name)))
+}
@@ -0,0 +1,177 @@
+use crate::error::*;
+use clap::Parser;
+#[cfg(feature = "android")]
+use crossbundle_tools::{commands::android::*, types::AndroidSdk};
+use crossbundle_tools::{
+    commands::{
+        dev_channel_token, parse_overlay_stats, DevChannelClient, SoakReport, SoakSample,
+        DEFAULT_DEV_CHANNEL_PORT,
+    },
+    types::Config,
+};
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+#[derive(Parser, Clone, Debug)]
+pub struct SoakCommand {
+    /// Duration of the test in minutes
+    #[clap(long, default_value_t = 10)]
+    pub minutes: u64,
+    /// Seconds between samples
+    #[clap(long, default_value_t = 10)]
+    pub interval: u64,
+    /// Port of the development channel started by the game
+    #[clap(long, default_value_t = DEFAULT_DEV_CHANNEL_PORT)]
+    pub port: u16,
+    /// Session token the game started the development channel with. By default it's
+    /// read from `CROSSBOW_DEV_TOKEN`
+    #[clap(long)]
+    pub token: Option<String>,
+    /// Serial of the Android device or emulator. Required if several devices are
+    /// connected
+    #[clap(long, short = 'd')]
+    pub device: Option<String>,
+    /// Don't forward the port to the Android device and read the thermal state from the
+    /// game, e.g. on the iOS simulator
+    #[clap(long)]
+    pub no_forward: bool,
+    /// Path of the JSON report
+    #[clap(long, short, default_value = "target/soak/report.json")]
+    pub output: PathBuf,
+    /// Fail if the frame rate of the last quarter of the test is lower
+    #[clap(long)]
+    pub min_sustained_fps: Option<f32>,
+    /// Fail if the sustained frame rate relative to the initial one is lower, e.g. `0.8`
+    #[clap(long)]
+    pub min_sustained_ratio: Option<f32>,
+}
+
+impl SoakCommand {
+    /// Samples the frame rate and the thermal status of the running game until the
+    /// duration ends and writes the throttling report.
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        #[cfg(feature = "android")]
+        let android = match self.no_forward {
+            true => None,
+            false => {
+                let sdk = AndroidSdk::from_env()?;
+                let device = select_android_device(&sdk, self.device.as_deref())?;
+                forward_tcp_port(&sdk, self.port, Some(&device))?;
+                Some((sdk, device))
+            }
+        };
+        let token = dev_channel_token(self.token.as_deref())?;
+        let client = DevChannelClient::new(self.port, &token);
+        if client.wait(Duration::from_secs(5)).is_err() {
+            config.shell().warn(
+                "development channel is not reachable, the frame rate is not sampled. \
+                 Build the game with `debug-overlay` feature of crossbow",
+            )?;
+        }
+
+        let duration = Duration::from_secs(self.minutes * 60);
+        let interval = Duration::from_secs(self.interval.max(1));
+        config.status_message(
+            "Soaking",
+            format!(
+                "for {} minutes, sampling every {:?}",
+                self.minutes, interval
+            ),
+        )?;
+        let start = Instant::now();
+        let mut samples = Vec::new();
+        loop {
+            let elapsed = start.elapsed();
+            let (fps, thermal_status) = match client.command("overlay stats") {
+                Ok(stats) => parse_overlay_stats(&stats),
+                Err(_) => (None, None),
+            };
+            // Android 9 and older don't have the thermal service
+            #[cfg(feature = "android")]
+            let (thermal_status, temperature) = match android
+                .as_ref()
+                .and_then(|(sdk, device)| android_thermal_info(sdk, device).ok())
+            {
+                Some(info) => (info.status.or(thermal_status), info.max_temperature),
+                None => (thermal_status, None),
+            };
+            #[cfg(not(feature = "android"))]
+            let temperature = None;
+            let sample = SoakSample {
+                elapsed_secs: elapsed.as_secs(),
+                fps,
+                thermal_status,
+                temperature,
+            };
+            config.status_message(
+                "Sample",
+                format!(
+                    "{:>5}s  fps: {}  thermal: {}  temperature: {}",
+                    sample.elapsed_secs,
+                    optional(sample.fps.map(|fps| format!("{:.1}", fps))),
+                    optional(sample.thermal_status.map(|status| format!("{:?}", status))),
+                    optional(sample.temperature.map(|t| format!("{:.1}°C", t)))
+                ),
+            )?;
+            samples.push(sample);
+            if elapsed >= duration {
+                break;
+            }
+            let next = interval * samples.len() as u32;
+            std::thread::sleep(next.min(duration).saturating_sub(start.elapsed()));
+        }
+
+        let report = SoakReport::new(samples, interval.as_secs());
+        if let Some(parent) = self.output.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.output, serde_json::to_string_pretty(&report)?)?;
+        println!(
+            "Frame rate: {} initial, {} sustained, {} minimum",
+            optional(report.initial_fps.map(|fps| format!("{:.1}", fps))),
+            optional(report.sustained_fps.map(|fps| format!("{:.1}", fps))),
+            optional(report.min_fps.map(|fps| format!("{:.1}", fps)))
+        );
+        if let Some(ratio) = report.sustained_ratio {
+            println!("Sustained performance: {:.0}%", ratio * 100.0);
+        }
+        match report.throttled_after_secs {
+            Some(secs) => println!("Throttling started after {}s", secs),
+            None => println!("No throttling"),
+        }
+        for (status, secs) in &report.thermal_status_secs {
+            println!("    {:<10} {}s", format!("{:?}", status), secs);
+        }
+        config.status_message("Report saved to", self.output.display())?;
+
+        let mut failures = Vec::new();
+        if let (Some(min), Some(fps)) = (self.min_sustained_fps, report.sustained_fps) {
+            if fps < min {
+                failures.push(format!(
+                    "sustained frame rate {:.1} is lower than {}",
+                    fps, min
+                ));
+            }
+        }
+        if let (Some(min), Some(ratio)) = (self.min_sustained_ratio, report.sustained_ratio) {
+            if ratio < min {
+                failures.push(format!(
+                    "sustained performance {:.2} is lower than {}",
+                    ratio, min
+                ));
+            }
+        }
+        if !failures.is_empty() {
+            return Err(
+                crossbundle_tools::error::Error::SustainedPerformance(failures.join(", ")).into(),
+            );
+        }
+        Ok(())
+    }
+}
+
+fn optional(value: Option<String>) -> String {
+    value.unwrap_or_else(|| "-".to_owned())
+}
@@ -0,0 +1,131 @@
+use crate::commands::build::android::AndroidBuildCommand;
+use crate::error::*;
+use clap::Parser;
+use crossbundle_tools::{
+    commands::{android::*, find_workspace_cargo_manifest_path},
+    error::AndroidError,
+    types::{AndroidTarget, Config, Profile},
+};
+use std::{io::Read, path::PathBuf};
+
+#[derive(Parser, Clone, Debug)]
+pub struct SymbolicateCommand {
+    /// File with the tombstone or logcat output of the crash. Reads standard input if
+    /// not specified
+    pub input: Option<PathBuf>,
+    /// Read the crash buffer of the connected device instead of the file
+    #[clap(long, conflicts_with = "input")]
+    pub from_device: bool,
+    /// Serial of the device or emulator to read the crash buffer from
+    #[clap(long, short = 'd')]
+    pub device: Option<String>,
+    /// Target of the crashed application. Detected by the `ABI:` line of the tombstone
+    /// if not specified
+    #[clap(long, short)]
+    pub target: Option<AndroidTarget>,
+    /// Look up libraries built with the release profile
+    #[clap(long, short)]
+    pub release: bool,
+    /// Directory for generated artifact and intermediate files
+    #[clap(long)]
+    pub target_dir: Option<PathBuf>,
+    /// Directory with unstripped `.so` libraries. Can be specified several times. By
+    /// default cargo output directories of the target are used
+    #[clap(long)]
+    pub symbols: Vec<PathBuf>,
+}
+
+impl SymbolicateCommand {
+    /// Resolves native backtraces of the crash with `ndk-stack`, or with
+    /// `llvm-symbolizer` if the NDK doesn't provide it.
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        let (sdk, ndk) =
+            AndroidBuildCommand::project_android_toolchain(config, self.target_dir.clone())?;
+        let tombstone = if self.from_device {
+            dump_crash_buffer(&sdk, self.device.as_deref())?
+        } else if let Some(input) = &self.input {
+            std::fs::read_to_string(input)?
+        } else {
+            let mut tombstone = String::new();
+            std::io::stdin().read_to_string(&mut tombstone)?;
+            tombstone
+        };
+        if !tombstone
+            .lines()
+            .any(|line| parse_backtrace_frame(line).is_some())
+        {
+            config
+                .shell()
+                .warn("No native backtrace found in the input")?;
+            return Ok(());
+        }
+        let symbol_dirs = if self.symbols.is_empty() {
+            let target = match self.target.or_else(|| tombstone_abi(&tombstone)) {
+                Some(target) => target,
+                None => {
+                    return Err(Error::InvalidMetadata(anyhow::anyhow!(
+                        "failed to detect the target of the crash, specify it with `--target`"
+                    )))
+                }
+            };
+            self.symbol_dirs(config, target)?
+        } else {
+            self.symbols.clone()
+        };
+        if symbol_dirs.is_empty() {
+            return Err(Error::InvalidMetadata(anyhow::anyhow!(
+                "unstripped libraries not found, build the application or specify the \
+                 directory with `--symbols`"
+            )));
+        }
+        for dir in &symbol_dirs {
+            config.status_message("Using symbols from", dir.display())?;
+        }
+        let symbolicated = match find_ndk_stack(&ndk) {
+            // ndk-stack accepts a single symbol directory
+            Some(script) if symbol_dirs.len() == 1 => {
+                let input = self.input_file(config, &tombstone)?;
+                ndk_stack(&script, &symbol_dirs[0], &input)?
+            }
+            _ => {
+                let symbolizer = find_llvm_symbolizer(&ndk).ok_or_else(|| {
+                    Error::CrossbundleTools(
+                        AndroidError::SymbolizerNotFound(ndk.ndk_path().to_owned()).into(),
+                    )
+                })?;
+                symbolize_tombstone(&symbolizer, &tombstone, &symbol_dirs)?
+            }
+        };
+        print!("{}", symbolicated);
+        Ok(())
+    }
+
+    /// Cargo output directories of the target in the workspace.
+    fn symbol_dirs(&self, config: &Config, target: AndroidTarget) -> Result<Vec<PathBuf>> {
+        let target_dir = match &self.target_dir {
+            Some(target_dir) => target_dir.clone(),
+            None => find_workspace_cargo_manifest_path(config.current_dir())?
+                .parent()
+                .unwrap()
+                .join("target"),
+        };
+        let profile = if self.release {
+            Profile::Release
+        } else {
+            Profile::Debug
+        };
+        Ok(unstripped_lib_dirs(&target_dir, target, &profile))
+    }
+
+    /// Returns the input file for ndk-stack. The crash read from the device or standard
+    /// input is saved into the temporary directory.
+    fn input_file(&self, config: &Config, tombstone: &str) -> Result<PathBuf> {
+        if let Some(input) = &self.input {
+            return Ok(input.clone());
+        }
+        let path = std::env::temp_dir().join("crossbundle-tombstone.txt");
+        std::fs::write(&path, tombstone)?;
+        config.status_message("Saved crash to", path.display())?;
+        Ok(path)
+    }
+}
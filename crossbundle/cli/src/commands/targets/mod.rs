@@ -0,0 +1,72 @@
+use crate::{commands::build::BuildContext, error::*};
+use clap::Parser;
+use crossbundle_tools::{
+    commands::{target_matrix, TargetCapability},
+    types::Config,
+};
+
+#[derive(Parser, Clone, Debug)]
+pub struct TargetsCommand {
+    /// Print the matrix as JSON to stdout
+    #[clap(long)]
+    pub json: bool,
+    /// Show only targets that can be built
+    #[clap(long)]
+    pub available: bool,
+}
+
+impl TargetsCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        let (ndk_version, min_ndk_version) = BuildContext::ndk_versions(config)?;
+        let matrix: Vec<TargetCapability> =
+            target_matrix(ndk_version.as_deref(), min_ndk_version.as_deref())
+                .into_iter()
+                .filter(|capability| !self.available || capability.can_build())
+                .collect();
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&matrix)?);
+            return Ok(());
+        }
+        println!(
+            "{:<14} {:<26} {:<6} {:<10} {:<8} {}",
+            "PLATFORM", "TARGET", "RUST", "TOOLCHAIN", "SIGNING", "STATUS"
+        );
+        for capability in &matrix {
+            let status = if capability.is_ready() {
+                "ready"
+            } else if capability.can_build() {
+                "build only"
+            } else {
+                "unavailable"
+            };
+            println!(
+                "{:<14} {:<26} {:<6} {:<10} {:<8} {}",
+                capability.platform,
+                capability.target,
+                yes_no(capability.rust_target),
+                yes_no(capability.toolchain),
+                yes_no(capability.signing),
+                status
+            );
+        }
+        // Fixes are often shared by all targets of the platform
+        let mut fixes: Vec<&String> = matrix
+            .iter()
+            .flat_map(|capability| &capability.missing)
+            .collect();
+        fixes.sort();
+        fixes.dedup();
+        for fix in fixes {
+            config.shell().note(fix)?;
+        }
+        Ok(())
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
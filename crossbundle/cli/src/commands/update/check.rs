@@ -1,5 +1,5 @@
 use crate::error::*;
-use crossbundle_tools::types::{Config, Version};
+use crossbundle_tools::types::{Config, SemVer};
 
 static VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -50,47 +50,22 @@ pub fn print_latest_version_using(version_string: &str, config: &Config) -> Resu
 }
 
 /// Parse the crossbundle project version used by the user and compare it with the latest
-/// available version. Return true if the user has the latest version
-fn is_same(version1: &str, version2: &str, default_result: bool) -> bool {
-    let version1 = Version::from_semver(version1);
-    match version1 {
-        Ok(values1) => {
-            let version2 = Version::from_semver(version2);
-
-            match version2 {
-                Ok(values2) => {
-                    values1.major == values2.major
-                        && values1.minor == values2.minor
-                        && values1.patch == values2.patch
-                }
-                _ => default_result,
-            }
-        }
+/// available version. Return true if the user has the latest version. Build metadata is
+/// ignored
+pub fn is_same(version1: &str, version2: &str, default_result: bool) -> bool {
+    match (version1.parse::<SemVer>(), version2.parse::<SemVer>()) {
+        (Ok(version1), Ok(version2)) => version1.is_same_as(&version2),
         _ => default_result,
     }
 }
 
 /// Parse the crossbundle project version used by the user and compare it with the latest
-/// available version
-fn is_newer(old_string: &str, new_string: &str, default_result: bool) -> bool {
-    let old_version = Version::from_semver(old_string);
-    if let Ok(old_values) = old_version {
-        let new_version = Version::from_semver(new_string);
-        if let Ok(new_values) = new_version {
-            if new_values.major == old_values.major {
-                if new_values.minor > old_values.minor {
-                    true
-                } else {
-                    new_values.minor == old_values.minor && new_values.patch > old_values.patch
-                }
-            } else {
-                new_values.major > old_values.major
-            }
-        } else {
-            default_result
-        }
-    } else {
-        default_result
+/// available version. Pre-releases have lower precedence than the release, so
+/// `0.2.0-rc.1` is older than `0.2.0`
+pub fn is_newer(old_string: &str, new_string: &str, default_result: bool) -> bool {
+    match (old_string.parse::<SemVer>(), new_string.parse::<SemVer>()) {
+        (Ok(old_version), Ok(new_version)) => new_version.is_newer_than(&old_version),
+        _ => default_result,
     }
 }
 
@@ -136,3 +111,41 @@ fn get_version_from_output(line: &str) -> Option<String> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn version_comparison_is_consistent(
+            old in (0u8.., 0u8.., 0u8..),
+            new in (0u8.., 0u8.., 0u8..),
+        ) {
+            let old_string = format!("{}.{}.{}", old.0, old.1, old.2);
+            let new_string = format!("{}.{}.{}", new.0, new.1, new.2);
+            prop_assert_eq!(is_newer(&old_string, &new_string, false), new > old);
+            prop_assert_eq!(is_same(&old_string, &new_string, false), new == old);
+            prop_assert!(
+                !(is_newer(&old_string, &new_string, false) && is_newer(&new_string, &old_string, false))
+            );
+        }
+
+        #[test]
+        fn prerelease_is_older_than_release(major in 0u8.., minor in 0u8.., patch in 0u8.., rc in 1u8..) {
+            let release = format!("{}.{}.{}", major, minor, patch);
+            let prerelease = format!("{}-rc.{}", release, rc);
+            prop_assert!(is_newer(&prerelease, &release, false));
+            prop_assert!(!is_newer(&release, &prerelease, false));
+            prop_assert!(is_same(&release, &format!("{}+build.{}", release, rc), false));
+        }
+
+        #[test]
+        fn malformed_versions_dont_panic(old in ".*", new in ".*") {
+            is_newer(&old, &new, false);
+            is_same(&old, &new, false);
+            get_version_from_output(&old);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{
+    commands::build::{record_audit_event, BuildContext},
+    error::*,
+};
+use clap::Parser;
+use crossbundle_tools::{
+    commands::apple::{AppStoreAuth, UploadApp, UNIVERSAL_IOS_DIR},
+    types::{Config, IosTarget},
+};
+use std::path::PathBuf;
+
+/// Environment variable with the App Store Connect API key ID.
+pub(crate) const API_KEY_ID_ENV: &str = "APP_STORE_CONNECT_KEY_ID";
+/// Environment variable with the App Store Connect API issuer ID.
+pub(crate) const API_ISSUER_ID_ENV: &str = "APP_STORE_CONNECT_ISSUER_ID";
+
+#[derive(Parser, Clone, Debug)]
+pub struct IosUploadCommand {
+    /// IPA to upload. By default the release build for `aarch64-apple-ios` of the
+    /// project is uploaded
+    pub ipa: Option<PathBuf>,
+    /// App Store Connect API key ID. `APP_STORE_CONNECT_KEY_ID` environment variable is
+    /// used if not specified. The key is read from `AuthKey_<key id>.p8` file in
+    /// `~/.appstoreconnect/private_keys`
+    #[clap(long)]
+    pub api_key_id: Option<String>,
+    /// App Store Connect API issuer ID. `APP_STORE_CONNECT_ISSUER_ID` environment
+    /// variable is used if not specified
+    #[clap(long)]
+    pub api_issuer_id: Option<String>,
+    /// Apple ID to authenticate with instead of the API key. The app-specific password
+    /// is read from `APP_STORE_CONNECT_PASSWORD` environment variable
+    #[clap(long, conflicts_with_all = &["api-key-id", "api-issuer-id"])]
+    pub username: Option<String>,
+    /// Only validate the IPA with App Store Connect without uploading it
+    #[clap(long)]
+    pub validate_only: bool,
+    /// Directory for generated artifact and intermediate files
+    #[clap(long)]
+    pub target_dir: Option<PathBuf>,
+}
+
+impl IosUploadCommand {
+    pub fn run(&self, config: &Config) -> Result<()> {
+        let ipa_path = match &self.ipa {
+            Some(ipa) => ipa.clone(),
+            None => self.default_ipa_path(config)?,
+        };
+        let auth = self.auth()?;
+        let key = auth.key().to_owned();
+        let mut upload = UploadApp::new(&ipa_path, auth);
+        upload.validate_only(self.validate_only);
+        match self.validate_only {
+            true => config.status_message("Validating", ipa_path.display())?,
+            false => config.status_message("Uploading", ipa_path.display())?,
+        };
+        let mut reported = 0;
+        upload
+            .run(|progress| {
+                // Report every 10% to keep the output short
+                let step = progress as u32 / 10 * 10;
+                if step > reported {
+                    reported = step;
+                    config.status_message("Progress", format!("{}%", step)).ok();
+                }
+            })
+            .map_err(Error::CrossbundleTools)?;
+        match self.validate_only {
+            true => config.status("IPA is valid for App Store Connect")?,
+            false => {
+                record_audit_event(config, "upload_ios", &ipa_path, &key)?;
+                config.status(
+                    "Upload finished, the build is available in TestFlight after App Store Connect processes it",
+                )?
+            }
+        };
+        Ok(())
+    }
+
+    fn auth(&self) -> Result<AppStoreAuth> {
+        if let Some(username) = &self.username {
+            return Ok(AppStoreAuth::AppleId {
+                username: username.clone(),
+            });
+        }
+        let key_id = self
+            .api_key_id
+            .clone()
+            .or_else(|| std::env::var(API_KEY_ID_ENV).ok());
+        let issuer_id = self
+            .api_issuer_id
+            .clone()
+            .or_else(|| std::env::var(API_ISSUER_ID_ENV).ok());
+        match (key_id, issuer_id) {
+            (Some(key_id), Some(issuer_id)) => Ok(AppStoreAuth::ApiKey { key_id, issuer_id }),
+            _ => Err(Error::InvalidMetadata(anyhow::anyhow!(
+                "specify `--api-key-id` and `--api-issuer-id` or `--username` to authenticate \
+                 with App Store Connect"
+            ))),
+        }
+    }
+
+    /// Returns the IPA of the release build for devices, the universal one if the app was
+    /// built for several device architectures.
+    fn default_ipa_path(&self, config: &Config) -> Result<PathBuf> {
+        let context = BuildContext::new(config, self.target_dir.clone())?;
+        let ipa_path = |dir: &str| {
+            context
+                .target_dir
+                .join("apple")
+                .join(dir)
+                .join("release")
+                .join(format!("{}.ipa", context.package_name()))
+        };
+        let universal_ipa_path = ipa_path(UNIVERSAL_IOS_DIR);
+        if universal_ipa_path.exists() {
+            return Ok(universal_ipa_path);
+        }
+        let ipa_path = ipa_path(IosTarget::Aarch64.rust_triple());
+        if !ipa_path.exists() {
+            return Err(Error::InvalidMetadata(anyhow::anyhow!(
+                "{} is not found, build it with `crossbundle build ios --release -t \
+                 aarch64-apple-ios` or specify the IPA",
+                ipa_path.display()
+            )));
+        }
+        Ok(ipa_path)
+    }
+}
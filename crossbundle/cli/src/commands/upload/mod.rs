@@ -0,0 +1,20 @@
+pub mod apple;
+
+use crate::error::Result;
+use clap::Parser;
+use crossbundle_tools::types::Config;
+
+#[derive(Parser, Clone, Debug)]
+pub enum UploadCommand {
+    /// Uploads the IPA to App Store Connect for TestFlight and App Store distribution
+    #[clap(alias = "apple")]
+    Ios(apple::IosUploadCommand),
+}
+
+impl UploadCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        match &self {
+            Self::Ios(cmd) => cmd.run(config),
+        }
+    }
+}
@@ -0,0 +1,163 @@
+#[cfg(feature = "android")]
+use super::device_profile::{with_device_profile, DeviceProfileArgs};
+use crate::error::*;
+use clap::Parser;
+#[cfg(feature = "apple")]
+use crossbundle_tools::commands::apple;
+#[cfg(feature = "android")]
+use crossbundle_tools::{commands::android::*, types::AndroidSdk};
+use crossbundle_tools::{commands::LaunchOutcome, types::Config};
+use std::{path::PathBuf, time::Duration};
+
+#[derive(Parser, Clone, Debug)]
+pub struct VerifyLaunchCommand {
+    /// Path to the APK file or `.app` bundle built for the simulator
+    pub artifact: PathBuf,
+    /// Serial of the Android device or emulator. Required if several devices are
+    /// connected
+    #[clap(long, short = 'd')]
+    pub device: Option<String>,
+    /// Name of the Apple simulator
+    #[clap(long, default_value = "iPhone 13")]
+    pub simulator_name: String,
+    /// Package name or bundle identifier. By default it's read from the artifact
+    #[clap(long)]
+    pub package: Option<String>,
+    /// Activity to launch. By default the launchable activity of the APK is used
+    #[clap(long)]
+    pub activity: Option<String>,
+    /// How many seconds to watch the application for crashes after launch
+    #[clap(long, default_value = "10")]
+    pub duration: u64,
+    /// Save screenshot of the screen after the watch period into the file
+    #[clap(long)]
+    pub screenshot: Option<PathBuf>,
+    /// Device state applied before the launch and restored afterwards
+    #[cfg(feature = "android")]
+    #[clap(flatten)]
+    pub device_profile: DeviceProfileArgs,
+}
+
+impl VerifyLaunchCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        let extension = self
+            .artifact
+            .extension()
+            .and_then(|extension| extension.to_str());
+        let outcome = match extension {
+            #[cfg(feature = "android")]
+            Some("apk") => self.verify_android(config)?,
+            #[cfg(feature = "apple")]
+            Some("app") => self.verify_apple(config)?,
+            _ => {
+                return Err(Error::InvalidMetadata(anyhow::anyhow!(
+                    "unsupported artifact {:?}, use APK file or `.app` bundle",
+                    self.artifact
+                )))
+            }
+        };
+        match &outcome {
+            LaunchOutcome::Crashed(log) | LaunchOutcome::NotResponding(log) => {
+                config.shell().error(log)?;
+            }
+            _ => {}
+        }
+        if !outcome.is_success() {
+            return Err(crossbundle_tools::error::Error::LaunchVerificationFailed(
+                outcome.to_string(),
+            )
+            .into());
+        }
+        config.status_message("Verified", outcome)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "android")]
+    fn verify_android(&self, config: &Config) -> Result<LaunchOutcome> {
+        let sdk = AndroidSdk::from_env()?;
+        let device = select_android_device(&sdk, self.device.as_deref())?;
+        config.status_message(
+            "Installing",
+            format!("{} on {}", self.artifact.display(), device),
+        )?;
+        install_apk(&sdk, &self.artifact, Some(&device))?;
+        with_device_profile(
+            config,
+            &sdk,
+            &device,
+            self.device_profile.to_profile(),
+            || self.launch_android(config, &sdk, &device),
+        )
+    }
+
+    #[cfg(feature = "android")]
+    fn launch_android(
+        &self,
+        config: &Config,
+        sdk: &AndroidSdk,
+        device: &str,
+    ) -> Result<LaunchOutcome> {
+        let badging = apk_badging(sdk, &self.artifact)?;
+        // `apk_badging` fails without the package name
+        let package = self.package.clone().or(badging.package).unwrap_or_default();
+        let activity = self
+            .activity
+            .clone()
+            .or(badging.launchable_activity)
+            .unwrap_or_else(|| "android.app.NativeActivity".to_owned());
+        config.status_message("Launching", &package)?;
+        // Logs of previous runs are skipped by the device time of the launch
+        let launch_time = device_log_time(sdk, Some(device))?;
+        start_app(sdk, &package, &activity, Some(device))?;
+        let pid = wait_for_app_pid(sdk, &package, Some(device), Duration::from_secs(10))?;
+        config.status_message(
+            "Watching",
+            format!("PID {} for {} seconds", pid, self.duration),
+        )?;
+        let outcome = watch_android_launch(
+            sdk,
+            &package,
+            pid,
+            Some(device),
+            &launch_time,
+            Duration::from_secs(self.duration),
+        )?;
+        if let Some(screenshot) = &self.screenshot {
+            android_screenshot(sdk, Some(device), screenshot)?;
+            config.status_message("Saved screenshot", screenshot.display())?;
+        }
+        Ok(outcome)
+    }
+
+    #[cfg(feature = "apple")]
+    fn verify_apple(&self, config: &Config) -> Result<LaunchOutcome> {
+        let bundle_id = match &self.package {
+            Some(bundle_id) => bundle_id.clone(),
+            None => {
+                apple::read_info_plist(&self.artifact.join("Info.plist"))?
+                    .identification
+                    .bundle_identifier
+            }
+        };
+        config.status_message(
+            "Launching",
+            format!("{} on {}", bundle_id, self.simulator_name),
+        )?;
+        let device =
+            apple::launch_apple_app(&self.artifact, &self.simulator_name, &bundle_id, false)?;
+        config.status_message(
+            "Watching",
+            format!("{} for {} seconds", bundle_id, self.duration),
+        )?;
+        let outcome = apple::watch_simulator_launch(
+            &device.udid,
+            &bundle_id,
+            Duration::from_secs(self.duration),
+        )?;
+        if let Some(screenshot) = &self.screenshot {
+            apple::simulator_screenshot(&device.udid, screenshot)?;
+            config.status_message("Saved screenshot", screenshot.display())?;
+        }
+        Ok(outcome)
+    }
+}
@@ -0,0 +1,76 @@
+use crate::error::*;
+use clap::Parser;
+use crossbundle_tools::{
+    commands::{
+        cosign_verify_blob, sigstore_bundle_path, CosignIdentity, OutputManifest,
+        OUTPUT_MANIFEST_NAME,
+    },
+    types::Config,
+};
+use std::path::{Path, PathBuf};
+
+#[derive(Parser, Clone, Debug)]
+pub struct VerifyProvenanceCommand {
+    /// Path to the downloaded artifact
+    pub artifact: PathBuf,
+    /// Path to the sigstore bundle. By default `<artifact>.sigstore.json`
+    #[clap(long)]
+    pub bundle: Option<PathBuf>,
+    /// Path to the output manifest. By default `crossbundle-outputs.json` next to the
+    /// artifact is verified if it exists
+    #[clap(long)]
+    pub output_manifest: Option<PathBuf>,
+    /// Path to the cosign public key used to verify key-based signatures
+    #[clap(
+        long,
+        required_unless_present = "certificate-identity",
+        conflicts_with_all = &["certificate-identity", "certificate-oidc-issuer"]
+    )]
+    pub key: Option<PathBuf>,
+    /// Identity of the keyless signature, e.g. the workflow URL of the CI that built the
+    /// artifact
+    #[clap(long, requires = "certificate-oidc-issuer")]
+    pub certificate_identity: Option<String>,
+    /// OIDC issuer of the keyless signature, e.g.
+    /// `https://token.actions.githubusercontent.com`
+    #[clap(long, requires = "certificate-identity")]
+    pub certificate_oidc_issuer: Option<String>,
+}
+
+impl VerifyProvenanceCommand {
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        let identity = match &self.key {
+            Some(key) => CosignIdentity::Key(key.clone()),
+            // Clap guarantees that identity and issuer are specified without the key
+            None => CosignIdentity::Keyless {
+                identity: self.certificate_identity.clone().unwrap_or_default(),
+                issuer: self.certificate_oidc_issuer.clone().unwrap_or_default(),
+            },
+        };
+        let bundle = self
+            .bundle
+            .clone()
+            .unwrap_or_else(|| sigstore_bundle_path(&self.artifact));
+        cosign_verify_blob(&self.artifact, &bundle, &identity)?;
+        config.status_message("Verified", self.artifact.display())?;
+
+        let output_manifest = self.output_manifest.clone().or_else(|| {
+            let path = self
+                .artifact
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .join(OUTPUT_MANIFEST_NAME);
+            path.exists().then_some(path)
+        });
+        if let Some(output_manifest) = output_manifest {
+            cosign_verify_blob(
+                &output_manifest,
+                &sigstore_bundle_path(&output_manifest),
+                &identity,
+            )?;
+            OutputManifest::read(&output_manifest)?.verify_artifact(&self.artifact)?;
+            config.status_message("Verified", output_manifest.display())?;
+        }
+        Ok(())
+    }
+}
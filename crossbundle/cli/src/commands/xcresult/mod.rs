@@ -0,0 +1,70 @@
+use crate::error::*;
+use clap::Parser;
+use crossbundle_tools::{
+    commands::apple::{read_xcresult, XcTestResult},
+    types::Config,
+};
+use std::path::PathBuf;
+
+#[derive(Parser, Clone, Debug)]
+pub struct XcresultCommand {
+    /// Result bundle written by `xcodebuild test -resultBundlePath <path>`
+    pub bundle: PathBuf,
+    /// Export attachments like failure screenshots into the directory
+    #[clap(long)]
+    pub attachments: Option<PathBuf>,
+    /// Print the results as JSON
+    #[clap(long)]
+    pub json: bool,
+}
+
+impl XcresultCommand {
+    /// Prints failed tests with their messages, totals and coverage. Fails if any test
+    /// failed.
+    pub fn handle_command(&self, config: &Config) -> Result<()> {
+        let summary = read_xcresult(&self.bundle, self.attachments.as_deref())?;
+        let failed = summary.count(XcTestResult::Failed);
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        } else {
+            config.status_message("Read", self.bundle.display())?;
+            for test in summary.failed() {
+                println!("FAILED {}", test.identifier);
+                for failure in &test.failures {
+                    println!("    {}", failure);
+                }
+                for attachment in &summary.attachments {
+                    if attachment.test == test.identifier {
+                        println!("    {}: {}", attachment.name, attachment.path.display());
+                    }
+                }
+            }
+            println!(
+                "Tests: {} passed, {} failed, {} skipped, {} expected failures",
+                summary.count(XcTestResult::Passed),
+                failed,
+                summary.count(XcTestResult::Skipped),
+                summary.count(XcTestResult::ExpectedFailure)
+            );
+            if let Some(coverage) = &summary.coverage {
+                println!(
+                    "Coverage: {:.1}% ({} of {} lines)",
+                    coverage.line_coverage * 100.0,
+                    coverage.covered_lines,
+                    coverage.executable_lines
+                );
+                for target in &coverage.targets {
+                    println!(
+                        "    {:>6.1}%  {}",
+                        target.line_coverage * 100.0,
+                        target.name
+                    );
+                }
+            }
+        }
+        if failed > 0 {
+            return Err(Error::TestsFailed(failed));
+        }
+        Ok(())
+    }
+}
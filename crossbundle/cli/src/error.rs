@@ -1,3 +1,4 @@
+use crossbundle_tools::error::{error_code, ErrorKind};
 #[cfg(feature = "android")]
 use crossbundle_tools::types::android_manifest;
 use displaydoc::Display;
@@ -11,6 +12,8 @@ pub enum Error {
     CantFindTargetToRun,
     /// Team identifier not provided
     TeamIdentifierNotProvided,
+    /// `--screenshot-dir` is required to launch the application with several appearances
+    ScreenshotDirRequired,
     /// Invalid metadata in manifest: {0:?}
     InvalidMetadata(anyhow::Error),
     /// IO error: {0:?}
@@ -21,6 +24,8 @@ pub enum Error {
     AnyhowError(#[from] anyhow::Error),
     /// Crossbundle Tools error: {0:?}
     CrossbundleTools(#[from] crossbundle_tools::error::Error),
+    /// Crossbow error: {0:?}
+    Crossbow(#[from] crossbow::error::CrossbowError),
     /// AndroidManifest error: {0:?}
     #[cfg(feature = "android")]
     AndroidManifest(#[from] android_manifest::error::Error),
@@ -30,6 +35,12 @@ pub enum Error {
     PathNotFound(std::path::PathBuf),
     /// Home dir not found
     HomeDirNotFound,
+    /// {0} environment check(s) failed. See suggested fixes above
+    DoctorChecksFailed(usize),
+    /// {0} test(s) failed. See failures above
+    TestsFailed(usize),
+    /// JSON error: {0:?}
+    Json(#[from] serde_json::Error),
     /// Failed to download jar file: {0:?}
     DownloadFailed(ureq::Error),
     /// Failed to create jar file in specified path `{path}` cause of `{cause}`
@@ -42,6 +53,50 @@ pub enum Error {
         path: std::path::PathBuf,
         cause: std::io::Error,
     },
+    /// Failed to download `{url}` cause of `{cause}`. Run the command again to resume the download
+    ChunkDownloadFailed { url: String, cause: std::io::Error },
+    /// SHA-256 hash of the downloaded file `{path}` is `{actual}`, but `{expected}` was expected
+    ChecksumMismatch {
+        path: std::path::PathBuf,
+        expected: String,
+        actual: String,
+    },
+    /// No SHA-256 hash is known for `{url}`. Pass the expected hash with `--sha256` to download it
+    UnknownChecksum { url: String },
+}
+
+impl Error {
+    /// Returns category of the error that determines the exit code.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::CrossbundleTools(error) => error.kind(),
+            Self::CantFindTargetToRun
+            | Self::TeamIdentifierNotProvided
+            | Self::ScreenshotDirRequired
+            | Self::InvalidMetadata(_)
+            | Self::Clap(_)
+            | Self::UnknownChecksum { .. }
+            | Self::PathNotFound(_) => ErrorKind::Config,
+            #[cfg(feature = "android")]
+            Self::AndroidManifest(_) => ErrorKind::Config,
+            Self::HomeDirNotFound
+            | Self::DoctorChecksFailed(_)
+            | Self::DownloadFailed(_)
+            | Self::ChunkDownloadFailed { .. }
+            | Self::ChecksumMismatch { .. }
+            | Self::JarFileCreationFailed { .. } => ErrorKind::Environment,
+            Self::TestsFailed(_) => ErrorKind::Device,
+            _ => ErrorKind::Internal,
+        }
+    }
+
+    /// Returns stable machine-readable code of the error, e.g. `android_ndk_not_found`.
+    pub fn code(&self) -> String {
+        match self {
+            Self::CrossbundleTools(error) => error.code(),
+            _ => error_code(self),
+        }
+    }
 }
 
 // TODO: Fix this. Is there a better casting for it?
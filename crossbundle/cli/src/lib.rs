@@ -64,11 +64,30 @@ pub fn run() -> std::result::Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Prints the error and exits with the code of its [`ErrorKind`](crossbundle_tools::error::ErrorKind).
+/// If `CROSSBUNDLE_ERROR_FORMAT=json` environment variable is set, the error is also
+/// printed to stderr as a single JSON line.
 pub fn handle_errors(run: impl FnOnce() -> std::result::Result<(), Box<dyn std::error::Error>>) {
     if let Err(error) = run() {
         eprintln!("{}: {}", "error".red().bold(), error);
         handle_error_source(error.source());
-        std::process::exit(1);
+        let (kind, code) = match error.downcast_ref::<error::Error>() {
+            Some(error) => (error.kind(), error.code()),
+            None => (
+                crossbundle_tools::error::ErrorKind::Internal,
+                "internal".to_owned(),
+            ),
+        };
+        if std::env::var("CROSSBUNDLE_ERROR_FORMAT").as_deref() == Ok("json") {
+            let json = serde_json::json!({
+                "kind": kind,
+                "code": code,
+                "exit_code": kind.exit_code(),
+                "message": error.to_string(),
+            });
+            eprintln!("{}", json);
+        }
+        std::process::exit(kind.exit_code());
     };
 }
 
@@ -1,9 +1,12 @@
 use crossbundle_tools::{
     commands::android::*,
-    types::{android_manifest::AndroidManifest, AndroidTarget, AppWrapper},
+    types::{
+        android_manifest::AndroidManifest, AndroidTarget, AppWrapper, MultiWindowConfig, Profile,
+        VersionCodeStrategy,
+    },
 };
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
 
 /// Full Android configuration.
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
@@ -35,7 +38,154 @@ pub struct AndroidConfig {
     /// Android release build targets.
     #[serde(default)]
     pub release_build_targets: Vec<AndroidTarget>,
+    /// Adaptive launcher icon for Android 8.0 and higher.
+    pub adaptive_icon: Option<AdaptiveIconConfig>,
+    /// String resources keyed by locale like `default`, `fr` or `pt-BR`. Generated into
+    /// `values-<locale>/strings.xml`. If `app_name` string is specified, it's used as the
+    /// application label.
+    #[serde(default)]
+    pub strings: LocalizedStrings,
+    /// Splash screen image shown centered while the application starts.
+    pub splash_image: Option<PathBuf>,
+    /// Splash screen background color like `#000000`. By default `#000000` if
+    /// `splash_image` is specified.
+    pub splash_color: Option<String>,
+    /// Resizeable activity and freeform window layout for foldables, tablets and
+    /// Chromebooks.
+    pub multi_window: Option<MultiWindowConfig>,
+    /// Compiled Java bytecode: `.class` files, `.jar` archives or directories with them.
+    /// Compiled into `classes.dex` with d8 and packaged by the native build strategies.
+    #[serde(default)]
+    pub java_inputs: Vec<PathBuf>,
+    /// Java sources: `.java` files or directories with them. Compiled with javac against
+    /// `android.jar` and `java_inputs` archives, then compiled into `classes.dex`.
+    #[serde(default)]
+    pub java_sources: Vec<PathBuf>,
+    /// Kotlin sources: `.kt` files or directories with them. Compiled with kotlinc
+    /// together with the Kotlin standard library, then compiled into `classes.dex`.
+    #[serde(default)]
+    pub kotlin_sources: Vec<PathBuf>,
+    /// Shrink and obfuscate Java code with R8 in release builds. `mapping.txt` with
+    /// obfuscated names is written next to the build artifacts.
+    #[serde(default)]
+    pub shrink_code: bool,
+    /// Files with R8 keep rules in ProGuard format.
+    #[serde(default)]
+    pub proguard_files: Vec<PathBuf>,
+    /// Inline R8 keep rules, e.g. `-keep class com.crossbow.game.Bridge { *; }`.
+    #[serde(default)]
+    pub keep_rules: Vec<String>,
+    /// Shorten resource paths like `res/drawable-xxhdpi/background.png` to `res/a1.png`
+    /// with `aapt2 optimize` in release APK builds. `resources-map.txt` with the original
+    /// paths is written next to the build artifacts.
+    #[serde(default)]
+    pub shorten_resource_paths: bool,
+    /// Collapse resource entry names with `aapt2 optimize` in release APK builds, so the
+    /// shipped resource table doesn't reveal them.
+    #[serde(default)]
+    pub collapse_resource_names: bool,
+    /// Resources like `string/app_name` that keep their names with
+    /// `collapse_resource_names`, e.g. the ones looked up with `getIdentifier`.
+    #[serde(default)]
+    pub no_collapse_resources: Vec<String>,
+    /// Path to the stable resource IDs file relatively to project path, e.g.
+    /// `android/stable-ids.txt`. IDs of the previous builds are reused by `aapt2 link`
+    /// with `native-aab` strategy and IDs of new resources are added to the file. Commit
+    /// it, so resource IDs don't change between releases.
+    pub stable_ids: Option<PathBuf>,
+    /// Strip native libraries with `llvm-strip` in release builds of native strategies.
+    /// Unstripped copies are kept in `target/crossbundle/symbols/<version>/<abi>` for
+    /// symbolication of crashes.
+    #[serde(default)]
+    pub strip_symbols: bool,
+    /// Dynamic feature modules packed into the AAB with `native-aab` strategy.
+    #[serde(default)]
+    pub dynamic_features: Vec<DynamicFeature>,
+    /// Play Asset Delivery asset packs packed into the AAB with `native-aab` strategy.
+    #[serde(default)]
+    pub asset_packs: Vec<AssetPack>,
+    /// How the version code is generated: `manifest`, `semver`, `git-commit-count` or
+    /// `timestamp`. Overrides `version_code` of the manifest unless it's `manifest`.
+    #[serde(default)]
+    pub version_code_strategy: VersionCodeStrategy,
+    /// NDK version installed by `crossbundle install android`, e.g. `23.1.7779620`. Builds
+    /// use this version from `$ANDROID_SDK/ndk/<version>` if specified.
+    pub ndk_version: Option<String>,
+    /// Minimum NDK version required by the project, e.g. `23` or `25.1.8937393`.
+    pub min_ndk_version: Option<String>,
+    /// Build tools version installed by `crossbundle install android`, e.g. `31.0.0`.
+    pub build_tools_version: Option<String>,
+    /// Build profiles keyed by name, selected with `--profile <name>` flag.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, AndroidBuildProfile>,
+    /// Emulator profiles keyed by name, selected with `--profile <name>` flag of
+    /// `crossbundle emulator start`. Override the built-in `ci` and `host` profiles.
+    #[serde(default)]
+    pub emulator_profiles: BTreeMap<String, EmulatorProfile>,
     /// Crossbow Android Plugins.
     #[serde(flatten)]
     pub plugins: AndroidGradlePlugins,
 }
+
+impl AndroidConfig {
+    /// Returns `true` if Java bytecode or sources are specified.
+    pub fn has_java_code(&self) -> bool {
+        !self.java_inputs.is_empty()
+            || !self.java_sources.is_empty()
+            || !self.kotlin_sources.is_empty()
+    }
+
+    /// Returns `true` if splash screen resources should be generated.
+    pub fn has_splash_screen(&self) -> bool {
+        self.splash_image.is_some() || self.splash_color.is_some()
+    }
+
+    /// Returns string resources with titles of the dynamic feature modules added to the
+    /// default locale.
+    pub fn strings_with_feature_titles(&self) -> LocalizedStrings {
+        let mut strings = self.strings.clone();
+        for feature in &self.dynamic_features {
+            if let Some(title) = &feature.title {
+                strings
+                    .entry(DEFAULT_STRINGS_LOCALE.to_owned())
+                    .or_default()
+                    .insert(feature.title_resource_name(), title.clone());
+            }
+        }
+        strings
+    }
+}
+
+/// Overrides of the build profile like `dev`, `staging` or `prod`. Lets one project
+/// produce several variants of the application without editing `Cargo.toml`.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct AndroidBuildProfile {
+    /// Suffix appended to the package name, e.g. `.staging`. Variants with different
+    /// package names can be installed side by side.
+    pub application_id_suffix: Option<String>,
+    /// Suffix appended to the version name, e.g. `-staging`.
+    pub version_name_suffix: Option<String>,
+    /// Cargo profile: `debug` or `release`. The `--release` flag takes priority.
+    pub cargo_profile: Option<Profile>,
+    /// Path to the signing key. Signing flags of the command take priority.
+    pub sign_key_path: Option<PathBuf>,
+    /// Signing key alias.
+    pub sign_key_alias: Option<String>,
+    /// Name of the environment variable with the signing key password, so the password
+    /// is not stored in `Cargo.toml`.
+    pub sign_key_pass_env: Option<String>,
+    /// Values of `${name}` placeholders in the manifest.
+    #[serde(default)]
+    pub manifest_placeholders: BTreeMap<String, String>,
+}
+
+/// Adaptive launcher icon configuration.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct AdaptiveIconConfig {
+    /// Foreground layer image. By default the global `icon` is used.
+    pub foreground: Option<PathBuf>,
+    /// Background layer color like `#FFFFFF` or image path. By default `#FFFFFF`.
+    pub background: Option<String>,
+    /// Monochrome layer image used for themed icons on Android 13+.
+    pub monochrome: Option<PathBuf>,
+}
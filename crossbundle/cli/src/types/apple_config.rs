@@ -1,6 +1,9 @@
-use crossbundle_tools::types::{apple_bundle::prelude::*, IosTarget};
+use crossbundle_tools::{
+    commands::apple::{OnDemandResource, SimulatorProfile},
+    types::{apple_bundle::prelude::*, InfoPlistSettings, IosTarget, MacosTarget},
+};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
 
 /// Full Apple configuration.
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
@@ -11,6 +14,18 @@ pub struct AppleConfig {
     ///
     /// **Important:** If this field specified - `info_plist` property will be ignored.
     pub info_plist_path: Option<PathBuf>,
+    /// Typed `Info.plist` settings like `device_families`, `orientations` and
+    /// `required_device_capabilities`, and any other keys in `info_plist_extra` table.
+    /// Applied on top of `info_plist` or `info_plist_path`.
+    #[serde(flatten)]
+    pub info_plist_settings: InfoPlistSettings,
+    /// Path to the entitlements plist, e.g. `Game.entitlements`. Its entitlements are
+    /// added to the application and team identifiers when the bundle is signed.
+    pub entitlements: Option<PathBuf>,
+    /// Usage descriptions by `Info.plist` key, e.g. `NSCameraUsageDescription`. Shown to
+    /// the user when the application requests the permission.
+    #[serde(default)]
+    pub usage_descriptions: BTreeMap<String, String>,
     /// Apple `resources` directory path relatively to project path.
     ///
     /// If specified more than one - all resources will be placed into one directory.
@@ -23,13 +38,42 @@ pub struct AppleConfig {
     /// **Important:** This property has higher priority than global property.
     #[serde(default)]
     pub assets: Vec<PathBuf>,
+    /// On-demand resources downloaded separately from the application when it requests
+    /// their tags.
+    #[serde(default)]
+    pub on_demand_resources: Vec<OnDemandResource>,
     /// Apple debug build targets.
     #[serde(default)]
     pub debug_build_targets: Vec<IosTarget>,
     /// Apple release build targets.
     #[serde(default)]
     pub release_build_targets: Vec<IosTarget>,
+    /// Simulator profiles keyed by name, selected with `--simulator-profile <name>`
+    /// flag of `crossbundle run apple`. Override the built-in `store` profile.
+    #[serde(default)]
+    pub simulator_profiles: BTreeMap<String, SimulatorProfile>,
+    /// Configuration of `crossbundle build macos`.
+    #[serde(default)]
+    pub macos: MacosConfig,
     // TODO: Add Apple plugins.
     // #[serde(flatten)]
     // pub plugins: ApplePlugins,
 }
+
+/// macOS application configuration.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct MacosConfig {
+    /// Minimum macOS version written into `LSMinimumSystemVersion`. `10.13` by default.
+    pub minimum_system_version: Option<String>,
+    /// App Store category written into `LSApplicationCategoryType`, e.g.
+    /// `public.app-category.games`.
+    pub category: Option<String>,
+    /// `.framework` bundles and `.dylib` libraries embedded into `Contents/Frameworks`.
+    #[serde(default)]
+    pub frameworks: Vec<PathBuf>,
+    /// PNG background of the DMG window generated with `--dmg` flag.
+    pub dmg_background: Option<PathBuf>,
+    /// macOS build targets. The host architecture is used if not specified.
+    #[serde(default)]
+    pub build_targets: Vec<MacosTarget>,
+}
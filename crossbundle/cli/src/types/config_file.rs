@@ -0,0 +1,90 @@
+use super::CrossbowMetadata;
+use crate::error::*;
+use std::path::{Path, PathBuf};
+
+/// Names of the configuration files looked up next to `Cargo.toml`. The file replaces
+/// `[package.metadata]` of the package, e.g. if the configuration is generated by another
+/// system.
+pub const CONFIG_FILE_NAMES: [&str; 3] = ["crossbow.yaml", "crossbow.yml", "crossbow.json"];
+
+/// Serialization format of the configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Detects the format by the file extension.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Ok(Self::Toml),
+            Some("yaml") | Some("yml") => Ok(Self::Yaml),
+            Some("json") => Ok(Self::Json),
+            _ => Err(Error::InvalidMetadata(anyhow::anyhow!(
+                "unsupported configuration format of {}, expected toml, yaml or json",
+                path.display()
+            ))),
+        }
+    }
+}
+
+impl CrossbowMetadata {
+    /// Returns path of the configuration file in the project directory if it exists.
+    pub fn find_config_file(project_path: &Path) -> Option<PathBuf> {
+        CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| project_path.join(name))
+            .find(|path| path.is_file())
+    }
+
+    /// Reads the configuration from the TOML, YAML or JSON file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::parse(&contents, ConfigFormat::from_path(path)?).map_err(|e| {
+            Error::InvalidMetadata(anyhow::anyhow!("failed to parse {}: {}", path.display(), e))
+        })
+    }
+
+    /// Parses the configuration in the given format.
+    pub fn parse(contents: &str, format: ConfigFormat) -> Result<Self> {
+        let metadata = match format {
+            ConfigFormat::Toml => toml::from_str(contents).map_err(anyhow::Error::from),
+            ConfigFormat::Yaml => serde_yaml::from_str(contents).map_err(anyhow::Error::from),
+            ConfigFormat::Json => serde_json::from_str(contents).map_err(anyhow::Error::from),
+        };
+        metadata.map_err(Error::InvalidMetadata)
+    }
+
+    /// Serializes the configuration in the given format.
+    pub fn serialize_to(&self, format: ConfigFormat) -> Result<String> {
+        let contents = match format {
+            // Converted into a value first, so tables are emitted after plain values
+            ConfigFormat::Toml => toml::Value::try_from(self)
+                .and_then(|value| toml::to_string_pretty(&value))
+                .map_err(anyhow::Error::from),
+            ConfigFormat::Yaml => serde_yaml::to_string(self).map_err(anyhow::Error::from),
+            ConfigFormat::Json => serde_json::to_string_pretty(self).map_err(anyhow::Error::from),
+        };
+        contents.map_err(Error::InvalidMetadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_formats() {
+        let yaml = "app_name: Game\nassets:\n  - assets\nhooks:\n  pre_build: []\n";
+        let metadata = CrossbowMetadata::parse(yaml, ConfigFormat::Yaml).unwrap();
+        assert_eq!(metadata.app_name.as_deref(), Some("Game"));
+        for format in [ConfigFormat::Toml, ConfigFormat::Yaml, ConfigFormat::Json] {
+            let contents = metadata.serialize_to(format).unwrap();
+            let parsed = CrossbowMetadata::parse(&contents, format).unwrap();
+            assert_eq!(parsed.app_name, metadata.app_name);
+            assert_eq!(parsed.assets, metadata.assets);
+        }
+    }
+}
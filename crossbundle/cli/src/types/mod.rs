@@ -2,13 +2,20 @@
 pub mod android_config;
 #[cfg(feature = "apple")]
 pub mod apple_config;
+pub mod config_file;
+#[cfg(feature = "windows")]
+pub mod windows_config;
 
 #[cfg(feature = "android")]
 pub use android_config::*;
 #[cfg(feature = "apple")]
 pub use apple_config::*;
+pub use config_file::*;
+#[cfg(feature = "windows")]
+pub use windows_config::*;
 
 use crossbow::Permission;
+use crossbundle_tools::commands::Hook;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -34,16 +41,51 @@ pub struct CrossbowMetadata {
     /// Info.plist properties.
     #[serde(default)]
     pub permissions: Vec<Permission>,
+    /// Device capabilities used by the application, e.g. `bluetooth` or `vibration`.
+    /// Required permissions, optional hardware features and usage descriptions are added
+    /// for them. Capabilities of crossbow plugins and permissions are inferred.
+    #[serde(default)]
+    pub capabilities: Vec<String>,
     /// Cross-platform icon for Android and Apple.
     ///
     /// All necessary icons will be automatically generated for Android and iOS.
     pub icon: Option<PathBuf>,
+    /// Generate `crossbow-content.json` manifest with sizes and hashes of the bundled
+    /// assets. Verify the assets at runtime with `crossbow::ContentManifest`.
+    #[serde(default)]
+    pub content_manifest: bool,
+    /// Protect the game window from screen capture. The Android activity sets
+    /// `FLAG_SECURE` on start with the Gradle build strategy. iOS doesn't block capture,
+    /// the game detects it with `crossbow::ios::capture` and hides sensitive content.
+    #[serde(default)]
+    pub secure_window: bool,
+    /// Commands executed before and after the build.
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    /// Storage of archived release artifacts, mappings and unstripped libraries. By
+    /// default they are stored in `target/crossbundle`.
+    pub storage: Option<StorageConfig>,
     #[cfg(feature = "android")]
     #[serde(default)]
     pub android: AndroidConfig,
     #[cfg(feature = "apple")]
     #[serde(default)]
     pub apple: AppleConfig,
+    #[cfg(feature = "windows")]
+    #[serde(default)]
+    pub windows: WindowsConfig,
+}
+
+/// User-defined build hooks. Hooks run in restricted mode unless `unrestricted` is set
+/// and must be trusted before the first run.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct HooksConfig {
+    /// Hooks executed before compiling the application.
+    #[serde(default)]
+    pub pre_build: Vec<Hook>,
+    /// Hooks executed after the application was packaged and signed.
+    #[serde(default)]
+    pub post_build: Vec<Hook>,
 }
 
 impl CrossbowMetadata {
@@ -65,6 +107,15 @@ impl CrossbowMetadata {
         }
     }
 
+    #[cfg(feature = "windows")]
+    pub fn get_windows_assets(&self) -> &[PathBuf] {
+        if !self.windows.assets.is_empty() {
+            &self.windows.assets
+        } else {
+            &self.assets
+        }
+    }
+
     #[cfg(feature = "android")]
     pub fn get_android_resources(&self) -> &[PathBuf] {
         &self.android.resources
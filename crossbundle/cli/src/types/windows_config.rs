@@ -0,0 +1,42 @@
+use crossbundle_tools::types::WindowsTarget;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Windows MSIX package configuration of `crossbundle build windows`.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct WindowsConfig {
+    /// Package identity name, e.g. `com.studio.game`. `com.crossbow.<package name>` by
+    /// default. Microsoft Store packages use the name reserved in Partner Center.
+    pub identity_name: Option<String>,
+    /// Subject of the signing certificate, e.g. `CN=Studio, O=Studio, C=US`.
+    /// `CN=Crossbow` by default.
+    pub publisher: Option<String>,
+    /// Publisher name shown to users. `app_name` by default.
+    pub publisher_display_name: Option<String>,
+    /// Description shown on the tiles. `app_name` by default.
+    pub description: Option<String>,
+    /// Background color of the tiles, e.g. `#1E1E1E`. `transparent` by default.
+    pub background_color: Option<String>,
+    /// Minimum Windows version, `10.0.17763.0` by default.
+    pub min_version: Option<String>,
+    /// Maximum Windows version the package was tested on, `10.0.22621.0` by default.
+    pub max_version_tested: Option<String>,
+    /// Extra package capabilities, e.g. `internetClientServer`. Capabilities of the
+    /// `capabilities` metadata are added automatically.
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    /// `.pfx` certificate the package is signed with, relatively to project path.
+    pub certificate: Option<PathBuf>,
+    /// Environment variable with the password of the `.pfx` certificate.
+    pub certificate_password_env: Option<String>,
+    /// RFC 3161 timestamp server used when the package is signed.
+    pub timestamp_url: Option<String>,
+    /// Custom Windows assets directory path relatively to project path.
+    ///
+    /// **Important:** This property has higher priority than global property.
+    #[serde(default)]
+    pub assets: Vec<PathBuf>,
+    /// Windows build targets. The host architecture is used if not specified.
+    #[serde(default)]
+    pub build_targets: Vec<WindowsTarget>,
+}
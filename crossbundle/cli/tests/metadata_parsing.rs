@@ -0,0 +1,22 @@
+use crossbundle_lib::types::CrossbowMetadata;
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn metadata_deserialization_doesnt_panic(metadata in "(([a-z_.\\[\\]]{1,12}) = .{0,20}\n){0,8}") {
+        let _ = toml::from_str::<CrossbowMetadata>(&metadata);
+    }
+
+    #[test]
+    fn metadata_arbitrary_values_dont_panic(
+        app_name in "[a-zA-Z0-9 _-]*",
+        assets in prop::collection::vec("[a-z/]{0,10}", 0..4),
+    ) {
+        let metadata = format!(
+            "app_name = {:?}\nassets = {:?}\n",
+            app_name, assets
+        );
+        let metadata: CrossbowMetadata = toml::from_str(&metadata).unwrap();
+        prop_assert_eq!(metadata.app_name, Some(app_name));
+    }
+}
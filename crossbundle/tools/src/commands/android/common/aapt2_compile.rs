@@ -0,0 +1,363 @@
+use crate::{error::*, types::AndroidSdk};
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+/// Maximum length of the command line. Windows limits it to 32767 characters, other
+/// systems allow much longer ones but limit the total size of arguments and
+/// environment.
+#[cfg(windows)]
+pub const MAX_COMMAND_LINE_LENGTH: usize = 32_767;
+#[cfg(not(windows))]
+pub const MAX_COMMAND_LINE_LENGTH: usize = 131_072;
+
+/// Resources compiled by `aapt2 compile`.
+#[derive(Clone, Debug)]
+pub enum Aapt2CompileInput {
+    /// Resource directory passed with `--dir`.
+    Dir(PathBuf),
+    /// ZIP archive of the resource directory passed with `--zip`.
+    Zip(PathBuf),
+    /// Resource files like `res/values/strings.xml`.
+    Files(Vec<PathBuf>),
+}
+
+/// Compiled resource file produced by `aapt2 compile`, named like
+/// `values-fr_strings.arsc.flat` or `mipmap-hdpi_ic_launcher.png.flat`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Aapt2FlatFile {
+    /// Name of the `.flat` file in the output directory or ZIP archive.
+    pub name: String,
+    /// Resource directory the file was compiled from, e.g. `values-fr`.
+    pub resource_dir: String,
+}
+
+impl Aapt2FlatFile {
+    /// Returns the compiled file for the name of the `.flat` file.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (resource_dir, _) = name.strip_suffix(".flat")?.split_once('_')?;
+        Some(Self {
+            name: name.to_owned(),
+            resource_dir: resource_dir.to_owned(),
+        })
+    }
+
+    /// Returns the file `aapt2 compile` produces for the resource file. Files in
+    /// `values` directories are compiled into resource tables.
+    pub fn from_resource(path: &Path) -> Option<Self> {
+        let resource_dir = path.parent()?.file_name()?.to_str()?;
+        let file_name = path.file_name()?.to_str()?;
+        let name = match resource_dir.starts_with("values") {
+            true => format!(
+                "{}_{}.arsc.flat",
+                resource_dir,
+                Path::new(file_name).file_stem()?.to_str()?
+            ),
+            false => format!("{}_{}.flat", resource_dir, file_name),
+        };
+        Some(Self {
+            name,
+            resource_dir: resource_dir.to_owned(),
+        })
+    }
+
+    /// Resource type like `values` or `mipmap` without configuration qualifiers.
+    pub fn resource_type(&self) -> &str {
+        self.resource_dir
+            .split_once('-')
+            .map_or(&self.resource_dir, |(resource_type, _)| resource_type)
+    }
+}
+
+/// Output of `aapt2 compile`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Aapt2CompileOutput {
+    /// Output directory or ZIP archive.
+    pub path: PathBuf,
+    /// Compiled files in the output.
+    pub files: Vec<Aapt2FlatFile>,
+}
+
+impl Aapt2CompileOutput {
+    pub fn is_archive(&self) -> bool {
+        self.path.extension().map_or(false, |ext| ext == "zip")
+    }
+
+    /// Returns inputs for `aapt2 link`: the ZIP archive or paths of the compiled files.
+    pub fn link_inputs(&self) -> Vec<PathBuf> {
+        match self.is_archive() {
+            true => vec![self.path.clone()],
+            false => self
+                .files
+                .iter()
+                .map(|file| self.path.join(&file.name))
+                .collect(),
+        }
+    }
+}
+
+/// Visibility of compiled resources for other packages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aapt2Visibility {
+    Public,
+    Private,
+    Default,
+}
+
+impl Aapt2Visibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Private => "private",
+            Self::Default => "default",
+        }
+    }
+}
+
+/// ## Aapt2 compile
+/// Compiles resources into `.flat` files. A directory or a ZIP archive of resources is
+/// compiled into a single ZIP archive of `.flat` files when the output path ends with
+/// `.zip`. Separate files are compiled into the output directory and split into
+/// several `aapt2 compile` runs when their paths exceed
+/// [`MAX_COMMAND_LINE_LENGTH`].
+///
+/// See more: https://developer.android.com/tools/aapt2#compile
+#[derive(Clone, Debug)]
+pub struct Aapt2CompileCommand {
+    input: Aapt2CompileInput,
+    output: PathBuf,
+    pseudo_localize: bool,
+    no_crunch: bool,
+    legacy: bool,
+    visibility: Option<Aapt2Visibility>,
+    output_text_symbols: Option<PathBuf>,
+    verbose: bool,
+}
+
+impl Aapt2CompileCommand {
+    /// Compiles resources of `input` into `output` directory or ZIP archive.
+    pub fn new(input: Aapt2CompileInput, output: &Path) -> Self {
+        Self {
+            input,
+            output: output.to_owned(),
+            pseudo_localize: false,
+            no_crunch: false,
+            legacy: false,
+            visibility: None,
+            output_text_symbols: None,
+            verbose: false,
+        }
+    }
+
+    /// Generates pseudo-localized versions of default strings: `en-XA` with accented
+    /// and longer text and `ar-XB` with right-to-left text.
+    pub fn pseudo_localize(&mut self, pseudo_localize: bool) -> &mut Self {
+        self.pseudo_localize = pseudo_localize;
+        self
+    }
+
+    /// Disables PNG processing. Speeds up debug builds when PNGs are already
+    /// optimized.
+    pub fn no_crunch(&mut self, no_crunch: bool) -> &mut Self {
+        self.no_crunch = no_crunch;
+        self
+    }
+
+    /// Treats errors that were allowed by AAPT as warnings.
+    pub fn legacy(&mut self, legacy: bool) -> &mut Self {
+        self.legacy = legacy;
+        self
+    }
+
+    /// Sets visibility of the compiled resources.
+    pub fn visibility(&mut self, visibility: Aapt2Visibility) -> &mut Self {
+        self.visibility = Some(visibility);
+        self
+    }
+
+    /// Generates a text file with resource symbols. Only supported when compiling a
+    /// single file.
+    pub fn output_text_symbols(&mut self, path: &Path) -> &mut Self {
+        self.output_text_symbols = Some(path.to_owned());
+        self
+    }
+
+    pub fn verbose(&mut self, verbose: bool) -> &mut Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Returns arguments of `aapt2` without the input files.
+    fn common_args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec!["compile".into(), "-o".into()];
+        args.push(self.output.clone().into());
+        match &self.input {
+            Aapt2CompileInput::Dir(dir) => {
+                args.push("--dir".into());
+                args.push(dir.clone().into());
+            }
+            Aapt2CompileInput::Zip(zip) => {
+                args.push("--zip".into());
+                args.push(zip.clone().into());
+            }
+            Aapt2CompileInput::Files(_) => {}
+        }
+        if let Some(visibility) = self.visibility {
+            args.push("--visibility".into());
+            args.push(visibility.as_str().into());
+        }
+        if let Some(path) = &self.output_text_symbols {
+            args.push("--output-text-symbols".into());
+            args.push(path.clone().into());
+        }
+        let flags = [
+            ("--pseudo-localize", self.pseudo_localize),
+            ("--no-crunch", self.no_crunch),
+            ("--legacy", self.legacy),
+            ("-v", self.verbose),
+        ];
+        args.extend(
+            flags
+                .into_iter()
+                .filter(|(_, enabled)| *enabled)
+                .map(|(flag, _)| flag.into()),
+        );
+        args
+    }
+
+    /// Returns arguments of every `aapt2` run. Input files are split between runs so
+    /// that the command line with the program path of `program_length` characters
+    /// doesn't exceed `max_length`. A file that doesn't fit even alone gets its own
+    /// run.
+    pub fn args_chunks(&self, program_length: usize, max_length: usize) -> Vec<Vec<OsString>> {
+        let common_args = self.common_args();
+        let files = match &self.input {
+            Aapt2CompileInput::Files(files) => files,
+            _ => return vec![common_args],
+        };
+        // Every argument may be quoted and is separated by a space
+        let arg_length = |arg: &OsString| arg.len() + 3;
+        let common_length = program_length + common_args.iter().map(arg_length).sum::<usize>();
+        let mut chunks = Vec::new();
+        let mut chunk = common_args.clone();
+        let mut length = common_length;
+        for file in files {
+            let file: OsString = file.clone().into();
+            if length + arg_length(&file) > max_length && chunk.len() > common_args.len() {
+                chunks.push(std::mem::replace(&mut chunk, common_args.clone()));
+                length = common_length;
+            }
+            length += arg_length(&file);
+            chunk.push(file);
+        }
+        if chunk.len() > common_args.len() {
+            chunks.push(chunk);
+        }
+        chunks
+    }
+
+    /// Runs `aapt2 compile` once or several times for long lists of files. Returns the
+    /// compiled files. For separate resource files only their outputs are returned,
+    /// otherwise all `.flat` files of the output directory or archive.
+    pub fn run(&self, sdk: &AndroidSdk) -> Result<Aapt2CompileOutput> {
+        if let Aapt2CompileInput::Files(_) = self.input {
+            std::fs::create_dir_all(&self.output)?;
+        }
+        let program_length = sdk.build_tool(bin!("aapt2"), None)?.get_program().len();
+        for args in self.args_chunks(program_length, MAX_COMMAND_LINE_LENGTH) {
+            let mut aapt2 = sdk.build_tool(bin!("aapt2"), None)?;
+            aapt2.args(args);
+            aapt2.output_err(true)?;
+        }
+        let files = match &self.input {
+            Aapt2CompileInput::Files(files) => files
+                .iter()
+                .filter_map(|file| Aapt2FlatFile::from_resource(file))
+                .filter(|file| self.output.join(&file.name).exists())
+                .collect(),
+            _ => {
+                let mut names = Vec::new();
+                if self.output.is_dir() {
+                    for entry in std::fs::read_dir(&self.output)? {
+                        names.push(entry?.file_name().to_string_lossy().into_owned());
+                    }
+                } else {
+                    let mut archive = zip::ZipArchive::new(std::fs::File::open(&self.output)?)?;
+                    for i in 0..archive.len() {
+                        names.push(archive.by_index(i)?.name().to_owned());
+                    }
+                }
+                names.sort();
+                names
+                    .iter()
+                    .filter_map(|name| Aapt2FlatFile::from_name(name))
+                    .collect()
+            }
+        };
+        Ok(Aapt2CompileOutput {
+            path: self.output.clone(),
+            files,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aapt2_compile_args_chunks() {
+        let mut compile = Aapt2CompileCommand::new(
+            Aapt2CompileInput::Dir(PathBuf::from("res")),
+            Path::new("res.zip"),
+        );
+        compile
+            .pseudo_localize(true)
+            .visibility(Aapt2Visibility::Public);
+        let expected: Vec<OsString> = [
+            "compile",
+            "-o",
+            "res.zip",
+            "--dir",
+            "res",
+            "--visibility",
+            "public",
+            "--pseudo-localize",
+        ]
+        .iter()
+        .map(OsString::from)
+        .collect();
+        assert_eq!(compile.args_chunks(10, 20), vec![expected]);
+
+        let files = (0..5)
+            .map(|i| PathBuf::from(format!("res/values/v{}.xml", i)))
+            .collect();
+        let mut compile =
+            Aapt2CompileCommand::new(Aapt2CompileInput::Files(files), Path::new("out"));
+        compile.no_crunch(true);
+        // Quoted "compile -o out --no-crunch" takes 35 characters, every file 20
+        let chunks = compile.args_chunks(0, 35 + 20 * 2);
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.len() - 4)
+                .collect::<Vec<_>>(),
+            [2, 2, 1]
+        );
+        assert_eq!(chunks[2].last().unwrap(), "res/values/v4.xml");
+    }
+
+    #[test]
+    fn test_aapt2_flat_file() {
+        let strings = Aapt2FlatFile::from_resource(Path::new("res/values-fr/strings.xml"));
+        assert_eq!(
+            strings,
+            Aapt2FlatFile::from_name("values-fr_strings.arsc.flat")
+        );
+        assert_eq!(strings.unwrap().resource_type(), "values");
+        let icon = Aapt2FlatFile::from_resource(Path::new("res/mipmap-hdpi/ic_launcher.png"));
+        assert_eq!(icon.unwrap().name, "mipmap-hdpi_ic_launcher.png.flat");
+        assert_eq!(Aapt2FlatFile::from_name("res.zip"), None);
+    }
+}
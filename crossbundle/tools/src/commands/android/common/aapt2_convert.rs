@@ -0,0 +1,134 @@
+use crate::{error::*, types::AndroidSdk};
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+/// Format of the resource table and XML files inside the APK.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aapt2OutputFormat {
+    /// Protobuf format used inside modules of Android App Bundles.
+    Proto,
+    /// Binary format installed on devices and used to link other packages against.
+    Binary,
+}
+
+impl Aapt2OutputFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Proto => "proto",
+            Self::Binary => "binary",
+        }
+    }
+}
+
+/// ## Aapt2 convert
+/// Converts the APK between the proto format of App Bundle modules and the binary
+/// format. The AAB build links resources once in proto format and converts the result
+/// to binary when feature modules need to link against the base module.
+///
+/// See more: https://developer.android.com/tools/aapt2#convert
+#[derive(Clone, Debug)]
+pub struct Aapt2ConvertCommand {
+    input: PathBuf,
+    output: PathBuf,
+    output_format: Aapt2OutputFormat,
+    enable_sparse_encoding: bool,
+    keep_raw_values: bool,
+    verbose: bool,
+}
+
+impl Aapt2ConvertCommand {
+    /// Converts `input` APK into `output` APK of the given format.
+    pub fn new(input: &Path, output: &Path, output_format: Aapt2OutputFormat) -> Self {
+        Self {
+            input: input.to_owned(),
+            output: output.to_owned(),
+            output_format,
+            enable_sparse_encoding: false,
+            keep_raw_values: false,
+            verbose: false,
+        }
+    }
+
+    /// Encodes sparse entries with a binary search tree, which reduces the size of the
+    /// resource table on API 26 and higher.
+    pub fn enable_sparse_encoding(&mut self, enable_sparse_encoding: bool) -> &mut Self {
+        self.enable_sparse_encoding = enable_sparse_encoding;
+        self
+    }
+
+    /// Preserves raw attribute values in XML files.
+    pub fn keep_raw_values(&mut self, keep_raw_values: bool) -> &mut Self {
+        self.keep_raw_values = keep_raw_values;
+        self
+    }
+
+    /// Enables verbose logging.
+    pub fn verbose(&mut self, verbose: bool) -> &mut Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Returns arguments of `aapt2` without the program.
+    pub fn args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec![
+            "convert".into(),
+            "-o".into(),
+            self.output.clone().into(),
+            "--output-format".into(),
+            self.output_format.as_str().into(),
+        ];
+        if self.enable_sparse_encoding {
+            args.push("--enable-sparse-encoding".into());
+        }
+        if self.keep_raw_values {
+            args.push("--keep-raw-values".into());
+        }
+        if self.verbose {
+            args.push("-v".into());
+        }
+        args.push(self.input.clone().into());
+        args
+    }
+
+    /// Runs `aapt2 convert -o <output> --output-format <format> ... <input>`. Returns
+    /// the output path.
+    pub fn run(&self, sdk: &AndroidSdk) -> Result<PathBuf> {
+        if let Some(parent) = self.output.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut aapt2 = sdk.build_tool(bin!("aapt2"), None)?;
+        aapt2.args(self.args());
+        aapt2.output_err(true)?;
+        Ok(self.output.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aapt2_convert_args() {
+        let mut convert = Aapt2ConvertCommand::new(
+            Path::new("module.apk"),
+            Path::new("base_res.apk"),
+            Aapt2OutputFormat::Binary,
+        );
+        convert.enable_sparse_encoding(true);
+        let expected: Vec<OsString> = [
+            "convert",
+            "-o",
+            "base_res.apk",
+            "--output-format",
+            "binary",
+            "--enable-sparse-encoding",
+            "module.apk",
+        ]
+        .iter()
+        .map(OsString::from)
+        .collect();
+        assert_eq!(convert.args(), expected);
+    }
+}
@@ -0,0 +1,361 @@
+use crate::{commands::sha256_file, error::*, types::AndroidSdk};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, ChildStdout, Stdio},
+    sync::mpsc::{channel, Receiver},
+    time::UNIX_EPOCH,
+};
+
+/// ## aapt2 daemon
+/// Long-running `aapt2 daemon` process. Running commands in the daemon saves starting a
+/// new aapt2 process for every resource file. Replaces the `Aapt2Daemon` of
+/// android-tools, which runs the daemon until it exits without sending commands to it.
+///
+/// The daemon reads a command name and its arguments line by line, an empty line ends
+/// the command. After the command `Done` is printed to stdout and stderr, failures are
+/// reported with `Error` line on stderr before it.
+pub struct Aapt2Daemon {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    stderr: Receiver<String>,
+}
+
+impl Aapt2Daemon {
+    /// Starts `aapt2 daemon` of the build tools.
+    pub fn start(sdk: &AndroidSdk) -> Result<Self> {
+        let mut aapt2 = sdk.build_tool(bin!("aapt2"), None)?;
+        aapt2
+            .arg("daemon")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = aapt2.spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        // Stderr is read in the background, otherwise the daemon blocks on a full pipe
+        let child_stderr = child.stderr.take().unwrap();
+        let (sender, stderr) = channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(child_stderr).lines().flatten() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            child,
+            stdin,
+            stdout,
+            stderr,
+        })
+    }
+
+    /// Runs aapt2 command like `compile` with the arguments in the daemon.
+    pub fn run<I, S>(&mut self, command: &str, args: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut input = format!("{}\n", command);
+        for arg in args {
+            input.push_str(&arg.as_ref().to_string_lossy());
+            input.push('\n');
+        }
+        input.push('\n');
+        self.stdin.write_all(input.as_bytes())?;
+        self.stdin.flush()?;
+
+        let daemon_exited = || AndroidError::Aapt2DaemonFailed("daemon exited".to_owned());
+        // `Ready` is printed on start, so stdout is read until `Done`
+        loop {
+            let mut line = String::new();
+            if self.stdout.read_line(&mut line)? == 0 {
+                return Err(daemon_exited().into());
+            }
+            if line.trim_end() == "Done" {
+                break;
+            }
+        }
+        let mut failed = false;
+        let mut messages = Vec::new();
+        loop {
+            let line = self.stderr.recv().map_err(|_| daemon_exited())?;
+            match line.as_str() {
+                "Done" => break,
+                "Error" => failed = true,
+                _ => messages.push(line),
+            }
+        }
+        if failed {
+            return Err(AndroidError::Aapt2DaemonFailed(messages.join("\n")).into());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Aapt2Daemon {
+    fn drop(&mut self) {
+        writeln!(self.stdin, "quit").ok();
+        self.stdin.flush().ok();
+        self.child.wait().ok();
+    }
+}
+
+/// Numbers of resource files processed by [`compile_res_incremental`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResCompileStats {
+    pub compiled: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+}
+
+/// State of the resource file at the last compilation.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct ResFileState {
+    modified: u128,
+    size: u64,
+    sha256: String,
+    /// Names of the compiled `.flat` files.
+    outputs: Vec<String>,
+}
+
+/// Compiles resources from `res_dir` into `.flat` files in `compiled_dir` with the aapt2
+/// daemon. Only files changed since the previous call are recompiled: the modification
+/// time and size are checked first, the content hash if they differ. Compiled files of
+/// removed resources are deleted, so `compiled_dir` can be linked as is.
+///
+/// The state is stored in `<compiled_dir>.state.json`.
+pub fn compile_res_incremental(
+    daemon: &mut Aapt2Daemon,
+    res_dir: &Path,
+    compiled_dir: &Path,
+) -> Result<ResCompileStats> {
+    compile_changed_res(res_dir, compiled_dir, |file, staging_dir| {
+        daemon.run(
+            "compile",
+            [OsStr::new("-o"), staging_dir.as_os_str(), file.as_os_str()],
+        )
+    })
+}
+
+/// Compiles changed resource files with `compile`, which writes `.flat` files of the
+/// resource file into the staging directory.
+fn compile_changed_res<F>(
+    res_dir: &Path,
+    compiled_dir: &Path,
+    mut compile: F,
+) -> Result<ResCompileStats>
+where
+    F: FnMut(&Path, &Path) -> Result<()>,
+{
+    let state_path = compiled_dir.with_extension("state.json");
+    let staging_dir = compiled_dir.with_extension("staging");
+    let mut state: BTreeMap<String, ResFileState> = match std::fs::read(&state_path) {
+        Ok(data) if compiled_dir.exists() => serde_json::from_slice(&data).unwrap_or_default(),
+        _ => BTreeMap::new(),
+    };
+    std::fs::create_dir_all(compiled_dir)?;
+
+    let mut stats = ResCompileStats::default();
+    let mut files = Vec::new();
+    res_files(res_dir, &mut files)?;
+    let mut current = BTreeMap::new();
+    for file in files {
+        let key = file
+            .strip_prefix(res_dir)
+            .unwrap_or(&file)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let metadata = std::fs::metadata(&file)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|modified| modified.as_nanos())
+            .unwrap_or_default();
+        let previous = state.remove(&key);
+        let outputs_exist = |previous: &ResFileState| {
+            previous
+                .outputs
+                .iter()
+                .all(|output| compiled_dir.join(output).exists())
+        };
+        if let Some(previous) = &previous {
+            if previous.modified == modified
+                && previous.size == metadata.len()
+                && outputs_exist(previous)
+            {
+                current.insert(key, previous.clone());
+                stats.unchanged += 1;
+                continue;
+            }
+        }
+        // Copied resources get new modification times, so the content is compared too
+        let sha256 = sha256_file(&file)?;
+        if let Some(previous) = previous {
+            if previous.sha256 == sha256 && outputs_exist(&previous) {
+                current.insert(
+                    key,
+                    ResFileState {
+                        modified,
+                        size: metadata.len(),
+                        ..previous
+                    },
+                );
+                stats.unchanged += 1;
+                continue;
+            }
+            remove_outputs(compiled_dir, &previous.outputs)?;
+        }
+        std::fs::remove_dir_all(&staging_dir).ok();
+        std::fs::create_dir_all(&staging_dir)?;
+        compile(&file, &staging_dir)?;
+        let mut outputs = Vec::new();
+        for entry in std::fs::read_dir(&staging_dir)? {
+            let path = entry?.path();
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            std::fs::rename(&path, compiled_dir.join(&name))?;
+            outputs.push(name);
+        }
+        current.insert(
+            key,
+            ResFileState {
+                modified,
+                size: metadata.len(),
+                sha256,
+                outputs,
+            },
+        );
+        stats.compiled += 1;
+    }
+    for removed in state.values() {
+        remove_outputs(compiled_dir, &removed.outputs)?;
+        stats.removed += 1;
+    }
+    std::fs::remove_dir_all(&staging_dir).ok();
+    std::fs::write(&state_path, serde_json::to_vec_pretty(&current)?)?;
+    Ok(stats)
+}
+
+/// Collects files of the resource directory recursively. Hidden files like `.DS_Store`
+/// are skipped, aapt2 fails on them.
+fn res_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .map_or(false, |name| name.to_string_lossy().starts_with('.'))
+        {
+            continue;
+        }
+        if path.is_dir() {
+            res_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn remove_outputs(compiled_dir: &Path, outputs: &[String]) -> Result<()> {
+    for output in outputs {
+        let path = compiled_dir.join(output);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Compiles resources with a fake compiler writing `<dir>_<name>.flat` files and
+    /// returns names of the compiled resource files.
+    fn compile(res_dir: &Path, compiled_dir: &Path) -> (ResCompileStats, Vec<String>) {
+        let mut compiled = Vec::new();
+        let stats = compile_changed_res(res_dir, compiled_dir, |file, staging_dir| {
+            let dir = file
+                .parent()
+                .unwrap()
+                .file_name()
+                .unwrap()
+                .to_string_lossy();
+            let name = file.file_stem().unwrap().to_string_lossy();
+            std::fs::write(staging_dir.join(format!("{}_{}.flat", dir, name)), [])?;
+            compiled.push(file.file_name().unwrap().to_string_lossy().to_string());
+            Ok(())
+        })
+        .unwrap();
+        compiled.sort();
+        (stats, compiled)
+    }
+
+    #[test]
+    fn test_compile_changed_res() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let res_dir = tempdir.path().join("res");
+        let compiled_dir = tempdir.path().join("compiled_res");
+        std::fs::create_dir_all(res_dir.join("values")).unwrap();
+        std::fs::create_dir_all(res_dir.join("drawable")).unwrap();
+        std::fs::write(res_dir.join("values").join("strings.xml"), "<resources/>").unwrap();
+        std::fs::write(res_dir.join("drawable").join("icon.xml"), "<vector/>").unwrap();
+        std::fs::write(res_dir.join("drawable").join(".DS_Store"), "").unwrap();
+
+        let (stats, compiled) = compile(&res_dir, &compiled_dir);
+        assert_eq!(compiled, vec!["icon.xml", "strings.xml"]);
+        assert_eq!(stats.compiled, 2);
+
+        let (stats, compiled) = compile(&res_dir, &compiled_dir);
+        assert!(compiled.is_empty());
+        assert_eq!(stats.unchanged, 2);
+
+        std::fs::write(
+            res_dir.join("values").join("strings.xml"),
+            "<resources><string name=\"app_name\">App</string></resources>",
+        )
+        .unwrap();
+        std::fs::remove_file(res_dir.join("drawable").join("icon.xml")).unwrap();
+        let (stats, compiled) = compile(&res_dir, &compiled_dir);
+        assert_eq!(compiled, vec!["strings.xml"]);
+        assert_eq!(
+            stats,
+            ResCompileStats {
+                compiled: 1,
+                unchanged: 0,
+                removed: 1,
+            }
+        );
+        assert!(compiled_dir.join("values_strings.flat").exists());
+        assert!(!compiled_dir.join("drawable_icon.flat").exists());
+    }
+
+    #[test]
+    fn test_compile_copied_res() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let res_dir = tempdir.path().join("res");
+        let compiled_dir = tempdir.path().join("compiled_res");
+        std::fs::create_dir_all(res_dir.join("values")).unwrap();
+        let strings = res_dir.join("values").join("strings.xml");
+        std::fs::write(&strings, "<resources/>").unwrap();
+        compile(&res_dir, &compiled_dir);
+
+        // Rewriting the same content changes the modification time only
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        std::fs::write(&strings, "<resources/>").unwrap();
+        let (stats, compiled) = compile(&res_dir, &compiled_dir);
+        assert!(compiled.is_empty());
+        assert_eq!(stats.unchanged, 1);
+
+        // Missing outputs are compiled again
+        std::fs::remove_file(compiled_dir.join("values_strings.flat")).unwrap();
+        let (stats, compiled) = compile(&res_dir, &compiled_dir);
+        assert_eq!(compiled, vec!["strings.xml"]);
+        assert_eq!(stats.compiled, 1);
+    }
+}
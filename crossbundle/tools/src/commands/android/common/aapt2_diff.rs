@@ -0,0 +1,137 @@
+use crate::{error::*, types::AndroidSdk};
+use std::path::{Path, PathBuf};
+
+/// Kind of the resource difference reported by `aapt2 diff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ResourceChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// Resource or its configuration that differs between two APKs.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ResourceDiff {
+    pub kind: ResourceChangeKind,
+    /// Resource name like `com.crossbow.game:string/app_name`, or a type like
+    /// `com.crossbow.game:raw` if the whole type was added or removed.
+    pub resource: String,
+    /// Configuration like `fr` or `xhdpi-v4`. Empty for the default configuration,
+    /// `None` if the whole resource differs.
+    pub config: Option<String>,
+}
+
+/// ## Aapt2 diff
+/// Compares resource tables of two APKs. The APKs must have the same package name.
+/// Bundles can't be compared, their resource tables are in proto format inside module
+/// directories.
+///
+/// See more: `aapt2 diff -h`
+#[derive(Clone, Debug)]
+pub struct Aapt2Diff {
+    before: PathBuf,
+    after: PathBuf,
+}
+
+impl Aapt2Diff {
+    pub fn new(before: &Path, after: &Path) -> Self {
+        Self {
+            before: before.to_owned(),
+            after: after.to_owned(),
+        }
+    }
+
+    /// Runs `aapt2 diff` and returns sorted differences. The command exits with an
+    /// error code if there are any, so only unparsable output fails.
+    pub fn run(&self, sdk: &AndroidSdk) -> Result<Vec<ResourceDiff>> {
+        let mut aapt2 = sdk.build_tool(bin!("aapt2"), None)?;
+        aapt2.arg("diff").arg(&self.before).arg(&self.after);
+        let output = aapt2.output()?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let diffs = parse_aapt2_diff(&stderr);
+        if !output.status.success() && diffs.is_empty() {
+            return Err(AndroidError::Aapt2DiffFailed(stderr.trim().to_owned()).into());
+        }
+        Ok(diffs)
+    }
+}
+
+/// Parses lines like `game.apk: missing com.game:string/title config=fr` of `aapt2
+/// diff` output. Printed values of changed resources are skipped.
+pub fn parse_aapt2_diff(output: &str) -> Vec<ResourceDiff> {
+    const MESSAGES: &[(&str, ResourceChangeKind)] = &[
+        (": missing ", ResourceChangeKind::Removed),
+        (": new entry ", ResourceChangeKind::Added),
+        (": new config ", ResourceChangeKind::Added),
+        (": new type ", ResourceChangeKind::Added),
+        (": value ", ResourceChangeKind::Changed),
+    ];
+    let mut diffs: Vec<ResourceDiff> = output
+        .lines()
+        .filter_map(|line| {
+            let (kind, message) = MESSAGES.iter().find_map(|(prefix, kind)| {
+                line.split_once(prefix).map(|(_, message)| (*kind, message))
+            })?;
+            let message = message
+                .trim_end_matches(':')
+                .trim_end_matches(" does not match");
+            let (resource, config) = match message.split_once(" config=") {
+                Some((resource, config)) => {
+                    (resource, Some(config.trim().trim_matches('\'').to_owned()))
+                }
+                None => (message, None),
+            };
+            let resource = resource.trim().trim_start_matches("type ");
+            resource.contains(':').then(|| ResourceDiff {
+                kind,
+                resource: resource.to_owned(),
+                config,
+            })
+        })
+        .collect();
+    diffs.sort();
+    diffs.dedup();
+    diffs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_aapt2_diff() {
+        let output = "new.apk: missing com.game:string/title config=fr
+new.apk: new entry com.game:string/play
+new.apk: value com.game:string/app_name config= does not match:
+(string8) \"Game\"
+ vs
+(string8) \"Crossbow Game\"
+new.apk: new config com.game:mipmap/ic_launcher config=xxxhdpi-v4
+";
+        assert_eq!(
+            parse_aapt2_diff(output),
+            vec![
+                ResourceDiff {
+                    kind: ResourceChangeKind::Added,
+                    resource: "com.game:mipmap/ic_launcher".to_owned(),
+                    config: Some("xxxhdpi-v4".to_owned()),
+                },
+                ResourceDiff {
+                    kind: ResourceChangeKind::Added,
+                    resource: "com.game:string/play".to_owned(),
+                    config: None,
+                },
+                ResourceDiff {
+                    kind: ResourceChangeKind::Removed,
+                    resource: "com.game:string/title".to_owned(),
+                    config: Some("fr".to_owned()),
+                },
+                ResourceDiff {
+                    kind: ResourceChangeKind::Changed,
+                    resource: "com.game:string/app_name".to_owned(),
+                    config: Some("".to_owned()),
+                },
+            ]
+        );
+    }
+}
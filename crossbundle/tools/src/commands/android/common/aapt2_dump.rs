@@ -0,0 +1,454 @@
+use crate::{error::*, types::AndroidSdk};
+use std::{collections::BTreeMap, path::Path};
+
+/// Runs `aapt2 dump badging <apk>` command and parses its output.
+pub fn aapt2_dump_badging(sdk: &AndroidSdk, apk_path: &Path) -> Result<BadgingInfo> {
+    let output = aapt2_dump(sdk, &["badging"], apk_path)?;
+    Ok(BadgingInfo::parse(&output))
+}
+
+/// Runs `aapt2 dump resources <apk>` command and parses its output.
+pub fn aapt2_dump_resources(sdk: &AndroidSdk, apk_path: &Path) -> Result<ResourceTable> {
+    let output = aapt2_dump(sdk, &["resources"], apk_path)?;
+    Ok(ResourceTable::parse(&output))
+}
+
+/// Runs `aapt2 dump xmltree --file <file> <apk>` command and parses its output.
+/// `file` is the path of the compiled XML in the APK, e.g. `AndroidManifest.xml`.
+pub fn aapt2_dump_xmltree(sdk: &AndroidSdk, apk_path: &Path, file: &str) -> Result<XmlTree> {
+    let output = aapt2_dump(sdk, &["xmltree", "--file", file], apk_path)?;
+    XmlTree::parse(&output)
+}
+
+fn aapt2_dump(sdk: &AndroidSdk, args: &[&str], apk_path: &Path) -> Result<String> {
+    if !apk_path.exists() {
+        return Err(Error::PathNotFound(apk_path.to_owned()));
+    }
+    let mut aapt2 = sdk.build_tool(bin!("aapt2"), None)?;
+    aapt2.arg("dump").args(args).arg(apk_path);
+    let output = aapt2.output_err(true)?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Values of `aapt2 dump badging` output.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BadgingInfo {
+    pub package: Option<String>,
+    pub version_code: Option<u32>,
+    pub version_name: Option<String>,
+    pub compile_sdk_version: Option<u32>,
+    pub min_sdk_version: Option<u32>,
+    pub target_sdk_version: Option<u32>,
+    /// Application label of the default locale.
+    pub label: Option<String>,
+    /// Application labels keyed by locale.
+    pub localized_labels: BTreeMap<String, String>,
+    pub launchable_activity: Option<String>,
+    pub permissions: Vec<String>,
+    /// Required hardware and software features.
+    pub features: Vec<String>,
+    /// Features declared with `android:required="false"`.
+    pub optional_features: Vec<String>,
+    pub locales: Vec<String>,
+    pub densities: Vec<String>,
+    /// ABIs of the native libraries.
+    pub native_code: Vec<String>,
+    /// ABIs of the native libraries that run through translation.
+    pub alt_native_code: Vec<String>,
+}
+
+impl BadgingInfo {
+    pub fn parse(output: &str) -> Self {
+        let mut info = Self::default();
+        for line in output.lines() {
+            let (key, value) = match line.trim_start().split_once(':') {
+                Some(parts) => parts,
+                None => continue,
+            };
+            match key {
+                "package" => {
+                    info.package = quoted_attribute(value, "name");
+                    info.version_code =
+                        quoted_attribute(value, "versionCode").and_then(|code| code.parse().ok());
+                    info.version_name = quoted_attribute(value, "versionName");
+                    info.compile_sdk_version = quoted_attribute(value, "compileSdkVersion")
+                        .and_then(|version| version.parse().ok());
+                }
+                "sdkVersion" => info.min_sdk_version = parse_first_value(value),
+                "targetSdkVersion" => info.target_sdk_version = parse_first_value(value),
+                "application-label" => info.label = quoted_values(value).into_iter().next(),
+                "launchable-activity" => info.launchable_activity = quoted_attribute(value, "name"),
+                "uses-permission" | "uses-permission-sdk-23" => {
+                    info.permissions.extend(quoted_attribute(value, "name"))
+                }
+                "uses-feature" => info.features.extend(quoted_attribute(value, "name")),
+                "uses-feature-not-required" => info
+                    .optional_features
+                    .extend(quoted_attribute(value, "name")),
+                "locales" => info.locales = quoted_values(value),
+                "densities" => info.densities = quoted_values(value),
+                "native-code" => info.native_code = quoted_values(value),
+                "alt-native-code" => info.alt_native_code = quoted_values(value),
+                _ => {
+                    let locale = key.strip_prefix("application-label-");
+                    let label = quoted_values(value).into_iter().next();
+                    if let (Some(locale), Some(label)) = (locale, label) {
+                        info.localized_labels.insert(locale.to_owned(), label);
+                    }
+                }
+            }
+        }
+        info
+    }
+}
+
+/// Returns value of `name='value'` attribute of the badging line.
+fn quoted_attribute(line: &str, name: &str) -> Option<String> {
+    let start = line.find(&format!(" {}='", name))? + name.len() + 3;
+    let end = line[start..].find('\'')?;
+    Some(line[start..start + end].to_owned())
+}
+
+/// Parses the first quoted value of the badging line, e.g. `sdkVersion:'21'`.
+fn parse_first_value(line: &str) -> Option<u32> {
+    quoted_values(line).first()?.parse().ok()
+}
+
+/// Returns values of `'first' 'second'` list of the badging line.
+fn quoted_values(line: &str) -> Vec<String> {
+    line.split('\'')
+        .skip(1)
+        .step_by(2)
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// Resource table of `aapt2 dump resources` output.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceTable {
+    pub packages: Vec<ResourcePackage>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourcePackage {
+    pub name: String,
+    pub id: Option<u8>,
+    pub types: Vec<ResourceType>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceType {
+    /// Type name, e.g. `drawable` or `string`.
+    pub name: String,
+    pub entries: Vec<ResourceEntry>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceEntry {
+    /// Resource id, e.g. `0x7f010000`.
+    pub id: u32,
+    /// Entry name without the type, e.g. `app_name`.
+    pub name: String,
+    pub values: Vec<ResourceValue>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceValue {
+    /// Configuration qualifiers, e.g. `fr` or `xxhdpi-v4`. Empty for the default one.
+    pub config: String,
+    /// Value as printed by aapt2, e.g. `"Game"` or `(file) res/drawable/icon.png`.
+    pub value: String,
+}
+
+impl ResourceTable {
+    pub fn parse(output: &str) -> Self {
+        let mut table = Self::default();
+        for line in output.lines() {
+            let trimmed = line.trim();
+            let mut words = trimmed.split_whitespace();
+            match words.next() {
+                Some("Package") => table.packages.push(ResourcePackage {
+                    name: key_value(trimmed, "name").unwrap_or_default(),
+                    id: key_value(trimmed, "id").and_then(|id| u8::from_str_radix(&id, 16).ok()),
+                    types: Vec::new(),
+                }),
+                Some("type") => {
+                    if let Some(package) = table.packages.last_mut() {
+                        package.types.push(ResourceType {
+                            name: words.next().unwrap_or_default().to_owned(),
+                            entries: Vec::new(),
+                        });
+                    }
+                }
+                Some("resource") => {
+                    let id = words
+                        .next()
+                        .and_then(|id| u32::from_str_radix(id.trim_start_matches("0x"), 16).ok());
+                    let name = words.next().map(|name| match name.split_once('/') {
+                        Some((_, name)) => name.to_owned(),
+                        None => name.to_owned(),
+                    });
+                    let resource_type = table
+                        .packages
+                        .last_mut()
+                        .and_then(|package| package.types.last_mut());
+                    if let (Some(resource_type), Some(id), Some(name)) = (resource_type, id, name) {
+                        resource_type.entries.push(ResourceEntry {
+                            id,
+                            name,
+                            values: Vec::new(),
+                        });
+                    }
+                }
+                Some(word) if word.starts_with('(') => {
+                    let entry = table
+                        .packages
+                        .last_mut()
+                        .and_then(|package| package.types.last_mut())
+                        .and_then(|resource_type| resource_type.entries.last_mut());
+                    if let (Some(entry), Some(end)) = (entry, trimmed.find(')')) {
+                        entry.values.push(ResourceValue {
+                            config: trimmed[1..end].to_owned(),
+                            value: trimmed[end + 1..].trim().to_owned(),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        table
+    }
+
+    /// Returns the entry by type and name, e.g. `("string", "app_name")`.
+    pub fn entry(&self, resource_type: &str, name: &str) -> Option<&ResourceEntry> {
+        self.packages
+            .iter()
+            .flat_map(|package| &package.types)
+            .filter(|t| t.name == resource_type)
+            .flat_map(|t| &t.entries)
+            .find(|entry| entry.name == name)
+    }
+
+    /// Returns number of entries by resource type.
+    pub fn type_counts(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for resource_type in self.packages.iter().flat_map(|package| &package.types) {
+            *counts.entry(resource_type.name.clone()).or_default() += resource_type.entries.len();
+        }
+        counts
+    }
+}
+
+/// Returns value of `key=value` word of the line.
+fn key_value(line: &str, key: &str) -> Option<String> {
+    line.split_whitespace()
+        .find_map(|word| word.strip_prefix(&format!("{}=", key)))
+        .map(ToOwned::to_owned)
+}
+
+/// Compiled XML of `aapt2 dump xmltree` output.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XmlTree {
+    pub root: XmlElement,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XmlElement {
+    pub name: String,
+    pub attributes: Vec<XmlAttribute>,
+    pub children: Vec<XmlElement>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XmlAttribute {
+    /// Namespace URI, e.g. `http://schemas.android.com/apk/res/android`.
+    pub namespace: Option<String>,
+    pub name: String,
+    /// Id of the framework attribute, e.g. `0x0101021b` for `versionCode`.
+    pub resource_id: Option<u32>,
+    /// Value without quotes. References are printed as `@0x7f010000`.
+    pub value: String,
+}
+
+impl XmlTree {
+    /// Parses `E:` element and `A:` attribute lines nested by indentation. Namespace
+    /// declarations are skipped.
+    pub fn parse(output: &str) -> Result<Self> {
+        // Elements that are not closed yet with their indentation
+        let mut stack: Vec<(usize, XmlElement)> = Vec::new();
+        let mut root = None;
+        let mut close = |stack: &mut Vec<(usize, XmlElement)>, indent: usize| {
+            while stack.last().map_or(false, |(i, _)| *i >= indent) {
+                let (_, element) = stack.pop().unwrap();
+                match stack.last_mut() {
+                    Some((_, parent)) => parent.children.push(element),
+                    None => root = root.take().or(Some(element)),
+                }
+            }
+        };
+        for line in output.lines() {
+            let indent = line.len() - line.trim_start().len();
+            let line = line.trim();
+            if let Some(element) = line.strip_prefix("E: ") {
+                close(&mut stack, indent);
+                let name = element.split_whitespace().next().unwrap_or_default();
+                stack.push((
+                    indent,
+                    XmlElement {
+                        name: name.to_owned(),
+                        ..Default::default()
+                    },
+                ));
+            } else if let Some(attribute) = line.strip_prefix("A: ") {
+                close(&mut stack, indent);
+                if let (Some((_, element)), Some(attribute)) =
+                    (stack.last_mut(), parse_xml_attribute(attribute))
+                {
+                    element.attributes.push(attribute);
+                }
+            }
+        }
+        close(&mut stack, 0);
+        match root {
+            Some(root) => Ok(Self { root }),
+            None => Err(AndroidError::InvalidAapt2DumpOutput("no XML elements".to_owned()).into()),
+        }
+    }
+}
+
+/// Parses `http://schemas.android.com/apk/res/android:versionCode(0x0101021b)=1` or
+/// `package="com.crossbow.game" (Raw: "com.crossbow.game")`.
+fn parse_xml_attribute(line: &str) -> Option<XmlAttribute> {
+    let (qualified_name, value) = line.split_once('=')?;
+    let (qualified_name, resource_id) = match qualified_name.split_once('(') {
+        Some((name, id)) => (
+            name,
+            u32::from_str_radix(id.trim_end_matches(')').trim_start_matches("0x"), 16).ok(),
+        ),
+        None => (qualified_name, None),
+    };
+    let (namespace, name) = match qualified_name.rsplit_once(':') {
+        Some((namespace, name)) => (Some(namespace.to_owned()), name),
+        None => (None, qualified_name),
+    };
+    let value = match value.rfind(" (Raw: ") {
+        Some(end) => &value[..end],
+        None => value,
+    };
+    Some(XmlAttribute {
+        namespace,
+        name: name.to_owned(),
+        resource_id,
+        value: value.trim().trim_matches('"').to_owned(),
+    })
+}
+
+impl XmlElement {
+    /// Returns value of the attribute by its name without namespace.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+            .map(|attribute| attribute.value.as_str())
+    }
+
+    /// Returns direct children with the name.
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// Returns the first element with the name in depth-first order, including this one.
+    pub fn find(&self, name: &str) -> Option<&XmlElement> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_badging_info() {
+        let info = BadgingInfo::parse(
+            "package: name='com.crossbow.game' versionCode='42' versionName='1.2.0' \
+             platformBuildVersionName='13' compileSdkVersion='33'\n\
+             sdkVersion:'21'\n\
+             targetSdkVersion:'33'\n\
+             uses-permission: name='android.permission.INTERNET'\n\
+             application-label:'Game'\n\
+             application-label-fr:'Jeu'\n\
+             launchable-activity: name='android.app.NativeActivity'  label='' icon=''\n\
+             uses-feature-not-required: name='android.hardware.camera'\n\
+             feature-group: label=''\n  uses-feature: name='android.hardware.touchscreen'\n\
+             locales: '--_--' 'fr'\n\
+             native-code: 'arm64-v8a' 'armeabi-v7a'\n",
+        );
+        assert_eq!(info.package.as_deref(), Some("com.crossbow.game"));
+        assert_eq!(info.version_code, Some(42));
+        assert_eq!(info.compile_sdk_version, Some(33));
+        assert_eq!(info.min_sdk_version, Some(21));
+        assert_eq!(info.localized_labels["fr"], "Jeu");
+        assert_eq!(
+            info.launchable_activity.as_deref(),
+            Some("android.app.NativeActivity")
+        );
+        assert_eq!(info.features, ["android.hardware.touchscreen"]);
+        assert_eq!(info.optional_features, ["android.hardware.camera"]);
+        assert_eq!(info.native_code, ["arm64-v8a", "armeabi-v7a"]);
+    }
+
+    #[test]
+    fn test_resource_table() {
+        let table = ResourceTable::parse(
+            "Binary APK\nPackage name=com.crossbow.game id=7f\n  type drawable id=01 \
+             entryCount=1\n    resource 0x7f010000 drawable/icon\n      () (file) \
+             res/drawable/icon.png type=PNG\n  type string id=02 entryCount=1\n    resource \
+             0x7f020000 string/app_name PUBLIC\n      () \"Game\"\n      (fr) \"Jeu\"\n",
+        );
+        assert_eq!(table.packages[0].id, Some(0x7f));
+        let app_name = table.entry("string", "app_name").unwrap();
+        assert_eq!(app_name.id, 0x7f020000);
+        assert_eq!(app_name.values[1].config, "fr");
+        assert_eq!(app_name.values[1].value, "\"Jeu\"");
+        assert_eq!(table.type_counts()["drawable"], 1);
+    }
+
+    #[test]
+    fn test_xml_tree() {
+        let tree = XmlTree::parse(
+            "N: android=http://schemas.android.com/apk/res/android (line=2)\n\
+             \x20 E: manifest (line=2)\n\
+             \x20   A: http://schemas.android.com/apk/res/android:versionCode(0x0101021b)=42\n\
+             \x20   A: package=\"com.crossbow.game\" (Raw: \"com.crossbow.game\")\n\
+             \x20   E: uses-sdk (line=7)\n\
+             \x20     A: http://schemas.android.com/apk/res/android:minSdkVersion(0x0101020c)=21\n\
+             \x20   E: application (line=9)\n\
+             \x20     E: activity (line=10)\n\
+             \x20       A: http://schemas.android.com/apk/res/android:name(0x01010003)=\"android.app.NativeActivity\" (Raw: \"android.app.NativeActivity\")\n",
+        )
+        .unwrap();
+        let manifest = &tree.root;
+        assert_eq!(manifest.name, "manifest");
+        assert_eq!(manifest.attribute("package"), Some("com.crossbow.game"));
+        assert_eq!(manifest.attributes[0].resource_id, Some(0x0101021b));
+        assert_eq!(
+            manifest.attributes[0].namespace.as_deref(),
+            Some("http://schemas.android.com/apk/res/android")
+        );
+        assert_eq!(
+            manifest
+                .children_named("uses-sdk")
+                .next()
+                .unwrap()
+                .attribute("minSdkVersion"),
+            Some("21")
+        );
+        assert_eq!(
+            manifest.find("activity").unwrap().attribute("name"),
+            Some("android.app.NativeActivity")
+        );
+    }
+}
@@ -0,0 +1,610 @@
+use crate::{error::*, types::AndroidSdk};
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+/// ## Aapt2 link
+/// Links compiled resources and the manifest into an APK with the complete option set
+/// of `aapt2 link`, including overlays, stable IDs, shared and static libraries and
+/// proto format output. Unlike [`Aapt2Link`](crate::types::Aapt2Link) of the SDK
+/// wrappers, every flag is typed, so complex projects don't need to build the command
+/// line manually.
+///
+/// See more: https://developer.android.com/tools/aapt2#link
+#[derive(Clone, Debug, Default)]
+pub struct Aapt2LinkCommand {
+    output: PathBuf,
+    manifest: PathBuf,
+    inputs: Vec<PathBuf>,
+    overlays: Vec<PathBuf>,
+    includes: Vec<PathBuf>,
+    assets: Vec<PathBuf>,
+    package_id: Option<String>,
+    allow_reserved_package_id: bool,
+    auto_add_overlay: bool,
+    override_styles_instead_of_overlaying: bool,
+    java: Option<PathBuf>,
+    custom_package: Option<String>,
+    extra_packages: Vec<String>,
+    proguard: Option<PathBuf>,
+    proguard_main_dex: Option<PathBuf>,
+    proguard_conditional_keep_rules: bool,
+    proguard_minimal_keep_rules: bool,
+    output_text_symbols: Option<PathBuf>,
+    stable_ids: Option<PathBuf>,
+    emit_ids: Option<PathBuf>,
+    private_symbols: Option<String>,
+    non_final_ids: bool,
+    min_sdk_version: Option<u32>,
+    target_sdk_version: Option<u32>,
+    version_code: Option<u32>,
+    version_code_major: Option<u32>,
+    version_name: Option<String>,
+    replace_version: bool,
+    compile_sdk_version_code: Option<u32>,
+    compile_sdk_version_name: Option<String>,
+    rename_manifest_package: Option<String>,
+    rename_resources_package: Option<String>,
+    rename_instrumentation_target_package: Option<String>,
+    configs: Vec<String>,
+    preferred_density: Option<String>,
+    splits: Vec<(PathBuf, Vec<String>)>,
+    exclude_configs: Vec<String>,
+    no_auto_version: bool,
+    no_version_vectors: bool,
+    no_version_transitions: bool,
+    no_resource_deduping: bool,
+    no_resource_removal: bool,
+    enable_sparse_encoding: bool,
+    require_localization: bool,
+    shared_lib: bool,
+    static_lib: bool,
+    no_static_lib_packages: bool,
+    proto_format: bool,
+    output_to_dir: bool,
+    merge_only: bool,
+    no_compress: bool,
+    no_compress_extensions: Vec<String>,
+    no_compress_regex: Option<String>,
+    keep_raw_values: bool,
+    exclude_sources: bool,
+    debug_mode: bool,
+    strict_visibility: bool,
+    warn_manifest_validation: bool,
+    verbose: bool,
+}
+
+impl Aapt2LinkCommand {
+    /// Links the manifest into `output` APK, or directory with
+    /// [`output_to_dir`](Self::output_to_dir).
+    pub fn new(output: &Path, manifest: &Path) -> Self {
+        Self {
+            output: output.to_owned(),
+            manifest: manifest.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Compiled resources: `.flat` files or ZIP archives of them, linked in order.
+    pub fn input(&mut self, input: &Path) -> &mut Self {
+        self.inputs.push(input.to_owned());
+        self
+    }
+
+    /// Compiled resources that override resources of the inputs and previous
+    /// overlays. Passed with `-R`.
+    pub fn overlay(&mut self, overlay: &Path) -> &mut Self {
+        self.overlays.push(overlay.to_owned());
+        self
+    }
+
+    /// `android.jar` or APK the resources are linked against. Passed with `-I`.
+    pub fn include(&mut self, include: &Path) -> &mut Self {
+        self.includes.push(include.to_owned());
+        self
+    }
+
+    /// Directory of assets. Passed with `-A`.
+    pub fn assets(&mut self, assets: &Path) -> &mut Self {
+        self.assets.push(assets.to_owned());
+        self
+    }
+
+    /// Package ID of the resources, e.g. `0x7e`. IDs lower than `0x7f` also require
+    /// [`allow_reserved_package_id`](Self::allow_reserved_package_id).
+    pub fn package_id(&mut self, package_id: &str) -> &mut Self {
+        self.package_id = Some(package_id.to_owned());
+        self
+    }
+
+    pub fn allow_reserved_package_id(&mut self, allow: bool) -> &mut Self {
+        self.allow_reserved_package_id = allow;
+        self
+    }
+
+    /// Adds resources that exist only in overlays.
+    pub fn auto_add_overlay(&mut self, auto_add_overlay: bool) -> &mut Self {
+        self.auto_add_overlay = auto_add_overlay;
+        self
+    }
+
+    /// Replaces styles of the inputs with styles of overlays instead of merging them.
+    pub fn override_styles_instead_of_overlaying(&mut self, value: bool) -> &mut Self {
+        self.override_styles_instead_of_overlaying = value;
+        self
+    }
+
+    /// Directory to generate `R.java` in.
+    pub fn java(&mut self, java: &Path) -> &mut Self {
+        self.java = Some(java.to_owned());
+        self
+    }
+
+    /// Java package of the generated `R.java`.
+    pub fn custom_package(&mut self, package: &str) -> &mut Self {
+        self.custom_package = Some(package.to_owned());
+        self
+    }
+
+    /// Additional Java package to generate the same `R.java` in.
+    pub fn extra_package(&mut self, package: &str) -> &mut Self {
+        self.extra_packages.push(package.to_owned());
+        self
+    }
+
+    /// File to write ProGuard rules for the resources into.
+    pub fn proguard(&mut self, proguard: &Path) -> &mut Self {
+        self.proguard = Some(proguard.to_owned());
+        self
+    }
+
+    /// File to write ProGuard rules for the main dex into.
+    pub fn proguard_main_dex(&mut self, proguard: &Path) -> &mut Self {
+        self.proguard_main_dex = Some(proguard.to_owned());
+        self
+    }
+
+    pub fn proguard_conditional_keep_rules(&mut self, value: bool) -> &mut Self {
+        self.proguard_conditional_keep_rules = value;
+        self
+    }
+
+    pub fn proguard_minimal_keep_rules(&mut self, value: bool) -> &mut Self {
+        self.proguard_minimal_keep_rules = value;
+        self
+    }
+
+    /// File to write the `R.txt` symbols into.
+    pub fn output_text_symbols(&mut self, path: &Path) -> &mut Self {
+        self.output_text_symbols = Some(path.to_owned());
+        self
+    }
+
+    /// File with `name = id` lines of the resource IDs to keep across builds.
+    pub fn stable_ids(&mut self, path: &Path) -> &mut Self {
+        self.stable_ids = Some(path.to_owned());
+        self
+    }
+
+    /// File to write the resource IDs into, in the format of
+    /// [`stable_ids`](Self::stable_ids).
+    pub fn emit_ids(&mut self, path: &Path) -> &mut Self {
+        self.emit_ids = Some(path.to_owned());
+        self
+    }
+
+    /// Java package of the private symbols.
+    pub fn private_symbols(&mut self, package: &str) -> &mut Self {
+        self.private_symbols = Some(package.to_owned());
+        self
+    }
+
+    /// Generates non-final resource IDs in `R.java`, used by libraries.
+    pub fn non_final_ids(&mut self, non_final_ids: bool) -> &mut Self {
+        self.non_final_ids = non_final_ids;
+        self
+    }
+
+    pub fn min_sdk_version(&mut self, version: u32) -> &mut Self {
+        self.min_sdk_version = Some(version);
+        self
+    }
+
+    pub fn target_sdk_version(&mut self, version: u32) -> &mut Self {
+        self.target_sdk_version = Some(version);
+        self
+    }
+
+    pub fn version_code(&mut self, version_code: u32) -> &mut Self {
+        self.version_code = Some(version_code);
+        self
+    }
+
+    pub fn version_code_major(&mut self, version_code_major: u32) -> &mut Self {
+        self.version_code_major = Some(version_code_major);
+        self
+    }
+
+    pub fn version_name(&mut self, version_name: &str) -> &mut Self {
+        self.version_name = Some(version_name.to_owned());
+        self
+    }
+
+    /// Replaces versions of the manifest with the given ones.
+    pub fn replace_version(&mut self, replace_version: bool) -> &mut Self {
+        self.replace_version = replace_version;
+        self
+    }
+
+    pub fn compile_sdk_version_code(&mut self, version: u32) -> &mut Self {
+        self.compile_sdk_version_code = Some(version);
+        self
+    }
+
+    pub fn compile_sdk_version_name(&mut self, version: &str) -> &mut Self {
+        self.compile_sdk_version_name = Some(version.to_owned());
+        self
+    }
+
+    /// Renames the package in the manifest, e.g. for variants with the application ID
+    /// suffix.
+    pub fn rename_manifest_package(&mut self, package: &str) -> &mut Self {
+        self.rename_manifest_package = Some(package.to_owned());
+        self
+    }
+
+    /// Renames the package of the resource table.
+    pub fn rename_resources_package(&mut self, package: &str) -> &mut Self {
+        self.rename_resources_package = Some(package.to_owned());
+        self
+    }
+
+    pub fn rename_instrumentation_target_package(&mut self, package: &str) -> &mut Self {
+        self.rename_instrumentation_target_package = Some(package.to_owned());
+        self
+    }
+
+    /// Keeps only resources of the configuration, e.g. `en` or `xxhdpi`. Passed with
+    /// `-c`.
+    pub fn config(&mut self, config: &str) -> &mut Self {
+        self.configs.push(config.to_owned());
+        self
+    }
+
+    /// Keeps only drawables of the density closest to the preferred one.
+    pub fn preferred_density(&mut self, density: &str) -> &mut Self {
+        self.preferred_density = Some(density.to_owned());
+        self
+    }
+
+    /// Splits resources of the configurations into a separate APK.
+    /// Passed as `--split <path>:<config>,<config>`.
+    pub fn split(&mut self, path: &Path, configs: &[&str]) -> &mut Self {
+        self.splits.push((
+            path.to_owned(),
+            configs.iter().map(|config| config.to_string()).collect(),
+        ));
+        self
+    }
+
+    /// Excludes resources of the configuration.
+    pub fn exclude_config(&mut self, config: &str) -> &mut Self {
+        self.exclude_configs.push(config.to_owned());
+        self
+    }
+
+    pub fn no_auto_version(&mut self, value: bool) -> &mut Self {
+        self.no_auto_version = value;
+        self
+    }
+
+    /// Doesn't version vector drawables for API levels below 21.
+    pub fn no_version_vectors(&mut self, value: bool) -> &mut Self {
+        self.no_version_vectors = value;
+        self
+    }
+
+    pub fn no_version_transitions(&mut self, value: bool) -> &mut Self {
+        self.no_version_transitions = value;
+        self
+    }
+
+    pub fn no_resource_deduping(&mut self, value: bool) -> &mut Self {
+        self.no_resource_deduping = value;
+        self
+    }
+
+    pub fn no_resource_removal(&mut self, value: bool) -> &mut Self {
+        self.no_resource_removal = value;
+        self
+    }
+
+    pub fn enable_sparse_encoding(&mut self, value: bool) -> &mut Self {
+        self.enable_sparse_encoding = value;
+        self
+    }
+
+    /// Requires translations of strings for all configured locales. Passed with `-z`.
+    pub fn require_localization(&mut self, value: bool) -> &mut Self {
+        self.require_localization = value;
+        self
+    }
+
+    /// Generates a shared Android runtime library.
+    pub fn shared_lib(&mut self, shared_lib: bool) -> &mut Self {
+        self.shared_lib = shared_lib;
+        self
+    }
+
+    /// Generates a static Android library.
+    pub fn static_lib(&mut self, static_lib: bool) -> &mut Self {
+        self.static_lib = static_lib;
+        self
+    }
+
+    pub fn no_static_lib_packages(&mut self, value: bool) -> &mut Self {
+        self.no_static_lib_packages = value;
+        self
+    }
+
+    /// Generates resources in protobuf format, required by bundletool.
+    pub fn proto_format(&mut self, proto_format: bool) -> &mut Self {
+        self.proto_format = proto_format;
+        self
+    }
+
+    /// Writes the APK contents into the output directory instead of the archive.
+    pub fn output_to_dir(&mut self, output_to_dir: bool) -> &mut Self {
+        self.output_to_dir = output_to_dir;
+        self
+    }
+
+    /// Only merges the resources without verifying references. Requires
+    /// [`static_lib`](Self::static_lib).
+    pub fn merge_only(&mut self, merge_only: bool) -> &mut Self {
+        self.merge_only = merge_only;
+        self
+    }
+
+    /// Doesn't compress any resources.
+    pub fn no_compress(&mut self, no_compress: bool) -> &mut Self {
+        self.no_compress = no_compress;
+        self
+    }
+
+    /// Doesn't compress files with the extension, e.g. `ogg`. Passed with `-0`.
+    pub fn no_compress_extension(&mut self, extension: &str) -> &mut Self {
+        self.no_compress_extensions.push(extension.to_owned());
+        self
+    }
+
+    /// Doesn't compress files matching the regular expression.
+    pub fn no_compress_regex(&mut self, regex: &str) -> &mut Self {
+        self.no_compress_regex = Some(regex.to_owned());
+        self
+    }
+
+    /// Keeps raw string values of the resources.
+    pub fn keep_raw_values(&mut self, value: bool) -> &mut Self {
+        self.keep_raw_values = value;
+        self
+    }
+
+    /// Doesn't write source information of the resources into proto format output.
+    pub fn exclude_sources(&mut self, value: bool) -> &mut Self {
+        self.exclude_sources = value;
+        self
+    }
+
+    /// Sets `android:debuggable="true"` in the manifest.
+    pub fn debug_mode(&mut self, debug_mode: bool) -> &mut Self {
+        self.debug_mode = debug_mode;
+        self
+    }
+
+    /// Fails on references to private resources of other packages.
+    pub fn strict_visibility(&mut self, value: bool) -> &mut Self {
+        self.strict_visibility = value;
+        self
+    }
+
+    /// Reports manifest validation errors as warnings.
+    pub fn warn_manifest_validation(&mut self, value: bool) -> &mut Self {
+        self.warn_manifest_validation = value;
+        self
+    }
+
+    pub fn verbose(&mut self, verbose: bool) -> &mut Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Returns arguments of `aapt2 link`.
+    pub fn args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec!["link".into()];
+        let mut value = |flag: &str, value: OsString| {
+            args.push(flag.into());
+            args.push(value);
+        };
+        value("-o", self.output.clone().into());
+        value("--manifest", self.manifest.clone().into());
+        for include in &self.includes {
+            value("-I", include.clone().into());
+        }
+        for assets in &self.assets {
+            value("-A", assets.clone().into());
+        }
+        for overlay in &self.overlays {
+            value("-R", overlay.clone().into());
+        }
+        let paths = [
+            ("--java", &self.java),
+            ("--proguard", &self.proguard),
+            ("--proguard-main-dex", &self.proguard_main_dex),
+            ("--output-text-symbols", &self.output_text_symbols),
+            ("--stable-ids", &self.stable_ids),
+            ("--emit-ids", &self.emit_ids),
+        ];
+        for (flag, path) in paths {
+            if let Some(path) = path {
+                value(flag, path.clone().into());
+            }
+        }
+        let numbers = [
+            ("--min-sdk-version", self.min_sdk_version),
+            ("--target-sdk-version", self.target_sdk_version),
+            ("--version-code", self.version_code),
+            ("--version-code-major", self.version_code_major),
+            ("--compile-sdk-version-code", self.compile_sdk_version_code),
+        ];
+        for (flag, number) in numbers {
+            if let Some(number) = number {
+                value(flag, number.to_string().into());
+            }
+        }
+        let strings = [
+            ("--package-id", &self.package_id),
+            ("--custom-package", &self.custom_package),
+            ("--private-symbols", &self.private_symbols),
+            ("--version-name", &self.version_name),
+            ("--compile-sdk-version-name", &self.compile_sdk_version_name),
+            ("--rename-manifest-package", &self.rename_manifest_package),
+            ("--rename-resources-package", &self.rename_resources_package),
+            (
+                "--rename-instrumentation-target-package",
+                &self.rename_instrumentation_target_package,
+            ),
+            ("--preferred-density", &self.preferred_density),
+            ("--no-compress-regex", &self.no_compress_regex),
+        ];
+        for (flag, string) in strings {
+            if let Some(string) = string {
+                value(flag, string.into());
+            }
+        }
+        for package in &self.extra_packages {
+            value("--extra-packages", package.into());
+        }
+        if !self.configs.is_empty() {
+            value("-c", self.configs.join(",").into());
+        }
+        for config in &self.exclude_configs {
+            value("--exclude-configs", config.into());
+        }
+        for (path, configs) in &self.splits {
+            let mut split = path.clone().into_os_string();
+            split.push(format!(":{}", configs.join(",")));
+            value("--split", split);
+        }
+        for extension in &self.no_compress_extensions {
+            value("-0", extension.into());
+        }
+        let flags = [
+            (
+                "--allow-reserved-package-id",
+                self.allow_reserved_package_id,
+            ),
+            ("--auto-add-overlay", self.auto_add_overlay),
+            (
+                "--override-styles-instead-of-overlaying",
+                self.override_styles_instead_of_overlaying,
+            ),
+            (
+                "--proguard-conditional-keep-rules",
+                self.proguard_conditional_keep_rules,
+            ),
+            (
+                "--proguard-minimal-keep-rules",
+                self.proguard_minimal_keep_rules,
+            ),
+            ("--non-final-ids", self.non_final_ids),
+            ("--replace-version", self.replace_version),
+            ("--no-auto-version", self.no_auto_version),
+            ("--no-version-vectors", self.no_version_vectors),
+            ("--no-version-transitions", self.no_version_transitions),
+            ("--no-resource-deduping", self.no_resource_deduping),
+            ("--no-resource-removal", self.no_resource_removal),
+            ("--enable-sparse-encoding", self.enable_sparse_encoding),
+            ("-z", self.require_localization),
+            ("--shared-lib", self.shared_lib),
+            ("--static-lib", self.static_lib),
+            ("--no-static-lib-packages", self.no_static_lib_packages),
+            ("--proto-format", self.proto_format),
+            ("--output-to-dir", self.output_to_dir),
+            ("--merge-only", self.merge_only),
+            ("--no-compress", self.no_compress),
+            ("--keep-raw-values", self.keep_raw_values),
+            ("--exclude-sources", self.exclude_sources),
+            ("--debug-mode", self.debug_mode),
+            ("--strict-visibility", self.strict_visibility),
+            ("--warn-manifest-validation", self.warn_manifest_validation),
+            ("-v", self.verbose),
+        ];
+        args.extend(
+            flags
+                .into_iter()
+                .filter(|(_, enabled)| *enabled)
+                .map(|(flag, _)| flag.into()),
+        );
+        args.extend(self.inputs.iter().map(|input| input.clone().into()));
+        args
+    }
+
+    /// Runs `aapt2 link` command. Returns the output path.
+    pub fn run(&self, sdk: &AndroidSdk) -> Result<PathBuf> {
+        let mut aapt2 = sdk.build_tool(bin!("aapt2"), None)?;
+        aapt2.args(self.args());
+        aapt2.output_err(true)?;
+        Ok(self.output.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aapt2_link_args() {
+        let mut link = Aapt2LinkCommand::new(Path::new("out.apk"), Path::new("Manifest.xml"));
+        link.input(Path::new("res.zip"))
+            .overlay(Path::new("flavor.zip"))
+            .include(Path::new("android.jar"))
+            .package_id("0x7e")
+            .allow_reserved_package_id(true)
+            .stable_ids(Path::new("ids.txt"))
+            .rename_manifest_package("com.crossbow.game.staging")
+            .split(Path::new("hdpi.apk"), &["hdpi", "xhdpi"])
+            .no_version_vectors(true)
+            .proto_format(true);
+        let args: Vec<String> = link
+            .args()
+            .into_iter()
+            .map(|arg| arg.into_string().unwrap())
+            .collect();
+        let expected = [
+            "link",
+            "-o",
+            "out.apk",
+            "--manifest",
+            "Manifest.xml",
+            "-I",
+            "android.jar",
+            "-R",
+            "flavor.zip",
+            "--stable-ids",
+            "ids.txt",
+            "--package-id",
+            "0x7e",
+            "--rename-manifest-package",
+            "com.crossbow.game.staging",
+            "--split",
+            "hdpi.apk:hdpi,xhdpi",
+            "--allow-reserved-package-id",
+            "--no-version-vectors",
+            "--proto-format",
+            "res.zip",
+        ];
+        assert_eq!(args, expected);
+    }
+}
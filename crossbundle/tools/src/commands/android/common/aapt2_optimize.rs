@@ -0,0 +1,93 @@
+use crate::{error::*, types::AndroidSdk};
+use std::path::{Path, PathBuf};
+
+/// ## Aapt2 optimize
+/// Optimizes resources of the linked APK. With `--shorten-resource-paths` long paths
+/// like `res/drawable-xxhdpi/background.png` are replaced with short ones like
+/// `res/a1.png`, which reduces the size of the resource table and the zip directory.
+/// Original paths are written into the shortening map. With
+/// `--collapse-resource-names` resource entry names are replaced with a single value,
+/// so the shipped resource table doesn't reveal them. Resources looked up by name
+/// must be exempted in the resources config.
+///
+/// See more: https://developer.android.com/tools/aapt2#optimize
+#[derive(Clone, Debug)]
+pub struct Aapt2OptimizeCommand {
+    input: PathBuf,
+    output: PathBuf,
+    shorten_resource_paths: bool,
+    resource_path_shortening_map: Option<PathBuf>,
+    collapse_resource_names: bool,
+    resources_config: Option<PathBuf>,
+}
+
+impl Aapt2OptimizeCommand {
+    /// Optimizes `input` APK into `output`.
+    pub fn new(input: &Path, output: &Path) -> Self {
+        Self {
+            input: input.to_owned(),
+            output: output.to_owned(),
+            shorten_resource_paths: false,
+            resource_path_shortening_map: None,
+            collapse_resource_names: false,
+            resources_config: None,
+        }
+    }
+
+    /// Shortens paths of resources inside the APK.
+    pub fn shorten_resource_paths(&mut self, shorten_resource_paths: bool) -> &mut Self {
+        self.shorten_resource_paths = shorten_resource_paths;
+        self
+    }
+
+    /// Path of the map from shortened resource paths to the original ones.
+    pub fn resource_path_shortening_map(&mut self, map: &Path) -> &mut Self {
+        self.resource_path_shortening_map = Some(map.to_owned());
+        self
+    }
+
+    /// Collapses resource entry names into a single value in the resource table.
+    pub fn collapse_resource_names(&mut self, collapse_resource_names: bool) -> &mut Self {
+        self.collapse_resource_names = collapse_resource_names;
+        self
+    }
+
+    /// Path of the resources config with `<type>/<name>#no_collapse` lines that exempt
+    /// resources from collapsing, e.g. the ones read with `getIdentifier`.
+    pub fn resources_config(&mut self, path: &Path) -> &mut Self {
+        self.resources_config = Some(path.to_owned());
+        self
+    }
+
+    /// Writes the resources config that exempts the resources like `string/app_name`
+    /// from collapsing. Returns the config path.
+    pub fn save_resources_config(path: &Path, no_collapse: &[String]) -> Result<PathBuf> {
+        let config: String = no_collapse
+            .iter()
+            .map(|resource| format!("{}#no_collapse\n", resource))
+            .collect();
+        std::fs::write(path, config)?;
+        Ok(path.to_owned())
+    }
+
+    /// Runs `aapt2 optimize -o <output> ... <input>`. Returns the output path.
+    pub fn run(&self, sdk: &AndroidSdk) -> Result<PathBuf> {
+        let mut aapt2 = sdk.build_tool(bin!("aapt2"), None)?;
+        aapt2.arg("optimize").arg("-o").arg(&self.output);
+        if self.shorten_resource_paths {
+            aapt2.arg("--shorten-resource-paths");
+        }
+        if let Some(map) = &self.resource_path_shortening_map {
+            aapt2.arg("--resource-path-shortening-map").arg(map);
+        }
+        if self.collapse_resource_names {
+            aapt2.arg("--collapse-resource-names");
+        }
+        if let Some(resources_config) = &self.resources_config {
+            aapt2.arg("--resources-config-path").arg(resources_config);
+        }
+        aapt2.arg(&self.input);
+        aapt2.output_err(true)?;
+        Ok(self.output.clone())
+    }
+}
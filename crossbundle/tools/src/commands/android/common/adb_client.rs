@@ -0,0 +1,430 @@
+use super::AdbServer;
+use crate::{error::*, types::AndroidSdk};
+use std::{
+    fs::File,
+    io::{Read, Write},
+    net::TcpStream,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Maximum size of the `DATA` packet of the sync protocol.
+const SYNC_DATA_MAX: usize = 64 * 1024;
+
+/// Shell v2 packet ids.
+const SHELL_STDOUT: u8 = 1;
+const SHELL_STDERR: u8 = 2;
+const SHELL_EXIT: u8 = 3;
+
+/// Output of the command executed with [`AdbClient::shell`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShellOutput {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub exit_code: u8,
+}
+
+impl ShellOutput {
+    /// Returns the output if the command succeeded, otherwise fails with its output.
+    pub fn success(self) -> Result<Self> {
+        if self.exit_code == 0 {
+            return Ok(self);
+        }
+        let mut message = String::from_utf8_lossy(&self.stdout).to_string();
+        message.push_str(&String::from_utf8_lossy(&self.stderr));
+        Err(AndroidError::AdbProtocol(message.trim().to_owned()).into())
+    }
+}
+
+/// Client of the adb server that speaks the adb wire protocol directly, so device
+/// operations work without the adb binary, e.g. in a minimal container connected to
+/// the adb server of the host with `ADB_SERVER_SOCKET`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdbClient {
+    server: AdbServer,
+}
+
+impl AdbClient {
+    pub fn new(server: AdbServer) -> Self {
+        Self { server }
+    }
+
+    /// Returns the client if device operations should use the wire protocol instead of
+    /// the adb binary: `CROSSBUNDLE_ADB=native` is set or adb is not installed in the
+    /// SDK. `CROSSBUNDLE_ADB=binary` always uses the adb binary.
+    pub fn preferred(sdk: &AndroidSdk) -> Result<Option<Self>> {
+        let native = match std::env::var("CROSSBUNDLE_ADB").as_deref() {
+            Ok("native") => true,
+            Ok("binary") => false,
+            _ => !sdk
+                .sdk_path()
+                .join("platform-tools")
+                .join(bin!("adb"))
+                .exists(),
+        };
+        if !native {
+            return Ok(None);
+        }
+        Ok(Some(Self::new(AdbServer::from_env()?)))
+    }
+
+    /// Opens the connection to the server and switches it to the device transport.
+    /// Uses the only connected device if `serial` is not specified.
+    fn transport(&self, serial: Option<&str>) -> Result<AdbConnection> {
+        let mut connection = AdbConnection::open(&self.server)?;
+        match serial {
+            Some(serial) => connection.request(&format!("host:transport:{}", serial))?,
+            None => connection.request("host:transport-any")?,
+        }
+        Ok(connection)
+    }
+
+    /// Returns devices in `adb devices -l` format.
+    /// Sends `host:devices-l` request
+    pub fn devices(&self) -> Result<String> {
+        let mut connection = AdbConnection::open(&self.server)?;
+        connection.request("host:devices-l")?;
+        let output = connection.read_hex_string()?;
+        Ok(format!("List of devices attached\n{}", output))
+    }
+
+    /// Executes the shell command on the device with separate stdout, stderr and exit
+    /// code. Sends `shell,v2,raw:<command>` request
+    pub fn shell(&self, serial: Option<&str>, command: &str) -> Result<ShellOutput> {
+        let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+        let exit_code = self.shell_stream(serial, command, |id, data| {
+            match id {
+                SHELL_STDOUT => stdout.extend_from_slice(data),
+                SHELL_STDERR => stderr.extend_from_slice(data),
+                _ => {}
+            }
+            Ok(())
+        })?;
+        Ok(ShellOutput {
+            stdout,
+            stderr,
+            exit_code,
+        })
+    }
+
+    /// Executes the long-running shell command on the device, e.g. `logcat`, and calls
+    /// `callback` for every line of its stdout. Returns the exit code.
+    pub fn shell_lines(
+        &self,
+        serial: Option<&str>,
+        command: &str,
+        mut callback: impl FnMut(&str) -> Result<()>,
+    ) -> Result<u8> {
+        let mut buffer = Vec::new();
+        let exit_code = self.shell_stream(serial, command, |id, data| {
+            if id != SHELL_STDOUT {
+                return Ok(());
+            }
+            buffer.extend_from_slice(data);
+            while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                callback(line.trim_end_matches(|c| c == '\n' || c == '\r'))?;
+            }
+            Ok(())
+        })?;
+        if !buffer.is_empty() {
+            callback(&String::from_utf8_lossy(&buffer))?;
+        }
+        Ok(exit_code)
+    }
+
+    /// Reads shell v2 packets until the command exits and passes their ids and data to
+    /// `callback`. Doesn't time out, the command may be silent for a long time.
+    fn shell_stream(
+        &self,
+        serial: Option<&str>,
+        command: &str,
+        mut callback: impl FnMut(u8, &[u8]) -> Result<()>,
+    ) -> Result<u8> {
+        let mut connection = self.transport(serial)?;
+        connection.request(&format!("shell,v2,raw:{}", command))?;
+        connection.stream.set_read_timeout(None)?;
+        loop {
+            let mut header = [0; 5];
+            if let Err(err) = connection.stream.read_exact(&mut header) {
+                if err.kind() == std::io::ErrorKind::UnexpectedEof {
+                    return Ok(0);
+                }
+                return Err(err.into());
+            }
+            let len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+            let mut data = vec![0; len];
+            connection.stream.read_exact(&mut data)?;
+            match header[0] {
+                SHELL_EXIT => return Ok(data.first().copied().unwrap_or_default()),
+                id => callback(id, &data)?,
+            }
+        }
+    }
+
+    /// Forwards the `local` socket of the host to the `remote` socket of the device,
+    /// e.g. `tcp:8080`. Sends `host-serial:<serial>:forward:<local>;<remote>` request
+    pub fn forward(&self, serial: Option<&str>, local: &str, remote: &str) -> Result<()> {
+        let mut connection = AdbConnection::open(&self.server)?;
+        let prefix = match serial {
+            Some(serial) => format!("host-serial:{}", serial),
+            None => "host".to_owned(),
+        };
+        connection.request(&format!("{}:forward:{};{}", prefix, local, remote))?;
+        // The server confirms the request and then the result of forwarding
+        connection.read_status("forward")
+    }
+
+    /// Pushes the local file to the device with the sync protocol.
+    pub fn push(&self, serial: Option<&str>, local: &Path, remote: &str) -> Result<()> {
+        let mut connection = self.transport(serial)?;
+        connection.request("sync:")?;
+        let mode = 0o100644;
+        connection.sync_packet(b"SEND", format!("{},{}", remote, mode).as_bytes())?;
+        let mut file = File::open(local)?;
+        let mut buf = vec![0; SYNC_DATA_MAX];
+        loop {
+            let len = file.read(&mut buf)?;
+            if len == 0 {
+                break;
+            }
+            connection.sync_packet(b"DATA", &buf[..len])?;
+        }
+        let mtime = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as u32;
+        connection.stream.write_all(b"DONE")?;
+        connection.stream.write_all(&mtime.to_le_bytes())?;
+        let (id, len) = connection.read_sync_header()?;
+        match &id {
+            b"OKAY" => Ok(()),
+            _ => Err(AndroidError::AdbProtocol(connection.read_message(len)?).into()),
+        }
+    }
+
+    /// Pulls the file from the device with the sync protocol.
+    pub fn pull(&self, serial: Option<&str>, remote: &str, local: &Path) -> Result<()> {
+        let mut connection = self.transport(serial)?;
+        connection.request("sync:")?;
+        connection.sync_packet(b"RECV", remote.as_bytes())?;
+        let mut file = File::create(local)?;
+        loop {
+            let (id, len) = connection.read_sync_header()?;
+            match &id {
+                b"DATA" => {
+                    let mut data = vec![0; len as usize];
+                    connection.stream.read_exact(&mut data)?;
+                    file.write_all(&data)?;
+                }
+                b"DONE" => return Ok(()),
+                _ => {
+                    let message = connection.read_message(len)?;
+                    drop(file);
+                    std::fs::remove_file(local)?;
+                    return Err(AndroidError::AdbProtocol(message).into());
+                }
+            }
+        }
+    }
+
+    /// Installs the APK: pushes it into `/data/local/tmp` and runs `pm install -r`.
+    pub fn install(&self, serial: Option<&str>, apk_path: &Path) -> Result<()> {
+        let remote = format!(
+            "/data/local/tmp/{}",
+            apk_path.file_name().unwrap().to_string_lossy()
+        );
+        self.push(serial, apk_path, &remote)?;
+        let output = self.shell(serial, &format!("pm install -r '{}'", remote))?;
+        let _ = self.shell(serial, &format!("rm -f '{}'", remote));
+        output.success()?;
+        Ok(())
+    }
+}
+
+/// Executes the shell command on the device with [`AdbClient`] if it's preferred,
+/// otherwise runs `adb [-s <serial>] shell <args>` command. Arguments are joined with
+/// spaces like adb does, so they must not contain spaces.
+pub fn adb_shell(sdk: &AndroidSdk, device: Option<&str>, args: &[&str]) -> Result<ShellOutput> {
+    if let Some(client) = AdbClient::preferred(sdk)? {
+        return client.shell(device, &args.join(" "));
+    }
+    let mut adb = sdk.adb(device)?;
+    adb.arg("shell").args(args);
+    let output = adb.output()?;
+    Ok(ShellOutput {
+        stdout: output.stdout,
+        stderr: output.stderr,
+        exit_code: output.status.code().unwrap_or(1) as u8,
+    })
+}
+
+/// Connection to the adb server.
+struct AdbConnection {
+    stream: TcpStream,
+}
+
+impl AdbConnection {
+    fn open(server: &AdbServer) -> Result<Self> {
+        let stream = TcpStream::connect((server.host.as_str(), server.port))
+            .map_err(|_| AndroidError::AdbServerUnreachable(server.to_string()))?;
+        stream.set_read_timeout(Some(Duration::from_secs(60)))?;
+        Ok(Self { stream })
+    }
+
+    /// Sends the request prefixed with its hex length and reads the `OKAY` or `FAIL`
+    /// status.
+    fn request(&mut self, request: &str) -> Result<()> {
+        write!(self.stream, "{:04x}{}", request.len(), request)?;
+        self.read_status(request)
+    }
+
+    /// Reads the `OKAY` or `FAIL` status of the request.
+    fn read_status(&mut self, request: &str) -> Result<()> {
+        let mut status = [0; 4];
+        self.stream.read_exact(&mut status)?;
+        match &status {
+            b"OKAY" => Ok(()),
+            b"FAIL" => Err(AndroidError::AdbProtocol(self.read_hex_string()?).into()),
+            _ => Err(AndroidError::AdbProtocol(format!(
+                "unexpected response `{}` to `{}`",
+                String::from_utf8_lossy(&status),
+                request
+            ))
+            .into()),
+        }
+    }
+
+    /// Reads the string prefixed with its hex length.
+    fn read_hex_string(&mut self) -> Result<String> {
+        let mut len = [0; 4];
+        self.stream.read_exact(&mut len)?;
+        let len = usize::from_str_radix(&String::from_utf8_lossy(&len), 16)
+            .map_err(|_| AndroidError::AdbProtocol("invalid length".to_owned()))?;
+        let mut data = vec![0; len];
+        self.stream.read_exact(&mut data)?;
+        Ok(String::from_utf8_lossy(&data).to_string())
+    }
+
+    /// Writes sync packet: 4-byte id, little-endian length and data.
+    fn sync_packet(&mut self, id: &[u8; 4], data: &[u8]) -> Result<()> {
+        self.stream.write_all(id)?;
+        self.stream.write_all(&(data.len() as u32).to_le_bytes())?;
+        self.stream.write_all(data)?;
+        Ok(())
+    }
+
+    fn read_sync_header(&mut self) -> Result<([u8; 4], u32)> {
+        let mut header = [0; 8];
+        self.stream.read_exact(&mut header)?;
+        let id = [header[0], header[1], header[2], header[3]];
+        let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        Ok((id, len))
+    }
+
+    fn read_message(&mut self, len: u32) -> Result<String> {
+        let mut message = vec![0; len as usize];
+        self.stream.read_exact(&mut message)?;
+        Ok(String::from_utf8_lossy(&message).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// Starts a fake adb server that accepts one connection and runs the handler.
+    fn fake_server(handler: impl FnOnce(TcpStream) + Send + 'static) -> AdbClient {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            handler(stream);
+        });
+        AdbClient::new(AdbServer {
+            host: "127.0.0.1".to_owned(),
+            port,
+        })
+    }
+
+    fn read_request(stream: &mut TcpStream) -> String {
+        let mut len = [0; 4];
+        stream.read_exact(&mut len).unwrap();
+        let len = usize::from_str_radix(std::str::from_utf8(&len).unwrap(), 16).unwrap();
+        let mut request = vec![0; len];
+        stream.read_exact(&mut request).unwrap();
+        String::from_utf8(request).unwrap()
+    }
+
+    #[test]
+    fn test_shell_v2() {
+        let client = fake_server(|mut stream| {
+            assert_eq!(read_request(&mut stream), "host:transport:emulator-5554");
+            stream.write_all(b"OKAY").unwrap();
+            assert_eq!(
+                read_request(&mut stream),
+                "shell,v2,raw:getprop ro.product.cpu.abi"
+            );
+            stream.write_all(b"OKAY").unwrap();
+            stream.write_all(&[SHELL_STDOUT, 7, 0, 0, 0]).unwrap();
+            stream.write_all(b"x86_64\n").unwrap();
+            stream.write_all(&[SHELL_EXIT, 1, 0, 0, 0, 0]).unwrap();
+        });
+        let output = client
+            .shell(Some("emulator-5554"), "getprop ro.product.cpu.abi")
+            .unwrap();
+        assert_eq!(output.stdout, b"x86_64\n");
+        assert_eq!(output.exit_code, 0);
+    }
+
+    #[test]
+    fn test_transport_failure() {
+        let client = fake_server(|mut stream| {
+            read_request(&mut stream);
+            stream.write_all(b"FAIL0010device not found").unwrap();
+        });
+        let err = client.shell(Some("missing"), "true").unwrap_err();
+        assert!(err.to_string().contains("device not found"));
+    }
+
+    #[test]
+    fn test_shell_lines() {
+        let client = fake_server(|mut stream| {
+            read_request(&mut stream);
+            stream.write_all(b"OKAY").unwrap();
+            assert_eq!(read_request(&mut stream), "shell,v2,raw:logcat '*:S'");
+            stream.write_all(b"OKAY").unwrap();
+            // Lines split across packets
+            stream.write_all(&[SHELL_STDOUT, 9, 0, 0, 0]).unwrap();
+            stream.write_all(b"first\r\nse").unwrap();
+            stream.write_all(&[SHELL_STDOUT, 6, 0, 0, 0]).unwrap();
+            stream.write_all(b"cond\nt").unwrap();
+            stream.write_all(&[SHELL_EXIT, 1, 0, 0, 0, 1]).unwrap();
+        });
+        let mut lines = Vec::new();
+        let exit_code = client
+            .shell_lines(None, "logcat '*:S'", |line| {
+                lines.push(line.to_owned());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(lines, ["first", "second", "t"]);
+        assert_eq!(exit_code, 1);
+    }
+
+    #[test]
+    fn test_forward() {
+        let client = fake_server(|mut stream| {
+            assert_eq!(
+                read_request(&mut stream),
+                "host-serial:emulator-5554:forward:tcp:8080;tcp:8080"
+            );
+            stream.write_all(b"OKAYOKAY").unwrap();
+        });
+        client
+            .forward(Some("emulator-5554"), "tcp:8080", "tcp:8080")
+            .unwrap();
+    }
+}
@@ -0,0 +1,265 @@
+use super::AdbClient;
+use crate::{error::*, types::AndroidSdk};
+use std::{
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+/// Port of the adb server if neither `ADB_SERVER_SOCKET` nor `ANDROID_ADB_SERVER_PORT`
+/// is set.
+pub const DEFAULT_ADB_SERVER_PORT: u16 = 5037;
+
+/// Address of the adb server. adb clients started by crossbundle inherit the
+/// environment, so they connect to the same server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdbServer {
+    pub host: String,
+    pub port: u16,
+}
+
+/// State of the adb server after [`AdbServer::ensure_running`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdbServerStatus {
+    /// Server was already running with the same version as the client.
+    Running,
+    /// Server wasn't running and was started.
+    Started,
+    /// Server of another version was killed and started again.
+    Restarted,
+}
+
+impl Default for AdbServer {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_owned(),
+            port: DEFAULT_ADB_SERVER_PORT,
+        }
+    }
+}
+
+impl std::fmt::Display for AdbServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "tcp:{}:{}", self.host, self.port)
+    }
+}
+
+impl AdbServer {
+    /// Reads the server address like adb does: `ADB_SERVER_SOCKET`, then
+    /// `ANDROID_ADB_SERVER_ADDRESS` and `ANDROID_ADB_SERVER_PORT`.
+    pub fn from_env() -> Result<Self> {
+        if let Ok(socket) = std::env::var("ADB_SERVER_SOCKET") {
+            return Self::parse_socket(&socket);
+        }
+        let mut server = Self::default();
+        if let Ok(host) = std::env::var("ANDROID_ADB_SERVER_ADDRESS") {
+            server.host = host;
+        }
+        if let Ok(port) = std::env::var("ANDROID_ADB_SERVER_PORT") {
+            server.port = port
+                .parse()
+                .map_err(|_| AndroidError::InvalidAdbServerSocket(port.clone()))?;
+        }
+        Ok(server)
+    }
+
+    /// Parses `tcp:<host>:<port>` or `tcp:<port>` socket specification.
+    pub fn parse_socket(socket: &str) -> Result<Self> {
+        let invalid = || AndroidError::InvalidAdbServerSocket(socket.to_owned());
+        let address = socket.strip_prefix("tcp:").ok_or_else(invalid)?;
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) => (host.trim_matches(|c| c == '[' || c == ']'), port),
+            None => ("127.0.0.1", address),
+        };
+        let port = port.parse().map_err(|_| invalid())?;
+        if host.is_empty() {
+            return Err(invalid().into());
+        }
+        Ok(Self {
+            host: host.to_owned(),
+            port,
+        })
+    }
+
+    /// Returns `true` if the server runs on this host, so it can be started by
+    /// crossbundle.
+    pub fn is_local(&self) -> bool {
+        matches!(self.host.as_str(), "localhost" | "127.0.0.1" | "::1")
+    }
+
+    /// Requests the version of the running server with `host:version` service. Returns
+    /// `None` if the server is not reachable.
+    pub fn server_version(&self) -> Option<u32> {
+        let address = (self.host.as_str(), self.port)
+            .to_socket_addrs()
+            .ok()?
+            .next()?;
+        let mut stream = TcpStream::connect_timeout(&address, Duration::from_secs(2)).ok()?;
+        stream.set_read_timeout(Some(Duration::from_secs(2))).ok()?;
+        let request = "host:version";
+        write!(stream, "{:04x}{}", request.len(), request).ok()?;
+        let mut status = [0; 4];
+        stream.read_exact(&mut status).ok()?;
+        if &status != b"OKAY" {
+            return None;
+        }
+        let mut len = [0; 4];
+        stream.read_exact(&mut len).ok()?;
+        let len = usize::from_str_radix(std::str::from_utf8(&len).ok()?, 16).ok()?;
+        let mut version = vec![0; len];
+        stream.read_exact(&mut version).ok()?;
+        u32::from_str_radix(std::str::from_utf8(&version).ok()?, 16).ok()
+    }
+
+    /// Returns the internal version of the adb client from `adb version`, e.g. `41` for
+    /// `Android Debug Bridge version 1.0.41`.
+    pub fn client_version(sdk: &AndroidSdk) -> Result<u32> {
+        let mut adb = sdk.adb(None)?;
+        adb.arg("version");
+        let output = adb.output_err(false)?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        match parse_adb_version(&stdout) {
+            Some(version) => Ok(version),
+            None => {
+                let mut adb = sdk.adb(None)?;
+                adb.arg("version");
+                Err(Error::CmdFailed(
+                    adb,
+                    stdout.to_string(),
+                    "unknown adb version".to_owned(),
+                ))
+            }
+        }
+    }
+
+    /// Starts the server on this host. Remote servers must be started on their hosts.
+    pub fn start(&self, sdk: &AndroidSdk) -> Result<()> {
+        if !self.is_local() {
+            return Err(AndroidError::AdbServerUnreachable(self.to_string()).into());
+        }
+        let mut adb = sdk.adb(None)?;
+        // `-L` overrides `ADB_SERVER_SOCKET`, unlike `-H` and `-P`
+        adb.arg("-L").arg(self.to_string()).arg("start-server");
+        adb.output_err(true)?;
+        Ok(())
+    }
+
+    /// Kills the server.
+    pub fn kill(&self, sdk: &AndroidSdk) -> Result<()> {
+        let mut adb = sdk.adb(None)?;
+        adb.arg("-L").arg(self.to_string()).arg("kill-server");
+        adb.output_err(false)?;
+        Ok(())
+    }
+
+    /// Starts the server if it's not running and restarts the local server if its version
+    /// doesn't match the client. Otherwise adb would restart the server on the first
+    /// command and disconnect the running ones.
+    ///
+    /// The version doesn't matter for [`AdbClient`], so with the native client the
+    /// server is only checked to be running and `adb version` is not called.
+    pub fn ensure_running(&self, sdk: &AndroidSdk) -> Result<AdbServerStatus> {
+        let native = AdbClient::preferred(sdk)?.is_some();
+        let server_version = match self.server_version() {
+            Some(_) if native => return Ok(AdbServerStatus::Running),
+            Some(version) => version,
+            // Starting the server requires the adb binary
+            None if native => {
+                return Err(AndroidError::AdbServerUnreachable(self.to_string()).into())
+            }
+            None => {
+                self.start(sdk)?;
+                return Ok(AdbServerStatus::Started);
+            }
+        };
+        let client_version = Self::client_version(sdk)?;
+        if server_version == client_version {
+            return Ok(AdbServerStatus::Running);
+        }
+        if !self.is_local() {
+            return Err(AndroidError::AdbVersionMismatch(client_version, server_version).into());
+        }
+        self.kill(sdk)?;
+        self.start(sdk)?;
+        Ok(AdbServerStatus::Restarted)
+    }
+
+    /// Runs adb operation and retries it once if it failed because the server was
+    /// restarted or stopped in the meantime, e.g. by another tool with another adb
+    /// version.
+    pub fn retry<T>(
+        &self,
+        sdk: &AndroidSdk,
+        mut operation: impl FnMut() -> Result<T>,
+    ) -> Result<T> {
+        match operation() {
+            Err(err) if self.server_version().is_none() || is_server_error(&err) => {
+                self.ensure_running(sdk)?;
+                operation()
+            }
+            result => result,
+        }
+    }
+}
+
+/// Returns `true` if the adb command failed because the connection to the server was
+/// lost.
+fn is_server_error(err: &Error) -> bool {
+    let message = err.to_string();
+    [
+        "daemon not running",
+        "cannot connect to daemon",
+        "server version",
+        "protocol fault",
+        "Connection reset",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+fn parse_adb_version(output: &str) -> Option<u32> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Android Debug Bridge version "))
+        .and_then(|version| version.rsplit('.').next())
+        .and_then(|version| version.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_parse_socket() {
+        let server = AdbServer::parse_socket("tcp:192.168.1.10:5038").unwrap();
+        assert_eq!(server.host, "192.168.1.10");
+        assert_eq!(server.port, 5038);
+        assert!(!server.is_local());
+        assert_eq!(AdbServer::parse_socket("tcp:5039").unwrap().port, 5039);
+        assert!(AdbServer::parse_socket("localhost:5037").is_err());
+        assert_eq!(
+            parse_adb_version("Android Debug Bridge version 1.0.41\nVersion 34.0.4-10411341\n"),
+            Some(41)
+        );
+    }
+
+    #[test]
+    fn test_server_version() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 16];
+            stream.read_exact(&mut request).unwrap();
+            assert_eq!(&request, b"000chost:version");
+            stream.write_all(b"OKAY00040029").unwrap();
+        });
+        let adb_server = AdbServer {
+            host: "127.0.0.1".to_owned(),
+            port,
+        };
+        assert_eq!(adb_server.server_version(), Some(41));
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,185 @@
+use super::{aapt2_dump_badging, aapt2_dump_resources};
+use crate::{error::*, types::AndroidSdk};
+use serde::Serialize;
+use std::{collections::BTreeMap, fs::File, path::Path};
+
+/// Category of the APK entry in the size breakdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApkCategory {
+    NativeLibs,
+    Dex,
+    Resources,
+    Assets,
+    Other,
+}
+
+impl ApkCategory {
+    /// Returns category of the entry by its path in the APK.
+    pub fn from_path(path: &str) -> Self {
+        if path.starts_with("lib/") {
+            Self::NativeLibs
+        } else if path.ends_with(".dex") && !path.contains('/') {
+            Self::Dex
+        } else if path.starts_with("res/")
+            || path == "resources.arsc"
+            || path == "AndroidManifest.xml"
+        {
+            Self::Resources
+        } else if path.starts_with("assets/") {
+            Self::Assets
+        } else {
+            Self::Other
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::NativeLibs => "native libs",
+            Self::Dex => "dex",
+            Self::Resources => "resources",
+            Self::Assets => "assets",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Size of the APK entry. Download size is the compressed size of the entry in the
+/// APK, install size is the uncompressed size on the device.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApkEntrySize {
+    pub path: String,
+    pub category: ApkCategory,
+    pub download_size: u64,
+    pub install_size: u64,
+}
+
+/// Total size of the entries of the category.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApkCategorySize {
+    pub category: ApkCategory,
+    pub files: usize,
+    pub download_size: u64,
+    pub install_size: u64,
+}
+
+/// Size breakdown of the APK.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ApkAnalysis {
+    pub package: Option<String>,
+    pub version_code: Option<u32>,
+    pub version_name: Option<String>,
+    /// Size of the APK file.
+    pub file_size: u64,
+    /// Sum of compressed sizes of the entries.
+    pub download_size: u64,
+    /// Sum of uncompressed sizes of the entries.
+    pub install_size: u64,
+    /// Number of resource entries by resource type, e.g. `drawable`.
+    pub resource_types: BTreeMap<String, usize>,
+    /// Categories sorted by download size, largest first.
+    pub categories: Vec<ApkCategorySize>,
+    /// Entries sorted by download size, largest first.
+    pub entries: Vec<ApkEntrySize>,
+}
+
+impl ApkAnalysis {
+    /// Reads the package with `aapt2 dump badging`, resource types with
+    /// `aapt2 dump resources` and sizes of the APK entries.
+    pub fn read(sdk: &AndroidSdk, apk_path: &Path) -> Result<Self> {
+        if !apk_path.exists() {
+            return Err(Error::PathNotFound(apk_path.to_owned()));
+        }
+        let mut analysis = Self::from_archive(apk_path)?;
+        let badging = aapt2_dump_badging(sdk, apk_path)?;
+        analysis.package = badging.package;
+        analysis.version_code = badging.version_code;
+        analysis.version_name = badging.version_name;
+        analysis.resource_types = aapt2_dump_resources(sdk, apk_path)?.type_counts();
+        Ok(analysis)
+    }
+
+    /// Measures entries of the APK without reading its manifest.
+    pub fn from_archive(apk_path: &Path) -> Result<Self> {
+        let mut analysis = Self {
+            file_size: std::fs::metadata(apk_path)?.len(),
+            ..Default::default()
+        };
+        let mut archive = zip::ZipArchive::new(File::open(apk_path)?)?;
+        let mut categories: BTreeMap<ApkCategory, ApkCategorySize> = BTreeMap::new();
+        for i in 0..archive.len() {
+            let file = archive.by_index(i)?;
+            if file.is_dir() {
+                continue;
+            }
+            let entry = ApkEntrySize {
+                path: file.name().to_owned(),
+                category: ApkCategory::from_path(file.name()),
+                download_size: file.compressed_size(),
+                install_size: file.size(),
+            };
+            let category = categories
+                .entry(entry.category)
+                .or_insert_with(|| ApkCategorySize {
+                    category: entry.category,
+                    files: 0,
+                    download_size: 0,
+                    install_size: 0,
+                });
+            category.files += 1;
+            category.download_size += entry.download_size;
+            category.install_size += entry.install_size;
+            analysis.download_size += entry.download_size;
+            analysis.install_size += entry.install_size;
+            analysis.entries.push(entry);
+        }
+        analysis.categories = categories.into_values().collect();
+        analysis
+            .categories
+            .sort_by_key(|category| std::cmp::Reverse(category.download_size));
+        analysis.entries.sort_by(|a, b| {
+            b.download_size
+                .cmp(&a.download_size)
+                .then(a.path.cmp(&b.path))
+        });
+        Ok(analysis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_apk_analysis() {
+        let dir = tempfile::tempdir().unwrap();
+        let apk = dir.path().join("game.apk");
+        let mut zip = zip::ZipWriter::new(File::create(&apk).unwrap());
+        let stored =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("lib/arm64-v8a/libgame.so", stored).unwrap();
+        zip.write_all(&[1; 4096]).unwrap();
+        zip.start_file("classes.dex", stored).unwrap();
+        zip.write_all(&[2; 512]).unwrap();
+        zip.start_file("assets/level.dat", stored).unwrap();
+        zip.write_all(&[3; 1024]).unwrap();
+        zip.start_file("res/drawable/icon.png", stored).unwrap();
+        zip.write_all(&[4; 256]).unwrap();
+        zip.finish().unwrap();
+
+        let analysis = ApkAnalysis::from_archive(&apk).unwrap();
+        assert_eq!(analysis.download_size, 4096 + 512 + 1024 + 256);
+        let categories: Vec<_> = analysis.categories.iter().map(|c| c.category).collect();
+        assert_eq!(
+            categories,
+            [
+                ApkCategory::NativeLibs,
+                ApkCategory::Assets,
+                ApkCategory::Dex,
+                ApkCategory::Resources
+            ]
+        );
+        assert_eq!(analysis.entries[0].path, "lib/arm64-v8a/libgame.so");
+    }
+}
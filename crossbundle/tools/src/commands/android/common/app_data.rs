@@ -0,0 +1,207 @@
+use super::force_stop_app;
+use crate::{commands::COVERAGE_DIR, error::*, types::AndroidSdk};
+use std::path::{Path, PathBuf};
+
+/// Deletes all data of the application: files, databases, preferences and cache. Runtime
+/// permissions are reset too.
+/// Runs `adb shell pm clear <package>` command
+pub fn clear_app_data(sdk: &AndroidSdk, package: &str, device: Option<&str>) -> Result<()> {
+    let mut adb = sdk.adb(device)?;
+    adb.args(["shell", "pm", "clear", package]);
+    adb.output_err(true)?;
+    Ok(())
+}
+
+/// Grants the runtime permission to the application.
+/// Runs `adb shell pm grant <package> <permission>` command
+pub fn grant_permission(
+    sdk: &AndroidSdk,
+    package: &str,
+    permission: &str,
+    device: Option<&str>,
+) -> Result<()> {
+    let mut adb = sdk.adb(device)?;
+    adb.args(["shell", "pm", "grant", package])
+        .arg(qualified_permission(permission));
+    adb.output_err(true)?;
+    Ok(())
+}
+
+/// Revokes the runtime permission from the application.
+/// Runs `adb shell pm revoke <package> <permission>` command
+pub fn revoke_permission(
+    sdk: &AndroidSdk,
+    package: &str,
+    permission: &str,
+    device: Option<&str>,
+) -> Result<()> {
+    let mut adb = sdk.adb(device)?;
+    adb.args(["shell", "pm", "revoke", package])
+        .arg(qualified_permission(permission));
+    adb.output_err(true)?;
+    Ok(())
+}
+
+/// Opens the URL with `VIEW` intent. If the package is specified, the intent is
+/// delivered only to the application, so App Links verification is not needed.
+/// Runs `adb shell am start -W -a android.intent.action.VIEW -d <url> [<package>]` command
+pub fn open_deeplink(
+    sdk: &AndroidSdk,
+    url: &str,
+    package: Option<&str>,
+    device: Option<&str>,
+) -> Result<()> {
+    let mut adb = sdk.adb(device)?;
+    adb.args([
+        "shell",
+        "am",
+        "start",
+        "-W",
+        "-a",
+        "android.intent.action.VIEW",
+    ])
+    .arg("-d")
+    // Shell on the device splits the command by spaces and treats `&` specially
+    .arg(format!("'{}'", url.replace('\'', "'\\''")));
+    if let Some(package) = package {
+        adb.arg(package);
+    }
+    adb.output_err(true)?;
+    Ok(())
+}
+
+/// Saves data of the application into the tar archive. Caches are not included. Works
+/// only for debuggable applications.
+/// Runs `adb exec-out run-as <package> tar -cf - .` command
+pub fn backup_app_data(
+    sdk: &AndroidSdk,
+    package: &str,
+    output: &Path,
+    device: Option<&str>,
+) -> Result<()> {
+    // `run-as` starts the command in the application data directory
+    let mut adb = sdk.adb(device)?;
+    adb.args(["exec-out", "run-as", package, "tar", "-cf", "-"])
+        .args(["--exclude=./cache", "--exclude=./code_cache", "."]);
+    let archive = adb.output_err(true)?.stdout;
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(output, archive)?;
+    Ok(())
+}
+
+/// Extracts the tar archive created by [`backup_app_data`] into the application data
+/// directory. Existing files are overwritten, other files are kept. The application is
+/// stopped first. Works only for debuggable applications.
+pub fn restore_app_data(
+    sdk: &AndroidSdk,
+    package: &str,
+    input: &Path,
+    device: Option<&str>,
+) -> Result<()> {
+    if !input.exists() {
+        return Err(Error::PathNotFound(input.to_owned()));
+    }
+    force_stop_app(sdk, package, device)?;
+    let remote_path = format!("/data/local/tmp/crossbundle-{}.tar", package);
+    // Runs `adb push <input> /data/local/tmp/...`
+    let mut adb = sdk.adb(device)?;
+    adb.arg("push").arg(input).arg(&remote_path);
+    adb.output_err(true)?;
+    // Runs `adb shell run-as <package> tar -xf /data/local/tmp/...`
+    let mut adb = sdk.adb(device)?;
+    adb.args(["shell", "run-as", package, "tar", "-xf", &remote_path]);
+    let extracted = adb.output_err(true);
+    let mut adb = sdk.adb(device)?;
+    adb.args(["shell", "rm", "-f", &remote_path]);
+    adb.output_err(false)?;
+    extracted?;
+    Ok(())
+}
+
+/// Copies coverage profiles written by the application built with coverage into the
+/// directory. Returns paths of the copied files. Works only for debuggable applications.
+/// Runs `adb exec-out run-as <package> cat files/coverage/<file>` command
+pub fn pull_coverage_profiles(
+    sdk: &AndroidSdk,
+    package: &str,
+    output_dir: &Path,
+    device: Option<&str>,
+) -> Result<Vec<PathBuf>> {
+    let remote_dir = format!("files/{}", COVERAGE_DIR);
+    let mut adb = sdk.adb(device)?;
+    adb.args(["shell", "run-as", package, "ls", &remote_dir]);
+    // The directory doesn't exist if the application didn't write any profiles
+    let listing = match adb.output_err(false) {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).to_string(),
+        Err(_) => return Ok(Vec::new()),
+    };
+    std::fs::create_dir_all(output_dir)?;
+    let mut profiles = Vec::new();
+    for name in listing
+        .split_whitespace()
+        .filter(|name| name.ends_with(".profraw"))
+    {
+        let mut adb = sdk.adb(device)?;
+        adb.args([
+            "exec-out",
+            "run-as",
+            package,
+            "cat",
+            &format!("{}/{}", remote_dir, name),
+        ]);
+        let profile = output_dir.join(name);
+        std::fs::write(&profile, adb.output_err(true)?.stdout)?;
+        profiles.push(profile);
+    }
+    Ok(profiles)
+}
+
+/// Deletes coverage profiles written by the application, so the next run starts with
+/// empty coverage.
+/// Runs `adb shell run-as <package> rm -rf files/coverage` command
+pub fn clear_coverage_profiles(
+    sdk: &AndroidSdk,
+    package: &str,
+    device: Option<&str>,
+) -> Result<()> {
+    let mut adb = sdk.adb(device)?;
+    adb.args([
+        "shell",
+        "run-as",
+        package,
+        "rm",
+        "-rf",
+        &format!("files/{}", COVERAGE_DIR),
+    ]);
+    adb.output_err(true)?;
+    Ok(())
+}
+
+/// Adds `android.permission.` prefix to the short permission name like `CAMERA`.
+pub fn qualified_permission(permission: &str) -> String {
+    if permission.contains('.') {
+        permission.to_owned()
+    } else {
+        format!("android.permission.{}", permission.to_ascii_uppercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qualified_permission() {
+        assert_eq!(qualified_permission("camera"), "android.permission.CAMERA");
+        assert_eq!(
+            qualified_permission("android.permission.POST_NOTIFICATIONS"),
+            "android.permission.POST_NOTIFICATIONS"
+        );
+        assert_eq!(
+            qualified_permission("com.crossbow.game.permission.SYNC"),
+            "com.crossbow.game.permission.SYNC"
+        );
+    }
+}
@@ -0,0 +1,42 @@
+use super::adb_shell;
+use crate::{commands::DeviceAppearance, error::*, types::AndroidSdk};
+
+/// Applies the appearance on the device or emulator. Locale is set only for the
+/// application with per-app language preferences, which requires Android 13 (API level
+/// 33). Font scale and dark mode are applied system-wide.
+pub fn apply_android_appearance(
+    sdk: &AndroidSdk,
+    package: &str,
+    device: Option<&str>,
+    appearance: &DeviceAppearance,
+) -> Result<()> {
+    if let Some(locale) = &appearance.locale {
+        // Runs `adb shell cmd locale set-app-locales <package> --locales <locale>`
+        let mut adb = sdk.adb(device)?;
+        adb.args(["shell", "cmd", "locale", "set-app-locales", package])
+            .args(["--user", "current", "--locales", locale]);
+        adb.output_err(true)?;
+    }
+    if let Some(font_scale) = appearance.font_scale {
+        // Runs `adb shell settings put system font_scale <scale>`
+        let mut adb = sdk.adb(device)?;
+        adb.args(["shell", "settings", "put", "system", "font_scale"])
+            .arg(font_scale.to_string());
+        adb.output_err(true)?;
+    }
+    if let Some(dark_mode) = appearance.dark_mode {
+        // Runs `adb shell cmd uimode night yes|no`
+        let mut adb = sdk.adb(device)?;
+        adb.args(["shell", "cmd", "uimode", "night"])
+            .arg(if dark_mode { "yes" } else { "no" });
+        adb.output_err(true)?;
+    }
+    Ok(())
+}
+
+/// Stops the application, so the next start picks up the new configuration.
+/// Runs `adb shell am force-stop <package>` command
+pub fn force_stop_app(sdk: &AndroidSdk, package: &str, device: Option<&str>) -> Result<()> {
+    adb_shell(sdk, device, &["am", "force-stop", package])?.success()?;
+    Ok(())
+}
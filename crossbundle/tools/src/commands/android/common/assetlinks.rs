@@ -0,0 +1,87 @@
+use crate::error::*;
+use android_manifest::AndroidManifest;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize)]
+struct AssetLinkStatement<'a> {
+    relation: [&'static str; 1],
+    target: AssetLinkTarget<'a>,
+}
+
+#[derive(Serialize)]
+struct AssetLinkTarget<'a> {
+    namespace: &'static str,
+    package_name: &'a str,
+    sha256_cert_fingerprints: &'a [String],
+}
+
+/// Returns hosts of the App Links declared in the manifest. App Links are `http` or
+/// `https` intent filters with `android:autoVerify="true"`.
+pub fn app_link_hosts(manifest: &AndroidManifest) -> Vec<String> {
+    let mut hosts: Vec<String> = manifest
+        .application
+        .activity
+        .iter()
+        .flat_map(|activity| activity.intent_filter.iter())
+        .filter(|intent_filter| intent_filter.auto_verify == Some(true))
+        .filter(|intent_filter| {
+            intent_filter
+                .data
+                .iter()
+                .any(|data| matches!(data.scheme.as_deref(), Some("http") | Some("https")))
+        })
+        .flat_map(|intent_filter| intent_filter.data.iter())
+        .filter_map(|data| data.host.clone())
+        .collect();
+    hosts.sort();
+    hosts.dedup();
+    hosts
+}
+
+/// Generates Digital Asset Links statement that allows the application signed with given
+/// certificates to handle App Links of the website.
+///
+/// See more: https://developer.android.com/training/app-links/verify-site-associations
+pub fn gen_assetlinks(package: &str, sha256_fingerprints: &[String]) -> Result<String> {
+    let statements = [AssetLinkStatement {
+        relation: ["delegate_permission/common.handle_all_urls"],
+        target: AssetLinkTarget {
+            namespace: "android_app",
+            package_name: package,
+            sha256_cert_fingerprints: sha256_fingerprints,
+        },
+    }];
+    Ok(serde_json::to_string_pretty(&statements)?)
+}
+
+/// Saves `.well-known/assetlinks.json` into given directory. The file should be
+/// published on every host returned by [`app_link_hosts`].
+pub fn save_assetlinks(
+    out_dir: &Path,
+    package: &str,
+    sha256_fingerprints: &[String],
+) -> Result<PathBuf> {
+    let well_known = out_dir.join(".well-known");
+    std::fs::create_dir_all(&well_known)?;
+    let path = well_known.join("assetlinks.json");
+    std::fs::write(&path, gen_assetlinks(package, sha256_fingerprints)?)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gen_assetlinks() {
+        let json = gen_assetlinks("com.crossbow.game", &["AB:CD".to_owned()]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[0]["target"]["package_name"], "com.crossbow.game");
+        assert_eq!(value[0]["target"]["sha256_cert_fingerprints"][0], "AB:CD");
+        assert_eq!(
+            value[0]["relation"][0],
+            "delegate_permission/common.handle_all_urls"
+        );
+    }
+}
@@ -1,17 +1,19 @@
+use super::adb_shell;
 use crate::{error::*, types::AndroidSdk};
 use std::process::Command;
+use std::time::{Duration, Instant};
 
 /// Returns `adb logcat` command
-fn logcat_cmd(sdk: &AndroidSdk) -> Result<Command> {
-    let mut adb = sdk.platform_tool(bin!("adb"))?;
+fn logcat_cmd(sdk: &AndroidSdk, device: Option<&str>) -> Result<Command> {
+    let mut adb = sdk.adb(device)?;
     adb.arg("logcat");
     Ok(adb)
 }
 
 /// Attach logger to device with filter that passes only Rust Stdout or Stderr.
 /// Runs`adb logcat RustStdoutStderr:D '*:S'` command
-pub fn attach_logger_only_rust(sdk: &AndroidSdk) -> Result<()> {
-    let mut adb = logcat_cmd(sdk)?;
+pub fn attach_logger_only_rust(sdk: &AndroidSdk, device: Option<&str>) -> Result<()> {
+    let mut adb = logcat_cmd(sdk, device)?;
     adb.arg("RustStdoutStderr:D")
         .arg("SAPP:D")
         .arg("Crossbow:D")
@@ -21,28 +23,56 @@ pub fn attach_logger_only_rust(sdk: &AndroidSdk) -> Result<()> {
     Ok(())
 }
 
-/// Attach logger to device with filter that filters only App Stdout or Stderr.
-/// Runs`adb logcat --pid=`adb shell pidof -s com.crossbow.game`` command
-pub fn attach_logger_only_app(sdk: &AndroidSdk) -> Result<()> {
-    let mut adb_shell = sdk.platform_tool(bin!("adb"))?;
-    adb_shell.args(["shell", "pidof", "-s", "com.crossbow.game"]);
-    let res = adb_shell.output()?.stdout;
-    let pid = String::from_utf8_lossy(&res).to_string();
+/// Returns PID of running application or `None` if application is not running.
+/// Runs `adb shell pidof -s <package>` command
+pub fn app_pid(sdk: &AndroidSdk, package: &str, device: Option<&str>) -> Result<Option<u32>> {
+    // `pidof` exits with 1 if the process is not running
+    let output = adb_shell(sdk, device, &["pidof", "-s", package])?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().parse().ok())
+}
+
+/// Waits until application process is started and returns its PID.
+pub fn wait_for_app_pid(
+    sdk: &AndroidSdk,
+    package: &str,
+    device: Option<&str>,
+    timeout: Duration,
+) -> Result<u32> {
+    let start = Instant::now();
+    loop {
+        if let Some(pid) = app_pid(sdk, package, device)? {
+            return Ok(pid);
+        }
+        if start.elapsed() >= timeout {
+            return Err(AndroidError::AppProcessNotFound(package.to_owned()).into());
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+}
 
-    let mut adb = logcat_cmd(sdk)?;
-    adb.arg("--pid").arg(pid.trim());
+/// Attach logger to device with filter that passes only output of the given process.
+/// Additional logcat filter specs like `RustStdoutStderr:D` can be provided.
+/// Blocks until logcat exits or is interrupted with Ctrl-C.
+/// Runs `adb logcat --pid=<pid> [filters...]` command
+pub fn attach_logger_to_pid(
+    sdk: &AndroidSdk,
+    pid: u32,
+    device: Option<&str>,
+    filters: &[String],
+) -> Result<()> {
+    let mut adb = logcat_cmd(sdk, device)?;
+    adb.arg(format!("--pid={}", pid)).args(filters);
     adb.spawn()?.wait()?;
     Ok(())
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-
-//     #[test]
-//     fn test_attach_logger_only_app() -> Result<()> {
-//         let sdk = AndroidSdk::from_env()?;
-//         attach_logger_only_app(&sdk)?;
-//         Ok(())
-//     }
-// }
+/// Waits until application is started and attaches logger to its process.
+pub fn attach_logger_only_app(
+    sdk: &AndroidSdk,
+    package: &str,
+    device: Option<&str>,
+    filters: &[String],
+) -> Result<()> {
+    let pid = wait_for_app_pid(sdk, package, device, Duration::from_secs(10))?;
+    attach_logger_to_pid(sdk, pid, device, filters)
+}
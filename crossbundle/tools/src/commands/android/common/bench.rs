@@ -0,0 +1,293 @@
+use super::cargo_env_target_cfg;
+use crate::{
+    error::*,
+    types::{AndroidNdk, AndroidSdk, AndroidTarget, IntoRustTriple},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Directory on the device the benchmarks are pushed to and run in.
+pub const DEVICE_BENCH_DIR: &str = "/data/local/tmp/crossbundle-bench";
+
+/// Builds benchmarks of the package for the Android target with the NDK toolchain.
+/// Returns paths of the benchmark executables.
+/// Runs `cargo bench --no-run --target <triple> --message-format=json` command
+pub fn build_android_benches(
+    ndk: &AndroidNdk,
+    build_target: AndroidTarget,
+    platform: u32,
+    project_path: &Path,
+    bench: Option<&str>,
+    features: &[String],
+) -> Result<Vec<PathBuf>> {
+    let rust_triple = build_target.rust_triple();
+    let (clang, clang_pp) = ndk.clang(build_target, platform)?;
+    let mut cargo = Command::new("cargo");
+    cargo
+        .current_dir(project_path)
+        .args(["bench", "--no-run", "--message-format=json"])
+        .args(["--target", rust_triple])
+        .env(format!("CC_{}", rust_triple), &clang)
+        .env(format!("CXX_{}", rust_triple), &clang_pp)
+        .env(cargo_env_target_cfg("LINKER", rust_triple), &clang)
+        .env(
+            format!("AR_{}", rust_triple),
+            ndk.toolchain_bin("ar", build_target)?,
+        );
+    if let Some(bench) = bench {
+        cargo.args(["--bench", bench]);
+    }
+    for feature in features {
+        cargo.args(["--features", feature]);
+    }
+    let output = cargo.output_err(true)?;
+    Ok(parse_bench_executables(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Returns executables of bench targets from JSON messages of cargo.
+pub fn parse_bench_executables(output: &str) -> Vec<PathBuf> {
+    let mut executables = Vec::new();
+    for line in output.lines() {
+        let message: serde_json::Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(_) => continue,
+        };
+        let is_bench = message["target"]["kind"]
+            .as_array()
+            .map_or(false, |kinds| kinds.iter().any(|kind| kind == "bench"));
+        if message["reason"] == "compiler-artifact" && is_bench {
+            if let Some(executable) = message["executable"].as_str() {
+                executables.push(PathBuf::from(executable));
+            }
+        }
+    }
+    executables
+}
+
+/// CPU frequency governors changed to `performance` for stable measurements.
+#[derive(Debug, Clone, Default)]
+pub struct CpuGovernors {
+    saved: Vec<(String, String)>,
+}
+
+impl CpuGovernors {
+    /// Switches governors of all CPU cores to `performance`. Requires root, which is
+    /// available on emulators and `userdebug` builds. Returns `None` if the governors
+    /// can't be changed.
+    pub fn lock_performance(sdk: &AndroidSdk, device: &str) -> Result<Option<Self>> {
+        let mut adb = sdk.adb(Some(device))?;
+        adb.args([
+            "shell",
+            "for f in /sys/devices/system/cpu/cpu*/cpufreq/scaling_governor; \
+             do echo $f $(cat $f); done",
+        ]);
+        let output = adb.output_err(false)?;
+        let saved: Vec<(String, String)> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .filter(|(_, governor)| !governor.is_empty())
+            .map(|(path, governor)| (path.to_owned(), governor.trim().to_owned()))
+            .collect();
+        if saved.is_empty() {
+            return Ok(None);
+        }
+        let governors = Self { saved };
+        if governors.write(sdk, device, |_| "performance").is_err() {
+            return Ok(None);
+        }
+        Ok(Some(governors))
+    }
+
+    /// Restores the previous governors.
+    pub fn restore(&self, sdk: &AndroidSdk, device: &str) -> Result<()> {
+        self.write(sdk, device, |governor| governor)
+    }
+
+    fn write<'a>(
+        &'a self,
+        sdk: &AndroidSdk,
+        device: &str,
+        governor: impl Fn(&'a str) -> &'a str,
+    ) -> Result<()> {
+        let script: Vec<String> = self
+            .saved
+            .iter()
+            .map(|(path, saved)| format!("echo {} > {}", governor(saved), path))
+            .collect();
+        let mut adb = sdk.adb(Some(device))?;
+        adb.args(["shell", "su", "0", "sh", "-c"])
+            .arg(format!("'{}'", script.join("; ")));
+        adb.output_err(true)?;
+        Ok(())
+    }
+}
+
+/// Mean time of the benchmark measured by criterion.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct BenchEstimate {
+    /// Benchmark ID like `physics/step/1000`.
+    pub id: String,
+    /// Mean time of one iteration in nanoseconds.
+    pub mean_ns: f64,
+}
+
+/// Pushes the benchmark executable to the device, runs it and reads criterion
+/// estimates. Returns the estimates and the output of the benchmark.
+pub fn run_android_bench(
+    sdk: &AndroidSdk,
+    device: &str,
+    executable: &Path,
+    filter: Option<&str>,
+) -> Result<(Vec<BenchEstimate>, String)> {
+    let name = executable
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+    let remote_path = format!("{}/{}", DEVICE_BENCH_DIR, name);
+    let criterion_home = format!("{}/criterion", DEVICE_BENCH_DIR);
+    // Results of previous runs would be read as the new ones
+    let mut adb = sdk.adb(Some(device))?;
+    adb.args(["shell", "rm", "-rf", &criterion_home, "&&", "mkdir", "-p"])
+        .arg(DEVICE_BENCH_DIR);
+    adb.output_err(true)?;
+    let mut adb = sdk.adb(Some(device))?;
+    adb.arg("push").arg(executable).arg(&remote_path);
+    adb.output_err(true)?;
+    let mut adb = sdk.adb(Some(device))?;
+    adb.args(["shell", "chmod", "755", &remote_path]);
+    adb.output_err(true)?;
+
+    let mut adb = sdk.adb(Some(device))?;
+    adb.args(["shell", "cd", DEVICE_BENCH_DIR, "&&"])
+        .arg(format!("CRITERION_HOME={}", criterion_home))
+        .args([&remote_path, "--bench", "--noplot"]);
+    if let Some(filter) = filter {
+        adb.arg(format!("'{}'", filter.replace('\'', "'\\''")));
+    }
+    let output = adb.output_err(true)?;
+    let log = String::from_utf8_lossy(&output.stdout).to_string();
+
+    let mut adb = sdk.adb(Some(device))?;
+    adb.args([
+        "shell",
+        "find",
+        &criterion_home,
+        "-path",
+        "'*/new/estimates.json'",
+    ]);
+    let output = adb.output_err(true)?;
+    let mut estimates = Vec::new();
+    for path in String::from_utf8_lossy(&output.stdout).lines() {
+        let path = path.trim();
+        let id = match path
+            .strip_prefix(&format!("{}/", criterion_home))
+            .and_then(|path| path.strip_suffix("/new/estimates.json"))
+        {
+            Some(id) => id.to_owned(),
+            None => continue,
+        };
+        let mut adb = sdk.adb(Some(device))?;
+        adb.args(["exec-out", "cat", path]);
+        let json = adb.output_err(true)?.stdout;
+        estimates.push(BenchEstimate {
+            id,
+            mean_ns: parse_criterion_mean(&String::from_utf8_lossy(&json))?,
+        });
+    }
+    estimates.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok((estimates, log))
+}
+
+/// Returns the mean point estimate in nanoseconds from criterion `estimates.json`.
+pub fn parse_criterion_mean(estimates: &str) -> Result<f64> {
+    let estimates: serde_json::Value = serde_json::from_str(estimates)?;
+    estimates["mean"]["point_estimate"]
+        .as_f64()
+        .ok_or_else(|| AndroidError::InvalidBenchResults("mean.point_estimate".to_owned()).into())
+}
+
+/// Benchmark result compared with the baseline.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchComparison {
+    pub id: String,
+    pub mean_ns: f64,
+    pub baseline_ns: Option<f64>,
+    /// Change of the mean time relative to the baseline in percent.
+    pub change_percent: Option<f64>,
+    /// The benchmark is slower than the baseline by more than the threshold.
+    pub regressed: bool,
+}
+
+/// Compares estimates with the baseline: means in nanoseconds by benchmark ID.
+pub fn compare_bench_results(
+    estimates: &[BenchEstimate],
+    baseline: &BTreeMap<String, f64>,
+    threshold_percent: f64,
+) -> Vec<BenchComparison> {
+    estimates
+        .iter()
+        .map(|estimate| {
+            let baseline_ns = baseline.get(&estimate.id).copied();
+            let change_percent = baseline_ns
+                .filter(|baseline_ns| *baseline_ns > 0.0)
+                .map(|baseline_ns| (estimate.mean_ns - baseline_ns) * 100.0 / baseline_ns);
+            BenchComparison {
+                id: estimate.id.clone(),
+                mean_ns: estimate.mean_ns,
+                baseline_ns,
+                change_percent,
+                regressed: change_percent.map_or(false, |change| change > threshold_percent),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bench_executables() {
+        let output = r#"{"reason":"compiler-artifact","target":{"kind":["lib"]},"executable":null}
+{"reason":"compiler-artifact","target":{"kind":["bench"]},"executable":"/game/target/aarch64-linux-android/release/deps/physics-1a2b"}
+{"reason":"build-finished","success":true}"#;
+        assert_eq!(
+            parse_bench_executables(output),
+            [PathBuf::from(
+                "/game/target/aarch64-linux-android/release/deps/physics-1a2b"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_compare_bench_results() {
+        let mean = parse_criterion_mean(
+            r#"{"mean":{"confidence_interval":{},"point_estimate":1100.0,"standard_error":2.0}}"#,
+        )
+        .unwrap();
+        let estimates = [
+            BenchEstimate {
+                id: "physics/step".to_owned(),
+                mean_ns: mean,
+            },
+            BenchEstimate {
+                id: "physics/new".to_owned(),
+                mean_ns: 50.0,
+            },
+        ];
+        let mut baseline = BTreeMap::new();
+        baseline.insert("physics/step".to_owned(), 1000.0);
+        let comparisons = compare_bench_results(&estimates, &baseline, 5.0);
+        assert!(comparisons[0].regressed);
+        assert_eq!(comparisons[0].change_percent.map(|c| c.round()), Some(10.0));
+        assert!(!comparisons[1].regressed);
+        assert!(!compare_bench_results(&estimates, &baseline, 15.0)[0].regressed);
+    }
+}
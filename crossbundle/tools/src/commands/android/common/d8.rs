@@ -0,0 +1,174 @@
+use crate::{error::*, types::AndroidSdk};
+use std::path::{Path, PathBuf};
+
+/// ## D8
+/// `d8` compiles Java bytecode (`.class` files and `.jar` archives) into DEX bytecode
+/// that runs on Android devices. Java 8 language features are desugared by default.
+///
+/// The output is `classes.dex` (and `classes2.dex`, ... for multidex) in the output
+/// directory, or a ZIP archive if the output path ends with `.zip` or `.jar`.
+///
+/// See more: https://developer.android.com/studio/command-line/d8
+#[derive(Clone, Debug)]
+pub struct D8 {
+    inputs: Vec<PathBuf>,
+    output: PathBuf,
+    release: bool,
+    min_api: Option<u32>,
+    lib: Vec<PathBuf>,
+    classpath: Vec<PathBuf>,
+    no_desugaring: bool,
+    intermediate: bool,
+}
+
+impl D8 {
+    /// Compiles `inputs` into DEX files in `output`.
+    pub fn new(inputs: &[PathBuf], output: &Path) -> Self {
+        Self {
+            inputs: inputs.to_vec(),
+            output: output.to_owned(),
+            release: false,
+            min_api: None,
+            lib: Vec::new(),
+            classpath: Vec::new(),
+            no_desugaring: false,
+            intermediate: false,
+        }
+    }
+
+    /// Compile without debug information.
+    pub fn release(&mut self, release: bool) -> &mut Self {
+        self.release = release;
+        self
+    }
+
+    /// Minimum API level of the application. Features not supported by this level are
+    /// desugared and multidex is used natively since API level 21.
+    pub fn min_api(&mut self, min_api: u32) -> &mut Self {
+        self.min_api = Some(min_api);
+        self
+    }
+
+    /// Path to `android.jar` of the target platform. Required for desugaring.
+    pub fn lib(&mut self, lib: &Path) -> &mut Self {
+        self.lib.push(lib.to_owned());
+        self
+    }
+
+    /// Classpath resources that are referenced by inputs but not compiled into output.
+    pub fn classpath(&mut self, classpath: &Path) -> &mut Self {
+        self.classpath.push(classpath.to_owned());
+        self
+    }
+
+    /// Disable Java 8 language features desugaring.
+    pub fn no_desugaring(&mut self, no_desugaring: bool) -> &mut Self {
+        self.no_desugaring = no_desugaring;
+        self
+    }
+
+    /// Compile intermediate DEX files that are merged later.
+    pub fn intermediate(&mut self, intermediate: bool) -> &mut Self {
+        self.intermediate = intermediate;
+        self
+    }
+
+    /// Runs `d8` from the build tools of given SDK. Returns the output path.
+    pub fn run(&self, sdk: &AndroidSdk) -> Result<PathBuf> {
+        if self.output.extension().is_none() {
+            std::fs::create_dir_all(&self.output)?;
+        }
+        let mut d8 = sdk.build_tool(bat!("d8"), None)?;
+        d8.arg(if self.release { "--release" } else { "--debug" });
+        if let Some(min_api) = self.min_api {
+            d8.arg("--min-api").arg(min_api.to_string());
+        }
+        for lib in &self.lib {
+            d8.arg("--lib").arg(lib);
+        }
+        for classpath in &self.classpath {
+            d8.arg("--classpath").arg(classpath);
+        }
+        if self.no_desugaring {
+            d8.arg("--no-desugaring");
+        }
+        if self.intermediate {
+            d8.arg("--intermediate");
+        }
+        d8.arg("--output").arg(&self.output).args(&self.inputs);
+        d8.output_err(true)?;
+        Ok(self.output.clone())
+    }
+}
+
+/// Returns `.class` and `.jar` files from given files and directories.
+pub fn java_bytecode_inputs(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut inputs = Vec::new();
+    for path in paths {
+        if !path.exists() {
+            return Err(Error::PathNotFound(path.clone()));
+        }
+        if path.is_dir() {
+            let entries = std::fs::read_dir(path)?
+                .map(|entry| Ok(entry?.path()))
+                .collect::<Result<Vec<_>>>()?;
+            inputs.extend(java_bytecode_inputs(&entries)?);
+        } else if matches!(
+            path.extension().and_then(|extension| extension.to_str()),
+            Some("class") | Some("jar")
+        ) {
+            inputs.push(path.clone());
+        }
+    }
+    inputs.sort();
+    Ok(inputs)
+}
+
+/// Returns DEX files in the output directory of [`D8`] sorted like `classes.dex`,
+/// `classes2.dex`, ...
+pub fn dex_files(dex_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut dex_files = std::fs::read_dir(dex_dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?;
+    dex_files.retain(|path| {
+        path.extension()
+            .map_or(false, |extension| extension == "dex")
+    });
+    dex_files.sort_by_key(|path| {
+        let name = path.file_stem().unwrap().to_string_lossy().to_string();
+        name.trim_start_matches("classes")
+            .parse::<u32>()
+            .unwrap_or(1)
+    });
+    Ok(dex_files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_java_bytecode_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let classes = dir.path().join("classes").join("com").join("crossbow");
+        std::fs::create_dir_all(&classes).unwrap();
+        std::fs::write(classes.join("Plugin.class"), b"").unwrap();
+        std::fs::write(classes.join("Plugin.java"), b"").unwrap();
+        std::fs::write(dir.path().join("lib.jar"), b"").unwrap();
+        let inputs = java_bytecode_inputs(&[dir.path().to_owned()]).unwrap();
+        assert_eq!(
+            inputs,
+            vec![classes.join("Plugin.class"), dir.path().join("lib.jar")]
+        );
+
+        for name in ["classes10.dex", "classes2.dex", "classes.dex"] {
+            std::fs::write(dir.path().join(name), b"").unwrap();
+        }
+        let names: Vec<_> = dex_files(dir.path())
+            .unwrap()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, ["classes.dex", "classes2.dex", "classes10.dex"]);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{error::*, types::AndroidSdk};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+
+/// Global settings saved before applying the profile and restored afterwards.
+const SNAPSHOT_SETTINGS: &[&str] = &[
+    "window_animation_scale",
+    "transition_animation_scale",
+    "animator_duration_scale",
+    "auto_time",
+    "auto_time_zone",
+    "sysui_demo_allowed",
+    "airplane_mode_on",
+];
+
+/// Device state that makes screenshots and end-to-end tests deterministic.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DeviceProfile {
+    /// Set window, transition and animator duration scales to zero.
+    #[serde(default)]
+    pub disable_animations: bool,
+    /// Time zone like `UTC` or `Europe/Berlin`. Disables automatic time zone.
+    pub timezone: Option<String>,
+    /// Device time in `YYYY-MM-DDTHH:MM:SSZ` format. Disables automatic time.
+    pub time: Option<String>,
+    /// Enable System UI demo mode: fixed clock, full battery and signal, no
+    /// notification icons.
+    #[serde(default)]
+    pub demo_mode: bool,
+    /// Enable or disable airplane mode.
+    pub airplane_mode: Option<bool>,
+}
+
+impl DeviceProfile {
+    /// Returns `true` if the profile doesn't change anything.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Device state captured before applying a [`DeviceProfile`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DeviceStateSnapshot {
+    /// Global settings. `None` if the setting was not set.
+    pub settings: BTreeMap<String, Option<String>>,
+    /// `persist.sys.timezone` property.
+    pub timezone: Option<String>,
+}
+
+impl DeviceStateSnapshot {
+    /// Reads the settings changed by device profiles.
+    pub fn capture(sdk: &AndroidSdk, device: Option<&str>) -> Result<Self> {
+        let mut settings = BTreeMap::new();
+        for name in SNAPSHOT_SETTINGS {
+            let value = adb_shell(sdk, device, &["settings", "get", "global", name])?;
+            let value = Some(value).filter(|value| !value.is_empty() && value != "null");
+            settings.insert(name.to_string(), value);
+        }
+        let timezone = adb_shell(sdk, device, &["getprop", "persist.sys.timezone"])?;
+        Ok(Self {
+            settings,
+            timezone: Some(timezone).filter(|timezone| !timezone.is_empty()),
+        })
+    }
+
+    /// Restores the captured state and exits demo mode.
+    pub fn restore(&self, sdk: &AndroidSdk, device: Option<&str>) -> Result<()> {
+        demo_mode_command(sdk, device, "exit", &[])?;
+        if let Some(timezone) = &self.timezone {
+            adb_shell(sdk, device, &["cmd", "alarm", "set-timezone", timezone])?;
+        }
+        for (name, value) in &self.settings {
+            match (name.as_str(), value) {
+                ("airplane_mode_on", value) => {
+                    let enable = value.as_deref() == Some("1");
+                    set_airplane_mode(sdk, device, enable)?;
+                }
+                (name, Some(value)) => {
+                    adb_shell(sdk, device, &["settings", "put", "global", name, value])?;
+                }
+                (name, None) => {
+                    adb_shell(sdk, device, &["settings", "delete", "global", name])?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Applies the profile on the device or emulator. Capture [`DeviceStateSnapshot`]
+/// before to restore the state afterwards.
+pub fn apply_device_profile(
+    sdk: &AndroidSdk,
+    device: Option<&str>,
+    profile: &DeviceProfile,
+) -> Result<()> {
+    if profile.disable_animations {
+        for name in &SNAPSHOT_SETTINGS[..3] {
+            adb_shell(sdk, device, &["settings", "put", "global", name, "0"])?;
+        }
+    }
+    if let Some(timezone) = &profile.timezone {
+        adb_shell(
+            sdk,
+            device,
+            &["settings", "put", "global", "auto_time_zone", "0"],
+        )?;
+        adb_shell(sdk, device, &["cmd", "alarm", "set-timezone", timezone])?;
+    }
+    let time = match &profile.time {
+        Some(time) => Some(
+            parse_utc_time(time).ok_or_else(|| AndroidError::InvalidDeviceTime(time.clone()))?,
+        ),
+        None => None,
+    };
+    if let Some(time) = time {
+        adb_shell(
+            sdk,
+            device,
+            &["settings", "put", "global", "auto_time", "0"],
+        )?;
+        let millis = (time * 1000).to_string();
+        adb_shell(sdk, device, &["cmd", "alarm", "set-time", &millis])?;
+    }
+    if let Some(airplane_mode) = profile.airplane_mode {
+        set_airplane_mode(sdk, device, airplane_mode)?;
+    }
+    if profile.demo_mode {
+        adb_shell(
+            sdk,
+            device,
+            &["settings", "put", "global", "sysui_demo_allowed", "1"],
+        )?;
+        demo_mode_command(sdk, device, "enter", &[])?;
+        // Demo clock shows the fixed time if it's specified
+        let hhmm = time
+            .map(|time| format!("{:02}{:02}", time % 86400 / 3600, time % 3600 / 60))
+            .unwrap_or_else(|| "1200".to_owned());
+        demo_mode_command(sdk, device, "clock", &[("hhmm", &hhmm)])?;
+        demo_mode_command(
+            sdk,
+            device,
+            "battery",
+            &[("level", "100"), ("plugged", "false")],
+        )?;
+        demo_mode_command(
+            sdk,
+            device,
+            "network",
+            &[("wifi", "show"), ("level", "4"), ("mobile", "show")],
+        )?;
+        demo_mode_command(sdk, device, "notifications", &[("visible", "false")])?;
+    }
+    Ok(())
+}
+
+/// Sends System UI demo mode command.
+/// Runs `adb shell am broadcast -a com.android.systemui.demo -e command <command> ...`
+fn demo_mode_command(
+    sdk: &AndroidSdk,
+    device: Option<&str>,
+    command: &str,
+    extras: &[(&str, &str)],
+) -> Result<()> {
+    let mut args = vec![
+        "am",
+        "broadcast",
+        "-a",
+        "com.android.systemui.demo",
+        "-e",
+        "command",
+        command,
+    ];
+    for (key, value) in extras {
+        args.extend(["-e", *key, *value]);
+    }
+    adb_shell(sdk, device, &args)?;
+    Ok(())
+}
+
+/// Runs `adb shell cmd connectivity airplane-mode enable|disable` command
+fn set_airplane_mode(sdk: &AndroidSdk, device: Option<&str>, enable: bool) -> Result<()> {
+    let mode = if enable { "enable" } else { "disable" };
+    adb_shell(sdk, device, &["cmd", "connectivity", "airplane-mode", mode])?;
+    Ok(())
+}
+
+/// Runs `adb shell <args>` and returns trimmed output.
+fn adb_shell(sdk: &AndroidSdk, device: Option<&str>, args: &[&str]) -> Result<String> {
+    let mut adb = sdk.adb(device)?;
+    adb.arg("shell").args(args);
+    let output = adb.output_err(false)?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// Parses UTC time in `YYYY-MM-DDTHH:MM:SSZ` format into seconds since Unix epoch.
+pub fn parse_utc_time(time: &str) -> Option<u64> {
+    let time = time.strip_suffix('Z')?;
+    let (date, time) = time.split_once('T')?;
+    let date: Vec<i64> = date
+        .split('-')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    let time: Vec<u64> = time
+        .split(':')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    let (year, month, day, hours, minutes, seconds) = match (date.as_slice(), time.as_slice()) {
+        (&[year, month, day], &[hours, minutes, seconds]) => {
+            (year, month, day, hours, minutes, seconds)
+        }
+        _ => return None,
+    };
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hours > 23
+        || minutes > 59
+        || seconds > 59
+    {
+        return None;
+    }
+    // Converts civil date into days since 1970-01-01. See
+    // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = u64::try_from(era * 146097 + doe - 719468).ok()?;
+    Some(days * 86400 + hours * 3600 + minutes * 60 + seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_utc_time() {
+        assert_eq!(parse_utc_time("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_utc_time("2024-02-29T09:41:00Z"), Some(1709199660));
+        assert_eq!(parse_utc_time("2024-02-29 09:41:00"), None);
+        assert_eq!(parse_utc_time("2024-13-01T00:00:00Z"), None);
+    }
+}
@@ -0,0 +1,157 @@
+use super::AdbClient;
+use crate::{error::*, types::AndroidSdk};
+
+/// How the Android device is connected to adb.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AndroidConnection {
+    Usb,
+    Wifi,
+    Emulator,
+}
+
+impl std::fmt::Display for AndroidConnection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Usb => write!(f, "usb"),
+            Self::Wifi => write!(f, "wifi"),
+            Self::Emulator => write!(f, "emulator"),
+        }
+    }
+}
+
+/// Android device or emulator returned by `adb devices -l`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AndroidDevice {
+    /// Serial number used with `adb -s`.
+    pub serial: String,
+    /// Device state, e.g. `device`, `offline` or `unauthorized`.
+    pub state: String,
+    /// Device model.
+    pub model: Option<String>,
+    /// `ro.build.version.sdk` property. Only available for online devices.
+    pub api_level: Option<u32>,
+    /// `ro.product.cpu.abi` property. Only available for online devices.
+    pub abi: Option<String>,
+    pub connection: AndroidConnection,
+}
+
+impl AndroidDevice {
+    /// Returns `true` if the device is online and authorized.
+    pub fn is_online(&self) -> bool {
+        self.state == "device"
+    }
+}
+
+/// Lists connected Android devices and emulators with their API level and ABI.
+/// Runs `adb devices -l` and `adb -s <serial> shell getprop ...` commands
+pub fn android_devices(sdk: &AndroidSdk) -> Result<Vec<AndroidDevice>> {
+    let output = match AdbClient::preferred(sdk)? {
+        Some(client) => client.devices()?,
+        None => {
+            let mut adb = sdk.platform_tool(bin!("adb"))?;
+            adb.arg("devices").arg("-l");
+            String::from_utf8_lossy(&adb.output_err(false)?.stdout).to_string()
+        }
+    };
+    let mut devices = parse_adb_devices(&output);
+    for device in devices.iter_mut().filter(|device| device.is_online()) {
+        device.api_level = device_property(sdk, &device.serial, "ro.build.version.sdk")?
+            .and_then(|api_level| api_level.parse().ok());
+        device.abi = device_property(sdk, &device.serial, "ro.product.cpu.abi")?;
+    }
+    Ok(devices)
+}
+
+/// Returns serial of the device to work with. If `device` is not specified, the only
+/// online device is used.
+pub fn select_android_device(sdk: &AndroidSdk, device: Option<&str>) -> Result<String> {
+    if let Some(device) = device {
+        return Ok(device.to_owned());
+    }
+    let devices: Vec<_> = android_devices(sdk)?
+        .into_iter()
+        .filter(|device| device.is_online())
+        .collect();
+    match devices.as_slice() {
+        [] => Err(AndroidError::NoAndroidDevices.into()),
+        [device] => Ok(device.serial.clone()),
+        devices => {
+            let serials = devices
+                .iter()
+                .map(|device| device.serial.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            Err(AndroidError::MultipleAndroidDevices(serials).into())
+        }
+    }
+}
+
+/// Returns a system property of the device.
+/// Runs `adb -s <serial> shell getprop <property>` command
+pub fn device_property(sdk: &AndroidSdk, serial: &str, property: &str) -> Result<Option<String>> {
+    if let Some(client) = AdbClient::preferred(sdk)? {
+        let output = client.shell(Some(serial), &format!("getprop {}", property))?;
+        let value = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+        return Ok((!value.is_empty()).then_some(value));
+    }
+    let mut adb = sdk.platform_tool(bin!("adb"))?;
+    adb.arg("-s")
+        .arg(serial)
+        .arg("shell")
+        .arg("getprop")
+        .arg(property);
+    let output = adb.output_err(false)?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    Ok((!value.is_empty()).then_some(value))
+}
+
+/// Parses `adb devices -l` output.
+pub fn parse_adb_devices(output: &str) -> Vec<AndroidDevice> {
+    output
+        .lines()
+        .skip_while(|line| !line.starts_with("List of devices attached"))
+        .skip(1)
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let serial = parts.next()?.to_owned();
+            let state = parts.next()?.to_owned();
+            let model = parts
+                .find_map(|part| part.strip_prefix("model:"))
+                .map(ToOwned::to_owned);
+            let connection = if serial.starts_with("emulator-") {
+                AndroidConnection::Emulator
+            } else if serial.contains(':') || serial.contains("._adb-tls-connect.") {
+                AndroidConnection::Wifi
+            } else {
+                AndroidConnection::Usb
+            };
+            Some(AndroidDevice {
+                serial,
+                state,
+                model,
+                api_level: None,
+                abi: None,
+                connection,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_adb_devices() {
+        let output = "* daemon started successfully\nList of devices attached\nemulator-5554          device product:sdk_gphone64_arm64 model:sdk_gphone64_arm64 device:emu64a transport_id:1\n192.168.1.5:5555       device product:oriole model:Pixel_6 device:oriole transport_id:3\nR58M12345              unauthorized usb:1-1 transport_id:2\n\n";
+        let devices = parse_adb_devices(output);
+        assert_eq!(devices.len(), 3);
+        assert_eq!(devices[0].connection, AndroidConnection::Emulator);
+        assert_eq!(devices[0].model.as_deref(), Some("sdk_gphone64_arm64"));
+        assert_eq!(devices[1].serial, "192.168.1.5:5555");
+        assert_eq!(devices[1].connection, AndroidConnection::Wifi);
+        assert_eq!(devices[2].state, "unauthorized");
+        assert_eq!(devices[2].connection, AndroidConnection::Usb);
+        assert!(!devices[2].is_online());
+    }
+}
@@ -0,0 +1,114 @@
+/// Known on-device failure detected from logcat output with the explanation of the
+/// configuration change that fixes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceDiagnosis {
+    /// Stable identifier of the failure, e.g. `native_lib_not_found`.
+    pub code: &'static str,
+    /// What happened on the device.
+    pub problem: &'static str,
+    /// How to fix it.
+    pub fix: &'static str,
+    /// Substrings of the logcat message. All of them must be present.
+    patterns: &'static [&'static str],
+}
+
+const SCOPED_STORAGE_PROBLEM: &str = "Access to the shared storage was denied by scoped storage";
+const SCOPED_STORAGE_FIX: &str = "Store files in app-specific directories (`Context.getExternalFilesDir`) or use MediaStore. To read media of other apps add `READ_MEDIA_IMAGES`/`READ_MEDIA_VIDEO`/`READ_MEDIA_AUDIO` (API 33+) or `READ_EXTERNAL_STORAGE` permission to `[package.metadata] permissions`";
+
+/// Failures that generate the most confusing runtime errors, ordered from specific to
+/// generic.
+pub const DEVICE_DIAGNOSES: &[DeviceDiagnosis] = &[
+    DeviceDiagnosis {
+        code: "native_lib_not_found",
+        problem: "The native library could not be loaded because it's missing from the APK or can't be loaded directly from it",
+        fix: "Build for the device ABI (see `crossbundle devices`). If `android:extractNativeLibs=\"false\"` is set, libraries must be stored uncompressed and page-aligned: build with `native-apk` strategy or set `extract_native_libs = true` in `[package.metadata.android.manifest.application]`",
+        patterns: &["dlopen failed", "not found"],
+    },
+    DeviceDiagnosis {
+        code: "native_lib_not_aligned",
+        problem: "The native library is not page-aligned inside the APK",
+        fix: "Rebuild the APK with `crossbundle build android -s=native-apk`, add `--page-align-16kb` for devices with 16KB memory pages",
+        patterns: &["dlopen failed", "alignment"],
+    },
+    DeviceDiagnosis {
+        code: "wx_text_relocations",
+        problem: "The native library has text relocations, which violate W^X policy since Android 6.0",
+        fix: "Build all C/C++ dependencies with `-fPIC` and avoid prebuilt libraries with text relocations",
+        patterns: &["text relocations"],
+    },
+    DeviceDiagnosis {
+        code: "wx_execmem_denied",
+        problem: "SELinux denied executable memory (W^X violation), e.g. JIT or self-modifying code",
+        fix: "Don't map memory as both writable and executable. Remove JIT engines or use interpreters on Android",
+        patterns: &["avc: denied", "execmem"],
+    },
+    DeviceDiagnosis {
+        code: "wx_execmod_denied",
+        problem: "SELinux denied modification of executable memory (W^X violation)",
+        fix: "Build native libraries with `-fPIC` and target API level 23 or higher",
+        patterns: &["avc: denied", "execmod"],
+    },
+    DeviceDiagnosis {
+        code: "scoped_storage_denied",
+        problem: SCOPED_STORAGE_PROBLEM,
+        fix: SCOPED_STORAGE_FIX,
+        patterns: &["/storage/emulated", "EACCES"],
+    },
+    DeviceDiagnosis {
+        code: "scoped_storage_denied",
+        problem: SCOPED_STORAGE_PROBLEM,
+        fix: SCOPED_STORAGE_FIX,
+        patterns: &["/sdcard", "EACCES"],
+    },
+    DeviceDiagnosis {
+        code: "permission_denied",
+        problem: "The application called an API that requires a permission it doesn't have",
+        fix: "Add the permission from the message to `[package.metadata] permissions` and request dangerous permissions at runtime",
+        patterns: &["SecurityException", "Permission Denial"],
+    },
+    DeviceDiagnosis {
+        code: "selinux_denied",
+        problem: "SELinux denied an operation of the application",
+        fix: "Applications can't access system files, devices and sockets outside of their sandbox. Check the denied operation in `{ ... }` and the target in `tcontext` and use public Android APIs instead",
+        patterns: &["avc: denied", "untrusted_app"],
+    },
+];
+
+/// Returns diagnosis for the logcat message if it matches a known failure.
+pub fn diagnose_logcat_message(message: &str) -> Option<&'static DeviceDiagnosis> {
+    DEVICE_DIAGNOSES.iter().find(|diagnosis| {
+        diagnosis
+            .patterns
+            .iter()
+            .all(|pattern| message.contains(pattern))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnose_logcat_message() {
+        let diagnose = |message| diagnose_logcat_message(message).map(|d| d.code);
+        assert_eq!(
+            diagnose(
+                "java.lang.UnsatisfiedLinkError: dlopen failed: library \"libgame.so\" not found"
+            ),
+            Some("native_lib_not_found")
+        );
+        assert_eq!(
+            diagnose("type=1400 audit(0.0:42): avc: denied { execmem } for scontext=u:r:untrusted_app:s0"),
+            Some("wx_execmem_denied")
+        );
+        assert_eq!(
+            diagnose("open failed: EACCES (Permission denied) /storage/emulated/0/Pictures/a.png"),
+            Some("scoped_storage_denied")
+        );
+        assert_eq!(
+            diagnose("avc: denied { read } for name=\"stat\" scontext=u:r:untrusted_app:s0"),
+            Some("selinux_denied")
+        );
+        assert_eq!(diagnose("Hello from Rust"), None);
+    }
+}
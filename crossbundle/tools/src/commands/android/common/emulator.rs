@@ -0,0 +1,474 @@
+use super::{
+    android_devices, device_property, AndroidConnection, EmulatorAccel, EmulatorGpu,
+    EmulatorProfile,
+};
+use crate::{error::*, types::AndroidSdk};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+/// Console port of the first emulator. Every emulator takes two consecutive ports.
+const FIRST_EMULATOR_PORT: u16 = 5554;
+/// Console port of the last emulator adb detects automatically.
+const LAST_EMULATOR_PORT: u16 = 5584;
+
+/// Returns ABI of system images that run without translation on this host.
+pub fn host_emulator_abi() -> &'static str {
+    if cfg!(target_arch = "aarch64") {
+        "arm64-v8a"
+    } else {
+        "x86_64"
+    }
+}
+
+/// Returns `sdkmanager` package name of the system image, e.g.
+/// `system-images;android-31;google_apis;x86_64`.
+pub fn system_image_package(api_level: u32, tag: &str, abi: &str) -> String {
+    format!("system-images;android-{};{};{}", api_level, tag, abi)
+}
+
+/// Returns `true` if the system image is installed in the SDK.
+pub fn is_system_image_installed(sdk_root: &Path, api_level: u32, tag: &str, abi: &str) -> bool {
+    sdk_root
+        .join("system-images")
+        .join(format!("android-{}", api_level))
+        .join(tag)
+        .join(abi)
+        .exists()
+}
+
+/// Finds `avdmanager` in command line tools of the SDK.
+pub fn find_avdmanager(sdk_root: &Path) -> Result<PathBuf> {
+    let cmdline_tools_path = sdk_root.join("cmdline-tools");
+    [
+        cmdline_tools_path.join("latest").join("bin"),
+        cmdline_tools_path.join("bin"),
+    ]
+    .into_iter()
+    .map(|path| path.join(bat!("avdmanager")))
+    .find(|path| path.exists())
+    .ok_or_else(|| AndroidError::AvdManagerNotFound(cmdline_tools_path).into())
+}
+
+/// Creates and deletes Android Virtual Devices with `avdmanager`.
+pub struct AvdManager {
+    avdmanager_path: PathBuf,
+}
+
+impl AvdManager {
+    pub fn new(sdk_root: &Path) -> Result<Self> {
+        Ok(Self {
+            avdmanager_path: find_avdmanager(sdk_root)?,
+        })
+    }
+
+    /// Creates AVD from the installed system image.
+    /// Runs `avdmanager create avd -n <name> -k <package>` command
+    pub fn create(
+        &self,
+        name: &str,
+        system_image_package: &str,
+        device: Option<&str>,
+        force: bool,
+    ) -> Result<()> {
+        let mut avdmanager = Command::new(&self.avdmanager_path);
+        avdmanager
+            .arg("create")
+            .arg("avd")
+            .arg("--name")
+            .arg(name)
+            .arg("--package")
+            .arg(system_image_package);
+        if let Some(device) = device {
+            avdmanager.arg("--device").arg(device);
+        }
+        if force {
+            avdmanager.arg("--force");
+        }
+        avdmanager
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        let mut child = avdmanager.spawn()?;
+        if let Some(stdin) = child.stdin.as_mut() {
+            // Declines the custom hardware profile prompt
+            let _ = stdin.write_all(b"no\n");
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(Error::CmdFailed(
+                avdmanager,
+                String::new(),
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Deletes AVD.
+    /// Runs `avdmanager delete avd -n <name>` command
+    pub fn delete(&self, name: &str) -> Result<()> {
+        let mut avdmanager = Command::new(&self.avdmanager_path);
+        avdmanager.arg("delete").arg("avd").arg("--name").arg(name);
+        avdmanager.output_err(false)?;
+        Ok(())
+    }
+}
+
+/// Starts Android Virtual Devices with the `emulator` of the SDK.
+pub struct Emulator {
+    emulator_path: PathBuf,
+    headless: bool,
+    port: Option<u16>,
+    wipe_data: bool,
+    snapshot: Option<String>,
+    no_snapshot_save: bool,
+    no_snapshot_load: bool,
+    gpu: Option<EmulatorGpu>,
+    accel: Option<EmulatorAccel>,
+    memory: Option<u32>,
+    args: Vec<String>,
+}
+
+impl Emulator {
+    pub fn new(sdk: &AndroidSdk) -> Result<Self> {
+        let emulator_path = sdk.sdk_path().join("emulator").join(bin!("emulator"));
+        if !emulator_path.exists() {
+            return Err(AndroidError::EmulatorNotFound(emulator_path).into());
+        }
+        Ok(Self {
+            emulator_path,
+            headless: false,
+            port: None,
+            wipe_data: false,
+            snapshot: None,
+            no_snapshot_save: false,
+            no_snapshot_load: false,
+            gpu: None,
+            accel: None,
+            memory: None,
+            args: Vec::new(),
+        })
+    }
+
+    /// Runs the emulator without window, audio and boot animation, e.g. on CI.
+    pub fn headless(&mut self, headless: bool) -> &mut Self {
+        self.headless = headless;
+        self
+    }
+
+    /// Console port of the emulator. The emulator gets `emulator-<port>` serial.
+    pub fn port(&mut self, port: u16) -> &mut Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Resets user data of the AVD.
+    pub fn wipe_data(&mut self, wipe_data: bool) -> &mut Self {
+        self.wipe_data = wipe_data;
+        self
+    }
+
+    /// Boots the AVD from the snapshot saved with [`save_snapshot`] instead of the cold
+    /// boot.
+    pub fn snapshot(&mut self, snapshot: &str) -> &mut Self {
+        self.snapshot = Some(snapshot.to_owned());
+        self
+    }
+
+    /// Doesn't save the state into the quick boot snapshot when the emulator exits, so
+    /// every run starts from the same state.
+    pub fn no_snapshot_save(&mut self, no_snapshot_save: bool) -> &mut Self {
+        self.no_snapshot_save = no_snapshot_save;
+        self
+    }
+
+    /// Ignores the quick boot snapshot and boots from scratch.
+    pub fn no_snapshot_load(&mut self, no_snapshot_load: bool) -> &mut Self {
+        self.no_snapshot_load = no_snapshot_load;
+        self
+    }
+
+    pub fn gpu(&mut self, gpu: EmulatorGpu) -> &mut Self {
+        self.gpu = Some(gpu);
+        self
+    }
+
+    pub fn accel(&mut self, accel: EmulatorAccel) -> &mut Self {
+        self.accel = Some(accel);
+        self
+    }
+
+    /// RAM of the emulator in megabytes.
+    pub fn memory(&mut self, memory: u32) -> &mut Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    /// Additional argument of the emulator.
+    pub fn arg(&mut self, arg: &str) -> &mut Self {
+        self.args.push(arg.to_owned());
+        self
+    }
+
+    /// Applies the emulator profile. Flags that are already enabled stay enabled.
+    pub fn profile(&mut self, profile: &EmulatorProfile) -> &mut Self {
+        self.headless |= profile.headless;
+        self.no_snapshot_load |= profile.cold_boot;
+        self.no_snapshot_save |= profile.no_snapshot_save;
+        if profile.gpu.is_some() {
+            self.gpu = profile.gpu;
+        }
+        if profile.accel.is_some() {
+            self.accel = profile.accel;
+        }
+        if profile.memory.is_some() {
+            self.memory = profile.memory;
+        }
+        self.args.extend(profile.args.iter().cloned());
+        self
+    }
+
+    /// Lists created AVDs.
+    /// Runs `emulator -list-avds` command
+    pub fn list_avds(&self) -> Result<Vec<String>> {
+        let mut emulator = Command::new(&self.emulator_path);
+        emulator.arg("-list-avds");
+        let output = emulator.output_err(false)?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with("INFO"))
+            .map(ToOwned::to_owned)
+            .collect())
+    }
+
+    /// Starts the AVD in background on the free console port and returns the emulator
+    /// process with its serial. The device is not booted yet, see [`wait_for_boot`].
+    pub fn start(&self, sdk: &AndroidSdk, avd: &str) -> Result<(Child, String)> {
+        let port = match self.port {
+            Some(port) => port,
+            None => free_emulator_port(sdk)?,
+        };
+        let mut emulator = Command::new(&self.emulator_path);
+        emulator
+            .arg("-avd")
+            .arg(avd)
+            .arg("-port")
+            .arg(port.to_string());
+        if self.headless {
+            emulator
+                .arg("-no-window")
+                .arg("-no-audio")
+                .arg("-no-boot-anim");
+        }
+        if self.wipe_data {
+            emulator.arg("-wipe-data");
+        }
+        if let Some(snapshot) = &self.snapshot {
+            emulator.arg("-snapshot").arg(snapshot);
+        }
+        if self.no_snapshot_save {
+            emulator.arg("-no-snapshot-save");
+        }
+        // The named snapshot is loaded even if the profile asks for the cold boot
+        if self.no_snapshot_load && self.snapshot.is_none() {
+            emulator.arg("-no-snapshot-load");
+        }
+        if let Some(gpu) = self.gpu {
+            emulator.arg("-gpu").arg(gpu.as_str());
+        }
+        if let Some(accel) = self.accel {
+            emulator.arg("-accel").arg(accel.as_str());
+        }
+        if let Some(memory) = self.memory {
+            emulator.arg("-memory").arg(memory.to_string());
+        }
+        emulator.args(&self.args);
+        emulator
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        let child = emulator.spawn()?;
+        Ok((child, format!("emulator-{}", port)))
+    }
+}
+
+/// Returns the first even console port in 5554..=5584 that isn't used by a running
+/// emulator.
+fn free_emulator_port(sdk: &AndroidSdk) -> Result<u16> {
+    let used: Vec<String> = android_devices(sdk)?
+        .into_iter()
+        .map(|device| device.serial)
+        .collect();
+    first_free_emulator_port(&used).ok_or_else(|| AndroidError::NoFreeEmulatorPort.into())
+}
+
+/// Returns the first console port that isn't taken by the serials of connected devices.
+fn first_free_emulator_port(serials: &[String]) -> Option<u16> {
+    (FIRST_EMULATOR_PORT..=LAST_EMULATOR_PORT)
+        .step_by(2)
+        .find(|port| !serials.contains(&format!("emulator-{}", port)))
+}
+
+/// Waits until the emulator finished booting: `sys.boot_completed` property is `1`.
+/// Pass the emulator process started with [`Emulator::start`] to fail as soon as it
+/// exits, e.g. on a broken AVD, instead of waiting for the timeout.
+pub fn wait_for_boot(
+    sdk: &AndroidSdk,
+    serial: &str,
+    mut process: Option<&mut Child>,
+    timeout: Duration,
+) -> Result<()> {
+    let started = Instant::now();
+    while started.elapsed() < timeout {
+        if let Some(process) = process.as_mut() {
+            if let Some(status) = process.try_wait()? {
+                return Err(
+                    AndroidError::EmulatorExited(serial.to_owned(), status.to_string()).into(),
+                );
+            }
+        }
+        // adb fails while the device is offline, so errors are expected
+        if let Ok(Some(value)) = device_property(sdk, serial, "sys.boot_completed") {
+            if value == "1" {
+                return Ok(());
+            }
+        }
+        std::thread::sleep(Duration::from_secs(2));
+    }
+    Err(AndroidError::EmulatorBootTimeout(serial.to_owned()).into())
+}
+
+/// Returns serial of the running emulator of the AVD.
+/// Runs `adb -s <serial> emu avd name` for every running emulator
+pub fn running_emulator(sdk: &AndroidSdk, avd: &str) -> Result<Option<String>> {
+    for device in android_devices(sdk)? {
+        if device.connection != AndroidConnection::Emulator {
+            continue;
+        }
+        let mut adb = sdk.adb(Some(&device.serial))?;
+        adb.arg("emu").arg("avd").arg("name");
+        let output = match adb.output_err(false) {
+            Ok(output) => output,
+            Err(_) => continue,
+        };
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if stdout.lines().next().map(str::trim) == Some(avd) {
+            return Ok(Some(device.serial));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns directory of AVDs: `ANDROID_AVD_HOME`, `ANDROID_EMULATOR_HOME/avd` or
+/// `~/.android/avd`.
+pub fn avd_home() -> Result<PathBuf> {
+    if let Ok(avd_home) = std::env::var("ANDROID_AVD_HOME") {
+        return Ok(PathBuf::from(avd_home));
+    }
+    if let Ok(emulator_home) = std::env::var("ANDROID_EMULATOR_HOME") {
+        return Ok(PathBuf::from(emulator_home).join("avd"));
+    }
+    let home_dir = dirs::home_dir().ok_or(Error::HomeDirNotFound)?;
+    Ok(home_dir.join(".android").join("avd"))
+}
+
+/// Returns names of the snapshots saved for the AVD.
+pub fn avd_snapshots(avd: &str) -> Result<Vec<String>> {
+    let snapshots_dir = avd_home()?.join(format!("{}.avd", avd)).join("snapshots");
+    if !snapshots_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut snapshots = Vec::new();
+    for entry in std::fs::read_dir(snapshots_dir)? {
+        let entry = entry?;
+        if entry.path().is_dir() {
+            snapshots.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+    snapshots.sort();
+    Ok(snapshots)
+}
+
+/// Saves the state of the running emulator into the named snapshot.
+/// Runs `adb -s <serial> emu avd snapshot save <name>` command
+pub fn save_snapshot(sdk: &AndroidSdk, serial: &str, snapshot: &str) -> Result<()> {
+    emulator_console(sdk, serial, &["avd", "snapshot", "save", snapshot])
+}
+
+/// Restores the state of the running emulator from the named snapshot.
+/// Runs `adb -s <serial> emu avd snapshot load <name>` command
+pub fn load_snapshot(sdk: &AndroidSdk, serial: &str, snapshot: &str) -> Result<()> {
+    emulator_console(sdk, serial, &["avd", "snapshot", "load", snapshot])
+}
+
+/// Deletes the named snapshot of the running emulator.
+/// Runs `adb -s <serial> emu avd snapshot delete <name>` command
+pub fn delete_snapshot(sdk: &AndroidSdk, serial: &str, snapshot: &str) -> Result<()> {
+    emulator_console(sdk, serial, &["avd", "snapshot", "delete", snapshot])
+}
+
+/// Sends the command to the emulator console. adb exits with zero code even if the
+/// command failed, so the output is checked for `KO` status.
+fn emulator_console(sdk: &AndroidSdk, serial: &str, args: &[&str]) -> Result<()> {
+    let mut adb = sdk.adb(Some(serial))?;
+    adb.arg("emu").args(args);
+    let output = adb.output_err(false)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if let Some(error) = stdout.lines().find(|line| line.starts_with("KO")) {
+        return Err(AndroidError::EmulatorConsole(
+            error.trim_start_matches("KO:").trim().to_owned(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Stops the running emulator.
+/// Runs `adb -s <serial> emu kill` command
+pub fn kill_emulator(sdk: &AndroidSdk, serial: &str) -> Result<()> {
+    let mut adb = sdk.adb(Some(serial))?;
+    adb.arg("emu").arg("kill");
+    adb.output_err(false)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_image_package() {
+        assert_eq!(
+            system_image_package(31, "google_apis", "x86_64"),
+            "system-images;android-31;google_apis;x86_64"
+        );
+        assert_eq!(
+            system_image_package(34, "google_apis_playstore", host_emulator_abi()),
+            format!(
+                "system-images;android-34;google_apis_playstore;{}",
+                host_emulator_abi()
+            )
+        );
+    }
+
+    #[test]
+    fn test_first_free_emulator_port() {
+        assert_eq!(first_free_emulator_port(&[]), Some(5554));
+        let serials = vec![
+            "emulator-5554".to_owned(),
+            "emulator-5558".to_owned(),
+            "R58M12345".to_owned(),
+        ];
+        assert_eq!(first_free_emulator_port(&serials), Some(5556));
+        let all = (5554..=5584)
+            .step_by(2)
+            .map(|port| format!("emulator-{}", port))
+            .collect::<Vec<_>>();
+        assert_eq!(first_free_emulator_port(&all), None);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{error::*, types::AndroidSdk};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, process::Command};
+
+/// Name of the built-in profile for CI machines without GPU.
+pub const CI_EMULATOR_PROFILE: &str = "ci";
+/// Name of the built-in profile for workstations with GPU.
+pub const HOST_EMULATOR_PROFILE: &str = "host";
+
+/// GPU emulation mode of the emulator, passed with `-gpu <mode>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmulatorGpu {
+    /// The emulator selects the mode.
+    Auto,
+    /// Renders with the GPU of the host. Requires a display and GPU drivers.
+    Host,
+    /// Renders with SwiftShader on the CPU. Works on machines without GPU.
+    SwiftshaderIndirect,
+    /// Renders with ANGLE on top of DirectX. Windows only.
+    AngleIndirect,
+    /// Renders with the software renderer of the system image.
+    Guest,
+}
+
+impl EmulatorGpu {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Host => "host",
+            Self::SwiftshaderIndirect => "swiftshader_indirect",
+            Self::AngleIndirect => "angle_indirect",
+            Self::Guest => "guest",
+        }
+    }
+}
+
+impl std::str::FromStr for EmulatorGpu {
+    type Err = AndroidError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "host" => Ok(Self::Host),
+            "swiftshader_indirect" => Ok(Self::SwiftshaderIndirect),
+            "angle_indirect" => Ok(Self::AngleIndirect),
+            "guest" => Ok(Self::Guest),
+            _ => Err(AndroidError::InvalidEmulatorGpu(s.to_owned())),
+        }
+    }
+}
+
+impl std::fmt::Display for EmulatorGpu {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Hardware acceleration mode of the emulator, passed with `-accel <mode>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmulatorAccel {
+    /// Uses acceleration if it's available.
+    Auto,
+    /// Requires acceleration, the emulator doesn't start without it.
+    On,
+    /// Emulates the CPU in software. Very slow, only ARM images on ARM hosts and x86
+    /// images on x86 hosts are usable.
+    Off,
+}
+
+impl EmulatorAccel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::On => "on",
+            Self::Off => "off",
+        }
+    }
+}
+
+/// Named emulator configuration from `package.metadata.android.emulator_profiles`:
+///
+/// ```toml
+/// [package.metadata.android.emulator_profiles.ci]
+/// gpu = "swiftshader_indirect"
+/// headless = true
+/// cold_boot = true
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmulatorProfile {
+    /// GPU emulation mode.
+    pub gpu: Option<EmulatorGpu>,
+    /// Hardware acceleration mode.
+    pub accel: Option<EmulatorAccel>,
+    /// Run without window, audio and boot animation.
+    #[serde(default)]
+    pub headless: bool,
+    /// Ignore the quick boot snapshot and boot from scratch.
+    #[serde(default)]
+    pub cold_boot: bool,
+    /// Don't save the quick boot snapshot on exit.
+    #[serde(default)]
+    pub no_snapshot_save: bool,
+    /// RAM of the emulator in megabytes.
+    pub memory: Option<u32>,
+    /// Additional arguments of the emulator.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl EmulatorProfile {
+    /// Returns built-in profiles: `ci` renders on the CPU without window and always
+    /// boots from scratch, `host` renders with the GPU of the host.
+    pub fn builtin() -> BTreeMap<String, Self> {
+        let mut profiles = BTreeMap::new();
+        profiles.insert(
+            CI_EMULATOR_PROFILE.to_owned(),
+            Self {
+                gpu: Some(EmulatorGpu::SwiftshaderIndirect),
+                accel: Some(EmulatorAccel::Auto),
+                headless: true,
+                cold_boot: true,
+                no_snapshot_save: true,
+                ..Default::default()
+            },
+        );
+        profiles.insert(
+            HOST_EMULATOR_PROFILE.to_owned(),
+            Self {
+                gpu: Some(EmulatorGpu::Host),
+                accel: Some(EmulatorAccel::On),
+                ..Default::default()
+            },
+        );
+        profiles
+    }
+
+    /// Returns the profile from the project profiles or the built-in one with the same
+    /// name. Project profiles take priority.
+    pub fn find(profiles: &BTreeMap<String, Self>, name: &str) -> Result<Self> {
+        let mut available = Self::builtin();
+        available.extend(profiles.clone());
+        available.get(name).cloned().ok_or_else(|| {
+            AndroidError::EmulatorProfileNotFound(
+                name.to_owned(),
+                available.keys().cloned().collect::<Vec<_>>().join(", "),
+            )
+            .into()
+        })
+    }
+}
+
+/// Result of the hardware acceleration check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmulatorAcceleration {
+    /// Hypervisor the emulator uses on this host: KVM, HVF or WHPX.
+    pub hypervisor: &'static str,
+    pub available: bool,
+    /// Status reported by `emulator -accel-check`.
+    pub details: String,
+}
+
+impl EmulatorAcceleration {
+    /// Fails with the fix for this host if acceleration is not available.
+    pub fn require(&self) -> Result<()> {
+        if self.available {
+            return Ok(());
+        }
+        Err(AndroidError::EmulatorAccelerationUnavailable(
+            self.hypervisor.to_owned(),
+            format!(
+                "{} {} Set `accel = \"off\"` in the emulator profile to run without \
+                 acceleration, very slowly.",
+                self.details,
+                acceleration_hint()
+            )
+            .trim()
+            .to_owned(),
+        )
+        .into())
+    }
+}
+
+/// Returns hypervisor the emulator uses on this host.
+pub fn host_hypervisor() -> &'static str {
+    if cfg!(target_os = "linux") {
+        "KVM"
+    } else if cfg!(target_os = "macos") {
+        "HVF"
+    } else {
+        "WHPX"
+    }
+}
+
+/// Returns how to enable hardware acceleration on this host.
+fn acceleration_hint() -> &'static str {
+    if cfg!(target_os = "linux") {
+        "Enable virtualization in BIOS, load the `kvm` module and add the user to the \
+         `kvm` group to access /dev/kvm. On CI runners make /dev/kvm accessible with a \
+         udev rule or use a runner with nested virtualization."
+    } else if cfg!(target_os = "macos") {
+        "Hypervisor.framework is required, check that `sysctl kern.hv_support` prints 1. \
+         Virtual machines without nested virtualization don't support it."
+    } else {
+        "Enable Windows Hypervisor Platform in Windows features and virtualization in BIOS."
+    }
+}
+
+/// Checks whether the emulator can use hardware acceleration.
+/// Runs `emulator -accel-check` command
+pub fn check_emulator_acceleration(sdk: &AndroidSdk) -> Result<EmulatorAcceleration> {
+    let emulator_path = sdk.sdk_path().join("emulator").join(bin!("emulator"));
+    if !emulator_path.exists() {
+        return Err(AndroidError::EmulatorNotFound(emulator_path).into());
+    }
+    let output = Command::new(&emulator_path).arg("-accel-check").output()?;
+    Ok(EmulatorAcceleration {
+        hypervisor: host_hypervisor(),
+        available: output.status.success(),
+        details: parse_accel_check(&String::from_utf8_lossy(&output.stdout)),
+    })
+}
+
+/// Returns the status message of `emulator -accel-check` output without the `accel:`
+/// markers and the status code.
+pub fn parse_accel_check(output: &str) -> String {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            !line.is_empty()
+                && *line != "accel:"
+                && *line != "accel"
+                && line.parse::<i32>().is_err()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emulator_profiles() {
+        let profiles: BTreeMap<String, EmulatorProfile> = toml::from_str(
+            "[ci]\ngpu = \"host\"\nheadless = true\n\n[slow]\naccel = \"off\"\nmemory = 2048\n",
+        )
+        .unwrap();
+        let ci = EmulatorProfile::find(&profiles, "ci").unwrap();
+        assert_eq!(ci.gpu, Some(EmulatorGpu::Host));
+        assert!(!ci.cold_boot);
+        let host = EmulatorProfile::find(&profiles, "host").unwrap();
+        assert_eq!(host.accel, Some(EmulatorAccel::On));
+        assert!(EmulatorProfile::find(&profiles, "missing")
+            .unwrap_err()
+            .to_string()
+            .contains("ci, host, slow"));
+        assert_eq!(
+            "swiftshader_indirect".parse::<EmulatorGpu>().unwrap(),
+            EmulatorGpu::SwiftshaderIndirect
+        );
+        assert_eq!(
+            parse_accel_check("accel:\n0\nKVM (version 12) is installed and usable.\naccel\n"),
+            "KVM (version 12) is installed and usable."
+        );
+    }
+}
@@ -1,4 +1,5 @@
-use android_tools::java_tools::{Key, KeyAlgorithm, Keytool};
+use super::{create_keystore, DEBUG_KEY_DNAME, DEFAULT_KEY_VALIDITY};
+use android_tools::java_tools::Key;
 use std::path::PathBuf;
 
 /// Generates keystore with default configuration. You can manage configuration with
@@ -8,50 +9,17 @@ pub fn gen_key(
     sign_key_pass: Option<String>,
     sign_key_alias: Option<String>,
 ) -> crate::error::Result<Key> {
-    let key = if let Some(key_path) = sign_key_path {
-        let aab_key = Key {
+    let aab_key = if let Some(key_path) = sign_key_path {
+        Key {
             key_path,
             key_pass: sign_key_pass.unwrap(),
             key_alias: sign_key_alias.unwrap(),
-        };
-        if aab_key.key_path.exists() {
-            aab_key
-        } else {
-            Keytool::new()
-                .genkeypair(true)
-                .v(true)
-                .keystore(&aab_key.key_path)
-                .alias(&aab_key.key_alias)
-                .keypass(&aab_key.key_pass)
-                .storepass(&aab_key.key_pass)
-                .dname(&["CN=Android Debug,O=Android,C=US".to_owned()])
-                .keyalg(KeyAlgorithm::RSA)
-                .keysize(2048)
-                .validity(10000)
-                .run()?
-                // This will never panic because of AabKey always returned if help flag not set
-                .unwrap()
         }
     } else {
-        let aab_key = Key::new_default()?;
-        if aab_key.key_path.exists() {
-            aab_key
-        } else {
-            Keytool::new()
-                .genkeypair(true)
-                .v(true)
-                .keystore(&aab_key.key_path)
-                .alias(&aab_key.key_alias)
-                .keypass(&aab_key.key_pass)
-                .storepass(&aab_key.key_pass)
-                .dname(&["CN=Android Debug,O=Android,C=US".to_owned()])
-                .keyalg(KeyAlgorithm::RSA)
-                .keysize(2048)
-                .validity(10000)
-                .run()?
-                // This will never panic because of AabKey always returned if help flag not set
-                .unwrap()
-        }
+        Key::new_default()?
     };
-    Ok(key)
+    if aab_key.key_path.exists() {
+        return Ok(aab_key);
+    }
+    create_keystore(&aab_key, DEBUG_KEY_DNAME, DEFAULT_KEY_VALIDITY)
 }
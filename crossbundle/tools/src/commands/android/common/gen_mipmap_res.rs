@@ -1,7 +1,10 @@
 use crate::error::*;
-use image::{DynamicImage, GenericImageView, ImageFormat, Rgba};
+use image::{imageops, DynamicImage, GenericImageView, ImageFormat, Rgba, RgbaImage};
 use serde::{Deserialize, Serialize};
-use std::{fs::File, path::PathBuf};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct ImageGeneration {
@@ -19,13 +22,13 @@ impl ImageGeneration {
     /// Generate mipmap resources from the icon. Width and height of the icon must be
     /// equal.
     pub fn gen_mipmap_res_from_icon(&self) -> Result<()> {
-        let image = image::open(&self.icon_path)?;
-        let (width, height) = image.dimensions();
+        let icon = open_icon(&self.icon_path)?;
+        let (width, height) = icon.dimensions();
         if width != height || height % 2 != 0 {
             return Err(Error::WidthAndHeightDifSizes);
         }
         for (name, size) in get_icon_sizes() {
-            let scaled = image.thumbnail(size, size);
+            let scaled = icon.fit(size, size);
             let img = Self::round_image(&scaled, size);
             // TODO: Add shadow. See this: https://github.com/romannurik/AndroidAssetStudio
             self.write_image(&name, img)?;
@@ -64,6 +67,221 @@ impl ImageGeneration {
     }
 }
 
+/// Background layer of the adaptive icon.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum AdaptiveIconBackground {
+    /// Color in `#RGB`, `#RRGGBB` or `#AARRGGBB` format.
+    Color(String),
+    /// Path to the background image.
+    Image(PathBuf),
+}
+
+impl AdaptiveIconBackground {
+    /// Parses background from metadata value: colors start with `#`, other values are
+    /// treated as image paths.
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.starts_with('#') {
+            true => Ok(Self::Color(check_color(value)?.to_owned())),
+            false => Ok(Self::Image(PathBuf::from(value))),
+        }
+    }
+}
+
+/// Checks that the value is a color in `#RGB`, `#RRGGBB` or `#AARRGGBB` format.
+pub(crate) fn check_color(value: &str) -> Result<&str> {
+    match value.strip_prefix('#') {
+        Some(hex)
+            if [3, 6, 8].contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            Ok(value)
+        }
+        _ => Err(Error::InvalidIconColor(value.to_owned())),
+    }
+}
+
+/// Generates adaptive launcher icon resources for Android 8.0 (API level 26) and higher:
+/// foreground and background layers for all densities and
+/// `mipmap-anydpi-v26/ic_launcher.xml`. Legacy icons are generated with
+/// [`ImageGeneration`].
+///
+/// See more: https://developer.android.com/develop/ui/views/launch/icon_design_adaptive
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AdaptiveIconGeneration {
+    /// The path to the source foreground image. Content should fit into the inner 66%
+    /// of the image, the rest can be masked by launchers.
+    pub foreground: PathBuf,
+    pub background: AdaptiveIconBackground,
+    /// Optional monochrome layer used for themed icons on Android 13+.
+    pub monochrome: Option<PathBuf>,
+    /// The output name of the icon without extension, e.g. `ic_launcher`.
+    pub out_icon_name: String,
+    /// Output path to Android resources for generated resources.
+    pub output_path: PathBuf,
+}
+
+impl AdaptiveIconGeneration {
+    pub fn gen_adaptive_icon_res(&self) -> Result<()> {
+        let foreground = open_icon(&self.foreground)?;
+        let monochrome = self.monochrome.as_deref().map(open_icon).transpose()?;
+        let background_image = match &self.background {
+            AdaptiveIconBackground::Image(path) => Some(open_icon(path)?),
+            AdaptiveIconBackground::Color(_) => None,
+        };
+        for (dpi, size) in get_adaptive_icon_sizes() {
+            let dir = self.output_path.join(format!("mipmap-{}", dpi));
+            std::fs::create_dir_all(&dir)?;
+            let layer = |suffix: &str, image: DynamicImage| -> Result<()> {
+                let mut output =
+                    File::create(dir.join(format!("{}_{}.png", self.out_icon_name, suffix)))?;
+                image.write_to(&mut output, ImageFormat::Png)?;
+                Ok(())
+            };
+            layer("foreground", Self::foreground_layer(&foreground, size))?;
+            if let Some(monochrome) = &monochrome {
+                layer("monochrome", Self::foreground_layer(monochrome, size))?;
+            }
+            if let Some(background) = &background_image {
+                layer("background", background.fill(size, size))?;
+            }
+        }
+        let background_ref = match &self.background {
+            AdaptiveIconBackground::Color(color) => {
+                let values = self.output_path.join("values");
+                std::fs::create_dir_all(&values)?;
+                std::fs::write(
+                    values.join(format!("{}_background.xml", self.out_icon_name)),
+                    format!(
+                        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<resources>\n    <color name=\"{}_background\">{}</color>\n</resources>\n",
+                        self.out_icon_name, color
+                    ),
+                )?;
+                format!("@color/{}_background", self.out_icon_name)
+            }
+            AdaptiveIconBackground::Image(_) => {
+                format!("@mipmap/{}_background", self.out_icon_name)
+            }
+        };
+        let monochrome_ref = match monochrome {
+            Some(_) => format!(
+                "\n    <monochrome android:drawable=\"@mipmap/{}_monochrome\" />",
+                self.out_icon_name
+            ),
+            None => String::new(),
+        };
+        let anydpi = self.output_path.join("mipmap-anydpi-v26");
+        std::fs::create_dir_all(&anydpi)?;
+        std::fs::write(
+            anydpi.join(format!("{}.xml", self.out_icon_name)),
+            format!(
+                "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<adaptive-icon xmlns:android=\"http://schemas.android.com/apk/res/android\">\n    <background android:drawable=\"{}\" />\n    <foreground android:drawable=\"@mipmap/{}_foreground\" />{}\n</adaptive-icon>\n",
+                background_ref, self.out_icon_name, monochrome_ref
+            ),
+        )?;
+        Ok(())
+    }
+
+    /// Scales the image into the inner 72dp of the 108dp layer, the outer 18dp on each
+    /// side are reserved for launcher visual effects.
+    pub(crate) fn foreground_layer(icon: &IconSource, size: u32) -> DynamicImage {
+        let inner = size * 2 / 3;
+        let scaled = icon.fit(inner, inner);
+        let mut layer = RgbaImage::new(size, size);
+        let x = (size - scaled.width()) / 2;
+        let y = (size - scaled.height()) / 2;
+        imageops::overlay(&mut layer, &scaled.to_rgba8(), x.into(), y.into());
+        layer.into()
+    }
+}
+
+/// Source image of the icon. SVG icons are rasterized at the size of each density
+/// instead of scaling a single bitmap.
+pub(crate) enum IconSource {
+    Raster(DynamicImage),
+    Svg(usvg::Tree),
+}
+
+impl IconSource {
+    /// Size of the image, or of the SVG document rounded up to whole pixels.
+    pub(crate) fn dimensions(&self) -> (u32, u32) {
+        match self {
+            Self::Raster(image) => image.dimensions(),
+            Self::Svg(tree) => (
+                tree.size.width().ceil() as u32,
+                tree.size.height().ceil() as u32,
+            ),
+        }
+    }
+
+    /// Scales the icon to fit into the bounds preserving its aspect ratio.
+    pub(crate) fn fit(&self, width: u32, height: u32) -> DynamicImage {
+        match self {
+            Self::Raster(image) => image.resize(width, height, imageops::FilterType::Lanczos3),
+            Self::Svg(tree) => {
+                let scale =
+                    (width as f64 / tree.size.width()).min(height as f64 / tree.size.height());
+                render_svg(
+                    tree,
+                    (tree.size.width() * scale).round() as u32,
+                    (tree.size.height() * scale).round() as u32,
+                )
+            }
+        }
+    }
+
+    /// Scales the icon to exactly the given size.
+    pub(crate) fn fill(&self, width: u32, height: u32) -> DynamicImage {
+        match self {
+            Self::Raster(image) => {
+                image.resize_exact(width, height, imageops::FilterType::Lanczos3)
+            }
+            Self::Svg(tree) => render_svg(tree, width, height),
+        }
+    }
+}
+
+/// Opens PNG, JPEG or SVG icon.
+pub(crate) fn open_icon(path: &Path) -> Result<IconSource> {
+    if path.extension().and_then(|ext| ext.to_str()) != Some("svg") {
+        return Ok(IconSource::Raster(image::open(path)?));
+    }
+    let data = std::fs::read(path)?;
+    let options = usvg::Options {
+        resources_dir: path.parent().map(Path::to_owned),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_data(&data, &options.to_ref())
+        .map_err(|err| Error::InvalidSvgIcon(path.to_owned(), err.to_string()))?;
+    Ok(IconSource::Svg(tree))
+}
+
+/// Renders the SVG document stretched to the given size.
+fn render_svg(tree: &usvg::Tree, width: u32, height: u32) -> DynamicImage {
+    let (width, height) = (width.max(1), height.max(1));
+    let mut pixmap = tiny_skia::Pixmap::new(width, height).unwrap();
+    let transform = tiny_skia::Transform::from_scale(
+        width as f32 / tree.size.width() as f32,
+        height as f32 / tree.size.height() as f32,
+    );
+    resvg::render(tree, usvg::FitTo::Original, transform, pixmap.as_mut());
+    RgbaImage::from_fn(width, height, |x, y| {
+        // Pixmap stores premultiplied colors
+        let pixel = pixmap.pixel(x, y).unwrap().demultiply();
+        Rgba([pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()])
+    })
+    .into()
+}
+
+/// Sizes of 108dp adaptive icon layers in pixels.
+fn get_adaptive_icon_sizes() -> Vec<(String, u32)> {
+    vec![
+        (MipmapDpi::Xxxhdpi.to_string(), 432),
+        (MipmapDpi::Xxhdpi.to_string(), 324),
+        (MipmapDpi::Xhdpi.to_string(), 216),
+        (MipmapDpi::Hdpi.to_string(), 162),
+        (MipmapDpi::Mdpi.to_string(), 108),
+    ]
+}
+
 /// Scale image down according to scale ratio.
 fn get_icon_sizes() -> Vec<(String, u32)> {
     vec![
@@ -129,4 +347,71 @@ mod tests {
             .join("ic_launcher.png")
             .exists())
     }
+
+    #[test]
+    fn test_adaptive_icon_gen() {
+        let tempfile = tempfile::tempdir().unwrap();
+        let res_dir_path = tempfile.path().join("res");
+        let icon_path = current_dir()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .join("assets")
+            .join("images")
+            .join("icon.png");
+        AdaptiveIconGeneration {
+            foreground: icon_path,
+            background: AdaptiveIconBackground::parse("#FFFFFF").unwrap(),
+            monochrome: None,
+            out_icon_name: "ic_launcher".to_owned(),
+            output_path: res_dir_path.clone(),
+        }
+        .gen_adaptive_icon_res()
+        .unwrap();
+        let foreground = image::open(
+            res_dir_path
+                .join("mipmap-xxxhdpi")
+                .join("ic_launcher_foreground.png"),
+        )
+        .unwrap();
+        assert_eq!(foreground.dimensions(), (432, 432));
+        let xml = std::fs::read_to_string(
+            res_dir_path
+                .join("mipmap-anydpi-v26")
+                .join("ic_launcher.xml"),
+        )
+        .unwrap();
+        assert!(xml.contains("@color/ic_launcher_background"));
+        assert!(res_dir_path
+            .join("values")
+            .join("ic_launcher_background.xml")
+            .exists());
+        assert!(AdaptiveIconBackground::parse("#12345").is_err());
+    }
+
+    #[test]
+    fn test_svg_icon() {
+        let tempfile = tempfile::tempdir().unwrap();
+        let icon_path = tempfile.path().join("icon.svg");
+        std::fs::write(
+            &icon_path,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10"><rect width="20" height="10" fill="red"/></svg>"#,
+        )
+        .unwrap();
+        let icon = open_icon(&icon_path).unwrap();
+        assert_eq!(icon.dimensions(), (20, 10));
+        // Rendered at the requested size instead of upscaling 20x10 pixels
+        let fitted = icon.fit(432, 432);
+        assert_eq!(fitted.dimensions(), (432, 216));
+        assert_eq!(fitted.get_pixel(431, 215), Rgba([255, 0, 0, 255]));
+        assert_eq!(icon.fill(48, 48).dimensions(), (48, 48));
+
+        std::fs::write(&icon_path, "<svg").unwrap();
+        assert!(matches!(
+            open_icon(&icon_path),
+            Err(Error::InvalidSvgIcon(..))
+        ));
+    }
 }
@@ -0,0 +1,136 @@
+use super::gen_mipmap_res::{check_color, open_icon, AdaptiveIconGeneration, MipmapDpi};
+use crate::error::*;
+use image::ImageFormat;
+use serde::{Deserialize, Serialize};
+use std::{fs::File, path::PathBuf};
+
+/// Name of the generated splash screen theme. Use it as `@style/Theme.Crossbow.Splash`.
+pub const SPLASH_THEME_NAME: &str = "Theme.Crossbow.Splash";
+
+/// Generates splash screen resources: the theme with `android:windowBackground` drawable
+/// for Android 11 and lower and the theme with SplashScreen attributes for Android 12
+/// (API level 31) and higher. Both themes extend
+/// `@android:style/Theme.DeviceDefault.NoActionBar.Fullscreen`.
+///
+/// See more: https://developer.android.com/develop/ui/views/launch/splash-screen
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SplashScreenGeneration {
+    /// The path to the splash image. Content should fit into the inner circle of 2/3 of
+    /// the image, Android 12 masks the rest.
+    pub image: Option<PathBuf>,
+    /// Background color in `#RGB`, `#RRGGBB` or `#AARRGGBB` format.
+    pub color: String,
+    /// Output path to Android resources for generated resources.
+    pub output_path: PathBuf,
+}
+
+impl SplashScreenGeneration {
+    pub fn gen_splash_screen_res(&self) -> Result<()> {
+        check_color(&self.color)?;
+        let image = self.image.as_deref().map(open_icon).transpose()?;
+        if let Some(image) = &image {
+            for (dpi, size) in get_splash_image_sizes() {
+                let dir = self.output_path.join(format!("drawable-{}", dpi));
+                std::fs::create_dir_all(&dir)?;
+                let mut output = File::create(dir.join("splash_image.png"))?;
+                AdaptiveIconGeneration::foreground_layer(image, size)
+                    .write_to(&mut output, ImageFormat::Png)?;
+            }
+        }
+        let write_res = |dir: &str, file_name: &str, content: String| -> Result<()> {
+            let dir = self.output_path.join(dir);
+            std::fs::create_dir_all(&dir)?;
+            std::fs::write(
+                dir.join(file_name),
+                format!("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n{}", content),
+            )?;
+            Ok(())
+        };
+        write_res(
+            "values",
+            "splash_colors.xml",
+            format!(
+                "<resources>\n    <color name=\"splash_background\">{}</color>\n</resources>\n",
+                self.color
+            ),
+        )?;
+        let bitmap = match image {
+            Some(_) => "\n    <item>\n        <bitmap android:gravity=\"center\" android:src=\"@drawable/splash_image\" />\n    </item>",
+            None => "",
+        };
+        write_res(
+            "drawable",
+            "splash_background.xml",
+            format!(
+                "<layer-list xmlns:android=\"http://schemas.android.com/apk/res/android\">\n    <item android:drawable=\"@color/splash_background\" />{}\n</layer-list>\n",
+                bitmap
+            ),
+        )?;
+        let style = |items: &str| {
+            format!(
+                "<resources>\n    <style name=\"{}\" parent=\"@android:style/Theme.DeviceDefault.NoActionBar.Fullscreen\">\n        <item name=\"android:windowBackground\">@drawable/splash_background</item>{}\n    </style>\n</resources>\n",
+                SPLASH_THEME_NAME, items
+            )
+        };
+        write_res("values", "splash_styles.xml", style(""))?;
+        let icon = match image {
+            Some(_) => "\n        <item name=\"android:windowSplashScreenAnimatedIcon\">@drawable/splash_image</item>",
+            None => "",
+        };
+        write_res(
+            "values-v31",
+            "splash_styles.xml",
+            style(&format!(
+                "\n        <item name=\"android:windowSplashScreenBackground\">@color/splash_background</item>{}",
+                icon
+            )),
+        )?;
+        Ok(())
+    }
+}
+
+/// Sizes of 288dp splash screen icon in pixels.
+fn get_splash_image_sizes() -> Vec<(String, u32)> {
+    vec![
+        (MipmapDpi::Xxxhdpi.to_string(), 1152),
+        (MipmapDpi::Xxhdpi.to_string(), 864),
+        (MipmapDpi::Xhdpi.to_string(), 576),
+        (MipmapDpi::Hdpi.to_string(), 432),
+        (MipmapDpi::Mdpi.to_string(), 288),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_splash_screen_gen() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let image_path = tempdir.path().join("splash.png");
+        image::RgbaImage::new(64, 64).save(&image_path).unwrap();
+        let res = tempdir.path().join("res");
+        SplashScreenGeneration {
+            image: Some(image_path),
+            color: "#1E1E1E".to_owned(),
+            output_path: res.clone(),
+        }
+        .gen_splash_screen_res()
+        .unwrap();
+        let splash_image = image::open(res.join("drawable-xxxhdpi/splash_image.png")).unwrap();
+        assert_eq!(
+            image::GenericImageView::dimensions(&splash_image),
+            (1152, 1152)
+        );
+        let v31 = std::fs::read_to_string(res.join("values-v31/splash_styles.xml")).unwrap();
+        assert!(v31.contains("android:windowSplashScreenAnimatedIcon"));
+        assert!(res.join("drawable/splash_background.xml").exists());
+
+        let invalid = SplashScreenGeneration {
+            image: None,
+            color: "black".to_owned(),
+            output_path: res,
+        };
+        assert!(invalid.gen_splash_screen_res().is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use crate::error::*;
+use std::{collections::BTreeMap, path::Path};
+
+/// Locale key of the strings used when no localized value matches the device locale.
+/// Generated into `values/strings.xml`.
+pub const DEFAULT_STRINGS_LOCALE: &str = "default";
+
+/// String resources keyed by locale and then by string name.
+pub type LocalizedStrings = BTreeMap<String, BTreeMap<String, String>>;
+
+/// Generates `values-<locale>/strings.xml` resources for every locale. Strings of the
+/// [`DEFAULT_STRINGS_LOCALE`] are generated into `values/strings.xml`.
+///
+/// See more: https://developer.android.com/guide/topics/resources/localization
+pub fn gen_strings_res(strings: &LocalizedStrings, res_dir: &Path) -> Result<()> {
+    for (locale, strings) in strings {
+        let values_dir = res_dir.join(android_values_dir(locale)?);
+        let strings_path = values_dir.join("strings.xml");
+        if strings_path.exists() {
+            return Err(Error::ResourceAlreadyExists(strings_path));
+        }
+        let mut content = "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<resources>\n".to_owned();
+        for (name, value) in strings {
+            if !is_valid_resource_name(name) {
+                return Err(Error::InvalidStringResourceName(name.to_owned()));
+            }
+            content.push_str(&format!(
+                "    <string name=\"{}\">{}</string>\n",
+                name,
+                escape_string_resource(value)
+            ));
+        }
+        content.push_str("</resources>\n");
+        std::fs::create_dir_all(&values_dir)?;
+        std::fs::write(strings_path, content)?;
+    }
+    Ok(())
+}
+
+/// Converts locale like `fr`, `pt-BR` or `sr-Latn` into the name of the values
+/// directory: `values-fr`, `values-pt-rBR` or `values-b+sr+Latn`.
+pub fn android_values_dir(locale: &str) -> Result<String> {
+    if locale == DEFAULT_STRINGS_LOCALE {
+        return Ok("values".to_owned());
+    }
+    let invalid = || Error::InvalidLocale(locale.to_owned());
+    let subtags: Vec<&str> = locale.split(|c| c == '-' || c == '_').collect();
+    let (language, rest) = subtags.split_first().ok_or_else(invalid)?;
+    if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(invalid());
+    }
+    let is_script = |s: &str| s.len() == 4 && s.chars().all(|c| c.is_ascii_alphabetic());
+    let is_region = |s: &str| {
+        (s.len() == 2 && s.chars().all(|c| c.is_ascii_alphabetic()))
+            || (s.len() == 3 && s.chars().all(|c| c.is_ascii_digit()))
+    };
+    let language = language.to_ascii_lowercase();
+    match rest {
+        [] => Ok(format!("values-{}", language)),
+        [region] if region.len() == 2 && is_region(region) => Ok(format!(
+            "values-{}-r{}",
+            language,
+            region.to_ascii_uppercase()
+        )),
+        // Scripts and numeric regions are supported only in BCP 47 format since API 24
+        [script] if is_script(script) || is_region(script) => {
+            Ok(format!("values-b+{}+{}", language, script))
+        }
+        [script, region] if is_script(script) && is_region(region) => {
+            Ok(format!("values-b+{}+{}+{}", language, script, region))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+fn is_valid_resource_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Escapes XML special characters and characters that have special meaning in Android
+/// string resources.
+fn escape_string_resource(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for (i, c) in value.chars().enumerate() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '\\' => escaped.push_str("\\\\"),
+            '\'' => escaped.push_str("\\'"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '@' | '?' if i == 0 => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gen_strings_res() {
+        assert_eq!(android_values_dir("fr").unwrap(), "values-fr");
+        assert_eq!(android_values_dir("pt_br").unwrap(), "values-pt-rBR");
+        assert_eq!(android_values_dir("sr-Latn").unwrap(), "values-b+sr+Latn");
+        assert_eq!(android_values_dir("es-419").unwrap(), "values-b+es+419");
+        assert!(android_values_dir("french").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut strings = LocalizedStrings::new();
+        strings
+            .entry("default".to_owned())
+            .or_default()
+            .insert("app_name".to_owned(), "Rock & Roll".to_owned());
+        strings
+            .entry("fr".to_owned())
+            .or_default()
+            .insert("app_name".to_owned(), "L'aventure".to_owned());
+        gen_strings_res(&strings, dir.path()).unwrap();
+        let default = std::fs::read_to_string(dir.path().join("values/strings.xml")).unwrap();
+        assert!(default.contains("<string name=\"app_name\">Rock &amp; Roll</string>"));
+        let fr = std::fs::read_to_string(dir.path().join("values-fr/strings.xml")).unwrap();
+        assert!(fr.contains("L\\'aventure"));
+        assert!(gen_strings_res(&strings, dir.path()).is_err());
+    }
+}
@@ -0,0 +1,250 @@
+use crate::error::*;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Java version of the compiled bytecode supported by d8 desugaring.
+const DEFAULT_JAVA_VERSION: &str = "1.8";
+
+/// ## Javac
+/// `javac` compiles Java sources into `.class` files. Sources are compiled against
+/// `android.jar` of the target platform passed with [`Javac::bootclasspath`], so the
+/// JDK classes that don't exist on Android can't be referenced.
+#[derive(Clone, Debug)]
+pub struct Javac {
+    sources: Vec<PathBuf>,
+    output: PathBuf,
+    java_version: String,
+    bootclasspath: Vec<PathBuf>,
+    classpath: Vec<PathBuf>,
+}
+
+impl Javac {
+    /// Compiles `sources` into `.class` files in `output` directory.
+    pub fn new(sources: &[PathBuf], output: &Path) -> Self {
+        Self {
+            sources: sources.to_vec(),
+            output: output.to_owned(),
+            java_version: DEFAULT_JAVA_VERSION.to_owned(),
+            bootclasspath: Vec::new(),
+            classpath: Vec::new(),
+        }
+    }
+
+    /// Java version of sources and compiled bytecode. `1.8` by default.
+    pub fn java_version(&mut self, java_version: &str) -> &mut Self {
+        self.java_version = java_version.to_owned();
+        self
+    }
+
+    /// Replaces JDK classes, e.g. with `android.jar` of the target platform.
+    pub fn bootclasspath(&mut self, bootclasspath: &Path) -> &mut Self {
+        self.bootclasspath.push(bootclasspath.to_owned());
+        self
+    }
+
+    /// Libraries referenced by sources.
+    pub fn classpath(&mut self, classpath: &Path) -> &mut Self {
+        self.classpath.push(classpath.to_owned());
+        self
+    }
+
+    /// Runs `javac` from `PATH` or `JAVA_HOME`. Returns the output directory.
+    pub fn run(&self) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.output)?;
+        let mut javac = javac_init()?;
+        javac
+            .arg("-source")
+            .arg(&self.java_version)
+            .arg("-target")
+            .arg(&self.java_version)
+            .arg("-encoding")
+            .arg("UTF-8");
+        if !self.bootclasspath.is_empty() {
+            javac
+                .arg("-bootclasspath")
+                .arg(join_classpath(&self.bootclasspath)?);
+        }
+        if !self.classpath.is_empty() {
+            javac
+                .arg("-classpath")
+                .arg(join_classpath(&self.classpath)?);
+        }
+        javac.arg("-d").arg(&self.output).args(&self.sources);
+        javac.output_err(true)?;
+        Ok(self.output.clone())
+    }
+}
+
+/// ## Kotlinc
+/// `kotlinc` compiles Kotlin sources into a `.jar` archive. Java sources of the project
+/// can be passed with [`Kotlinc::java_sources`] to resolve references to them, they are
+/// not compiled by `kotlinc` and must be compiled with [`Javac`] afterwards.
+#[derive(Clone, Debug)]
+pub struct Kotlinc {
+    sources: Vec<PathBuf>,
+    output: PathBuf,
+    jvm_target: String,
+    classpath: Vec<PathBuf>,
+    java_sources: Vec<PathBuf>,
+    include_runtime: bool,
+    no_jdk: bool,
+}
+
+impl Kotlinc {
+    /// Compiles `sources` into `output` jar archive.
+    pub fn new(sources: &[PathBuf], output: &Path) -> Self {
+        Self {
+            sources: sources.to_vec(),
+            output: output.to_owned(),
+            jvm_target: DEFAULT_JAVA_VERSION.to_owned(),
+            classpath: Vec::new(),
+            java_sources: Vec::new(),
+            include_runtime: false,
+            no_jdk: false,
+        }
+    }
+
+    /// Java version of the compiled bytecode. `1.8` by default.
+    pub fn jvm_target(&mut self, jvm_target: &str) -> &mut Self {
+        self.jvm_target = jvm_target.to_owned();
+        self
+    }
+
+    /// Libraries referenced by sources.
+    pub fn classpath(&mut self, classpath: &Path) -> &mut Self {
+        self.classpath.push(classpath.to_owned());
+        self
+    }
+
+    /// Java sources referenced by Kotlin sources.
+    pub fn java_sources(&mut self, java_sources: &[PathBuf]) -> &mut Self {
+        self.java_sources.extend_from_slice(java_sources);
+        self
+    }
+
+    /// Include Kotlin standard library into the output jar.
+    pub fn include_runtime(&mut self, include_runtime: bool) -> &mut Self {
+        self.include_runtime = include_runtime;
+        self
+    }
+
+    /// Don't add JDK classes into the classpath. Use it with `android.jar` in
+    /// [`Kotlinc::classpath`].
+    pub fn no_jdk(&mut self, no_jdk: bool) -> &mut Self {
+        self.no_jdk = no_jdk;
+        self
+    }
+
+    /// Runs `kotlinc` from `PATH` or `KOTLIN_HOME`. Returns the output jar.
+    pub fn run(&self) -> Result<PathBuf> {
+        if let Some(parent) = self.output.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut kotlinc = kotlinc_init()?;
+        kotlinc
+            .arg("-jvm-target")
+            .arg(&self.jvm_target)
+            .arg("-no-reflect");
+        if self.include_runtime {
+            kotlinc.arg("-include-runtime");
+        }
+        if self.no_jdk {
+            kotlinc.arg("-no-jdk");
+        }
+        if !self.classpath.is_empty() {
+            kotlinc
+                .arg("-classpath")
+                .arg(join_classpath(&self.classpath)?);
+        }
+        kotlinc
+            .arg("-d")
+            .arg(&self.output)
+            .args(&self.sources)
+            .args(&self.java_sources);
+        kotlinc.output_err(true)?;
+        Ok(self.output.clone())
+    }
+}
+
+/// Finds `javac` executable in `PATH` or `JAVA_HOME` and initializes it.
+pub fn javac_init() -> Result<Command> {
+    jdk_tool_init(bin!("javac")).ok_or_else(|| AndroidError::JavacNotFound.into())
+}
+
+/// Finds `java` executable in `PATH` or `JAVA_HOME` and initializes it.
+pub fn java_init() -> Result<Command> {
+    jdk_tool_init(bin!("java")).ok_or_else(|| AndroidError::JavaNotFound.into())
+}
+
+fn jdk_tool_init(tool: &str) -> Option<Command> {
+    if let Ok(path) = which::which(tool) {
+        return Some(Command::new(path));
+    }
+    let path = PathBuf::from(std::env::var("JAVA_HOME").ok()?)
+        .join("bin")
+        .join(tool);
+    path.exists().then(|| Command::new(path))
+}
+
+/// Finds `kotlinc` executable in `PATH` or `KOTLIN_HOME` and initializes it.
+pub fn kotlinc_init() -> Result<Command> {
+    if let Ok(kotlinc) = which::which(bat!("kotlinc")) {
+        return Ok(Command::new(kotlinc));
+    }
+    let kotlin_home = std::env::var("KOTLIN_HOME").map_err(|_| AndroidError::KotlincNotFound)?;
+    let kotlinc = PathBuf::from(kotlin_home).join("bin").join(bat!("kotlinc"));
+    if !kotlinc.exists() {
+        return Err(AndroidError::KotlincNotFound.into());
+    }
+    Ok(Command::new(kotlinc))
+}
+
+/// Returns files with given extension from given files and directories.
+pub fn source_files(paths: &[PathBuf], extension: &str) -> Result<Vec<PathBuf>> {
+    let mut sources = Vec::new();
+    for path in paths {
+        if !path.exists() {
+            return Err(Error::PathNotFound(path.clone()));
+        }
+        if path.is_dir() {
+            let entries = std::fs::read_dir(path)?
+                .map(|entry| Ok(entry?.path()))
+                .collect::<Result<Vec<_>>>()?;
+            sources.extend(source_files(&entries, extension)?);
+        } else if path.extension().map_or(false, |ext| ext == extension) {
+            sources.push(path.clone());
+        }
+    }
+    sources.sort();
+    Ok(sources)
+}
+
+fn join_classpath(paths: &[PathBuf]) -> Result<std::ffi::OsString> {
+    std::env::join_paths(paths).map_err(|error| Error::OtherError(Box::new(error)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = dir.path().join("com").join("crossbow");
+        std::fs::create_dir_all(&package).unwrap();
+        for name in ["MainActivity.java", "Bridge.kt", "README.md"] {
+            std::fs::write(package.join(name), b"").unwrap();
+        }
+        let paths = [dir.path().to_owned()];
+        assert_eq!(
+            source_files(&paths, "java").unwrap(),
+            vec![package.join("MainActivity.java")]
+        );
+        assert_eq!(
+            source_files(&paths, "kt").unwrap(),
+            vec![package.join("Bridge.kt")]
+        );
+    }
+}
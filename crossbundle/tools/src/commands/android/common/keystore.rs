@@ -0,0 +1,201 @@
+use crate::error::*;
+use android_tools::java_tools::{Key, KeyAlgorithm, Keytool};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Distinguished name used for generated debug keystores.
+pub const DEBUG_KEY_DNAME: &str = "CN=Android Debug,O=Android,C=US";
+
+/// Default validity of generated keys in days.
+pub const DEFAULT_KEY_VALIDITY: u32 = 10000;
+
+/// Entry of the keystore returned by `keytool -list`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeystoreEntry {
+    /// Alias of the entry.
+    pub alias: String,
+    /// Entry type, e.g. `PrivateKeyEntry` or `trustedCertEntry`.
+    pub entry_type: String,
+}
+
+/// Certificate information of the keystore entry returned by `keytool -list -v`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CertificateInfo {
+    /// Alias of the entry.
+    pub alias: String,
+    /// Distinguished name of the certificate owner.
+    pub owner: Option<String>,
+    /// Distinguished name of the certificate issuer.
+    pub issuer: Option<String>,
+    /// Validity period of the certificate.
+    pub valid: Option<String>,
+    /// SHA-1 certificate fingerprint.
+    pub sha1: Option<String>,
+    /// SHA-256 certificate fingerprint.
+    pub sha256: Option<String>,
+    /// Certificate signature algorithm.
+    pub signature_algorithm: Option<String>,
+}
+
+/// Finds `keytool` executable in `PATH` or `JAVA_HOME` and initializes it.
+pub fn keytool_init() -> Result<Command> {
+    if let Ok(keytool) = which::which(bin!("keytool")) {
+        return Ok(Command::new(keytool));
+    }
+    let java_home = std::env::var("JAVA_HOME").map_err(|_| AndroidError::KeytoolNotFound)?;
+    let keytool = PathBuf::from(java_home).join("bin").join(bin!("keytool"));
+    if !keytool.exists() {
+        return Err(AndroidError::KeytoolNotFound.into());
+    }
+    Ok(Command::new(keytool))
+}
+
+/// Generates a new keystore with a single RSA key pair described by given `key`.
+/// Runs `keytool -genkeypair ...` command
+pub fn create_keystore(key: &Key, dname: &str, validity: u32) -> Result<Key> {
+    if let Some(parent) = key.key_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let key = Keytool::new()
+        .genkeypair(true)
+        .v(true)
+        .keystore(&key.key_path)
+        .alias(&key.key_alias)
+        .keypass(&key.key_pass)
+        .storepass(&key.key_pass)
+        .dname(&[dname.to_owned()])
+        .keyalg(KeyAlgorithm::RSA)
+        .keysize(2048)
+        .validity(validity)
+        .run()?
+        // This will never panic because of Key always returned if help flag not set
+        .unwrap();
+    Ok(key)
+}
+
+/// Generates the default debug keystore if it doesn't exist yet.
+pub fn create_debug_keystore() -> Result<Key> {
+    let key = Key::new_default()?;
+    if key.key_path.exists() {
+        return Ok(key);
+    }
+    create_keystore(&key, DEBUG_KEY_DNAME, DEFAULT_KEY_VALIDITY)
+}
+
+/// Lists all entries in the keystore.
+/// Runs `keytool -list ...` command
+pub fn list_keystore(keystore_path: &Path, storepass: &str) -> Result<Vec<KeystoreEntry>> {
+    if !keystore_path.exists() {
+        return Err(Error::PathNotFound(keystore_path.to_owned()));
+    }
+    let mut keytool = keytool_init()?;
+    keytool
+        .arg("-list")
+        .arg("-keystore")
+        .arg(keystore_path)
+        .arg("-storepass")
+        .arg(storepass);
+    let output = keytool.output_err(false)?;
+    Ok(parse_keystore_entries(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Prints certificate information of the keystore entry with given alias.
+/// Runs `keytool -list -v -alias ...` command
+pub fn inspect_keystore(
+    keystore_path: &Path,
+    storepass: &str,
+    alias: &str,
+) -> Result<CertificateInfo> {
+    if !keystore_path.exists() {
+        return Err(Error::PathNotFound(keystore_path.to_owned()));
+    }
+    let mut keytool = keytool_init()?;
+    keytool
+        .arg("-list")
+        .arg("-v")
+        .arg("-alias")
+        .arg(alias)
+        .arg("-keystore")
+        .arg(keystore_path)
+        .arg("-storepass")
+        .arg(storepass);
+    let output = keytool.output_err(false)?;
+    let info = parse_certificate_info(&String::from_utf8_lossy(&output.stdout));
+    if info.alias.is_empty() {
+        return Err(AndroidError::KeystoreAliasNotFound(alias.to_owned()).into());
+    }
+    Ok(info)
+}
+
+/// Parses entries from `keytool -list` output. Each entry is printed as
+/// `<alias>, <creation date>, <entry type>,`.
+pub fn parse_keystore_entries(output: &str) -> Vec<KeystoreEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_end_matches(',');
+            let (alias, rest) = line.split_once(", ")?;
+            let entry_type = rest.rsplit(", ").next()?;
+            if !entry_type.ends_with("Entry") {
+                return None;
+            }
+            Some(KeystoreEntry {
+                alias: alias.to_owned(),
+                entry_type: entry_type.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Parses the first certificate from `keytool -list -v` output.
+pub fn parse_certificate_info(output: &str) -> CertificateInfo {
+    let mut info = CertificateInfo::default();
+    for line in output.lines() {
+        let (key, value) = match line.trim().split_once(": ") {
+            Some((key, value)) => (key.trim(), value.trim().to_owned()),
+            None => continue,
+        };
+        match key {
+            "Alias name" if info.alias.is_empty() => info.alias = value,
+            "Owner" if info.owner.is_none() => info.owner = Some(value),
+            "Issuer" if info.issuer.is_none() => info.issuer = Some(value),
+            "Valid from" if info.valid.is_none() => info.valid = Some(value),
+            "SHA1" if info.sha1.is_none() => info.sha1 = Some(value),
+            "SHA256" if info.sha256.is_none() => info.sha256 = Some(value),
+            "Signature algorithm name" if info.signature_algorithm.is_none() => {
+                info.signature_algorithm = Some(value)
+            }
+            _ => (),
+        }
+    }
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keytool_output() {
+        let list = "Keystore type: PKCS12\nKeystore provider: SUN\n\nYour keystore contains 1 entry\n\nandroiddebugkey, Oct 17, 2022, PrivateKeyEntry, \nCertificate fingerprint (SHA-256): AB:CD\n";
+        assert_eq!(
+            parse_keystore_entries(list),
+            vec![KeystoreEntry {
+                alias: "androiddebugkey".to_owned(),
+                entry_type: "PrivateKeyEntry".to_owned(),
+            }]
+        );
+
+        let verbose = "Alias name: androiddebugkey\nCreation date: Oct 17, 2022\nEntry type: PrivateKeyEntry\nOwner: CN=Android Debug, O=Android, C=US\nIssuer: CN=Android Debug, O=Android, C=US\nValid from: Mon Oct 17 10:00:00 UTC 2022 until: Fri Mar 04 10:00:00 UTC 2050\nCertificate fingerprints:\n\t SHA1: 01:02\n\t SHA256: 03:04\nSignature algorithm name: SHA256withRSA\n";
+        let info = parse_certificate_info(verbose);
+        assert_eq!(info.alias, "androiddebugkey");
+        assert_eq!(info.owner.unwrap(), "CN=Android Debug, O=Android, C=US");
+        assert_eq!(info.sha1.unwrap(), "01:02");
+        assert_eq!(info.sha256.unwrap(), "03:04");
+        assert_eq!(info.signature_algorithm.unwrap(), "SHA256withRSA");
+    }
+}
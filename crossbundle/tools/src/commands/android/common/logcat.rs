@@ -0,0 +1,262 @@
+use super::AdbClient;
+use crate::{
+    error::*,
+    types::{AndroidSdk, Shell},
+};
+use std::io::{BufRead, BufReader};
+use std::process::Stdio;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use termcolor::Color;
+
+/// Priority of the logcat message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogPriority {
+    Verbose,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl LogPriority {
+    /// Single-letter priority used by logcat.
+    pub fn as_char(&self) -> char {
+        match self {
+            Self::Verbose => 'V',
+            Self::Debug => 'D',
+            Self::Info => 'I',
+            Self::Warn => 'W',
+            Self::Error => 'E',
+            Self::Fatal => 'F',
+        }
+    }
+
+    /// Color used to print messages with this priority.
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Verbose => Color::White,
+            Self::Debug => Color::Cyan,
+            Self::Info => Color::Green,
+            Self::Warn => Color::Yellow,
+            Self::Error => Color::Red,
+            Self::Fatal => Color::Magenta,
+        }
+    }
+}
+
+impl std::str::FromStr for LogPriority {
+    type Err = Error;
+
+    fn from_str(priority: &str) -> Result<Self> {
+        match priority.to_ascii_uppercase().as_str() {
+            "V" | "VERBOSE" => Ok(Self::Verbose),
+            "D" | "DEBUG" => Ok(Self::Debug),
+            "I" | "INFO" => Ok(Self::Info),
+            "W" | "WARN" => Ok(Self::Warn),
+            "E" | "ERROR" => Ok(Self::Error),
+            "F" | "FATAL" => Ok(Self::Fatal),
+            _ => Err(Error::InvalidLogPriority(priority.to_owned())),
+        }
+    }
+}
+
+/// Single message of `adb logcat -v threadtime` output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogcatLine {
+    /// Date and time in `MM-DD hh:mm:ss.mmm` format.
+    pub timestamp: String,
+    pub pid: u32,
+    pub tid: u32,
+    pub priority: LogPriority,
+    pub tag: String,
+    pub message: String,
+}
+
+impl LogcatLine {
+    /// Prints the message with colorized priority and tag.
+    pub fn print(&self, shell: &mut Shell) -> Result<()> {
+        shell.status_with_color(
+            format!(
+                "{} {}/{}",
+                self.timestamp,
+                self.priority.as_char(),
+                self.tag
+            ),
+            &self.message,
+            self.priority.color(),
+        )
+    }
+}
+
+/// Parses line of `adb logcat -v threadtime` output, e.g.
+/// `10-17 12:34:56.789  1234  1250 I RustStdoutStderr: message`.
+/// Returns `None` for lines in other formats like `--------- beginning of main`.
+pub fn parse_logcat_line(line: &str) -> Option<LogcatLine> {
+    let mut parts = line.split_whitespace();
+    let date = parts.next()?;
+    let time = parts.next()?;
+    let pid = parts.next()?.parse().ok()?;
+    let tid = parts.next()?.parse().ok()?;
+    let priority = parts.next()?.parse::<LogPriority>().ok()?;
+    // Tag can contain spaces and is separated from the message with `: `
+    let (_, rest) = line.split_once(&format!(" {} ", priority.as_char()))?;
+    let (tag, message) = rest.split_once(':').unwrap_or((rest, ""));
+    Some(LogcatLine {
+        timestamp: format!("{} {}", date, time),
+        pid,
+        tid,
+        priority,
+        tag: tag.trim().to_owned(),
+        message: message.strip_prefix(' ').unwrap_or(message).to_owned(),
+    })
+}
+
+/// Converts `--since` value into the time format accepted by `adb logcat -T`.
+/// Relative durations like `30s`, `5m` or `1h` are converted into the epoch time
+/// calculated with the host clock. Other values are passed to logcat as is.
+pub fn logcat_since(since: &str) -> String {
+    let relative = since
+        .strip_suffix('s')
+        .map(|n| (n, 1))
+        .or_else(|| since.strip_suffix('m').map(|n| (n, 60)))
+        .or_else(|| since.strip_suffix('h').map(|n| (n, 3600)))
+        .and_then(|(n, multiplier)| n.parse::<u64>().ok().map(|n| n * multiplier));
+    match relative {
+        Some(seconds) => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            let since = now.saturating_sub(Duration::from_secs(seconds));
+            format!("{}.{:03}", since.as_secs(), since.subsec_millis())
+        }
+        None => since.to_owned(),
+    }
+}
+
+/// ## Logcat
+/// Streams `adb logcat` output of the device filtered by process, tags and priority.
+///
+/// See more: https://developer.android.com/studio/command-line/logcat
+#[derive(Clone, Debug, Default)]
+pub struct Logcat {
+    device: Option<String>,
+    pid: Option<u32>,
+    tags: Vec<String>,
+    priority: Option<LogPriority>,
+    since: Option<String>,
+}
+
+impl Logcat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serial of the device to read logs from.
+    pub fn device(&mut self, device: &str) -> &mut Self {
+        self.device = Some(device.to_owned());
+        self
+    }
+
+    /// Show only messages of the process with given PID.
+    pub fn pid(&mut self, pid: u32) -> &mut Self {
+        self.pid = Some(pid);
+        self
+    }
+
+    /// Show only messages with given tag. Can be called several times.
+    pub fn tag(&mut self, tag: &str) -> &mut Self {
+        self.tags.push(tag.to_owned());
+        self
+    }
+
+    /// Show only messages with given or higher priority.
+    pub fn priority(&mut self, priority: LogPriority) -> &mut Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Show messages since given time. See [`logcat_since`] for the supported formats.
+    pub fn since(&mut self, since: &str) -> &mut Self {
+        self.since = Some(logcat_since(since));
+        self
+    }
+
+    /// Returns logcat filter specs, e.g. `["RustStdoutStderr:D", "*:S"]`.
+    pub fn filter_specs(&self) -> Vec<String> {
+        let priority = self.priority.unwrap_or(LogPriority::Verbose).as_char();
+        if self.tags.is_empty() {
+            return vec![format!("*:{}", priority)];
+        }
+        self.tags
+            .iter()
+            .map(|tag| format!("{}:{}", tag, priority))
+            .chain(std::iter::once("*:S".to_owned()))
+            .collect()
+    }
+
+    /// Runs `adb logcat -v threadtime ...` command and calls `callback` for every line
+    /// until logcat exits or is interrupted with Ctrl-C. Lines that can't be parsed are
+    /// passed as `Err` with raw text.
+    pub fn stream<F>(&self, sdk: &AndroidSdk, mut callback: F) -> Result<()>
+    where
+        F: FnMut(std::result::Result<LogcatLine, &str>) -> Result<()>,
+    {
+        let mut args = vec!["-v".to_owned(), "threadtime".to_owned()];
+        if let Some(pid) = self.pid {
+            args.push(format!("--pid={}", pid));
+        }
+        if let Some(since) = &self.since {
+            args.push("-T".to_owned());
+            args.push(since.clone());
+        }
+        args.extend(self.filter_specs());
+        let mut handle_line = |line: &str| match parse_logcat_line(line) {
+            Some(parsed) => callback(Ok(parsed)),
+            None => callback(Err(line)),
+        };
+        if let Some(client) = AdbClient::preferred(sdk)? {
+            // Filter specs like `*:S` are quoted for the device shell
+            let command = format!("logcat '{}'", args.join("' '"));
+            client.shell_lines(self.device.as_deref(), &command, handle_line)?;
+            return Ok(());
+        }
+        let mut adb = sdk.adb(self.device.as_deref())?;
+        adb.arg("logcat").args(&args);
+        let mut child = adb.stdout(Stdio::piped()).spawn()?;
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).lines() {
+                handle_line(&line?)?;
+            }
+        }
+        child.wait()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_logcat_line() {
+        let line = "10-17 12:34:56.789  1234  1250 I RustStdoutStderr: Hello: world";
+        assert_eq!(
+            parse_logcat_line(line),
+            Some(LogcatLine {
+                timestamp: "10-17 12:34:56.789".to_owned(),
+                pid: 1234,
+                tid: 1250,
+                priority: LogPriority::Info,
+                tag: "RustStdoutStderr".to_owned(),
+                message: "Hello: world".to_owned(),
+            })
+        );
+        assert_eq!(parse_logcat_line("--------- beginning of main"), None);
+        let logcat = Logcat::new()
+            .tag("Crossbow")
+            .priority(LogPriority::Warn)
+            .clone();
+        assert_eq!(logcat.filter_specs(), vec!["Crossbow:W", "*:S"]);
+    }
+}
@@ -0,0 +1,43 @@
+use crate::error::{AndroidError, Result};
+use android_manifest::AndroidManifest;
+use std::collections::BTreeMap;
+
+/// Replaces `${name}` placeholders in attribute values of the manifest, e.g.
+/// `android:host="${api_host}"`. Unknown placeholders are left as is.
+pub fn apply_manifest_placeholders(
+    manifest: &AndroidManifest,
+    placeholders: &BTreeMap<String, String>,
+) -> Result<AndroidManifest> {
+    if placeholders.is_empty() {
+        return Ok(manifest.clone());
+    }
+    let mut xml = android_manifest::to_string_pretty(manifest).map_err(AndroidError::from)?;
+    for (name, value) in placeholders {
+        xml = xml.replace(&format!("${{{}}}", name), &xml_escape(value));
+    }
+    Ok(android_manifest::from_str(&xml).map_err(AndroidError::from)?)
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_manifest_placeholders() {
+        let mut manifest = AndroidManifest::default();
+        manifest.package = "com.crossbow.${flavor}".to_owned();
+        manifest.version_name = Some("${version}".to_owned());
+        let placeholders = BTreeMap::from([("flavor".to_owned(), "staging".to_owned())]);
+        let manifest = apply_manifest_placeholders(&manifest, &placeholders).unwrap();
+        assert_eq!(manifest.package, "com.crossbow.staging");
+        assert_eq!(manifest.version_name.as_deref(), Some("${version}"));
+    }
+}
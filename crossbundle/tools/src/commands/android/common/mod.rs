@@ -1,23 +1,107 @@
+mod aapt2_compile;
+mod aapt2_convert;
+mod aapt2_daemon;
+mod aapt2_diff;
+mod aapt2_dump;
+mod aapt2_link;
+mod aapt2_optimize;
+mod adb_client;
+mod adb_server;
+mod apk_analysis;
+mod app_data;
+mod appearance;
+mod assetlinks;
 mod attach_logger;
+mod bench;
+mod d8;
 mod detect_abi;
+mod device_profile;
+mod devices;
+mod diagnostics;
+mod emulator;
+mod emulator_profile;
 mod extract_archive;
 mod gen_key;
 mod gen_mipmap_res;
+mod gen_splash_screen_res;
+mod gen_strings_res;
 mod helper_functions;
+mod javac;
+mod keystore;
+mod logcat;
+mod manifest_placeholders;
+mod native_debug;
+mod port_forward;
+mod pseudo_locale;
+mod r8;
 mod read_manifest;
+mod release_diff;
+mod retrace;
 mod rust_compile;
 mod save_manifest;
+mod screen_record;
+mod sdkmanager;
+mod simpleperf;
+mod stable_ids;
 mod start_app;
+mod strip_symbols;
+mod symbolicate;
+mod thermal;
+mod verify_launch;
+mod waydroid;
+mod wireless;
 mod write_zip;
 
+pub use aapt2_compile::*;
+pub use aapt2_convert::*;
+pub use aapt2_daemon::*;
+pub use aapt2_diff::*;
+pub use aapt2_dump::*;
+pub use aapt2_link::*;
+pub use aapt2_optimize::*;
+pub use adb_client::*;
+pub use adb_server::*;
+pub use apk_analysis::*;
+pub use app_data::*;
+pub use appearance::*;
+pub use assetlinks::*;
 pub use attach_logger::*;
+pub use bench::*;
+pub use d8::*;
 pub use detect_abi::*;
+pub use device_profile::*;
+pub use devices::*;
+pub use diagnostics::*;
+pub use emulator::*;
+pub use emulator_profile::*;
 pub use extract_archive::*;
 pub use gen_key::*;
 pub use gen_mipmap_res::*;
+pub use gen_splash_screen_res::*;
+pub use gen_strings_res::*;
 pub use helper_functions::*;
+pub use javac::*;
+pub use keystore::*;
+pub use logcat::*;
+pub use manifest_placeholders::*;
+pub use native_debug::*;
+pub use port_forward::*;
+pub use pseudo_locale::*;
+pub use r8::*;
 pub use read_manifest::*;
+pub use release_diff::*;
+pub use retrace::*;
 pub use rust_compile::*;
 pub use save_manifest::*;
+pub use screen_record::*;
+pub use sdkmanager::*;
+pub use simpleperf::*;
+pub use stable_ids::*;
 pub use start_app::*;
+pub use strip_symbols::*;
+pub use symbolicate::*;
+pub use thermal::*;
+pub use verify_launch::*;
+pub use waydroid::*;
+pub use wireless::*;
 pub use write_zip::*;
@@ -0,0 +1,251 @@
+use crate::{
+    error::*,
+    types::{AndroidNdk, AndroidSdk, AndroidTarget, IntoRustTriple},
+};
+use std::{
+    io::{Read, Write},
+    net::{Ipv4Addr, TcpStream},
+    path::{Path, PathBuf},
+    process::Child,
+    time::{Duration, Instant},
+};
+
+/// Temporary location of lldb-server on the device before it's copied into the
+/// application data directory.
+const DEVICE_TMP_LLDB_SERVER: &str = "/data/local/tmp/lldb-server";
+
+/// Name of the abstract unix socket lldb-server listens on, prefixed with the package.
+pub const LLDB_SOCKET_NAME: &str = "crossbundle-debug.sock";
+
+/// Returns path of the lldb-server binary for the target from the NDK clang runtime:
+/// `<toolchain>/lib64/clang/<version>/lib/linux/<arch>/lldb-server` or `lib/clang` in
+/// newer NDKs.
+pub fn find_lldb_server(ndk: &AndroidNdk, target: AndroidTarget) -> Result<PathBuf> {
+    let arch = match target {
+        AndroidTarget::Armv7 => "arm",
+        AndroidTarget::Aarch64 => "aarch64",
+        AndroidTarget::I686 => "i386",
+        AndroidTarget::X8664 => "x86_64",
+    };
+    let toolchain_dir = ndk.toolchain_dir()?;
+    for clang_dir in [
+        toolchain_dir.join("lib64").join("clang"),
+        toolchain_dir.join("lib").join("clang"),
+    ] {
+        let mut versions = match std::fs::read_dir(&clang_dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .collect::<Vec<_>>(),
+            Err(_) => continue,
+        };
+        // Newer clang versions go first
+        versions.sort();
+        for version in versions.into_iter().rev() {
+            let lldb_server = version
+                .join("lib")
+                .join("linux")
+                .join(arch)
+                .join("lldb-server");
+            if lldb_server.exists() {
+                return Ok(lldb_server);
+            }
+        }
+    }
+    Err(AndroidError::LldbServerNotFound(target.rust_triple().to_owned()).into())
+}
+
+/// Returns path of lldb from the NDK toolchain or from PATH. The NDK wrapper script is
+/// preferred because it sets up the bundled Python.
+pub fn find_lldb(ndk: &AndroidNdk) -> Result<PathBuf> {
+    let bin_dir = ndk.toolchain_dir()?.join("bin");
+    #[cfg(not(target_os = "windows"))]
+    let wrapper = "lldb.sh";
+    #[cfg(target_os = "windows")]
+    let wrapper = "lldb.cmd";
+    let ndk_lldb = [bin_dir.join(wrapper), bin_dir.join(bin!("lldb"))]
+        .into_iter()
+        .find(|path| path.exists());
+    if let Some(lldb) = ndk_lldb {
+        return Ok(lldb);
+    }
+    std::env::var_os("PATH")
+        .and_then(|paths| {
+            std::env::split_paths(&paths)
+                .map(|path| path.join(bin!("lldb")))
+                .find(|path| path.exists())
+        })
+        .ok_or_else(|| AndroidError::LldbNotFound.into())
+}
+
+/// Copies lldb-server into the data directory of the debuggable application and starts
+/// it in platform mode on the abstract unix socket `<package>/<LLDB_SOCKET_NAME>`. The
+/// server runs until the returned process is killed.
+/// Runs `adb shell run-as <package> ./lldb-server platform --server ...` command
+pub fn start_lldb_server(
+    sdk: &AndroidSdk,
+    package: &str,
+    lldb_server: &Path,
+    device: Option<&str>,
+) -> Result<Child> {
+    let mut adb = sdk.adb(device)?;
+    adb.arg("push").arg(lldb_server).arg(DEVICE_TMP_LLDB_SERVER);
+    adb.output_err(true)?;
+    // `run-as` runs commands in the data directory with the application user, so the
+    // server can attach to the application process
+    let mut adb = sdk.adb(device)?;
+    adb.args(["shell", "run-as", package, "sh", "-c"])
+        .arg(format!(
+            "'cp {} ./lldb-server && chmod 700 ./lldb-server'",
+            DEVICE_TMP_LLDB_SERVER
+        ));
+    adb.output_err(true)?;
+    let mut adb = sdk.adb(device)?;
+    adb.args([
+        "shell",
+        "run-as",
+        package,
+        "./lldb-server",
+        "platform",
+        "--server",
+    ])
+    .arg("--listen")
+    .arg(format!("unix-abstract:///{}/{}", package, LLDB_SOCKET_NAME));
+    Ok(adb.spawn()?)
+}
+
+/// Starts activity of the application that waits for the Java debugger before running
+/// any code.
+/// Runs `adb shell am start -D -n <package>/<activity>` command
+pub fn start_app_for_debugger(
+    sdk: &AndroidSdk,
+    package: &str,
+    activity: &str,
+    device: Option<&str>,
+) -> Result<()> {
+    let mut adb = sdk.adb(device)?;
+    adb.args([
+        "shell",
+        "am",
+        "start",
+        "-D",
+        "-a",
+        "android.intent.action.MAIN",
+    ])
+    .arg("-n")
+    .arg(format!("{}/{}", package, activity));
+    adb.output_err(true)?;
+    Ok(())
+}
+
+/// Waits until a native debugger is attached to the application process.
+/// Runs `adb shell run-as <package> cat /proc/<pid>/status` command
+pub fn wait_for_native_debugger(
+    sdk: &AndroidSdk,
+    package: &str,
+    pid: u32,
+    device: Option<&str>,
+    timeout: Duration,
+) -> Result<()> {
+    let start = Instant::now();
+    loop {
+        let mut adb = sdk.adb(device)?;
+        adb.args(["shell", "run-as", package, "cat"])
+            .arg(format!("/proc/{}/status", pid));
+        let output = adb.output()?;
+        let traced = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find_map(|line| line.strip_prefix("TracerPid:"))
+            .map_or(false, |tracer| tracer.trim() != "0");
+        if traced {
+            return Ok(());
+        }
+        if start.elapsed() >= timeout {
+            return Err(AndroidError::DebuggerAttachTimeout(pid).into());
+        }
+        std::thread::sleep(Duration::from_millis(250));
+    }
+}
+
+/// Lets the application started with [`start_app_for_debugger`] continue. Connects to
+/// the JDWP agent of the process through the forwarded port and resumes the virtual
+/// machine.
+/// Runs `adb forward tcp:<port> jdwp:<pid>` command
+pub fn resume_java_debugger(
+    sdk: &AndroidSdk,
+    pid: u32,
+    port: u16,
+    device: Option<&str>,
+) -> Result<()> {
+    let mut adb = sdk.adb(device)?;
+    adb.arg("forward")
+        .arg(format!("tcp:{}", port))
+        .arg(format!("jdwp:{}", pid));
+    adb.output_err(true)?;
+    let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port))?;
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    stream.write_all(b"JDWP-Handshake")?;
+    let mut handshake = [0; 14];
+    stream.read_exact(&mut handshake)?;
+    // VirtualMachine.Resume command: length, id, flags, command set 1, command 9
+    let mut packet = Vec::with_capacity(11);
+    packet.extend_from_slice(&11u32.to_be_bytes());
+    packet.extend_from_slice(&1u32.to_be_bytes());
+    packet.extend_from_slice(&[0, 1, 9]);
+    stream.write_all(&packet)?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Returns lldb commands that connect to lldb-server started with
+/// [`start_lldb_server`], attach to the process, set breakpoints on the given functions
+/// and continue it. Symbols are loaded from unstripped libraries in `symbol_dirs`.
+pub fn lldb_attach_commands(
+    package: &str,
+    pid: u32,
+    symbol_dirs: &[PathBuf],
+    breakpoints: &[String],
+) -> String {
+    let mut commands = String::from("platform select remote-android\n");
+    commands.push_str(&format!(
+        "platform connect unix-abstract-connect:///{}/{}\n",
+        package, LLDB_SOCKET_NAME
+    ));
+    for dir in symbol_dirs {
+        commands.push_str(&format!(
+            "settings append target.exec-search-paths \"{}\"\n",
+            dir.display()
+        ));
+    }
+    commands.push_str(&format!("process attach --pid {}\n", pid));
+    for breakpoint in breakpoints {
+        commands.push_str(&format!("breakpoint set --name \"{}\"\n", breakpoint));
+    }
+    // The application is stopped on attach and waits for the Java debugger after that
+    commands.push_str("process continue\n");
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lldb_attach_commands() {
+        let commands = lldb_attach_commands(
+            "com.crossbow.game",
+            1234,
+            &[PathBuf::from("target/aarch64-linux-android/debug")],
+            &["android_main".to_owned()],
+        );
+        assert_eq!(
+            commands,
+            "platform select remote-android\n\
+             platform connect unix-abstract-connect:///com.crossbow.game/crossbundle-debug.sock\n\
+             settings append target.exec-search-paths \"target/aarch64-linux-android/debug\"\n\
+             process attach --pid 1234\n\
+             breakpoint set --name \"android_main\"\n\
+             process continue\n"
+        );
+    }
+}
@@ -0,0 +1,18 @@
+use super::AdbClient;
+use crate::{error::*, types::AndroidSdk};
+
+/// Forwards the TCP port of the host to the same port of the device, so host connections
+/// to `127.0.0.1:<port>` reach the application listening on the device.
+/// Runs `adb forward tcp:<port> tcp:<port>` command
+pub fn forward_tcp_port(sdk: &AndroidSdk, port: u16, device: Option<&str>) -> Result<()> {
+    let socket = format!("tcp:{}", port);
+    if let Some(client) = AdbClient::preferred(sdk)? {
+        return client.forward(device, &socket, &socket);
+    }
+    let mut adb = sdk.adb(device)?;
+    adb.arg("forward")
+        .arg(format!("tcp:{}", port))
+        .arg(format!("tcp:{}", port));
+    adb.output_err(true)?;
+    Ok(())
+}
@@ -0,0 +1,259 @@
+use crate::error::*;
+use std::path::Path;
+
+/// Pseudo-locale with accented and expanded strings. Shows hardcoded strings, missing
+/// glyphs and truncation of longer translations.
+pub const PSEUDO_ACCENTED_LOCALE: &str = "en-XA";
+/// Pseudo-locale with right-to-left strings. Shows layouts that don't mirror.
+pub const PSEUDO_BIDI_LOCALE: &str = "ar-XB";
+
+/// Words appended to accented strings, so they are about 40% longer like German or
+/// Finnish translations.
+const EXPANSION_WORDS: &[&str] = &[
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+];
+
+/// Generates `values-en-rXA/strings.xml` and `values-ar-rXB/strings.xml` with
+/// pseudo-localized strings from `values/*.xml` files of the resource directory.
+/// Strings with `translatable="false"` are skipped. Returns the number of strings.
+pub fn gen_pseudo_locale_res(res_dir: &Path) -> Result<usize> {
+    let values_dir = res_dir.join("values");
+    let mut strings = Vec::new();
+    if values_dir.exists() {
+        let mut files: Vec<_> = std::fs::read_dir(&values_dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<_>>()?;
+        files.sort();
+        for file in files {
+            if file.extension().map_or(false, |ext| ext == "xml") {
+                strings.extend(parse_string_resources(&std::fs::read_to_string(&file)?));
+            }
+        }
+    }
+    if strings.is_empty() {
+        return Ok(0);
+    }
+    let locales: [(&str, fn(&str) -> String); 2] = [
+        ("values-en-rXA", pseudo_accented),
+        ("values-ar-rXB", pseudo_bidi),
+    ];
+    for (dir, pseudo_localize) in locales {
+        let strings_path = res_dir.join(dir).join("strings.xml");
+        if strings_path.exists() {
+            return Err(Error::ResourceAlreadyExists(strings_path));
+        }
+        let mut content = "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<resources>\n".to_owned();
+        for (name, value) in &strings {
+            content.push_str(&format!(
+                "    <string name=\"{}\">{}</string>\n",
+                name,
+                pseudo_localize(value)
+            ));
+        }
+        content.push_str("</resources>\n");
+        std::fs::create_dir_all(strings_path.parent().unwrap())?;
+        std::fs::write(strings_path, content)?;
+    }
+    Ok(strings.len())
+}
+
+/// Returns names and escaped values of `<string>` elements of the resources file.
+pub fn parse_string_resources(xml: &str) -> Vec<(String, String)> {
+    let mut strings = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find("<string ") {
+        rest = &rest[start + "<string ".len()..];
+        let (attributes, body) = match rest.split_once('>') {
+            Some(split) => split,
+            None => break,
+        };
+        // Self-closing `<string name="empty"/>`
+        if attributes.ends_with('/') {
+            continue;
+        }
+        let (value, after) = match body.split_once("</string>") {
+            Some(split) => split,
+            None => break,
+        };
+        rest = after;
+        if attributes.contains("translatable=\"false\"") {
+            continue;
+        }
+        let name = attributes
+            .split("name=\"")
+            .nth(1)
+            .and_then(|name| name.split('"').next());
+        if let Some(name) = name {
+            strings.push((name.to_owned(), value.to_owned()));
+        }
+    }
+    strings
+}
+
+/// Part of the escaped string resource value.
+enum Token<'a> {
+    /// Text shown to the user.
+    Text(char),
+    /// Markup, entity, escape sequence or format placeholder that must stay unchanged.
+    Verbatim(&'a str),
+}
+
+/// Splits the value into text characters and parts that must not be changed: tags like
+/// `<b>`, entities like `&amp;`, escapes like `\n` and placeholders like `%1$s` or
+/// `{count}`.
+fn tokenize(value: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut chars = value.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let end = match c {
+            '<' => value[start..].find('>').map(|i| start + i + 1),
+            '&' => value[start..]
+                .find(';')
+                .filter(|i| *i <= 10)
+                .map(|i| start + i + 1),
+            '{' => value[start..].find('}').map(|i| start + i + 1),
+            '\\' => chars.peek().map(|(i, c)| i + c.len_utf8()),
+            '%' => value[start + 1..]
+                .find(|c: char| !(c.is_ascii_digit() || "$.-+#".contains(c)))
+                .map(|i| start + 1 + i)
+                .and_then(|i| value[i..].chars().next().map(|c| i + c.len_utf8())),
+            _ => None,
+        };
+        match end {
+            Some(end) => {
+                tokens.push(Token::Verbatim(&value[start..end]));
+                while chars.peek().map_or(false, |(i, _)| *i < end) {
+                    chars.next();
+                }
+            }
+            None => tokens.push(Token::Text(c)),
+        }
+    }
+    tokens
+}
+
+/// Returns the `en-XA` version of the escaped value: letters are replaced with accented
+/// ones, the text is wrapped in brackets and expanded with extra words. Placeholders
+/// and markup are kept.
+pub fn pseudo_accented(value: &str) -> String {
+    let mut result = String::from("[");
+    let mut letters = 0;
+    for token in tokenize(value) {
+        match token {
+            Token::Text(c) => {
+                if c.is_alphabetic() {
+                    letters += 1;
+                }
+                result.push(accented(c));
+            }
+            Token::Verbatim(verbatim) => result.push_str(verbatim),
+        }
+    }
+    // Short strings grow more in translations than long ones
+    let expansion = match letters {
+        0..=10 => letters,
+        11..=20 => letters * 6 / 10,
+        _ => letters * 4 / 10,
+    };
+    let mut added = 0;
+    for word in EXPANSION_WORDS.iter().cycle() {
+        if added >= expansion {
+            break;
+        }
+        result.push(' ');
+        result.push_str(word);
+        added += word.len() + 1;
+    }
+    result.push(']');
+    result
+}
+
+/// Returns the `ar-XB` version of the escaped value: every word is wrapped in
+/// right-to-left override marks, so the text reads backwards like right-to-left
+/// languages. Placeholders and markup are kept.
+pub fn pseudo_bidi(value: &str) -> String {
+    const RLM: char = '\u{200F}';
+    const RLO: char = '\u{202E}';
+    const PDF: char = '\u{202C}';
+    let mut result = String::new();
+    let mut in_word = false;
+    for token in tokenize(value) {
+        match token {
+            Token::Text(c) if !c.is_whitespace() => {
+                if !in_word {
+                    result.push(RLM);
+                    result.push(RLO);
+                    in_word = true;
+                }
+                result.push(c);
+            }
+            token => {
+                if in_word {
+                    result.push(PDF);
+                    result.push(RLM);
+                    in_word = false;
+                }
+                match token {
+                    Token::Text(c) => result.push(c),
+                    Token::Verbatim(verbatim) => result.push_str(verbatim),
+                }
+            }
+        }
+    }
+    if in_word {
+        result.push(PDF);
+        result.push(RLM);
+    }
+    result
+}
+
+fn accented(c: char) -> char {
+    const FROM: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    const TO: &str = "åƀçđéƒĝĥîĵķļɱñöþǫŕšţûṽŵẋýžÅƁÇĐÉƑĜĤÎĴĶĻṀÑÖÞǪŔŠŢÛṼŴẊÝŽ";
+    FROM.chars()
+        .position(|from| from == c)
+        .and_then(|i| TO.chars().nth(i))
+        .unwrap_or(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pseudo_locale() {
+        assert_eq!(pseudo_accented("Hi"), "[Ĥî one]");
+        assert_eq!(
+            pseudo_accented("Score: %1$d &amp; <b>{name}</b>\\n"),
+            "[Šçöŕé: %1$d &amp; <b>{name}</b>\\n one two]"
+        );
+        assert_eq!(
+            pseudo_bidi("Play %s"),
+            "\u{200F}\u{202E}Play\u{202C}\u{200F} %s"
+        );
+
+        let xml = r#"<resources>
+    <string name="app_name">Game</string>
+    <string name="version" translatable="false">1.0</string>
+    <string name="empty"/>
+    <string name="welcome">Hello, %s!</string>
+</resources>"#;
+        let strings = parse_string_resources(xml);
+        assert_eq!(
+            strings,
+            [
+                ("app_name".to_owned(), "Game".to_owned()),
+                ("welcome".to_owned(), "Hello, %s!".to_owned())
+            ]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("values")).unwrap();
+        std::fs::write(dir.path().join("values/strings.xml"), xml).unwrap();
+        assert_eq!(gen_pseudo_locale_res(dir.path()).unwrap(), 2);
+        let accented =
+            std::fs::read_to_string(dir.path().join("values-en-rXA/strings.xml")).unwrap();
+        assert!(accented.contains("<string name=\"welcome\">[Ĥéļļö, %s! one two]</string>"));
+        assert!(dir.path().join("values-ar-rXB/strings.xml").exists());
+    }
+}
@@ -0,0 +1,161 @@
+use super::java_init;
+use crate::{error::*, types::AndroidSdk};
+use std::path::{Path, PathBuf};
+
+/// Keep rules always passed to R8. Activities, services and other components are
+/// instantiated by the system from the manifest, and native methods are looked up by
+/// name from Rust with JNI, so they must not be removed or renamed. Classes of the
+/// Crossbow library and plugins are called from Rust with JNI as well, plugins are
+/// created with reflection and their methods are found by the annotation.
+pub const DEFAULT_KEEP_RULES: &str = "\
+-keep public class * extends android.app.Activity
+-keep public class * extends android.app.Application
+-keep public class * extends android.app.Service
+-keep public class * extends android.content.BroadcastReceiver
+-keep public class * extends android.content.ContentProvider
+-keepclasseswithmembernames,includedescriptorclasses class * {
+    native <methods>;
+}
+-keep class com.crossbow.** { *; }
+-keep public class * extends com.crossbow.library.plugin.CrossbowPlugin {
+    public <init>(com.crossbow.library.Crossbow);
+    public *;
+}
+-keepclassmembers class * {
+    @com.crossbow.library.plugin.ExposedToCrossbow *;
+}
+-keepattributes SourceFile,LineNumberTable,*Annotation*
+";
+
+/// ## R8
+/// R8 shrinks, optimizes and obfuscates Java bytecode and compiles it into DEX files like
+/// [`D8`](super::D8). Classes and members that are not reachable from the keep rules are
+/// removed, the rest are renamed. Obfuscated names are written into the mapping file that
+/// is required to retrace stack traces.
+///
+/// R8 is run from `lib/d8.jar` of the build tools.
+///
+/// See more: https://developer.android.com/studio/build/shrink-code
+#[derive(Clone, Debug)]
+pub struct R8 {
+    inputs: Vec<PathBuf>,
+    output: PathBuf,
+    min_api: Option<u32>,
+    lib: Vec<PathBuf>,
+    pg_conf: Vec<PathBuf>,
+    pg_map_output: Option<PathBuf>,
+    no_obfuscation: bool,
+}
+
+impl R8 {
+    /// Shrinks `inputs` into DEX files in `output`.
+    pub fn new(inputs: &[PathBuf], output: &Path) -> Self {
+        Self {
+            inputs: inputs.to_vec(),
+            output: output.to_owned(),
+            min_api: None,
+            lib: Vec::new(),
+            pg_conf: Vec::new(),
+            pg_map_output: None,
+            no_obfuscation: false,
+        }
+    }
+
+    /// Minimum API level of the application.
+    pub fn min_api(&mut self, min_api: u32) -> &mut Self {
+        self.min_api = Some(min_api);
+        self
+    }
+
+    /// Path to `android.jar` of the target platform.
+    pub fn lib(&mut self, lib: &Path) -> &mut Self {
+        self.lib.push(lib.to_owned());
+        self
+    }
+
+    /// File with keep rules in ProGuard format.
+    pub fn pg_conf(&mut self, pg_conf: &Path) -> &mut Self {
+        self.pg_conf.push(pg_conf.to_owned());
+        self
+    }
+
+    /// Path of the mapping file with obfuscated names.
+    pub fn pg_map_output(&mut self, pg_map_output: &Path) -> &mut Self {
+        self.pg_map_output = Some(pg_map_output.to_owned());
+        self
+    }
+
+    /// Shrink the code without renaming classes and members.
+    pub fn no_obfuscation(&mut self, no_obfuscation: bool) -> &mut Self {
+        self.no_obfuscation = no_obfuscation;
+        self
+    }
+
+    /// Runs `java -cp <build tools>/lib/d8.jar com.android.tools.r8.R8 ...`. Returns the
+    /// output path.
+    pub fn run(&self, sdk: &AndroidSdk) -> Result<PathBuf> {
+        let r8_jar = sdk
+            .build_deps_path()
+            .join(sdk.build_deps_version())
+            .join("lib")
+            .join("d8.jar");
+        if !r8_jar.exists() {
+            return Err(Error::PathNotFound(r8_jar));
+        }
+        if self.output.extension().is_none() {
+            std::fs::create_dir_all(&self.output)?;
+        }
+        let mut r8 = java_init()?;
+        r8.arg("-cp")
+            .arg(r8_jar)
+            .arg("com.android.tools.r8.R8")
+            .arg("--release");
+        if let Some(min_api) = self.min_api {
+            r8.arg("--min-api").arg(min_api.to_string());
+        }
+        for lib in &self.lib {
+            r8.arg("--lib").arg(lib);
+        }
+        for pg_conf in &self.pg_conf {
+            r8.arg("--pg-conf").arg(pg_conf);
+        }
+        if let Some(pg_map_output) = &self.pg_map_output {
+            r8.arg("--pg-map-output").arg(pg_map_output);
+        }
+        if self.no_obfuscation {
+            r8.arg("--no-minification");
+        }
+        r8.arg("--output").arg(&self.output).args(&self.inputs);
+        r8.output_err(true)?;
+        Ok(self.output.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_keep_rules() {
+        let mut depth = 0;
+        for line in DEFAULT_KEEP_RULES.lines() {
+            if depth == 0 {
+                assert!(line.starts_with('-'), "rule outside of options: {}", line);
+            }
+            depth += line.matches('{').count();
+            depth -= line.matches('}').count();
+        }
+        assert_eq!(depth, 0);
+        for rule in [
+            "-keep class com.crossbow.** { *; }",
+            "-keep public class * extends com.crossbow.library.plugin.CrossbowPlugin {",
+            "    @com.crossbow.library.plugin.ExposedToCrossbow *;",
+        ] {
+            assert!(
+                DEFAULT_KEEP_RULES.lines().any(|line| line == rule),
+                "{}",
+                rule
+            );
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use super::{
+    aapt2_dump_badging, java_init, keytool_init, latest_archived_release, parse_certificate_info,
+    BadgingInfo, ResourceDiff,
+};
+use crate::{
+    commands::{find_bundletool_jar, ArtifactStorage},
+    error::*,
+    types::AndroidSdk,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+/// Stores the published artifact under `releases/<release>`, so the next build can be
+/// compared against it. Returns location of the stored artifact.
+pub fn archive_release(
+    storage: &dyn ArtifactStorage,
+    release: &str,
+    artifact: &Path,
+) -> Result<String> {
+    let file_name = artifact.file_name().unwrap().to_string_lossy();
+    storage.put(&format!("releases/{}/{}", release, file_name), artifact)
+}
+
+/// Returns the artifact with the given extension, `apk` or `aab`, of the latest
+/// archived release. Artifacts from the remote storage are downloaded into
+/// `<target_dir>/crossbundle/releases/<release>`.
+pub fn latest_released_artifact(
+    storage: &dyn ArtifactStorage,
+    target_dir: &Path,
+    extension: &str,
+) -> Result<Option<PathBuf>> {
+    let suffix = format!(".{}", extension);
+    let keys = storage
+        .list("releases")?
+        .into_iter()
+        .filter(|key| key.ends_with(&suffix))
+        .collect::<Vec<_>>();
+    let release = match latest_archived_release(keys.iter().map(String::as_str)) {
+        Some(release) => release,
+        None => return Ok(None),
+    };
+    let prefix = format!("releases/{}/", release);
+    let key = keys.iter().find(|key| key.starts_with(&prefix)).unwrap();
+    Ok(Some(storage.fetch(key, &target_dir.join("crossbundle"))?))
+}
+
+/// Manifest values, signer and sizes of the APK or AAB.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArtifactSummary {
+    pub package: Option<String>,
+    pub version_code: Option<u32>,
+    pub version_name: Option<String>,
+    pub min_sdk_version: Option<u32>,
+    pub target_sdk_version: Option<u32>,
+    pub permissions: BTreeSet<String>,
+    pub features: BTreeSet<String>,
+    /// SHA-256 digest of the signing certificate in lowercase hex without colons.
+    pub signer: Option<String>,
+    /// Size of the artifact in bytes.
+    pub size: u64,
+    /// Compressed sizes of the archive entries by path.
+    pub entries: BTreeMap<String, u64>,
+}
+
+impl ArtifactSummary {
+    /// Reads the APK with `aapt2 dump badging` and `apksigner verify --print-certs`, or
+    /// the AAB with `bundletool dump manifest` and `keytool -printcert -jarfile`.
+    pub fn read(sdk: &AndroidSdk, path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(Error::PathNotFound(path.to_owned()));
+        }
+        let mut summary = if path.extension().map_or(false, |ext| ext == "aab") {
+            let mut summary = Self::read_aab_manifest(path)?;
+            let mut keytool = keytool_init()?;
+            keytool.arg("-printcert").arg("-jarfile").arg(path);
+            let output = keytool.output_err(false)?;
+            summary.signer = parse_certificate_info(&String::from_utf8_lossy(&output.stdout))
+                .sha256
+                .map(|digest| normalize_digest(&digest));
+            summary
+        } else {
+            let mut summary: Self = aapt2_dump_badging(sdk, path)?.into();
+            let mut apksigner = sdk.build_tool(bat!("apksigner"), None)?;
+            apksigner.arg("verify").arg("--print-certs").arg(path);
+            let output = apksigner.output_err(false)?;
+            summary.signer = parse_apksigner_digest(&String::from_utf8_lossy(&output.stdout));
+            summary
+        };
+        summary.size = std::fs::metadata(path)?.len();
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+        for i in 0..archive.len() {
+            let file = archive.by_index(i)?;
+            summary
+                .entries
+                .insert(file.name().to_owned(), file.compressed_size());
+        }
+        Ok(summary)
+    }
+
+    /// Reads the manifest of the base module with `bundletool dump manifest`.
+    fn read_aab_manifest(path: &Path) -> Result<Self> {
+        let bundletool = find_bundletool_jar().ok_or(AndroidError::BundletoolNotFound)?;
+        let mut java = java_init()?;
+        java.arg("-jar")
+            .arg(bundletool)
+            .arg("dump")
+            .arg("manifest")
+            .arg(format!("--bundle={}", path.display()));
+        let output = java.output_err(true)?;
+        let manifest =
+            android_manifest::from_reader(output.stdout.as_slice()).map_err(AndroidError::from)?;
+        let uses_sdk = manifest.uses_sdk.as_ref();
+        Ok(Self {
+            package: Some(manifest.package.clone()),
+            version_code: manifest.version_code,
+            version_name: manifest.version_name.clone(),
+            min_sdk_version: uses_sdk.and_then(|uses_sdk| uses_sdk.min_sdk_version),
+            target_sdk_version: uses_sdk.and_then(|uses_sdk| uses_sdk.target_sdk_version),
+            permissions: manifest
+                .uses_permission
+                .iter()
+                .filter_map(|permission| permission.name.clone())
+                .collect(),
+            features: manifest
+                .uses_feature
+                .iter()
+                .filter_map(|feature| feature.name.clone())
+                .collect(),
+            ..Default::default()
+        })
+    }
+}
+
+impl From<BadgingInfo> for ArtifactSummary {
+    fn from(info: BadgingInfo) -> Self {
+        Self {
+            package: info.package,
+            version_code: info.version_code,
+            version_name: info.version_name,
+            min_sdk_version: info.min_sdk_version,
+            target_sdk_version: info.target_sdk_version,
+            permissions: info.permissions.into_iter().collect(),
+            features: info
+                .features
+                .into_iter()
+                .chain(info.optional_features)
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Parses `Signer #1 certificate SHA-256 digest: <hex>` line of `apksigner` output.
+fn parse_apksigner_digest(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        line.split_once("certificate SHA-256 digest: ")
+            .map(|(_, digest)| normalize_digest(digest))
+    })
+}
+
+fn normalize_digest(digest: &str) -> String {
+    digest.trim().replace(':', "").to_lowercase()
+}
+
+/// Differences between the published artifact and the new build.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReleaseDiff {
+    /// Changed manifest values as `(name, previous, new)`.
+    pub manifest: Vec<(&'static str, String, String)>,
+    pub added_permissions: Vec<String>,
+    pub removed_permissions: Vec<String>,
+    pub added_features: Vec<String>,
+    pub removed_features: Vec<String>,
+    /// Previous and new signer digests if the signing certificate changed.
+    pub signer: Option<(String, String)>,
+    /// Previous and new sizes of the artifact.
+    pub size: (u64, u64),
+    /// Entries with changed sizes as `(path, previous, new)`. Missing entries have size
+    /// 0.
+    pub entries: Vec<(String, u64, u64)>,
+    /// Added, removed and changed resources from [`Aapt2Diff`](super::Aapt2Diff). Only
+    /// filled for APKs.
+    pub resources: Vec<ResourceDiff>,
+}
+
+impl ReleaseDiff {
+    pub fn new(previous: &ArtifactSummary, new: &ArtifactSummary) -> Self {
+        let mut manifest = Vec::new();
+        let mut compare = |name, previous: String, new: String| {
+            if previous != new {
+                manifest.push((name, previous, new));
+            }
+        };
+        let text = |value: &Option<String>| value.clone().unwrap_or_default();
+        let number = |value: Option<u32>| value.map(|v| v.to_string()).unwrap_or_default();
+        compare("package", text(&previous.package), text(&new.package));
+        compare(
+            "versionCode",
+            number(previous.version_code),
+            number(new.version_code),
+        );
+        compare(
+            "versionName",
+            text(&previous.version_name),
+            text(&new.version_name),
+        );
+        compare(
+            "minSdkVersion",
+            number(previous.min_sdk_version),
+            number(new.min_sdk_version),
+        );
+        compare(
+            "targetSdkVersion",
+            number(previous.target_sdk_version),
+            number(new.target_sdk_version),
+        );
+        let difference = |a: &BTreeSet<String>, b: &BTreeSet<String>| {
+            a.difference(b).cloned().collect::<Vec<_>>()
+        };
+        let signer = match (&previous.signer, &new.signer) {
+            (Some(previous), Some(new)) if previous != new => Some((previous.clone(), new.clone())),
+            _ => None,
+        };
+        let paths: BTreeSet<&String> = previous.entries.keys().chain(new.entries.keys()).collect();
+        let entries = paths
+            .into_iter()
+            .filter_map(|path| {
+                let previous = previous.entries.get(path).copied().unwrap_or(0);
+                let new = new.entries.get(path).copied().unwrap_or(0);
+                (previous != new).then(|| (path.clone(), previous, new))
+            })
+            .collect();
+        Self {
+            manifest,
+            added_permissions: difference(&new.permissions, &previous.permissions),
+            removed_permissions: difference(&previous.permissions, &new.permissions),
+            added_features: difference(&new.features, &previous.features),
+            removed_features: difference(&previous.features, &new.features),
+            signer,
+            size: (previous.size, new.size),
+            entries,
+            resources: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_diff() {
+        let previous: ArtifactSummary = BadgingInfo::parse(
+            "package: name='com.crossbow.game' versionCode='41' versionName='1.1.0' \
+             platformBuildVersionName='13'\n\
+             sdkVersion:'21'\n\
+             targetSdkVersion:'33'\n\
+             uses-permission: name='android.permission.INTERNET'\n\
+             uses-feature-not-required: name='android.hardware.camera'\n",
+        )
+        .into();
+        assert_eq!(previous.package.as_deref(), Some("com.crossbow.game"));
+        assert_eq!(previous.version_code, Some(41));
+        assert_eq!(previous.min_sdk_version, Some(21));
+        let mut new = previous.clone();
+        new.version_code = Some(42);
+        new.permissions
+            .insert("android.permission.RECORD_AUDIO".to_owned());
+        new.entries
+            .insert("lib/arm64-v8a/libgame.so".to_owned(), 100);
+        let diff = ReleaseDiff::new(&previous, &new);
+        assert_eq!(
+            diff.manifest,
+            vec![("versionCode", "41".to_owned(), "42".to_owned())]
+        );
+        assert_eq!(diff.added_permissions, ["android.permission.RECORD_AUDIO"]);
+        assert!(diff.removed_permissions.is_empty());
+        assert_eq!(
+            diff.entries,
+            vec![("lib/arm64-v8a/libgame.so".to_owned(), 0, 100)]
+        );
+        assert_eq!(
+            parse_apksigner_digest("Signer #1 certificate SHA-256 digest: AB:cd\n"),
+            Some("abcd".to_owned())
+        );
+    }
+}
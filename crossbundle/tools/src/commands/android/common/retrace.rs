@@ -0,0 +1,412 @@
+use crate::{commands::ArtifactStorage, error::*};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Name of the R8 mapping file written next to the build artifacts.
+pub const R8_MAPPING_FILE_NAME: &str = "mapping.txt";
+/// Name of the resource path shortening map written next to the build artifacts.
+pub const RESOURCE_MAP_FILE_NAME: &str = "resources-map.txt";
+
+/// Returns identifier of the release used to archive mappings, e.g. `1.2.0-42`.
+pub fn release_id(version_name: Option<&str>, version_code: Option<u32>) -> String {
+    format!(
+        "{}-{}",
+        version_name.unwrap_or("0.0.0"),
+        version_code.unwrap_or(1)
+    )
+}
+
+/// Returns directory with archived mappings of the release in the local storage:
+/// `<target_dir>/crossbundle/mappings/<release>`.
+pub fn mappings_dir(target_dir: &Path, release: &str) -> PathBuf {
+    target_dir
+        .join("crossbundle")
+        .join("mappings")
+        .join(release)
+}
+
+/// Returns key of the mapping file in the artifact storage:
+/// `mappings/<release>/<file name>`.
+pub fn mapping_key(release: &str, file_name: &str) -> String {
+    format!("mappings/{}/{}", release, file_name)
+}
+
+/// Stores existing mapping files in the archive of the release, so stack traces from
+/// production can be retraced after the next build. Returns locations of the stored
+/// files.
+pub fn archive_mappings(
+    storage: &dyn ArtifactStorage,
+    release: &str,
+    files: &[PathBuf],
+) -> Result<Vec<String>> {
+    files
+        .iter()
+        .filter(|file| file.exists())
+        .map(|file| {
+            let file_name = file.file_name().unwrap().to_string_lossy();
+            storage.put(&mapping_key(release, &file_name), file)
+        })
+        .collect()
+}
+
+/// Returns the sort key of the release identifier: its version code and the identifier
+/// itself.
+pub fn release_order(release: &str) -> (u32, &str) {
+    let version_code = release
+        .rsplit_once('-')
+        .and_then(|(_, code)| code.parse().ok())
+        .unwrap_or_default();
+    (version_code, release)
+}
+
+/// Returns the release with the highest version code among the archived ones under
+/// `<kind>/<release>/...` keys of the storage.
+pub fn latest_archived_release<'a>(keys: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    keys.into_iter()
+        .filter_map(|key| key.split('/').nth(1))
+        .max_by(|a, b| release_order(a).cmp(&release_order(b)))
+}
+
+/// Returns the directory with archived mappings of the release. Mappings from the
+/// remote storage are downloaded into `<target_dir>/crossbundle/mappings/<release>`.
+/// Releases may have only one of the mappings.
+pub fn fetch_mappings(
+    storage: &dyn ArtifactStorage,
+    target_dir: &Path,
+    release: &str,
+) -> Result<PathBuf> {
+    let cache_dir = target_dir.join("crossbundle");
+    let mut dir = mappings_dir(target_dir, release);
+    for key in storage.list(&format!("mappings/{}", release))? {
+        let path = storage.fetch(&key, &cache_dir)?;
+        dir = path.parent().unwrap().to_owned();
+    }
+    Ok(dir)
+}
+
+/// Returns the directory with mappings of the latest archived release.
+pub fn latest_mappings_dir(
+    storage: &dyn ArtifactStorage,
+    target_dir: &Path,
+) -> Result<Option<PathBuf>> {
+    let keys = storage.list("mappings")?;
+    match latest_archived_release(keys.iter().map(String::as_str)) {
+        Some(release) => Ok(Some(fetch_mappings(storage, target_dir, release)?)),
+        None => Ok(None),
+    }
+}
+
+/// Mapping of obfuscated Java classes and methods written by R8.
+#[derive(Debug, Clone, Default)]
+pub struct ProguardMapping {
+    /// Classes by obfuscated name.
+    classes: HashMap<String, ClassMapping>,
+}
+
+#[derive(Debug, Clone)]
+struct ClassMapping {
+    original: String,
+    source_file: Option<String>,
+    methods: Vec<MethodMapping>,
+}
+
+#[derive(Debug, Clone)]
+struct MethodMapping {
+    obfuscated: String,
+    /// Original name, qualified with the class name if the method was inlined from
+    /// another class.
+    original: String,
+    /// Line range in the obfuscated code.
+    range: Option<(u32, u32)>,
+    /// Line range in the original code.
+    original_range: Option<(u32, Option<u32>)>,
+}
+
+impl ProguardMapping {
+    /// Parses the mapping file in ProGuard format:
+    /// `com.example.Foo -> a.a:` followed by members like
+    /// `    1:3:void bar(int):10:12 -> a`.
+    pub fn parse(contents: &str) -> Self {
+        let mut mapping = Self::default();
+        let mut current: Option<String> = None;
+        for line in contents.lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('#') {
+                // R8 stores the source file name in a JSON comment after the class
+                if let (Some(class), Some(file_name)) = (&current, source_file_name(trimmed)) {
+                    if let Some(class) = mapping.classes.get_mut(class) {
+                        class.source_file = Some(file_name);
+                    }
+                }
+                continue;
+            }
+            let (left, obfuscated) = match trimmed.split_once(" -> ") {
+                Some(parts) => parts,
+                None => continue,
+            };
+            if !line.starts_with(char::is_whitespace) {
+                let obfuscated = obfuscated.trim_end_matches(':').to_owned();
+                mapping.classes.insert(
+                    obfuscated.clone(),
+                    ClassMapping {
+                        original: left.to_owned(),
+                        source_file: None,
+                        methods: Vec::new(),
+                    },
+                );
+                current = Some(obfuscated);
+                continue;
+            }
+            let class = match current.as_ref().and_then(|c| mapping.classes.get_mut(c)) {
+                Some(class) => class,
+                None => continue,
+            };
+            if let Some(method) = parse_method(left, obfuscated) {
+                class.methods.push(method);
+            }
+        }
+        mapping
+    }
+
+    /// Returns the original name of the obfuscated class.
+    pub fn original_class(&self, obfuscated: &str) -> Option<&str> {
+        self.classes
+            .get(obfuscated)
+            .map(|class| class.original.as_str())
+    }
+
+    /// Retraces the stack frame like `at a.a.a(SourceFile:3)`. Returns several frames if
+    /// methods were inlined or the frame is ambiguous.
+    fn retrace_frame(&self, line: &str) -> Option<Vec<String>> {
+        let at = line
+            .find("at ")
+            .filter(|&at| line[..at].trim().is_empty())?;
+        let (prefix, frame) = line.split_at(at + 3);
+        let open = frame.find('(')?;
+        let close = frame.rfind(')')?;
+        let (class, method) = frame[..open].rsplit_once('.')?;
+        let class_mapping = self.classes.get(class)?;
+        let line_number = frame[open + 1..close]
+            .rsplit_once(':')
+            .and_then(|(_, line)| line.parse::<u32>().ok());
+        let by_name: Vec<&MethodMapping> = class_mapping
+            .methods
+            .iter()
+            .filter(|m| m.obfuscated == method)
+            .collect();
+        let in_range: Vec<&MethodMapping> = by_name
+            .iter()
+            .copied()
+            .filter(|m| match (m.range, line_number) {
+                (Some((start, end)), Some(line)) => start <= line && line <= end,
+                _ => false,
+            })
+            .collect();
+        let candidates = if in_range.is_empty() {
+            by_name
+        } else {
+            in_range
+        };
+        let file = |class: &str, source_file: Option<&String>| match source_file {
+            Some(source_file) => source_file.clone(),
+            None => {
+                let simple = class.rsplit('.').next().unwrap_or(class);
+                format!("{}.java", simple.split('$').next().unwrap_or(simple))
+            }
+        };
+        if candidates.is_empty() {
+            let file = file(&class_mapping.original, class_mapping.source_file.as_ref());
+            let location = line_number.map_or(file.clone(), |l| format!("{}:{}", file, l));
+            return Some(vec![format!(
+                "{}{}.{}({})",
+                prefix, class_mapping.original, method, location
+            )]);
+        }
+        let frames = candidates
+            .into_iter()
+            .map(|m| {
+                let (class, name, source_file) = match m.original.rsplit_once('.') {
+                    Some((class, name)) => (class.to_owned(), name, None),
+                    None => (
+                        class_mapping.original.clone(),
+                        m.original.as_str(),
+                        class_mapping.source_file.as_ref(),
+                    ),
+                };
+                let file = file(&class, source_file);
+                let original_line = match (m.range, m.original_range, line_number) {
+                    (Some((start, _)), Some((original_start, Some(_))), Some(line)) => {
+                        Some(original_start + line.saturating_sub(start))
+                    }
+                    (_, Some((original_start, None)), _) => Some(original_start),
+                    (_, None, line) => line,
+                    _ => None,
+                };
+                match original_line {
+                    Some(line) => format!("{}{}.{}({}:{})", prefix, class, name, file, line),
+                    None => format!("{}{}.{}({})", prefix, class, name, file),
+                }
+            })
+            .collect();
+        Some(frames)
+    }
+}
+
+/// Parses the method line of the mapping. Fields are skipped.
+fn parse_method(left: &str, obfuscated: &str) -> Option<MethodMapping> {
+    let parts: Vec<&str> = left.split(':').collect();
+    let signature_index = parts.iter().position(|part| part.contains('('))?;
+    let signature = parts[signature_index];
+    let name = signature[..signature.find('(')?]
+        .rsplit(' ')
+        .next()?
+        .to_owned();
+    let number = |index: usize| parts.get(index).and_then(|part| part.parse::<u32>().ok());
+    let range = match signature_index {
+        2 => Some((number(0)?, number(1)?)),
+        _ => None,
+    };
+    let original_range =
+        number(signature_index + 1).map(|start| (start, number(signature_index + 2)));
+    Some(MethodMapping {
+        obfuscated: obfuscated.to_owned(),
+        original: name,
+        range,
+        original_range,
+    })
+}
+
+/// Extracts `fileName` from `# {"id":"sourceFile","fileName":"Foo.kt"}` comment.
+fn source_file_name(comment: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(comment.trim_start_matches('#')).ok()?;
+    if json.get("id")?.as_str()? != "sourceFile" {
+        return None;
+    }
+    Some(json.get("fileName")?.as_str()?.to_owned())
+}
+
+/// Map of shortened resource paths written by `aapt2 optimize`.
+#[derive(Debug, Clone, Default)]
+pub struct ResourcePathMap {
+    /// Pairs of shortened and original paths, longest shortened paths first.
+    paths: Vec<(String, String)>,
+}
+
+impl ResourcePathMap {
+    /// Parses lines like `res/drawable/icon.png -> res/a1.png`.
+    pub fn parse(contents: &str) -> Self {
+        let mut paths: Vec<(String, String)> = contents
+            .lines()
+            .filter_map(|line| line.split_once(" -> "))
+            .map(|(original, short)| (short.trim().to_owned(), original.trim().to_owned()))
+            .collect();
+        paths.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+        Self { paths }
+    }
+
+    /// Replaces shortened paths in the text with the original ones.
+    pub fn deobfuscate(&self, text: &str) -> String {
+        let mut text = text.to_owned();
+        for (short, original) in &self.paths {
+            text = text.replace(short, original);
+        }
+        text
+    }
+}
+
+/// De-obfuscates stack traces and resource paths of the report. Stack frames are
+/// retraced with the R8 mapping, exception class names are replaced with the original
+/// ones.
+pub fn retrace(
+    report: &str,
+    mapping: Option<&ProguardMapping>,
+    resources: Option<&ResourcePathMap>,
+) -> String {
+    let mut output = String::new();
+    for line in report.lines() {
+        let line = match resources {
+            Some(resources) => resources.deobfuscate(line),
+            None => line.to_owned(),
+        };
+        let mapping = match mapping {
+            Some(mapping) => mapping,
+            None => {
+                output.push_str(&line);
+                output.push('\n');
+                continue;
+            }
+        };
+        if let Some(frames) = mapping.retrace_frame(&line) {
+            for frame in frames {
+                output.push_str(&frame);
+                output.push('\n');
+            }
+            continue;
+        }
+        // Exception lines like `Caused by: a.b: message`
+        let words: Vec<String> = line
+            .split(' ')
+            .map(|word| {
+                let name = word.trim_end_matches(':');
+                match mapping.original_class(name) {
+                    Some(original) if name.contains('.') => word.replacen(name, original, 1),
+                    _ => word.to_owned(),
+                }
+            })
+            .collect();
+        output.push_str(&words.join(" "));
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retrace() {
+        let mapping = ProguardMapping::parse(
+            "# compiler: R8\n\
+             com.crossbow.game.MainActivity -> a.a:\n\
+             # {\"id\":\"sourceFile\",\"fileName\":\"MainActivity.kt\"}\n\
+             \x20   int score -> a\n\
+             \x20   1:3:void onCreate(android.os.Bundle):20:22 -> a\n\
+             \x20   4:4:void com.crossbow.game.Util.check():7:7 -> b\n\
+             \x20   4:4:void update():40 -> b\n\
+             com.crossbow.game.GameException -> a.b:\n",
+        );
+        let resources = ResourcePathMap::parse("res/drawable/icon.png -> res/a1.png\n");
+        let report = "a.b: failed to load res/a1.png\n\
+                      \tat a.a.a(SourceFile:2)\n\
+                      \tat a.a.b(SourceFile:4)\n";
+        assert_eq!(
+            retrace(report, Some(&mapping), Some(&resources)),
+            "com.crossbow.game.GameException: failed to load res/drawable/icon.png\n\
+             \tat com.crossbow.game.MainActivity.onCreate(MainActivity.kt:21)\n\
+             \tat com.crossbow.game.Util.check(Util.java:7)\n\
+             \tat com.crossbow.game.MainActivity.update(MainActivity.kt:40)\n"
+        );
+    }
+
+    #[test]
+    fn test_latest_mappings_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = crate::commands::StorageConfig::storage(None, dir.path()).unwrap();
+        assert_eq!(
+            latest_mappings_dir(storage.as_ref(), dir.path()).unwrap(),
+            None
+        );
+        let mapping = dir.path().join(R8_MAPPING_FILE_NAME);
+        std::fs::write(&mapping, "a -> b").unwrap();
+        for release in ["1.9.0-9", "1.10.0-10", "1.2.0-2"] {
+            archive_mappings(storage.as_ref(), release, &[mapping.clone()]).unwrap();
+        }
+        assert_eq!(
+            latest_mappings_dir(storage.as_ref(), dir.path()).unwrap(),
+            Some(mappings_dir(dir.path(), "1.10.0-10"))
+        );
+    }
+}
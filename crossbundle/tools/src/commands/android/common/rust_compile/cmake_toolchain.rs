@@ -16,9 +16,18 @@ pub fn set_cmake_vars(
         build_target,
     )?;
 
-    // Set cmake environment variables
-    std::env::set_var("CMAKE_TOOLCHAIN_FILE", cmake_toolchain_path);
-    std::env::set_var("CMAKE_GENERATOR", r#"Unix Makefiles"#);
+    // Set cmake environment variables. The cmake crate reads target specific variables,
+    // so targets built in parallel don't use toolchains of each other. Make of the NDK
+    // is the same for all targets
+    let rust_triple = build_target.rust_triple().replace('-', "_");
+    std::env::set_var(
+        format!("CMAKE_TOOLCHAIN_FILE_{}", rust_triple),
+        cmake_toolchain_path,
+    );
+    std::env::set_var(
+        format!("CMAKE_GENERATOR_{}", rust_triple),
+        r#"Unix Makefiles"#,
+    );
     std::env::set_var("CMAKE_MAKE_PROGRAM", make_path(ndk.ndk_path()));
     Ok(())
 }
@@ -22,6 +22,7 @@ pub fn compile_options(
     build_target_dir: &Path,
     lib_name: &str,
     profile: Profile,
+    coverage: bool,
 ) -> Result<CompileOptions> {
     // Configure compilation options so that we will build the desired build_target
     let config = workspace.config();
@@ -41,14 +42,19 @@ pub fn compile_options(
         CliFeatures::from_command_line(features, all_features, !no_default_features)?;
 
     // Set the path and file name for the generated shared library
-    opts.target_rustc_args = Some(vec![format!(
+    let mut target_rustc_args = vec![format!(
         "--emit=link={}",
         build_target_dir
             .join(lib_name)
             .into_os_string()
             .into_string()
             .unwrap()
-    )]);
+    )];
+    // Only the crate itself is instrumented, dependencies are built as usual
+    if coverage {
+        target_rustc_args.push(crate::commands::COVERAGE_RUSTC_ARG.to_owned());
+    }
+    opts.target_rustc_args = Some(target_rustc_args);
 
     // Set desired profile
     if profile == Profile::Release {
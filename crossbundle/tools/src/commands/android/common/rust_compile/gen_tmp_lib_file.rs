@@ -3,7 +3,7 @@ use std::{fs, io::Write, path::Path};
 use tempfile::{Builder, NamedTempFile};
 
 /// Generate source file that will be built
-pub fn generate_lib_file(path: &Path, extra_code: &'static str) -> CargoResult<NamedTempFile> {
+pub fn generate_lib_file(path: &Path, extra_code: &str) -> CargoResult<NamedTempFile> {
     let original_src_filepath = path;
 
     // Determine the name of the temporary file
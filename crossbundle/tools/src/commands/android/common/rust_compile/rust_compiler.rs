@@ -1,19 +1,17 @@
 use super::*;
 use crate::{error::*, types::*};
 
-pub fn rust_compile(
+/// Sets environment variables of the C/C++ toolchain for the build target, which are
+/// used by the cc and cmake crates in build scripts. Only variables suffixed with the
+/// target triple are set, so it must be called on the main thread for every build
+/// target before they are compiled, in parallel or not.
+pub fn set_rust_compile_env(
     ndk: &AndroidNdk,
     build_target: AndroidTarget,
     project_path: &std::path::Path,
     profile: Profile,
-    features: Vec<String>,
-    all_features: bool,
-    no_default_features: bool,
     target_sdk_version: u32,
-    lib_name: &str,
-    app_wrapper: AppWrapper,
 ) -> Result<()> {
-    // Specify path to workspace
     let rust_triple = build_target.rust_triple();
 
     // Set environment variables needed for use with the cc crate
@@ -24,8 +22,50 @@ pub fn rust_compile(
     let ar = ndk.toolchain_bin("ar", build_target)?;
     std::env::set_var(format!("AR_{}", rust_triple), &ar);
 
+    // Use libc++. It is current default C++ runtime
+    std::env::set_var(format!("CXXSTDLIB_{}", rust_triple), "c++");
+
     let cargo_config = cargo::util::Config::default()?;
     let workspace = cargo::core::Workspace::new(&project_path.join("Cargo.toml"), &cargo_config)?;
+    let build_target_dir = workspace
+        .root()
+        .join("target")
+        .join(rust_triple)
+        .join(profile);
+    std::fs::create_dir_all(&build_target_dir)?;
+    set_cmake_vars(build_target, ndk, target_sdk_version, &build_target_dir)?;
+    Ok(())
+}
+
+/// Compiles the crate into the shared library for the build target. `cargo_target_dir`
+/// overrides the target directory of cargo, e.g. to build several targets in parallel
+/// without waiting for the lock of the shared directory. The library is written into
+/// `target/<rust triple>/<profile>` of the workspace anyway. With `coverage` the crate
+/// is instrumented and writes coverage profiles into the application data directory.
+/// Environment of the build target must be set with [`set_rust_compile_env`] before.
+pub fn rust_compile(
+    ndk: &AndroidNdk,
+    build_target: AndroidTarget,
+    project_path: &std::path::Path,
+    profile: Profile,
+    features: Vec<String>,
+    all_features: bool,
+    no_default_features: bool,
+    target_sdk_version: u32,
+    lib_name: &str,
+    app_wrapper: AppWrapper,
+    cargo_target_dir: Option<&std::path::Path>,
+    coverage: bool,
+) -> Result<()> {
+    // Specify path to workspace
+    let rust_triple = build_target.rust_triple();
+
+    let cargo_config = cargo::util::Config::default()?;
+    let mut workspace =
+        cargo::core::Workspace::new(&project_path.join("Cargo.toml"), &cargo_config)?;
+    if let Some(cargo_target_dir) = cargo_target_dir {
+        workspace.set_target_dir(cargo::util::Filesystem::new(cargo_target_dir.to_owned()));
+    }
 
     // Define directory to build project
     let build_target_dir = workspace
@@ -35,11 +75,6 @@ pub fn rust_compile(
         .join(profile);
     std::fs::create_dir_all(&build_target_dir).unwrap();
 
-    set_cmake_vars(build_target, ndk, target_sdk_version, &build_target_dir)?;
-
-    // Use libc++. It is current default C++ runtime
-    std::env::set_var("CXXSTDLIB", "c++");
-
     // Configure compilation options so that we will build the desired build_target
     let opts = compile_options::compile_options(
         &workspace,
@@ -50,6 +85,7 @@ pub fn rust_compile(
         &build_target_dir,
         lib_name,
         profile,
+        coverage,
     )?;
 
     // Create the executor
@@ -60,6 +96,7 @@ pub fn rust_compile(
             build_target,
             ndk: ndk.clone(),
             app_wrapper,
+            coverage,
         });
 
     // Compile all targets for the requested build target
@@ -74,6 +111,7 @@ struct SharedLibraryExecutor {
     build_target: AndroidTarget,
     ndk: AndroidNdk,
     app_wrapper: AppWrapper,
+    coverage: bool,
 }
 
 impl cargo::core::compiler::Executor for SharedLibraryExecutor {
@@ -92,10 +130,14 @@ impl cargo::core::compiler::Executor for SharedLibraryExecutor {
         {
             let mut new_args = cmd.get_args().cloned().collect::<Vec<_>>();
 
-            let extra_code = match self.app_wrapper {
+            let mut extra_code = match self.app_wrapper {
                 AppWrapper::Quad => consts::QUAD_EXTRA_CODE,
                 AppWrapper::NdkGlue => consts::NDK_GLUE_EXTRA_CODE,
-            };
+            }
+            .to_owned();
+            if self.coverage {
+                extra_code.push_str(crate::commands::ANDROID_COVERAGE_EXTRA_CODE);
+            }
 
             let path =
                 if let cargo::core::manifest::TargetSourcePath::Path(path) = target.src_path() {
@@ -108,8 +150,8 @@ impl cargo::core::compiler::Executor for SharedLibraryExecutor {
             // Generate tmp_file with bevy or quad extra code depending on either quad or ndk glue
             // dependency
             let tmp_file = match self.app_wrapper {
-                AppWrapper::Quad => gen_tmp_lib_file::generate_lib_file(&path, extra_code)?,
-                AppWrapper::NdkGlue => gen_tmp_lib_file::generate_lib_file(&path, extra_code)?,
+                AppWrapper::Quad => gen_tmp_lib_file::generate_lib_file(&path, &extra_code)?,
+                AppWrapper::NdkGlue => gen_tmp_lib_file::generate_lib_file(&path, &extra_code)?,
             };
 
             // Replace source argument
@@ -0,0 +1,69 @@
+use crate::{error::*, types::AndroidSdk};
+use std::path::Path;
+
+/// Path of the recording on the device before it's pulled.
+const DEVICE_RECORDING_PATH: &str = "/sdcard/crossbundle_recording.mp4";
+
+/// Records the device screen into MP4 file with `screenrecord`.
+pub struct ScreenRecord {
+    time_limit: u32,
+    bit_rate: Option<u32>,
+    size: Option<String>,
+}
+
+impl Default for ScreenRecord {
+    fn default() -> Self {
+        Self {
+            time_limit: 30,
+            bit_rate: None,
+            size: None,
+        }
+    }
+}
+
+impl ScreenRecord {
+    /// Maximum recording duration in seconds. `screenrecord` doesn't record longer than
+    /// 180 seconds.
+    pub fn time_limit(&mut self, time_limit: u32) -> &mut Self {
+        self.time_limit = time_limit.clamp(1, 180);
+        self
+    }
+
+    /// Video bit rate in bits per second, e.g. `8000000`.
+    pub fn bit_rate(&mut self, bit_rate: u32) -> &mut Self {
+        self.bit_rate = Some(bit_rate);
+        self
+    }
+
+    /// Video size like `1280x720`. By default the native resolution of the display is
+    /// used.
+    pub fn size(&mut self, size: &str) -> &mut Self {
+        self.size = Some(size.to_owned());
+        self
+    }
+
+    /// Records the screen until the time limit, pulls the video and deletes it from the
+    /// device. Runs `adb shell screenrecord ...` and `adb pull` commands
+    pub fn run(&self, sdk: &AndroidSdk, device: Option<&str>, output: &Path) -> Result<()> {
+        let mut adb = sdk.adb(device)?;
+        adb.arg("shell")
+            .arg("screenrecord")
+            .arg("--time-limit")
+            .arg(self.time_limit.to_string());
+        if let Some(bit_rate) = self.bit_rate {
+            adb.arg("--bit-rate").arg(bit_rate.to_string());
+        }
+        if let Some(size) = &self.size {
+            adb.arg("--size").arg(size);
+        }
+        adb.arg(DEVICE_RECORDING_PATH);
+        adb.output_err(false)?;
+        let mut adb = sdk.adb(device)?;
+        adb.arg("pull").arg(DEVICE_RECORDING_PATH).arg(output);
+        adb.output_err(false)?;
+        let mut adb = sdk.adb(device)?;
+        adb.args(["shell", "rm", "-f", DEVICE_RECORDING_PATH]);
+        adb.output_err(false)?;
+        Ok(())
+    }
+}
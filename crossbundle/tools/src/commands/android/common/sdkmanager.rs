@@ -0,0 +1,165 @@
+use crate::error::*;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// Build tools version installed by default.
+pub const DEFAULT_BUILD_TOOLS_VERSION: &str = "31.0.0";
+
+/// NDK version installed by default.
+pub const DEFAULT_NDK_VERSION: &str = "23.1.7779620";
+
+/// Android platform installed by default.
+pub const DEFAULT_PLATFORM: u32 = 31;
+
+/// Finds `sdkmanager` in the command line tools of given SDK.
+///
+/// Android Studio installs cmdline-tools into `$ANDROID_SDK_ROOT/cmdline-tools/<version>/bin`
+/// while `crossbundle install command-line-tools` extracts them without `<version>`
+/// directory, so both layouts are checked.
+pub fn find_sdkmanager(sdk_root: &Path) -> Result<PathBuf> {
+    let cmdline_tools_path = sdk_root.join("cmdline-tools");
+    [
+        cmdline_tools_path.join("latest").join("bin"),
+        cmdline_tools_path.join("bin"),
+    ]
+    .into_iter()
+    .map(|path| path.join(bat!("sdkmanager")))
+    .find(|path| path.exists())
+    .ok_or_else(|| AndroidError::SdkManagerNotFound(cmdline_tools_path).into())
+}
+
+/// Returns `sdkmanager` package names of the platform, build tools and NDK required to
+/// build the application.
+pub fn required_sdk_packages(
+    platform: u32,
+    build_tools_version: &str,
+    ndk_version: &str,
+) -> Vec<String> {
+    vec![
+        "platform-tools".to_owned(),
+        format!("platforms;android-{}", platform),
+        format!("build-tools;{}", build_tools_version),
+        format!("ndk;{}", ndk_version),
+    ]
+}
+
+/// ## Sdkmanager
+/// `sdkmanager` is a command line tool that allows you to view, install, update, and
+/// uninstall packages for the Android SDK.
+///
+/// See more: https://developer.android.com/studio/command-line/sdkmanager
+#[derive(Clone, Debug)]
+pub struct SdkManager {
+    sdkmanager_path: PathBuf,
+    sdk_root: PathBuf,
+    packages: Vec<String>,
+    channel: Option<u32>,
+    verbose: bool,
+}
+
+impl SdkManager {
+    /// Finds `sdkmanager` in given SDK. Packages will be installed into the same SDK.
+    pub fn new(sdk_root: &Path) -> Result<Self> {
+        Ok(Self {
+            sdkmanager_path: find_sdkmanager(sdk_root)?,
+            sdk_root: sdk_root.to_owned(),
+            packages: Vec::new(),
+            channel: None,
+            verbose: false,
+        })
+    }
+
+    /// Package to install, e.g. `ndk;23.1.7779620`.
+    pub fn package(&mut self, package: &str) -> &mut Self {
+        self.packages.push(package.to_owned());
+        self
+    }
+
+    /// Packages to install.
+    pub fn packages(&mut self, packages: &[String]) -> &mut Self {
+        self.packages.extend_from_slice(packages);
+        self
+    }
+
+    /// Include packages in channels up to and including channel_id. Available channels
+    /// are: 0 (Stable), 1 (Beta), 2 (Dev), and 3 (Canary).
+    pub fn channel(&mut self, channel: u32) -> &mut Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    /// Verbose output mode. Errors, warnings and informational messages are printed.
+    pub fn verbose(&mut self, verbose: bool) -> &mut Self {
+        self.verbose = verbose;
+        self
+    }
+
+    fn command(&self) -> Command {
+        let mut sdkmanager = Command::new(&self.sdkmanager_path);
+        sdkmanager.arg(format!("--sdk_root={}", self.sdk_root.to_str().unwrap()));
+        if let Some(channel) = self.channel {
+            sdkmanager.arg(format!("--channel={}", channel));
+        }
+        if self.verbose {
+            sdkmanager.arg("--verbose");
+        }
+        sdkmanager
+    }
+
+    /// Accepts all SDK licenses that weren't accepted yet.
+    /// Runs `sdkmanager --licenses` command and answers `y` to every prompt
+    pub fn accept_licenses(&self) -> Result<()> {
+        let mut sdkmanager = self.command();
+        sdkmanager
+            .arg("--licenses")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null());
+        let mut child = sdkmanager.spawn()?;
+        if let Some(stdin) = child.stdin.as_mut() {
+            // sdkmanager closes stdin after the last license, so write errors are expected
+            for _ in 0..64 {
+                if stdin.write_all(b"y\n").is_err() {
+                    break;
+                }
+            }
+        }
+        if !child.wait()?.success() {
+            return Err(Error::CmdFailed(sdkmanager, String::new(), String::new()));
+        }
+        Ok(())
+    }
+
+    /// Installs specified packages.
+    /// Runs `sdkmanager <packages>...` command
+    pub fn install(&self) -> Result<()> {
+        if self.packages.is_empty() {
+            return Ok(());
+        }
+        let mut sdkmanager = self.command();
+        sdkmanager.args(&self.packages);
+        sdkmanager.output_err(true)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_sdk_packages() {
+        let packages = required_sdk_packages(31, "31.0.0", "23.1.7779620");
+        assert_eq!(
+            packages,
+            vec![
+                "platform-tools",
+                "platforms;android-31",
+                "build-tools;31.0.0",
+                "ndk;23.1.7779620"
+            ]
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{error::*, types::AndroidNdk};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+
+/// Name of the profile recorded by `app_profiler.py` in the working directory.
+pub const PERF_DATA_FILE_NAME: &str = "perf.data";
+
+/// Format of the converted profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PerfFormat {
+    /// Interactive HTML report with flamegraphs, generated by `report_html.py`.
+    Html,
+    /// Protobuf profile for `pprof` and other viewers, generated by
+    /// `pprof_proto_generator.py`.
+    Pprof,
+    /// Folded stacks for `flamegraph.pl` or inferno, generated by `stackcollapse.py`.
+    Folded,
+}
+
+impl PerfFormat {
+    /// Name of the converted file.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Self::Html => "report.html",
+            Self::Pprof => "pprof.profile",
+            Self::Folded => "out.folded",
+        }
+    }
+}
+
+impl FromStr for PerfFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "html" => Ok(Self::Html),
+            "pprof" => Ok(Self::Pprof),
+            "folded" => Ok(Self::Folded),
+            _ => Err(Error::InvalidPerfFormat(s.to_owned())),
+        }
+    }
+}
+
+/// Returns the `simpleperf` directory of the NDK with the device binaries and python
+/// scripts.
+pub fn find_simpleperf(ndk: &AndroidNdk) -> Result<PathBuf> {
+    let simpleperf = ndk.ndk_path().join("simpleperf");
+    if !simpleperf.join("app_profiler.py").exists() {
+        return Err(AndroidError::SimpleperfNotFound(ndk.ndk_path().to_owned()).into());
+    }
+    Ok(simpleperf)
+}
+
+/// Records the profile of the running application with `app_profiler.py` of simpleperf.
+/// The script pushes simpleperf to the device, records the profile, pulls `perf.data`
+/// and copies libraries with symbols into `binary_cache` of the working directory.
+/// Runs `python3 app_profiler.py --app <package> -r <record options> -lib <symbols>`
+/// command
+#[derive(Debug, Clone)]
+pub struct AppProfiler {
+    simpleperf: PathBuf,
+    package: String,
+    work_dir: PathBuf,
+    duration: Option<u32>,
+    frequency: Option<u32>,
+    call_graph: Option<String>,
+    symbol_dir: Option<PathBuf>,
+    device: Option<String>,
+    adb_dir: Option<PathBuf>,
+}
+
+impl AppProfiler {
+    pub fn new(simpleperf: &Path, package: &str, work_dir: &Path) -> Self {
+        Self {
+            simpleperf: simpleperf.to_owned(),
+            package: package.to_owned(),
+            work_dir: work_dir.to_owned(),
+            duration: None,
+            frequency: None,
+            call_graph: None,
+            symbol_dir: None,
+            device: None,
+            adb_dir: None,
+        }
+    }
+
+    /// Duration of the recording in seconds.
+    pub fn duration(&mut self, duration: u32) -> &mut Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Number of samples per second.
+    pub fn frequency(&mut self, frequency: u32) -> &mut Self {
+        self.frequency = Some(frequency);
+        self
+    }
+
+    /// Call graph recording method: `dwarf` or `fp`. `fp` requires frame pointers in the
+    /// libraries.
+    pub fn call_graph(&mut self, call_graph: &str) -> &mut Self {
+        self.call_graph = Some(call_graph.to_owned());
+        self
+    }
+
+    /// Directory with unstripped libraries of the application.
+    pub fn symbol_dir(&mut self, symbol_dir: &Path) -> &mut Self {
+        self.symbol_dir = Some(symbol_dir.to_owned());
+        self
+    }
+
+    /// Serial of the device the application runs on.
+    pub fn device(&mut self, device: &str) -> &mut Self {
+        self.device = Some(device.to_owned());
+        self
+    }
+
+    /// Directory with `adb`, added to `PATH` of the script.
+    pub fn adb_dir(&mut self, adb_dir: &Path) -> &mut Self {
+        self.adb_dir = Some(adb_dir.to_owned());
+        self
+    }
+
+    /// Records the profile and returns path of `perf.data`.
+    pub fn run(&self) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.work_dir)?;
+        let mut record_options = String::from("-e task-clock:u");
+        if let Some(frequency) = self.frequency {
+            record_options.push_str(&format!(" -f {}", frequency));
+        }
+        if let Some(duration) = self.duration {
+            record_options.push_str(&format!(" --duration {}", duration));
+        }
+        match &self.call_graph {
+            Some(call_graph) => record_options.push_str(&format!(" --call-graph {}", call_graph)),
+            None => record_options.push_str(" -g"),
+        }
+        let mut app_profiler = python_script(&self.simpleperf, "app_profiler.py")?;
+        app_profiler
+            .current_dir(&self.work_dir)
+            .arg("--app")
+            .arg(&self.package)
+            .arg("-r")
+            .arg(record_options)
+            .arg("-o")
+            .arg(PERF_DATA_FILE_NAME);
+        if let Some(symbol_dir) = &self.symbol_dir {
+            app_profiler.arg("-lib").arg(symbol_dir);
+        }
+        if let Some(device) = &self.device {
+            app_profiler.env("ANDROID_SERIAL", device);
+        }
+        if let Some(adb_dir) = &self.adb_dir {
+            let mut paths = vec![adb_dir.clone()];
+            if let Some(path) = std::env::var_os("PATH") {
+                paths.extend(std::env::split_paths(&path));
+            }
+            app_profiler.env(
+                "PATH",
+                std::env::join_paths(paths).map_err(anyhow::Error::from)?,
+            );
+        }
+        app_profiler.output_err(true)?;
+        Ok(self.work_dir.join(PERF_DATA_FILE_NAME))
+    }
+}
+
+/// Converts `perf.data` recorded by [`AppProfiler`] into the given format. Symbols are
+/// taken from `binary_cache` of the working directory.
+/// Runs `python3 report_html.py`, `pprof_proto_generator.py` or `stackcollapse.py`
+/// command
+pub fn convert_perf_data(
+    simpleperf: &Path,
+    perf_data: &Path,
+    format: PerfFormat,
+) -> Result<PathBuf> {
+    let work_dir = perf_data.parent().unwrap_or_else(|| Path::new("."));
+    let output = work_dir.join(format.file_name());
+    let script = match format {
+        PerfFormat::Html => "report_html.py",
+        PerfFormat::Pprof => "pprof_proto_generator.py",
+        PerfFormat::Folded => "stackcollapse.py",
+    };
+    let mut converter = python_script(simpleperf, script)?;
+    converter.current_dir(work_dir).arg("-i").arg(perf_data);
+    match format {
+        PerfFormat::Html => {
+            converter.arg("-o").arg(&output).arg("--no_browser");
+        }
+        PerfFormat::Pprof => {
+            converter.arg("-o").arg(&output);
+        }
+        PerfFormat::Folded => {}
+    }
+    let result = converter.output_err(true)?;
+    // stackcollapse.py prints folded stacks to stdout
+    if format == PerfFormat::Folded {
+        std::fs::write(&output, result.stdout)?;
+    }
+    Ok(output)
+}
+
+/// Returns command running the simpleperf python script with `python3` from PATH.
+fn python_script(simpleperf: &Path, script: &str) -> Result<Command> {
+    let python = which::which(bin!("python3"))
+        .or_else(|_| which::which(bin!("python")))
+        .map_err(|_| Error::CmdNotFound("python3".to_owned()))?;
+    let mut command = Command::new(python);
+    command.arg(simpleperf.join(script));
+    Ok(command)
+}
@@ -0,0 +1,74 @@
+use crate::error::*;
+use std::{collections::BTreeMap, path::Path};
+
+/// Parses `com.crossbow.game:string/app_name = 0x7f0b0000` lines of the file written by
+/// `aapt2 link --emit-ids`.
+pub fn parse_stable_ids(content: &str) -> BTreeMap<String, String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (name, id) = line.split_once('=')?;
+            Some((name.trim().to_owned(), id.trim().to_owned()))
+        })
+        .filter(|(name, id)| !name.is_empty() && id.starts_with("0x"))
+        .collect()
+}
+
+/// Merges IDs emitted by the last link into the stable IDs file. IDs of removed
+/// resources are kept, so they are not given to other resources when the removed ones
+/// come back. Returns `true` if the file was changed.
+pub fn update_stable_ids(stable_ids_path: &Path, emitted_ids_path: &Path) -> Result<bool> {
+    let previous = match stable_ids_path.exists() {
+        true => std::fs::read_to_string(stable_ids_path)?,
+        false => String::new(),
+    };
+    let mut ids = parse_stable_ids(&previous);
+    ids.extend(parse_stable_ids(&std::fs::read_to_string(
+        emitted_ids_path,
+    )?));
+    let content: String = ids
+        .iter()
+        .map(|(name, id)| format!("{} = {}\n", name, id))
+        .collect();
+    if content == previous {
+        return Ok(false);
+    }
+    if let Some(parent) = stable_ids_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(stable_ids_path, content)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_stable_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let stable_ids = dir.path().join("android").join("stable-ids.txt");
+        let emitted = dir.path().join("emitted-ids.txt");
+        std::fs::write(
+            &emitted,
+            "com.game:string/title = 0x7f0b0001\ncom.game:string/app_name = 0x7f0b0000\n",
+        )
+        .unwrap();
+        assert!(update_stable_ids(&stable_ids, &emitted).unwrap());
+        assert!(!update_stable_ids(&stable_ids, &emitted).unwrap());
+
+        // Removed `title` keeps its ID
+        std::fs::write(
+            &emitted,
+            "com.game:string/app_name = 0x7f0b0000\ncom.game:string/play = 0x7f0b0002\n",
+        )
+        .unwrap();
+        assert!(update_stable_ids(&stable_ids, &emitted).unwrap());
+        assert_eq!(
+            std::fs::read_to_string(&stable_ids).unwrap(),
+            "com.game:string/app_name = 0x7f0b0000\n\
+             com.game:string/play = 0x7f0b0002\n\
+             com.game:string/title = 0x7f0b0001\n"
+        );
+    }
+}
@@ -1,16 +1,23 @@
+use super::adb_shell;
 use crate::{error::*, types::AndroidSdk};
 
-/// Installing APK or AAB on emulator or connected device.
-/// Runs `adb shell am start ...` command
-pub fn start_app(sdk: &AndroidSdk, package: &str, activity: &str) -> Result<()> {
-    let mut adb = sdk.platform_tool(bin!("adb"))?;
-    adb.arg("shell")
-        .arg("am")
-        .arg("start")
-        .arg("-a")
-        .arg("android.intent.action.MAIN")
-        .arg("-n")
-        .arg(format!("{}/{}", package, activity));
-    adb.output_err(true)?;
+/// Starts activity of installed application on emulator or connected device.
+/// Runs `adb [-s <serial>] shell am start ...` command
+pub fn start_app(
+    sdk: &AndroidSdk,
+    package: &str,
+    activity: &str,
+    device: Option<&str>,
+) -> Result<()> {
+    let component = format!("{}/{}", package, activity);
+    let args = [
+        "am",
+        "start",
+        "-a",
+        "android.intent.action.MAIN",
+        "-n",
+        &component,
+    ];
+    adb_shell(sdk, device, &args)?.success()?;
     Ok(())
 }
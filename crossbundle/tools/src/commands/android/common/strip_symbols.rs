@@ -0,0 +1,54 @@
+use crate::{
+    error::*,
+    types::{AndroidNdk, AndroidTarget},
+};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Returns directory with unstripped libraries of the release:
+/// `<target_dir>/crossbundle/symbols/<release>`. Libraries are placed into subdirectories
+/// by ABI, like `arm64-v8a`.
+pub fn symbols_dir(target_dir: &Path, release: &str) -> PathBuf {
+    target_dir.join("crossbundle").join("symbols").join(release)
+}
+
+/// Strips symbols and debug info from native libraries before packaging. Unstripped
+/// copies are kept in the symbols directory, so crashes of the release can be
+/// symbolicated with `ndk-stack` or `llvm-symbolizer` later.
+#[derive(Debug, Clone)]
+pub struct SymbolStripper {
+    strip: PathBuf,
+    symbols_dir: PathBuf,
+}
+
+impl SymbolStripper {
+    /// Uses `strip` of the NDK toolchain for the target. Unstripped libraries are copied
+    /// into the ABI subdirectory of `symbols_dir`.
+    pub fn new(ndk: &AndroidNdk, build_target: AndroidTarget, symbols_dir: &Path) -> Result<Self> {
+        Ok(Self {
+            strip: ndk.toolchain_bin("strip", build_target)?,
+            symbols_dir: symbols_dir.join(build_target.android_abi()),
+        })
+    }
+
+    /// Directory with unstripped copies of the libraries.
+    pub fn symbols_dir(&self) -> &Path {
+        &self.symbols_dir
+    }
+
+    /// Copies the library into the symbols directory and strips it in place.
+    /// Runs `llvm-strip --strip-unneeded <lib>` command
+    pub fn strip(&self, lib_path: &Path) -> Result<()> {
+        std::fs::create_dir_all(&self.symbols_dir)?;
+        std::fs::copy(
+            lib_path,
+            self.symbols_dir.join(lib_path.file_name().unwrap()),
+        )?;
+        let mut strip = Command::new(&self.strip);
+        strip.arg("--strip-unneeded").arg(lib_path);
+        strip.output_err(true)?;
+        Ok(())
+    }
+}
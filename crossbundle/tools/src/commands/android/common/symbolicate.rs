@@ -0,0 +1,190 @@
+use crate::{
+    error::*,
+    types::{AndroidNdk, AndroidSdk, AndroidTarget, IntoRustTriple, Profile},
+};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Frame of a native backtrace in a tombstone or a logcat crash dump, e.g.
+/// `#00 pc 000000000004a8c4  /data/app/com.crossbow.game/lib/arm64/libgame.so (...)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BacktraceFrame {
+    pub index: u32,
+    /// Relative address of the instruction in the library.
+    pub pc: u64,
+    /// Path of the library on the device.
+    pub library: String,
+}
+
+impl BacktraceFrame {
+    /// File name of the library, e.g. `libgame.so`.
+    pub fn library_name(&self) -> &str {
+        self.library.rsplit('/').next().unwrap_or(&self.library)
+    }
+}
+
+/// Parses the backtrace frame from a line of the tombstone. Logcat prefixes like
+/// `F DEBUG   :` are skipped.
+pub fn parse_backtrace_frame(line: &str) -> Option<BacktraceFrame> {
+    let start = line.find('#')?;
+    let mut parts = line[start + 1..].split_whitespace();
+    let index = parts.next()?.parse().ok()?;
+    if parts.next()? != "pc" {
+        return None;
+    }
+    let pc = u64::from_str_radix(parts.next()?, 16).ok()?;
+    let library = parts.next()?.to_owned();
+    Some(BacktraceFrame { index, pc, library })
+}
+
+/// Returns existing directories with unstripped libraries of the target: the regular
+/// cargo output and the output of parallel builds.
+pub fn unstripped_lib_dirs(
+    target_dir: &Path,
+    target: AndroidTarget,
+    profile: &Profile,
+) -> Vec<PathBuf> {
+    let rust_triple = target.rust_triple();
+    [
+        target_dir.join(rust_triple).join(profile),
+        target_dir
+            .join("parallel")
+            .join(rust_triple)
+            .join(rust_triple)
+            .join(profile),
+    ]
+    .into_iter()
+    .filter(|dir| dir.exists())
+    .collect()
+}
+
+/// Returns the target of the crashed process from the `ABI: 'arm64'` line of the
+/// tombstone.
+pub fn tombstone_abi(tombstone: &str) -> Option<AndroidTarget> {
+    tombstone.lines().find_map(|line| {
+        let abi = line.split("ABI: '").nth(1)?.split('\'').next()?;
+        match abi {
+            "arm" => Some(AndroidTarget::Armv7),
+            "arm64" => Some(AndroidTarget::Aarch64),
+            "x86" => Some(AndroidTarget::I686),
+            "x86_64" => Some(AndroidTarget::X8664),
+            _ => None,
+        }
+    })
+}
+
+/// Returns native crashes from the crash buffer of the device.
+/// Runs `adb logcat -d -b crash` command
+pub fn dump_crash_buffer(sdk: &AndroidSdk, device: Option<&str>) -> Result<String> {
+    let mut adb = sdk.adb(device)?;
+    adb.args(["logcat", "-d", "-b", "crash"]);
+    let output = adb.output_err(true)?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Returns path of the `ndk-stack` script in the NDK root if it exists.
+pub fn find_ndk_stack(ndk: &AndroidNdk) -> Option<PathBuf> {
+    #[cfg(not(target_os = "windows"))]
+    let name = "ndk-stack";
+    #[cfg(target_os = "windows")]
+    let name = "ndk-stack.cmd";
+    let ndk_stack = ndk.ndk_path().join(name);
+    ndk_stack.exists().then_some(ndk_stack)
+}
+
+/// Returns path of `llvm-symbolizer` from the NDK toolchain if it exists.
+pub fn find_llvm_symbolizer(ndk: &AndroidNdk) -> Option<PathBuf> {
+    let symbolizer = ndk
+        .toolchain_dir()
+        .ok()?
+        .join("bin")
+        .join(bin!("llvm-symbolizer"));
+    symbolizer.exists().then_some(symbolizer)
+}
+
+/// Symbolicates the tombstone with unstripped libraries from `symbol_dir`.
+/// Runs `ndk-stack -sym <symbol_dir> -i <tombstone>` command
+pub fn ndk_stack(ndk_stack: &Path, symbol_dir: &Path, tombstone: &Path) -> Result<String> {
+    let mut ndk_stack = Command::new(ndk_stack);
+    ndk_stack
+        .arg("-sym")
+        .arg(symbol_dir)
+        .arg("-i")
+        .arg(tombstone);
+    let output = ndk_stack.output_err(true)?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Appends functions and source locations to the backtrace frames of the tombstone.
+/// Frames of libraries that are not found in `symbol_dirs`, like system libraries, are
+/// left as is.
+/// Runs `llvm-symbolizer --obj=<library> --demangle --inlines 0x<pc>` command
+pub fn symbolize_tombstone(
+    symbolizer: &Path,
+    tombstone: &str,
+    symbol_dirs: &[PathBuf],
+) -> Result<String> {
+    let mut output = String::new();
+    for line in tombstone.lines() {
+        output.push_str(line);
+        output.push('\n');
+        let frame = match parse_backtrace_frame(line) {
+            Some(frame) => frame,
+            None => continue,
+        };
+        let library = symbol_dirs
+            .iter()
+            .map(|dir| dir.join(frame.library_name()))
+            .find(|path| path.exists());
+        let library = match library {
+            Some(library) => library,
+            None => continue,
+        };
+        let mut llvm_symbolizer = Command::new(symbolizer);
+        llvm_symbolizer
+            .arg(format!("--obj={}", library.display()))
+            .args(["--demangle", "--inlines"])
+            .arg(format!("0x{:x}", frame.pc));
+        let symbols = llvm_symbolizer.output_err(true)?;
+        // Pairs of function and location lines, one pair for every inlined frame
+        let symbols = String::from_utf8_lossy(&symbols.stdout).to_string();
+        let lines = symbols
+            .lines()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
+        for pair in lines.chunks(2) {
+            if pair[0] == "??" {
+                continue;
+            }
+            output.push_str(&format!("        {}", pair[0]));
+            if let Some(location) = pair.get(1).filter(|location| !location.starts_with("??")) {
+                output.push_str(&format!(" at {}", location));
+            }
+            output.push('\n');
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_backtrace_frame() {
+        let line = "10-17 12:00:00.000  4242  4242 F DEBUG   :       #03 pc 000000000004a8c4  \
+                    /data/app/~~abc==/com.crossbow.game-xyz==/lib/arm64/libgame.so \
+                    (BuildId: 1234)";
+        let frame = parse_backtrace_frame(line).unwrap();
+        assert_eq!(frame.index, 3);
+        assert_eq!(frame.pc, 0x4a8c4);
+        assert_eq!(frame.library_name(), "libgame.so");
+        assert_eq!(parse_backtrace_frame("backtrace:"), None);
+        assert_eq!(
+            tombstone_abi("F DEBUG   : ABI: 'arm64'"),
+            Some(AndroidTarget::Aarch64)
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{commands::ThermalStatus, error::*, types::AndroidSdk};
+
+/// Thermal status and temperatures of the Android device.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AndroidThermalInfo {
+    pub status: Option<ThermalStatus>,
+    /// Highest CPU, GPU, battery or skin temperature in degrees Celsius.
+    pub max_temperature: Option<f32>,
+}
+
+/// Reads the thermal status of the device. Requires Android 10 or higher.
+/// Runs `adb shell dumpsys thermalservice` command
+pub fn android_thermal_info(sdk: &AndroidSdk, device: &str) -> Result<AndroidThermalInfo> {
+    let mut adb = sdk.adb(Some(device))?;
+    adb.args(["shell", "dumpsys", "thermalservice"]);
+    let output = adb.output_err(false)?;
+    Ok(parse_thermalservice(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Parses `dumpsys thermalservice` output. Temperatures are read from the `Current
+/// temperatures from HAL` section, cached values of other sections may be stale.
+pub fn parse_thermalservice(output: &str) -> AndroidThermalInfo {
+    let mut info = AndroidThermalInfo::default();
+    let mut hal_section = false;
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(status) = line.strip_prefix("Thermal Status:") {
+            info.status = status.trim().parse().ok().map(ThermalStatus::from_android);
+        } else if line.ends_with(':') {
+            hal_section = line.starts_with("Current temperatures from HAL");
+        } else if hal_section {
+            // Temperature{mValue=41.3, mType=0, mName=cpu0, mStatus=0}
+            let value = line
+                .split("mValue=")
+                .nth(1)
+                .and_then(|value| value.split(',').next())
+                .and_then(|value| value.parse::<f32>().ok());
+            if let Some(value) = value {
+                info.max_temperature =
+                    Some(info.max_temperature.map_or(value, |max| max.max(value)));
+            }
+        }
+    }
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_thermalservice() {
+        let output = "IsStatusOverride: false
+ThermalEventListeners:
+\tcallbacks: 1
+Thermal Status: 2
+Cached temperatures:
+\tTemperature{mValue=90.0, mType=0, mName=cpu0, mStatus=0}
+HAL Ready: true
+Current temperatures from HAL:
+\tTemperature{mValue=41.3, mType=0, mName=cpu0, mStatus=0}
+\tTemperature{mValue=44.8, mType=1, mName=gpu0, mStatus=0}
+\tTemperature{mValue=33.0, mType=3, mName=skin, mStatus=0}
+Current cooling devices from HAL:
+\tCoolingDevice{mValue=0, mType=2, mName=cpu0}
+";
+        let info = parse_thermalservice(output);
+        assert_eq!(info.status, Some(ThermalStatus::Moderate));
+        assert_eq!(info.max_temperature, Some(44.8));
+    }
+}
@@ -0,0 +1,101 @@
+use super::{aapt2_dump_badging, adb_shell, app_pid, BadgingInfo};
+use crate::{commands::LaunchOutcome, error::*, types::AndroidSdk};
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// Reads `aapt2 dump badging` output of the APK. Fails if the package name is missing,
+/// so `package` of the returned value is always set.
+pub fn apk_badging(sdk: &AndroidSdk, apk_path: &Path) -> Result<BadgingInfo> {
+    let badging = aapt2_dump_badging(sdk, apk_path)?;
+    match badging.package {
+        Some(_) => Ok(badging),
+        None => Err(Error::FailedToFindManifest(apk_path.to_owned())),
+    }
+}
+
+/// Returns current time of the device clock in the `<seconds>.<milliseconds>` format
+/// accepted by `adb logcat -T`. Read it before the launch to skip older log entries.
+/// Runs `adb shell date +%s` command
+pub fn device_log_time(sdk: &AndroidSdk, device: Option<&str>) -> Result<String> {
+    let output = adb_shell(sdk, device, &["date", "+%s"])?.success()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse_device_time(&stdout).ok_or_else(|| {
+        Error::LaunchVerificationFailed(format!("failed to read device time: {}", stdout))
+    })
+}
+
+fn parse_device_time(output: &str) -> Option<String> {
+    let seconds = output.trim().parse::<u64>().ok()?;
+    Some(format!("{}.000", seconds))
+}
+
+/// Watches the application process for `duration`. Stops early if the process dies.
+/// Crashes are read from the `crash` logcat buffer and ANRs from the `system` buffer,
+/// entries logged before `since` are skipped, see [`device_log_time`].
+pub fn watch_android_launch(
+    sdk: &AndroidSdk,
+    package: &str,
+    pid: u32,
+    device: Option<&str>,
+    since: &str,
+    duration: Duration,
+) -> Result<LaunchOutcome> {
+    let start = Instant::now();
+    let mut alive = true;
+    while start.elapsed() < duration {
+        std::thread::sleep(Duration::from_millis(500));
+        if app_pid(sdk, package, device)? != Some(pid) {
+            alive = false;
+            break;
+        }
+    }
+    let mut adb = sdk.adb(device)?;
+    adb.args(["logcat", "-d", "-b", "crash", "-T", since])
+        .arg(format!("--pid={}", pid));
+    let crash_log = String::from_utf8_lossy(&adb.output_err(false)?.stdout)
+        .lines()
+        .filter(|line| !line.starts_with("---------"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if !crash_log.is_empty() {
+        return Ok(LaunchOutcome::Crashed(crash_log));
+    }
+    let mut adb = sdk.adb(device)?;
+    adb.args(["logcat", "-d", "-b", "system", "-T", since])
+        .args(["-s", "ActivityManager:E"]);
+    let system_log = String::from_utf8_lossy(&adb.output_err(false)?.stdout).to_string();
+    let anr = format!("ANR in {}", package);
+    if let Some(line) = system_log.lines().find(|line| line.contains(&anr)) {
+        return Ok(LaunchOutcome::NotResponding(line.to_owned()));
+    }
+    Ok(match alive {
+        true => LaunchOutcome::Running,
+        false => LaunchOutcome::Exited,
+    })
+}
+
+/// Saves screenshot of the device screen in PNG format.
+/// Runs `adb exec-out screencap -p` command
+pub fn android_screenshot(sdk: &AndroidSdk, device: Option<&str>, output: &Path) -> Result<()> {
+    let mut adb = sdk.adb(device)?;
+    adb.args(["exec-out", "screencap", "-p"]);
+    let screenshot = adb.output_err(false)?;
+    std::fs::write(output, screenshot.stdout)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_device_time() {
+        assert_eq!(
+            parse_device_time("1660000000\r\n").unwrap(),
+            "1660000000.000"
+        );
+        assert_eq!(parse_device_time("date: unknown option"), None);
+    }
+}
@@ -0,0 +1,149 @@
+use super::{adb_connect, android_devices, wait_for_boot};
+use crate::{error::*, types::AndroidSdk};
+use std::{
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+/// Port adbd listens on in Waydroid and other Android containers.
+pub const CONTAINER_ADB_PORT: u16 = 5555;
+
+/// State of the Waydroid session reported by `waydroid status`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WaydroidStatus {
+    pub session_running: bool,
+    /// IP address of the container, known once the session is running.
+    pub ip_address: Option<String>,
+}
+
+impl WaydroidStatus {
+    /// Returns adb serial of the container: `<ip>:5555`.
+    pub fn serial(&self) -> Option<String> {
+        self.ip_address
+            .as_ref()
+            .map(|ip| format!("{}:{}", ip, CONTAINER_ADB_PORT))
+    }
+}
+
+/// Returns `true` if `waydroid` is found in `PATH`.
+pub fn is_waydroid_installed() -> bool {
+    Command::new("waydroid")
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_or(false, |status| status.success())
+}
+
+/// Returns state of the Waydroid session.
+/// Runs `waydroid status` command
+pub fn waydroid_status() -> Result<WaydroidStatus> {
+    let mut waydroid = Command::new("waydroid");
+    waydroid.arg("status");
+    let output = waydroid.output_err(false)?;
+    Ok(parse_waydroid_status(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Parses `waydroid status` output:
+///
+/// ```text
+/// Session:        RUNNING
+/// Container:      RUNNING
+/// Vendor type:    MAINLINE
+/// IP address:     192.168.240.112
+/// ```
+pub fn parse_waydroid_status(output: &str) -> WaydroidStatus {
+    let mut status = WaydroidStatus::default();
+    for line in output.lines() {
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue,
+        };
+        match key {
+            "Session" => status.session_running = value == "RUNNING",
+            "IP address" if !value.is_empty() && value != "UNKNOWN" => {
+                status.ip_address = Some(value.to_owned())
+            }
+            _ => {}
+        }
+    }
+    status
+}
+
+/// Starts the Waydroid session in background unless it's running and waits until the
+/// container gets its IP address. Returns adb serial of the container.
+pub fn start_waydroid_session(timeout: Duration) -> Result<String> {
+    let status = waydroid_status()?;
+    if let (true, Some(serial)) = (status.session_running, status.serial()) {
+        return Ok(serial);
+    }
+    if !status.session_running {
+        // `waydroid session start` keeps running while the session is alive
+        Command::new("waydroid")
+            .arg("session")
+            .arg("start")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+    }
+    let started = Instant::now();
+    while started.elapsed() < timeout {
+        let status = waydroid_status()?;
+        if let (true, Some(serial)) = (status.session_running, status.serial()) {
+            return Ok(serial);
+        }
+        std::thread::sleep(Duration::from_secs(2));
+    }
+    Err(AndroidError::ContainerNotReady("Waydroid session".to_owned()).into())
+}
+
+/// Connects adb to the Android container and waits until it finishes booting. The
+/// address gets the default adb port if it's missing. Returns adb serial of the
+/// container.
+pub fn connect_android_container(
+    sdk: &AndroidSdk,
+    address: &str,
+    timeout: Duration,
+) -> Result<String> {
+    let serial = if address.contains(':') {
+        address.to_owned()
+    } else {
+        format!("{}:{}", address, CONTAINER_ADB_PORT)
+    };
+    let connected = android_devices(sdk)?
+        .iter()
+        .any(|device| device.serial == serial && device.is_online());
+    if !connected {
+        // adbd of the container may start a few seconds after the network
+        let started = Instant::now();
+        while let Err(err) = adb_connect(sdk, &serial) {
+            if started.elapsed() > timeout {
+                return Err(err);
+            }
+            std::thread::sleep(Duration::from_secs(2));
+        }
+    }
+    wait_for_boot(sdk, &serial, None, timeout)
+        .map_err(|_| AndroidError::ContainerNotReady(serial.clone()))?;
+    Ok(serial)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_waydroid_status() {
+        let status = parse_waydroid_status(
+            "Session:\tRUNNING\nContainer:\tRUNNING\nVendor type:\tMAINLINE\n\
+             IP address:\t192.168.240.112\nSession user:\tuser(1000)\n",
+        );
+        assert!(status.session_running);
+        assert_eq!(status.serial().as_deref(), Some("192.168.240.112:5555"));
+        let status = parse_waydroid_status("Session:\tSTOPPED\nVendor type:\tMAINLINE\n");
+        assert_eq!(status, WaydroidStatus::default());
+    }
+}
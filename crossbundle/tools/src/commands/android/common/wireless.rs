@@ -0,0 +1,196 @@
+use super::android_devices;
+use crate::{error::*, types::AndroidSdk};
+use std::{
+    io::Write,
+    net::{TcpStream, ToSocketAddrs},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Time to wait for the TCP connection to the remembered device before skipping it.
+const RECONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Pairs the device with Wireless debugging enabled using the pairing code shown on the
+/// device. Runs `adb pair <address> <code>` command
+pub fn adb_pair(sdk: &AndroidSdk, address: &str, code: &str) -> Result<()> {
+    let mut adb = sdk.adb(None)?;
+    adb.arg("pair").arg(address).arg(code);
+    let output = adb.output_err(false)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !stdout.contains("Successfully paired") {
+        return Err(
+            AndroidError::AdbPairFailed(address.to_owned(), stdout.trim().to_owned()).into(),
+        );
+    }
+    Ok(())
+}
+
+/// Connects to the paired device over WiFi.
+/// Runs `adb connect <address>` command
+pub fn adb_connect(sdk: &AndroidSdk, address: &str) -> Result<()> {
+    let mut adb = sdk.adb(None)?;
+    adb.arg("connect").arg(address);
+    let output = adb.output_err(false)?;
+    // adb exits with zero code even if the connection failed
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !stdout.contains("connected to") {
+        return Err(
+            AndroidError::AdbConnectFailed(address.to_owned(), stdout.trim().to_owned()).into(),
+        );
+    }
+    Ok(())
+}
+
+/// Disconnects the device connected over WiFi.
+/// Runs `adb disconnect <address>` command
+pub fn adb_disconnect(sdk: &AndroidSdk, address: &str) -> Result<()> {
+    let mut adb = sdk.adb(None)?;
+    adb.arg("disconnect").arg(address);
+    adb.output_err(false)?;
+    Ok(())
+}
+
+/// Reconnects remembered WiFi devices if no device is online, or only the `device` if
+/// it's remembered and not connected. Returns addresses of the reconnected devices.
+/// Addresses that don't accept connections within a second are forgotten: the port of
+/// Wireless debugging changes every time it's enabled.
+pub fn reconnect_paired_devices(
+    sdk: &AndroidSdk,
+    store: &PairedDevices,
+    device: Option<&str>,
+) -> Result<Vec<String>> {
+    let addresses = store.addresses()?;
+    if addresses.is_empty() {
+        return Ok(Vec::new());
+    }
+    let online: Vec<String> = android_devices(sdk)?
+        .into_iter()
+        .filter(|device| device.is_online())
+        .map(|device| device.serial)
+        .collect();
+    let addresses: Vec<String> = match device {
+        Some(device) if online.iter().any(|serial| serial == device) => Vec::new(),
+        Some(device) => addresses.into_iter().filter(|a| a == device).collect(),
+        None if !online.is_empty() => Vec::new(),
+        None => addresses,
+    };
+    let mut reconnected = Vec::new();
+    for address in addresses {
+        if is_reachable(&address) && adb_connect(sdk, &address).is_ok() {
+            reconnected.push(address);
+        } else {
+            store.forget(&address)?;
+        }
+    }
+    Ok(reconnected)
+}
+
+/// Returns `true` if the address accepts TCP connections within [`RECONNECT_TIMEOUT`].
+/// `adb connect` waits much longer for unreachable hosts.
+fn is_reachable(address: &str) -> bool {
+    address
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .map_or(false, |addr| {
+            TcpStream::connect_timeout(&addr, RECONNECT_TIMEOUT).is_ok()
+        })
+}
+
+/// Addresses of devices connected over WiFi before, stored one per line.
+pub struct PairedDevices {
+    path: PathBuf,
+}
+
+impl PairedDevices {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_owned(),
+        }
+    }
+
+    /// Returns the store in the home directory.
+    pub fn from_home_dir() -> Result<Self> {
+        let home_dir = dirs::home_dir().ok_or(Error::HomeDirNotFound)?;
+        Ok(Self::new(
+            &home_dir.join(".crossbundle").join("paired_devices"),
+        ))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns remembered addresses.
+    pub fn addresses(&self) -> Result<Vec<String>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(std::fs::read_to_string(&self.path)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(ToOwned::to_owned)
+            .collect())
+    }
+
+    /// Remembers the address.
+    pub fn remember(&self, address: &str) -> Result<()> {
+        if self.addresses()?.iter().any(|a| a == address) {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", address)?;
+        Ok(())
+    }
+
+    /// Forgets the address.
+    pub fn forget(&self, address: &str) -> Result<()> {
+        let addresses: Vec<String> = self
+            .addresses()?
+            .into_iter()
+            .filter(|a| a != address)
+            .collect();
+        let mut content = addresses.join("\n");
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        if self.path.exists() {
+            std::fs::write(&self.path, content)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paired_devices() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = PairedDevices::new(&dir.path().join("paired_devices"));
+        assert!(store.addresses().unwrap().is_empty());
+        store.remember("192.168.1.5:37000").unwrap();
+        store.remember("192.168.1.6:41000").unwrap();
+        store.remember("192.168.1.5:37000").unwrap();
+        store.forget("192.168.1.6:41000").unwrap();
+        assert_eq!(store.addresses().unwrap(), vec!["192.168.1.5:37000"]);
+    }
+
+    #[test]
+    fn test_is_reachable() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        assert!(is_reachable(&address));
+        drop(listener);
+        assert!(!is_reachable(&address));
+        assert!(!is_reachable("not an address"));
+    }
+}
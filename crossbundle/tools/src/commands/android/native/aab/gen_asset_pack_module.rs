@@ -0,0 +1,127 @@
+use super::gen_feature_module::check_module_name;
+use crate::{commands::android::*, error::*, types::*};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Play Asset Delivery asset pack packed into the AAB. Asset packs contain only assets
+/// and are not limited by the 150MB size of the base module.
+///
+/// See more: https://developer.android.com/guide/playcore/asset-delivery
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AssetPack {
+    /// Asset pack name. Must start with a letter and contain only letters, digits and `_`.
+    pub name: String,
+    /// How the asset pack is delivered to the device.
+    #[serde(default)]
+    pub delivery: AssetPackDelivery,
+    /// Assets directory of the asset pack.
+    pub assets: PathBuf,
+}
+
+/// Delivery mode of the asset pack.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AssetPackDelivery {
+    /// Installed together with the application. Assets are available through the
+    /// `AssetManager` like the base module assets.
+    #[default]
+    InstallTime,
+    /// Downloaded automatically right after the application is installed.
+    FastFollow,
+    /// Downloaded when the application requests it with the Play Core library.
+    OnDemand,
+}
+
+impl AssetPack {
+    pub fn validate(&self) -> Result<()> {
+        if let Err(reason) = check_module_name(&self.name) {
+            return Err(
+                AndroidError::InvalidAssetPack(self.name.clone(), reason.to_owned()).into(),
+            );
+        }
+        if !self.assets.exists() {
+            return Err(Error::PathNotFound(self.assets.clone()));
+        }
+        Ok(())
+    }
+
+    /// Generates AndroidManifest.xml of the asset pack.
+    pub fn gen_manifest(&self, package: &str) -> String {
+        let delivery = match self.delivery {
+            AssetPackDelivery::InstallTime => "install-time",
+            AssetPackDelivery::FastFollow => "fast-follow",
+            AssetPackDelivery::OnDemand => "on-demand",
+        };
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<manifest xmlns:android="http://schemas.android.com/apk/res/android"
+    xmlns:dist="http://schemas.android.com/apk/distribution"
+    package="{package}"
+    split="{name}">
+    <dist:module dist:type="asset-pack">
+        <dist:delivery>
+            <dist:{delivery} />
+        </dist:delivery>
+        <dist:fusing dist:include="true" />
+    </dist:module>
+</manifest>
+"#,
+            package = package,
+            name = self.name,
+            delivery = delivery,
+        )
+    }
+}
+
+/// Generates ZIP module of the asset pack for
+/// [`gen_aab_from_modules`](super::gen_aab_from_modules).
+pub fn gen_asset_pack_module(
+    sdk: &AndroidSdk,
+    asset_pack: &AssetPack,
+    package: &str,
+    target_sdk_version: u32,
+    build_dir: &Path,
+) -> Result<PathBuf> {
+    asset_pack.validate()?;
+    let pack_dir = build_dir.join("asset_packs").join(&asset_pack.name);
+    std::fs::remove_dir_all(&pack_dir).ok();
+    std::fs::create_dir_all(&pack_dir)?;
+    let manifest_path = pack_dir.join("AndroidManifest.xml");
+    std::fs::write(&manifest_path, asset_pack.gen_manifest(package))?;
+
+    let apk_path = pack_dir.join(format!("{}.apk", asset_pack.name));
+    sdk.aapt2()?
+        .link_compiled_res(None, &apk_path, &manifest_path)
+        .assets(asset_pack.assets.clone())
+        .android_jar(sdk.android_jar(target_sdk_version)?)
+        .proto_format(true)
+        .run()?;
+
+    let extracted_apk_path = extract_archive(&apk_path, &pack_dir.join("extracted_apk_files"))?;
+    super::gen_zip_modules(&pack_dir, &asset_pack.name, &extracted_apk_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asset_pack_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let asset_pack = AssetPack {
+            name: "level_data".to_owned(),
+            delivery: AssetPackDelivery::FastFollow,
+            assets: dir.path().to_owned(),
+        };
+        asset_pack.validate().unwrap();
+        let manifest = asset_pack.gen_manifest("com.crossbow.game");
+        assert!(manifest.contains("split=\"level_data\""));
+        assert!(manifest.contains("<dist:fast-follow />"));
+
+        let invalid = AssetPack {
+            name: "1levels".to_owned(),
+            ..asset_pack
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -0,0 +1,274 @@
+use crate::{commands::android::*, error::*, types::*};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Package ID of the first feature module resources. Base module uses `0x7f`, the
+/// following features use `0x7d`, `0x7c` and so on.
+const FIRST_FEATURE_PACKAGE_ID: u8 = 0x7e;
+/// Lowest package ID available for features, `0x01` is used by the framework.
+const LAST_FEATURE_PACKAGE_ID: u8 = 0x02;
+
+/// Dynamic feature module packed into the AAB next to the base module.
+///
+/// See more: https://developer.android.com/guide/playcore/feature-delivery
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DynamicFeature {
+    /// Module name. Must start with a letter and contain only letters, digits and `_`.
+    pub name: String,
+    /// How the module is delivered to the device.
+    #[serde(default)]
+    pub delivery: FeatureDelivery,
+    /// Title shown to the user when the module is downloaded. Required for `on-demand`
+    /// delivery. Generated into the `<name>_feature_title` string of the base module.
+    pub title: Option<String>,
+    /// Assets directory of the module.
+    pub assets: Option<PathBuf>,
+    /// Include the module into the fused APK for devices without split APKs support.
+    #[serde(default = "default_fusing")]
+    pub fusing: bool,
+    /// Conditions of the `conditional` delivery.
+    #[serde(default)]
+    pub conditions: DeliveryConditions,
+}
+
+fn default_fusing() -> bool {
+    true
+}
+
+/// Delivery mode of the dynamic feature module.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FeatureDelivery {
+    /// Installed together with the application.
+    #[default]
+    InstallTime,
+    /// Installed at install time only on devices that match the conditions.
+    Conditional,
+    /// Downloaded when the application requests it with the Play Core library.
+    OnDemand,
+}
+
+/// Device conditions of the `conditional` delivery. All of them must match.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DeliveryConditions {
+    /// Minimum API level of the device.
+    pub min_sdk_version: Option<u32>,
+    /// Features the device must have, e.g. `android.hardware.vulkan.level`.
+    #[serde(default)]
+    pub device_features: Vec<String>,
+    /// Two-letter country codes of the users the module is delivered to.
+    #[serde(default)]
+    pub user_countries: Vec<String>,
+}
+
+impl DeliveryConditions {
+    pub fn is_empty(&self) -> bool {
+        self.min_sdk_version.is_none()
+            && self.device_features.is_empty()
+            && self.user_countries.is_empty()
+    }
+}
+
+impl DynamicFeature {
+    /// Name of the string resource with the module title.
+    pub fn title_resource_name(&self) -> String {
+        format!("{}_feature_title", self.name)
+    }
+
+    /// Checks the module name and that the delivery mode has everything it needs.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: &str| -> Result<()> {
+            Err(AndroidError::InvalidDynamicFeature(self.name.clone(), reason.to_owned()).into())
+        };
+        if let Err(reason) = check_module_name(&self.name) {
+            return invalid(reason);
+        }
+        match self.delivery {
+            FeatureDelivery::OnDemand if self.title.is_none() => {
+                invalid("`title` is required for on-demand delivery")
+            }
+            FeatureDelivery::Conditional if self.conditions.is_empty() => {
+                invalid("`conditions` are required for conditional delivery")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Generates AndroidManifest.xml of the module. `dist` namespace isn't supported by
+    /// [`AndroidManifest`](android_manifest::AndroidManifest), so the manifest is
+    /// written as text.
+    pub fn gen_manifest(&self, package: &str) -> String {
+        let title = match &self.title {
+            Some(_) => format!(" dist:title=\"@string/{}\"", self.title_resource_name()),
+            None => String::new(),
+        };
+        let delivery = match self.delivery {
+            FeatureDelivery::InstallTime => "            <dist:install-time />".to_owned(),
+            FeatureDelivery::OnDemand => "            <dist:on-demand />".to_owned(),
+            FeatureDelivery::Conditional => {
+                let mut conditions = String::new();
+                for feature in &self.conditions.device_features {
+                    conditions.push_str(&format!(
+                        "                    <dist:device-feature dist:name=\"{}\" />\n",
+                        feature
+                    ));
+                }
+                if let Some(min_sdk_version) = self.conditions.min_sdk_version {
+                    conditions.push_str(&format!(
+                        "                    <dist:min-sdk dist:value=\"{}\" />\n",
+                        min_sdk_version
+                    ));
+                }
+                if !self.conditions.user_countries.is_empty() {
+                    conditions.push_str("                    <dist:user-countries>\n");
+                    for country in &self.conditions.user_countries {
+                        conditions.push_str(&format!(
+                            "                        <dist:country dist:code=\"{}\" />\n",
+                            country.to_ascii_uppercase()
+                        ));
+                    }
+                    conditions.push_str("                    </dist:user-countries>\n");
+                }
+                format!(
+                    "            <dist:install-time>\n                <dist:conditions>\n{}                </dist:conditions>\n            </dist:install-time>",
+                    conditions
+                )
+            }
+        };
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<manifest xmlns:android="http://schemas.android.com/apk/res/android"
+    xmlns:dist="http://schemas.android.com/apk/distribution"
+    package="{package}"
+    split="{name}"
+    android:isFeatureSplit="true">
+    <dist:module{title}>
+        <dist:delivery>
+{delivery}
+        </dist:delivery>
+        <dist:fusing dist:include="{fusing}" />
+    </dist:module>
+    <application android:hasCode="false" />
+</manifest>
+"#,
+            package = package,
+            name = self.name,
+            title = title,
+            delivery = delivery,
+            fusing = self.fusing,
+        )
+    }
+}
+
+/// Returns resource package ID of the feature module by its index among the dynamic
+/// features, so resources of different features don't collide. Returns `None` if no
+/// IDs are left.
+pub fn feature_package_id(index: usize) -> Option<String> {
+    let id = (FIRST_FEATURE_PACKAGE_ID as usize).checked_sub(index)?;
+    (id >= LAST_FEATURE_PACKAGE_ID as usize).then(|| format!("{:#04x}", id))
+}
+
+/// Checks that the name can be used as AAB module name.
+pub(crate) fn check_module_name(name: &str) -> std::result::Result<(), &'static str> {
+    let mut chars = name.chars();
+    if !matches!(chars.next(), Some(c) if c.is_ascii_alphabetic())
+        || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err("name must start with a letter and contain only letters, digits and `_`");
+    }
+    if name == "base" {
+        return Err("`base` is reserved for the base module");
+    }
+    Ok(())
+}
+
+/// Generates ZIP module of the dynamic feature for
+/// [`gen_aab_from_modules`](super::gen_aab_from_modules). `base_apk` must be the base
+/// module linked in binary format, so the module can reference its title string.
+/// `index` is the index of the feature among the dynamic features of the AAB.
+pub fn gen_feature_module(
+    sdk: &AndroidSdk,
+    feature: &DynamicFeature,
+    index: usize,
+    package: &str,
+    target_sdk_version: u32,
+    base_apk: &Path,
+    build_dir: &Path,
+) -> Result<PathBuf> {
+    feature.validate()?;
+    let package_id = feature_package_id(index).ok_or_else(|| {
+        AndroidError::InvalidDynamicFeature(
+            feature.name.clone(),
+            "too many dynamic features, resource package IDs are exhausted".to_owned(),
+        )
+    })?;
+    let feature_dir = build_dir.join("features").join(&feature.name);
+    std::fs::remove_dir_all(&feature_dir).ok();
+    std::fs::create_dir_all(&feature_dir)?;
+    let manifest_path = feature_dir.join("AndroidManifest.xml");
+    std::fs::write(&manifest_path, feature.gen_manifest(package))?;
+
+    let apk_path = feature_dir.join(format!("{}.apk", feature.name));
+    let android_jar = sdk.android_jar(target_sdk_version)?;
+    let mut aapt2_link = Aapt2LinkCommand::new(&apk_path, &manifest_path);
+    aapt2_link
+        .proto_format(true)
+        .package_id(&package_id)
+        .allow_reserved_package_id(true)
+        .include(&android_jar)
+        .include(base_apk);
+    if let Some(assets) = &feature.assets {
+        aapt2_link.assets(assets);
+    }
+    aapt2_link.run(sdk)?;
+
+    let extracted_apk_path = extract_archive(&apk_path, &feature_dir.join("extracted_apk_files"))?;
+    super::gen_zip_modules(&feature_dir, &feature.name, &extracted_apk_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dynamic_feature_manifest() {
+        let feature = DynamicFeature {
+            name: "hd_textures".to_owned(),
+            delivery: FeatureDelivery::Conditional,
+            title: None,
+            assets: None,
+            fusing: true,
+            conditions: DeliveryConditions {
+                min_sdk_version: Some(28),
+                user_countries: vec!["us".to_owned()],
+                ..Default::default()
+            },
+        };
+        feature.validate().unwrap();
+        let manifest = feature.gen_manifest("com.crossbow.game");
+        assert!(manifest.contains("split=\"hd_textures\""));
+        assert!(manifest.contains("<dist:min-sdk dist:value=\"28\" />"));
+        assert!(manifest.contains("<dist:country dist:code=\"US\" />"));
+
+        let on_demand = DynamicFeature {
+            delivery: FeatureDelivery::OnDemand,
+            ..feature.clone()
+        };
+        assert!(on_demand.validate().is_err());
+        let base = DynamicFeature {
+            name: "base".to_owned(),
+            delivery: FeatureDelivery::InstallTime,
+            ..feature
+        };
+        assert!(base.validate().is_err());
+    }
+
+    #[test]
+    fn test_feature_package_id() {
+        assert_eq!(feature_package_id(0).unwrap(), "0x7e");
+        assert_eq!(feature_package_id(1).unwrap(), "0x7d");
+        assert_eq!(feature_package_id(0x7c).unwrap(), "0x02");
+        assert_eq!(feature_package_id(0x7d), None);
+        assert_eq!(feature_package_id(usize::MAX), None);
+    }
+}
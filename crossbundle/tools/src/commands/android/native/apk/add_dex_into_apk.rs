@@ -0,0 +1,17 @@
+use crate::{commands::android::dex_files, error::*, types::AndroidSdk};
+use std::path::Path;
+
+/// Adds DEX files compiled by [`D8`](crate::commands::android::D8) into the root of the
+/// APK. Uses `aapt` tool
+pub fn add_dex_into_apk(sdk: &AndroidSdk, apk_path: &Path, dex_dir: &Path) -> Result<()> {
+    let apk_dir = apk_path.parent().unwrap();
+    for dex_file in dex_files(dex_dir)? {
+        let file_name = dex_file.file_name().unwrap();
+        std::fs::copy(&dex_file, apk_dir.join(file_name))?;
+        // `aapt a[dd] [-v] file.{zip,jar,apk} file1 [file2 ...]`
+        let mut aapt = sdk.build_tool(bin!("aapt"), Some(apk_dir))?;
+        aapt.arg("add").arg(apk_path).arg(file_name);
+        aapt.output_err(true)?;
+    }
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use crate::{
+    commands::android::SymbolStripper,
     error::*,
     types::{AndroidNdk, AndroidSdk, AndroidTarget, IntoRustTriple, Profile},
 };
@@ -8,7 +9,8 @@ use std::{
     path::{Path, PathBuf},
 };
 
-/// Adds given lib and all reletad libs into APK.
+/// Adds given lib and all reletad libs into APK. Libraries are stripped before adding
+/// if `stripper` is specified.
 /// Uses `readelf`, `aapt` tools
 pub fn add_libs_into_apk(
     sdk: &AndroidSdk,
@@ -20,6 +22,7 @@ pub fn add_libs_into_apk(
     min_sdk_version: u32,
     build_dir: &Path,
     target_dir: &Path,
+    stripper: Option<&SymbolStripper>,
 ) -> Result<PathBuf> {
     // Get list of android system libs (https://developer.android.com/ndk/guides/stable_apis)
     let mut system_libs = Vec::new();
@@ -47,7 +50,7 @@ pub fn add_libs_into_apk(
     let abi = build_target.android_abi();
     let out_dir = build_dir.join("libs").join(profile).join(abi);
     for (_lib_name, lib_path) in needed_libs {
-        aapt_add_lib(sdk, apk_path, &lib_path, &out_dir, abi)?;
+        aapt_add_lib(sdk, apk_path, &lib_path, &out_dir, abi, stripper)?;
     }
     Ok(out_dir)
 }
@@ -59,6 +62,7 @@ fn aapt_add_lib(
     lib_path: &Path,
     out_dir: &Path,
     abi: &str,
+    stripper: Option<&SymbolStripper>,
 ) -> Result<()> {
     if !lib_path.exists() {
         return Err(Error::PathNotFound(lib_path.to_owned()));
@@ -69,6 +73,9 @@ fn aapt_add_lib(
     let native_lib_path = apk_path.parent().unwrap().join("lib").join(abi);
     std::fs::create_dir_all(&native_lib_path)?;
     std::fs::copy(lib_path, &native_lib_path.join(file_name))?;
+    if let Some(stripper) = stripper {
+        stripper.strip(&native_lib_path.join(file_name))?;
+    }
     // `aapt a[dd] [-v] file.{zip,jar,apk} file1 [file2 ...]`
     // Add specified files to Zip-compatible archive
     let mut aapt = sdk.build_tool(bin!("aapt"), Some(apk_path.parent().unwrap()))?;
@@ -1,22 +1,25 @@
+use super::ZipAlign;
 use crate::{error::*, types::*};
 use std::path::{Path, PathBuf};
 
-/// Aligns APK on 4-byte memory boundary.
-/// Uses `zipalign` build tools
+/// Aligns APK on 4-byte memory boundary and page-aligns uncompressed native libraries.
+/// If `page_size` is specified, native libraries will be aligned on `page_size` KiB
+/// boundaries (e.g. 16KB pages). Uses `zipalign` build tools
 pub fn align_apk(
     sdk: &AndroidSdk,
     unaligned_apk_path: &Path,
     package_name: &str,
     build_dir: &Path,
+    page_size: Option<u32>,
 ) -> Result<PathBuf> {
     let unsigned_apk_path = build_dir.join(format!("{}.apk", package_name));
-    let mut zipalign = sdk.build_tool(bin!("zipalign"), None)?;
+    let mut zipalign = ZipAlign::new(unaligned_apk_path, &unsigned_apk_path);
     zipalign
-        .arg("-f")
-        .arg("-v")
-        .arg("4")
-        .arg(unaligned_apk_path)
-        .arg(&unsigned_apk_path);
-    zipalign.output_err(true)?;
-    Ok(unsigned_apk_path)
+        .force(true)
+        .verbose(true)
+        .page_align_shared_libs(true);
+    if let Some(page_size) = page_size {
+        zipalign.page_size(page_size);
+    }
+    zipalign.run(sdk)
 }
@@ -1,10 +1,14 @@
-use crate::{error::*, types::AndroidSdk};
+use crate::{commands::android::AdbClient, error::*, types::AndroidSdk};
 use std::path::Path;
 
-/// Installs given APK in emulator or connected device.
-/// Runs `adb install -r ...` command
-pub fn install_apk(sdk: &AndroidSdk, apk_path: &Path) -> Result<()> {
-    let mut adb = sdk.platform_tool(bin!("adb"))?;
+/// Installs given APK in emulator or connected device. If `device` is not specified,
+/// the only connected device will be used.
+/// Runs `adb [-s <serial>] install -r ...` command
+pub fn install_apk(sdk: &AndroidSdk, apk_path: &Path, device: Option<&str>) -> Result<()> {
+    if let Some(client) = AdbClient::preferred(sdk)? {
+        return client.install(device, apk_path);
+    }
+    let mut adb = sdk.adb(device)?;
     adb.arg("install").arg("-r").arg(apk_path);
     adb.output_err(true)?;
     Ok(())
@@ -1,11 +1,15 @@
+pub mod add_dex_into_apk;
 pub mod add_libs_into_apk;
 pub mod align_apk;
 pub mod gen_unaligned_apk;
 pub mod install_apk;
 pub mod sign_apk;
+pub mod zipalign;
 
+pub use add_dex_into_apk::*;
 pub use add_libs_into_apk::*;
 pub use align_apk::*;
 pub use gen_unaligned_apk::*;
 pub use install_apk::*;
 pub use sign_apk::*;
+pub use zipalign::*;
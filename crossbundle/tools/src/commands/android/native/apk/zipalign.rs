@@ -0,0 +1,134 @@
+use crate::{error::*, types::AndroidSdk};
+use std::path::{Path, PathBuf};
+
+/// Default zip alignment in bytes. All uncompressed data in the archive will be aligned
+/// relative to the start of the file.
+pub const DEFAULT_ZIP_ALIGNMENT: u32 = 4;
+
+/// Page size in KiB used to align uncompressed native libraries on devices with 16KB
+/// memory pages. Supported by `zipalign` since build-tools 35.
+pub const PAGE_SIZE_16KB: u32 = 16;
+
+/// ## Zipalign
+/// `zipalign` is a zip archive alignment tool. It ensures that all uncompressed files in
+/// the archive are aligned relative to the start of the file. This allows those files to
+/// be accessed directly via `mmap`, eliminating the need to copy this data in RAM and
+/// reducing your app's memory usage.
+///
+/// `zipalign` should be used to optimize your APK file before distributing it to
+/// end-users. If you sign your APK with `apksigner`, `zipalign` must be performed before
+/// the APK file has been signed.
+///
+/// See more: https://developer.android.com/studio/command-line/zipalign
+#[derive(Clone, Debug)]
+pub struct ZipAlign {
+    input_zip: PathBuf,
+    output_zip: Option<PathBuf>,
+    alignment: u32,
+    check: bool,
+    force: bool,
+    page_align_shared_libs: bool,
+    page_size: Option<u32>,
+    recompress: bool,
+    verbose: bool,
+}
+
+impl ZipAlign {
+    /// Aligns `input_zip` and writes the result into `output_zip`.
+    pub fn new(input_zip: &Path, output_zip: &Path) -> Self {
+        Self {
+            input_zip: input_zip.to_owned(),
+            output_zip: Some(output_zip.to_owned()),
+            alignment: DEFAULT_ZIP_ALIGNMENT,
+            check: false,
+            force: false,
+            page_align_shared_libs: false,
+            page_size: None,
+            recompress: false,
+            verbose: false,
+        }
+    }
+
+    /// Only verifies the alignment of `input_zip` without writing anything.
+    pub fn check(input_zip: &Path) -> Self {
+        Self {
+            output_zip: None,
+            check: true,
+            ..Self::new(input_zip, input_zip)
+        }
+    }
+
+    /// Alignment in bytes, e.g. `4` provides 32-bit alignment.
+    pub fn alignment(&mut self, alignment: u32) -> &mut Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Overwrite existing output file.
+    pub fn force(&mut self, force: bool) -> &mut Self {
+        self.force = force;
+        self
+    }
+
+    /// Page-align uncompressed `.so` files.
+    pub fn page_align_shared_libs(&mut self, page_align_shared_libs: bool) -> &mut Self {
+        self.page_align_shared_libs = page_align_shared_libs;
+        self
+    }
+
+    /// Page size in KiB used to align uncompressed `.so` files. Implies
+    /// `page_align_shared_libs`. Use [`PAGE_SIZE_16KB`] to support devices with 16KB
+    /// memory pages.
+    pub fn page_size(&mut self, page_size: u32) -> &mut Self {
+        self.page_align_shared_libs = true;
+        self.page_size = Some(page_size);
+        self
+    }
+
+    /// Recompresses using Zopfli.
+    pub fn recompress(&mut self, recompress: bool) -> &mut Self {
+        self.recompress = recompress;
+        self
+    }
+
+    /// Verbose output.
+    pub fn verbose(&mut self, verbose: bool) -> &mut Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Runs `zipalign` from the build tools of given SDK. Returns path to the aligned
+    /// archive, or to the checked archive in check mode.
+    pub fn run(&self, sdk: &AndroidSdk) -> Result<PathBuf> {
+        let mut zipalign = sdk.build_tool(bin!("zipalign"), None)?;
+        if self.check {
+            zipalign.arg("-c");
+        }
+        if self.force {
+            zipalign.arg("-f");
+        }
+        if self.page_align_shared_libs {
+            zipalign.arg("-p");
+        }
+        if let Some(page_size) = self.page_size {
+            zipalign.arg("-P").arg(page_size.to_string());
+        }
+        if self.recompress {
+            zipalign.arg("-z");
+        }
+        if self.verbose {
+            zipalign.arg("-v");
+        }
+        zipalign
+            .arg(self.alignment.to_string())
+            .arg(&self.input_zip);
+        if let Some(output_zip) = &self.output_zip {
+            zipalign.arg(output_zip);
+        }
+        zipalign.output_err(true)?;
+        Ok(self
+            .output_zip
+            .clone()
+            .unwrap_or_else(|| self.input_zip.clone()))
+    }
+}
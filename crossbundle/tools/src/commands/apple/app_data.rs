@@ -0,0 +1,123 @@
+use super::terminate_simulator_app;
+use crate::{
+    commands::{COVERAGE_DIR, PROFRAW_FILE_PATTERN},
+    error::*,
+};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Returns path of the application data container on the simulator.
+/// Runs `xcrun simctl get_app_container <udid> <bundle id> data`
+pub fn simulator_app_container(udid: &str, bundle_id: &str) -> Result<PathBuf> {
+    let mut simctl = Command::new("xcrun");
+    simctl.args(["simctl", "get_app_container", udid, bundle_id, "data"]);
+    let output = simctl.output_err(true)?;
+    let container = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    if !container.exists() {
+        return Err(Error::PathNotFound(container));
+    }
+    Ok(container)
+}
+
+/// Returns the directory of coverage profiles in the application data container on
+/// the simulator: `Documents/coverage`.
+pub fn simulator_coverage_dir(udid: &str, bundle_id: &str) -> Result<PathBuf> {
+    Ok(simulator_app_container(udid, bundle_id)?
+        .join("Documents")
+        .join(COVERAGE_DIR))
+}
+
+/// Makes applications launched by `simctl` from this process write coverage profiles
+/// into [`simulator_coverage_dir`]. Profiles are written in continuous mode, so they are
+/// up to date even if the application is killed. Returns the directory.
+pub fn enable_simulator_coverage(udid: &str, bundle_id: &str) -> Result<PathBuf> {
+    let coverage_dir = simulator_coverage_dir(udid, bundle_id)?;
+    std::fs::create_dir_all(&coverage_dir)?;
+    // simctl passes variables with `SIMCTL_CHILD_` prefix to the launched application
+    std::env::set_var(
+        "SIMCTL_CHILD_LLVM_PROFILE_FILE",
+        coverage_dir.join(PROFRAW_FILE_PATTERN),
+    );
+    Ok(coverage_dir)
+}
+
+/// Deletes contents of the application data container on the simulator: documents,
+/// preferences, caches and temporary files. The application is terminated first.
+pub fn clear_simulator_app_data(udid: &str, bundle_id: &str) -> Result<()> {
+    terminate_simulator_app(udid, bundle_id)?;
+    let container = simulator_app_container(udid, bundle_id)?;
+    for entry in std::fs::read_dir(&container)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            std::fs::remove_dir_all(&path)?;
+            // Applications expect standard directories of the container to exist
+            std::fs::create_dir(&path)?;
+        } else {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Saves the application data container of the simulator into the tar archive. Caches
+/// and temporary files are not included.
+/// Runs `tar -cf <output> -C <container> .`
+pub fn backup_simulator_app_data(udid: &str, bundle_id: &str, output: &Path) -> Result<()> {
+    let container = simulator_app_container(udid, bundle_id)?;
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut tar = Command::new("tar");
+    tar.arg("-cf")
+        .arg(output)
+        .args(["--exclude", "./Library/Caches", "--exclude", "./tmp", "-C"])
+        .arg(container)
+        .arg(".");
+    tar.output_err(true)?;
+    Ok(())
+}
+
+/// Extracts the tar archive created by [`backup_simulator_app_data`] into the
+/// application data container. Existing files are overwritten, other files are kept.
+/// The application is terminated first.
+/// Runs `tar -xf <input> -C <container>`
+pub fn restore_simulator_app_data(udid: &str, bundle_id: &str, input: &Path) -> Result<()> {
+    if !input.exists() {
+        return Err(Error::PathNotFound(input.to_owned()));
+    }
+    terminate_simulator_app(udid, bundle_id)?;
+    let container = simulator_app_container(udid, bundle_id)?;
+    let mut tar = Command::new("tar");
+    tar.arg("-xf").arg(input).arg("-C").arg(container);
+    tar.output_err(true)?;
+    Ok(())
+}
+
+/// Grants or revokes access to the privacy-sensitive service like `camera`, `photos`,
+/// `location` or `microphone` on the simulator.
+/// Runs `xcrun simctl privacy <udid> grant|revoke <service> <bundle id>`
+pub fn set_simulator_privacy(
+    udid: &str,
+    bundle_id: &str,
+    service: &str,
+    grant: bool,
+) -> Result<()> {
+    let mut simctl = Command::new("xcrun");
+    simctl
+        .args(["simctl", "privacy", udid])
+        .arg(if grant { "grant" } else { "revoke" })
+        .args([service, bundle_id]);
+    simctl.output_err(true)?;
+    Ok(())
+}
+
+/// Opens the URL on the simulator.
+/// Runs `xcrun simctl openurl <udid> <url>`
+pub fn open_simulator_url(udid: &str, url: &str) -> Result<()> {
+    let mut simctl = Command::new("xcrun");
+    simctl.args(["simctl", "openurl", udid, url]);
+    simctl.output_err(true)?;
+    Ok(())
+}
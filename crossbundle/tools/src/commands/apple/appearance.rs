@@ -0,0 +1,95 @@
+use crate::{commands::DeviceAppearance, error::*};
+use std::process::Command;
+
+/// Applies font scale and dark mode on the simulator. Locale is passed to the
+/// application with [`simulator_launch_args`].
+pub fn apply_simulator_appearance(udid: &str, appearance: &DeviceAppearance) -> Result<()> {
+    if let Some(font_scale) = appearance.font_scale {
+        // Runs `xcrun simctl ui <udid> content_size <category>`
+        let mut simctl = Command::new("xcrun");
+        simctl
+            .args(["simctl", "ui", udid, "content_size"])
+            .arg(content_size_category(font_scale));
+        simctl.output_err(true)?;
+    }
+    if let Some(dark_mode) = appearance.dark_mode {
+        // Runs `xcrun simctl ui <udid> appearance dark|light`
+        let mut simctl = Command::new("xcrun");
+        simctl
+            .args(["simctl", "ui", udid, "appearance"])
+            .arg(if dark_mode { "dark" } else { "light" });
+        simctl.output_err(true)?;
+    }
+    Ok(())
+}
+
+/// Returns Dynamic Type content size category closest to the font scale.
+pub fn content_size_category(font_scale: f32) -> &'static str {
+    match font_scale {
+        s if s < 0.85 => "extra-small",
+        s if s < 0.95 => "small",
+        s if s < 1.05 => "large",
+        s if s < 1.15 => "extra-large",
+        s if s < 1.25 => "extra-extra-large",
+        s if s < 1.45 => "extra-extra-extra-large",
+        s if s < 1.75 => "accessibility-medium",
+        s if s < 2.25 => "accessibility-large",
+        _ => "accessibility-extra-large",
+    }
+}
+
+/// Returns launch arguments that override the application language and region.
+pub fn simulator_launch_args(appearance: &DeviceAppearance) -> Vec<String> {
+    match &appearance.locale {
+        Some(locale) => {
+            let language = locale
+                .split(|c| c == '-' || c == '_')
+                .next()
+                .unwrap_or(locale);
+            vec![
+                "-AppleLanguages".to_owned(),
+                format!("({})", language),
+                "-AppleLocale".to_owned(),
+                locale.replace('-', "_"),
+            ]
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Launches the installed application on the simulator, terminating the running
+/// instance first.
+/// Runs `xcrun simctl launch --terminate-running-process <udid> <bundle id> [args]`
+pub fn launch_simulator_app(udid: &str, bundle_id: &str, args: &[String]) -> Result<()> {
+    let mut simctl = Command::new("xcrun");
+    simctl
+        .args([
+            "simctl",
+            "launch",
+            "--terminate-running-process",
+            udid,
+            bundle_id,
+        ])
+        .args(args);
+    simctl.output_err(true)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulator_appearance() {
+        assert_eq!(content_size_category(1.0), "large");
+        assert_eq!(content_size_category(1.3), "extra-extra-extra-large");
+        let appearance = DeviceAppearance {
+            locale: Some("pt-BR".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            simulator_launch_args(&appearance),
+            ["-AppleLanguages", "(pt)", "-AppleLocale", "pt_BR"]
+        );
+    }
+}
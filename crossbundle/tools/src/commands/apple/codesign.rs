@@ -1,5 +1,6 @@
 use crate::error::*;
 use std::{
+    ffi::OsString,
     path::{Path, PathBuf},
     process::Command,
 };
@@ -7,6 +8,122 @@ use std::{
 const XCODE_PATH: &str = "/Applications/Xcode.app/Contents/Developer/Toolchains/XcodeDefault.xctoolchain/usr/bin/codesign_allocate";
 const BIN_PATH: &str = "/usr/bin/codesign_allocate";
 
+/// ## Codesign
+/// Signs the binary or the bundle with the identity from the keychain. Nested code of
+/// the bundle is signed too with `deep`. Ad-hoc signature is used if the identity is
+/// not specified, it's enough for simulators only.
+///
+/// See more: `man codesign`
+#[derive(Clone, Debug)]
+pub struct Codesign {
+    path: PathBuf,
+    identity: Option<String>,
+    entitlements: Option<PathBuf>,
+    force: bool,
+    deep: bool,
+    hardened_runtime: bool,
+    timestamp: bool,
+}
+
+impl Codesign {
+    /// Signs the binary or the bundle at `path`.
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_owned(),
+            identity: None,
+            entitlements: None,
+            force: false,
+            deep: false,
+            hardened_runtime: false,
+            timestamp: false,
+        }
+    }
+
+    /// Name or SHA-1 hash of the signing identity, e.g. `Apple Development: Name (ID)`.
+    pub fn identity(&mut self, identity: &str) -> &mut Self {
+        self.identity = Some(identity.to_owned());
+        self
+    }
+
+    /// Entitlements plist embedded into the signature.
+    pub fn entitlements(&mut self, entitlements: &Path) -> &mut Self {
+        self.entitlements = Some(entitlements.to_owned());
+        self
+    }
+
+    /// Replaces the existing signature.
+    pub fn force(&mut self, force: bool) -> &mut Self {
+        self.force = force;
+        self
+    }
+
+    /// Also signs frameworks, plugins and helpers nested in the bundle.
+    pub fn deep(&mut self, deep: bool) -> &mut Self {
+        self.deep = deep;
+        self
+    }
+
+    /// Enables the hardened runtime. Required to notarize macOS applications.
+    pub fn hardened_runtime(&mut self, hardened_runtime: bool) -> &mut Self {
+        self.hardened_runtime = hardened_runtime;
+        self
+    }
+
+    /// Requests a secure timestamp from Apple. Required to notarize macOS applications,
+    /// needs network access.
+    pub fn timestamp(&mut self, timestamp: bool) -> &mut Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec!["--sign".into()];
+        args.push(self.identity.as_deref().unwrap_or("-").into());
+        if self.force {
+            args.push("--force".into());
+        }
+        if self.deep {
+            args.push("--deep".into());
+        }
+        if self.hardened_runtime {
+            args.push("--options".into());
+            args.push("runtime".into());
+        }
+        match self.timestamp {
+            true => args.push("--timestamp".into()),
+            false => args.push("--timestamp=none".into()),
+        }
+        if let Some(entitlements) = &self.entitlements {
+            args.push("--entitlements".into());
+            args.push(entitlements.clone().into());
+        }
+        args.push(self.path.clone().into());
+        args
+    }
+
+    /// Runs `codesign --sign ...` command.
+    pub fn run(&self) -> Result<()> {
+        if !self.path.exists() {
+            return Err(AppleError::CodesignFailed("Item not found".to_owned()).into());
+        }
+        let mut codesign_allocate_path = XCODE_PATH;
+        if !Path::new(codesign_allocate_path).exists() {
+            codesign_allocate_path = BIN_PATH;
+            if !Path::new(codesign_allocate_path).exists() {
+                return Err(AppleError::CodesignAllocateNotFound.into());
+            }
+        }
+        let mut cmd = Command::new("codesign");
+        cmd.env("CODESIGN_ALLOCATE", codesign_allocate_path);
+        cmd.args(self.args());
+        let output = cmd.output()?;
+        if !output.status.success() {
+            return Err(AppleError::CodesignFailed(codesign_error(&output.stderr)).into());
+        }
+        Ok(())
+    }
+}
+
 /// Signs app. Runs `codesign ...` command.
 pub fn codesign(
     item_path: &Path,
@@ -14,40 +131,148 @@ pub fn codesign(
     sign_identity: Option<String>,
     entitlements: Option<PathBuf>,
 ) -> Result<()> {
-    if !item_path.exists() {
-        return Err(AppleError::CodesignFailed("Item not found".to_owned()).into());
-    }
-    let mut codesign_allocate_path = XCODE_PATH;
-    if !Path::new(codesign_allocate_path).exists() {
-        codesign_allocate_path = BIN_PATH;
-        if !Path::new(codesign_allocate_path).exists() {
-            return Err(AppleError::CodesignAllocateNotFound.into());
-        }
-    }
-    let mut cmd = Command::new("codesign");
-    cmd.env("CODESIGN_ALLOCATE", codesign_allocate_path);
-    if force {
-        cmd.arg("--force");
-    }
+    let mut codesign = Codesign::new(item_path);
+    codesign.force(force);
     if let Some(sign_identity) = sign_identity {
-        cmd.args(["--sign", &sign_identity]);
-    } else {
-        cmd.args(["--sign", "-"]);
+        codesign.identity(&sign_identity);
     }
-    cmd.arg("--timestamp=none");
     if let Some(entitlements) = entitlements {
-        cmd.args(["--entitlements", entitlements.to_str().unwrap()]);
+        codesign.entitlements(&entitlements);
+    }
+    codesign.run()
+}
+
+/// Checks that the signature of the binary or the bundle is valid and the sealed
+/// resources were not modified. Runs `codesign --verify --strict ...` command.
+pub fn verify_codesign(item_path: &Path, deep: bool) -> Result<()> {
+    let mut cmd = Command::new("codesign");
+    cmd.args(["--verify", "--strict", "--verbose=2"]);
+    if deep {
+        cmd.arg("--deep");
     }
     cmd.arg(item_path);
     let output = cmd.output()?;
     if !output.status.success() {
-        return Err(AppleError::CodesignFailed(
-            String::from_utf8(output.stderr)
-                .unwrap()
-                .replace("error: ", "")
-                .replace('\n', ""),
-        )
-        .into());
+        return Err(AppleError::CodesignVerificationFailed(codesign_error(&output.stderr)).into());
     }
     Ok(())
 }
+
+/// Valid codesigning identity from the keychain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodesignIdentity {
+    /// SHA-1 hash of the certificate.
+    pub hash: String,
+    /// Common name like `Apple Development: Jane Doe (ABCDE12345)`.
+    pub name: String,
+}
+
+impl CodesignIdentity {
+    /// The identity signs builds for the App Store, TestFlight or ad-hoc distribution.
+    pub fn is_distribution(&self) -> bool {
+        self.name.starts_with("Apple Distribution") || self.name.starts_with("iPhone Distribution")
+    }
+
+    /// The identity signs development builds for devices registered in the team.
+    pub fn is_development(&self) -> bool {
+        self.name.starts_with("Apple Development") || self.name.starts_with("iPhone Developer")
+    }
+}
+
+/// Returns valid codesigning identities of the keychain.
+/// Runs `security find-identity -v -p codesigning` command.
+pub fn find_codesign_identities() -> Result<Vec<CodesignIdentity>> {
+    let mut security = Command::new("security");
+    security.args(["find-identity", "-v", "-p", "codesigning"]);
+    let output = security.output_err(false)?;
+    Ok(parse_find_identity(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Parses lines like `1) 0123...CDEF "Apple Development: Jane Doe (ABCDE12345)"`.
+pub fn parse_find_identity(output: &str) -> Vec<CodesignIdentity> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (_, line) = line.trim().split_once(") ")?;
+            let (hash, name) = line.split_once(' ')?;
+            let name = name.trim().strip_prefix('"')?.strip_suffix('"')?;
+            Some(CodesignIdentity {
+                hash: hash.to_owned(),
+                name: name.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Selects the identity for device builds: a distribution one for release builds if
+/// there is one, otherwise a development one.
+pub fn select_codesign_identity(
+    identities: &[CodesignIdentity],
+    release: bool,
+) -> Option<&CodesignIdentity> {
+    let distribution = identities
+        .iter()
+        .find(|identity| identity.is_distribution());
+    let development = identities.iter().find(|identity| identity.is_development());
+    match release {
+        true => distribution.or(development),
+        false => development.or(distribution),
+    }
+}
+
+fn codesign_error(stderr: &[u8]) -> String {
+    String::from_utf8_lossy(stderr)
+        .replace("error: ", "")
+        .replace('\n', "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codesign() {
+        let mut codesign = Codesign::new(Path::new("Game.app"));
+        codesign
+            .identity("Apple Distribution: Team (ABCDE12345)")
+            .entitlements(Path::new("Game.entitlements"))
+            .deep(true)
+            .hardened_runtime(true)
+            .timestamp(true);
+        let expected: Vec<OsString> = [
+            "--sign",
+            "Apple Distribution: Team (ABCDE12345)",
+            "--deep",
+            "--options",
+            "runtime",
+            "--timestamp",
+            "--entitlements",
+            "Game.entitlements",
+            "Game.app",
+        ]
+        .iter()
+        .map(OsString::from)
+        .collect();
+        assert_eq!(codesign.args(), expected);
+
+        let output = r#"  1) 0123456789ABCDEF0123456789ABCDEF01234567 "Apple Development: Jane Doe (ABCDE12345)"
+  2) 89ABCDEF0123456789ABCDEF0123456789ABCDEF "Apple Distribution: Team (FGHIJ67890)"
+     2 valid identities found"#;
+        let identities = parse_find_identity(output);
+        assert_eq!(identities.len(), 2);
+        assert_eq!(
+            identities[0].hash,
+            "0123456789ABCDEF0123456789ABCDEF01234567"
+        );
+        assert_eq!(
+            select_codesign_identity(&identities, false),
+            Some(&identities[0])
+        );
+        assert_eq!(
+            select_codesign_identity(&identities, true),
+            Some(&identities[1])
+        );
+    }
+}
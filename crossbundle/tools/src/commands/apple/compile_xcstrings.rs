@@ -0,0 +1,223 @@
+use crate::error::*;
+use apple_bundle::plist;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// String catalog edited in Xcode 15+, a JSON file with strings of all languages.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StringCatalog {
+    pub source_language: String,
+    #[serde(default)]
+    pub strings: BTreeMap<String, CatalogEntry>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    /// `false` if the string must stay in the source language.
+    #[serde(default = "default_should_translate")]
+    pub should_translate: bool,
+    #[serde(default)]
+    pub localizations: BTreeMap<String, CatalogLocalization>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogLocalization {
+    pub string_unit: Option<StringUnit>,
+    /// Variations keyed by kind like `plural` or `device`, then by case like `one`.
+    #[serde(default)]
+    pub variations: BTreeMap<String, BTreeMap<String, CatalogLocalization>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StringUnit {
+    /// State like `translated`, `needs_review` or `new`.
+    pub state: String,
+    pub value: String,
+}
+
+fn default_should_translate() -> bool {
+    true
+}
+
+/// Compiles `*.xcstrings` string catalogs in the root of the resources directory into
+/// `<language>.lproj/<table>.strings` and `.stringsdict` files like Xcode does, and
+/// removes the catalogs. The table name is the catalog file name, e.g. `Localizable`.
+/// Returns the number of compiled catalogs.
+pub fn compile_xcstrings_dir(res_dir: &Path) -> Result<usize> {
+    let mut catalogs: Vec<PathBuf> = std::fs::read_dir(res_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()?;
+    catalogs.retain(|path| path.extension().map_or(false, |ext| ext == "xcstrings"));
+    catalogs.sort();
+    for catalog in &catalogs {
+        compile_xcstrings(catalog, res_dir)?;
+        std::fs::remove_file(catalog)?;
+    }
+    Ok(catalogs.len())
+}
+
+/// Compiles the string catalog into `<language>.lproj` directories of `out_dir`.
+pub fn compile_xcstrings(catalog_path: &Path, out_dir: &Path) -> Result<()> {
+    let invalid =
+        |reason: String| AppleError::InvalidStringCatalog(catalog_path.to_owned(), reason);
+    let catalog: StringCatalog = serde_json::from_str(&std::fs::read_to_string(catalog_path)?)
+        .map_err(|error| invalid(error.to_string()))?;
+    let table = catalog_path.file_stem().unwrap().to_string_lossy();
+    let mut strings: BTreeMap<&str, String> = BTreeMap::new();
+    let mut plurals: BTreeMap<&str, plist::Dictionary> = BTreeMap::new();
+    for (key, entry) in &catalog.strings {
+        for (language, localization) in &entry.localizations {
+            if !entry.should_translate && *language != catalog.source_language {
+                continue;
+            }
+            if let Some(unit) = &localization.string_unit {
+                let content = strings.entry(language.as_str()).or_default();
+                content.push_str(&format!(
+                    "\"{}\" = \"{}\";\n",
+                    escape_strings_value(key),
+                    escape_strings_value(&unit.value)
+                ));
+                continue;
+            }
+            for kind in localization.variations.keys() {
+                if kind != "plural" {
+                    return Err(invalid(format!(
+                        "`{}` variations of `{}` are not supported",
+                        kind, key
+                    ))
+                    .into());
+                }
+            }
+            if let Some(cases) = localization.variations.get("plural") {
+                let rule = plural_rule(cases).ok_or_else(|| {
+                    invalid(format!(
+                        "plural variations of `{}` have no `other` case",
+                        key
+                    ))
+                })?;
+                plurals
+                    .entry(language.as_str())
+                    .or_default()
+                    .insert(key.clone(), rule.into());
+            }
+        }
+    }
+    for (language, content) in strings {
+        let lproj = out_dir.join(format!("{}.lproj", language));
+        std::fs::create_dir_all(&lproj)?;
+        std::fs::write(lproj.join(format!("{}.strings", table)), content)?;
+    }
+    for (language, dictionary) in plurals {
+        let lproj = out_dir.join(format!("{}.lproj", language));
+        std::fs::create_dir_all(&lproj)?;
+        plist::Value::Dictionary(dictionary)
+            .to_file_xml(lproj.join(format!("{}.stringsdict", table)))?;
+    }
+    Ok(())
+}
+
+/// Returns `.stringsdict` rule of the plural cases like `one` and `other`.
+fn plural_rule(cases: &BTreeMap<String, CatalogLocalization>) -> Option<plist::Dictionary> {
+    let other = cases.get("other")?.string_unit.as_ref()?;
+    let mut variable = plist::Dictionary::new();
+    variable.insert(
+        "NSStringFormatSpecTypeKey".to_owned(),
+        "NSStringPluralRuleType".into(),
+    );
+    variable.insert(
+        "NSStringFormatValueTypeKey".to_owned(),
+        format_value_type(&other.value).into(),
+    );
+    for (case, localization) in cases {
+        if let Some(unit) = &localization.string_unit {
+            variable.insert(case.clone(), unit.value.clone().into());
+        }
+    }
+    let mut rule = plist::Dictionary::new();
+    rule.insert("NSStringLocalizedFormatKey".to_owned(), "%#@count@".into());
+    rule.insert("count".to_owned(), variable.into());
+    Some(rule)
+}
+
+/// Returns type of the first format specifier, e.g. `lld` for `%lld items`.
+fn format_value_type(value: &str) -> String {
+    value
+        .split('%')
+        .skip(1)
+        .find_map(|spec| {
+            let spec = spec.trim_start_matches(|c: char| c.is_ascii_digit() || c == '$');
+            let length = spec.find(|c: char| !"lhqjzt".contains(c))?;
+            let conversion = spec[length..].chars().next()?;
+            (conversion != '%').then(|| spec[..length + conversion.len_utf8()].to_owned())
+        })
+        .unwrap_or_else(|| "d".to_owned())
+}
+
+fn escape_strings_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_xcstrings() {
+        let dir = tempfile::tempdir().unwrap();
+        let catalog = r#"{
+  "sourceLanguage" : "en",
+  "strings" : {
+    "Play" : {
+      "localizations" : {
+        "fr" : { "stringUnit" : { "state" : "translated", "value" : "Jouer" } }
+      }
+    },
+    "Crossbow" : {
+      "shouldTranslate" : false,
+      "localizations" : {
+        "fr" : { "stringUnit" : { "state" : "translated", "value" : "Arbalète" } }
+      }
+    },
+    "%lld coins" : {
+      "localizations" : {
+        "en" : { "variations" : { "plural" : {
+          "one" : { "stringUnit" : { "state" : "translated", "value" : "%lld coin" } },
+          "other" : { "stringUnit" : { "state" : "translated", "value" : "%lld coins" } }
+        } } }
+      }
+    }
+  },
+  "version" : "1.0"
+}"#;
+        std::fs::write(dir.path().join("Localizable.xcstrings"), catalog).unwrap();
+        assert_eq!(compile_xcstrings_dir(dir.path()).unwrap(), 1);
+        assert!(!dir.path().join("Localizable.xcstrings").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("fr.lproj/Localizable.strings")).unwrap(),
+            "\"Play\" = \"Jouer\";\n"
+        );
+        let plurals =
+            plist::Value::from_file(dir.path().join("en.lproj/Localizable.stringsdict")).unwrap();
+        let count = plurals
+            .as_dictionary()
+            .and_then(|plurals| plurals.get("%lld coins"))
+            .and_then(|rule| rule.as_dictionary())
+            .and_then(|rule| rule.get("count"))
+            .and_then(|count| count.as_dictionary())
+            .unwrap();
+        assert_eq!(
+            count.get("NSStringFormatValueTypeKey"),
+            Some(&plist::Value::from("lld"))
+        );
+        assert_eq!(count.get("one"), Some(&plist::Value::from("%lld coin")));
+    }
+}
@@ -0,0 +1,91 @@
+use crate::error::*;
+use simctl::Simctl;
+use std::process::Command;
+
+/// Apple simulator or connected device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppleDevice {
+    pub name: String,
+    /// Identifier used with `--device-id` or `simctl`.
+    pub udid: String,
+    /// OS version. Only available for connected devices.
+    pub os_version: Option<String>,
+    /// Simulator state, e.g. `Booted` or `Shutdown`. `Connected` for devices.
+    pub state: String,
+    pub simulator: bool,
+}
+
+/// Lists available simulators.
+pub fn apple_simulators() -> Result<Vec<AppleDevice>> {
+    let simctl = Simctl::new();
+    let device_list = simctl.list()?;
+    Ok(device_list
+        .devices()
+        .iter()
+        .filter(|device| device.is_available)
+        .map(|device| AppleDevice {
+            name: device.name.clone(),
+            udid: device.udid.clone(),
+            os_version: None,
+            state: format!("{:?}", device.state),
+            simulator: true,
+        })
+        .collect())
+}
+
+/// Lists connected physical devices.
+/// Runs `xcrun xctrace list devices` command
+pub fn apple_connected_devices() -> Result<Vec<AppleDevice>> {
+    let mut xctrace = Command::new("xcrun");
+    xctrace.arg("xctrace").arg("list").arg("devices");
+    let output = xctrace.output_err(false)?;
+    // xctrace prints the list into stderr on some Xcode versions
+    let mut list = String::from_utf8_lossy(&output.stdout).to_string();
+    list.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok(parse_xctrace_devices(&list))
+}
+
+/// Parses `== Devices ==` section of `xcrun xctrace list devices` output. Each device
+/// is printed as `<name> (<os version>) (<udid>)`. The host Mac is printed without OS
+/// version and is skipped.
+pub fn parse_xctrace_devices(output: &str) -> Vec<AppleDevice> {
+    output
+        .lines()
+        .skip_while(|line| line.trim() != "== Devices ==")
+        .skip(1)
+        .take_while(|line| !line.starts_with("=="))
+        .filter_map(|line| {
+            let line = line.trim();
+            let (rest, udid) = line.strip_suffix(')')?.rsplit_once(" (")?;
+            let (name, os_version) = rest.strip_suffix(')')?.rsplit_once(" (")?;
+            Some(AppleDevice {
+                name: name.to_owned(),
+                udid: udid.to_owned(),
+                os_version: Some(os_version.to_owned()),
+                state: "Connected".to_owned(),
+                simulator: false,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xctrace_devices() {
+        let output = "== Devices ==\nJohn's MacBook Pro (00006000-001A2B3C4D5E6F70)\nJohn's iPhone (16.0.2) (00008030-001A2B3C4D5E6F70)\n\n== Simulators ==\niPhone 14 Simulator (16.0) (A1B2C3D4-0000-0000-0000-000000000000)\n";
+        let devices = parse_xctrace_devices(output);
+        assert_eq!(
+            devices,
+            vec![AppleDevice {
+                name: "John's iPhone".to_owned(),
+                udid: "00008030-001A2B3C4D5E6F70".to_owned(),
+                os_version: Some("16.0.2".to_owned()),
+                state: "Connected".to_owned(),
+                simulator: false,
+            }]
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use crate::error::*;
+use fs_extra::dir::{copy as copy_dir, CopyOptions};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// ## Generate DMG
+/// Wraps the macOS app into a compressed disk image with `/Applications` symlink, so
+/// users install it by dragging the app onto the symlink. With the background image
+/// the Finder window of the mounted image is laid out with AppleScript, which needs a
+/// logged in GUI session.
+///
+/// See more: `man hdiutil`
+#[derive(Clone, Debug)]
+pub struct GenDmg {
+    app_path: PathBuf,
+    dmg_path: PathBuf,
+    volume_name: String,
+    background: Option<PathBuf>,
+}
+
+impl GenDmg {
+    /// The volume is named after the app by default.
+    pub fn new(app_path: &Path, dmg_path: &Path) -> Self {
+        Self {
+            app_path: app_path.to_owned(),
+            dmg_path: dmg_path.to_owned(),
+            volume_name: app_path
+                .file_stem()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            background: None,
+        }
+    }
+
+    /// Name of the volume shown in the Finder.
+    pub fn volume_name(&mut self, volume_name: &str) -> &mut Self {
+        self.volume_name = volume_name.to_owned();
+        self
+    }
+
+    /// PNG image of the Finder window background. The window has the size of the image.
+    pub fn background(&mut self, background: &Path) -> &mut Self {
+        self.background = Some(background.to_owned());
+        self
+    }
+
+    /// Runs `hdiutil create ...` command. Returns the path to the disk image.
+    pub fn run(&self) -> Result<PathBuf> {
+        if !self.app_path.exists() {
+            return Err(Error::PathNotFound(self.app_path.clone()));
+        }
+        let staging = tempfile::tempdir()?;
+        let source = staging.path().join("source");
+        std::fs::create_dir_all(&source)?;
+        let mut options = CopyOptions::new();
+        options.copy_inside = true;
+        copy_dir(&self.app_path, &source, &options)?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("/Applications", source.join("Applications"))?;
+        if let Some(parent) = self.dmg_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let background = match &self.background {
+            Some(background) => background,
+            None => {
+                let mut create = Command::new("hdiutil");
+                create.args(self.create_args(&source, "UDZO", &self.dmg_path));
+                create.output_err(true)?;
+                return Ok(self.dmg_path.clone());
+            }
+        };
+        if !background.exists() {
+            return Err(Error::PathNotFound(background.clone()));
+        }
+        let background_dir = source.join(".background");
+        std::fs::create_dir_all(&background_dir)?;
+        std::fs::copy(background, background_dir.join("background.png"))?;
+        let (width, height) = image::image_dimensions(background)?;
+
+        // The layout is stored in `.DS_Store` of the writable image, then the image is
+        // compressed
+        let rw_dmg_path = staging.path().join("rw.dmg");
+        let mut create = Command::new("hdiutil");
+        create.args(self.create_args(&source, "UDRW", &rw_dmg_path));
+        create.output_err(true)?;
+        let mount_point = staging.path().join("mount");
+        let mut attach = Command::new("hdiutil");
+        attach
+            .args([
+                "attach",
+                "-readwrite",
+                "-noverify",
+                "-noautoopen",
+                "-mountpoint",
+            ])
+            .arg(&mount_point)
+            .arg(&rw_dmg_path);
+        attach.output_err(true)?;
+        let app_name = self.app_path.file_name().unwrap().to_string_lossy();
+        let mut osascript = Command::new("osascript");
+        osascript.args([
+            "-e",
+            &finder_layout_script(&self.volume_name, &app_name, width, height),
+        ]);
+        // The image is detached even if the layout failed
+        let layout = osascript.output_err(true);
+        let mut detach = Command::new("hdiutil");
+        detach.args(["detach", "-quiet"]).arg(&mount_point);
+        detach.output_err(true)?;
+        layout?;
+        let mut convert = Command::new("hdiutil");
+        convert
+            .arg("convert")
+            .arg(&rw_dmg_path)
+            .args(["-format", "UDZO", "-ov", "-o"])
+            .arg(&self.dmg_path);
+        convert.output_err(true)?;
+        Ok(self.dmg_path.clone())
+    }
+
+    fn create_args(&self, source: &Path, format: &str, dmg_path: &Path) -> Vec<String> {
+        vec![
+            "create".to_owned(),
+            "-volname".to_owned(),
+            self.volume_name.clone(),
+            "-srcfolder".to_owned(),
+            source.to_string_lossy().into_owned(),
+            "-ov".to_owned(),
+            "-format".to_owned(),
+            format.to_owned(),
+            dmg_path.to_string_lossy().into_owned(),
+        ]
+    }
+}
+
+/// Returns AppleScript that sets the background of the Finder window and places the app
+/// on the left and `/Applications` symlink on the right.
+pub fn finder_layout_script(volume_name: &str, app_name: &str, width: u32, height: u32) -> String {
+    let escape = |value: &str| value.replace('\\', "\\\\").replace('"', "\\\"");
+    format!(
+        r#"tell application "Finder"
+    tell disk "{volume}"
+        open
+        set current view of container window to icon view
+        set toolbar visible of container window to false
+        set statusbar visible of container window to false
+        set the bounds of container window to {{100, 100, {right}, {bottom}}}
+        set viewOptions to the icon view options of container window
+        set arrangement of viewOptions to not arranged
+        set icon size of viewOptions to 128
+        set background picture of viewOptions to file ".background:background.png"
+        set position of item "{app}" of container window to {{{app_x}, {y}}}
+        set position of item "Applications" of container window to {{{applications_x}, {y}}}
+        update without registering applications
+        close
+    end tell
+end tell"#,
+        volume = escape(volume_name),
+        app = escape(app_name),
+        right = 100 + width,
+        bottom = 100 + height,
+        app_x = width / 4,
+        applications_x = width * 3 / 4,
+        y = height / 2,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gen_dmg() {
+        let dmg = GenDmg::new(Path::new("target/Game.app"), Path::new("target/Game.dmg"));
+        assert_eq!(
+            dmg.create_args(Path::new("source"), "UDZO", Path::new("Game.dmg")),
+            [
+                "create",
+                "-volname",
+                "Game",
+                "-srcfolder",
+                "source",
+                "-ov",
+                "-format",
+                "UDZO",
+                "Game.dmg"
+            ]
+        );
+        let script = finder_layout_script("Game", "Game.app", 600, 400);
+        assert!(script.contains("tell disk \"Game\""));
+        assert!(script.contains("set the bounds of container window to {100, 100, 700, 500}"));
+        assert!(
+            script.contains("set position of item \"Game.app\" of container window to {150, 200}")
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use crate::error::*;
+use image::{imageops::FilterType, GenericImageView, ImageFormat};
+use std::{io::Cursor, path::Path};
+
+/// Smallest size of the source icon. Retina icons of the Finder and the Dock need at
+/// least 512x512 pixels.
+pub const MIN_ICNS_SOURCE_SIZE: u32 = 512;
+
+/// `icns` element types with PNG data by size in pixels.
+const ICNS_TYPES: [(&[u8; 4], u32); 10] = [
+    (b"icp4", 16),
+    (b"ic11", 32),
+    (b"icp5", 32),
+    (b"ic12", 64),
+    (b"icp6", 64),
+    (b"ic07", 128),
+    (b"ic13", 256),
+    (b"ic08", 256),
+    (b"ic14", 512),
+    (b"ic09", 512),
+];
+
+/// Generates `.icns` icon of the macOS application from the square PNG or JPEG image.
+/// `ic10` 1024x1024 icon is added if the image is large enough.
+pub fn gen_icns(icon_path: &Path, out_path: &Path) -> Result<()> {
+    let image = image::open(icon_path)?;
+    let (width, height) = image.dimensions();
+    if width != height {
+        return Err(Error::WidthAndHeightDifSizes);
+    }
+    if width < MIN_ICNS_SOURCE_SIZE {
+        return Err(AppleError::IconTooSmall(width, MIN_ICNS_SOURCE_SIZE).into());
+    }
+    let mut types = ICNS_TYPES.to_vec();
+    if width >= 1024 {
+        types.push((b"ic10", 1024));
+    }
+    let mut elements = Vec::new();
+    for (icns_type, size) in types {
+        let mut png = Vec::new();
+        image
+            .resize_exact(size, size, FilterType::Lanczos3)
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+        elements.extend_from_slice(icns_type);
+        elements.extend_from_slice(&(png.len() as u32 + 8).to_be_bytes());
+        elements.extend_from_slice(&png);
+    }
+    let mut icns = Vec::with_capacity(elements.len() + 8);
+    icns.extend_from_slice(b"icns");
+    icns.extend_from_slice(&(elements.len() as u32 + 8).to_be_bytes());
+    icns.extend_from_slice(&elements);
+    std::fs::write(out_path, icns)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gen_icns() {
+        let dir = tempfile::tempdir().unwrap();
+        let icon_path = dir.path().join("icon.png");
+        image::RgbaImage::new(512, 512).save(&icon_path).unwrap();
+        let icns_path = dir.path().join("AppIcon.icns");
+        gen_icns(&icon_path, &icns_path).unwrap();
+
+        let icns = std::fs::read(&icns_path).unwrap();
+        assert_eq!(&icns[..4], b"icns");
+        assert_eq!(
+            u32::from_be_bytes(icns[4..8].try_into().unwrap()) as usize,
+            icns.len()
+        );
+        assert_eq!(&icns[8..12], b"icp4");
+        assert!(!icns.windows(4).any(|window| window == b"ic10"));
+
+        image::RgbaImage::new(256, 256).save(&icon_path).unwrap();
+        assert!(gen_icns(&icon_path, &icns_path).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use crate::error::*;
+use apple_bundle::plist;
+use fs_extra::dir::{copy as copy_dir, CopyOptions};
+use std::fs::{create_dir_all, remove_dir_all};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Generates a macOS app folder: the binary goes to `Contents/MacOS`, resources and
+/// assets to `Contents/Resources` and frameworks to `Contents/Frameworks`.
+pub fn gen_macos_app_folder(
+    target_dir: &Path,
+    project_name: &str,
+    assets_dir: Option<PathBuf>,
+    resources_dir: Option<PathBuf>,
+) -> Result<PathBuf> {
+    let app_path = target_dir.join(format!("{}.app", project_name));
+    remove_dir_all(&app_path).ok();
+    let contents_path = app_path.join("Contents");
+    create_dir_all(contents_path.join("MacOS"))?;
+    let resources_path = contents_path.join("Resources");
+    create_dir_all(&resources_path)?;
+    let mut options = CopyOptions::new();
+    options.skip_exist = true;
+    options.content_only = true;
+    if let Some(resources_dir) = &resources_dir {
+        if !resources_dir.exists() {
+            return Err(AppleError::ResourcesNotFound.into());
+        }
+        copy_dir(resources_dir, &resources_path, &options)?;
+    }
+    if let Some(assets_dir) = &assets_dir {
+        if !assets_dir.exists() {
+            return Err(AppleError::AssetsNotFound.into());
+        }
+        let assets_path = resources_path.join("assets");
+        create_dir_all(&assets_path)?;
+        copy_dir(assets_dir, &assets_path, &options)?;
+    }
+    Ok(app_path)
+}
+
+/// Copies `.framework` bundles and `.dylib` libraries into `Contents/Frameworks` of the
+/// macOS app. The binary finds them with `@executable_path/../Frameworks` rpath.
+/// Returns paths of the embedded frameworks to sign them before the app.
+pub fn embed_macos_frameworks(app_path: &Path, frameworks: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let frameworks_path = app_path.join("Contents").join("Frameworks");
+    remove_dir_all(&frameworks_path).ok();
+    if frameworks.is_empty() {
+        return Ok(Vec::new());
+    }
+    create_dir_all(&frameworks_path)?;
+    let mut options = CopyOptions::new();
+    options.copy_inside = true;
+    let mut embedded = Vec::new();
+    for framework in frameworks {
+        if !framework.exists() {
+            return Err(Error::PathNotFound(framework.clone()));
+        }
+        let path = frameworks_path.join(framework.file_name().unwrap());
+        match framework.is_dir() {
+            true => copy_dir(framework, &frameworks_path, &options).map(|_| ())?,
+            false => std::fs::copy(framework, &path).map(|_| ())?,
+        }
+        embedded.push(path);
+    }
+    Ok(embedded)
+}
+
+/// Adds `@executable_path/../Frameworks` rpath to the binary copied into the app, so it
+/// loads the embedded frameworks. Runs `install_name_tool -add_rpath ...` command.
+pub fn add_frameworks_rpath(binary_path: &Path) -> Result<()> {
+    let mut install_name_tool = Command::new("install_name_tool");
+    install_name_tool
+        .args(["-add_rpath", "@executable_path/../Frameworks"])
+        .arg(binary_path);
+    install_name_tool.output_err(true)?;
+    Ok(())
+}
+
+/// Returns `Info.plist` keys of macOS applications. `icon_file` is the name of `.icns`
+/// file in `Contents/Resources` without extension.
+pub fn macos_info_plist_keys(
+    minimum_system_version: &str,
+    category: Option<&str>,
+    icon_file: Option<&str>,
+) -> plist::Dictionary {
+    let mut keys = plist::Dictionary::new();
+    keys.insert(
+        "LSMinimumSystemVersion".to_owned(),
+        minimum_system_version.into(),
+    );
+    keys.insert("NSHighResolutionCapable".to_owned(), true.into());
+    if let Some(category) = category {
+        keys.insert("LSApplicationCategoryType".to_owned(), category.into());
+    }
+    if let Some(icon_file) = icon_file {
+        keys.insert("CFBundleIconFile".to_owned(), icon_file.into());
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gen_macos_app_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let assets = dir.path().join("assets");
+        create_dir_all(&assets).unwrap();
+        std::fs::write(assets.join("level.json"), "{}").unwrap();
+        let dylib = dir.path().join("libsteam_api.dylib");
+        std::fs::write(&dylib, "").unwrap();
+
+        let app_path = gen_macos_app_folder(dir.path(), "Game", Some(assets), None).unwrap();
+        assert!(app_path.join("Contents/MacOS").exists());
+        assert!(app_path
+            .join("Contents/Resources/assets/level.json")
+            .exists());
+        let embedded = embed_macos_frameworks(&app_path, &[dylib]).unwrap();
+        assert_eq!(
+            embedded,
+            vec![app_path.join("Contents/Frameworks/libsteam_api.dylib")]
+        );
+        assert!(embedded[0].exists());
+    }
+}
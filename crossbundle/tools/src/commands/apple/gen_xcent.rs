@@ -35,3 +35,18 @@ pub fn gen_xcent(
     }
     Ok(file_path)
 }
+
+/// Adds entitlements from the plist file, e.g. `Game.entitlements` of an Xcode project,
+/// to the generated `.xcent` file. The application and team identifiers of the
+/// generated file are kept.
+pub fn merge_entitlements(xcent_path: &Path, entitlements_path: &Path) -> Result<()> {
+    let mut xcent: plist::Dictionary = plist::from_file(xcent_path)?;
+    let entitlements: plist::Dictionary = plist::from_file(entitlements_path)?;
+    for (key, value) in entitlements {
+        if !xcent.contains_key(&key) {
+            xcent.insert(key, value);
+        }
+    }
+    plist::to_file_xml(xcent_path, &xcent)?;
+    Ok(())
+}
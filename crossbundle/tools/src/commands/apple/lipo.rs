@@ -0,0 +1,107 @@
+use crate::{error::*, types::*};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Build directory of the universal iOS binary for devices.
+pub const UNIVERSAL_IOS_DIR: &str = "universal-apple-ios";
+/// Build directory of the universal iOS binary for simulators.
+pub const UNIVERSAL_IOS_SIM_DIR: &str = "universal-apple-ios-sim";
+/// Build directory of the universal macOS binary.
+pub const UNIVERSAL_MACOS_DIR: &str = "universal-apple-darwin";
+
+/// Merges binaries built for different architectures into one universal binary. Runs
+/// `lipo -create ... -output ...` command.
+pub fn lipo_create(binaries: &[PathBuf], output: &Path) -> Result<()> {
+    for binary in binaries {
+        if !binary.exists() {
+            return Err(Error::PathNotFound(binary.clone()));
+        }
+    }
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut lipo = Command::new("lipo");
+    lipo.arg("-create")
+        .args(binaries)
+        .arg("-output")
+        .arg(output);
+    lipo.output_err(true)?;
+    Ok(())
+}
+
+/// Returns architectures of the binary, e.g. `["x86_64", "arm64"]`. Runs
+/// `lipo -archs ...` command.
+pub fn lipo_archs(binary: &Path) -> Result<Vec<String>> {
+    let mut lipo = Command::new("lipo");
+    lipo.arg("-archs").arg(binary);
+    let output = lipo.output_err(false)?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .map(ToOwned::to_owned)
+        .collect())
+}
+
+/// Groups iOS, tvOS and watchOS targets into universal binaries. Device and simulator
+/// slices can't share one binary, `aarch64-apple-ios` and `aarch64-apple-ios-sim` are
+/// both `arm64`, so device targets and simulator targets of every platform form
+/// separate groups.
+pub fn ios_universal_groups(targets: &[IosTarget]) -> Vec<Vec<IosTarget>> {
+    let mut groups: Vec<Vec<IosTarget>> = Vec::new();
+    for target in targets {
+        match groups.iter_mut().find(|group| {
+            group[0].platform() == target.platform() && group[0].is_device() == target.is_device()
+        }) {
+            Some(group) if !group.contains(target) => group.push(*target),
+            Some(_) => {}
+            None => groups.push(vec![*target]),
+        }
+    }
+    groups
+}
+
+/// Returns the build directory name of the group: the Rust triple of a single target or
+/// the universal directory.
+pub fn ios_build_dir_name(targets: &[IosTarget]) -> &'static str {
+    let target = match targets {
+        [target] => return target.rust_triple(),
+        [target, ..] => target,
+        [] => return UNIVERSAL_IOS_SIM_DIR,
+    };
+    match (target.platform(), target.is_device()) {
+        (ApplePlatform::Ios, true) => UNIVERSAL_IOS_DIR,
+        (ApplePlatform::Ios, false) => UNIVERSAL_IOS_SIM_DIR,
+        (ApplePlatform::Tvos, true) => "universal-apple-tvos",
+        (ApplePlatform::Tvos, false) => "universal-apple-tvos-sim",
+        (ApplePlatform::Watchos, true) => "universal-apple-watchos",
+        (ApplePlatform::Watchos, false) => "universal-apple-watchos-sim",
+        (ApplePlatform::MacCatalyst, _) => "universal-apple-ios-macabi",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ios_universal_groups() {
+        let groups = ios_universal_groups(&[
+            IosTarget::Aarch64Sim,
+            IosTarget::Aarch64,
+            IosTarget::X86_64,
+            IosTarget::Aarch64Sim,
+            IosTarget::TvosAarch64Sim,
+        ]);
+        assert_eq!(
+            groups,
+            vec![
+                vec![IosTarget::Aarch64Sim, IosTarget::X86_64],
+                vec![IosTarget::Aarch64],
+                vec![IosTarget::TvosAarch64Sim],
+            ]
+        );
+        assert_eq!(ios_build_dir_name(&groups[0]), UNIVERSAL_IOS_SIM_DIR);
+        assert_eq!(ios_build_dir_name(&groups[1]), "aarch64-apple-ios");
+    }
+}
@@ -1,21 +1,55 @@
+mod app_data;
+mod appearance;
 mod codesign;
+mod compile_xcstrings;
 mod copy_profile;
+mod devices;
 mod gen_app_folder;
+mod gen_dmg;
+mod gen_icns;
 mod gen_ipa;
+mod gen_macos_app;
 mod gen_xcent;
 mod launch_app;
+mod lipo;
+mod notarize;
+mod on_demand_resources;
+mod platform;
 mod read_plist;
 mod run_on_device;
 mod rust_compile;
 mod save_plist;
+mod screen_record;
+mod simulator_profile;
+mod upload_app;
+mod verify_launch;
+mod xcresult;
+mod xcrun_simctl;
 
+pub use app_data::*;
+pub use appearance::*;
 pub use codesign::*;
+pub use compile_xcstrings::*;
 pub use copy_profile::*;
+pub use devices::*;
 pub use gen_app_folder::*;
+pub use gen_dmg::*;
+pub use gen_icns::*;
 pub use gen_ipa::*;
+pub use gen_macos_app::*;
 pub use gen_xcent::*;
 pub use launch_app::*;
+pub use lipo::*;
+pub use notarize::*;
+pub use on_demand_resources::*;
+pub use platform::*;
 pub use read_plist::*;
 pub use run_on_device::*;
 pub use rust_compile::*;
 pub use save_plist::*;
+pub use screen_record::*;
+pub use simulator_profile::*;
+pub use upload_app::*;
+pub use verify_launch::*;
+pub use xcresult::*;
+pub use xcrun_simctl::*;
@@ -0,0 +1,221 @@
+use crate::error::*;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant},
+};
+
+/// Credentials of the Apple notary service.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NotaryAuth {
+    /// Credentials stored in the keychain with
+    /// `xcrun notarytool store-credentials <profile>`.
+    KeychainProfile(String),
+    /// App Store Connect API key.
+    ApiKey {
+        key_path: PathBuf,
+        key_id: String,
+        issuer_id: String,
+    },
+}
+
+impl NotaryAuth {
+    /// Keychain profile or API key ID, e.g. to record who notarized the app.
+    pub fn key(&self) -> &str {
+        match self {
+            Self::KeychainProfile(profile) => profile,
+            Self::ApiKey { key_id, .. } => key_id,
+        }
+    }
+
+    fn args(&self) -> Vec<String> {
+        match self {
+            Self::KeychainProfile(profile) => {
+                vec!["--keychain-profile".to_owned(), profile.clone()]
+            }
+            Self::ApiKey {
+                key_path,
+                key_id,
+                issuer_id,
+            } => vec![
+                "--key".to_owned(),
+                key_path.to_string_lossy().into_owned(),
+                "--key-id".to_owned(),
+                key_id.clone(),
+                "--issuer".to_owned(),
+                issuer_id.clone(),
+            ],
+        }
+    }
+}
+
+/// ## Notarize
+/// Submits the signed macOS app or disk image to the Apple notary service, waits for the
+/// result and staples the ticket to it, so Gatekeeper accepts it offline. The app must
+/// be signed with a Developer ID identity, the hardened runtime and a secure timestamp.
+///
+/// See more: `xcrun notarytool --help`
+#[derive(Clone, Debug)]
+pub struct Notarize {
+    path: PathBuf,
+    auth: NotaryAuth,
+    timeout: Duration,
+    poll_interval: Duration,
+}
+
+impl Notarize {
+    pub fn new(path: &Path, auth: NotaryAuth) -> Self {
+        Self {
+            path: path.to_owned(),
+            auth,
+            timeout: Duration::from_secs(60 * 60),
+            poll_interval: Duration::from_secs(30),
+        }
+    }
+
+    /// How long to wait for the result. One hour by default.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Submits the app, polls the status of the submission and staples the ticket.
+    /// Status changes like `In Progress` are passed to `on_status`.
+    pub fn run(&self, mut on_status: impl FnMut(&str)) -> Result<()> {
+        if !self.path.exists() {
+            return Err(Error::PathNotFound(self.path.clone()));
+        }
+        // Bundles are submitted in a zip archive, the ticket is stapled to the bundle
+        let archive = tempfile::tempdir()?;
+        let submission = match self.path.extension().map_or(false, |ext| ext == "app") {
+            true => {
+                let zip_path = archive.path().join("submission.zip");
+                let mut ditto = Command::new("ditto");
+                ditto
+                    .args(["-c", "-k", "--keepParent"])
+                    .arg(&self.path)
+                    .arg(&zip_path);
+                ditto.output_err(true)?;
+                zip_path
+            }
+            false => self.path.clone(),
+        };
+        let output = self.notarytool(&[
+            "submit".to_owned(),
+            submission.to_string_lossy().into_owned(),
+        ])?;
+        let id = notary_field(&output, "id").ok_or_else(|| {
+            AppleError::NotarizationFailed("submission ID is not found".to_owned(), output)
+        })?;
+        on_status("Submitted");
+
+        let started = Instant::now();
+        let mut last_status = String::new();
+        loop {
+            let output = self.notarytool(&["info".to_owned(), id.clone()])?;
+            let status = notary_field(&output, "status").unwrap_or_default();
+            if status != last_status {
+                on_status(&status);
+                last_status = status.clone();
+            }
+            match status.as_str() {
+                "Accepted" => break,
+                "In Progress" | "" => {}
+                _ => {
+                    let log = self
+                        .notarytool(&["log".to_owned(), id.clone()])
+                        .map(|log| notary_log_issues(&log).join("\n"))
+                        .unwrap_or_default();
+                    return Err(AppleError::NotarizationFailed(status, log).into());
+                }
+            }
+            if started.elapsed() > self.timeout {
+                return Err(AppleError::NotarizationTimedOut(id).into());
+            }
+            std::thread::sleep(self.poll_interval);
+        }
+        staple(&self.path)
+    }
+
+    /// Runs `xcrun notarytool <args> --output-format json ...` command and returns its
+    /// output.
+    fn notarytool(&self, args: &[String]) -> Result<String> {
+        let mut notarytool = Command::new("xcrun");
+        notarytool
+            .arg("notarytool")
+            .args(args)
+            .args(["--output-format", "json"])
+            .args(self.auth.args());
+        let output = notarytool.output_err(false)?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Attaches the notarization ticket to the app or the disk image. Runs
+/// `xcrun stapler staple ...` command.
+pub fn staple(path: &Path) -> Result<()> {
+    let mut stapler = Command::new("xcrun");
+    stapler.args(["stapler", "staple"]).arg(path);
+    stapler.output_err(true)?;
+    Ok(())
+}
+
+/// Returns the string field of `notarytool` JSON output.
+pub fn notary_field(output: &str, field: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(output).ok()?;
+    value.get(field)?.as_str().map(ToOwned::to_owned)
+}
+
+/// Returns issues of the notarization log as `<path>: <message>` lines.
+pub fn notary_log_issues(log: &str) -> Vec<String> {
+    let value: serde_json::Value = match serde_json::from_str(log) {
+        Ok(value) => value,
+        Err(_) => return vec![log.trim().to_owned()],
+    };
+    let issues = match value.get("issues").and_then(|issues| issues.as_array()) {
+        Some(issues) => issues,
+        None => return Vec::new(),
+    };
+    issues
+        .iter()
+        .map(|issue| {
+            let field = |name: &str| issue.get(name).and_then(|value| value.as_str());
+            format!(
+                "{}: {}",
+                field("path").unwrap_or_default(),
+                field("message").unwrap_or_default()
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notarize() {
+        let auth = NotaryAuth::KeychainProfile("crossbow".to_owned());
+        assert_eq!(auth.args(), ["--keychain-profile", "crossbow"]);
+
+        let output = r#"{"id":"2efe2717-52ef-43a5-96dc-0797e4ca1041","message":"Successfully uploaded file"}"#;
+        assert_eq!(
+            notary_field(output, "id").as_deref(),
+            Some("2efe2717-52ef-43a5-96dc-0797e4ca1041")
+        );
+        let log = r#"{
+  "status": "Invalid",
+  "issues": [
+    {
+      "severity": "error",
+      "path": "Game.zip/Game.app/Contents/MacOS/Game",
+      "message": "The executable does not have the hardened runtime enabled."
+    }
+  ]
+}"#;
+        assert_eq!(
+            notary_log_issues(log),
+            ["Game.zip/Game.app/Contents/MacOS/Game: The executable does not have the hardened runtime enabled."]
+        );
+    }
+}
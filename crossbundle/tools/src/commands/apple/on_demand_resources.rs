@@ -0,0 +1,194 @@
+use crate::{commands::relative_files, error::*};
+use apple_bundle::plist;
+use fs_extra::dir::{copy as copy_dir, CopyOptions};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Assets downloaded by the App Store separately from the application, when it
+/// requests one of the tags with `NSBundleResourceRequest`.
+///
+/// See more: https://developer.apple.com/library/archive/documentation/FileManagement/Conceptual/On_Demand_Resources_Guide/
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct OnDemandResource {
+    /// Asset pack name. Must start with a letter and contain only letters, digits and `-`.
+    pub name: String,
+    /// Tags requested by the application. The name is used if not specified.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Assets directory of the asset pack.
+    pub assets: PathBuf,
+}
+
+impl OnDemandResource {
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: &str| -> Error {
+            AppleError::InvalidOnDemandResource(self.name.clone(), reason.to_owned()).into()
+        };
+        if !self.name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return Err(invalid("name must start with a letter"));
+        }
+        if !self
+            .name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(invalid("name must contain only letters, digits and `-`"));
+        }
+        if self.tags.iter().any(|tag| tag.trim().is_empty()) {
+            return Err(invalid("tags must not be empty"));
+        }
+        if !self.assets.exists() {
+            return Err(Error::PathNotFound(self.assets.clone()));
+        }
+        Ok(())
+    }
+
+    /// Returns tags of the asset pack.
+    pub fn tags(&self) -> Vec<String> {
+        match self.tags.is_empty() {
+            true => vec![self.name.clone()],
+            false => self.tags.clone(),
+        }
+    }
+
+    /// Returns bundle identifier of the asset pack.
+    pub fn bundle_id(&self, app_bundle_id: &str) -> String {
+        format!("{}.asset-pack-{}", app_bundle_id, self.name)
+    }
+}
+
+/// Generates asset packs of on-demand resources in `OnDemandResources` directory of the
+/// application bundle, and `OnDemandResources.plist` and
+/// `AssetPackManifestTemplate.plist` describing them. Embedded asset packs work in
+/// development builds, the App Store hosts them separately after upload.
+pub fn gen_on_demand_resources(
+    app_path: &Path,
+    app_bundle_id: &str,
+    resources: &[OnDemandResource],
+) -> Result<()> {
+    let packs_dir = app_path.join("OnDemandResources");
+    std::fs::remove_dir_all(&packs_dir).ok();
+    let mut options = CopyOptions::new();
+    options.content_only = true;
+    let mut tags = plist::Dictionary::new();
+    let mut asset_packs = plist::Dictionary::new();
+    let mut manifest_resources = Vec::new();
+    for resource in resources {
+        resource.validate()?;
+        let bundle_id = resource.bundle_id(app_bundle_id);
+        let pack_path = packs_dir.join(format!("{}.assetpack", bundle_id));
+        std::fs::create_dir_all(&pack_path)?;
+        copy_dir(&resource.assets, &pack_path, &options)?;
+        let mut files = relative_files(&pack_path)?;
+        files.sort();
+        let mut size = 0;
+        let mut modified = std::time::UNIX_EPOCH;
+        for file in &files {
+            let metadata = std::fs::metadata(pack_path.join(file))?;
+            size += metadata.len();
+            modified = modified.max(metadata.modified()?);
+        }
+        let modified = modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+
+        let mut info = plist::Dictionary::new();
+        info.insert("CFBundleIdentifier".to_owned(), bundle_id.clone().into());
+        let pack_tags: Vec<plist::Value> = resource.tags().into_iter().map(Into::into).collect();
+        info.insert("Tags".to_owned(), pack_tags.into());
+        plist::Value::Dictionary(info).to_file_xml(pack_path.join("Info.plist"))?;
+
+        for tag in resource.tags() {
+            let tag = tags.entry(tag).or_insert(plist::Dictionary::new().into());
+            if let Some(tag) = tag.as_dictionary_mut() {
+                let packs = tag
+                    .entry("NSAssetPacks")
+                    .or_insert(plist::Value::Array(Vec::new()));
+                if let Some(packs) = packs.as_array_mut() {
+                    packs.push(bundle_id.clone().into());
+                }
+            }
+        }
+        let files: Vec<plist::Value> = files
+            .iter()
+            .map(|file| file.to_string_lossy().replace('\\', "/").into())
+            .collect();
+        asset_packs.insert(bundle_id.clone(), files.into());
+
+        let mut hash = plist::Dictionary::new();
+        hash.insert(
+            "hash".to_owned(),
+            format!("{}.{:09}", modified.as_secs(), modified.subsec_nanos()).into(),
+        );
+        hash.insert("strategy".to_owned(), "modtime".into());
+        let mut manifest_resource = plist::Dictionary::new();
+        manifest_resource.insert(
+            "URL".to_owned(),
+            format!("http://127.0.0.1/{}.assetpack", bundle_id).into(),
+        );
+        manifest_resource.insert("bundleKey".to_owned(), bundle_id.into());
+        manifest_resource.insert("isStreamable".to_owned(), true.into());
+        manifest_resource.insert("primaryContentHash".to_owned(), hash.into());
+        manifest_resource.insert("uncompressedSize".to_owned(), size.into());
+        manifest_resources.push(manifest_resource.into());
+    }
+
+    let mut on_demand_resources = plist::Dictionary::new();
+    on_demand_resources.insert("NSBundleResourceRequestTags".to_owned(), tags.into());
+    on_demand_resources.insert(
+        "NSBundleResourceRequestAssetPacks".to_owned(),
+        asset_packs.into(),
+    );
+    plist::Value::Dictionary(on_demand_resources)
+        .to_file_xml(app_path.join("OnDemandResources.plist"))?;
+    let mut manifest = plist::Dictionary::new();
+    manifest.insert("resources".to_owned(), manifest_resources.into());
+    plist::Value::Dictionary(manifest)
+        .to_file_xml(app_path.join("AssetPackManifestTemplate.plist"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gen_on_demand_resources() {
+        let dir = tempfile::tempdir().unwrap();
+        let assets = dir.path().join("levels");
+        std::fs::create_dir_all(assets.join("maps")).unwrap();
+        std::fs::write(assets.join("maps/level_2.json"), "{}").unwrap();
+        let app_path = dir.path().join("Game.app");
+        std::fs::create_dir_all(&app_path).unwrap();
+        let resource = OnDemandResource {
+            name: "levels".to_owned(),
+            tags: vec![],
+            assets,
+        };
+        gen_on_demand_resources(&app_path, "com.crossbow.game", &[resource.clone()]).unwrap();
+
+        let pack_path = app_path
+            .join("OnDemandResources")
+            .join("com.crossbow.game.asset-pack-levels.assetpack");
+        assert!(pack_path.join("maps/level_2.json").exists());
+        let plist = plist::Value::from_file(app_path.join("OnDemandResources.plist")).unwrap();
+        let packs = plist
+            .as_dictionary()
+            .and_then(|plist| plist.get("NSBundleResourceRequestTags"))
+            .and_then(|tags| tags.as_dictionary())
+            .and_then(|tags| tags.get("levels"))
+            .and_then(|tag| tag.as_dictionary())
+            .and_then(|tag| tag.get("NSAssetPacks"))
+            .unwrap();
+        assert_eq!(
+            packs,
+            &plist::Value::Array(vec!["com.crossbow.game.asset-pack-levels".into()])
+        );
+
+        let invalid = OnDemandResource {
+            name: "level_2".to_owned(),
+            ..resource
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
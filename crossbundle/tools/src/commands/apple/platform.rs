@@ -0,0 +1,79 @@
+use crate::{error::*, types::*};
+use apple_bundle::plist;
+use std::{path::PathBuf, process::Command};
+
+/// `Info.plist` key that asks the game to hide sensitive content while the screen is
+/// captured. Read at runtime by `crossbow::ios::capture::secure_window_requested`.
+pub const SECURE_WINDOW_INFO_PLIST_KEY: &str = "CrossbowSecureWindow";
+
+/// Returns the path to the SDK, e.g. `appletvos` or `watchsimulator`. Runs
+/// `xcrun --sdk <sdk> --show-sdk-path` command.
+pub fn sdk_path(sdk_name: &str) -> Result<PathBuf> {
+    let mut xcrun = Command::new("xcrun");
+    xcrun.args(["--sdk", sdk_name, "--show-sdk-path"]);
+    let output = xcrun.output_err(false)?;
+    Ok(PathBuf::from(
+        String::from_utf8_lossy(&output.stdout).trim().to_owned(),
+    ))
+}
+
+/// Returns `Info.plist` keys of the platform of the target: the supported platform and
+/// the device family of tvOS, watchOS and Mac Catalyst apps. Mac Catalyst apps use the
+/// iPad idiom. iOS apps have no extra keys.
+pub fn platform_info_plist_keys(build_target: IosTarget) -> plist::Dictionary {
+    let mut keys = plist::Dictionary::new();
+    let (platform_name, device_family) = match (build_target.platform(), build_target.is_device()) {
+        (ApplePlatform::Ios, _) => return keys,
+        (ApplePlatform::Tvos, true) => ("AppleTVOS", AppleDeviceFamily::Tv),
+        (ApplePlatform::Tvos, false) => ("AppleTVSimulator", AppleDeviceFamily::Tv),
+        (ApplePlatform::Watchos, true) => ("WatchOS", AppleDeviceFamily::Watch),
+        (ApplePlatform::Watchos, false) => ("WatchSimulator", AppleDeviceFamily::Watch),
+        (ApplePlatform::MacCatalyst, _) => ("MacOSX", AppleDeviceFamily::Ipad),
+    };
+    keys.insert(
+        "CFBundleSupportedPlatforms".to_owned(),
+        plist::Value::Array(vec![platform_name.into()]),
+    );
+    keys.insert(
+        "UIDeviceFamily".to_owned(),
+        plist::Value::Array(vec![plist::Value::Integer(device_family.value().into())]),
+    );
+    if build_target.platform() == ApplePlatform::Watchos {
+        // Standalone watchOS app without an iOS companion
+        keys.insert("WKApplication".to_owned(), true.into());
+        keys.insert("WKWatchOnly".to_owned(), true.into());
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_platform_info_plist_keys() {
+        assert!(platform_info_plist_keys(IosTarget::Aarch64).is_empty());
+        let keys = platform_info_plist_keys(IosTarget::TvosAarch64Sim);
+        assert_eq!(
+            keys.get("CFBundleSupportedPlatforms"),
+            Some(&plist::Value::Array(vec!["AppleTVSimulator".into()]))
+        );
+        assert_eq!(
+            keys.get("UIDeviceFamily"),
+            Some(&plist::Value::Array(vec![plist::Value::Integer(
+                3u64.into()
+            )]))
+        );
+        let keys = platform_info_plist_keys(IosTarget::WatchosArm64_32);
+        assert_eq!(
+            keys.get("CFBundleSupportedPlatforms"),
+            Some(&plist::Value::Array(vec!["WatchOS".into()]))
+        );
+        assert_eq!(keys.get("WKApplication"), Some(&true.into()));
+        let keys = platform_info_plist_keys(IosTarget::CatalystAarch64);
+        assert_eq!(
+            keys.get("CFBundleSupportedPlatforms"),
+            Some(&plist::Value::Array(vec!["MacOSX".into()]))
+        );
+    }
+}
@@ -1,5 +1,8 @@
 use crate::error::*;
-use std::{path::Path, process::Command};
+use std::{
+    path::Path,
+    process::{Child, Command, Stdio},
+};
 
 /// Runs and debugs app on device.
 /// Runs `ios-deploy ...` command.
@@ -29,3 +32,106 @@ pub fn run_and_debug(
     cmd.output_err(true)?;
     Ok(())
 }
+
+/// Tools that install and launch applications on connected devices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppleDeviceTool {
+    /// `ios-deploy`, launches the application under lldb.
+    IosDeploy,
+    /// `ideviceinstaller` and `idevicedebug` of libimobiledevice.
+    Libimobiledevice,
+}
+
+impl AppleDeviceTool {
+    /// Returns `ios-deploy` if it's installed, otherwise libimobiledevice tools.
+    pub fn find() -> Result<Self> {
+        if which::which("ios-deploy").is_ok() {
+            return Ok(Self::IosDeploy);
+        }
+        if which::which("ideviceinstaller").is_ok() && which::which("idevicedebug").is_ok() {
+            return Ok(Self::Libimobiledevice);
+        }
+        Err(AppleError::DeviceToolNotFound.into())
+    }
+}
+
+/// Installs the signed `.app` or `.ipa` on the connected device.
+/// Runs `ios-deploy --id <udid> --bundle <app> --no-wifi` or `ideviceinstaller -u
+/// <udid> -i <app>` command
+pub fn install_on_device(tool: AppleDeviceTool, udid: &str, app_path: &Path) -> Result<()> {
+    let mut cmd = match tool {
+        AppleDeviceTool::IosDeploy => {
+            let mut cmd = Command::new("ios-deploy");
+            cmd.args(["--id", udid, "--bundle"])
+                .arg(app_path)
+                .arg("--no-wifi");
+            cmd
+        }
+        AppleDeviceTool::Libimobiledevice => {
+            let mut cmd = Command::new("ideviceinstaller");
+            cmd.args(["-u", udid, "-i"]).arg(app_path);
+            cmd
+        }
+    };
+    cmd.output_err(true)?;
+    Ok(())
+}
+
+/// Launches the installed application on the connected device. Its stdout and stderr
+/// are written to the terminal until it exits. With `debug` ios-deploy opens an
+/// interactive lldb session instead.
+/// Runs `ios-deploy --id <udid> --bundle <app> --noinstall --noninteractive` or
+/// `idevicedebug -u <udid> run <bundle id>` command
+pub fn launch_on_device(
+    tool: AppleDeviceTool,
+    udid: &str,
+    app_path: &Path,
+    bundle_id: &str,
+    debug: bool,
+) -> Result<Child> {
+    let mut cmd = match tool {
+        AppleDeviceTool::IosDeploy => {
+            let mut cmd = Command::new("ios-deploy");
+            cmd.args(["--id", udid, "--bundle"])
+                .arg(app_path)
+                .args(["--noinstall", "--no-wifi"]);
+            match debug {
+                true => cmd.arg("--debug"),
+                false => cmd.arg("--noninteractive"),
+            };
+            cmd
+        }
+        AppleDeviceTool::Libimobiledevice => {
+            let mut cmd = Command::new("idevicedebug");
+            cmd.args(["-u", udid, "run", bundle_id]);
+            cmd
+        }
+    };
+    Ok(cmd.spawn()?)
+}
+
+/// Streams syslog messages of the process from the connected device to the terminal.
+/// Returns `None` if libimobiledevice is not installed.
+/// Runs `idevicesyslog -u <udid> -p <process>` command
+pub fn device_syslog(udid: &str, process: &str) -> Result<Option<Child>> {
+    if which::which("idevicesyslog").is_err() {
+        return Ok(None);
+    }
+    let mut cmd = Command::new("idevicesyslog");
+    cmd.args(["-u", udid, "-p", process]);
+    Ok(Some(cmd.spawn()?))
+}
+
+/// Forwards the local TCP port to the same port of the connected device until the
+/// returned process is killed. Returns `None` if libimobiledevice is not installed.
+/// Runs `iproxy <port>:<port> -u <udid>` command
+pub fn forward_device_port(udid: &str, port: u16) -> Result<Option<Child>> {
+    if which::which("iproxy").is_err() {
+        return Ok(None);
+    }
+    let mut cmd = Command::new("iproxy");
+    cmd.arg(format!("{}:{}", port, port))
+        .args(["-u", udid])
+        .stdout(Stdio::null());
+    Ok(Some(cmd.spawn()?))
+}
@@ -1,7 +1,11 @@
+use super::sdk_path;
 use crate::{error::*, types::*};
 use std::{path::Path, process::Command};
 
-/// Compiles rust code for iOS.
+/// Compiles rust code for iOS, tvOS and watchOS. The SDK of the target is selected
+/// with `xcrun --sdk` and passed to the linker in `SDKROOT`. tvOS and watchOS targets
+/// build the standard library with `cargo +nightly rustc -Zbuild-std`. With `coverage`
+/// the crate is instrumented for source-based code coverage.
 ///
 /// Initialises `cargo rustc` [`Command`] with given args and return it.
 ///
@@ -15,9 +19,78 @@ pub fn compile_rust_for_ios(
     all_features: bool,
     no_default_features: bool,
     crate_types: &[CrateType],
+    coverage: bool,
+) -> Result<()> {
+    let sdk_root = sdk_path(build_target.sdk_name())?;
+    compile_rust_for_apple(
+        target,
+        build_target.rust_triple(),
+        project_path,
+        profile,
+        features,
+        all_features,
+        no_default_features,
+        crate_types,
+        coverage,
+        Some(&sdk_root),
+        build_target.requires_build_std(),
+    )
+}
+
+/// Compiles rust code for macOS.
+///
+/// Initialises `cargo rustc` [`Command`] with given args and return it.
+///
+/// [`Command`]: std::process::Command
+pub fn compile_rust_for_macos(
+    target: Target,
+    build_target: MacosTarget,
+    project_path: &Path,
+    profile: Profile,
+    features: Vec<String>,
+    all_features: bool,
+    no_default_features: bool,
+    coverage: bool,
+) -> Result<()> {
+    compile_rust_for_apple(
+        target,
+        build_target.rust_triple(),
+        project_path,
+        profile,
+        features,
+        all_features,
+        no_default_features,
+        &[],
+        coverage,
+        None,
+        false,
+    )
+}
+
+fn compile_rust_for_apple(
+    target: Target,
+    triple: &str,
+    project_path: &Path,
+    profile: Profile,
+    features: Vec<String>,
+    all_features: bool,
+    no_default_features: bool,
+    crate_types: &[CrateType],
+    coverage: bool,
+    sdk_root: Option<&Path>,
+    build_std: bool,
 ) -> Result<()> {
     let mut cargo = Command::new("cargo");
+    if build_std {
+        cargo.arg("+nightly");
+    }
     cargo.arg("rustc");
+    if build_std {
+        cargo.arg("-Zbuild-std");
+    }
+    if let Some(sdk_root) = sdk_root {
+        cargo.env("SDKROOT", sdk_root);
+    }
     match &target {
         Target::Bin(name) => cargo.args(["--bin", name]),
         Target::Example(name) => cargo.args(["--example", name]),
@@ -36,15 +109,22 @@ pub fn compile_rust_for_ios(
     if no_default_features {
         cargo.arg("--no-default-features");
     };
-    let triple = build_target.rust_triple();
     cargo.args(["--target", triple]);
+    let mut rustc_args = Vec::new();
     if !crate_types.is_empty() {
         // Creates a comma-separated string
         let crate_types: String =
             itertools::Itertools::intersperse(crate_types.iter().map(|v| v.as_ref()), ",")
                 .collect();
-        cargo.args(["--", "--crate-type", &crate_types]);
+        rustc_args.push("--crate-type".to_owned());
+        rustc_args.push(crate_types);
     };
+    if coverage {
+        rustc_args.push(crate::commands::COVERAGE_RUSTC_ARG.to_owned());
+    }
+    if !rustc_args.is_empty() {
+        cargo.arg("--").args(rustc_args);
+    }
     cargo.output_err(true)?;
     Ok(())
 }
@@ -3,15 +3,38 @@ use apple_bundle::{plist, prelude::*};
 use std::fs::File;
 use std::path::Path;
 
-/// Saves given InfoPlist in new `Info.plist` file.
-pub fn save_info_plist(out_dir: &Path, properties: &InfoPlist, binary: bool) -> Result<()> {
+/// Saves given InfoPlist in new `Info.plist` file. `extra` keys are added to the
+/// dictionary, overriding the keys of `properties`.
+pub fn save_info_plist(
+    out_dir: &Path,
+    properties: &InfoPlist,
+    extra: &plist::Dictionary,
+    binary: bool,
+) -> Result<()> {
     // Create Info.plist file
     let file_path = out_dir.join("Info.plist");
     let file = File::create(file_path)?;
-    // Write to Info.plist file
+    if extra.is_empty() {
+        // Write to Info.plist file
+        match binary {
+            true => plist::to_writer_binary(file, properties)?,
+            false => plist::to_writer_xml(file, properties)?,
+        }
+        return Ok(());
+    }
+    // Keys are merged through the plist dictionary, so any key is supported
+    let mut xml = Vec::new();
+    plist::to_writer_xml(&mut xml, properties)?;
+    let mut value = plist::Value::from_reader_xml(xml.as_slice())?;
+    let dictionary = value
+        .as_dictionary_mut()
+        .ok_or_else(|| AppleError::FailedToFindInfoPlist("not a dictionary".to_owned()))?;
+    for (key, extra_value) in extra {
+        dictionary.insert(key.clone(), extra_value.clone());
+    }
     match binary {
-        true => plist::to_writer_binary(file, properties)?,
-        false => plist::to_writer_xml(file, properties)?,
+        true => value.to_writer_binary(file)?,
+        false => value.to_writer_xml(file)?,
     }
     Ok(())
 }
@@ -104,7 +127,7 @@ mod tests {
             },
             ..Default::default()
         };
-        save_info_plist(dir.path(), &properties, false).unwrap();
+        save_info_plist(dir.path(), &properties, &Default::default(), false).unwrap();
         let file_path = dir.path().join("Info.plist");
         let result = std::fs::read_to_string(&file_path).unwrap();
         assert_eq!(result, PLIST_TEST_EXAMPLE.replace("    ", "\t"));
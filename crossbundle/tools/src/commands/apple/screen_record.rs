@@ -0,0 +1,29 @@
+use crate::error::*;
+use std::{path::Path, process::Command, time::Duration};
+
+/// Records the simulator screen into H.264 video for the given duration.
+/// Runs `xcrun simctl io <udid> recordVideo <output>` command and stops it with SIGINT,
+/// so the video is finalized
+pub fn simulator_record_video(udid: &str, output: &Path, duration: Duration) -> Result<()> {
+    let mut simctl = Command::new("xcrun");
+    simctl
+        .args([
+            "simctl",
+            "io",
+            udid,
+            "recordVideo",
+            "--codec",
+            "h264",
+            "--force",
+        ])
+        .arg(output);
+    let mut child = simctl.spawn()?;
+    std::thread::sleep(duration);
+    let mut kill = Command::new("kill");
+    kill.arg("-INT").arg(child.id().to_string());
+    kill.output_err(false)?;
+    if !child.wait()?.success() {
+        return Err(Error::CmdFailed(simctl, String::new(), String::new()));
+    }
+    Ok(())
+}
@@ -0,0 +1,312 @@
+use crate::error::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Name of the built-in profile with the status bar of App Store screenshots.
+pub const STORE_SIMULATOR_PROFILE: &str = "store";
+
+/// Preferences domain with the system language and region of the simulator.
+const GLOBAL_DOMAIN: &str = "Apple Global Domain";
+
+/// Status bar values shown instead of the real ones.
+/// Passed to `xcrun simctl status_bar <udid> override`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatusBarOverride {
+    /// Time text, e.g. `9:41`.
+    pub time: Option<String>,
+    /// `hide`, `wifi`, `3g`, `4g`, `lte`, `lte-a`, `lte+`, `5g`, `5g+`, `5g-uwb` or
+    /// `5g-uc`.
+    pub data_network: Option<String>,
+    /// `searching`, `failed` or `active`.
+    pub wifi_mode: Option<String>,
+    /// WiFi bars from 0 to 3.
+    pub wifi_bars: Option<u8>,
+    /// `notSupported`, `searching`, `failed` or `active`.
+    pub cellular_mode: Option<String>,
+    /// Cellular bars from 0 to 4.
+    pub cellular_bars: Option<u8>,
+    pub operator_name: Option<String>,
+    /// `charging`, `charged` or `discharging`.
+    pub battery_state: Option<String>,
+    /// Battery level from 0 to 100.
+    pub battery_level: Option<u8>,
+}
+
+impl StatusBarOverride {
+    /// Returns arguments of `simctl status_bar override`.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        let mut value = |flag: &str, value: Option<String>| {
+            if let Some(value) = value {
+                args.push(flag.to_owned());
+                args.push(value);
+            }
+        };
+        let number = |value: Option<u8>| value.map(|v| v.to_string());
+        value("--time", self.time.clone());
+        value("--dataNetwork", self.data_network.clone());
+        value("--wifiMode", self.wifi_mode.clone());
+        value("--wifiBars", number(self.wifi_bars));
+        value("--cellularMode", self.cellular_mode.clone());
+        value("--cellularBars", number(self.cellular_bars));
+        value("--operatorName", self.operator_name.clone());
+        value("--batteryState", self.battery_state.clone());
+        value("--batteryLevel", number(self.battery_level));
+        args
+    }
+}
+
+/// Simulator state from `package.metadata.apple.simulator_profiles` applied before the
+/// application launches, so screenshots and demos look the same on every machine:
+///
+/// ```toml
+/// [package.metadata.apple.simulator_profiles.demo]
+/// locale = "fr_FR"
+/// dark_mode = true
+/// push = "push/welcome.apns"
+///
+/// [package.metadata.apple.simulator_profiles.demo.status_bar]
+/// time = "9:41"
+/// battery_level = 100
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SimulatorProfile {
+    pub status_bar: Option<StatusBarOverride>,
+    /// System locale like `fr_FR`. The language is taken from its first part.
+    pub locale: Option<String>,
+    /// Dark or light appearance.
+    pub dark_mode: Option<bool>,
+    /// APNs payload JSON file sent to the application after launch.
+    pub push: Option<PathBuf>,
+}
+
+impl SimulatorProfile {
+    /// Returns built-in profiles: `store` shows the status bar of App Store
+    /// screenshots with 9:41, full battery and full signal.
+    pub fn builtin() -> BTreeMap<String, Self> {
+        let mut profiles = BTreeMap::new();
+        profiles.insert(
+            STORE_SIMULATOR_PROFILE.to_owned(),
+            Self {
+                status_bar: Some(StatusBarOverride {
+                    time: Some("9:41".to_owned()),
+                    data_network: Some("wifi".to_owned()),
+                    wifi_mode: Some("active".to_owned()),
+                    wifi_bars: Some(3),
+                    cellular_mode: Some("active".to_owned()),
+                    cellular_bars: Some(4),
+                    operator_name: Some(String::new()),
+                    battery_state: Some("charged".to_owned()),
+                    battery_level: Some(100),
+                }),
+                ..Default::default()
+            },
+        );
+        profiles
+    }
+
+    /// Returns the profile from the project profiles or the built-in one with the same
+    /// name. Project profiles take priority.
+    pub fn find(profiles: &BTreeMap<String, Self>, name: &str) -> Result<Self> {
+        let mut available = Self::builtin();
+        available.extend(profiles.clone());
+        available.get(name).cloned().ok_or_else(|| {
+            AppleError::SimulatorProfileNotFound(
+                name.to_owned(),
+                available.keys().cloned().collect::<Vec<_>>().join(", "),
+            )
+            .into()
+        })
+    }
+}
+
+/// Simulator state captured before applying a [`SimulatorProfile`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SimulatorStateSnapshot {
+    /// `light` or `dark`.
+    pub appearance: Option<String>,
+    /// `AppleLocale` preference.
+    pub locale: Option<String>,
+    /// First language of `AppleLanguages` preference.
+    pub language: Option<String>,
+}
+
+impl SimulatorStateSnapshot {
+    /// Reads the appearance and locale of the booted simulator.
+    pub fn capture(udid: &str) -> Result<Self> {
+        let appearance = simctl_output(&["ui", udid, "appearance"])?;
+        let locale = simctl_output(&[
+            "spawn",
+            udid,
+            "defaults",
+            "read",
+            GLOBAL_DOMAIN,
+            "AppleLocale",
+        ])
+        .ok();
+        let language = simctl_output(&[
+            "spawn",
+            udid,
+            "defaults",
+            "read",
+            GLOBAL_DOMAIN,
+            "AppleLanguages",
+        ])
+        .ok()
+        .and_then(|languages| first_plist_array_item(&languages));
+        Ok(Self {
+            appearance: Some(appearance).filter(|a| a == "light" || a == "dark"),
+            locale: locale.filter(|locale| !locale.is_empty()),
+            language,
+        })
+    }
+
+    /// Restores the captured state and clears the status bar override.
+    pub fn restore(&self, udid: &str) -> Result<()> {
+        simctl(&["status_bar", udid, "clear"])?;
+        if let Some(appearance) = &self.appearance {
+            simctl(&["ui", udid, "appearance", appearance])?;
+        }
+        if let Some(locale) = &self.locale {
+            set_simulator_locale(udid, locale, self.language.as_deref())?;
+        }
+        Ok(())
+    }
+}
+
+/// Applies the status bar, appearance and locale of the profile on the booted
+/// simulator. The locale is used by applications launched afterwards.
+pub fn apply_simulator_profile(udid: &str, profile: &SimulatorProfile) -> Result<()> {
+    if let Some(status_bar) = &profile.status_bar {
+        let mut args = vec!["status_bar", udid, "override"];
+        let override_args = status_bar.args();
+        args.extend(override_args.iter().map(String::as_str));
+        simctl(&args)?;
+    }
+    if let Some(dark_mode) = profile.dark_mode {
+        simctl(&[
+            "ui",
+            udid,
+            "appearance",
+            if dark_mode { "dark" } else { "light" },
+        ])?;
+    }
+    if let Some(locale) = &profile.locale {
+        set_simulator_locale(udid, locale, None)?;
+    }
+    Ok(())
+}
+
+/// Sets `AppleLocale` and `AppleLanguages` preferences of the simulator. The language
+/// is taken from the locale if it's not specified.
+fn set_simulator_locale(udid: &str, locale: &str, language: Option<&str>) -> Result<()> {
+    let language = language.unwrap_or_else(|| {
+        locale
+            .split(|c| c == '_' || c == '-')
+            .next()
+            .unwrap_or(locale)
+    });
+    simctl(&[
+        "spawn",
+        udid,
+        "defaults",
+        "write",
+        GLOBAL_DOMAIN,
+        "AppleLocale",
+        "-string",
+        &locale.replace('-', "_"),
+    ])?;
+    simctl(&[
+        "spawn",
+        udid,
+        "defaults",
+        "write",
+        GLOBAL_DOMAIN,
+        "AppleLanguages",
+        "-array",
+        language,
+    ])
+}
+
+/// Sends the push notification payload to the application on the simulator.
+/// Runs `xcrun simctl push <udid> <bundle id> <payload>` command
+pub fn send_simulator_push(udid: &str, bundle_id: &str, payload: &Path) -> Result<()> {
+    if !payload.exists() {
+        return Err(Error::PathNotFound(payload.to_owned()));
+    }
+    let mut simctl = Command::new("xcrun");
+    simctl
+        .args(["simctl", "push", udid, bundle_id])
+        .arg(payload);
+    simctl.output_err(true)?;
+    Ok(())
+}
+
+/// Runs `xcrun simctl <args>` command.
+fn simctl(args: &[&str]) -> Result<()> {
+    let mut simctl = Command::new("xcrun");
+    simctl.arg("simctl").args(args);
+    simctl.output_err(true)?;
+    Ok(())
+}
+
+/// Runs `xcrun simctl <args>` command and returns its trimmed stdout.
+fn simctl_output(args: &[&str]) -> Result<String> {
+    let mut simctl = Command::new("xcrun");
+    simctl.arg("simctl").args(args);
+    let output = simctl.output_err(false)?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// Returns the first item of the array printed by `defaults read`:
+/// `(\n    "en-US",\n    fr\n)`.
+fn first_plist_array_item(output: &str) -> Option<String> {
+    output
+        .trim()
+        .strip_prefix('(')?
+        .split(',')
+        .next()
+        .map(|item| {
+            item.trim()
+                .trim_end_matches(')')
+                .trim()
+                .trim_matches('"')
+                .to_owned()
+        })
+        .filter(|item| !item.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulator_profiles() {
+        let profiles: BTreeMap<String, SimulatorProfile> =
+            toml::from_str("[demo]\nlocale = \"fr_FR\"\n\n[demo.status_bar]\ntime = \"10:00\"\n")
+                .unwrap();
+        let demo = SimulatorProfile::find(&profiles, "demo").unwrap();
+        assert_eq!(
+            demo.status_bar.unwrap().args(),
+            ["--time".to_owned(), "10:00".to_owned()]
+        );
+        let store = SimulatorProfile::find(&profiles, "store").unwrap();
+        assert!(store
+            .status_bar
+            .unwrap()
+            .args()
+            .windows(2)
+            .any(|args| args == ["--batteryLevel", "100"]));
+        assert!(SimulatorProfile::find(&profiles, "missing").is_err());
+        assert_eq!(
+            first_plist_array_item("(\n    \"en-US\",\n    fr\n)\n").as_deref(),
+            Some("en-US")
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use crate::error::*;
+use std::{
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// Environment variable with the app-specific password of the Apple ID.
+pub const APP_STORE_CONNECT_PASSWORD_ENV: &str = "APP_STORE_CONNECT_PASSWORD";
+
+/// Credentials of App Store Connect.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AppStoreAuth {
+    /// App Store Connect API key. `altool` looks for `AuthKey_<key id>.p8` file in
+    /// `./private_keys`, `~/private_keys`, `~/.private_keys` and
+    /// `~/.appstoreconnect/private_keys`.
+    ApiKey { key_id: String, issuer_id: String },
+    /// Apple ID with the app-specific password read from
+    /// `APP_STORE_CONNECT_PASSWORD` environment variable.
+    AppleId { username: String },
+}
+
+impl AppStoreAuth {
+    /// API key ID or Apple ID, e.g. to record who uploaded the app.
+    pub fn key(&self) -> &str {
+        match self {
+            Self::ApiKey { key_id, .. } => key_id,
+            Self::AppleId { username } => username,
+        }
+    }
+}
+
+/// ## Upload app
+/// Uploads the IPA to App Store Connect, where it's processed and becomes available in
+/// TestFlight. Rejections of the upload are returned as typed errors with the fix.
+///
+/// See more: `xcrun altool --help`
+#[derive(Clone, Debug)]
+pub struct UploadApp {
+    ipa_path: PathBuf,
+    auth: AppStoreAuth,
+    validate_only: bool,
+}
+
+impl UploadApp {
+    pub fn new(ipa_path: &Path, auth: AppStoreAuth) -> Self {
+        Self {
+            ipa_path: ipa_path.to_owned(),
+            auth,
+            validate_only: false,
+        }
+    }
+
+    /// Only validates the IPA with App Store Connect without uploading it.
+    pub fn validate_only(&mut self, validate_only: bool) -> &mut Self {
+        self.validate_only = validate_only;
+        self
+    }
+
+    pub fn args(&self) -> Vec<String> {
+        let action = match self.validate_only {
+            true => "--validate-app",
+            false => "--upload-app",
+        };
+        let mut args = vec![
+            "altool".to_owned(),
+            action.to_owned(),
+            "--file".to_owned(),
+            self.ipa_path.to_string_lossy().into_owned(),
+            "--type".to_owned(),
+            "ios".to_owned(),
+        ];
+        match &self.auth {
+            AppStoreAuth::ApiKey { key_id, issuer_id } => {
+                args.extend(["--apiKey".to_owned(), key_id.clone()]);
+                args.extend(["--apiIssuer".to_owned(), issuer_id.clone()]);
+            }
+            AppStoreAuth::AppleId { username } => {
+                // The password is read by altool itself, so it's not visible in the
+                // process list
+                args.extend(["--username".to_owned(), username.clone()]);
+                args.extend([
+                    "--password".to_owned(),
+                    format!("@env:{}", APP_STORE_CONNECT_PASSWORD_ENV),
+                ]);
+            }
+        }
+        args.push("--verbose".to_owned());
+        args
+    }
+
+    /// Runs `xcrun altool --upload-app ...` command. Upload progress in percent is
+    /// passed to `on_progress` as it's reported.
+    pub fn run(&self, mut on_progress: impl FnMut(f32)) -> Result<()> {
+        if !self.ipa_path.exists() {
+            return Err(Error::PathNotFound(self.ipa_path.clone()));
+        }
+        if let AppStoreAuth::AppleId { .. } = self.auth {
+            if std::env::var(APP_STORE_CONNECT_PASSWORD_ENV).is_err() {
+                return Err(AppleError::UploadAuthenticationFailed(format!(
+                    "`{}` environment variable is not set",
+                    APP_STORE_CONNECT_PASSWORD_ENV
+                ))
+                .into());
+            }
+        }
+        let mut altool = Command::new("xcrun");
+        altool
+            .args(self.args())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = altool.spawn()?;
+        let mut stderr = child.stderr.take().unwrap();
+        let stderr_thread = std::thread::spawn(move || {
+            let mut output = String::new();
+            stderr.read_to_string(&mut output).ok();
+            output
+        });
+        let mut output = String::new();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        for line in stdout.lines() {
+            let line = line?;
+            if let Some(progress) = parse_upload_progress(&line) {
+                on_progress(progress);
+            }
+            output.push_str(&line);
+            output.push('\n');
+        }
+        let status = child.wait()?;
+        output.push_str(&stderr_thread.join().unwrap_or_default());
+        if !status.success() || output.contains("*** Error:") {
+            return Err(upload_error(&output).into());
+        }
+        Ok(())
+    }
+}
+
+/// Parses progress lines of `altool --verbose` output like `Upload progress: 42.5%`.
+pub fn parse_upload_progress(line: &str) -> Option<f32> {
+    if !line.to_lowercase().contains("progress") {
+        return None;
+    }
+    let (number, _) = line.split_once('%')?;
+    let start = number
+        .rfind(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .map_or(0, |i| i + 1);
+    number[start..].parse().ok()
+}
+
+/// Returns the typed error of the rejected upload by the `altool` output.
+pub fn upload_error(output: &str) -> AppleError {
+    let errors: Vec<&str> = output
+        .lines()
+        .filter_map(|line| line.split_once("*** Error:").map(|(_, error)| error.trim()))
+        .collect();
+    let message = match errors.is_empty() {
+        true => output
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .unwrap_or_default()
+            .trim()
+            .to_owned(),
+        false => errors.join(" "),
+    };
+    let contains = |patterns: &[&str]| patterns.iter().any(|pattern| output.contains(pattern));
+    if contains(&[
+        "Unable to authenticate",
+        "Authentication failed",
+        "NOT_AUTHORIZED",
+        "-19209",
+    ]) {
+        AppleError::UploadAuthenticationFailed(message)
+    } else if contains(&["No suitable application records were found"]) {
+        AppleError::AppRecordNotFound(message)
+    } else if contains(&["ITMS-4238", "ITMS-90062", "Redundant Binary Upload"]) {
+        AppleError::DuplicateBuildVersion(message)
+    } else if contains(&["ITMS-90035", "ITMS-90161", "Invalid Signature"]) {
+        AppleError::InvalidUploadSignature(message)
+    } else if contains(&[
+        "ITMS-90022",
+        "ITMS-90023",
+        "ITMS-90713",
+        "Missing required icon",
+    ]) {
+        AppleError::MissingAppIcon(message)
+    } else if contains(&["ITMS-90683", "Missing Purpose String"]) {
+        AppleError::MissingUsageDescriptions(message)
+    } else {
+        AppleError::UploadFailed(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upload_app() {
+        let upload = UploadApp::new(
+            Path::new("Game.ipa"),
+            AppStoreAuth::ApiKey {
+                key_id: "ABC123".to_owned(),
+                issuer_id: "issuer".to_owned(),
+            },
+        );
+        assert_eq!(
+            upload.args()[..6],
+            [
+                "altool",
+                "--upload-app",
+                "--file",
+                "Game.ipa",
+                "--type",
+                "ios"
+            ]
+        );
+        assert!(upload.args().contains(&"--apiKey".to_owned()));
+
+        assert_eq!(parse_upload_progress("Upload progress: 42.5%"), Some(42.5));
+        assert_eq!(parse_upload_progress("Uploaded 42%"), None);
+
+        let output = "*** Error: Error uploading 'Game.ipa'.
+*** Error: Redundant Binary Upload. You've already uploaded a build with build number '42'. (ITMS-4238)";
+        assert!(matches!(
+            upload_error(output),
+            AppleError::DuplicateBuildVersion(message) if message.starts_with("Error uploading 'Game.ipa'. Redundant")
+        ));
+        assert!(matches!(
+            upload_error("*** Error: Unable to authenticate. (-19209)"),
+            AppleError::UploadAuthenticationFailed(_)
+        ));
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{commands::LaunchOutcome, error::*};
+use std::{
+    path::Path,
+    process::Command,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Returns PID of the application running on the simulator or `None` if it's not running.
+/// Runs `xcrun simctl spawn <udid> launchctl list` command
+pub fn simulator_app_pid(udid: &str, bundle_id: &str) -> Result<Option<u32>> {
+    let mut launchctl = Command::new("xcrun");
+    launchctl.args(["simctl", "spawn", udid, "launchctl", "list"]);
+    let output = launchctl.output_err(false)?;
+    Ok(parse_launchctl_pid(
+        &String::from_utf8_lossy(&output.stdout),
+        bundle_id,
+    ))
+}
+
+/// Parses `launchctl list` output: `<pid or -> <status> <label>`. Applications are
+/// labeled `UIKitApplication:<bundle id>[...]`.
+pub fn parse_launchctl_pid(output: &str, bundle_id: &str) -> Option<u32> {
+    let label = format!("UIKitApplication:{}[", bundle_id);
+    output
+        .lines()
+        .find(|line| line.contains(&label))
+        .and_then(|line| line.split_whitespace().next())
+        .and_then(|pid| pid.parse().ok())
+}
+
+/// Watches the application process on the simulator for `duration`. Stops early if the
+/// process dies. Crashes are read from the new crash reports in
+/// `~/Library/Logs/DiagnosticReports`.
+pub fn watch_simulator_launch(
+    udid: &str,
+    bundle_id: &str,
+    duration: Duration,
+) -> Result<LaunchOutcome> {
+    let started = SystemTime::now();
+    let start = Instant::now();
+    let mut alive = simulator_app_pid(udid, bundle_id)?.is_some();
+    while alive && start.elapsed() < duration {
+        std::thread::sleep(Duration::from_millis(500));
+        alive = simulator_app_pid(udid, bundle_id)?.is_some();
+    }
+    if alive {
+        return Ok(LaunchOutcome::Running);
+    }
+    // Crash reports are written with a short delay after the process dies
+    std::thread::sleep(Duration::from_secs(2));
+    if let Some(reports_dir) =
+        dirs::home_dir().map(|home| home.join("Library/Logs/DiagnosticReports"))
+    {
+        if let Ok(reports) = std::fs::read_dir(reports_dir) {
+            for report in reports.flatten() {
+                let is_new = report
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .map(|modified| modified >= started)
+                    .unwrap_or(false);
+                if !is_new {
+                    continue;
+                }
+                let content = std::fs::read_to_string(report.path()).unwrap_or_default();
+                if content.contains(bundle_id) {
+                    return Ok(LaunchOutcome::Crashed(
+                        content.lines().take(40).collect::<Vec<_>>().join("\n"),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(LaunchOutcome::Exited)
+}
+
+/// Saves screenshot of the simulator screen in PNG format.
+/// Runs `xcrun simctl io <udid> screenshot <output>` command
+pub fn simulator_screenshot(udid: &str, output: &Path) -> Result<()> {
+    let mut simctl = Command::new("xcrun");
+    simctl
+        .args(["simctl", "io", udid, "screenshot"])
+        .arg(output);
+    simctl.output_err(false)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_launchctl_pid() {
+        let output = "PID\tStatus\tLabel\n-\t0\tcom.apple.mobilesafari\n4242\t0\tUIKitApplication:com.crossbow.game[8c1f][rb-legacy]\n";
+        assert_eq!(parse_launchctl_pid(output, "com.crossbow.game"), Some(4242));
+        assert_eq!(parse_launchctl_pid(output, "com.crossbow"), None);
+    }
+}
@@ -0,0 +1,302 @@
+use crate::error::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Result of the test case reported by `xcresulttool`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum XcTestResult {
+    Passed,
+    Failed,
+    Skipped,
+    ExpectedFailure,
+    Unknown,
+}
+
+impl XcTestResult {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "Passed" => Self::Passed,
+            "Failed" => Self::Failed,
+            "Skipped" => Self::Skipped,
+            "Expected Failure" => Self::ExpectedFailure,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Test case of the result bundle with its failure messages.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct XcTestCase {
+    /// Identifier like `LoginTests/testLogin()`.
+    pub identifier: String,
+    pub result: XcTestResult,
+    pub duration_secs: Option<f64>,
+    pub failures: Vec<String>,
+}
+
+/// File attached to the test case, e.g. a screenshot taken on failure.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct XcAttachment {
+    pub test: String,
+    pub name: String,
+    /// Path of the exported file.
+    pub path: PathBuf,
+    pub failure: bool,
+}
+
+/// Line coverage of the target.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct XcTargetCoverage {
+    pub name: String,
+    pub covered_lines: u64,
+    pub executable_lines: u64,
+    /// Share of covered lines from 0 to 1.
+    pub line_coverage: f64,
+}
+
+/// Line coverage reported by `xccov`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct XcCoverage {
+    pub covered_lines: u64,
+    pub executable_lines: u64,
+    /// Share of covered lines from 0 to 1.
+    pub line_coverage: f64,
+    #[serde(default)]
+    pub targets: Vec<XcTargetCoverage>,
+}
+
+/// Structured content of the `.xcresult` bundle written by `xcodebuild test
+/// -resultBundlePath`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct XcResultSummary {
+    pub tests: Vec<XcTestCase>,
+    pub attachments: Vec<XcAttachment>,
+    pub coverage: Option<XcCoverage>,
+}
+
+impl XcResultSummary {
+    /// Returns the number of tests with the result.
+    pub fn count(&self, result: XcTestResult) -> usize {
+        self.tests
+            .iter()
+            .filter(|test| test.result == result)
+            .count()
+    }
+
+    /// Returns tests that failed.
+    pub fn failed(&self) -> impl Iterator<Item = &XcTestCase> {
+        self.tests
+            .iter()
+            .filter(|test| test.result == XcTestResult::Failed)
+    }
+}
+
+/// Reads test results and coverage of the result bundle. Attachments are exported into
+/// `attachments_dir` if it's specified. Coverage is `None` if the tests were run without
+/// code coverage.
+pub fn read_xcresult(bundle: &Path, attachments_dir: Option<&Path>) -> Result<XcResultSummary> {
+    if !bundle.exists() {
+        return Err(Error::PathNotFound(bundle.to_owned()));
+    }
+    let mut xcresulttool = Command::new("xcrun");
+    xcresulttool
+        .args(["xcresulttool", "get", "test-results", "tests", "--path"])
+        .arg(bundle);
+    let output = xcresulttool.output_err(false)?;
+    let tests = parse_xcresult_tests(&String::from_utf8_lossy(&output.stdout))?;
+    let attachments = match attachments_dir {
+        Some(dir) => export_xcresult_attachments(bundle, dir)?,
+        None => Vec::new(),
+    };
+    let mut xccov = Command::new("xcrun");
+    xccov
+        .args(["xccov", "view", "--report", "--json"])
+        .arg(bundle);
+    let coverage = xccov
+        .output_err(false)
+        .ok()
+        .and_then(|output| serde_json::from_slice(&output.stdout).ok());
+    Ok(XcResultSummary {
+        tests,
+        attachments,
+        coverage,
+    })
+}
+
+/// Exports attachments of the result bundle into the directory.
+/// Runs `xcrun xcresulttool export attachments --path <bundle> --output-path <dir>`
+pub fn export_xcresult_attachments(bundle: &Path, dir: &Path) -> Result<Vec<XcAttachment>> {
+    std::fs::create_dir_all(dir)?;
+    let mut xcresulttool = Command::new("xcrun");
+    xcresulttool
+        .args(["xcresulttool", "export", "attachments", "--path"])
+        .arg(bundle)
+        .arg("--output-path")
+        .arg(dir);
+    xcresulttool.output_err(true)?;
+    let manifest = std::fs::read_to_string(dir.join("manifest.json"))?;
+    parse_xcresult_attachments(&manifest, dir)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TestResults {
+    #[serde(default)]
+    test_nodes: Vec<TestNode>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TestNode {
+    name: String,
+    node_type: String,
+    node_identifier: Option<String>,
+    result: Option<String>,
+    duration_in_seconds: Option<f64>,
+    duration: Option<String>,
+    #[serde(default)]
+    children: Vec<TestNode>,
+}
+
+/// Parses `xcresulttool get test-results tests` output: a tree of test plans, bundles
+/// and suites with test cases and their failure messages as leaves.
+pub fn parse_xcresult_tests(output: &str) -> Result<Vec<XcTestCase>> {
+    let results: TestResults = serde_json::from_str(output)?;
+    let mut tests = Vec::new();
+    collect_test_cases(&results.test_nodes, &mut tests);
+    Ok(tests)
+}
+
+fn collect_test_cases(nodes: &[TestNode], tests: &mut Vec<XcTestCase>) {
+    for node in nodes {
+        if node.node_type != "Test Case" {
+            collect_test_cases(&node.children, tests);
+            continue;
+        }
+        let failures = node
+            .children
+            .iter()
+            .filter(|child| child.node_type == "Failure Message")
+            .map(|child| child.name.clone())
+            .collect();
+        // Older versions report the duration only as text like `0.52s`
+        let duration_secs = node.duration_in_seconds.or_else(|| {
+            node.duration
+                .as_ref()
+                .and_then(|duration| duration.trim_end_matches('s').parse().ok())
+        });
+        tests.push(XcTestCase {
+            identifier: node
+                .node_identifier
+                .clone()
+                .unwrap_or_else(|| node.name.clone()),
+            result: XcTestResult::from_name(node.result.as_deref().unwrap_or_default()),
+            duration_secs,
+            failures,
+        });
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AttachmentsManifestEntry {
+    test_identifier: String,
+    #[serde(default)]
+    attachments: Vec<AttachmentsManifestFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AttachmentsManifestFile {
+    exported_file_name: String,
+    suggested_human_readable_name: Option<String>,
+    #[serde(default)]
+    is_associated_with_failure: bool,
+}
+
+/// Parses `manifest.json` written by `xcresulttool export attachments`.
+pub fn parse_xcresult_attachments(manifest: &str, dir: &Path) -> Result<Vec<XcAttachment>> {
+    let entries: Vec<AttachmentsManifestEntry> = serde_json::from_str(manifest)?;
+    let mut attachments = Vec::new();
+    for entry in entries {
+        for file in entry.attachments {
+            attachments.push(XcAttachment {
+                test: entry.test_identifier.clone(),
+                name: file
+                    .suggested_human_readable_name
+                    .unwrap_or_else(|| file.exported_file_name.clone()),
+                path: dir.join(&file.exported_file_name),
+                failure: file.is_associated_with_failure,
+            });
+        }
+    }
+    Ok(attachments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xcresult() {
+        let output = r#"{
+            "devices": [],
+            "testNodes": [{
+                "name": "Game", "nodeType": "Test Plan", "result": "Failed",
+                "children": [{
+                    "name": "GameTests", "nodeType": "Unit test bundle", "result": "Failed",
+                    "children": [{
+                        "name": "LoginTests", "nodeType": "Test Suite", "result": "Failed",
+                        "children": [
+                            {
+                                "name": "testLogin()", "nodeType": "Test Case",
+                                "nodeIdentifier": "LoginTests/testLogin()",
+                                "result": "Failed", "duration": "0.52s",
+                                "children": [{
+                                    "name": "LoginTests.swift:12: XCTAssertTrue failed",
+                                    "nodeType": "Failure Message", "result": "Failed"
+                                }]
+                            },
+                            {
+                                "name": "testLogout()", "nodeType": "Test Case",
+                                "nodeIdentifier": "LoginTests/testLogout()",
+                                "result": "Passed", "durationInSeconds": 0.1
+                            }
+                        ]
+                    }]
+                }]
+            }]
+        }"#;
+        let summary = XcResultSummary {
+            tests: parse_xcresult_tests(output).unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(summary.count(XcTestResult::Passed), 1);
+        let failed: Vec<_> = summary.failed().collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].identifier, "LoginTests/testLogin()");
+        assert_eq!(failed[0].duration_secs, Some(0.52));
+        assert_eq!(
+            failed[0].failures,
+            ["LoginTests.swift:12: XCTAssertTrue failed"]
+        );
+
+        let manifest = r#"[{
+            "testIdentifier": "LoginTests/testLogin()",
+            "attachments": [{
+                "exportedFileName": "A1B2.png",
+                "suggestedHumanReadableName": "Screenshot",
+                "isAssociatedWithFailure": true
+            }]
+        }]"#;
+        let attachments = parse_xcresult_attachments(manifest, Path::new("out")).unwrap();
+        assert_eq!(attachments[0].path, Path::new("out").join("A1B2.png"));
+        assert!(attachments[0].failure);
+    }
+}
@@ -0,0 +1,187 @@
+use crate::error::*;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    process::{Child, Command, Stdio},
+};
+
+/// Simulator runtime installed with Xcode, e.g. `iOS 16.4`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatorRuntime {
+    /// Identifier like `com.apple.CoreSimulator.SimRuntime.iOS-16-4`.
+    pub identifier: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub is_available: bool,
+}
+
+/// Simulator device created in Xcode.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatorDevice {
+    pub udid: String,
+    pub name: String,
+    /// State like `Booted` or `Shutdown`.
+    pub state: String,
+    /// Identifier of the runtime the device runs.
+    #[serde(skip)]
+    pub runtime: String,
+    #[serde(default)]
+    pub is_available: bool,
+}
+
+impl SimulatorDevice {
+    pub fn is_booted(&self) -> bool {
+        self.state == "Booted"
+    }
+
+    /// Returns OS version numbers of the runtime, e.g. `[16, 4]` for `iOS-16-4`.
+    pub fn runtime_version(&self) -> Vec<u32> {
+        self.runtime
+            .rsplit('.')
+            .next()
+            .unwrap_or_default()
+            .split('-')
+            .filter_map(|part| part.parse().ok())
+            .collect()
+    }
+}
+
+/// Lists installed simulator runtimes.
+/// Runs `xcrun simctl list runtimes --json` command
+pub fn simulator_runtimes() -> Result<Vec<SimulatorRuntime>> {
+    let mut simctl = Command::new("xcrun");
+    simctl.args(["simctl", "list", "runtimes", "--json"]);
+    let output = simctl.output_err(true)?;
+    parse_simctl_runtimes(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Lists simulator devices of all runtimes.
+/// Runs `xcrun simctl list devices --json` command
+pub fn simulator_devices() -> Result<Vec<SimulatorDevice>> {
+    let mut simctl = Command::new("xcrun");
+    simctl.args(["simctl", "list", "devices", "--json"]);
+    let output = simctl.output_err(true)?;
+    parse_simctl_devices(&String::from_utf8_lossy(&output.stdout))
+}
+
+pub fn parse_simctl_runtimes(json: &str) -> Result<Vec<SimulatorRuntime>> {
+    #[derive(Deserialize)]
+    struct RuntimeList {
+        runtimes: Vec<SimulatorRuntime>,
+    }
+    Ok(serde_json::from_str::<RuntimeList>(json)?.runtimes)
+}
+
+pub fn parse_simctl_devices(json: &str) -> Result<Vec<SimulatorDevice>> {
+    #[derive(Deserialize)]
+    struct DeviceList {
+        devices: BTreeMap<String, Vec<SimulatorDevice>>,
+    }
+    let list: DeviceList = serde_json::from_str(json)?;
+    Ok(list
+        .devices
+        .into_iter()
+        .flat_map(|(runtime, devices)| {
+            devices.into_iter().map(move |device| SimulatorDevice {
+                runtime: runtime.clone(),
+                ..device
+            })
+        })
+        .collect())
+}
+
+/// Finds the available simulator by UDID or name. If several simulators have the name,
+/// the booted one or the one with the newest runtime is returned.
+pub fn find_simulator<'a>(
+    devices: &'a [SimulatorDevice],
+    name_or_udid: &str,
+) -> Option<&'a SimulatorDevice> {
+    devices
+        .iter()
+        .filter(|device| device.is_available)
+        .filter(|device| device.udid == name_or_udid || device.name == name_or_udid)
+        .max_by_key(|device| (device.is_booted(), device.runtime_version()))
+}
+
+/// Boots the simulator and waits until it finishes booting. Does nothing if it's
+/// already booted.
+/// Runs `xcrun simctl bootstatus <udid> -b` command
+pub fn boot_simulator(udid: &str) -> Result<()> {
+    let mut simctl = Command::new("xcrun");
+    simctl.args(["simctl", "bootstatus", udid, "-b"]);
+    simctl.output_err(true)?;
+    Ok(())
+}
+
+/// Installs the application bundle on the booted simulator.
+/// Runs `xcrun simctl install <udid> <app path>` command
+pub fn install_simulator_app(udid: &str, app_path: &Path) -> Result<()> {
+    let mut simctl = Command::new("xcrun");
+    simctl.args(["simctl", "install", udid]).arg(app_path);
+    simctl.output_err(true)?;
+    Ok(())
+}
+
+/// Launches the installed application, terminating the running instance first. Stdout
+/// and stderr of the application are written into stdout of the returned process
+/// until the application exits.
+/// Runs `xcrun simctl launch --console-pty --terminate-running-process <udid> <bundle
+/// id> [args]` command
+pub fn launch_simulator_console(udid: &str, bundle_id: &str, args: &[String]) -> Result<Child> {
+    let mut simctl = Command::new("xcrun");
+    simctl
+        .args([
+            "simctl",
+            "launch",
+            "--console-pty",
+            "--terminate-running-process",
+            udid,
+            bundle_id,
+        ])
+        .args(args)
+        .stdout(Stdio::piped());
+    Ok(simctl.spawn()?)
+}
+
+/// Terminates the application on the simulator if it's running.
+/// Runs `xcrun simctl terminate <udid> <bundle id>`
+pub fn terminate_simulator_app(udid: &str, bundle_id: &str) -> Result<()> {
+    // Fails if the application is not running, so the status is ignored
+    Command::new("xcrun")
+        .args(["simctl", "terminate", udid, bundle_id])
+        .output()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_simctl() {
+        let runtimes = r#"{"runtimes": [{"identifier": "com.apple.CoreSimulator.SimRuntime.iOS-16-4",
+            "name": "iOS 16.4", "version": "16.4", "isAvailable": true, "buildversion": "20E247"}]}"#;
+        let runtimes = parse_simctl_runtimes(runtimes).unwrap();
+        assert_eq!(runtimes[0].name, "iOS 16.4");
+
+        let devices = r#"{"devices": {
+            "com.apple.CoreSimulator.SimRuntime.iOS-15-5": [
+                {"udid": "A", "name": "iPhone 13", "state": "Shutdown", "isAvailable": true}
+            ],
+            "com.apple.CoreSimulator.SimRuntime.iOS-16-4": [
+                {"udid": "B", "name": "iPhone 13", "state": "Shutdown", "isAvailable": true},
+                {"udid": "C", "name": "iPhone 14", "state": "Booted", "isAvailable": false}
+            ]
+        }}"#;
+        let devices = parse_simctl_devices(devices).unwrap();
+        assert_eq!(devices.len(), 3);
+        assert_eq!(devices[1].runtime_version(), [16, 4]);
+        assert_eq!(find_simulator(&devices, "iPhone 13").unwrap().udid, "B");
+        assert_eq!(find_simulator(&devices, "A").unwrap().udid, "A");
+        assert_eq!(find_simulator(&devices, "iPhone 14"), None);
+    }
+}
@@ -0,0 +1,162 @@
+use std::cmp::Ordering;
+
+/// Platform the API requirement applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiPlatform {
+    Android,
+    Ios,
+}
+
+impl std::fmt::Display for ApiPlatform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Android => write!(f, "Android API level"),
+            Self::Ios => write!(f, "iOS"),
+        }
+    }
+}
+
+/// Minimum platform version needed by a crossbow runtime API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiRequirement {
+    /// Crossbow plugin crate like `play-core` or crossbow permission like `photos`.
+    pub api: &'static str,
+    pub platform: ApiPlatform,
+    /// Android API level like `26` or iOS version like `14.0`.
+    pub min_version: &'static str,
+    /// Platform API that is missing on older versions.
+    pub reason: &'static str,
+}
+
+/// Requirements of crossbow plugins and permissions. Keep in sync with the Android
+/// libraries used by plugins and with the platform calls of `crossbow-android` and
+/// `crossbow-ios` permission requests.
+pub const API_REQUIREMENTS: &[ApiRequirement] = &[
+    ApiRequirement {
+        api: "play-core",
+        platform: ApiPlatform::Android,
+        min_version: "21",
+        reason: "Play In-App Updates library 2.0 requires API 21",
+    },
+    ApiRequirement {
+        api: "admob-android",
+        platform: ApiPlatform::Android,
+        min_version: "19",
+        reason: "Google Mobile Ads SDK 21 requires API 19",
+    },
+    ApiRequirement {
+        api: "location-always",
+        platform: ApiPlatform::Android,
+        min_version: "29",
+        reason: "ACCESS_BACKGROUND_LOCATION permission was added in API 29, so the request \
+                 is never granted on older devices",
+    },
+    ApiRequirement {
+        api: "phone",
+        platform: ApiPlatform::Android,
+        min_version: "26",
+        reason: "ANSWER_PHONE_CALLS permission was added in API 26, so the request is never \
+                 granted on older devices",
+    },
+    ApiRequirement {
+        api: "photos",
+        platform: ApiPlatform::Ios,
+        min_version: "14.0",
+        reason: "PHPhotoLibrary requestAuthorizationForAccessLevel:handler: is available \
+                 since iOS 14",
+    },
+    ApiRequirement {
+        api: "speech",
+        platform: ApiPlatform::Ios,
+        min_version: "10.0",
+        reason: "SFSpeechRecognizer is available since iOS 10",
+    },
+    ApiRequirement {
+        api: "media",
+        platform: ApiPlatform::Ios,
+        min_version: "9.3",
+        reason: "MPMediaLibrary requestAuthorization: is available since iOS 9.3",
+    },
+];
+
+/// API used by the crate that needs a higher platform version than configured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiLevelWarning {
+    pub requirement: ApiRequirement,
+    /// Configured minimum version: `min_sdk_version` or `MinimumOSVersion`.
+    pub configured: String,
+}
+
+impl std::fmt::Display for ApiLevelWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "`{}` needs {} {}, but the minimum is {}: {}",
+            self.requirement.api,
+            self.requirement.platform,
+            self.requirement.min_version,
+            self.configured,
+            self.requirement.reason
+        )
+    }
+}
+
+/// Checks used APIs against the configured minimum platform version. `used_apis` are
+/// names of crate dependencies and crossbow permissions.
+pub fn check_api_levels(
+    used_apis: &[String],
+    platform: ApiPlatform,
+    configured_min: &str,
+) -> Vec<ApiLevelWarning> {
+    API_REQUIREMENTS
+        .iter()
+        .filter(|requirement| requirement.platform == platform)
+        .filter(|requirement| used_apis.iter().any(|api| api == requirement.api))
+        .filter(|requirement| {
+            compare_versions(configured_min, requirement.min_version) == Ordering::Less
+        })
+        .map(|requirement| ApiLevelWarning {
+            requirement: *requirement,
+            configured: configured_min.to_owned(),
+        })
+        .collect()
+}
+
+/// Compares dotted versions like `9.3` and `14.0` number by number. Missing numbers are
+/// zeros.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |version: &str| -> Vec<u32> {
+        version
+            .trim()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    let (a, b) = (parse(a), parse(b));
+    for i in 0..a.len().max(b.len()) {
+        let ordering = a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0));
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_api_levels() {
+        let used_apis = vec!["play-core".to_owned(), "photos".to_owned()];
+        let warnings = check_api_levels(&used_apis, ApiPlatform::Android, "19");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].requirement.api, "play-core");
+        assert!(check_api_levels(&used_apis, ApiPlatform::Android, "21").is_empty());
+        assert_eq!(
+            check_api_levels(&used_apis, ApiPlatform::Ios, "13.4").len(),
+            1
+        );
+        assert!(check_api_levels(&used_apis, ApiPlatform::Ios, "14").is_empty());
+    }
+}
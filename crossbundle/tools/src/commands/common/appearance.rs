@@ -0,0 +1,99 @@
+/// Device settings the application is launched with, used to check localization and UI
+/// scaling. `None` keeps the current device setting.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeviceAppearance {
+    /// BCP 47 language tag like `fr-FR`.
+    pub locale: Option<String>,
+    /// Font scale where `1.0` is the default size.
+    pub font_scale: Option<f32>,
+    /// Dark or light system theme.
+    pub dark_mode: Option<bool>,
+}
+
+impl DeviceAppearance {
+    /// Returns all combinations of the values. Empty dimension keeps the device setting.
+    pub fn matrix(locales: &[String], font_scales: &[f32], dark_modes: &[bool]) -> Vec<Self> {
+        fn values<T: Clone>(values: &[T]) -> Vec<Option<T>> {
+            if values.is_empty() {
+                vec![None]
+            } else {
+                values.iter().cloned().map(Some).collect()
+            }
+        }
+        let mut matrix = Vec::new();
+        for locale in values(locales) {
+            for font_scale in values(font_scales) {
+                for dark_mode in values(dark_modes) {
+                    matrix.push(Self {
+                        locale: locale.clone(),
+                        font_scale,
+                        dark_mode,
+                    });
+                }
+            }
+        }
+        matrix
+    }
+
+    /// Returns file name without extension for the screenshot taken with this
+    /// appearance, e.g. `fr-FR_1.3x_dark`.
+    pub fn file_stem(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(locale) = &self.locale {
+            parts.push(locale.clone());
+        }
+        if let Some(font_scale) = self.font_scale {
+            parts.push(format!("{}x", font_scale));
+        }
+        if let Some(dark_mode) = self.dark_mode {
+            parts.push(if dark_mode { "dark" } else { "light" }.to_owned());
+        }
+        if parts.is_empty() {
+            return "default".to_owned();
+        }
+        parts.join("_")
+    }
+}
+
+impl std::fmt::Display for DeviceAppearance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(locale) = &self.locale {
+            parts.push(format!("locale {}", locale));
+        }
+        if let Some(font_scale) = self.font_scale {
+            parts.push(format!("font scale {}", font_scale));
+        }
+        if let Some(dark_mode) = self.dark_mode {
+            parts.push(if dark_mode { "dark mode" } else { "light mode" }.to_owned());
+        }
+        if parts.is_empty() {
+            return write!(f, "device settings");
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_appearance_matrix() {
+        let matrix = DeviceAppearance::matrix(
+            &["en".to_owned(), "fr-FR".to_owned()],
+            &[1.3],
+            &[true, false],
+        );
+        assert_eq!(matrix.len(), 4);
+        assert_eq!(matrix[1].file_stem(), "en_1.3x_light");
+        assert_eq!(
+            matrix[2].to_string(),
+            "locale fr-FR, font scale 1.3, dark mode"
+        );
+        assert_eq!(
+            DeviceAppearance::matrix(&[], &[], &[]),
+            vec![DeviceAppearance::default()]
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use crate::error::*;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Environment variable with the path to the audit log file.
+pub const AUDIT_LOG_ENV: &str = "CROSSBUNDLE_AUDIT_LOG";
+
+/// Single signing or publishing operation recorded in the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditEvent {
+    /// UTC time in RFC 3339 format, e.g. `2022-10-17T12:34:56Z`.
+    pub timestamp: String,
+    /// Operation name, e.g. `sign_apk`, `sign_aab` or `codesign`.
+    pub operation: String,
+    pub artifact: PathBuf,
+    /// SHA-256 hash of the artifact after the operation.
+    pub sha256: String,
+    /// Identifier of the key, e.g. `release.keystore:upload` or codesign identity.
+    pub key: String,
+    /// User or CI identity that performed the operation.
+    pub actor: String,
+    /// CI run identifier if the operation was performed on CI.
+    pub ci_run: Option<String>,
+}
+
+impl AuditEvent {
+    /// Creates event for given artifact with current time, actor and artifact hash.
+    pub fn new(operation: &str, artifact: &Path, key: &str) -> Result<Self> {
+        Ok(Self {
+            timestamp: rfc3339_now(),
+            operation: operation.to_owned(),
+            artifact: artifact.to_owned(),
+            sha256: sha256_file(artifact)?,
+            key: key.to_owned(),
+            actor: audit_actor(),
+            ci_run: ci_run(),
+        })
+    }
+}
+
+/// Append-only JSONL log of signing and publishing operations.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_owned(),
+        }
+    }
+
+    /// Returns audit log from [`AUDIT_LOG_ENV`] environment variable or `None` if it's
+    /// not set.
+    pub fn from_env() -> Option<Self> {
+        std::env::var_os(AUDIT_LOG_ENV).map(|path| Self::new(Path::new(&path)))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends the event as a single JSON line. The file is never truncated.
+    pub fn record(&self, event: &AuditEvent) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut line = serde_json::to_string(event)?;
+        line.push('\n');
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        file.sync_all()?;
+        Ok(())
+    }
+}
+
+/// Returns hex-encoded SHA-256 hash of the file. Directories are not supported.
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// Returns identity of the user or CI that runs the command.
+fn audit_actor() -> String {
+    [
+        "CROSSBUNDLE_AUDIT_ACTOR",
+        "GITHUB_ACTOR",
+        "GITLAB_USER_LOGIN",
+        "BUILD_REQUESTEDFOR",
+        "USER",
+        "USERNAME",
+    ]
+    .iter()
+    .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+    .unwrap_or_else(|| "unknown".to_owned())
+}
+
+pub(crate) fn ci_run() -> Option<String> {
+    [
+        "GITHUB_RUN_ID",
+        "CI_PIPELINE_ID",
+        "BUILD_BUILDID",
+        "BUILD_ID",
+    ]
+    .iter()
+    .find_map(|var| {
+        std::env::var(var)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .map(|value| format!("{}={}", var, value))
+    })
+}
+
+/// Formats current UTC time in RFC 3339 format.
+pub(crate) fn rfc3339_now() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, secs_of_day) = (secs / 86400, secs % 86400);
+    // Converts days since 1970-01-01 into civil date. See
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_log_is_appended() {
+        let dir = tempfile::tempdir().unwrap();
+        let artifact = dir.path().join("app.apk");
+        std::fs::write(&artifact, b"apk").unwrap();
+        let log = AuditLog::new(&dir.path().join("audit").join("log.jsonl"));
+        let event =
+            AuditEvent::new("sign_apk", &artifact, "debug.keystore:androiddebugkey").unwrap();
+        assert_eq!(
+            event.sha256,
+            "dd37c2d7274f7ea982cb83390c36918fee9ce8889073c44b68cdc00bdb8c3e04"
+        );
+        log.record(&event).unwrap();
+        log.record(&event).unwrap();
+        let content = std::fs::read_to_string(log.path()).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        let line: serde_json::Value =
+            serde_json::from_str(content.lines().next().unwrap()).unwrap();
+        assert_eq!(line["operation"], "sign_apk");
+        assert!(line["timestamp"].as_str().unwrap().ends_with('Z'));
+    }
+}
@@ -0,0 +1,269 @@
+use crate::error::*;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Number of cached entries kept for every step.
+const CACHE_ENTRIES_PER_STEP: usize = 3;
+
+/// SHA-256 fingerprint of the inputs of a packaging step. Files are hashed by content,
+/// so touching a file without changing it keeps the fingerprint.
+#[derive(Clone)]
+pub struct Fingerprint {
+    hasher: Sha256,
+}
+
+impl Fingerprint {
+    pub fn new(step: &str) -> Self {
+        let mut fingerprint = Self {
+            hasher: Sha256::new(),
+        };
+        fingerprint.update("step", step.as_bytes());
+        fingerprint
+    }
+
+    /// Adds the named value, e.g. a target SDK version or a signing key alias.
+    pub fn value(&mut self, name: &str, value: impl ToString) -> &mut Self {
+        self.update(name, value.to_string().as_bytes());
+        self
+    }
+
+    /// Adds contents of the file or all files of the directory with their relative
+    /// paths. Missing paths are recorded as missing.
+    pub fn path(&mut self, name: &str, path: &Path) -> Result<&mut Self> {
+        if path.is_dir() {
+            let mut files = Vec::new();
+            collect_files(path, &mut files)?;
+            files.sort();
+            self.update(name, b"dir");
+            for file in files {
+                let relative = file.strip_prefix(path).unwrap_or(&file);
+                self.update(
+                    "file",
+                    relative.to_string_lossy().replace('\\', "/").as_bytes(),
+                );
+                self.update("content", &std::fs::read(&file)?);
+            }
+        } else if path.is_file() {
+            self.update(name, &std::fs::read(path)?);
+        } else {
+            self.update(name, b"missing");
+        }
+        Ok(self)
+    }
+
+    /// Adds the optional file or directory.
+    pub fn optional_path(&mut self, name: &str, path: Option<&Path>) -> Result<&mut Self> {
+        match path {
+            Some(path) => self.path(name, path),
+            None => {
+                self.update(name, b"none");
+                Ok(self)
+            }
+        }
+    }
+
+    /// Returns hex-encoded fingerprint.
+    pub fn finish(&self) -> String {
+        self.hasher
+            .clone()
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Hashes the length-prefixed name and value, so different inputs can't produce the
+    /// same stream of bytes.
+    fn update(&mut self, name: &str, value: &[u8]) {
+        self.hasher.update((name.len() as u64).to_le_bytes());
+        self.hasher.update(name.as_bytes());
+        self.hasher.update((value.len() as u64).to_le_bytes());
+        self.hasher.update(value);
+    }
+}
+
+/// Cache of packaging step outputs keyed by fingerprints of their inputs. Entries are
+/// stored in `<cache dir>/<step>/<fingerprint>`.
+#[derive(Debug, Clone)]
+pub struct BuildCache {
+    cache_dir: PathBuf,
+    disabled: bool,
+}
+
+impl BuildCache {
+    /// Creates the cache in `target/crossbundle/cache`.
+    pub fn new(target_dir: &Path) -> Self {
+        Self {
+            cache_dir: target_dir.join("crossbundle").join("cache"),
+            disabled: false,
+        }
+    }
+
+    /// Always run steps without reading or storing their outputs.
+    pub fn disabled(&mut self, disabled: bool) -> &mut Self {
+        self.disabled = disabled;
+        self
+    }
+
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+
+    /// Restores outputs of the step from the cache, or runs the step and stores its
+    /// outputs. Outputs are files or directories. Returns `true` if the outputs were
+    /// restored.
+    pub fn cached<F>(
+        &self,
+        step: &str,
+        fingerprint: &Fingerprint,
+        outputs: &[&Path],
+        run: F,
+    ) -> Result<bool>
+    where
+        F: FnOnce() -> Result<()>,
+    {
+        if self.disabled {
+            run()?;
+            return Ok(false);
+        }
+        let entry_dir = self.cache_dir.join(step).join(fingerprint.finish());
+        if entry_dir.join(".complete").exists() {
+            for (i, output) in outputs.iter().enumerate() {
+                copy_path(&entry_dir.join(i.to_string()), output)?;
+            }
+            // Used entries are kept when old ones are pruned
+            std::fs::write(entry_dir.join(".complete"), [])?;
+            return Ok(true);
+        }
+        run()?;
+        std::fs::remove_dir_all(&entry_dir).ok();
+        std::fs::create_dir_all(&entry_dir)?;
+        for (i, output) in outputs.iter().enumerate() {
+            copy_path(output, &entry_dir.join(i.to_string()))?;
+        }
+        // Entries without the marker are incomplete, e.g. if the build was interrupted
+        std::fs::write(entry_dir.join(".complete"), [])?;
+        self.prune(step)?;
+        Ok(false)
+    }
+
+    /// Removes all cached entries.
+    pub fn clear(&self) -> Result<()> {
+        if self.cache_dir.exists() {
+            std::fs::remove_dir_all(&self.cache_dir)?;
+        }
+        Ok(())
+    }
+
+    /// Removes the least recently used entries of the step.
+    fn prune(&self, step: &str) -> Result<()> {
+        let mut entries = Vec::new();
+        for entry in std::fs::read_dir(self.cache_dir.join(step))? {
+            let path = entry?.path();
+            let used = std::fs::metadata(path.join(".complete"))
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            entries.push((used, path));
+        }
+        entries.sort();
+        let stale = entries.len().saturating_sub(CACHE_ENTRIES_PER_STEP);
+        for (_, path) in entries.into_iter().take(stale) {
+            std::fs::remove_dir_all(path)?;
+        }
+        Ok(())
+    }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Replaces `to` with a copy of the file or the directory.
+fn copy_path(from: &Path, to: &Path) -> Result<()> {
+    if to.is_dir() {
+        std::fs::remove_dir_all(to)?;
+    } else if to.exists() {
+        std::fs::remove_file(to)?;
+    }
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if from.is_dir() {
+        let mut files = Vec::new();
+        collect_files(from, &mut files)?;
+        std::fs::create_dir_all(to)?;
+        for file in files {
+            let target = to.join(file.strip_prefix(from).unwrap());
+            std::fs::create_dir_all(target.parent().unwrap())?;
+            std::fs::copy(&file, target)?;
+        }
+    } else {
+        std::fs::copy(from, to)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_cache() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let input = tempdir.path().join("input.txt");
+        let output = tempdir.path().join("output.txt");
+        std::fs::write(&input, "input").unwrap();
+        let cache = BuildCache::new(&tempdir.path().join("target"));
+        let build = || {
+            let mut fingerprint = Fingerprint::new("copy");
+            fingerprint.path("input", &input).unwrap();
+            cache.cached("copy", &fingerprint, &[&output], || {
+                std::fs::copy(&input, &output)?;
+                Ok(())
+            })
+        };
+        assert!(!build().unwrap());
+        std::fs::remove_file(&output).unwrap();
+        assert!(build().unwrap());
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "input");
+        std::fs::write(&input, "changed").unwrap();
+        assert!(!build().unwrap());
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "changed");
+    }
+
+    #[test]
+    fn test_build_cache_prune() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let output = tempdir.path().join("output.txt");
+        let cache = BuildCache::new(&tempdir.path().join("target"));
+        let build = |step: &str, input: usize| {
+            let mut fingerprint = Fingerprint::new(step);
+            fingerprint.value("input", input);
+            cache.cached(step, &fingerprint, &[&output], || {
+                std::fs::write(&output, input.to_string())?;
+                Ok(())
+            })
+        };
+        build("apk-arm64-v8a", 0).unwrap();
+        for input in 0..=CACHE_ENTRIES_PER_STEP {
+            build("apk-x86_64", input).unwrap();
+        }
+        let entries = |step: &str| {
+            std::fs::read_dir(cache.cache_dir().join(step))
+                .unwrap()
+                .count()
+        };
+        assert_eq!(entries("apk-x86_64"), CACHE_ENTRIES_PER_STEP);
+        // Entries of other steps are kept
+        assert_eq!(entries("apk-arm64-v8a"), 1);
+        assert!(build("apk-arm64-v8a", 0).unwrap());
+    }
+}
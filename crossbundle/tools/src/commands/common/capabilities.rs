@@ -0,0 +1,297 @@
+use crate::error::*;
+#[cfg(feature = "windows")]
+use crate::types::AppxManifest;
+#[cfg(feature = "android")]
+use android_manifest::{AndroidManifest, UsesFeature, UsesPermission};
+#[cfg(feature = "apple")]
+use apple_bundle::{plist, prelude::InfoPlist};
+#[cfg(feature = "apple")]
+use std::collections::BTreeMap;
+
+/// Device capability used by the application with the platform declarations it needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capability {
+    /// Name used in `capabilities` of the configuration, e.g. `bluetooth`.
+    pub name: &'static str,
+    /// Crossbow plugins and permissions that enable the capability.
+    pub enabled_by: &'static [&'static str],
+    /// Permissions added to the Android manifest.
+    pub android_permissions: &'static [&'static str],
+    /// Hardware features added to the Android manifest with `required="false"`, so Google
+    /// Play doesn't filter out devices without them.
+    pub android_features: &'static [&'static str],
+    /// `Info.plist` keys of usage descriptions required by App Review.
+    pub usage_description_keys: &'static [&'static str],
+}
+
+/// Capabilities known to crossbundle. Keep in sync with crossbow plugins and permissions.
+pub const CAPABILITIES: &[Capability] = &[
+    Capability {
+        name: "billing",
+        enabled_by: &["play-billing"],
+        android_permissions: &["com.android.vending.BILLING"],
+        android_features: &[],
+        usage_description_keys: &[],
+    },
+    Capability {
+        name: "bluetooth",
+        enabled_by: &[],
+        android_permissions: &[
+            "android.permission.BLUETOOTH",
+            "android.permission.BLUETOOTH_ADMIN",
+            "android.permission.BLUETOOTH_CONNECT",
+            "android.permission.BLUETOOTH_SCAN",
+        ],
+        android_features: &["android.hardware.bluetooth"],
+        usage_description_keys: &["NSBluetoothAlwaysUsageDescription"],
+    },
+    Capability {
+        name: "calendars",
+        enabled_by: &["calendar-read", "calendar-write"],
+        android_permissions: &[],
+        android_features: &[],
+        usage_description_keys: &["NSCalendarsUsageDescription"],
+    },
+    Capability {
+        name: "camera",
+        enabled_by: &["camera", "flashlight"],
+        android_permissions: &["android.permission.CAMERA"],
+        android_features: &["android.hardware.camera"],
+        usage_description_keys: &["NSCameraUsageDescription"],
+    },
+    Capability {
+        name: "contacts",
+        enabled_by: &["contacts-read", "contacts-write"],
+        android_permissions: &[],
+        android_features: &[],
+        usage_description_keys: &["NSContactsUsageDescription"],
+    },
+    Capability {
+        name: "flashlight",
+        enabled_by: &["flashlight"],
+        android_permissions: &[],
+        android_features: &["android.hardware.camera.flash"],
+        usage_description_keys: &[],
+    },
+    Capability {
+        name: "internet",
+        enabled_by: &[
+            "admob-android",
+            "play-billing",
+            "play-core",
+            "play-games-services",
+        ],
+        android_permissions: &[
+            "android.permission.INTERNET",
+            "android.permission.ACCESS_NETWORK_STATE",
+        ],
+        android_features: &[],
+        usage_description_keys: &[],
+    },
+    Capability {
+        name: "location",
+        enabled_by: &["location-when-in-use", "location-always"],
+        android_permissions: &[],
+        android_features: &["android.hardware.location", "android.hardware.location.gps"],
+        usage_description_keys: &["NSLocationWhenInUseUsageDescription"],
+    },
+    Capability {
+        name: "media",
+        enabled_by: &["media"],
+        android_permissions: &[],
+        android_features: &[],
+        usage_description_keys: &["NSAppleMusicUsageDescription"],
+    },
+    Capability {
+        name: "microphone",
+        enabled_by: &["microphone", "speech"],
+        android_permissions: &["android.permission.RECORD_AUDIO"],
+        android_features: &["android.hardware.microphone"],
+        usage_description_keys: &["NSMicrophoneUsageDescription"],
+    },
+    Capability {
+        name: "motion",
+        enabled_by: &["sensors"],
+        android_permissions: &[],
+        android_features: &[],
+        usage_description_keys: &["NSMotionUsageDescription"],
+    },
+    Capability {
+        name: "photos",
+        enabled_by: &["photos"],
+        android_permissions: &[],
+        android_features: &[],
+        usage_description_keys: &[
+            "NSPhotoLibraryUsageDescription",
+            "NSPhotoLibraryAddUsageDescription",
+        ],
+    },
+    Capability {
+        name: "reminders",
+        enabled_by: &["reminders"],
+        android_permissions: &[],
+        android_features: &[],
+        usage_description_keys: &["NSRemindersUsageDescription"],
+    },
+    Capability {
+        name: "speech",
+        enabled_by: &["speech"],
+        android_permissions: &[],
+        android_features: &[],
+        usage_description_keys: &["NSSpeechRecognitionUsageDescription"],
+    },
+    Capability {
+        name: "telephony",
+        enabled_by: &["phone", "sms"],
+        android_permissions: &[],
+        android_features: &["android.hardware.telephony"],
+        usage_description_keys: &[],
+    },
+    Capability {
+        name: "vibration",
+        enabled_by: &[],
+        android_permissions: &["android.permission.VIBRATE"],
+        android_features: &[],
+        usage_description_keys: &[],
+    },
+];
+
+/// Returns capabilities listed in the configuration and inferred from `used_apis`: names
+/// of crate dependencies and crossbow permissions.
+pub fn enabled_capabilities(
+    used_apis: &[String],
+    configured: &[String],
+) -> Result<Vec<&'static Capability>> {
+    for name in configured {
+        if !CAPABILITIES
+            .iter()
+            .any(|capability| capability.name == name)
+        {
+            let names = CAPABILITIES
+                .iter()
+                .map(|capability| capability.name)
+                .collect::<Vec<_>>();
+            return Err(Error::UnknownCapability(name.clone(), names.join(", ")));
+        }
+    }
+    Ok(CAPABILITIES
+        .iter()
+        .filter(|capability| {
+            configured.iter().any(|name| name == capability.name)
+                || capability
+                    .enabled_by
+                    .iter()
+                    .any(|api| used_apis.iter().any(|used| used == api))
+        })
+        .collect())
+}
+
+/// Adds permissions and optional hardware features of the capabilities to the manifest.
+/// Entries already present in the manifest are left as is.
+#[cfg(feature = "android")]
+pub fn add_capabilities_to_manifest(manifest: &mut AndroidManifest, capabilities: &[&Capability]) {
+    for capability in capabilities {
+        for permission in capability.android_permissions {
+            if !manifest
+                .uses_permission
+                .iter()
+                .any(|p| p.name.as_deref() == Some(*permission))
+            {
+                manifest.uses_permission.push(UsesPermission {
+                    name: Some(permission.to_string()),
+                    ..Default::default()
+                });
+            }
+        }
+        for feature in capability.android_features {
+            if !manifest
+                .uses_feature
+                .iter()
+                .any(|f| f.name.as_deref() == Some(*feature))
+            {
+                manifest.uses_feature.push(UsesFeature {
+                    name: Some(feature.to_string()),
+                    required: Some(false),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+}
+
+/// Sets configured usage descriptions by `Info.plist` key and checks that every
+/// capability has its usage descriptions. App Review rejects binaries without them.
+#[cfg(feature = "apple")]
+pub fn apply_usage_descriptions(
+    info_plist: &mut InfoPlist,
+    capabilities: &[&Capability],
+    descriptions: &BTreeMap<String, String>,
+) -> Result<()> {
+    // Keys are set through the plist dictionary, so any key is supported
+    let mut xml = Vec::new();
+    plist::to_writer_xml(&mut xml, info_plist).map_err(AppleError::from)?;
+    let mut value = plist::Value::from_reader_xml(xml.as_slice()).map_err(AppleError::from)?;
+    let dictionary = value
+        .as_dictionary_mut()
+        .ok_or_else(|| AppleError::FailedToFindInfoPlist("not a dictionary".to_owned()))?;
+    for (key, description) in descriptions {
+        dictionary.insert(key.clone(), plist::Value::String(description.clone()));
+    }
+    let missing = capabilities
+        .iter()
+        .flat_map(|capability| capability.usage_description_keys.iter())
+        .filter(|key| !dictionary.contains_key(key))
+        .map(|key| key.to_string())
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        return Err(AppleError::MissingUsageDescriptions(missing.join(", ")).into());
+    }
+    let mut xml = Vec::new();
+    value.to_writer_xml(&mut xml).map_err(AppleError::from)?;
+    *info_plist = plist::from_reader_xml(xml.as_slice()).map_err(AppleError::from)?;
+    Ok(())
+}
+
+/// Adds capabilities and device capabilities of the capabilities to the MSIX package
+/// manifest. Capabilities without a Windows counterpart are skipped.
+#[cfg(feature = "windows")]
+pub fn add_capabilities_to_appx_manifest(
+    manifest: &mut AppxManifest,
+    capabilities: &[&Capability],
+) {
+    for capability in capabilities {
+        let (list, name) = match capability.name {
+            "internet" => (&mut manifest.capabilities, "internetClient"),
+            "bluetooth" => (&mut manifest.device_capabilities, "bluetooth"),
+            "camera" => (&mut manifest.device_capabilities, "webcam"),
+            "location" => (&mut manifest.device_capabilities, "location"),
+            "microphone" => (&mut manifest.device_capabilities, "microphone"),
+            _ => continue,
+        };
+        if !list.iter().any(|c| c == name) {
+            list.push(name.to_owned());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enabled_capabilities() {
+        let used_apis = vec!["play-billing".to_owned(), "camera".to_owned()];
+        let names = |capabilities: Vec<&Capability>| {
+            capabilities
+                .iter()
+                .map(|capability| capability.name)
+                .collect::<Vec<_>>()
+        };
+        let capabilities = enabled_capabilities(&used_apis, &["vibration".to_owned()]).unwrap();
+        assert_eq!(
+            names(capabilities),
+            ["billing", "camera", "internet", "vibration"]
+        );
+        assert!(enabled_capabilities(&[], &["teleport".to_owned()]).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use crate::error::*;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Argument of rustc that instruments the crate for source-based code coverage.
+pub const COVERAGE_RUSTC_ARG: &str = "-Cinstrument-coverage";
+
+/// Directory inside the application data directory the coverage profiles are written
+/// into: `files/coverage` on Android and `Documents/coverage` on iOS.
+pub const COVERAGE_DIR: &str = "coverage";
+
+/// Name pattern of the coverage profiles on Apple simulators. `%p` is replaced with
+/// the process ID, `%c` enables continuous mode that keeps the file up to date while
+/// the application is running.
+pub const PROFRAW_FILE_PATTERN: &str = "crossbow-%p%c.profraw";
+
+/// Code appended to the crate built with coverage on Android. The profile is written
+/// into the application data directory every two seconds, since Android kills
+/// applications without running exit handlers.
+pub const ANDROID_COVERAGE_EXTRA_CODE: &str = r#"
+#[cfg(target_os = "android")]
+mod crossbow_coverage {
+    extern "C" {
+        fn __llvm_profile_set_filename(name: *const std::os::raw::c_char);
+        fn __llvm_profile_write_file() -> std::os::raw::c_int;
+    }
+
+    #[used]
+    #[link_section = ".init_array"]
+    static INIT: extern "C" fn() = init;
+
+    extern "C" fn init() {
+        // The process name is the package name of the application
+        let cmdline = std::fs::read("/proc/self/cmdline").unwrap_or_default();
+        let package = cmdline.split(|byte| *byte == 0).next().unwrap_or_default();
+        let dir = format!("/data/data/{}/files/coverage", String::from_utf8_lossy(package));
+        let _ = std::fs::create_dir_all(&dir);
+        if let Ok(path) = std::ffi::CString::new(format!("{}/crossbow-%p.profraw", dir)) {
+            unsafe { __llvm_profile_set_filename(path.as_ptr()) };
+        }
+        std::thread::spawn(|| loop {
+            std::thread::sleep(std::time::Duration::from_secs(2));
+            unsafe { __llvm_profile_write_file() };
+        });
+    }
+}
+"#;
+
+/// Returns path of the LLVM tool shipped with the `llvm-tools-preview` rustup
+/// component, e.g. `llvm-profdata`.
+pub fn find_llvm_tool(name: &str) -> Result<PathBuf> {
+    let mut rustc = Command::new("rustc");
+    rustc.args(["--print", "target-libdir"]);
+    let output = rustc.output_err(false)?;
+    // `<sysroot>/lib/rustlib/<host>/lib`, tools are in the sibling `bin` directory
+    let target_libdir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    let tool = target_libdir
+        .parent()
+        .map(|host_dir| {
+            host_dir
+                .join("bin")
+                .join(format!("{}{}", name, std::env::consts::EXE_SUFFIX))
+        })
+        .filter(|tool| tool.exists());
+    tool.ok_or_else(|| Error::LlvmToolNotFound(name.to_owned()))
+}
+
+/// Returns `.profraw` files of the directory.
+pub fn find_profraw_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if !dir.exists() {
+        return Ok(files);
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().map_or(false, |ext| ext == "profraw") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Merges raw coverage profiles into the indexed profile.
+/// Runs `llvm-profdata merge -sparse <profraw>... -o <output>` command
+pub fn merge_profraw(profraw: &[PathBuf], output: &Path) -> Result<()> {
+    if profraw.is_empty() {
+        return Err(Error::CoverageDataNotFound(
+            "no .profraw files were written by the application".to_owned(),
+        ));
+    }
+    let mut profdata = Command::new(find_llvm_tool("llvm-profdata")?);
+    profdata
+        .args(["merge", "-sparse"])
+        .args(profraw)
+        .arg("-o")
+        .arg(output);
+    profdata.output_err(true)?;
+    Ok(())
+}
+
+/// Exports coverage of the instrumented binaries in lcov format, the same format
+/// `cargo llvm-cov` and `grcov` produce for host tests. Sources of dependencies and
+/// the standard library are ignored.
+/// Runs `llvm-cov export -format=lcov -instr-profile=<profdata> <objects>` command
+pub fn export_lcov(profdata: &Path, objects: &[PathBuf], output: &Path) -> Result<()> {
+    let (first, rest) = match objects.split_first() {
+        Some(objects) => objects,
+        None => {
+            return Err(Error::CoverageDataNotFound(
+                "no instrumented binaries specified".to_owned(),
+            ))
+        }
+    };
+    let mut llvm_cov = Command::new(find_llvm_tool("llvm-cov")?);
+    llvm_cov
+        .args(["export", "-format=lcov"])
+        .arg(format!("-instr-profile={}", profdata.display()))
+        .arg("-ignore-filename-regex=/.cargo/(registry|git)/|/rustc/")
+        .arg(first);
+    for object in rest {
+        llvm_cov.arg("-object").arg(object);
+    }
+    let lcov = llvm_cov.output_err(true)?.stdout;
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(output, lcov)?;
+    Ok(())
+}
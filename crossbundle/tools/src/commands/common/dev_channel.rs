@@ -0,0 +1,157 @@
+use crate::error::*;
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{Ipv4Addr, TcpStream},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// Port of the development channel of the `crossbow` crate used by default.
+pub const DEFAULT_DEV_CHANNEL_PORT: u16 = 8741;
+/// Environment variable with the session token of the development channel.
+pub const DEV_CHANNEL_TOKEN_ENV: &str = "CROSSBOW_DEV_TOKEN";
+
+/// Returns the session token passed with `--token` or set in `CROSSBOW_DEV_TOKEN`.
+pub fn dev_channel_token(token: Option<&str>) -> Result<String> {
+    match token {
+        Some(token) => Ok(token.to_owned()),
+        None => std::env::var(DEV_CHANNEL_TOKEN_ENV)
+            .ok()
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| {
+                Error::DevChannel(format!(
+                    "session token is not set. Pass the token the game started the channel \
+                     with in `--token` or `{}`",
+                    DEV_CHANNEL_TOKEN_ENV
+                ))
+            }),
+    }
+}
+
+/// Client of the development channel started with `crossbow::DevChannel` in the running
+/// game. Every request opens a new connection to `127.0.0.1:<port>` and sends the session
+/// token first, forward the port with
+/// [`forward_tcp_port`](crate::commands::android::forward_tcp_port) for Android devices.
+#[derive(Debug, Clone)]
+pub struct DevChannelClient {
+    port: u16,
+    token: String,
+}
+
+impl DevChannelClient {
+    pub fn new(port: u16, token: &str) -> Self {
+        Self {
+            port,
+            token: token.to_owned(),
+        }
+    }
+
+    /// Saves the local file into the channel root on the device under `remote` path.
+    pub fn push(&self, local: &Path, remote: &str) -> Result<()> {
+        let body = std::fs::read(local)?;
+        self.request(&format!("PUSH {} {}", body.len(), remote), &body)?;
+        Ok(())
+    }
+
+    /// Downloads the file from the channel root on the device.
+    pub fn pull(&self, remote: &str, local: &Path) -> Result<()> {
+        let body = self.request(&format!("PULL {}", remote), &[])?;
+        if let Some(parent) = local.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(local, body)?;
+        Ok(())
+    }
+
+    /// Runs the console command in the game and returns its output.
+    pub fn command(&self, command: &str) -> Result<String> {
+        let body = self.request(&format!("CMD {}", command.replace('\n', " ")), &[])?;
+        Ok(String::from_utf8_lossy(&body).to_string())
+    }
+
+    /// Waits until the game starts the channel, e.g. right after launching it.
+    pub fn wait(&self, timeout: Duration) -> Result<()> {
+        let start = Instant::now();
+        loop {
+            match self.request("PING", &[]) {
+                Ok(_) => return Ok(()),
+                Err(error) if start.elapsed() >= timeout => return Err(error),
+                Err(_) => std::thread::sleep(Duration::from_millis(250)),
+            }
+        }
+    }
+
+    /// Sends the request and returns body of the `OK` response.
+    fn request(&self, header: &str, body: &[u8]) -> Result<Vec<u8>> {
+        let connection_error = |error: std::io::Error| {
+            Error::DevChannel(format!(
+                "failed to connect to port {}: {}. Make sure the game is running with \
+                 `dev-channel` feature",
+                self.port, error
+            ))
+        };
+        let mut stream =
+            TcpStream::connect((Ipv4Addr::LOCALHOST, self.port)).map_err(connection_error)?;
+        stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+        writeln!(stream, "TOKEN {}", self.token)?;
+        writeln!(stream, "{}", header)?;
+        stream.write_all(body)?;
+        stream.flush()?;
+
+        let mut reader = BufReader::new(stream);
+        let mut status = String::new();
+        reader.read_line(&mut status)?;
+        let status = status.trim_end();
+        // Adb accepts connections of the forwarded port even if nothing listens on the
+        // device and closes them right away
+        if status.is_empty() {
+            return Err(Error::DevChannel(format!(
+                "no response on port {}. Make sure the game is running with `dev-channel` \
+                 feature",
+                self.port
+            )));
+        }
+        match status.split_once(' ') {
+            Some(("OK", length)) => {
+                let length: u64 = length
+                    .parse()
+                    .map_err(|_| Error::DevChannel(format!("invalid response `{}`", status)))?;
+                let mut body = Vec::new();
+                reader.take(length).read_to_end(&mut body)?;
+                Ok(body)
+            }
+            Some(("ERR", message)) => Err(Error::DevChannel(message.to_owned())),
+            _ => Err(Error::DevChannel(format!("invalid response `{}`", status))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_dev_channel_client() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            assert_eq!(header, "TOKEN secret\n");
+            header.clear();
+            reader.read_line(&mut header).unwrap();
+            assert_eq!(header, "CMD spawn enemy\n");
+            let mut stream = stream;
+            stream.write_all(b"OK 7\nspawned").unwrap();
+        });
+        let output = DevChannelClient::new(port, "secret")
+            .command("spawn enemy")
+            .unwrap();
+        assert_eq!(output, "spawned");
+        server.join().unwrap();
+        assert_eq!(dev_channel_token(Some("secret")).unwrap(), "secret");
+    }
+}
@@ -0,0 +1,312 @@
+use super::rustup::rustup_installed_targets;
+#[cfg(feature = "android")]
+use crate::{
+    commands::android::keytool_init,
+    types::{AndroidNdk, AndroidSdk},
+};
+use serde::Serialize;
+use std::process::Command;
+
+/// Severity of the single environment check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// Result of the single environment check with an actionable fix if check failed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DoctorCheck {
+    /// Stable identifier of the check, e.g. `android.ndk`. Codes don't change between
+    /// releases, so CI scripts can gate on specific classes of problems.
+    pub code: String,
+    /// Short name of the checked tool or setting.
+    pub name: String,
+    #[serde(rename = "severity")]
+    pub status: CheckStatus,
+    /// Version, path or error description.
+    pub details: String,
+    /// Suggested fix.
+    pub fix: Option<String>,
+}
+
+impl DoctorCheck {
+    pub fn ok(code: &str, name: &str, details: impl Into<String>) -> Self {
+        Self {
+            code: code.to_owned(),
+            name: name.to_owned(),
+            status: CheckStatus::Ok,
+            details: details.into(),
+            fix: None,
+        }
+    }
+
+    pub fn warning(
+        code: &str,
+        name: &str,
+        details: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            code: code.to_owned(),
+            name: name.to_owned(),
+            status: CheckStatus::Warning,
+            details: details.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    pub fn error(
+        code: &str,
+        name: &str,
+        details: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            code: code.to_owned(),
+            name: name.to_owned(),
+            status: CheckStatus::Error,
+            details: details.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Summary of all environment checks.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DoctorReport {
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    /// Returns `true` if none of the checks failed. Warnings are allowed.
+    pub fn is_ok(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.status != CheckStatus::Error)
+    }
+}
+
+/// Verifies the whole environment needed to build applications for enabled platforms.
+/// NDK of the `ndk_version` is checked if it's pinned, which must be at least of the
+/// `min_ndk_version`.
+#[cfg_attr(not(feature = "android"), allow(unused_variables))]
+pub fn doctor(ndk_version: Option<&str>, min_ndk_version: Option<&str>) -> DoctorReport {
+    let installed_targets = rustup_installed_targets();
+    let mut checks = vec![check_rustup(&installed_targets)];
+    #[cfg(feature = "android")]
+    checks.extend(android_checks(
+        &installed_targets,
+        ndk_version,
+        min_ndk_version,
+    ));
+    #[cfg(all(feature = "apple", target_os = "macos"))]
+    checks.extend(apple_checks(&installed_targets));
+    DoctorReport { checks }
+}
+
+/// Returns the first non-empty line of the command output. Some tools like `java`
+/// print their version into stderr.
+fn tool_version(mut command: Command) -> Option<String> {
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stdout
+        .lines()
+        .chain(stderr.lines())
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(ToOwned::to_owned)
+}
+
+fn check_rustup(installed_targets: &Option<Vec<String>>) -> DoctorCheck {
+    match installed_targets {
+        Some(targets) => DoctorCheck::ok(
+            "rust.rustup",
+            "rustup",
+            format!("{} targets installed", targets.len()),
+        ),
+        None => DoctorCheck::error(
+            "rust.rustup",
+            "rustup",
+            "rustup not found",
+            "Install rustup from https://rustup.rs",
+        ),
+    }
+}
+
+#[cfg(any(feature = "android", all(feature = "apple", target_os = "macos")))]
+fn check_rust_target(installed_targets: &Option<Vec<String>>, target: &str) -> DoctorCheck {
+    let name = format!("rust target {}", target);
+    match installed_targets {
+        Some(targets) if targets.iter().any(|t| t == target) => {
+            DoctorCheck::ok("rust.target", &name, "installed")
+        }
+        _ => DoctorCheck::error(
+            "rust.target",
+            &name,
+            "not installed",
+            format!("Run `rustup target add {}`", target),
+        ),
+    }
+}
+
+#[cfg(feature = "android")]
+fn android_checks(
+    installed_targets: &Option<Vec<String>>,
+    ndk_version: Option<&str>,
+    min_ndk_version: Option<&str>,
+) -> Vec<DoctorCheck> {
+    let mut checks = vec![check_rust_target(
+        installed_targets,
+        "aarch64-linux-android",
+    )];
+    let sdk = match AndroidSdk::from_env() {
+        Ok(sdk) => {
+            checks.push(DoctorCheck::ok(
+                "android.sdk",
+                "Android SDK",
+                sdk.sdk_path().display().to_string(),
+            ));
+            Some(sdk)
+        }
+        Err(err) => {
+            checks.push(DoctorCheck::error(
+                "android.sdk",
+                "Android SDK",
+                err.to_string(),
+                "Set ANDROID_SDK_ROOT or run `crossbundle install android`",
+            ));
+            None
+        }
+    };
+    if let Some(sdk) = &sdk {
+        let ndk =
+            AndroidNdk::from_env_with_requirements(sdk.sdk_path(), ndk_version, min_ndk_version);
+        checks.push(match ndk {
+            Ok(ndk) => DoctorCheck::ok(
+                "android.ndk",
+                "Android NDK",
+                format!("{} ({})", ndk.version(), ndk.ndk_path().display()),
+            ),
+            Err(err) => DoctorCheck::error(
+                "android.ndk",
+                "Android NDK",
+                err.to_string(),
+                "Set ANDROID_NDK_ROOT or run `crossbundle install android`",
+            ),
+        });
+        checks.push(
+            match sdk
+                .build_tool(bin!("aapt2"), None)
+                .ok()
+                .and_then(|mut aapt2| {
+                    aapt2.arg("version");
+                    tool_version(aapt2)
+                }) {
+                Some(version) => DoctorCheck::ok("android.aapt2", "aapt2", version),
+                None => DoctorCheck::error(
+                    "android.aapt2",
+                    "aapt2",
+                    format!("not found in build-tools {}", sdk.build_deps_version()),
+                    "Run `crossbundle install android`",
+                ),
+            },
+        );
+        checks.push(
+            match sdk.platform_tool(bin!("adb")).ok().and_then(|mut adb| {
+                adb.arg("version");
+                tool_version(adb)
+            }) {
+                Some(version) => DoctorCheck::ok("android.adb", "adb", version),
+                None => DoctorCheck::warning(
+                    "android.adb",
+                    "adb",
+                    "platform-tools not installed",
+                    "Run `crossbundle install sdkmanager --install platform-tools`",
+                ),
+            },
+        );
+    }
+    checks.push(
+        match keytool_init().ok().and_then(|_| {
+            let mut java = Command::new(bin!("java"));
+            java.arg("-version");
+            tool_version(java)
+        }) {
+            Some(version) => DoctorCheck::ok("android.jdk", "JDK", version),
+            None => DoctorCheck::error(
+                "android.jdk",
+                "JDK",
+                "java or keytool not found",
+                "Install JDK and add it to PATH or set JAVA_HOME",
+            ),
+        },
+    );
+    checks.push(match find_bundletool_jar() {
+        Some(path) => DoctorCheck::ok(
+            "android.bundletool",
+            "bundletool",
+            path.display().to_string(),
+        ),
+        None => DoctorCheck::warning(
+            "android.bundletool",
+            "bundletool",
+            "not found, AAB builds won't work",
+            "Run `crossbundle install bundletool` or set BUNDLETOOL_PATH",
+        ),
+    });
+    checks
+}
+
+/// Finds bundletool jar in `BUNDLETOOL_PATH` or in the home directory where
+/// `crossbundle install bundletool` saves it.
+#[cfg(feature = "android")]
+pub fn find_bundletool_jar() -> Option<std::path::PathBuf> {
+    if let Some(path) = std::env::var_os("BUNDLETOOL_PATH").map(std::path::PathBuf::from) {
+        return path.exists().then_some(path);
+    }
+    std::fs::read_dir(dirs::home_dir()?)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| name.starts_with("bundletool") && name.ends_with(".jar"))
+                .unwrap_or(false)
+        })
+}
+
+#[cfg(all(feature = "apple", target_os = "macos"))]
+fn apple_checks(installed_targets: &Option<Vec<String>>) -> Vec<DoctorCheck> {
+    let mut checks = vec![check_rust_target(installed_targets, "aarch64-apple-ios")];
+    let mut xcodebuild = Command::new("xcodebuild");
+    xcodebuild.arg("-version");
+    checks.push(match tool_version(xcodebuild) {
+        Some(version) => DoctorCheck::ok("apple.xcode", "Xcode", version),
+        None => DoctorCheck::error(
+            "apple.xcode",
+            "Xcode",
+            "xcodebuild not found",
+            "Install Xcode from the App Store and run `xcode-select --install`",
+        ),
+    });
+    let mut simctl = Command::new("xcrun");
+    simctl.arg("simctl").arg("help");
+    checks.push(match tool_version(simctl) {
+        Some(_) => DoctorCheck::ok("apple.simctl", "simctl", "available"),
+        None => DoctorCheck::warning(
+            "apple.simctl",
+            "simctl",
+            "not available, iOS simulators can't be used",
+            "Run `sudo xcode-select -s /Applications/Xcode.app`",
+        ),
+    });
+    checks
+}
@@ -1,40 +1,231 @@
-pub const MINIMAL_BEVY_CARGO_TOML_VALUE: &str = r#"
+pub const CARGO_TOML_PACKAGE_VALUE: &str = r#"
 [package]
-name = "example"
+name = "{name}"
 version = "0.1.0"
 authors = ["DodoRare Team <support@dodorare.com>"]
 edition = "2021"
-
-[dependencies]
-crossbow = { git = "https://github.com/dodorare/crossbow" }
 "#;
 
-pub const BEVY_MAIN_RS_VALUE: &str = r#"fn main(){println!("hello");}"#;
-
-pub const MINIMAL_MQ_CARGO_TOML_VALUE: &str = r#"
-[package]
-name = "example"
-version = "0.1.0"
-authors = ["DodoRare Team <support@dodorare.com>"]
-edition = "2021"
+pub const CROSSBOW_GIT_URL: &str = "https://github.com/dodorare/crossbow";
 
-[dependencies]
-crossbow = { git = "https://github.com/dodorare/crossbow" }
-anyhow = "1.0"
+pub const MQ_DEPENDENCIES_VALUE: &str = r#"anyhow = "1.0"
 macroquad = "=0.3.7"
+"#;
 
+pub const MQ_PATCH_VALUE: &str = r#"
 [patch.crates-io]
 miniquad = { git = "https://github.com/not-fl3/miniquad", rev = "d67ffe6950cf73df307e2d23aaa4726f14399985" }
+"#;
 
-[package.metadata.android]
-app_wrapper = "quad"
+pub const WINIT_DEPENDENCIES_VALUE: &str = r#"winit = "0.27"
+"#;
+
+pub const WGPU_DEPENDENCIES_VALUE: &str = r#"winit = "0.27"
+wgpu = "0.14"
+pollster = "0.2"
+"#;
+
+pub const BEVY_DEPENDENCIES_VALUE: &str = r#"bevy = { version = "0.8.1", default-features = false, features = ["bevy_winit", "render"] }
+"#;
+
+pub const BEVY_PATCH_VALUE: &str = r#"
+[patch.crates-io]
+bevy = { git = "https://github.com/dodorare/bevy", rev = "732fc8c585ebd3a622153771a8c51ace93024a04" }
 "#;
 
+pub const EMPTY_MAIN_RS_VALUE: &str = r#"fn main(){println!("hello");}"#;
+
 pub const MQ_MAIN_RS_VALUE: &str = r#"
 #[macroquad::main("Macroquad 3D")]
 async fn main() -> anyhow::Result<()> {Ok(())}
 "#;
 
+pub const WINIT_MAIN_RS_VALUE: &str = r#"use winit::{
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowBuilder,
+};
+
+fn main() {
+    let event_loop = EventLoop::new();
+    let _window = WindowBuilder::new()
+        .with_title("Crossbow Winit")
+        .build(&event_loop)
+        .unwrap();
+    event_loop.run(move |event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+        if let Event::WindowEvent {
+            event: WindowEvent::CloseRequested,
+            ..
+        } = event
+        {
+            *control_flow = ControlFlow::Exit;
+        }
+    });
+}
+"#;
+
+pub const WGPU_MAIN_RS_VALUE: &str = r#"use winit::{
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::Window,
+};
+
+const SHADER: &str = "
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> @builtin(position) vec4<f32> {
+    let x = f32(i32(in_vertex_index) - 1);
+    let y = f32(i32(in_vertex_index & 1u) * 2 - 1);
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+}
+";
+
+async fn run(event_loop: EventLoop<()>, window: Window) {
+    let size = window.inner_size();
+    let instance = wgpu::Instance::new(wgpu::Backends::all());
+    let surface = unsafe { instance.create_surface(&window) };
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            force_fallback_adapter: false,
+            compatible_surface: Some(&surface),
+        })
+        .await
+        .expect("Failed to find an appropriate adapter");
+    let (device, queue) = adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                label: None,
+                features: wgpu::Features::empty(),
+                limits: wgpu::Limits::downlevel_webgl2_defaults()
+                    .using_resolution(adapter.limits()),
+            },
+            None,
+        )
+        .await
+        .expect("Failed to create device");
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: None,
+        source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+    });
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: None,
+        bind_group_layouts: &[],
+        push_constant_ranges: &[],
+    });
+    let swapchain_format = surface.get_supported_formats(&adapter)[0];
+    let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: None,
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(swapchain_format.into())],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    });
+    let mut config = wgpu::SurfaceConfiguration {
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        format: swapchain_format,
+        width: size.width,
+        height: size.height,
+        present_mode: wgpu::PresentMode::Fifo,
+        alpha_mode: surface.get_supported_alpha_modes(&adapter)[0],
+    };
+    surface.configure(&device, &config);
+
+    event_loop.run(move |event, _, control_flow| {
+        let _ = (&instance, &adapter, &shader, &pipeline_layout);
+        *control_flow = ControlFlow::Wait;
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::Resized(size),
+                ..
+            } => {
+                config.width = size.width;
+                config.height = size.height;
+                surface.configure(&device, &config);
+                window.request_redraw();
+            }
+            Event::RedrawRequested(_) => {
+                let frame = surface
+                    .get_current_texture()
+                    .expect("Failed to acquire next swap chain texture");
+                let view = frame
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                let mut encoder =
+                    device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+                {
+                    let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: None,
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view: &view,
+                            resolve_target: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Clear(wgpu::Color::GREEN),
+                                store: true,
+                            },
+                        })],
+                        depth_stencil_attachment: None,
+                    });
+                    rpass.set_pipeline(&render_pipeline);
+                    rpass.draw(0..3, 0..1);
+                }
+                queue.submit(Some(encoder.finish()));
+                frame.present();
+            }
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => *control_flow = ControlFlow::Exit,
+            _ => {}
+        }
+    });
+}
+
+fn main() {
+    let event_loop = EventLoop::new();
+    let window = Window::new(&event_loop).unwrap();
+    pollster::block_on(run(event_loop, window));
+}
+"#;
+
+pub const BEVY_MAIN_RS_VALUE: &str = r#"use bevy::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_startup_system(setup)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn_bundle(Camera2dBundle::default());
+    commands.spawn_bundle(SpriteBundle {
+        sprite: Sprite {
+            color: Color::rgb(0.25, 0.25, 0.75),
+            custom_size: Some(Vec2::new(50.0, 100.0)),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+}
+"#;
+
 pub const STRINGS_XML_VALUE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
 <resources>
     <string name="hello">Hello!</string>
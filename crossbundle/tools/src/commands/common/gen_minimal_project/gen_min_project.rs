@@ -1,35 +1,208 @@
 use super::*;
 use crate::error::*;
+use serde::{Deserialize, Serialize};
 use std::{
     fs::{create_dir, File},
     io::Write,
+    path::Path,
 };
 
-// TODO: Fix this file logic.
-
-/// Generates a new minimal project in given path.
-pub fn gen_minimal_project(out_dir: &std::path::Path, macroquad_project: bool) -> Result<String> {
-    // Create Cargo.toml file
-    let file_path = out_dir.join("Cargo.toml");
-    let mut file = File::create(file_path)?;
-    if macroquad_project {
-        file.write_all(MINIMAL_MQ_CARGO_TOML_VALUE.as_bytes())?;
-    } else {
-        file.write_all(MINIMAL_BEVY_CARGO_TOML_VALUE.as_bytes())?;
-    }
-    // Create src folder
-    let src_path = out_dir.join("src");
-    create_dir(&src_path)?;
-    // Create main.rs
-    let main_rs_path = src_path.join("main.rs");
-    let mut main_rs = File::create(main_rs_path)?;
-    if macroquad_project {
-        main_rs.write_all(MQ_MAIN_RS_VALUE.as_bytes())?;
-    } else {
-        main_rs.write_all(BEVY_MAIN_RS_VALUE.as_bytes())?;
-    }
-    create_res_folder(out_dir)?;
-    Ok("example".to_owned())
+/// Game engine or windowing library used by generated minimal project.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum MinimalProjectTemplate {
+    /// Application that only depends on crossbow and prints to stdout.
+    #[default]
+    #[serde(rename = "empty")]
+    Empty,
+    /// Macroquad application. Uses `quad` application wrapper on Android.
+    #[serde(rename = "macroquad")]
+    Macroquad,
+    /// Winit application that opens an empty window.
+    #[serde(rename = "winit")]
+    Winit,
+    /// Wgpu application that renders a triangle.
+    #[serde(rename = "wgpu-triangle")]
+    WgpuTriangle,
+    /// Bevy application that renders a sprite.
+    #[serde(rename = "bevy-sprite")]
+    BevySprite,
+}
+
+impl MinimalProjectTemplate {
+    /// Dependencies added to `Cargo.toml` besides crossbow.
+    fn dependencies(&self) -> &'static str {
+        match self {
+            Self::Empty => "",
+            Self::Macroquad => MQ_DEPENDENCIES_VALUE,
+            Self::Winit => WINIT_DEPENDENCIES_VALUE,
+            Self::WgpuTriangle => WGPU_DEPENDENCIES_VALUE,
+            Self::BevySprite => BEVY_DEPENDENCIES_VALUE,
+        }
+    }
+
+    /// `[patch.crates-io]` section of `Cargo.toml` if needed.
+    fn patch(&self) -> &'static str {
+        match self {
+            Self::Macroquad => MQ_PATCH_VALUE,
+            Self::BevySprite => BEVY_PATCH_VALUE,
+            _ => "",
+        }
+    }
+
+    /// Contents of `src/main.rs`.
+    fn main_rs(&self) -> &'static str {
+        match self {
+            Self::Empty => EMPTY_MAIN_RS_VALUE,
+            Self::Macroquad => MQ_MAIN_RS_VALUE,
+            Self::Winit => WINIT_MAIN_RS_VALUE,
+            Self::WgpuTriangle => WGPU_MAIN_RS_VALUE,
+            Self::BevySprite => BEVY_MAIN_RS_VALUE,
+        }
+    }
+}
+
+impl std::str::FromStr for MinimalProjectTemplate {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "empty" => Ok(Self::Empty),
+            "macroquad" => Ok(Self::Macroquad),
+            "winit" => Ok(Self::Winit),
+            "wgpu-triangle" => Ok(Self::WgpuTriangle),
+            "bevy-sprite" => Ok(Self::BevySprite),
+            _ => Err(Error::InvalidProjectTemplate(s.to_owned())),
+        }
+    }
+}
+
+/// Platforms the minimal project is generated for.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum MinimalProjectPlatform {
+    /// Android and Apple.
+    #[default]
+    #[serde(rename = "all")]
+    All,
+    #[serde(rename = "android")]
+    Android,
+    #[serde(rename = "apple")]
+    Apple,
+}
+
+impl MinimalProjectPlatform {
+    /// Whether Android metadata and resources should be generated.
+    pub fn has_android(&self) -> bool {
+        matches!(self, Self::All | Self::Android)
+    }
+
+    /// Crossbow features enabled for the platform.
+    fn crossbow_features(&self) -> &'static str {
+        match self {
+            Self::All => "",
+            Self::Android => r#", default-features = false, features = ["android"]"#,
+            Self::Apple => r#", default-features = false, features = ["ios"]"#,
+        }
+    }
+}
+
+impl std::str::FromStr for MinimalProjectPlatform {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Self::All),
+            "android" => Ok(Self::Android),
+            "apple" => Ok(Self::Apple),
+            _ => Err(Error::InvalidProjectTemplate(s.to_owned())),
+        }
+    }
+}
+
+/// Generator of minimal projects that can be built with crossbundle. Used by
+/// integration tests and by `crossbundle new` command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinimalProject {
+    /// Package name.
+    pub name: String,
+    /// Game engine or windowing library to use.
+    pub template: MinimalProjectTemplate,
+    /// Platforms to generate the project for.
+    pub platform: MinimalProjectPlatform,
+}
+
+impl Default for MinimalProject {
+    fn default() -> Self {
+        Self {
+            name: "example".to_owned(),
+            template: MinimalProjectTemplate::default(),
+            platform: MinimalProjectPlatform::default(),
+        }
+    }
+}
+
+impl MinimalProject {
+    /// Creates a new minimal project generator with given template.
+    pub fn new(template: MinimalProjectTemplate) -> Self {
+        Self {
+            template,
+            ..Default::default()
+        }
+    }
+
+    /// Sets package name.
+    pub fn name(&mut self, name: &str) -> &mut Self {
+        self.name = name.to_owned();
+        self
+    }
+
+    /// Sets platforms to generate the project for.
+    pub fn platform(&mut self, platform: MinimalProjectPlatform) -> &mut Self {
+        self.platform = platform;
+        self
+    }
+
+    /// Returns contents of `Cargo.toml`.
+    pub fn cargo_toml(&self) -> String {
+        let mut cargo_toml = CARGO_TOML_PACKAGE_VALUE.replace("{name}", &self.name);
+        cargo_toml.push_str(&format!(
+            "\n[dependencies]\ncrossbow = {{ git = \"{}\"{} }}\n",
+            CROSSBOW_GIT_URL,
+            self.platform.crossbow_features()
+        ));
+        cargo_toml.push_str(self.template.dependencies());
+        cargo_toml.push_str(self.template.patch());
+        if self.platform.has_android() && self.template == MinimalProjectTemplate::Macroquad {
+            cargo_toml.push_str("\n[package.metadata.android]\napp_wrapper = \"quad\"\n");
+        }
+        cargo_toml
+    }
+
+    /// Generates the project in given directory. Returns package name.
+    pub fn generate(&self, out_dir: &Path) -> Result<String> {
+        // Create Cargo.toml file
+        let mut file = File::create(out_dir.join("Cargo.toml"))?;
+        file.write_all(self.cargo_toml().as_bytes())?;
+        // Create src folder
+        let src_path = out_dir.join("src");
+        create_dir(&src_path)?;
+        // Create main.rs
+        let mut main_rs = File::create(src_path.join("main.rs"))?;
+        main_rs.write_all(self.template.main_rs().as_bytes())?;
+        if self.platform.has_android() {
+            create_res_folder(out_dir)?;
+        }
+        Ok(self.name.clone())
+    }
+}
+
+/// Generates a new minimal project in given path. Shortcut for [`MinimalProject`] with
+/// empty or macroquad template.
+pub fn gen_minimal_project(out_dir: &Path, macroquad_project: bool) -> Result<String> {
+    let template = match macroquad_project {
+        true => MinimalProjectTemplate::Macroquad,
+        false => MinimalProjectTemplate::Empty,
+    };
+    MinimalProject::new(template).generate(out_dir)
 }
 
 #[cfg(test)]
@@ -41,4 +214,25 @@ mod tests {
         let dir = tempfile::tempdir().unwrap();
         gen_minimal_project(dir.path(), true).unwrap();
     }
+
+    #[test]
+    fn test_minimal_project_templates() {
+        for template in [
+            MinimalProjectTemplate::Winit,
+            MinimalProjectTemplate::WgpuTriangle,
+            MinimalProjectTemplate::BevySprite,
+        ] {
+            let dir = tempfile::tempdir().unwrap();
+            let name = MinimalProject::new(template)
+                .name("crossbow-test")
+                .platform(MinimalProjectPlatform::Apple)
+                .generate(dir.path())
+                .unwrap();
+            assert_eq!(name, "crossbow-test");
+            assert!(dir.path().join("src").join("main.rs").exists());
+            assert!(!dir.path().join("res").exists());
+            let cargo_toml = std::fs::read_to_string(dir.path().join("Cargo.toml")).unwrap();
+            assert!(cargo_toml.contains(r#"features = ["ios"]"#));
+        }
+    }
 }
@@ -0,0 +1,294 @@
+use crate::error::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Environment variables always passed to restricted hooks.
+const DEFAULT_HOOK_ENV: &[&str] = &["PATH", "HOME", "TMPDIR", "LANG", "TERM"];
+
+/// User-defined command executed before or after the build.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Hook {
+    /// Program and its arguments, e.g. `["python3", "scripts/gen_assets.py"]`.
+    pub command: Vec<String>,
+    /// Restrictions applied to the hook process.
+    #[serde(default)]
+    pub sandbox: HookSandbox,
+}
+
+/// Restrictions of the hook process. By default hooks run without network access, with
+/// read-only file system except temporary directory and with constrained environment.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct HookSandbox {
+    /// Run hook without any restrictions.
+    #[serde(default)]
+    pub unrestricted: bool,
+    /// Allow network access.
+    #[serde(default)]
+    pub network: bool,
+    /// Paths relative to the project directory the hook is allowed to write to.
+    #[serde(default)]
+    pub writable_paths: Vec<PathBuf>,
+    /// Names of the environment variables passed to the hook.
+    #[serde(default)]
+    pub env: Vec<String>,
+}
+
+/// OS facility used to restrict hook processes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SandboxBackend {
+    /// [Bubblewrap](https://github.com/containers/bubblewrap) on Linux.
+    Bubblewrap(PathBuf),
+    /// `sandbox-exec` on macOS.
+    SandboxExec(PathBuf),
+    /// No OS sandbox available. Only environment is constrained.
+    Environment,
+}
+
+impl SandboxBackend {
+    /// Finds sandbox facility available on the host.
+    pub fn detect() -> Self {
+        if cfg!(target_os = "linux") {
+            if let Ok(bwrap) = which::which("bwrap") {
+                return Self::Bubblewrap(bwrap);
+            }
+        }
+        if cfg!(target_os = "macos") {
+            if let Ok(sandbox_exec) = which::which("sandbox-exec") {
+                return Self::SandboxExec(sandbox_exec);
+            }
+        }
+        Self::Environment
+    }
+}
+
+impl Hook {
+    /// Returns hook command as a single string for messages.
+    pub fn display_command(&self) -> String {
+        self.command.join(" ")
+    }
+
+    /// Returns SHA-256 fingerprint of the hook definition in given project. Any change of
+    /// the command, its restrictions or scripts of the project it runs requires trusting
+    /// the hook again.
+    pub fn fingerprint(&self, project_dir: &Path) -> Result<String> {
+        let mut hasher = Sha256::new();
+        hasher.update(project_dir.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(serde_json::to_vec(self)?);
+        // Program and arguments that are files of the project, e.g. `scripts/gen.py`
+        for arg in &self.command {
+            let path = project_dir.join(arg);
+            if path.starts_with(project_dir) && path.is_file() {
+                hasher.update([0]);
+                hasher.update(arg.as_bytes());
+                hasher.update([0]);
+                hasher.update(std::fs::read(&path)?);
+            }
+        }
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect())
+    }
+
+    /// Creates command that runs the hook in the project directory with restrictions
+    /// supported by the sandbox backend.
+    pub fn command(&self, project_dir: &Path, backend: &SandboxBackend) -> Result<Command> {
+        let (program, args) = self
+            .command
+            .split_first()
+            .ok_or_else(|| Error::EmptyHookCommand(project_dir.to_owned()))?;
+        if self.sandbox.unrestricted {
+            let mut command = Command::new(program);
+            command.args(args).current_dir(project_dir);
+            return Ok(command);
+        }
+        let writable_paths = self
+            .sandbox
+            .writable_paths
+            .iter()
+            .map(|path| {
+                let path = project_dir.join(path);
+                std::fs::create_dir_all(&path)?;
+                Ok(dunce::canonicalize(path)?)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut command = match backend {
+            SandboxBackend::Bubblewrap(bwrap) => {
+                let mut bwrap = Command::new(bwrap);
+                bwrap
+                    .args(["--ro-bind", "/", "/"])
+                    .args(["--dev", "/dev"])
+                    .args(["--proc", "/proc"])
+                    .args(["--tmpfs", "/tmp"]);
+                for path in &writable_paths {
+                    bwrap.arg("--bind").arg(path).arg(path);
+                }
+                if !self.sandbox.network {
+                    bwrap.arg("--unshare-net");
+                }
+                bwrap
+                    .arg("--unshare-pid")
+                    .arg("--die-with-parent")
+                    .arg("--chdir")
+                    .arg(project_dir)
+                    .arg("--")
+                    .arg(program)
+                    .args(args);
+                bwrap
+            }
+            SandboxBackend::SandboxExec(sandbox_exec) => {
+                let mut sandbox_exec = Command::new(sandbox_exec);
+                sandbox_exec
+                    .arg("-p")
+                    .arg(sandbox_profile(self.sandbox.network, &writable_paths))
+                    .arg(program)
+                    .args(args);
+                sandbox_exec
+            }
+            SandboxBackend::Environment => {
+                let mut command = Command::new(program);
+                command.args(args);
+                command
+            }
+        };
+        command.current_dir(project_dir).env_clear();
+        let allowed_env = DEFAULT_HOOK_ENV
+            .iter()
+            .copied()
+            .chain(self.sandbox.env.iter().map(String::as_str));
+        for name in allowed_env {
+            if let Some(value) = std::env::var_os(name) {
+                command.env(name, value);
+            }
+        }
+        Ok(command)
+    }
+}
+
+/// Generates `sandbox-exec` profile that denies network and file writes outside of
+/// temporary directories and writable paths.
+fn sandbox_profile(network: bool, writable_paths: &[PathBuf]) -> String {
+    let mut profile = "(version 1)\n(allow default)\n".to_owned();
+    if !network {
+        profile.push_str("(deny network*)\n");
+    }
+    profile.push_str("(deny file-write*)\n(allow file-write*\n  (literal \"/dev/null\")\n  (subpath \"/private/tmp\")\n  (subpath \"/private/var/folders\")");
+    for path in writable_paths {
+        let path = path
+            .to_string_lossy()
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"");
+        profile.push_str(&format!("\n  (subpath \"{}\")", path));
+    }
+    profile.push_str(")\n");
+    profile
+}
+
+/// List of hook fingerprints the user agreed to run.
+/// Stored in `~/.crossbundle/trusted_hooks` by default.
+#[derive(Debug, Clone)]
+pub struct HookTrustStore {
+    path: PathBuf,
+}
+
+impl HookTrustStore {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_owned(),
+        }
+    }
+
+    /// Returns trust store in the home directory.
+    pub fn from_home_dir() -> Result<Self> {
+        let home_dir = dirs::home_dir().ok_or(Error::HomeDirNotFound)?;
+        Ok(Self::new(
+            &home_dir.join(".crossbundle").join("trusted_hooks"),
+        ))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns `true` if the fingerprint was trusted before.
+    pub fn is_trusted(&self, fingerprint: &str) -> Result<bool> {
+        if !self.path.exists() {
+            return Ok(false);
+        }
+        let trusted = std::fs::read_to_string(&self.path)?;
+        Ok(trusted.lines().any(|line| line.trim() == fingerprint))
+    }
+
+    /// Remembers the fingerprint.
+    pub fn trust(&self, fingerprint: &str) -> Result<()> {
+        if self.is_trusted(fingerprint)? {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", fingerprint)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_sandbox() {
+        let dir = tempfile::tempdir().unwrap();
+        let hook = Hook {
+            command: vec!["python3".to_owned(), "gen.py".to_owned()],
+            sandbox: HookSandbox {
+                writable_paths: vec![PathBuf::from("generated")],
+                ..Default::default()
+            },
+        };
+        let command = hook
+            .command(dir.path(), &SandboxBackend::Bubblewrap("bwrap".into()))
+            .unwrap();
+        let args: Vec<_> = command.get_args().map(|a| a.to_string_lossy()).collect();
+        assert!(args.contains(&"--unshare-net".into()));
+        assert!(dir.path().join("generated").exists());
+        assert_eq!(args[args.len() - 2..], ["python3", "gen.py"]);
+
+        let store = HookTrustStore::new(&dir.path().join("trusted_hooks"));
+        let fingerprint = hook.fingerprint(dir.path()).unwrap();
+        assert!(!store.is_trusted(&fingerprint).unwrap());
+        store.trust(&fingerprint).unwrap();
+        assert!(store.is_trusted(&fingerprint).unwrap());
+        let mut changed = hook.clone();
+        changed.sandbox.network = true;
+        assert_ne!(changed.fingerprint(dir.path()).unwrap(), fingerprint);
+    }
+
+    #[test]
+    fn test_hook_script_fingerprint() {
+        let dir = tempfile::tempdir().unwrap();
+        let hook = Hook {
+            command: vec!["python3".to_owned(), "gen.py".to_owned()],
+            sandbox: HookSandbox::default(),
+        };
+        let missing = hook.fingerprint(dir.path()).unwrap();
+        std::fs::write(dir.path().join("gen.py"), "print('assets')").unwrap();
+        let fingerprint = hook.fingerprint(dir.path()).unwrap();
+        assert_ne!(fingerprint, missing);
+        assert_eq!(hook.fingerprint(dir.path()).unwrap(), fingerprint);
+        // Editing the script requires trusting the hook again
+        std::fs::write(dir.path().join("gen.py"), "import os").unwrap();
+        assert_ne!(hook.fingerprint(dir.path()).unwrap(), fingerprint);
+    }
+}
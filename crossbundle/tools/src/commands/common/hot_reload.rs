@@ -0,0 +1,111 @@
+use crate::{error::*, types::Profile};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    time::SystemTime,
+};
+
+/// Directory of the channel root the game loads hot reloaded libraries from.
+pub const HOT_RELOAD_DIR: &str = "hot-reload";
+
+/// Builds the library of `package` with `crate-type = ["cdylib"]` for `rust_triple`.
+/// `envs` are passed to cargo, e.g. the linker of the Android NDK. Returns the path to
+/// the built library.
+pub fn build_hot_reload_library(
+    project_path: &Path,
+    target_dir: &Path,
+    package: &str,
+    rust_triple: &str,
+    profile: Profile,
+    envs: &[(String, PathBuf)],
+) -> Result<PathBuf> {
+    let mut cargo = Command::new("cargo");
+    cargo
+        .current_dir(project_path)
+        .args([
+            "build",
+            "--lib",
+            "--package",
+            package,
+            "--target",
+            rust_triple,
+        ])
+        .arg("--target-dir")
+        .arg(target_dir)
+        .envs(envs.iter().map(|(key, value)| (key, value)));
+    if profile == Profile::Release {
+        cargo.arg("--release");
+    }
+    cargo.output_err(true)?;
+    let library_path = target_dir
+        .join(rust_triple)
+        .join(profile)
+        .join(library_file_name(package, rust_triple));
+    if !library_path.exists() {
+        return Err(Error::PathNotFound(library_path));
+    }
+    Ok(library_path)
+}
+
+/// Returns the file name of the dynamic library built by cargo, e.g.
+/// `libgame_logic.so` for `game-logic` package on Android.
+pub fn library_file_name(package: &str, rust_triple: &str) -> String {
+    let name = package.replace('-', "_");
+    if rust_triple.contains("windows") {
+        format!("{}.dll", name)
+    } else if rust_triple.contains("apple") {
+        format!("lib{}.dylib", name)
+    } else {
+        format!("lib{}.so", name)
+    }
+}
+
+/// Returns the path of the library in the channel root. Every version gets a unique
+/// name, since `dlopen` returns the already loaded library for the same path.
+pub fn hot_reload_remote_path(library_path: &Path, version: u128) -> String {
+    let stem = library_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    match library_path.extension() {
+        Some(ext) => format!(
+            "{}/{}-{}.{}",
+            HOT_RELOAD_DIR,
+            stem,
+            version,
+            ext.to_string_lossy()
+        ),
+        None => format!("{}/{}-{}", HOT_RELOAD_DIR, stem, version),
+    }
+}
+
+/// Returns the latest modification time of the files in `dir`. Used to rebuild the
+/// library when the sources change.
+pub fn latest_modification(dir: &Path) -> Result<SystemTime> {
+    let mut latest = std::fs::metadata(dir)?.modified()?;
+    if dir.is_dir() {
+        for entry in std::fs::read_dir(dir)? {
+            latest = latest.max(latest_modification(&entry?.path())?);
+        }
+    }
+    Ok(latest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hot_reload_remote_path() {
+        let file_name = library_file_name("game-logic", "aarch64-linux-android");
+        assert_eq!(file_name, "libgame_logic.so");
+        assert_eq!(
+            library_file_name("game-logic", "aarch64-apple-ios-sim"),
+            "libgame_logic.dylib"
+        );
+        assert_eq!(
+            hot_reload_remote_path(Path::new(&file_name), 1666000000000),
+            "hot-reload/libgame_logic-1666000000000.so"
+        );
+    }
+}
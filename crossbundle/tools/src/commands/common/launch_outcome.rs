@@ -0,0 +1,30 @@
+/// Result of watching the application after launch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaunchOutcome {
+    /// Application is still running after the watch period.
+    Running,
+    /// Application crashed. Contains the crash log.
+    Crashed(String),
+    /// Application is not responding. Contains the ANR log.
+    NotResponding(String),
+    /// Application process exited without a crash report.
+    Exited,
+}
+
+impl LaunchOutcome {
+    /// Returns `true` if the application survived the watch period.
+    pub fn is_success(&self) -> bool {
+        *self == Self::Running
+    }
+}
+
+impl std::fmt::Display for LaunchOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Running => write!(f, "application is running"),
+            Self::Crashed(_) => write!(f, "application crashed"),
+            Self::NotResponding(_) => write!(f, "application is not responding (ANR)"),
+            Self::Exited => write!(f, "application exited"),
+        }
+    }
+}
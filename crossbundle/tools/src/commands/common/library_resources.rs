@@ -0,0 +1,299 @@
+use crate::error::*;
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Resource and asset directories declared by a library crate in `[package.metadata]`
+/// with the same keys as in the application: `assets`, `android.resources`,
+/// `android.assets`, `apple.resources` and `apple.assets`. Paths are resolved
+/// relatively to the library crate directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LibraryResources {
+    /// Name of the library crate.
+    pub package: String,
+    pub assets: Vec<PathBuf>,
+    pub android_resources: Vec<PathBuf>,
+    pub android_assets: Vec<PathBuf>,
+    pub apple_resources: Vec<PathBuf>,
+    pub apple_assets: Vec<PathBuf>,
+}
+
+impl LibraryResources {
+    pub fn get_android_assets(&self) -> &[PathBuf] {
+        if !self.android_assets.is_empty() {
+            &self.android_assets
+        } else {
+            &self.assets
+        }
+    }
+
+    pub fn get_apple_assets(&self) -> &[PathBuf] {
+        if !self.apple_assets.is_empty() {
+            &self.apple_assets
+        } else {
+            &self.assets
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+            && self.android_resources.is_empty()
+            && self.android_assets.is_empty()
+            && self.apple_resources.is_empty()
+            && self.apple_assets.is_empty()
+    }
+}
+
+/// Returns resources of the library crates the package depends on, directly or
+/// transitively. Build and dev dependencies are skipped.
+/// Runs `cargo metadata --format-version 1` command
+pub fn find_library_resources(manifest_path: &Path) -> Result<Vec<LibraryResources>> {
+    let mut cargo = Command::new("cargo");
+    cargo
+        .args(["metadata", "--format-version", "1", "--manifest-path"])
+        .arg(manifest_path);
+    let output = cargo.output_err(true)?;
+    parse_library_resources(
+        &String::from_utf8_lossy(&output.stdout),
+        &dunce::canonicalize(manifest_path)?,
+    )
+}
+
+/// Parses `cargo metadata` output and returns resources of the dependencies of the
+/// package with `manifest_path`, sorted by crate name.
+pub fn parse_library_resources(json: &str, manifest_path: &Path) -> Result<Vec<LibraryResources>> {
+    #[derive(Deserialize)]
+    struct Metadata {
+        packages: Vec<Package>,
+        resolve: Option<Resolve>,
+    }
+    #[derive(Deserialize)]
+    struct Package {
+        id: String,
+        name: String,
+        manifest_path: PathBuf,
+        #[serde(default)]
+        metadata: Option<ResourcesMetadata>,
+    }
+    #[derive(Deserialize, Default)]
+    struct ResourcesMetadata {
+        #[serde(default)]
+        assets: Vec<PathBuf>,
+        #[serde(default)]
+        android: PlatformResources,
+        #[serde(default)]
+        apple: PlatformResources,
+    }
+    #[derive(Deserialize, Default)]
+    struct PlatformResources {
+        #[serde(default)]
+        resources: Vec<PathBuf>,
+        #[serde(default)]
+        assets: Vec<PathBuf>,
+    }
+    #[derive(Deserialize)]
+    struct Resolve {
+        nodes: Vec<Node>,
+    }
+    #[derive(Deserialize)]
+    struct Node {
+        id: String,
+        #[serde(default)]
+        deps: Vec<NodeDep>,
+    }
+    #[derive(Deserialize)]
+    struct NodeDep {
+        pkg: String,
+        #[serde(default)]
+        dep_kinds: Vec<DepKind>,
+    }
+    #[derive(Deserialize)]
+    struct DepKind {
+        kind: Option<String>,
+    }
+
+    // Unrelated metadata of other tools may not match the structure, it's ignored
+    let mut metadata: serde_json::Value = serde_json::from_str(json)?;
+    if let Some(packages) = metadata["packages"].as_array_mut() {
+        for package in packages {
+            let valid =
+                serde_json::from_value::<ResourcesMetadata>(package["metadata"].clone()).is_ok();
+            if !valid {
+                package["metadata"] = serde_json::Value::Null;
+            }
+        }
+    }
+    let metadata: Metadata = serde_json::from_value(metadata)?;
+    let root = match metadata
+        .packages
+        .iter()
+        .find(|package| package.manifest_path == manifest_path)
+    {
+        Some(package) => package.id.clone(),
+        None => return Ok(Vec::new()),
+    };
+    let nodes: HashMap<&str, &Node> = metadata
+        .resolve
+        .iter()
+        .flat_map(|resolve| &resolve.nodes)
+        .map(|node| (node.id.as_str(), node))
+        .collect();
+    let mut dependencies = HashSet::new();
+    let mut queue = vec![root.as_str()];
+    while let Some(id) = queue.pop() {
+        let deps = nodes
+            .get(id)
+            .map(|node| node.deps.as_slice())
+            .unwrap_or_default();
+        for dep in deps {
+            let normal = dep.dep_kinds.is_empty() || dep.dep_kinds.iter().any(|k| k.kind.is_none());
+            if normal && dependencies.insert(dep.pkg.as_str()) {
+                queue.push(dep.pkg.as_str());
+            }
+        }
+    }
+    let mut libraries: Vec<LibraryResources> = metadata
+        .packages
+        .iter()
+        .filter(|package| dependencies.contains(package.id.as_str()))
+        .filter_map(|package| {
+            let metadata = package.metadata.as_ref()?;
+            let dir = package.manifest_path.parent()?;
+            let resolve = |paths: &[PathBuf]| -> Vec<PathBuf> {
+                paths.iter().map(|path| dir.join(path)).collect()
+            };
+            let library = LibraryResources {
+                package: package.name.clone(),
+                assets: resolve(&metadata.assets),
+                android_resources: resolve(&metadata.android.resources),
+                android_assets: resolve(&metadata.android.assets),
+                apple_resources: resolve(&metadata.apple.resources),
+                apple_assets: resolve(&metadata.apple.assets),
+            };
+            (!library.is_empty()).then(|| library)
+        })
+        .collect();
+    libraries.sort_by(|a, b| a.package.cmp(&b.package));
+    Ok(libraries)
+}
+
+/// Returns directories to combine into one in the returned order: directories of the
+/// libraries first, then the application ones, so the application can override any
+/// library file. Fails if two libraries provide a file with the same relative path.
+pub fn merge_library_dirs(
+    app_dirs: &[PathBuf],
+    library_dirs: &[(&str, &[PathBuf])],
+) -> Result<Vec<PathBuf>> {
+    let mut app_files = HashSet::new();
+    for dir in app_dirs {
+        app_files.extend(relative_files(dir)?);
+    }
+    let mut providers: BTreeMap<PathBuf, &str> = BTreeMap::new();
+    let mut merged = Vec::new();
+    for (package, dirs) in library_dirs {
+        for dir in dirs.iter() {
+            for file in relative_files(dir)? {
+                if app_files.contains(&file) {
+                    continue;
+                }
+                match providers.get(&file) {
+                    Some(first) if first != package => {
+                        return Err(Error::LibraryResourceConflict {
+                            path: file,
+                            first: first.to_string(),
+                            second: package.to_string(),
+                        });
+                    }
+                    _ => {
+                        providers.insert(file, *package);
+                    }
+                }
+            }
+            merged.push(dir.clone());
+        }
+    }
+    merged.extend(app_dirs.iter().cloned());
+    Ok(merged)
+}
+
+/// Returns paths of all files in the directory relatively to it.
+pub(crate) fn relative_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if !dir.exists() {
+        return Ok(files);
+    }
+    let mut dirs = vec![dir.to_owned()];
+    while let Some(current) = dirs.pop() {
+        for entry in std::fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if let Ok(relative) = path.strip_prefix(dir) {
+                files.push(relative.to_owned());
+            }
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_library_resources() {
+        let json = r#"{
+            "packages": [
+                {"id": "game 0.1.0", "name": "game", "manifest_path": "/game/Cargo.toml",
+                 "metadata": {"app_name": "Game"}},
+                {"id": "ui 0.1.0", "name": "ui", "manifest_path": "/ui/Cargo.toml",
+                 "metadata": {"assets": ["assets"], "android": {"resources": ["res"]}}},
+                {"id": "codegen 0.1.0", "name": "codegen", "manifest_path": "/codegen/Cargo.toml",
+                 "metadata": {"assets": ["assets"]}},
+                {"id": "docs 0.1.0", "name": "docs", "manifest_path": "/docs/Cargo.toml",
+                 "metadata": {"android": "unrelated"}}
+            ],
+            "resolve": {"nodes": [
+                {"id": "game 0.1.0", "deps": [
+                    {"pkg": "ui 0.1.0", "dep_kinds": [{"kind": null}]},
+                    {"pkg": "codegen 0.1.0", "dep_kinds": [{"kind": "build"}]},
+                    {"pkg": "docs 0.1.0", "dep_kinds": [{"kind": null}]}
+                ]},
+                {"id": "ui 0.1.0", "deps": []}
+            ]}
+        }"#;
+        let libraries = parse_library_resources(json, Path::new("/game/Cargo.toml")).unwrap();
+        assert_eq!(
+            libraries,
+            [LibraryResources {
+                package: "ui".to_owned(),
+                assets: vec![PathBuf::from("/ui/assets")],
+                android_resources: vec![PathBuf::from("/ui/res")],
+                ..Default::default()
+            }]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let write = |path: &str| {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        };
+        write("app/values/strings.xml");
+        write("ui/values/strings.xml");
+        write("ui/drawable/button.xml");
+        write("icons/drawable/button.xml");
+        let app = [dir.path().join("app")];
+        let ui = [dir.path().join("ui")];
+        let icons = [dir.path().join("icons")];
+        let merged = merge_library_dirs(&app, &[("ui", &ui)]).unwrap();
+        assert_eq!(merged, [ui[0].clone(), app[0].clone()]);
+        assert!(matches!(
+            merge_library_dirs(&app, &[("icons", &icons), ("ui", &ui)]),
+            Err(Error::LibraryResourceConflict { .. })
+        ));
+    }
+}
@@ -1,13 +1,49 @@
 //! Common commands used in all platforms.
 
+mod api_levels;
+mod appearance;
+mod audit_log;
+mod build_cache;
+mod capabilities;
 mod combine_folders;
+mod coverage;
 mod create_project;
+mod dev_channel;
+mod doctor;
 mod find_cargo_manifest_path;
 mod gen_minimal_project;
+mod hooks;
+mod hot_reload;
+mod launch_outcome;
+mod library_resources;
 mod parse_manifest;
+mod provenance;
+mod rustup;
+mod size_budget;
+mod soak;
+mod storage;
+mod target_matrix;
 
+pub use api_levels::*;
+pub use appearance::*;
+pub use audit_log::*;
+pub use build_cache::*;
+pub use capabilities::*;
 pub use combine_folders::*;
+pub use coverage::*;
 pub use create_project::*;
+pub use dev_channel::*;
+pub use doctor::*;
 pub use find_cargo_manifest_path::*;
 pub use gen_minimal_project::*;
+pub use hooks::*;
+pub use hot_reload::*;
+pub use launch_outcome::*;
+pub use library_resources::*;
 pub use parse_manifest::*;
+pub use provenance::*;
+pub use rustup::*;
+pub use size_budget::*;
+pub use soak::*;
+pub use storage::*;
+pub use target_matrix::*;
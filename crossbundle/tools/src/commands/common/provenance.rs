@@ -0,0 +1,192 @@
+use super::audit_log::{ci_run, rfc3339_now, sha256_file};
+use crate::error::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// File name of the output manifest saved next to the build artifacts.
+pub const OUTPUT_MANIFEST_NAME: &str = "crossbundle-outputs.json";
+
+/// Artifact listed in the output manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct OutputArtifact {
+    /// File name of the artifact.
+    pub name: String,
+    pub sha256: String,
+}
+
+/// Version code of the Android artifacts and how it was generated.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct VersionCodeRecord {
+    /// Version code strategy, e.g. `git-commit-count`.
+    pub strategy: String,
+    pub version_code: u32,
+    /// Per-ABI offsets were added to the version codes of split APKs.
+    #[serde(default)]
+    pub abi_offsets: bool,
+}
+
+/// List of build artifacts with their hashes. Signed together with the artifacts, so a
+/// downloaded artifact can be checked against the whole build output.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct OutputManifest {
+    /// UTC time in RFC 3339 format.
+    pub created: String,
+    /// Version of crossbundle that produced the artifacts.
+    pub builder: String,
+    /// CI run identifier if the artifacts were built on CI.
+    pub ci_run: Option<String>,
+    pub artifacts: Vec<OutputArtifact>,
+    /// Version code of the Android artifacts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_code: Option<VersionCodeRecord>,
+}
+
+impl OutputManifest {
+    /// Creates manifest for given artifacts.
+    pub fn new(artifacts: &[PathBuf]) -> Result<Self> {
+        let artifacts = artifacts
+            .iter()
+            .map(|artifact| {
+                Ok(OutputArtifact {
+                    name: file_name(artifact),
+                    sha256: sha256_file(artifact)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            created: rfc3339_now(),
+            builder: format!("crossbundle {}", env!("CARGO_PKG_VERSION")),
+            ci_run: ci_run(),
+            artifacts,
+            version_code: None,
+        })
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Checks that the artifact is listed in the manifest with the same hash.
+    pub fn verify_artifact(&self, artifact: &Path) -> Result<()> {
+        let name = file_name(artifact);
+        let sha256 = sha256_file(artifact)?;
+        match self.artifacts.iter().find(|listed| listed.name == name) {
+            Some(listed) if listed.sha256 == sha256 => Ok(()),
+            Some(_) => Err(Error::ProvenanceMismatch(format!(
+                "hash of `{}` differs from the output manifest",
+                name
+            ))),
+            None => Err(Error::ProvenanceMismatch(format!(
+                "`{}` is not listed in the output manifest",
+                name
+            ))),
+        }
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Identity expected to sign the artifact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CosignIdentity {
+    /// Public key matching the private key used with `cosign sign-blob --key`.
+    Key(PathBuf),
+    /// Certificate identity and OIDC issuer of the keyless signature, e.g.
+    /// `https://github.com/org/repo/.github/workflows/release.yml@refs/heads/main` and
+    /// `https://token.actions.githubusercontent.com`.
+    Keyless { identity: String, issuer: String },
+}
+
+/// Returns path of the sigstore bundle for the artifact: `<artifact>.sigstore.json`.
+pub fn sigstore_bundle_path(artifact: &Path) -> PathBuf {
+    let mut bundle = artifact.as_os_str().to_owned();
+    bundle.push(".sigstore.json");
+    PathBuf::from(bundle)
+}
+
+fn cosign() -> Result<Command> {
+    let cosign =
+        which::which(bin!("cosign")).map_err(|_| Error::CmdNotFound("cosign".to_owned()))?;
+    Ok(Command::new(cosign))
+}
+
+/// Signs the artifact and saves the signature with certificate into the sigstore bundle.
+/// Keyless signing is used if `key` is not specified: on CI the ambient OIDC token is used,
+/// locally cosign opens the browser to authenticate. Key password is read from the
+/// `COSIGN_PASSWORD` environment variable.
+/// Runs `cosign sign-blob --yes --bundle <bundle> [--key <key>] <artifact>` command
+pub fn cosign_sign_blob(artifact: &Path, key: Option<&Path>) -> Result<PathBuf> {
+    let bundle = sigstore_bundle_path(artifact);
+    let mut cosign = cosign()?;
+    cosign
+        .arg("sign-blob")
+        .arg("--yes")
+        .arg("--bundle")
+        .arg(&bundle);
+    if let Some(key) = key {
+        cosign.arg("--key").arg(key);
+    }
+    cosign.arg(artifact);
+    cosign.output_err(true)?;
+    Ok(bundle)
+}
+
+/// Verifies the artifact signature from the sigstore bundle.
+/// Runs `cosign verify-blob --bundle <bundle> ... <artifact>` command
+pub fn cosign_verify_blob(artifact: &Path, bundle: &Path, identity: &CosignIdentity) -> Result<()> {
+    let mut cosign = cosign()?;
+    cosign.arg("verify-blob").arg("--bundle").arg(bundle);
+    match identity {
+        CosignIdentity::Key(key) => {
+            cosign.arg("--key").arg(key);
+        }
+        CosignIdentity::Keyless { identity, issuer } => {
+            cosign
+                .arg("--certificate-identity")
+                .arg(identity)
+                .arg("--certificate-oidc-issuer")
+                .arg(issuer);
+        }
+    }
+    cosign.arg(artifact);
+    cosign.output_err(false)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let artifact = dir.path().join("app.apk");
+        std::fs::write(&artifact, b"apk").unwrap();
+        let manifest_path = dir.path().join(OUTPUT_MANIFEST_NAME);
+        OutputManifest::new(&[artifact.clone()])
+            .unwrap()
+            .save(&manifest_path)
+            .unwrap();
+        let manifest = OutputManifest::read(&manifest_path).unwrap();
+        assert_eq!(manifest.artifacts[0].name, "app.apk");
+        manifest.verify_artifact(&artifact).unwrap();
+        std::fs::write(&artifact, b"tampered").unwrap();
+        assert!(manifest.verify_artifact(&artifact).is_err());
+        assert_eq!(
+            sigstore_bundle_path(&artifact),
+            dir.path().join("app.apk.sigstore.json")
+        );
+    }
+}
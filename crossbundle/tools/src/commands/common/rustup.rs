@@ -0,0 +1,139 @@
+use crate::error::*;
+use std::process::Command;
+
+/// Returns list of installed rust targets or `None` if rustup is not available.
+pub fn rustup_installed_targets() -> Option<Vec<String>> {
+    let mut rustup = Command::new("rustup");
+    rustup.arg("target").arg("list").arg("--installed");
+    let output = rustup.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_installed_targets(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+fn parse_installed_targets(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(|line| line.trim().to_owned())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Returns rust targets from the list that aren't installed with rustup. Returns
+/// `None` if rustup is not available, e.g. if rust was installed with a package
+/// manager, so installed targets can't be checked.
+pub fn missing_rust_targets(rust_triples: &[&str]) -> Option<Vec<String>> {
+    let installed_targets = rustup_installed_targets()?;
+    Some(filter_missing_targets(rust_triples, &installed_targets))
+}
+
+fn filter_missing_targets(rust_triples: &[&str], installed_targets: &[String]) -> Vec<String> {
+    let mut missing = Vec::new();
+    for rust_triple in rust_triples {
+        let installed = installed_targets.iter().any(|target| target == rust_triple);
+        if !installed && !missing.iter().any(|target| target == rust_triple) {
+            missing.push(rust_triple.to_string());
+        }
+    }
+    missing
+}
+
+/// Returns what's missing to build the standard library from source with
+/// `cargo +nightly -Zbuild-std`: `nightly` toolchain and `rust-src` component of it.
+/// Returns `None` if rustup is not available.
+pub fn missing_build_std_components() -> Option<Vec<String>> {
+    let output = Command::new("rustup")
+        .arg("toolchain")
+        .arg("list")
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    if !has_nightly_toolchain(&String::from_utf8_lossy(&output.stdout)) {
+        return Some(vec!["nightly".to_owned(), "rust-src".to_owned()]);
+    }
+    let mut rustup = Command::new("rustup");
+    rustup.args(["component", "list", "--toolchain", "nightly", "--installed"]);
+    let output = rustup.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let components = parse_installed_targets(&String::from_utf8_lossy(&output.stdout));
+    match components.iter().any(|component| component == "rust-src") {
+        true => Some(Vec::new()),
+        false => Some(vec!["rust-src".to_owned()]),
+    }
+}
+
+/// Returns `true` if `rustup toolchain list` output contains the `nightly` toolchain.
+/// Toolchains of dated nightlies like `nightly-2022-08-01` aren't used by `+nightly`.
+fn has_nightly_toolchain(output: &str) -> bool {
+    output.lines().any(|line| {
+        let toolchain = line.split_whitespace().next().unwrap_or_default();
+        toolchain == "nightly"
+            || toolchain.strip_prefix("nightly-").map_or(false, |host| {
+                !host.starts_with(|c: char| c.is_ascii_digit())
+            })
+    })
+}
+
+/// Installs components returned by [`missing_build_std_components`]: the `nightly`
+/// toolchain with `rust-src` or only the component.
+pub fn rustup_install_build_std(missing: &[String]) -> Result<()> {
+    let mut rustup = Command::new("rustup");
+    if missing.iter().any(|component| component == "nightly") {
+        rustup.args(["toolchain", "install", "nightly", "--component", "rust-src"]);
+    } else if missing.iter().any(|component| component == "rust-src") {
+        rustup.args(["component", "add", "rust-src", "--toolchain", "nightly"]);
+    } else {
+        return Ok(());
+    }
+    rustup.output_err(true)?;
+    Ok(())
+}
+
+/// Installs rust targets with `rustup target add`.
+pub fn rustup_target_add(rust_triples: &[String]) -> Result<()> {
+    if rust_triples.is_empty() {
+        return Ok(());
+    }
+    let mut rustup = Command::new("rustup");
+    rustup.arg("target").arg("add").args(rust_triples);
+    rustup.output_err(true)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_missing_targets() {
+        let installed =
+            parse_installed_targets("x86_64-unknown-linux-gnu\naarch64-linux-android\n\n");
+        let missing = filter_missing_targets(
+            &[
+                "aarch64-linux-android",
+                "armv7-linux-androideabi",
+                "armv7-linux-androideabi",
+            ],
+            &installed,
+        );
+        assert_eq!(missing, vec!["armv7-linux-androideabi".to_owned()]);
+    }
+
+    #[test]
+    fn test_has_nightly_toolchain() {
+        assert!(has_nightly_toolchain(
+            "stable-aarch64-apple-darwin (default)\nnightly-aarch64-apple-darwin\n"
+        ));
+        assert!(!has_nightly_toolchain(
+            "stable-aarch64-apple-darwin (default)\nnightly-2022-08-01-aarch64-apple-darwin\n"
+        ));
+        assert!(!has_nightly_toolchain(""));
+    }
+}
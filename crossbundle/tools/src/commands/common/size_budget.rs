@@ -0,0 +1,274 @@
+use crate::error::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+/// File name of the size budgets in the package directory.
+pub const SIZE_BUDGETS_FILE: &str = "budgets.toml";
+
+/// File name of the recorded artifact sizes saved next to the build artifacts.
+pub const SIZE_REPORT_NAME: &str = "crossbundle-sizes.json";
+
+/// Size in bytes. Deserialized from a number of bytes or a string like `150MB`. `KB`,
+/// `MB` and `GB` are multiples of 1024.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(try_from = "RawByteSize", into = "u64")]
+pub struct ByteSize(pub u64);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawByteSize {
+    Bytes(u64),
+    Text(String),
+}
+
+impl TryFrom<RawByteSize> for ByteSize {
+    type Error = Error;
+
+    fn try_from(raw: RawByteSize) -> Result<Self> {
+        match raw {
+            RawByteSize::Bytes(bytes) => Ok(Self(bytes)),
+            RawByteSize::Text(text) => text.parse(),
+        }
+    }
+}
+
+impl From<ByteSize> for u64 {
+    fn from(size: ByteSize) -> Self {
+        size.0
+    }
+}
+
+impl std::str::FromStr for ByteSize {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+            "" | "B" => 1,
+            "KB" | "K" => 1 << 10,
+            "MB" | "M" => 1 << 20,
+            "GB" | "G" => 1 << 30,
+            _ => return Err(Error::InvalidByteSize(s.to_owned())),
+        };
+        let number: f64 = number
+            .parse()
+            .map_err(|_| Error::InvalidByteSize(s.to_owned()))?;
+        Ok(Self((number * multiplier as f64) as u64))
+    }
+}
+
+impl std::fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let size = self.0 as f64;
+        if self.0 >= 1 << 30 {
+            write!(f, "{:.2} GB", size / (1u64 << 30) as f64)
+        } else if self.0 >= 1 << 20 {
+            write!(f, "{:.2} MB", size / (1u64 << 20) as f64)
+        } else if self.0 >= 1 << 10 {
+            write!(f, "{:.2} KB", size / (1u64 << 10) as f64)
+        } else {
+            write!(f, "{} B", self.0)
+        }
+    }
+}
+
+/// Size limits of the build artifacts read from `budgets.toml`:
+///
+/// ```toml
+/// max_download_size = "150MB"
+/// max_native_lib_size = "40MB"
+/// max_assets_size = "100MB"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SizeBudgets {
+    /// Maximum size of the APK or AAB file.
+    pub max_download_size: Option<ByteSize>,
+    /// Maximum uncompressed size of the native libraries of a single ABI.
+    pub max_native_lib_size: Option<ByteSize>,
+    /// Maximum uncompressed size of the assets.
+    pub max_assets_size: Option<ByteSize>,
+}
+
+impl SizeBudgets {
+    pub fn read(path: &Path) -> Result<Self> {
+        Ok(toml::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Returns descriptions of the exceeded budgets.
+    pub fn check(&self, sizes: &ArtifactSizes) -> Vec<String> {
+        let mut exceeded = Vec::new();
+        let mut check = |name: String, size: u64, budget: Option<ByteSize>| {
+            if let Some(budget) = budget {
+                if size > budget.0 {
+                    exceeded.push(format!(
+                        "{} is {}, budget is {} (over by {})",
+                        name,
+                        ByteSize(size),
+                        budget,
+                        ByteSize(size - budget.0)
+                    ));
+                }
+            }
+        };
+        check(
+            "download size".to_owned(),
+            sizes.download,
+            self.max_download_size,
+        );
+        for (abi, size) in &sizes.native_libs {
+            check(
+                format!("{} native libraries", abi),
+                *size,
+                self.max_native_lib_size,
+            );
+        }
+        check("assets".to_owned(), sizes.assets, self.max_assets_size);
+        exceeded
+    }
+}
+
+/// Sizes of the APK or AAB contents.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ArtifactSizes {
+    /// Size of the artifact file.
+    pub download: u64,
+    /// Uncompressed size of the native libraries keyed by ABI.
+    pub native_libs: BTreeMap<String, u64>,
+    /// Uncompressed size of the assets.
+    pub assets: u64,
+}
+
+impl ArtifactSizes {
+    /// Measures APK or AAB. Entries of all AAB modules are counted.
+    pub fn measure(artifact: &Path) -> Result<Self> {
+        let mut sizes = Self {
+            download: std::fs::metadata(artifact)?.len(),
+            ..Default::default()
+        };
+        let mut archive = zip::ZipArchive::new(File::open(artifact)?)?;
+        for i in 0..archive.len() {
+            let entry = archive.by_index(i)?;
+            let parts: Vec<&str> = entry.name().split('/').collect();
+            // AAB entries are prefixed with the module name
+            let parts = match parts.first() {
+                Some(&"lib") | Some(&"assets") => &parts[..],
+                _ => &parts[1.min(parts.len())..],
+            };
+            match parts {
+                ["lib", abi, file] if file.ends_with(".so") => {
+                    *sizes.native_libs.entry(abi.to_string()).or_default() += entry.size();
+                }
+                ["assets", ..] => sizes.assets += entry.size(),
+                _ => {}
+            }
+        }
+        Ok(sizes)
+    }
+
+    /// Returns lines describing the sizes and their changes against the previous build.
+    pub fn diff(&self, previous: Option<&ArtifactSizes>) -> Vec<String> {
+        let line = |name: &str, size: u64, previous: Option<u64>| match previous {
+            Some(previous) if previous != size => {
+                let sign = if size > previous { '+' } else { '-' };
+                format!(
+                    "{}: {} ({}{})",
+                    name,
+                    ByteSize(size),
+                    sign,
+                    ByteSize(size.abs_diff(previous))
+                )
+            }
+            _ => format!("{}: {}", name, ByteSize(size)),
+        };
+        let mut lines = vec![line(
+            "download size",
+            self.download,
+            previous.map(|p| p.download),
+        )];
+        for (abi, size) in &self.native_libs {
+            let previous = previous.map(|p| p.native_libs.get(abi).copied().unwrap_or_default());
+            lines.push(line(&format!("lib/{}", abi), *size, previous));
+        }
+        lines.push(line("assets", self.assets, previous.map(|p| p.assets)));
+        lines
+    }
+}
+
+/// Recorded artifact sizes keyed by the artifact file name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SizeReport {
+    pub artifacts: BTreeMap<String, ArtifactSizes>,
+}
+
+impl SizeReport {
+    /// Returns path of the report in the output directory of the artifact.
+    pub fn path(artifact: &Path) -> PathBuf {
+        artifact
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(SIZE_REPORT_NAME)
+    }
+
+    /// Reads the report or returns an empty one if it doesn't exist.
+    pub fn read_or_default(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_size_budgets() {
+        assert_eq!("150MB".parse::<ByteSize>().unwrap().0, 150 << 20);
+        assert_eq!("1.5 KB".parse::<ByteSize>().unwrap().0, 1536);
+        assert!("10 apples".parse::<ByteSize>().is_err());
+        let budgets: SizeBudgets =
+            toml::from_str("max_download_size = 1000\nmax_native_lib_size = \"1KB\"").unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let aab = dir.path().join("game.aab");
+        let mut zip = zip::ZipWriter::new(File::create(&aab).unwrap());
+        let options = zip::write::FileOptions::default();
+        zip.start_file("base/lib/arm64-v8a/libgame.so", options)
+            .unwrap();
+        zip.write_all(&[0; 2048]).unwrap();
+        zip.start_file("base/assets/level.dat", options).unwrap();
+        zip.write_all(&[0; 100]).unwrap();
+        zip.finish().unwrap();
+
+        let sizes = ArtifactSizes::measure(&aab).unwrap();
+        assert_eq!(sizes.native_libs["arm64-v8a"], 2048);
+        assert_eq!(sizes.assets, 100);
+        let exceeded = budgets.check(&sizes);
+        assert_eq!(exceeded.len(), 1);
+        assert!(exceeded[0].starts_with("arm64-v8a native libraries"));
+
+        let previous = ArtifactSizes {
+            assets: 200,
+            ..sizes.clone()
+        };
+        assert!(sizes
+            .diff(Some(&previous))
+            .contains(&"assets: 100 B (-100 B)".to_owned()));
+    }
+}
@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Thermal status of the device. Levels follow `PowerManager.THERMAL_STATUS_*` of
+/// Android, iOS thermal states are mapped onto them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThermalStatus {
+    None,
+    Light,
+    Moderate,
+    Severe,
+    Critical,
+    Emergency,
+    Shutdown,
+}
+
+impl ThermalStatus {
+    /// Returns the status for the value of `PowerManager.getCurrentThermalStatus()`.
+    pub fn from_android(status: u32) -> Self {
+        match status {
+            0 => Self::None,
+            1 => Self::Light,
+            2 => Self::Moderate,
+            3 => Self::Severe,
+            4 => Self::Critical,
+            5 => Self::Emergency,
+            _ => Self::Shutdown,
+        }
+    }
+
+    /// Returns the status for `NSProcessInfoThermalState` name reported by the debug
+    /// overlay of the `crossbow` crate.
+    pub fn from_ios(state: &str) -> Option<Self> {
+        match state {
+            "nominal" => Some(Self::None),
+            "fair" => Some(Self::Light),
+            "serious" => Some(Self::Severe),
+            "critical" => Some(Self::Critical),
+            _ => None,
+        }
+    }
+
+    /// The system limits performance of the device on this status.
+    pub fn is_throttling(&self) -> bool {
+        *self >= Self::Moderate
+    }
+}
+
+/// Measurement taken during the soak test.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SoakSample {
+    /// Seconds since the start of the test.
+    pub elapsed_secs: u64,
+    /// Average frame rate of the last frames reported by the game.
+    pub fps: Option<f32>,
+    pub thermal_status: Option<ThermalStatus>,
+    /// Highest CPU, GPU or skin temperature in degrees Celsius.
+    pub temperature: Option<f32>,
+}
+
+/// Parses `fps=<fps> frame_ms=<ms> [thermal=<state>]` returned by `overlay stats`
+/// console command of the game.
+pub fn parse_overlay_stats(stats: &str) -> (Option<f32>, Option<ThermalStatus>) {
+    let mut fps = None;
+    let mut thermal_status = None;
+    for pair in stats.split_whitespace() {
+        match pair.split_once('=') {
+            Some(("fps", value)) => fps = value.parse().ok(),
+            Some(("thermal", value)) => thermal_status = ThermalStatus::from_ios(value),
+            _ => {}
+        }
+    }
+    (fps, thermal_status)
+}
+
+/// Throttling report of the soak test.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SoakReport {
+    pub duration_secs: u64,
+    /// Average frame rate of the first minute, before the device heats up.
+    pub initial_fps: Option<f32>,
+    /// Average frame rate of the last quarter of the test.
+    pub sustained_fps: Option<f32>,
+    pub min_fps: Option<f32>,
+    /// Sustained frame rate relative to the initial one, from 0 to 1.
+    pub sustained_ratio: Option<f32>,
+    pub max_thermal_status: Option<ThermalStatus>,
+    /// Seconds since the start when the device started throttling.
+    pub throttled_after_secs: Option<u64>,
+    /// Seconds spent in each thermal status.
+    pub thermal_status_secs: BTreeMap<ThermalStatus, u64>,
+    pub max_temperature: Option<f32>,
+    pub samples: Vec<SoakSample>,
+}
+
+impl SoakReport {
+    /// Builds the report from samples taken every `interval_secs`.
+    pub fn new(samples: Vec<SoakSample>, interval_secs: u64) -> Self {
+        let duration_secs = samples.last().map_or(0, |sample| sample.elapsed_secs);
+        let initial: Vec<f32> = samples
+            .iter()
+            .filter(|sample| sample.elapsed_secs <= 60)
+            .filter_map(|sample| sample.fps)
+            .collect();
+        let sustained_from = duration_secs - duration_secs / 4;
+        let sustained: Vec<f32> = samples
+            .iter()
+            .filter(|sample| sample.elapsed_secs >= sustained_from)
+            .filter_map(|sample| sample.fps)
+            .collect();
+        let initial_fps = mean(&initial);
+        let sustained_fps = mean(&sustained);
+        let mut thermal_status_secs = BTreeMap::new();
+        for status in samples.iter().filter_map(|sample| sample.thermal_status) {
+            *thermal_status_secs.entry(status).or_insert(0) += interval_secs;
+        }
+        Self {
+            duration_secs,
+            initial_fps,
+            sustained_fps,
+            min_fps: samples
+                .iter()
+                .filter_map(|sample| sample.fps)
+                .reduce(f32::min),
+            sustained_ratio: initial_fps
+                .zip(sustained_fps)
+                .filter(|(initial, _)| *initial > 0.0)
+                .map(|(initial, sustained)| sustained / initial),
+            max_thermal_status: thermal_status_secs.keys().next_back().copied(),
+            throttled_after_secs: samples
+                .iter()
+                .find(|sample| sample.thermal_status.map_or(false, |s| s.is_throttling()))
+                .map(|sample| sample.elapsed_secs),
+            thermal_status_secs,
+            max_temperature: samples
+                .iter()
+                .filter_map(|sample| sample.temperature)
+                .reduce(f32::max),
+            samples,
+        }
+    }
+}
+
+fn mean(values: &[f32]) -> Option<f32> {
+    match values.is_empty() {
+        true => None,
+        false => Some(values.iter().sum::<f32>() / values.len() as f32),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_soak_report() {
+        assert_eq!(
+            parse_overlay_stats("fps=59.80 frame_ms=16.72 thermal=serious"),
+            (Some(59.8), Some(ThermalStatus::Severe))
+        );
+        let samples = (0..=10)
+            .map(|i| SoakSample {
+                elapsed_secs: i * 60,
+                fps: Some(if i < 5 { 60.0 } else { 45.0 }),
+                thermal_status: Some(if i < 5 {
+                    ThermalStatus::Light
+                } else {
+                    ThermalStatus::Moderate
+                }),
+                temperature: Some(30.0 + i as f32),
+            })
+            .collect();
+        let report = SoakReport::new(samples, 60);
+        assert_eq!(report.initial_fps, Some(60.0));
+        assert_eq!(report.sustained_fps, Some(45.0));
+        assert_eq!(report.sustained_ratio, Some(0.75));
+        assert_eq!(report.min_fps, Some(45.0));
+        assert_eq!(report.max_thermal_status, Some(ThermalStatus::Moderate));
+        assert_eq!(report.throttled_after_secs, Some(300));
+        assert_eq!(report.thermal_status_secs[&ThermalStatus::Light], 300);
+        assert_eq!(report.max_temperature, Some(40.0));
+    }
+}
@@ -0,0 +1,631 @@
+use crate::error::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsStr,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+/// Storage of archived artifacts: release artifacts, R8 mappings and unstripped
+/// libraries. Keys are relative paths like `mappings/1.2.0-42/mapping.txt`.
+pub trait ArtifactStorage {
+    /// Uploads the file under the key. Returns location of the stored file.
+    fn put(&self, key: &str, file: &Path) -> Result<String>;
+    /// Downloads the file stored under the key into `dest`.
+    fn get(&self, key: &str, dest: &Path) -> Result<()>;
+    /// Returns sorted keys of the files stored under the prefix, e.g. `mappings`.
+    /// Returns empty list if nothing is stored under the prefix.
+    fn list(&self, prefix: &str) -> Result<Vec<String>>;
+    /// Returns local path of the file stored under the key. Remote files are downloaded
+    /// into `cache_dir` under the same key once.
+    fn fetch(&self, key: &str, cache_dir: &Path) -> Result<PathBuf> {
+        let dest = cache_dir.join(key);
+        if !dest.exists() {
+            self.get(key, &dest)?;
+        }
+        Ok(dest)
+    }
+}
+
+/// Storage backend selected in `storage` of the configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum StorageConfig {
+    /// Local directory. Relative paths are resolved from the target directory.
+    Local { path: PathBuf },
+    /// Amazon S3 or S3-compatible bucket, accessed with `aws` CLI and its credentials.
+    S3 {
+        bucket: String,
+        #[serde(default)]
+        prefix: String,
+        region: Option<String>,
+        /// Endpoint of S3-compatible storages like MinIO or Cloudflare R2.
+        endpoint: Option<String>,
+    },
+    /// Google Cloud Storage bucket, accessed with `gcloud` CLI and its credentials.
+    Gcs {
+        bucket: String,
+        #[serde(default)]
+        prefix: String,
+    },
+    /// WebDAV server, accessed with `curl`. The password is read from the environment
+    /// variable.
+    WebDav {
+        url: String,
+        username: Option<String>,
+        password_env: Option<String>,
+    },
+}
+
+impl StorageConfig {
+    /// Creates the storage. Without configuration artifacts are stored in
+    /// `<target_dir>/crossbundle`.
+    pub fn storage(
+        config: Option<&StorageConfig>,
+        target_dir: &Path,
+    ) -> Result<Box<dyn ArtifactStorage>> {
+        Ok(match config {
+            None => Box::new(LocalStorage::new(&target_dir.join("crossbundle"))),
+            Some(Self::Local { path }) => Box::new(LocalStorage::new(&target_dir.join(path))),
+            Some(Self::S3 {
+                bucket,
+                prefix,
+                region,
+                endpoint,
+            }) => Box::new(S3Storage {
+                bucket: bucket.clone(),
+                prefix: prefix.clone(),
+                region: region.clone(),
+                endpoint: endpoint.clone(),
+            }),
+            Some(Self::Gcs { bucket, prefix }) => Box::new(GcsStorage {
+                bucket: bucket.clone(),
+                prefix: prefix.clone(),
+            }),
+            Some(Self::WebDav {
+                url,
+                username,
+                password_env,
+            }) => {
+                let password = match password_env {
+                    Some(name) => Some(
+                        std::env::var(name)
+                            .map_err(|_| Error::StorageCredentialsNotFound(name.clone()))?,
+                    ),
+                    None => None,
+                };
+                Box::new(WebDavStorage {
+                    url: url.trim_end_matches('/').to_owned(),
+                    username: username.clone(),
+                    password,
+                })
+            }
+        })
+    }
+}
+
+/// Joins the prefix and the key with `/`.
+fn object_key(prefix: &str, key: &str) -> String {
+    let prefix = prefix.trim_matches('/');
+    if prefix.is_empty() {
+        key.to_owned()
+    } else {
+        format!("{}/{}", prefix, key)
+    }
+}
+
+/// Strips the prefix from the object name. Returns `None` for objects outside of the
+/// prefix.
+fn strip_object_prefix(prefix: &str, object: &str) -> Option<String> {
+    let prefix = prefix.trim_matches('/');
+    if prefix.is_empty() {
+        Some(object.to_owned())
+    } else {
+        Some(object.strip_prefix(prefix)?.strip_prefix('/')?.to_owned())
+    }
+}
+
+/// Finds the CLI of the storage in PATH.
+fn command(name: &str) -> Result<Command> {
+    let path = which::which(name).map_err(|_| Error::CmdNotFound(name.to_owned()))?;
+    Ok(Command::new(path))
+}
+
+/// Stores artifacts in the local directory.
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_owned(),
+        }
+    }
+}
+
+impl ArtifactStorage for LocalStorage {
+    fn put(&self, key: &str, file: &Path) -> Result<String> {
+        let dest = self.root.join(key);
+        // Files like unstripped libraries can be written into the storage directly
+        if dunce::canonicalize(&dest).ok() != Some(dunce::canonicalize(file)?) {
+            std::fs::create_dir_all(dest.parent().unwrap())?;
+            std::fs::copy(file, &dest)?;
+        }
+        Ok(dest.display().to_string())
+    }
+
+    fn get(&self, key: &str, dest: &Path) -> Result<()> {
+        let source = self.root.join(key);
+        if !source.exists() {
+            return Err(Error::PathNotFound(source));
+        }
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(source, dest)?;
+        Ok(())
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut dirs = vec![self.root.join(prefix.trim_matches('/'))];
+        while let Some(dir) = dirs.pop() {
+            if !dir.is_dir() {
+                continue;
+            }
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if let Ok(key) = path.strip_prefix(&self.root) {
+                    let key = key
+                        .components()
+                        .map(|c| c.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>();
+                    keys.push(key.join("/"));
+                }
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+
+    fn fetch(&self, key: &str, _cache_dir: &Path) -> Result<PathBuf> {
+        let path = self.root.join(key);
+        if !path.exists() {
+            return Err(Error::PathNotFound(path));
+        }
+        Ok(path)
+    }
+}
+
+/// Stores artifacts in the S3 bucket.
+/// Runs `aws s3 cp` and `aws s3 ls` commands
+#[derive(Debug, Clone)]
+pub struct S3Storage {
+    bucket: String,
+    prefix: String,
+    region: Option<String>,
+    endpoint: Option<String>,
+}
+
+impl S3Storage {
+    fn url(&self, key: &str) -> String {
+        format!("s3://{}/{}", self.bucket, object_key(&self.prefix, key))
+    }
+
+    fn aws(&self) -> Result<Command> {
+        let mut aws = command("aws")?;
+        if let Some(region) = &self.region {
+            aws.arg("--region").arg(region);
+        }
+        if let Some(endpoint) = &self.endpoint {
+            aws.arg("--endpoint-url").arg(endpoint);
+        }
+        Ok(aws)
+    }
+
+    fn copy(&self, from: impl AsRef<OsStr>, to: impl AsRef<OsStr>) -> Result<()> {
+        let mut aws = self.aws()?;
+        aws.args(["s3", "cp", "--only-show-errors"])
+            .arg(from)
+            .arg(to);
+        aws.output_err(true)?;
+        Ok(())
+    }
+}
+
+/// Parses output of `aws s3 ls --recursive` with lines like
+/// `2022-08-01 12:00:00    1024 ci/mappings/1.0.0-1/mapping.txt`.
+fn parse_s3_ls(output: &str, prefix: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut object = line.trim_start();
+            // Skip date, time and size columns
+            for _ in 0..3 {
+                object = object.split_once(char::is_whitespace)?.1.trim_start();
+            }
+            strip_object_prefix(prefix, object)
+        })
+        .collect()
+}
+
+impl ArtifactStorage for S3Storage {
+    fn put(&self, key: &str, file: &Path) -> Result<String> {
+        let url = self.url(key);
+        self.copy(file, &url)?;
+        Ok(url)
+    }
+
+    fn get(&self, key: &str, dest: &Path) -> Result<()> {
+        self.copy(self.url(key), dest)
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut aws = self.aws()?;
+        aws.args(["s3", "ls", "--recursive"])
+            .arg(format!("{}/", self.url(prefix.trim_matches('/'))));
+        let output = aws.output()?;
+        // `aws s3 ls` exits with 1 without errors if nothing matches the prefix
+        if !output.status.success() && !output.stderr.is_empty() {
+            return Err(Error::CmdFailed(
+                aws,
+                String::from_utf8_lossy(&output.stdout).to_string(),
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+        let mut keys = parse_s3_ls(&String::from_utf8_lossy(&output.stdout), &self.prefix);
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+/// Stores artifacts in the Google Cloud Storage bucket.
+/// Runs `gcloud storage cp` and `gcloud storage ls` commands
+#[derive(Debug, Clone)]
+pub struct GcsStorage {
+    bucket: String,
+    prefix: String,
+}
+
+impl GcsStorage {
+    fn url(&self, key: &str) -> String {
+        format!("gs://{}/{}", self.bucket, object_key(&self.prefix, key))
+    }
+
+    fn copy(&self, from: impl AsRef<OsStr>, to: impl AsRef<OsStr>) -> Result<()> {
+        let mut gcloud = command("gcloud")?;
+        gcloud.args(["storage", "cp", "--quiet"]).arg(from).arg(to);
+        gcloud.output_err(true)?;
+        Ok(())
+    }
+}
+
+/// Parses output of `gcloud storage ls` with object URLs like
+/// `gs://bucket/ci/mappings/1.0.0-1/mapping.txt`.
+fn parse_gcs_ls(output: &str, bucket: &str, prefix: &str) -> Vec<String> {
+    let bucket_url = format!("gs://{}/", bucket);
+    output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix(&bucket_url))
+        .filter(|object| !object.ends_with('/') && !object.ends_with(':'))
+        .filter_map(|object| strip_object_prefix(prefix, object))
+        .collect()
+}
+
+impl ArtifactStorage for GcsStorage {
+    fn put(&self, key: &str, file: &Path) -> Result<String> {
+        let url = self.url(key);
+        self.copy(file, &url)?;
+        Ok(url)
+    }
+
+    fn get(&self, key: &str, dest: &Path) -> Result<()> {
+        self.copy(self.url(key), dest)
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut gcloud = command("gcloud")?;
+        gcloud
+            .args(["storage", "ls"])
+            .arg(format!("{}/**", self.url(prefix.trim_matches('/'))));
+        let output = gcloud.output()?;
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        if !output.status.success() && !stderr.contains("matched no objects") {
+            return Err(Error::CmdFailed(
+                gcloud,
+                String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr,
+            ));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut keys = parse_gcs_ls(&stdout, &self.bucket, &self.prefix);
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+/// Stores artifacts on the WebDAV server. Missing collections are created with `MKCOL`.
+/// Runs `curl` command
+#[derive(Debug, Clone)]
+pub struct WebDavStorage {
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl WebDavStorage {
+    fn curl(&self) -> Result<Command> {
+        let mut curl = command("curl")?;
+        curl.args(["--silent", "--show-error"]);
+        Ok(curl)
+    }
+
+    /// Returns curl config with the credentials. It's passed with `--config -` on stdin,
+    /// so the password doesn't appear in the process list.
+    fn curl_config(&self) -> Option<String> {
+        let username = self.username.as_ref()?;
+        let user = format!(
+            "{}:{}",
+            username,
+            self.password.as_deref().unwrap_or_default()
+        );
+        let user = user.replace('\\', "\\\\").replace('"', "\\\"");
+        Some(format!("user = \"{}\"\n", user))
+    }
+
+    /// Runs curl with the credentials and returns its output.
+    fn run(&self, mut curl: Command, print_logs: bool) -> Result<Output> {
+        let config = self.curl_config();
+        match config {
+            Some(_) => curl.args(["--config", "-"]).stdin(Stdio::piped()),
+            None => curl.stdin(Stdio::null()),
+        };
+        if !print_logs {
+            curl.stdout(Stdio::piped()).stderr(Stdio::piped());
+        }
+        let mut child = curl.spawn()?;
+        if let (Some(config), Some(mut stdin)) = (config, child.stdin.take()) {
+            stdin.write_all(config.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(Error::CmdFailed(
+                curl,
+                String::from_utf8_lossy(&output.stdout).to_string(),
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+        Ok(output)
+    }
+
+    /// Lists the collection with `PROPFIND` of depth 1. Returns keys of the members,
+    /// keys of collections end with `/`. Returns `None` if the collection doesn't exist.
+    fn propfind(&self, collection: &str) -> Result<Option<Vec<String>>> {
+        let url = match collection {
+            "" => format!("{}/", self.url),
+            collection => format!("{}/{}/", self.url, collection),
+        };
+        let mut curl = self.curl()?;
+        curl.args(["--request", "PROPFIND", "--header", "Depth: 1"])
+            .args(["--write-out", "\n%{http_code}"])
+            .arg(&url);
+        let output = self.run(curl, false)?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let (body, status) = stdout.rsplit_once('\n').unwrap_or(("", &stdout));
+        match status.trim() {
+            "404" => Ok(None),
+            status if status.starts_with('2') => Ok(Some(
+                parse_propfind_hrefs(body)
+                    .iter()
+                    .filter_map(|href| webdav_key(&self.url, href))
+                    .collect(),
+            )),
+            status => Err(Error::StorageRequestFailed(format!(
+                "PROPFIND {} returned {}",
+                url, status
+            ))),
+        }
+    }
+}
+
+/// Returns decoded values of `href` elements of the WebDAV multistatus response.
+fn parse_propfind_hrefs(xml: &str) -> Vec<String> {
+    xml.split('<')
+        .filter_map(|element| {
+            let (tag, text) = element.split_once('>')?;
+            let name = tag.rsplit(':').next()?;
+            (!tag.starts_with('/') && name.trim() == "href").then(|| percent_decode(text.trim()))
+        })
+        .collect()
+}
+
+/// Returns key of the resource by its `href` relative to the storage URL.
+fn webdav_key(base_url: &str, href: &str) -> Option<String> {
+    // Servers return absolute paths or full URLs
+    fn url_path(url: &str) -> &str {
+        match url.split_once("://") {
+            Some((_, rest)) => rest.find('/').map_or("", |i| &rest[i..]),
+            None => url,
+        }
+    }
+    let base = percent_decode(url_path(base_url));
+    let path = url_path(href);
+    let key = path.strip_prefix(base.trim_end_matches('/'))?;
+    let key = key.trim_start_matches('/');
+    (!key.is_empty()).then(|| key.to_owned())
+}
+
+/// Decodes `%XX` escapes of the URL path.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+impl ArtifactStorage for WebDavStorage {
+    fn put(&self, key: &str, file: &Path) -> Result<String> {
+        // MKCOL fails with 405 on existing collections, so its status is ignored
+        let segments = key.split('/').collect::<Vec<_>>();
+        for i in 1..segments.len() {
+            let mut curl = self.curl()?;
+            curl.args(["--request", "MKCOL", "--output"])
+                .arg(if cfg!(windows) { "NUL" } else { "/dev/null" })
+                .arg(format!("{}/{}/", self.url, segments[..i].join("/")));
+            self.run(curl, false)?;
+        }
+        let url = format!("{}/{}", self.url, key);
+        let mut curl = self.curl()?;
+        curl.arg("--fail").arg("--upload-file").arg(file).arg(&url);
+        self.run(curl, true)?;
+        Ok(url)
+    }
+
+    fn get(&self, key: &str, dest: &Path) -> Result<()> {
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut curl = self.curl()?;
+        curl.arg("--fail")
+            .arg("--output")
+            .arg(dest)
+            .arg(format!("{}/{}", self.url, key));
+        self.run(curl, true)?;
+        Ok(())
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut collections = vec![prefix.trim_matches('/').to_owned()];
+        while let Some(collection) = collections.pop() {
+            // The response includes the listed collection itself
+            for key in self.propfind(&collection)?.unwrap_or_default() {
+                match key.strip_suffix('/') {
+                    Some(member) if member != collection => collections.push(member.to_owned()),
+                    Some(_) => {}
+                    None => keys.push(key),
+                }
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = StorageConfig::storage(None, dir.path()).unwrap();
+        let file = dir.path().join("mapping.txt");
+        std::fs::write(&file, "a -> b").unwrap();
+        let location = storage.put("mappings/1.0.0-1/mapping.txt", &file).unwrap();
+        assert!(location.ends_with("mapping.txt"));
+        // Putting the stored file again doesn't truncate it
+        storage
+            .put("mappings/1.0.0-1/mapping.txt", Path::new(&location))
+            .unwrap();
+        let dest = dir.path().join("restored.txt");
+        storage.get("mappings/1.0.0-1/mapping.txt", &dest).unwrap();
+        assert_eq!(std::fs::read_to_string(dest).unwrap(), "a -> b");
+        assert_eq!(
+            storage.list("mappings").unwrap(),
+            vec!["mappings/1.0.0-1/mapping.txt".to_owned()]
+        );
+        assert!(storage.list("releases").unwrap().is_empty());
+        let cache_dir = dir.path().join("cache");
+        let fetched = storage
+            .fetch("mappings/1.0.0-1/mapping.txt", &cache_dir)
+            .unwrap();
+        assert_eq!(fetched, Path::new(&location));
+        assert!(!cache_dir.exists());
+        assert_eq!(
+            object_key("/ci/", "releases/app.aab"),
+            "ci/releases/app.aab"
+        );
+    }
+
+    #[test]
+    fn test_parse_cloud_listings() {
+        let output = "2022-08-01 12:00:00       1024 ci/mappings/1.0.0-1/mapping.txt\n\
+                      2022-08-01 12:00:01         18 ci/mappings/1.0.0-1/resources map.txt\n\
+                      2022-08-01 12:00:02         18 other/mapping.txt\n";
+        assert_eq!(
+            parse_s3_ls(output, "ci"),
+            vec![
+                "mappings/1.0.0-1/mapping.txt".to_owned(),
+                "mappings/1.0.0-1/resources map.txt".to_owned(),
+            ]
+        );
+        let output = "gs://bucket/releases/1.0.0-1/:\n\
+                      gs://bucket/releases/1.0.0-1/app.aab\n\
+                      gs://bucket/releases/1.0.0-2/app.apk\n";
+        assert_eq!(
+            parse_gcs_ls(output, "bucket", ""),
+            vec![
+                "releases/1.0.0-1/app.aab".to_owned(),
+                "releases/1.0.0-2/app.apk".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_propfind() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<D:multistatus xmlns:D="DAV:">
+  <D:response><D:href>/dav/ci/releases/</D:href></D:response>
+  <D:response><D:href>/dav/ci/releases/1.0.0-1/</D:href></D:response>
+  <D:response><d:href>https://example.com/dav/ci/releases/my%20app.apk</d:href></D:response>
+</D:multistatus>"#;
+        let keys = parse_propfind_hrefs(xml)
+            .iter()
+            .filter_map(|href| webdav_key("https://example.com/dav/ci", href))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            vec!["releases/", "releases/1.0.0-1/", "releases/my app.apk"]
+        );
+        assert_eq!(webdav_key("https://example.com/dav", "/dav/"), None);
+    }
+
+    #[test]
+    fn test_webdav_credentials_config() {
+        let storage = WebDavStorage {
+            url: "https://example.com/dav".to_owned(),
+            username: Some("ci".to_owned()),
+            password: Some(r#"p"a\ss"#.to_owned()),
+        };
+        assert_eq!(
+            storage.curl_config().unwrap(),
+            "user = \"ci:p\\\"a\\\\ss\"\n"
+        );
+        let anonymous = WebDavStorage {
+            username: None,
+            ..storage
+        };
+        assert_eq!(anonymous.curl_config(), None);
+    }
+}
@@ -0,0 +1,191 @@
+use super::rustup::rustup_installed_targets;
+#[cfg(any(feature = "android", feature = "apple"))]
+use crate::types::IntoRustTriple;
+#[cfg(feature = "apple")]
+use crate::types::IosTarget;
+#[cfg(feature = "android")]
+use crate::{
+    commands::android::keytool_init,
+    types::{AndroidNdk, AndroidSdk, AndroidTarget},
+};
+use serde::Serialize;
+#[cfg(feature = "apple")]
+use std::process::Command;
+
+/// API level used to check that the NDK provides clang for the target.
+#[cfg(feature = "android")]
+const NDK_CHECK_PLATFORM: u32 = 21;
+
+/// What the environment can produce for the single platform and architecture.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TargetCapability {
+    /// `android`, `ios` or `ios-simulator`.
+    pub platform: String,
+    /// Rust target triple.
+    pub target: String,
+    /// The rust target is installed with rustup.
+    pub rust_target: bool,
+    /// Platform toolchain is found: Android SDK and NDK, or Xcode.
+    pub toolchain: bool,
+    /// Artifacts can be signed: keytool for Android debug keys, a codesigning identity for
+    /// iOS devices. Simulator builds don't need signing.
+    pub signing: bool,
+    /// Missing pieces with suggested fixes.
+    pub missing: Vec<String>,
+}
+
+impl TargetCapability {
+    fn new(platform: &str, target: &str, installed_targets: &Option<Vec<String>>) -> Self {
+        let rust_target = installed_targets
+            .as_ref()
+            .map_or(false, |targets| targets.iter().any(|t| t == target));
+        let mut missing = Vec::new();
+        if !rust_target {
+            missing.push(format!("Run `rustup target add {}`", target));
+        }
+        Self {
+            platform: platform.to_owned(),
+            target: target.to_owned(),
+            rust_target,
+            toolchain: false,
+            signing: false,
+            missing,
+        }
+    }
+
+    /// Returns `true` if the target can be compiled and packaged.
+    pub fn can_build(&self) -> bool {
+        self.rust_target && self.toolchain
+    }
+
+    /// Returns `true` if the target can be built and signed, e.g. to run on a device.
+    pub fn is_ready(&self) -> bool {
+        self.can_build() && self.signing
+    }
+}
+
+/// Checks every target of the enabled platforms: installed rust targets, platform
+/// toolchains and signing. Android targets are checked with NDK of the `ndk_version`
+/// if it's pinned, which must be at least of the `min_ndk_version`.
+#[cfg_attr(not(feature = "android"), allow(unused_variables))]
+pub fn target_matrix(
+    ndk_version: Option<&str>,
+    min_ndk_version: Option<&str>,
+) -> Vec<TargetCapability> {
+    #[allow(unused_variables)]
+    let installed_targets = rustup_installed_targets();
+    #[allow(unused_mut)]
+    let mut matrix = Vec::new();
+    #[cfg(feature = "android")]
+    matrix.extend(android_capabilities(
+        &installed_targets,
+        ndk_version,
+        min_ndk_version,
+    ));
+    #[cfg(feature = "apple")]
+    matrix.extend(apple_capabilities(&installed_targets));
+    matrix
+}
+
+#[cfg(feature = "android")]
+fn android_capabilities(
+    installed_targets: &Option<Vec<String>>,
+    ndk_version: Option<&str>,
+    min_ndk_version: Option<&str>,
+) -> Vec<TargetCapability> {
+    let sdk = AndroidSdk::from_env();
+    let ndk = sdk.as_ref().ok().and_then(|sdk| {
+        AndroidNdk::from_env_with_requirements(sdk.sdk_path(), ndk_version, min_ndk_version).ok()
+    });
+    let signing = keytool_init().is_ok();
+    [
+        AndroidTarget::Aarch64,
+        AndroidTarget::Armv7,
+        AndroidTarget::X8664,
+        AndroidTarget::I686,
+    ]
+    .into_iter()
+    .map(|target| {
+        let mut capability =
+            TargetCapability::new("android", target.rust_triple(), installed_targets);
+        match (&sdk, &ndk) {
+            (Err(_), _) => capability
+                .missing
+                .push("Set ANDROID_SDK_ROOT or run `crossbundle install android`".to_owned()),
+            (Ok(_), None) => capability
+                .missing
+                .push("Set ANDROID_NDK_ROOT or run `crossbundle install android`".to_owned()),
+            (Ok(_), Some(ndk)) => match ndk.clang(target, NDK_CHECK_PLATFORM) {
+                Ok(_) => capability.toolchain = true,
+                Err(_) => capability.missing.push(format!(
+                    "NDK at {} has no clang for {}, install a newer NDK",
+                    ndk.ndk_path().display(),
+                    target.rust_triple()
+                )),
+            },
+        }
+        capability.signing = signing;
+        if !signing {
+            capability
+                .missing
+                .push("Install JDK to generate debug keys with keytool".to_owned());
+        }
+        capability
+    })
+    .collect()
+}
+
+#[cfg(feature = "apple")]
+fn apple_capabilities(installed_targets: &Option<Vec<String>>) -> Vec<TargetCapability> {
+    let xcode = cfg!(target_os = "macos") && {
+        let mut xcodebuild = Command::new("xcodebuild");
+        xcodebuild.arg("-version");
+        xcodebuild
+            .output()
+            .map_or(false, |output| output.status.success())
+    };
+    let codesigning = xcode && has_codesigning_identity();
+    [
+        ("ios", IosTarget::Aarch64),
+        ("ios-simulator", IosTarget::Aarch64Sim),
+        ("ios-simulator", IosTarget::X86_64),
+    ]
+    .into_iter()
+    .map(|(platform, target)| {
+        let mut capability =
+            TargetCapability::new(platform, target.rust_triple(), installed_targets);
+        capability.toolchain = xcode;
+        if !cfg!(target_os = "macos") {
+            capability
+                .missing
+                .push("Requires macOS with Xcode".to_owned());
+        } else if !xcode {
+            capability.missing.push(
+                "Install Xcode from the App Store and run `xcode-select --install`".to_owned(),
+            );
+        }
+        capability.signing = platform == "ios-simulator" || codesigning;
+        if !capability.signing && xcode {
+            capability.missing.push(
+                "Add an Apple Development certificate in Xcode to sign builds for devices"
+                    .to_owned(),
+            );
+        }
+        capability
+    })
+    .collect()
+}
+
+/// Checks that the keychain contains a valid codesigning identity.
+/// Runs `security find-identity -v -p codesigning`
+#[cfg(feature = "apple")]
+fn has_codesigning_identity() -> bool {
+    let mut security = Command::new("security");
+    security.args(["find-identity", "-v", "-p", "codesigning"]);
+    match security.output() {
+        Ok(output) if output.status.success() => {
+            !String::from_utf8_lossy(&output.stdout).contains("0 valid identities found")
+        }
+        _ => false,
+    }
+}
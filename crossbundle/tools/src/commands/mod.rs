@@ -3,5 +3,7 @@ pub mod android;
 #[cfg(feature = "apple")]
 pub mod apple;
 mod common;
+#[cfg(feature = "windows")]
+pub mod windows;
 
 pub use common::*;
@@ -0,0 +1,109 @@
+use crate::{error::*, types::*};
+use fs_extra::dir::{copy as copy_dir, CopyOptions};
+use image::{imageops::FilterType, GenericImageView, RgbaImage};
+use std::fs::{create_dir_all, remove_dir_all};
+use std::path::{Path, PathBuf};
+
+/// Directory of the logos in the package layout. Not `Assets`, which would clash with
+/// `assets` of the game on case-insensitive file systems.
+pub const APPX_IMAGES_DIR: &str = "Images";
+/// Scales of the logos generated for displays with different DPI.
+const LOGO_SCALES: [u32; 3] = [100, 200, 400];
+/// Sizes of the app list and taskbar icons generated from `Square44x44Logo`.
+const TARGET_SIZES: [u32; 5] = [16, 24, 32, 48, 256];
+
+/// Generates the layout of the MSIX package: the executable goes to the root and the
+/// assets to `assets` next to it, where the game finds them at runtime. Returns the
+/// layout directory path.
+pub fn gen_appx_folder(
+    target_dir: &Path,
+    project_name: &str,
+    exe_path: &Path,
+    assets_dir: Option<PathBuf>,
+) -> Result<PathBuf> {
+    if !exe_path.exists() {
+        return Err(Error::PathNotFound(exe_path.to_owned()));
+    }
+    let layout_path = target_dir.join(project_name);
+    remove_dir_all(&layout_path).ok();
+    create_dir_all(layout_path.join(APPX_IMAGES_DIR))?;
+    std::fs::copy(exe_path, layout_path.join(exe_path.file_name().unwrap()))?;
+    if let Some(assets_dir) = &assets_dir {
+        if !assets_dir.exists() {
+            return Err(Error::PathNotFound(assets_dir.clone()));
+        }
+        let assets_path = layout_path.join("assets");
+        create_dir_all(&assets_path)?;
+        let mut options = CopyOptions::new();
+        options.content_only = true;
+        copy_dir(assets_dir, &assets_path, &options)?;
+    }
+    Ok(layout_path)
+}
+
+/// Generates the logos of [`APPX_LOGOS`] from the square PNG or JPEG icon at 100%, 200%
+/// and 400% scales, and the taskbar icons of `Square44x44Logo` in common target sizes.
+/// The icon is centered on a transparent background on the wide tile.
+pub fn gen_appx_logos(icon_path: &Path, images_dir: &Path) -> Result<()> {
+    let icon = image::open(icon_path)?;
+    let (width, height) = icon.dimensions();
+    if width != height {
+        return Err(Error::WidthAndHeightDifSizes);
+    }
+    let save_logo = |name: &str, logo_width: u32, logo_height: u32| -> Result<()> {
+        let side = logo_width.min(logo_height);
+        let resized = icon.resize_exact(side, side, FilterType::Lanczos3);
+        let mut logo = RgbaImage::new(logo_width, logo_height);
+        image::imageops::overlay(
+            &mut logo,
+            &resized.to_rgba8(),
+            ((logo_width - side) / 2) as i64,
+            ((logo_height - side) / 2) as i64,
+        );
+        logo.save(images_dir.join(format!("{}.png", name)))?;
+        Ok(())
+    };
+    create_dir_all(images_dir)?;
+    for (name, logo_width, logo_height) in APPX_LOGOS {
+        for scale in LOGO_SCALES {
+            save_logo(
+                &format!("{}.scale-{}", name, scale),
+                logo_width * scale / 100,
+                logo_height * scale / 100,
+            )?;
+        }
+    }
+    for size in TARGET_SIZES {
+        save_logo(&format!("Square44x44Logo.targetsize-{}", size), size, size)?;
+        // Taskbar icons without the accent color plate
+        save_logo(
+            &format!("Square44x44Logo.targetsize-{}_altform-unplated", size),
+            size,
+            size,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gen_appx_logos() {
+        let dir = tempfile::tempdir().unwrap();
+        let icon_path = dir.path().join("icon.png");
+        RgbaImage::new(256, 256).save(&icon_path).unwrap();
+        let images_dir = dir.path().join(APPX_IMAGES_DIR);
+        gen_appx_logos(&icon_path, &images_dir).unwrap();
+
+        let wide = image::open(images_dir.join("Wide310x150Logo.scale-200.png")).unwrap();
+        assert_eq!(wide.dimensions(), (620, 300));
+        assert!(images_dir
+            .join("Square44x44Logo.targetsize-24_altform-unplated.png")
+            .exists());
+
+        RgbaImage::new(256, 128).save(&icon_path).unwrap();
+        assert!(gen_appx_logos(&icon_path, &images_dir).is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use super::windows_sdk_tool;
+use crate::error::*;
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// ## MakeAppx
+/// Packs the package layout directory with `AppxManifest.xml` into `.msix` package.
+///
+/// See more: https://learn.microsoft.com/en-us/windows/msix/package/create-app-package-with-makeappx-tool
+#[derive(Clone, Debug)]
+pub struct MakeAppx {
+    layout_dir: PathBuf,
+    output: PathBuf,
+    overwrite: bool,
+    no_validation: bool,
+}
+
+impl MakeAppx {
+    /// Packs the layout directory into the `output` package.
+    pub fn new(layout_dir: &Path, output: &Path) -> Self {
+        Self {
+            layout_dir: layout_dir.to_owned(),
+            output: output.to_owned(),
+            overwrite: false,
+            no_validation: false,
+        }
+    }
+
+    /// Replaces the existing package.
+    pub fn overwrite(&mut self, overwrite: bool) -> &mut Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Skips semantic validation of the manifest, e.g. that the logos exist.
+    pub fn no_validation(&mut self, no_validation: bool) -> &mut Self {
+        self.no_validation = no_validation;
+        self
+    }
+
+    pub fn args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec!["pack".into(), "/d".into()];
+        args.push(self.layout_dir.clone().into());
+        args.push("/p".into());
+        args.push(self.output.clone().into());
+        if self.overwrite {
+            args.push("/o".into());
+        }
+        if self.no_validation {
+            args.push("/nv".into());
+        }
+        args
+    }
+
+    /// Runs `makeappx pack ...` command. Returns the package path.
+    pub fn run(&self) -> Result<PathBuf> {
+        let mut makeappx = Command::new(windows_sdk_tool("makeappx")?);
+        makeappx.args(self.args());
+        makeappx.output_err(true)?;
+        Ok(self.output.clone())
+    }
+}
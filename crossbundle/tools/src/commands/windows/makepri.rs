@@ -0,0 +1,38 @@
+use super::windows_sdk_tool;
+use crate::{error::*, types::APPX_MANIFEST_FILE};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Indexes the scaled logos of the package layout into `resources.pri`, so Windows
+/// picks the logo for the display scale. The config is written next to the layout
+/// directory, it's not packaged. Runs `makepri createconfig ...` and `makepri new ...`
+/// commands.
+pub fn gen_resources_pri(layout_dir: &Path) -> Result<PathBuf> {
+    let makepri = windows_sdk_tool("makepri")?;
+    let mut config_name = layout_dir.file_name().unwrap_or_default().to_owned();
+    config_name.push(".priconfig.xml");
+    let config_path = layout_dir.with_file_name(config_name);
+    let mut createconfig = Command::new(&makepri);
+    createconfig
+        .arg("createconfig")
+        .arg("/cf")
+        .arg(&config_path)
+        .args(["/dq", "en-US", "/pv", "10.0.0", "/o"]);
+    createconfig.output_err(true)?;
+    let pri_path = layout_dir.join("resources.pri");
+    let mut new = Command::new(&makepri);
+    new.arg("new")
+        .arg("/pr")
+        .arg(layout_dir)
+        .arg("/cf")
+        .arg(&config_path)
+        .arg("/mn")
+        .arg(layout_dir.join(APPX_MANIFEST_FILE))
+        .arg("/of")
+        .arg(&pri_path)
+        .arg("/o");
+    new.output_err(true)?;
+    Ok(pri_path)
+}
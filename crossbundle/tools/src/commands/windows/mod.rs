@@ -0,0 +1,13 @@
+mod gen_appx_folder;
+mod makeappx;
+mod makepri;
+mod rust_compile;
+mod signtool;
+mod windows_sdk;
+
+pub use gen_appx_folder::*;
+pub use makeappx::*;
+pub use makepri::*;
+pub use rust_compile::*;
+pub use signtool::*;
+pub use windows_sdk::*;
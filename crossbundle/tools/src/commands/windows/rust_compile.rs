@@ -0,0 +1,46 @@
+use crate::{error::*, types::*};
+use std::{path::Path, process::Command};
+
+/// Compiles rust code for Windows with the MSVC toolchain. With `coverage` the crate is
+/// instrumented for source-based code coverage.
+///
+/// Initialises `cargo rustc` [`Command`] with given args and return it.
+///
+/// [`Command`]: std::process::Command
+pub fn compile_rust_for_windows(
+    target: Target,
+    build_target: WindowsTarget,
+    project_path: &Path,
+    profile: Profile,
+    features: Vec<String>,
+    all_features: bool,
+    no_default_features: bool,
+    coverage: bool,
+) -> Result<()> {
+    let mut cargo = Command::new("cargo");
+    cargo.arg("rustc");
+    match &target {
+        Target::Bin(name) => cargo.args(["--bin", name]),
+        Target::Example(name) => cargo.args(["--example", name]),
+        Target::Lib => cargo.arg("--lib"),
+    };
+    cargo.current_dir(project_path);
+    if profile == Profile::Release {
+        cargo.arg("--release");
+    };
+    for feature in features.iter() {
+        cargo.args(["--features", feature]);
+    }
+    if all_features {
+        cargo.arg("--all-features");
+    };
+    if no_default_features {
+        cargo.arg("--no-default-features");
+    };
+    cargo.args(["--target", build_target.rust_triple()]);
+    if coverage {
+        cargo.arg("--").arg(crate::commands::COVERAGE_RUSTC_ARG);
+    }
+    cargo.output_err(true)?;
+    Ok(())
+}
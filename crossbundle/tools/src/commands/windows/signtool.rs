@@ -0,0 +1,109 @@
+use super::windows_sdk_tool;
+use crate::error::*;
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// ## SignTool
+/// Signs the MSIX package with SHA-256 digest. The publisher of the package manifest
+/// must match the subject of the certificate. The certificate is taken from the `.pfx`
+/// file or from the certificate store by the subject name.
+///
+/// See more: https://learn.microsoft.com/en-us/windows/msix/package/sign-app-package-using-signtool
+#[derive(Clone, Debug)]
+pub struct SignTool {
+    path: PathBuf,
+    certificate: Option<PathBuf>,
+    password: Option<String>,
+    subject_name: Option<String>,
+    timestamp_url: Option<String>,
+}
+
+impl SignTool {
+    /// Signs the package at `path`.
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_owned(),
+            certificate: None,
+            password: None,
+            subject_name: None,
+            timestamp_url: None,
+        }
+    }
+
+    /// `.pfx` file with the certificate and the private key.
+    pub fn certificate(&mut self, certificate: &Path) -> &mut Self {
+        self.certificate = Some(certificate.to_owned());
+        self
+    }
+
+    /// Password of the `.pfx` file.
+    pub fn password(&mut self, password: &str) -> &mut Self {
+        self.password = Some(password.to_owned());
+        self
+    }
+
+    /// Subject name of the certificate in the certificate store, e.g. `Studio`.
+    pub fn subject_name(&mut self, subject_name: &str) -> &mut Self {
+        self.subject_name = Some(subject_name.to_owned());
+        self
+    }
+
+    /// RFC 3161 timestamp server, e.g. `http://timestamp.digicert.com`. Timestamped
+    /// packages stay valid after the certificate expires, needs network access.
+    pub fn timestamp_url(&mut self, timestamp_url: &str) -> &mut Self {
+        self.timestamp_url = Some(timestamp_url.to_owned());
+        self
+    }
+
+    pub fn args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec!["sign".into(), "/fd".into(), "SHA256".into()];
+        if let Some(certificate) = &self.certificate {
+            args.push("/f".into());
+            args.push(certificate.clone().into());
+        }
+        if let Some(password) = &self.password {
+            args.push("/p".into());
+            args.push(password.into());
+        }
+        match &self.subject_name {
+            Some(subject_name) => {
+                args.push("/n".into());
+                args.push(subject_name.into());
+            }
+            // Selects the best certificate automatically
+            None if self.certificate.is_none() => args.push("/a".into()),
+            None => {}
+        }
+        if let Some(timestamp_url) = &self.timestamp_url {
+            args.push("/tr".into());
+            args.push(timestamp_url.into());
+            args.push("/td".into());
+            args.push("SHA256".into());
+        }
+        args.push(self.path.clone().into());
+        args
+    }
+
+    /// Runs `signtool sign ...` command. The error doesn't contain the command line,
+    /// which has the password.
+    pub fn run(&self) -> Result<()> {
+        if !self.path.exists() {
+            return Err(Error::PathNotFound(self.path.clone()));
+        }
+        let mut signtool = Command::new(windows_sdk_tool("signtool")?);
+        signtool.args(self.args());
+        let output = signtool.output()?;
+        if !output.status.success() {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = format!("{} {}", stdout.trim(), stderr.trim())
+                .trim()
+                .to_owned();
+            return Err(WindowsError::SigntoolFailed(message).into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use crate::error::*;
+use std::path::{Path, PathBuf};
+
+/// Default installation directory of Windows 10 and Windows 11 SDKs.
+const WINDOWS_KITS_BIN_DIR: &str = r"C:\Program Files (x86)\Windows Kits\10\bin";
+
+/// Finds `makeappx`, `makepri` or `signtool` tool of the Windows SDK. Tools in PATH are
+/// preferred, then the SDK selected by `WindowsSdkVerBinPath` of the Visual Studio
+/// developer prompt, then the newest installed SDK.
+pub fn windows_sdk_tool(name: &str) -> Result<PathBuf> {
+    if let Ok(path) = which::which(name) {
+        return Ok(path);
+    }
+    let file_name = format!("{}.exe", name);
+    let host_arch = match cfg!(target_arch = "aarch64") {
+        true => "arm64",
+        false => "x64",
+    };
+    if let Ok(bin_path) = std::env::var("WindowsSdkVerBinPath") {
+        let path = Path::new(&bin_path).join(host_arch).join(&file_name);
+        if path.exists() {
+            return Ok(path);
+        }
+    }
+    let mut versions: Vec<(Vec<u32>, PathBuf)> = std::fs::read_dir(WINDOWS_KITS_BIN_DIR)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let version = path
+                .file_name()?
+                .to_str()?
+                .split('.')
+                .map(|number| number.parse().ok())
+                .collect::<Option<Vec<u32>>>()?;
+            Some((version, path))
+        })
+        .collect();
+    versions.sort();
+    versions
+        .into_iter()
+        .rev()
+        .map(|(_, path)| path.join(host_arch).join(&file_name))
+        .find(|path| path.exists())
+        .ok_or_else(|| WindowsError::WindowsSdkToolNotFound(name.to_owned()).into())
+}
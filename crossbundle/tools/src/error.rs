@@ -1,8 +1,12 @@
-//! Contains `Error`, `AndroidError`, `AppleError` types used by `crossbundle-tools`.
+//! Contains `Error`, `AndroidError`, `AppleError`, `WindowsError` types used by
+//! `crossbundle-tools`.
 
+#[cfg(feature = "android")]
+use crate::types::NdkVersion;
 #[cfg(feature = "apple")]
 use apple_bundle::plist;
 use displaydoc::Display;
+use serde::Serialize;
 use std::path::PathBuf;
 use std::process::Command;
 use thiserror::Error;
@@ -10,12 +14,73 @@ use thiserror::Error;
 /// `Result` type that used in `crossbundle-tools`.
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Category of the error. Every category has its own process exit code, so scripts can
+/// branch on the failure type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorKind {
+    /// Unexpected error, e.g. IO failure.
+    Internal,
+    /// Invalid configuration, metadata, manifest or command-line arguments.
+    Config,
+    /// Missing or broken build environment: SDK, NDK, JDK, Xcode or other tools.
+    Environment,
+    /// Compilation, packaging or signing failure.
+    Build,
+    /// Device or emulator is missing or failed to install or run the application.
+    Device,
+    /// Failed to upload or publish the application.
+    Publish,
+}
+
+impl ErrorKind {
+    /// Exit code of `crossbundle` process. `2` is reserved for command-line usage errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Internal => 1,
+            Self::Config => 3,
+            Self::Environment => 4,
+            Self::Build => 5,
+            Self::Device => 6,
+            Self::Publish => 7,
+        }
+    }
+}
+
+/// Returns snake case name of the enum variant from its `Debug` representation, e.g.
+/// `android_ndk_not_found` for `AndroidNdkNotFound`.
+pub fn error_code<T: std::fmt::Debug>(error: &T) -> String {
+    let debug = format!("{:?}", error);
+    let variant = debug
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+        .unwrap_or_default();
+    let mut code = String::new();
+    for (i, c) in variant.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                code.push('_');
+            }
+            code.extend(c.to_lowercase());
+        } else {
+            code.push(c);
+        }
+    }
+    code
+}
+
 /// Android specific error type.
 #[cfg(feature = "android")]
 #[derive(Display, Debug, Error)]
 pub enum AndroidError {
-    /// Android NDK is not found
+    /// Android NDK is not found. Run `crossbundle install android` to install it
     AndroidNdkNotFound,
+    /// NDK {0} is not installed. Run `crossbundle install android` to install it
+    NdkVersionNotInstalled(String),
+    /// NDK {0} is older than the required {1}. Install a newer one with `crossbundle install android --ndk-version <version>`
+    NdkVersionTooOld(NdkVersion, NdkVersion),
+    /// Invalid NDK version `{0}`. Use `<major>.<minor>.<build>` format, e.g. `23.1.7779620`
+    InvalidNdkVersion(String),
     /// Failed to read source.properties
     FailedToReadSourceProperties,
     /// Invalid source.properties: {0}
@@ -36,8 +101,22 @@ pub enum AndroidError {
     UnsupportedTarget,
     /// Host {0} is not supported
     UnsupportedHost(String),
-    /// Invalid semver
-    InvalidSemver,
+    /// Major, minor and patch versions must be less than 256 to generate version code: {0}
+    VersionCodeOverflow(String),
+    /// Invalid version code `{0}`. It must be between 1 and 2100000000
+    InvalidVersionCode(String),
+    /// Unsupported version code strategy: {0}. Use manifest, semver, git-commit-count or timestamp
+    InvalidVersionCodeStrategy(String),
+    /// Version code {0} must be less than 100000000 to generate per-ABI version codes
+    SplitVersionCodeOverflow(u32),
+    /// Invalid dynamic feature module `{0}`: {1}
+    InvalidDynamicFeature(String, String),
+    /// Invalid asset pack `{0}`: {1}
+    InvalidAssetPack(String, String),
+    /// Invalid device time `{0}`. Use `YYYY-MM-DDTHH:MM:SSZ` format
+    InvalidDeviceTime(String),
+    /// None of the built APKs supports device ABIs: {0}
+    NoApkForDeviceAbi(String),
     /// Unsupported or invalid target: {0}
     InvalidBuildTarget(String),
     /// Unsupported or invalid app wrapper: {0}
@@ -48,12 +127,157 @@ pub enum AndroidError {
     FailedToFindAndroidManifest(String),
     /// Unable to find NDK file
     UnableToFindNDKFile,
+    /// Keytool is not found. Install JDK and add it to PATH or set JAVA_HOME
+    KeytoolNotFound,
+    /// Java is not found. Install JDK and add it to PATH or set JAVA_HOME
+    JavaNotFound,
+    /// Javac is not found. Install JDK and add it to PATH or set JAVA_HOME
+    JavacNotFound,
+    /// Kotlinc is not found. Install Kotlin compiler and add it to PATH or set KOTLIN_HOME
+    KotlincNotFound,
+    /// Alias `{0}` not found in keystore
+    KeystoreAliasNotFound(String),
+    /// Sdkmanager is not found in {0:?}. Run `crossbundle install command-line-tools`
+    SdkManagerNotFound(PathBuf),
+    /// Process of `{0}` application not found. Check whether the application crashed on start with `adb logcat`
+    AppProcessNotFound(String),
+    /// No online Android devices found. Connect a device or start an emulator
+    NoAndroidDevices,
+    /// Several Android devices connected: {0}. Select one with `--device <serial>`
+    MultipleAndroidDevices(String),
+    /// aapt2 daemon failed: {0}
+    Aapt2DaemonFailed(String),
+    /// aapt2 diff failed: {0}
+    Aapt2DiffFailed(String),
+    /// lldb-server for {0} not found in the NDK. Install NDK r23 or newer
+    LldbServerNotFound(String),
+    /// lldb not found in the NDK or PATH
+    LldbNotFound,
+    /// Debugger didn't attach to the process {0} in time
+    DebuggerAttachTimeout(u32),
+    /// Neither ndk-stack nor llvm-symbolizer found in the NDK at {0:?}
+    SymbolizerNotFound(PathBuf),
+    /// Simpleperf not found in the NDK at {0:?}. Install NDK r22 or newer
+    SimpleperfNotFound(PathBuf),
+    /// Bundletool is not found. Run `crossbundle install bundletool` or set BUNDLETOOL_PATH
+    BundletoolNotFound,
+    /// The new build adds permissions: {0}. Pass `--allow-new-permissions` if they are intended
+    NewPermissions(String),
+    /// adb server at {0} is not reachable. Start it on the remote host with `adb -a -P <port> server nodaemon`
+    AdbServerUnreachable(String),
+    /// adb server version {1} doesn't match the client version {0}. Use the same platform-tools on both hosts
+    AdbVersionMismatch(u32, u32),
+    /// adb server returned an error: {0}
+    AdbProtocol(String),
+    /// Failed to pair {0}: {1}. Check the pairing code and port in Wireless debugging settings of the device
+    AdbPairFailed(String, String),
+    /// Failed to connect to {0}: {1}
+    AdbConnectFailed(String, String),
+    /// Invalid adb server socket `{0}`, expected `tcp:<host>:<port>` or `tcp:<port>`
+    InvalidAdbServerSocket(String),
+    /// avdmanager not found in {0:?}. Run `crossbundle install command-line-tools` to install it
+    AvdManagerNotFound(PathBuf),
+    /// Android emulator not found at {0:?}. Install it with `crossbundle install sdkmanager --install emulator`
+    EmulatorNotFound(PathBuf),
+    /// Emulator {0} didn't finish booting in time
+    EmulatorBootTimeout(String),
+    /// Emulator {0} exited before it finished booting with {1}. Check the AVD with `emulator -avd <name> -verbose`
+    EmulatorExited(String, String),
+    /// All emulator console ports from 5554 to 5584 are in use
+    NoFreeEmulatorPort,
+    /// Unexpected aapt2 dump output: {0}
+    InvalidAapt2DumpOutput(String),
+    /// Emulator console command failed: {0}
+    EmulatorConsole(String),
+    /// Android container {0} didn't become ready in time. Check that the container is running and adbd listens on port 5555
+    ContainerNotReady(String),
+    /// Invalid emulator GPU mode `{0}`, expected `auto`, `host`, `swiftshader_indirect`, `angle_indirect` or `guest`
+    InvalidEmulatorGpu(String),
+    /// Emulator profile `{0}` is not found in `package.metadata.android.emulator_profiles`, available profiles: [{1}]
+    EmulatorProfileNotFound(String, String),
+    /// Hardware acceleration ({0}) is not available for the emulator: {1}
+    EmulatorAccelerationUnavailable(String, String),
+    /// Invalid criterion results on the device, `{0}` not found in `estimates.json`
+    InvalidBenchResults(String),
     /// AndroidTools error: {0:?}
     AndroidTools(#[from] android_tools::error::Error),
     /// AndroidManifest error: {0:?}
     AndroidManifest(#[from] android_manifest::error::Error),
 }
 
+#[cfg(feature = "android")]
+impl AndroidError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::AndroidNdkNotFound
+            | Self::NdkVersionNotInstalled(_)
+            | Self::NdkVersionTooOld(..)
+            | Self::FailedToReadSourceProperties
+            | Self::InvalidSourceProperties(_)
+            | Self::GradleNotFound
+            | Self::BuildToolsNotFound
+            | Self::NoPlatformsFound
+            | Self::PlatformNotFound(_)
+            | Self::UnsupportedHost(_)
+            | Self::UnableToFindNDKFile
+            | Self::KeytoolNotFound
+            | Self::JavaNotFound
+            | Self::JavacNotFound
+            | Self::KotlincNotFound
+            | Self::SdkManagerNotFound(_)
+            | Self::LldbServerNotFound(_)
+            | Self::LldbNotFound
+            | Self::SymbolizerNotFound(_)
+            | Self::SimpleperfNotFound(_)
+            | Self::BundletoolNotFound
+            | Self::AdbServerUnreachable(_)
+            | Self::AdbVersionMismatch(..)
+            | Self::AvdManagerNotFound(_)
+            | Self::EmulatorNotFound(_)
+            | Self::EmulatorAccelerationUnavailable(..)
+            | Self::InvalidAapt2DumpOutput(_) => ErrorKind::Environment,
+            Self::AppProcessNotFound(_)
+            | Self::NoAndroidDevices
+            | Self::MultipleAndroidDevices(_)
+            | Self::NoApkForDeviceAbi(_)
+            | Self::DebuggerAttachTimeout(_)
+            | Self::EmulatorBootTimeout(_)
+            | Self::EmulatorExited(..)
+            | Self::NoFreeEmulatorPort
+            | Self::EmulatorConsole(_)
+            | Self::ContainerNotReady(_)
+            | Self::AdbProtocol(_)
+            | Self::AdbPairFailed(..)
+            | Self::AdbConnectFailed(..)
+            | Self::InvalidBenchResults(_) => ErrorKind::Device,
+            Self::AndroidTools(_)
+            | Self::Aapt2DaemonFailed(_)
+            | Self::Aapt2DiffFailed(_)
+            | Self::NewPermissions(_) => ErrorKind::Build,
+            Self::InvalidNdkVersion(_)
+            | Self::GradleDependencyProjectNotFound(_)
+            | Self::GradleDependencyProjectNoBuildFile(_)
+            | Self::UnsupportedTarget
+            | Self::VersionCodeOverflow(_)
+            | Self::InvalidVersionCode(_)
+            | Self::InvalidVersionCodeStrategy(_)
+            | Self::SplitVersionCodeOverflow(_)
+            | Self::InvalidDynamicFeature(..)
+            | Self::InvalidAssetPack(..)
+            | Self::InvalidDeviceTime(_)
+            | Self::InvalidBuildTarget(_)
+            | Self::InvalidAppWrapper(_)
+            | Self::InvalidBuildStrategy(_)
+            | Self::FailedToFindAndroidManifest(_)
+            | Self::KeystoreAliasNotFound(_)
+            | Self::InvalidAdbServerSocket(_)
+            | Self::InvalidEmulatorGpu(_)
+            | Self::EmulatorProfileNotFound(..)
+            | Self::AndroidManifest(_) => ErrorKind::Config,
+        }
+    }
+}
+
 /// Apple specific error type.
 #[cfg(feature = "apple")]
 #[derive(Display, Debug, Error)]
@@ -64,6 +288,8 @@ pub enum AppleError {
     CodeSigningProfileNotProvided,
     /// Codesign failed {0}
     CodesignFailed(String),
+    /// Code signature is invalid: {0}
+    CodesignVerificationFailed(String),
     /// Failed to archive payload
     ZipCommandFailed,
     /// Codesign allocate not found
@@ -82,10 +308,114 @@ pub enum AppleError {
     AssetsNotFound,
     /// Failed to find Info.plist in path: {0}
     FailedToFindInfoPlist(String),
+    /// Info.plist has no usage descriptions required by App Review: {0}. Add them to `apple.usage_descriptions`
+    MissingUsageDescriptions(String),
+    /// Neither ios-deploy nor libimobiledevice is installed. Install one with `brew install ios-deploy` or `brew install libimobiledevice ideviceinstaller`
+    DeviceToolNotFound,
+    /// {0}
+    ConnectedDeviceNotFound(String),
+    /// Invalid string catalog {0:?}: {1}
+    InvalidStringCatalog(PathBuf, String),
+    /// The icon is {0}x{0} pixels, macOS icons must be at least {1}x{1} pixels
+    IconTooSmall(u32, u32),
+    /// Invalid on-demand resource `{0}`: {1}
+    InvalidOnDemandResource(String, String),
+    /// Simulator `{0}` is not found, available simulators: [{1}]
+    SimulatorNotFound(String, String),
+    /// Simulator profile `{0}` is not found in `package.metadata.apple.simulator_profiles`, available profiles: [{1}]
+    SimulatorProfileNotFound(String, String),
+    /// App Store Connect authentication failed: {0}. Check the API key ID, the issuer ID and `AuthKey_<key id>.p8` file, or the Apple ID and its app-specific password
+    UploadAuthenticationFailed(String),
+    /// App Store Connect has no app with the bundle identifier of the IPA: {0}. Create the app record in App Store Connect first
+    AppRecordNotFound(String),
+    /// The build number was already uploaded: {0}. Increase `build_number` or `version` of the application
+    DuplicateBuildVersion(String),
+    /// The IPA is not signed for App Store distribution: {0}. Sign it with an Apple Distribution identity and an App Store provisioning profile
+    InvalidUploadSignature(String),
+    /// The IPA has no required app icons: {0}. Add the icons to the Apple resources
+    MissingAppIcon(String),
+    /// Upload to App Store Connect failed: {0}
+    UploadFailed(String),
+    /// Notarization finished with status `{0}`: {1}
+    NotarizationFailed(String, String),
+    /// Notarization of submission `{0}` is not finished in time. Check it later with `xcrun notarytool info {0}`
+    NotarizationTimedOut(String),
     /// Plist data error: {0:?}
     Plist(#[from] plist::Error),
 }
 
+#[cfg(feature = "apple")]
+impl AppleError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::CodesignFailed(_)
+            | Self::CodesignVerificationFailed(_)
+            | Self::ZipCommandFailed => ErrorKind::Build,
+            Self::CodesignAllocateNotFound | Self::DeviceToolNotFound => ErrorKind::Environment,
+            Self::Simctl(_) | Self::SimulatorNotFound(..) | Self::ConnectedDeviceNotFound(_) => {
+                ErrorKind::Device
+            }
+            Self::UploadAuthenticationFailed(_)
+            | Self::AppRecordNotFound(_)
+            | Self::DuplicateBuildVersion(_)
+            | Self::InvalidUploadSignature(_)
+            | Self::MissingAppIcon(_)
+            | Self::UploadFailed(_)
+            | Self::NotarizationFailed(..)
+            | Self::NotarizationTimedOut(_) => ErrorKind::Publish,
+            Self::CodeSigningProfilesNotFound
+            | Self::CodeSigningProfileNotProvided
+            | Self::TargetNotFound
+            | Self::ResourcesNotFound
+            | Self::InvalidBuildStrategy(_)
+            | Self::InvalidBuildTarget(_)
+            | Self::AssetsNotFound
+            | Self::FailedToFindInfoPlist(_)
+            | Self::MissingUsageDescriptions(_)
+            | Self::InvalidStringCatalog(..)
+            | Self::IconTooSmall(..)
+            | Self::InvalidOnDemandResource(..)
+            | Self::SimulatorProfileNotFound(..)
+            | Self::Plist(_) => ErrorKind::Config,
+        }
+    }
+}
+
+/// Windows specific error type.
+#[cfg(feature = "windows")]
+#[derive(Display, Debug, Error)]
+pub enum WindowsError {
+    /// Windows SDK tool `{0}` is not found. Install Windows 10 SDK or newer with Visual Studio Installer, or add its `bin` directory to PATH
+    WindowsSdkToolNotFound(String),
+    /// Unsupported or invalid target: {0}
+    InvalidBuildTarget(String),
+    /// Invalid package identity name `{0}`. Use 3 to 50 letters, digits, `.` and `-`
+    InvalidIdentityName(String),
+    /// Invalid publisher `{0}`. Use the subject of the signing certificate, e.g. `CN=Studio, O=Studio, C=US`
+    InvalidPublisher(String),
+    /// Invalid package version `{0}`. MSIX versions have up to four numbers less than 65536
+    InvalidPackageVersion(String),
+    /// MSIX packages require logos. Set `icon` in the metadata
+    IconNotSpecified,
+    /// Signtool failed: {0}
+    SigntoolFailed(String),
+}
+
+#[cfg(feature = "windows")]
+impl WindowsError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::WindowsSdkToolNotFound(_) => ErrorKind::Environment,
+            Self::SigntoolFailed(_) => ErrorKind::Build,
+            Self::InvalidBuildTarget(_)
+            | Self::InvalidIdentityName(_)
+            | Self::InvalidPublisher(_)
+            | Self::InvalidPackageVersion(_)
+            | Self::IconNotSpecified => ErrorKind::Config,
+        }
+    }
+}
+
 /// Main error type.
 #[derive(Display, Debug, Error)]
 #[ignore_extra_doc_attributes]
@@ -103,10 +433,38 @@ pub enum Error {
     WidthAndHeightDifSizes,
     /// Icons already exist. Use overwrite flag
     IconsAlreadyExist,
+    /// Invalid icon background color `{0}`. Use #RGB, #RRGGBB or #AARRGGBB format
+    InvalidIconColor(String),
+    /// Failed to parse SVG icon {0:?}: {1}
+    InvalidSvgIcon(PathBuf, String),
     /// Failed to find the manifest in path: {0}
     FailedToFindManifest(PathBuf),
     /// Invalid profile: {0}
     InvalidProfile(String),
+    /// Invalid project template or platform: {0}
+    InvalidProjectTemplate(String),
+    /// Invalid log priority `{0}`. Use one of V, D, I, W, E, F
+    InvalidLogPriority(String),
+    /// Invalid profile format `{0}`. Use html, pprof or folded
+    InvalidPerfFormat(String),
+    /// Environment variable `{0}` with the storage password is not set
+    StorageCredentialsNotFound(String),
+    /// Storage request failed: {0}
+    StorageRequestFailed(String),
+    /// Invalid locale `{0}`. Use language code with optional script and region, e.g. `fr`, `pt-BR` or `sr-Latn`
+    InvalidLocale(String),
+    /// Invalid string resource name `{0}`. Use letters, digits, `_` and `.`
+    InvalidStringResourceName(String),
+    /// Resource file {0:?} already exists. Remove it or the generated strings from metadata
+    ResourceAlreadyExists(PathBuf),
+    /// Resource {path:?} is provided by both `{first}` and `{second}` crates. Rename it in one of them or override it in the application
+    LibraryResourceConflict {
+        path: PathBuf,
+        first: String,
+        second: String,
+    },
+    /// Invalid semantic version `{version}`: {reason}
+    InvalidSemver { version: String, reason: String },
     /// GNU toolchain binary `{gnu_bin}` nor LLVM toolchain binary `{llvm_bin}` found in
     /// `{toolchain_path:?}`
     ToolchainBinaryNotFound {
@@ -116,11 +474,45 @@ pub enum Error {
     },
     /// Path {0:?} doesn't exist
     PathNotFound(PathBuf),
+    /// Home directory not found
+    HomeDirNotFound,
+    /// Hook in {0:?} has an empty command
+    EmptyHookCommand(PathBuf),
+    /// Hook `{0}` is not trusted. Review it and run the command with `--trust` flag
+    UntrustedHook(String),
+    /// Rust targets `{0}` are not installed. Install them with `rustup target add {0}` or run the command with `--yes` flag
+    RustTargetsNotInstalled(String),
+    /// Rust `{0}` required to build the standard library of tvOS and watchOS targets is not installed. Install it with `rustup toolchain install nightly --component rust-src` or run the command with `--yes` flag
+    BuildStdNotInstalled(String),
+    /// LLVM tool `{0}` not found. Install it with `rustup component add llvm-tools-preview`
+    LlvmToolNotFound(String),
+    /// Coverage data not found: {0}
+    CoverageDataNotFound(String),
+    /// Provenance verification failed: {0}
+    ProvenanceMismatch(String),
+    /// Launch verification failed: {0}
+    LaunchVerificationFailed(String),
+    /// Development channel error: {0}
+    DevChannel(String),
+    /// Invalid size `{0}`. Use number of bytes or a number with KB, MB or GB unit
+    InvalidByteSize(String),
+    /// Size budget exceeded: {0}
+    SizeBudgetExceeded(String),
+    /// Benchmarks regressed more than the threshold: {0}
+    BenchRegression(String),
+    /// Sustained performance target missed: {0}
+    SustainedPerformance(String),
+    /// Unknown capability `{0}`. Supported capabilities: {1}
+    UnknownCapability(String, String),
     /// Failed to find cargo manifest: {0}
     FailedToFindCargoManifest(String),
     /// Failed to choose shell string color.
     /// Argument for --color must be auto, always, or never, but found `{}`
     FailedToChooseShellStringColor(String),
+    /// JSON error: {0:?}
+    Json(#[from] serde_json::Error),
+    /// TOML error: {0:?}
+    Toml(#[from] toml::de::Error),
     /// IO error: {0:?}
     Io(#[from] std::io::Error),
     /// FS Extra error: {0:?}
@@ -135,12 +527,79 @@ pub enum Error {
     /// Apple error: {0:?}
     #[cfg(feature = "apple")]
     Apple(#[from] AppleError),
+    /// Windows error: {0:?}
+    #[cfg(feature = "windows")]
+    Windows(#[from] WindowsError),
     /// Anyhow error: {0:?}
     AnyhowError(#[from] anyhow::Error),
     /// Other error: {0:?}
     OtherError(#[from] Box<dyn std::error::Error>),
 }
 
+impl Error {
+    /// Returns category of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::CmdFailed(..)
+            | Self::SizeBudgetExceeded(_)
+            | Self::BenchRegression(_)
+            | Self::SustainedPerformance(_) => ErrorKind::Build,
+            Self::CmdNotFound(_)
+            | Self::ToolchainBinaryNotFound { .. }
+            | Self::HomeDirNotFound
+            | Self::RustTargetsNotInstalled(_)
+            | Self::BuildStdNotInstalled(_)
+            | Self::LlvmToolNotFound(_) => ErrorKind::Environment,
+            Self::WidthAndHeightDifSizes
+            | Self::IconsAlreadyExist
+            | Self::InvalidIconColor(_)
+            | Self::InvalidSvgIcon(..)
+            | Self::FailedToFindManifest(_)
+            | Self::InvalidProfile(_)
+            | Self::InvalidProjectTemplate(_)
+            | Self::InvalidLogPriority(_)
+            | Self::InvalidPerfFormat(_)
+            | Self::StorageCredentialsNotFound(_)
+            | Self::InvalidLocale(_)
+            | Self::InvalidStringResourceName(_)
+            | Self::ResourceAlreadyExists(_)
+            | Self::LibraryResourceConflict { .. }
+            | Self::InvalidByteSize(_)
+            | Self::UnknownCapability(..)
+            | Self::Toml(_)
+            | Self::InvalidSemver { .. }
+            | Self::PathNotFound(_)
+            | Self::EmptyHookCommand(_)
+            | Self::UntrustedHook(_)
+            | Self::FailedToFindCargoManifest(_)
+            | Self::FailedToChooseShellStringColor(_)
+            | Self::CoverageDataNotFound(_) => ErrorKind::Config,
+            Self::ProvenanceMismatch(_) | Self::StorageRequestFailed(_) => ErrorKind::Publish,
+            Self::LaunchVerificationFailed(_) | Self::DevChannel(_) => ErrorKind::Device,
+            #[cfg(feature = "android")]
+            Self::Android(error) => error.kind(),
+            #[cfg(feature = "apple")]
+            Self::Apple(error) => error.kind(),
+            #[cfg(feature = "windows")]
+            Self::Windows(error) => error.kind(),
+            _ => ErrorKind::Internal,
+        }
+    }
+
+    /// Returns stable machine-readable code of the error, e.g. `android_ndk_not_found`.
+    pub fn code(&self) -> String {
+        match self {
+            #[cfg(feature = "android")]
+            Self::Android(error) => error_code(error),
+            #[cfg(feature = "apple")]
+            Self::Apple(error) => error_code(error),
+            #[cfg(feature = "windows")]
+            Self::Windows(error) => error_code(error),
+            _ => error_code(self),
+        }
+    }
+}
+
 /// Extension trait for [`Command`] that helps
 /// to wrap output and print logs from command execution.
 ///
@@ -189,3 +648,21 @@ impl From<android_tools::error::Error> for Error {
         AndroidError::AndroidTools(error).into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code() {
+        assert_eq!(
+            Error::InvalidProfile("bench".to_owned()).code(),
+            "invalid_profile"
+        );
+        assert_eq!(
+            Error::InvalidProfile(String::new()).kind(),
+            ErrorKind::Config
+        );
+        assert_eq!(Error::CmdNotFound("adb".to_owned()).kind().exit_code(), 4);
+    }
+}
@@ -3,17 +3,78 @@ use crate::types::{AndroidTarget, IntoRustTriple};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// Version of the NDK release from `Pkg.Revision`, e.g. `23.1.7779620`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NdkVersion {
+    pub major: u32,
+    pub minor: u32,
+    /// Build tag, incrementing across NDK releases.
+    pub build: u32,
+}
+
+impl std::str::FromStr for NdkVersion {
+    type Err = Error;
+
+    /// Parses `23.1.7779620` or `25.0.8775105-beta1`. Missing minor and build fields are
+    /// parsed as 0, so `23` can be used as the minimum version.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || AndroidError::InvalidNdkVersion(s.to_owned());
+        let version = s.trim();
+        let version = version
+            .split_once('-')
+            .map_or(version, |(version, _beta)| version);
+        let mut parts = version.split('.');
+        let mut next = || -> Result<u32> {
+            match parts.next() {
+                Some(part) => Ok(part.parse().map_err(|_| invalid())?),
+                None => Ok(0),
+            }
+        };
+        let major = next()?;
+        let minor = next()?;
+        let build = next()?;
+        if parts.next().is_some() {
+            return Err(invalid().into());
+        }
+        Ok(Self {
+            major,
+            minor,
+            build,
+        })
+    }
+}
+
+impl std::fmt::Display for NdkVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.build)
+    }
+}
+
 /// Helper structure that contains information about the Android NDK Path
 /// and returns paths to the tools.
 #[derive(Debug, Clone)]
 pub struct AndroidNdk {
     ndk_path: PathBuf,
-    build_tag: u32,
+    version: NdkVersion,
 }
 
 impl AndroidNdk {
     /// Using environment variables
     pub fn from_env(sdk_path: &Path) -> Result<Self> {
+        Self::from_env_with_version(sdk_path, None)
+    }
+
+    /// Using NDK of the pinned version from `$ANDROID_SDK/ndk/<version>`, or environment
+    /// variables, `ndk-bundle` and the highest version from `$ANDROID_SDK/ndk` in this
+    /// order if the version isn't pinned.
+    pub fn from_env_with_version(sdk_path: &Path, pinned: Option<&str>) -> Result<Self> {
+        if let Some(pinned) = pinned {
+            let ndk_path = sdk_path.join("ndk").join(pinned);
+            if !ndk_path.exists() {
+                return Err(AndroidError::NdkVersionNotInstalled(pinned.to_owned()).into());
+            }
+            return Self::from_path(ndk_path);
+        }
         let ndk_path = {
             let ndk_path = std::env::var("ANDROID_NDK_ROOT")
                 .ok()
@@ -26,52 +87,138 @@ impl AndroidNdk {
             } else if ndk_path.is_none() && sdk_path.join("ndk-bundle").exists() {
                 sdk_path.join("ndk-bundle")
             } else {
-                let ndk_path = sdk_path.join("ndk");
-                let ndk_ver = std::fs::read_dir(&ndk_path)
-                    .map_err(|_| Error::PathNotFound(ndk_path.clone()))?
-                    .filter_map(|path| path.ok())
-                    .filter(|path| path.path().is_dir())
-                    .filter_map(|path| path.file_name().into_string().ok())
-                    .filter(|name| name.chars().next().unwrap().is_ascii_digit())
-                    .max()
+                let (_, ndk_ver) = Self::installed_versions(sdk_path)?
+                    .pop()
                     .ok_or(AndroidError::AndroidNdkNotFound)?;
-                ndk_path.join(ndk_ver)
+                sdk_path.join("ndk").join(ndk_ver)
             }
         };
-        let build_tag = std::fs::read_to_string(ndk_path.join("source.properties"))
+        Self::from_path(ndk_path)
+    }
+
+    /// Using NDK of the pinned version like [`Self::from_env_with_version`]. The NDK
+    /// must be at least of `min_version` if specified.
+    pub fn from_env_with_requirements(
+        sdk_path: &Path,
+        pinned: Option<&str>,
+        min_version: Option<&str>,
+    ) -> Result<Self> {
+        let ndk = Self::from_env_with_version(sdk_path, pinned)?;
+        if let Some(min_version) = min_version {
+            ndk.require_version(min_version)?;
+        }
+        Ok(ndk)
+    }
+
+    /// Returns versions and directory names of NDKs installed in `$ANDROID_SDK/ndk`,
+    /// sorted from the lowest version to the highest.
+    pub fn installed_versions(sdk_path: &Path) -> Result<Vec<(NdkVersion, String)>> {
+        let ndk_path = sdk_path.join("ndk");
+        let mut versions = std::fs::read_dir(&ndk_path)
+            .map_err(|_| Error::PathNotFound(ndk_path.clone()))?
+            .filter_map(|path| path.ok())
+            .filter(|path| path.path().is_dir())
+            .filter_map(|path| path.file_name().into_string().ok())
+            .filter_map(|name| Some((name.parse::<NdkVersion>().ok()?, name)))
+            .collect::<Vec<_>>();
+        versions.sort();
+        Ok(versions)
+    }
+
+    /// Using NDK installed in given path
+    pub fn from_path(ndk_path: PathBuf) -> Result<Self> {
+        let source_properties = std::fs::read_to_string(ndk_path.join("source.properties"))
             .map_err(|_| AndroidError::FailedToReadSourceProperties)?;
-        let build_tag = build_tag
-            .split('\n')
-            .find_map(|line| {
-                if let Some((key, value)) = line.split_once('=') {
-                    if key.trim() == "Pkg.Revision" {
-                        // AOSP writes a constantly-incrementing build version to the patch field.
-                        // This number is incrementing across NDK releases.
-                        let mut parts = value.trim().split('.');
-                        let _major = parts.next().unwrap();
-                        let _minor = parts.next().unwrap();
-                        let patch = parts.next().unwrap();
-                        // Can have an optional `XXX-beta1`
-                        let patch = patch.split_once('-').map_or(patch, |(patch, _beta)| patch);
-                        return Some(patch.parse().expect("Failed to parse patch field"));
-                    }
-                }
-                None
-            })
+        let version = Self::parse_version(&source_properties)?;
+        Ok(Self { ndk_path, version })
+    }
+
+    /// Parses version from `Pkg.Revision` field of NDK `source.properties` file.
+    pub fn parse_version(source_properties: &str) -> Result<NdkVersion> {
+        Ok(NdkVersion {
+            major: Self::parse_major_version(source_properties)?,
+            minor: Self::pkg_revision(source_properties)?
+                .split('.')
+                .nth(1)
+                .and_then(|minor| minor.parse().ok())
+                .unwrap_or(0),
+            build: Self::parse_build_tag(source_properties)?,
+        })
+    }
+
+    /// Returns `Pkg.Revision` field of NDK `source.properties` file, e.g. `23.1.7779620`.
+    fn pkg_revision(source_properties: &str) -> Result<&str> {
+        Ok(source_properties
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .find(|(key, _)| key.trim() == "Pkg.Revision")
+            .map(|(_, value)| value.trim())
             .ok_or_else(|| {
                 AndroidError::InvalidSourceProperties(
                     "No `Pkg.Revision` in source.properties".to_owned(),
                 )
-            })?;
-        Ok(Self {
-            ndk_path,
-            build_tag,
-        })
+            })?)
+    }
+
+    /// Parses major version of the NDK release, e.g. `23` for r23, from `Pkg.Revision`
+    /// field of NDK `source.properties` file.
+    pub fn parse_major_version(source_properties: &str) -> Result<u32> {
+        let revision = Self::pkg_revision(source_properties)?;
+        let major = revision.split('.').next().unwrap_or(revision);
+        let major_version = major.parse().map_err(|_| {
+            AndroidError::InvalidSourceProperties(format!(
+                "Failed to parse major version in `Pkg.Revision = {}`",
+                revision
+            ))
+        })?;
+        Ok(major_version)
+    }
+
+    /// Parses build tag from `Pkg.Revision` field of NDK `source.properties` file.
+    pub fn parse_build_tag(source_properties: &str) -> Result<u32> {
+        let revision = Self::pkg_revision(source_properties)?;
+        // AOSP writes a constantly-incrementing build version to the patch field.
+        // This number is incrementing across NDK releases.
+        let patch = revision.split('.').nth(2).ok_or_else(|| {
+            AndroidError::InvalidSourceProperties(format!(
+                "No patch field in `Pkg.Revision = {}`",
+                revision
+            ))
+        })?;
+        // Can have an optional `XXX-beta1`
+        let patch = patch.split_once('-').map_or(patch, |(patch, _beta)| patch);
+        let build_tag = patch.parse().map_err(|_| {
+            AndroidError::InvalidSourceProperties(format!(
+                "Failed to parse patch field in `Pkg.Revision = {}`",
+                revision
+            ))
+        })?;
+        Ok(build_tag)
     }
 
     /// Build tag
     pub fn build_tag(&self) -> u32 {
-        self.build_tag
+        self.version.build
+    }
+
+    /// Major version of the NDK release, e.g. `23` for r23
+    pub fn major_version(&self) -> u32 {
+        self.version.major
+    }
+
+    /// Version of the NDK release
+    pub fn version(&self) -> NdkVersion {
+        self.version
+    }
+
+    /// Returns an error if the NDK is older than `min_version`, e.g. `23` or
+    /// `25.1.8937393`.
+    pub fn require_version(&self, min_version: &str) -> Result<()> {
+        let min_version: NdkVersion = min_version.parse()?;
+        if self.version < min_version {
+            return Err(AndroidError::NdkVersionTooOld(self.version, min_version).into());
+        }
+        Ok(())
     }
 
     /// NDK path
@@ -147,7 +294,8 @@ impl AndroidNdk {
         Ok((clang, clang_pp))
     }
 
-    /// Path to GNU binutils
+    /// Path to binutils like `readelf`, `ar` or `strip`: triple-prefixed GNU binutils of
+    /// older NDKs or LLVM binutils like `llvm-readelf`
     pub fn toolchain_bin(&self, name: &str, build_target: AndroidTarget) -> Result<PathBuf> {
         #[cfg(target_os = "windows")]
         let ext = ".exe";
@@ -159,22 +307,25 @@ impl AndroidNdk {
         // Since r23 (https://github.com/android/ndk/wiki/Changelog-r23) GNU binutils have been removed.
         // To maintain stability with the current ndk-build crate release, prefer GNU binutils for
         // as long as it is provided by the NDK instead of trying to use llvm-* from r21 onwards.
+        // Leftover GNU binutils of NDK r23+ installed over an older NDK are ignored.
         let gnu_bin = format!("{}-{}{}", build_target.ndk_triple(), name, ext);
+        let llvm_bin = match name {
+            "ld" => format!("ld.lld{}", ext),
+            _ => format!("llvm-{}{}", name, ext),
+        };
         let gnu_path = toolchain_path.join(&gnu_bin);
-        if gnu_path.exists() {
-            Ok(gnu_path)
-        } else {
-            let llvm_bin = format!("llvm-{}{}", name, ext);
-            let llvm_path = toolchain_path.join(&llvm_bin);
-            llvm_path
-                .exists()
-                .then_some(llvm_path)
-                .ok_or(Error::ToolchainBinaryNotFound {
-                    toolchain_path,
-                    gnu_bin,
-                    llvm_bin,
-                })
+        if self.version.major < 23 && gnu_path.exists() {
+            return Ok(gnu_path);
         }
+        let llvm_path = toolchain_path.join(&llvm_bin);
+        llvm_path
+            .exists()
+            .then_some(llvm_path)
+            .ok_or(Error::ToolchainBinaryNotFound {
+                toolchain_path,
+                gnu_bin,
+                llvm_bin,
+            })
     }
 
     /// Displaying various information
@@ -275,7 +426,7 @@ impl AndroidNdk {
             let ext = "";
             linker_path = self.tool_root()?.join("bin").join(format!(
                 "{}{}-clang{}",
-                build_target.rust_triple(),
+                build_target.ndk_llvm_triple(),
                 target_sdk_version,
                 ext,
             ))
@@ -360,7 +511,11 @@ impl AndroidNdk {
 
     /// Returns dir to libunwind.a for the correct architecture
     pub fn find_libunwind_dir(&self, build_target: &AndroidTarget) -> cargo::CargoResult<PathBuf> {
-        let libunwind_dir = self.tool_root()?.join("lib64").join("clang");
+        // Since r26 clang libraries are placed into `lib` instead of `lib64`
+        let mut libunwind_dir = self.tool_root()?.join("lib64").join("clang");
+        if !libunwind_dir.exists() {
+            libunwind_dir = self.tool_root()?.join("lib").join("clang");
+        }
         let clang_ver = libunwind_dir
             .read_dir()?
             .next()
@@ -16,7 +16,11 @@ pub struct AndroidSdk {
 impl AndroidSdk {
     /// Using environment variables tools
     pub fn from_env() -> Result<Self> {
-        let sdk_path = android_sdk_path()?;
+        Self::from_path(android_sdk_path()?)
+    }
+
+    /// Using SDK installed in given path
+    pub fn from_path(sdk_path: PathBuf) -> Result<Self> {
         let build_deps_path = sdk_path.join("build-tools");
         let build_deps_version = std::fs::read_dir(&build_deps_path)
             .map_err(|_| Error::PathNotFound(build_deps_path.clone()))?
@@ -105,6 +109,16 @@ impl AndroidSdk {
         Ok(ProcessCommand::new(dunce::canonicalize(path)?))
     }
 
+    /// Returns `adb` command. If device serial is specified, command will be executed
+    /// only on this device via `adb -s <serial>`
+    pub fn adb(&self, device: Option<&str>) -> Result<ProcessCommand> {
+        let mut adb = self.platform_tool(bin!("adb"))?;
+        if let Some(serial) = device {
+            adb.arg("-s").arg(serial);
+        }
+        Ok(adb)
+    }
+
     /// Default platforms
     pub fn default_platform(&self) -> u32 {
         self.platforms().iter().max().cloned().unwrap()
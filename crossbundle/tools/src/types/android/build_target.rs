@@ -4,6 +4,9 @@ use crate::{
 };
 use serde::{Deserialize, Serialize};
 
+/// Offset between version codes of split APKs for different ABIs.
+const SPLIT_VERSION_CODE_OFFSET: u32 = 100_000_000;
+
 /// Android Target.
 ///
 /// More details: https://doc.rust-lang.org/nightly/rustc/platform-support.html
@@ -61,6 +64,26 @@ impl AndroidTarget {
         }
     }
 
+    /// Code of the ABI used in version codes of per-ABI split APKs.
+    pub fn abi_version_code(self) -> u32 {
+        match self {
+            Self::Armv7 => 1,
+            Self::Aarch64 => 2,
+            Self::I686 => 3,
+            Self::X8664 => 4,
+        }
+    }
+
+    /// Returns version code of the split APK for this ABI: `abi_code * 100000000 +
+    /// version_code`. Split APKs get higher version codes than the universal APK, so
+    /// stores prefer them, and 64-bit splits are preferred over 32-bit ones.
+    pub fn split_version_code(self, version_code: u32) -> Result<u32> {
+        if version_code >= SPLIT_VERSION_CODE_OFFSET {
+            return Err(AndroidError::SplitVersionCodeOverflow(version_code).into());
+        }
+        Ok(self.abi_version_code() * SPLIT_VERSION_CODE_OFFSET + version_code)
+    }
+
     // Returns just the architecture component for clang
     pub fn clang_arch(self) -> &'static str {
         match self {
@@ -102,3 +125,39 @@ impl IntoRustTriple for AndroidTarget {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn test_split_version_code() {
+        assert_eq!(
+            AndroidTarget::Armv7.split_version_code(7).unwrap(),
+            100_000_007
+        );
+        assert_eq!(
+            AndroidTarget::Aarch64.split_version_code(7).unwrap(),
+            200_000_007
+        );
+        assert_eq!(
+            AndroidTarget::I686.split_version_code(7).unwrap(),
+            300_000_007
+        );
+        assert_eq!(
+            AndroidTarget::X8664.split_version_code(99_999_999).unwrap(),
+            499_999_999
+        );
+        assert!(matches!(
+            AndroidTarget::X8664.split_version_code(100_000_000),
+            Err(Error::Android(AndroidError::SplitVersionCodeOverflow(
+                100_000_000
+            )))
+        ));
+        assert!(matches!(
+            AndroidTarget::Armv7.split_version_code(u32::MAX),
+            Err(Error::Android(AndroidError::SplitVersionCodeOverflow(_)))
+        ));
+    }
+}
@@ -3,6 +3,16 @@ pub use android_manifest;
 use super::AndroidStrategy;
 use android_manifest::*;
 
+/// Class name of the `FileProvider` of AndroidX.
+const FILE_PROVIDER: &str = "androidx.core.content.FileProvider";
+/// Name of the `FileProvider` `<meta-data>` with the XML resource of shared paths.
+const FILE_PROVIDER_PATHS: &str = "android.support.FILE_PROVIDER_PATHS";
+/// Default XML resource with the paths shared by `FileProvider`.
+pub const DEFAULT_FILE_PROVIDER_PATHS: &str = "@xml/file_paths";
+
+/// Name of the activity `<meta-data>` that marks the window secure at runtime.
+pub const SECURE_WINDOW_META_DATA: &str = "crossbow.secure_window";
+
 /// Updates [`AndroidManifest`](android_manifest::AndroidManifest) with default values.
 pub fn update_android_manifest_with_default(
     manifest: &mut AndroidManifest,
@@ -83,4 +93,102 @@ pub fn update_android_manifest_with_default(
             }];
         }
     }
+    update_application_components(manifest);
+}
+
+/// Updates services, broadcast receivers and content providers declared in
+/// [`Application`](android_manifest::Application) with default values.
+///
+/// Since Android 12 components with intent filters must explicitly set
+/// `android:exported`, so it defaults to `true` for services and receivers with intent
+/// filters and to `false` otherwise. Providers are not exported by default. AndroidX
+/// `FileProvider` crashes on start without the `FILE_PROVIDER_PATHS` meta-data, so it
+/// defaults to [`DEFAULT_FILE_PROVIDER_PATHS`]. If any service declares
+/// `android:foregroundServiceType`, the `FOREGROUND_SERVICE` permission is added.
+pub fn update_application_components(manifest: &mut AndroidManifest) {
+    for service in manifest.application.service.iter_mut() {
+        if service.exported.is_none() {
+            service.exported = Some(!service.intent_filter.is_empty());
+        }
+    }
+    for receiver in manifest.application.receiver.iter_mut() {
+        if receiver.exported.is_none() {
+            receiver.exported = Some(!receiver.intent_filter.is_empty());
+        }
+    }
+    for provider in manifest.application.provider.iter_mut() {
+        if provider.exported.is_none() {
+            provider.exported = Some(false);
+        }
+        if provider.name == FILE_PROVIDER
+            && !provider
+                .meta_data
+                .iter()
+                .any(|m| m.name.as_deref() == Some(FILE_PROVIDER_PATHS))
+        {
+            provider.meta_data.push(MetaData {
+                name: Some(FILE_PROVIDER_PATHS.to_owned()),
+                resource: Some(DEFAULT_FILE_PROVIDER_PATHS.to_owned()),
+                ..Default::default()
+            });
+        }
+    }
+    let foreground_service = "android.permission.FOREGROUND_SERVICE";
+    if manifest
+        .application
+        .service
+        .iter()
+        .any(|service| service.foreground_service_type.is_some())
+        && !manifest
+            .uses_permission
+            .iter()
+            .any(|p| p.name.as_deref() == Some(foreground_service))
+    {
+        manifest.uses_permission.push(UsesPermission {
+            name: Some(foreground_service.to_owned()),
+            ..Default::default()
+        });
+    }
+}
+
+/// Adds `crossbow.secure_window` meta-data to every activity of the manifest.
+/// `CrossbowNativeActivity` of the Crossbow Java library reads it on start and sets
+/// `FLAG_SECURE` on the window, which blocks screenshots, screen recording and
+/// non-secure displays.
+pub fn add_secure_window_meta_data(manifest: &mut AndroidManifest) {
+    for activity in manifest.application.activity.iter_mut() {
+        if activity
+            .meta_data
+            .iter()
+            .any(|m| m.name.as_deref() == Some(SECURE_WINDOW_META_DATA))
+        {
+            continue;
+        }
+        activity.meta_data.push(MetaData {
+            name: Some(SECURE_WINDOW_META_DATA.to_string()),
+            value: Some("true".to_string()),
+            ..Default::default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_secure_window_meta_data() {
+        let mut manifest = AndroidManifest::default();
+        manifest.application.activity = vec![Activity::default(), Activity::default()];
+        add_secure_window_meta_data(&mut manifest);
+        add_secure_window_meta_data(&mut manifest);
+        for activity in &manifest.application.activity {
+            assert_eq!(activity.meta_data.len(), 1);
+            assert_eq!(
+                activity.meta_data[0].name.as_deref(),
+                Some(SECURE_WINDOW_META_DATA)
+            );
+            assert_eq!(activity.meta_data[0].value.as_deref(), Some("true"));
+        }
+    }
 }
@@ -3,6 +3,7 @@ mod android_sdk;
 mod app_wrapper;
 mod build_target;
 mod manifest;
+mod multi_window;
 mod strategies;
 mod version_code;
 
@@ -11,9 +12,12 @@ pub use android_sdk::*;
 pub use app_wrapper::*;
 pub use build_target::*;
 pub use manifest::*;
+pub use multi_window::*;
 pub use strategies::*;
 pub use version_code::*;
 
 pub use android_tools::aapt2::*;
+// Shadows the daemon wrapper of android-tools, so there is a single `Aapt2Daemon`
+pub use crate::commands::android::Aapt2Daemon;
 pub use android_tools::bundletool::*;
 pub use android_tools::error::Error as AndroidToolsError;
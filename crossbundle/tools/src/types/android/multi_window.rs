@@ -0,0 +1,105 @@
+use android_manifest::{AndroidManifest, Layout};
+use serde::{Deserialize, Serialize};
+
+/// Multi-window configuration of the activities for foldables, tablets, Chromebooks and
+/// freeform windows.
+///
+/// ```toml
+/// [package.metadata.android.multi_window]
+/// resizeable = true
+/// default_width = "800dp"
+/// default_height = "600dp"
+/// min_width = "400dp"
+/// min_height = "300dp"
+/// gravity = "center"
+/// picture_in_picture = true
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MultiWindowConfig {
+    /// `android:resizeableActivity`. Activities that are not resizeable are letterboxed
+    /// on unfolded foldables and large screens. `true` by default.
+    #[serde(default = "default_resizeable")]
+    pub resizeable: bool,
+    /// `android:defaultWidth` of the freeform window, e.g. `800dp` or `50%`.
+    pub default_width: Option<String>,
+    /// `android:defaultHeight` of the freeform window.
+    pub default_height: Option<String>,
+    /// `android:minWidth` of the window in split-screen and freeform modes.
+    pub min_width: Option<String>,
+    /// `android:minHeight` of the window in split-screen and freeform modes.
+    pub min_height: Option<String>,
+    /// `android:gravity` of the freeform window, e.g. `center` or `top|end`.
+    pub gravity: Option<String>,
+    /// `android:supportsPictureInPicture`. Allows the game to enter picture-in-picture
+    /// mode, e.g. to keep a cutscene or a match spectator view visible while the user
+    /// switches apps. `false` by default.
+    #[serde(default)]
+    pub picture_in_picture: bool,
+}
+
+fn default_resizeable() -> bool {
+    true
+}
+
+impl MultiWindowConfig {
+    /// Sets `android:resizeableActivity`, `android:supportsPictureInPicture` and the
+    /// `<layout>` element of every activity of the manifest. Layout attributes that are
+    /// not configured are kept.
+    pub fn apply(&self, manifest: &mut AndroidManifest) {
+        let has_layout = self.default_width.is_some()
+            || self.default_height.is_some()
+            || self.min_width.is_some()
+            || self.min_height.is_some()
+            || self.gravity.is_some();
+        for activity in manifest.application.activity.iter_mut() {
+            activity.resizeable_activity = Some(self.resizeable);
+            if self.picture_in_picture {
+                activity.supports_picture_in_picture = Some(true);
+            }
+            if !has_layout {
+                continue;
+            }
+            let layout = activity.layout.get_or_insert_with(Layout::default);
+            let attributes = [
+                (&mut layout.default_width, &self.default_width),
+                (&mut layout.default_height, &self.default_height),
+                (&mut layout.min_width, &self.min_width),
+                (&mut layout.min_height, &self.min_height),
+                (&mut layout.gravity, &self.gravity),
+            ];
+            for (attribute, value) in attributes {
+                if value.is_some() {
+                    *attribute = value.clone();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use android_manifest::Activity;
+
+    #[test]
+    fn test_multi_window_config() {
+        let config: MultiWindowConfig = serde_json::from_str(
+            r#"{"min_width": "400dp", "gravity": "center", "picture_in_picture": true}"#,
+        )
+        .unwrap();
+        assert!(config.resizeable);
+        let mut manifest = AndroidManifest::default();
+        manifest.application.activity = vec![Activity {
+            resizeable_activity: Some(false),
+            ..Default::default()
+        }];
+        config.apply(&mut manifest);
+        let activity = &manifest.application.activity[0];
+        assert_eq!(activity.resizeable_activity, Some(true));
+        assert_eq!(activity.supports_picture_in_picture, Some(true));
+        let layout = activity.layout.as_ref().unwrap();
+        assert_eq!(layout.min_width.as_deref(), Some("400dp"));
+        assert_eq!(layout.gravity.as_deref(), Some("center"));
+        assert_eq!(layout.default_width, None);
+    }
+}
@@ -1,6 +1,17 @@
-use crate::error::{AndroidError, Result};
+use crate::{
+    error::{AndroidError, CommandExt, Result},
+    types::SemVer,
+};
+use serde::{Deserialize, Serialize};
+use std::{path::Path, process::Command};
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// Maximum version code accepted by Google Play.
+pub const MAX_VERSION_CODE: u32 = 2100000000;
+
+/// Version used to generate Android version code. Contains only major, minor and patch
+/// fields of the semantic version, see [`SemVer`] for the full version with pre-release
+/// and build metadata. Versions are ordered by major, minor and patch fields.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
 pub struct Version {
     pub major: u8,
     pub minor: u8,
@@ -16,16 +27,10 @@ impl Version {
         }
     }
 
-    /// Create `Version` by parsing from string representation
+    /// Create `Version` by parsing from string representation. Pre-release and build
+    /// metadata are dropped
     pub fn from_semver(version: &str) -> Result<Self> {
-        let mut iter = version.split(|c1| ['.', '-', '+'].iter().any(|c2| c1 == *c2));
-        let mut p = || {
-            iter.next()
-                .ok_or(AndroidError::InvalidSemver)?
-                .parse()
-                .map_err(|_| AndroidError::InvalidSemver)
-        };
-        Ok(Self::new(p()?, p()?, p()?))
+        Self::try_from(&version.parse::<SemVer>()?)
     }
 
     pub fn to_code(&self, apk_id: u8) -> u32 {
@@ -35,3 +40,117 @@ impl Version {
             | self.patch as u32
     }
 }
+
+impl TryFrom<&SemVer> for Version {
+    type Error = crate::error::Error;
+
+    fn try_from(version: &SemVer) -> Result<Self> {
+        let p = |n: u64| {
+            u8::try_from(n).map_err(|_| AndroidError::VersionCodeOverflow(version.to_string()))
+        };
+        Ok(Self::new(
+            p(version.major)?,
+            p(version.minor)?,
+            p(version.patch)?,
+        ))
+    }
+}
+
+/// How the version code of the application is generated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VersionCodeStrategy {
+    /// Use `version_code` from the manifest metadata, `1` if it's not specified. Default
+    /// strategy.
+    #[default]
+    Manifest,
+    /// Generate from the version name: `major << 16 | minor << 8 | patch`.
+    Semver,
+    /// Number of commits in the current git branch.
+    GitCommitCount,
+    /// Minutes since Unix epoch, so every build gets a higher version code.
+    Timestamp,
+}
+
+impl VersionCodeStrategy {
+    /// Generates version code. Returns `None` for [`VersionCodeStrategy::Manifest`].
+    pub fn version_code(self, version_name: &str, project_path: &Path) -> Result<Option<u32>> {
+        let version_code = match self {
+            Self::Manifest => return Ok(None),
+            Self::Semver => Version::from_semver(version_name)?.to_code(0),
+            Self::GitCommitCount => {
+                // Runs `git rev-list --count HEAD`
+                let mut git = Command::new("git");
+                git.args(["rev-list", "--count", "HEAD"])
+                    .current_dir(project_path);
+                let output = git.output_err(true)?;
+                let count = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+                count
+                    .parse()
+                    .map_err(|_| AndroidError::InvalidVersionCode(count))?
+            }
+            Self::Timestamp => {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default();
+                (now.as_secs() / 60) as u32
+            }
+        };
+        if version_code == 0 || version_code > MAX_VERSION_CODE {
+            return Err(AndroidError::InvalidVersionCode(version_code.to_string()).into());
+        }
+        Ok(Some(version_code))
+    }
+}
+
+impl std::str::FromStr for VersionCodeStrategy {
+    type Err = AndroidError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "manifest" => Ok(Self::Manifest),
+            "semver" => Ok(Self::Semver),
+            "git-commit-count" => Ok(Self::GitCommitCount),
+            "timestamp" => Ok(Self::Timestamp),
+            _ => Err(AndroidError::InvalidVersionCodeStrategy(s.to_owned())),
+        }
+    }
+}
+
+impl std::fmt::Display for VersionCodeStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Manifest => write!(f, "manifest"),
+            Self::Semver => write!(f, "semver"),
+            Self::GitCommitCount => write!(f, "git-commit-count"),
+            Self::Timestamp => write!(f, "timestamp"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_code_strategy() {
+        let path = Path::new(".");
+        assert_eq!(
+            VersionCodeStrategy::Semver
+                .version_code("1.2.3", path)
+                .unwrap(),
+            Some(0x010203)
+        );
+        assert_eq!(
+            VersionCodeStrategy::Manifest
+                .version_code("1.2.3", path)
+                .unwrap(),
+            None
+        );
+        assert!(VersionCodeStrategy::Semver
+            .version_code("0.0.0", path)
+            .is_err());
+        let strategy: VersionCodeStrategy = "git-commit-count".parse().unwrap();
+        assert_eq!(strategy.to_string(), "git-commit-count");
+    }
+}
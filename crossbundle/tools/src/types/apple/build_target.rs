@@ -1,7 +1,17 @@
 use crate::{error::AppleError, types::IntoRustTriple};
 use serde::{Deserialize, Serialize};
 
-/// iOS Target.
+/// Apple platform of [`IosTarget`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ApplePlatform {
+    Ios,
+    Tvos,
+    Watchos,
+    /// iOS apps running on macOS with Mac Catalyst.
+    MacCatalyst,
+}
+
+/// iOS, tvOS, watchOS and Mac Catalyst Target.
 ///
 /// More details: https://doc.rust-lang.org/nightly/rustc/platform-support.html
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
@@ -18,6 +28,74 @@ pub enum IosTarget {
     Armv7,
     #[serde(rename = "armv7s-apple-ios")]
     Armv7s,
+    #[serde(rename = "aarch64-apple-tvos")]
+    TvosAarch64,
+    #[serde(rename = "aarch64-apple-tvos-sim")]
+    TvosAarch64Sim,
+    #[serde(rename = "x86_64-apple-tvos")]
+    TvosX86_64,
+    #[serde(rename = "arm64_32-apple-watchos")]
+    WatchosArm64_32,
+    #[serde(rename = "armv7k-apple-watchos")]
+    WatchosArmv7k,
+    #[serde(rename = "aarch64-apple-watchos-sim")]
+    WatchosAarch64Sim,
+    #[serde(rename = "x86_64-apple-watchos-sim")]
+    WatchosX86_64Sim,
+    #[serde(rename = "aarch64-apple-ios-macabi")]
+    CatalystAarch64,
+    #[serde(rename = "x86_64-apple-ios-macabi")]
+    CatalystX86_64,
+}
+
+impl IosTarget {
+    /// The target runs on physical devices, not on the simulator. Mac Catalyst apps run
+    /// on the Mac and are not provisioned like devices.
+    pub fn is_device(&self) -> bool {
+        matches!(
+            self,
+            Self::Aarch64
+                | Self::Armv7
+                | Self::Armv7s
+                | Self::TvosAarch64
+                | Self::WatchosArm64_32
+                | Self::WatchosArmv7k
+        )
+    }
+
+    pub fn platform(&self) -> ApplePlatform {
+        match self {
+            Self::TvosAarch64 | Self::TvosAarch64Sim | Self::TvosX86_64 => ApplePlatform::Tvos,
+            Self::WatchosArm64_32
+            | Self::WatchosArmv7k
+            | Self::WatchosAarch64Sim
+            | Self::WatchosX86_64Sim => ApplePlatform::Watchos,
+            Self::CatalystAarch64 | Self::CatalystX86_64 => ApplePlatform::MacCatalyst,
+            _ => ApplePlatform::Ios,
+        }
+    }
+
+    /// Name of the SDK passed to `xcrun --sdk`, e.g. `appletvsimulator`.
+    pub fn sdk_name(&self) -> &'static str {
+        match (self.platform(), self.is_device()) {
+            (ApplePlatform::Ios, true) => "iphoneos",
+            (ApplePlatform::Ios, false) => "iphonesimulator",
+            (ApplePlatform::Tvos, true) => "appletvos",
+            (ApplePlatform::Tvos, false) => "appletvsimulator",
+            (ApplePlatform::Watchos, true) => "watchos",
+            (ApplePlatform::Watchos, false) => "watchsimulator",
+            (ApplePlatform::MacCatalyst, _) => "macosx",
+        }
+    }
+
+    /// tvOS and watchOS targets are Tier 3: rustup doesn't ship their standard library,
+    /// so it's built from source with `-Zbuild-std` on the nightly toolchain.
+    pub fn requires_build_std(&self) -> bool {
+        matches!(
+            self.platform(),
+            ApplePlatform::Tvos | ApplePlatform::Watchos
+        )
+    }
 }
 
 impl IntoRustTriple for IosTarget {
@@ -29,6 +107,15 @@ impl IntoRustTriple for IosTarget {
             Self::Aarch64Sim => "aarch64-apple-ios-sim",
             Self::Armv7 => "armv7-apple-ios",
             Self::Armv7s => "armv7s-apple-ios",
+            Self::TvosAarch64 => "aarch64-apple-tvos",
+            Self::TvosAarch64Sim => "aarch64-apple-tvos-sim",
+            Self::TvosX86_64 => "x86_64-apple-tvos",
+            Self::WatchosArm64_32 => "arm64_32-apple-watchos",
+            Self::WatchosArmv7k => "armv7k-apple-watchos",
+            Self::WatchosAarch64Sim => "aarch64-apple-watchos-sim",
+            Self::WatchosX86_64Sim => "x86_64-apple-watchos-sim",
+            Self::CatalystAarch64 => "aarch64-apple-ios-macabi",
+            Self::CatalystX86_64 => "x86_64-apple-ios-macabi",
         }
     }
 }
@@ -44,6 +131,57 @@ impl std::str::FromStr for IosTarget {
             "aarch64-apple-ios-sim" => Ok(Self::Aarch64Sim),
             "armv7-apple-ios" => Ok(Self::Armv7),
             "armv7s-apple-ios" => Ok(Self::Armv7s),
+            "aarch64-apple-tvos" => Ok(Self::TvosAarch64),
+            "aarch64-apple-tvos-sim" => Ok(Self::TvosAarch64Sim),
+            "x86_64-apple-tvos" => Ok(Self::TvosX86_64),
+            "arm64_32-apple-watchos" => Ok(Self::WatchosArm64_32),
+            "armv7k-apple-watchos" => Ok(Self::WatchosArmv7k),
+            "aarch64-apple-watchos-sim" => Ok(Self::WatchosAarch64Sim),
+            "x86_64-apple-watchos-sim" => Ok(Self::WatchosX86_64Sim),
+            "aarch64-apple-ios-macabi" => Ok(Self::CatalystAarch64),
+            "x86_64-apple-ios-macabi" => Ok(Self::CatalystX86_64),
+            _ => Err(AppleError::InvalidBuildTarget(s.to_owned())),
+        }
+    }
+}
+
+/// macOS Target.
+///
+/// More details: https://doc.rust-lang.org/nightly/rustc/platform-support.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum MacosTarget {
+    #[serde(rename = "x86_64-apple-darwin")]
+    X86_64,
+    #[serde(rename = "aarch64-apple-darwin")]
+    Aarch64,
+}
+
+impl MacosTarget {
+    /// Returns the target of the host architecture.
+    pub fn host() -> Self {
+        match cfg!(target_arch = "x86_64") {
+            true => Self::X86_64,
+            false => Self::Aarch64,
+        }
+    }
+}
+
+impl IntoRustTriple for MacosTarget {
+    fn rust_triple(&self) -> &'static str {
+        match self {
+            Self::X86_64 => "x86_64-apple-darwin",
+            Self::Aarch64 => "aarch64-apple-darwin",
+        }
+    }
+}
+
+impl std::str::FromStr for MacosTarget {
+    type Err = AppleError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "x86_64-apple-darwin" => Ok(Self::X86_64),
+            "aarch64-apple-darwin" => Ok(Self::Aarch64),
             _ => Err(AppleError::InvalidBuildTarget(s.to_owned())),
         }
     }
@@ -0,0 +1,280 @@
+use apple_bundle::{plist, prelude::InfoPlist};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Device family the application runs on. Written into `UIDeviceFamily`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AppleDeviceFamily {
+    Iphone,
+    Ipad,
+    Tv,
+    Watch,
+}
+
+impl AppleDeviceFamily {
+    pub fn value(&self) -> u64 {
+        match self {
+            Self::Iphone => 1,
+            Self::Ipad => 2,
+            Self::Tv => 3,
+            Self::Watch => 4,
+        }
+    }
+}
+
+/// Interface orientation written into `UISupportedInterfaceOrientations`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AppleOrientation {
+    Portrait,
+    PortraitUpsideDown,
+    LandscapeLeft,
+    LandscapeRight,
+}
+
+impl AppleOrientation {
+    pub fn value(&self) -> &'static str {
+        match self {
+            Self::Portrait => "UIInterfaceOrientationPortrait",
+            Self::PortraitUpsideDown => "UIInterfaceOrientationPortraitUpsideDown",
+            Self::LandscapeLeft => "UIInterfaceOrientationLandscapeLeft",
+            Self::LandscapeRight => "UIInterfaceOrientationLandscapeRight",
+        }
+    }
+}
+
+/// Style of the status bar written into `UIStatusBarStyle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AppleStatusBarStyle {
+    Default,
+    LightContent,
+    DarkContent,
+}
+
+impl AppleStatusBarStyle {
+    pub fn value(&self) -> &'static str {
+        match self {
+            Self::Default => "UIStatusBarStyleDefault",
+            Self::LightContent => "UIStatusBarStyleLightContent",
+            Self::DarkContent => "UIStatusBarStyleDarkContent",
+        }
+    }
+}
+
+/// Typed `Info.plist` settings of `[package.metadata.apple]` with readable names. They
+/// are applied on top of `info_plist` or `info_plist_path`. Keys without a typed
+/// setting are written from `info_plist_extra` as is.
+///
+/// ```toml
+/// [package.metadata.apple]
+/// bundle_identifier = "com.crossbow.game"
+/// device_families = ["iphone", "ipad"]
+/// orientations = ["landscape-left", "landscape-right"]
+/// required_device_capabilities = ["arm64", "metal"]
+/// status_bar_hidden = true
+/// multitasking = true
+///
+/// [package.metadata.apple.info_plist_extra]
+/// ITSAppUsesNonExemptEncryption = false
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct InfoPlistSettings {
+    /// `CFBundleIdentifier`.
+    pub bundle_identifier: Option<String>,
+    /// User-visible version, `CFBundleShortVersionString`.
+    pub version: Option<String>,
+    /// Build number, `CFBundleVersion`.
+    pub build_number: Option<String>,
+    /// `UIDeviceFamily`.
+    #[serde(default)]
+    pub device_families: Vec<AppleDeviceFamily>,
+    /// `UISupportedInterfaceOrientations`.
+    #[serde(default)]
+    pub orientations: Vec<AppleOrientation>,
+    /// `UISupportedInterfaceOrientations~ipad`. iPad uses `orientations` if it's empty.
+    #[serde(default)]
+    pub ipad_orientations: Vec<AppleOrientation>,
+    /// `UIRequiredDeviceCapabilities`, e.g. `arm64` or `metal`. The App Store doesn't
+    /// offer the application to devices without them.
+    #[serde(default)]
+    pub required_device_capabilities: Vec<String>,
+    /// `UIStatusBarHidden`.
+    pub status_bar_hidden: Option<bool>,
+    /// `UIStatusBarStyle`.
+    pub status_bar_style: Option<AppleStatusBarStyle>,
+    /// `UIViewControllerBasedStatusBarAppearance`. Must be `false` for
+    /// `status_bar_hidden` and `status_bar_style` to take effect.
+    pub view_controller_based_status_bar_appearance: Option<bool>,
+    /// `UIRequiresFullScreen`. Opts out of iPad multitasking.
+    pub requires_full_screen: Option<bool>,
+    /// iPad Split View, Slide Over and Stage Manager windows. `true` sets
+    /// `UIRequiresFullScreen` to `false` and supports all iPad orientations, which
+    /// multitasking requires. `false` requires full screen.
+    pub multitasking: Option<bool>,
+    /// Any other `Info.plist` keys with their values.
+    #[serde(default)]
+    pub info_plist_extra: BTreeMap<String, plist::Value>,
+}
+
+impl InfoPlistSettings {
+    /// Sets the bundle identifier and versions of the typed `InfoPlist`, the
+    /// application is looked up by them on devices and simulators.
+    pub fn apply(&self, info_plist: &mut InfoPlist) {
+        if let Some(bundle_identifier) = &self.bundle_identifier {
+            info_plist.identification.bundle_identifier = bundle_identifier.clone();
+        }
+        if let Some(version) = &self.version {
+            info_plist.bundle_version.bundle_short_version_string = Some(version.clone());
+        }
+        if let Some(build_number) = &self.build_number {
+            info_plist.bundle_version.bundle_version = Some(build_number.clone());
+        }
+    }
+
+    /// Returns the other settings and extra keys as `Info.plist` keys. Extra keys
+    /// override typed settings.
+    pub fn to_dictionary(&self) -> plist::Dictionary {
+        let mut dictionary = plist::Dictionary::new();
+        let orientations = |orientations: &[AppleOrientation]| {
+            plist::Value::Array(
+                orientations
+                    .iter()
+                    .map(|orientation| plist::Value::String(orientation.value().to_owned()))
+                    .collect(),
+            )
+        };
+        if !self.device_families.is_empty() {
+            let families = self
+                .device_families
+                .iter()
+                .map(|family| plist::Value::Integer(family.value().into()))
+                .collect();
+            dictionary.insert("UIDeviceFamily".to_owned(), plist::Value::Array(families));
+        }
+        if !self.orientations.is_empty() {
+            dictionary.insert(
+                "UISupportedInterfaceOrientations".to_owned(),
+                orientations(&self.orientations),
+            );
+        }
+        if self.multitasking == Some(true) {
+            dictionary.insert(
+                "UISupportedInterfaceOrientations~ipad".to_owned(),
+                orientations(&[
+                    AppleOrientation::Portrait,
+                    AppleOrientation::PortraitUpsideDown,
+                    AppleOrientation::LandscapeLeft,
+                    AppleOrientation::LandscapeRight,
+                ]),
+            );
+        } else if !self.ipad_orientations.is_empty() {
+            dictionary.insert(
+                "UISupportedInterfaceOrientations~ipad".to_owned(),
+                orientations(&self.ipad_orientations),
+            );
+        }
+        if !self.required_device_capabilities.is_empty() {
+            let capabilities = self
+                .required_device_capabilities
+                .iter()
+                .map(|capability| plist::Value::String(capability.clone()))
+                .collect();
+            dictionary.insert(
+                "UIRequiredDeviceCapabilities".to_owned(),
+                plist::Value::Array(capabilities),
+            );
+        }
+        let flags = [
+            ("UIStatusBarHidden", self.status_bar_hidden),
+            (
+                "UIViewControllerBasedStatusBarAppearance",
+                self.view_controller_based_status_bar_appearance,
+            ),
+            (
+                "UIRequiresFullScreen",
+                self.requires_full_screen
+                    .or_else(|| self.multitasking.map(|multitasking| !multitasking)),
+            ),
+        ];
+        for (key, value) in flags {
+            if let Some(value) = value {
+                dictionary.insert(key.to_owned(), plist::Value::Boolean(value));
+            }
+        }
+        if let Some(style) = self.status_bar_style {
+            dictionary.insert(
+                "UIStatusBarStyle".to_owned(),
+                plist::Value::String(style.value().to_owned()),
+            );
+        }
+        for (key, value) in &self.info_plist_extra {
+            dictionary.insert(key.clone(), value.clone());
+        }
+        dictionary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_info_plist_settings() {
+        let settings: InfoPlistSettings = serde_json::from_str(
+            r#"{
+                "bundle_identifier": "com.crossbow.game",
+                "device_families": ["iphone", "ipad"],
+                "orientations": ["landscape-left", "landscape-right"],
+                "status_bar_hidden": true,
+                "info_plist_extra": {"ITSAppUsesNonExemptEncryption": false}
+            }"#,
+        )
+        .unwrap();
+        let mut info_plist = InfoPlist::default();
+        settings.apply(&mut info_plist);
+        assert_eq!(
+            info_plist.identification.bundle_identifier,
+            "com.crossbow.game"
+        );
+        let dictionary = settings.to_dictionary();
+        assert_eq!(
+            dictionary.get("UIDeviceFamily"),
+            Some(&plist::Value::Array(vec![
+                plist::Value::Integer(1u64.into()),
+                plist::Value::Integer(2u64.into())
+            ]))
+        );
+        assert_eq!(
+            dictionary.get("UISupportedInterfaceOrientations"),
+            Some(&plist::Value::Array(vec![
+                plist::Value::String("UIInterfaceOrientationLandscapeLeft".to_owned()),
+                plist::Value::String("UIInterfaceOrientationLandscapeRight".to_owned())
+            ]))
+        );
+        assert_eq!(
+            dictionary.get("UIStatusBarHidden"),
+            Some(&plist::Value::Boolean(true))
+        );
+        assert_eq!(
+            dictionary.get("ITSAppUsesNonExemptEncryption"),
+            Some(&plist::Value::Boolean(false))
+        );
+
+        let settings = InfoPlistSettings {
+            ipad_orientations: vec![AppleOrientation::LandscapeLeft],
+            multitasking: Some(true),
+            ..Default::default()
+        };
+        let dictionary = settings.to_dictionary();
+        assert_eq!(
+            dictionary.get("UIRequiresFullScreen"),
+            Some(&plist::Value::Boolean(false))
+        );
+        let ipad_orientations = dictionary
+            .get("UISupportedInterfaceOrientations~ipad")
+            .and_then(|orientations| orientations.as_array());
+        assert_eq!(ipad_orientations.map(Vec::len), Some(4));
+    }
+}
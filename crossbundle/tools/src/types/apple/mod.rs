@@ -1,9 +1,11 @@
 mod build_target;
 mod info_plist;
+mod info_plist_settings;
 mod strategies;
 
 pub use build_target::*;
 pub use info_plist::*;
+pub use info_plist_settings::*;
 pub use strategies::*;
 
 pub use simctl;
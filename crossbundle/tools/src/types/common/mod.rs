@@ -1,11 +1,13 @@
 mod config;
 mod crate_type;
 mod profile;
+mod semver;
 mod shell;
 mod target;
 
 pub use config::*;
 pub use crate_type::*;
 pub use profile::*;
+pub use semver::*;
 pub use shell::*;
 pub use target::*;
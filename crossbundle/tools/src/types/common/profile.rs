@@ -1,7 +1,9 @@
 use crate::error::Error;
+use serde::{Deserialize, Serialize};
 use std::{path::Path, str::FromStr};
 
-#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Profile {
     Debug,
     Release,
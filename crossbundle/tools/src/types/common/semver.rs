@@ -0,0 +1,243 @@
+use crate::error::*;
+use std::cmp::Ordering;
+
+/// Pre-release identifier of the semantic version.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Identifier {
+    /// Identifier that consists only of digits, e.g. `1` in `1.0.0-rc.1`.
+    Numeric(u64),
+    /// Identifier that contains letters or hyphens, e.g. `rc` in `1.0.0-rc.1`.
+    AlphaNumeric(String),
+}
+
+impl std::fmt::Display for Identifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Numeric(n) => write!(f, "{}", n),
+            Self::AlphaNumeric(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl PartialOrd for Identifier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Identifier {
+    /// Numeric identifiers are compared numerically and always have lower precedence
+    /// than alphanumeric identifiers, which are compared lexically in ASCII order.
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Numeric(a), Self::Numeric(b)) => a.cmp(b),
+            (Self::Numeric(_), Self::AlphaNumeric(_)) => Ordering::Less,
+            (Self::AlphaNumeric(_), Self::Numeric(_)) => Ordering::Greater,
+            (Self::AlphaNumeric(a), Self::AlphaNumeric(b)) => a.cmp(b),
+        }
+    }
+}
+
+/// Semantic version as described in [Semantic Versioning 2.0.0](https://semver.org).
+///
+/// Versions are ordered by precedence: `1.0.0-alpha < 1.0.0-alpha.1 < 1.0.0-rc.1 < 1.0.0`.
+/// Build metadata doesn't affect precedence, use [`SemVer::cmp_precedence`] to compare
+/// versions ignoring it. [`Ord`] uses build metadata only to break ties, so it stays
+/// consistent with [`Eq`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SemVer {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// Pre-release identifiers, e.g. `["rc", 1]` for `1.0.0-rc.1`.
+    pub pre: Vec<Identifier>,
+    /// Build metadata identifiers, e.g. `["build", "5"]` for `1.0.0+build.5`.
+    pub build: Vec<String>,
+}
+
+impl SemVer {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            pre: Vec::new(),
+            build: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if version has pre-release identifiers.
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    /// Compares versions by precedence as described in the semver spec. Build metadata
+    /// is ignored.
+    pub fn cmp_precedence(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                // A pre-release version has lower precedence than a normal version
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            })
+    }
+
+    /// Returns `true` if this version has higher precedence than `other`.
+    pub fn is_newer_than(&self, other: &Self) -> bool {
+        self.cmp_precedence(other) == Ordering::Greater
+    }
+
+    /// Returns `true` if versions have the same precedence. Build metadata is ignored.
+    pub fn is_same_as(&self, other: &Self) -> bool {
+        self.cmp_precedence(other) == Ordering::Equal
+    }
+}
+
+impl PartialOrd for SemVer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SemVer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_precedence(other)
+            .then_with(|| self.build.cmp(&other.build))
+    }
+}
+
+impl std::fmt::Display for SemVer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.pre.is_empty() {
+            let pre: Vec<_> = self.pre.iter().map(ToString::to_string).collect();
+            write!(f, "-{}", pre.join("."))?;
+        }
+        if !self.build.is_empty() {
+            write!(f, "+{}", self.build.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for SemVer {
+    type Err = Error;
+
+    fn from_str(version: &str) -> Result<Self> {
+        let invalid = |reason: &str| Error::InvalidSemver {
+            version: version.to_owned(),
+            reason: reason.to_owned(),
+        };
+        let (rest, build) = match version.split_once('+') {
+            Some((rest, build)) => (rest, Some(build)),
+            None => (version, None),
+        };
+        let (core, pre) = match rest.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (rest, None),
+        };
+        let mut numbers = core.split('.').map(|number| {
+            parse_numeric(number).ok_or_else(|| invalid("invalid major, minor or patch"))
+        });
+        let mut next_number = || {
+            numbers
+                .next()
+                .unwrap_or_else(|| Err(invalid("expected major.minor.patch")))
+        };
+        let (major, minor, patch) = (next_number()?, next_number()?, next_number()?);
+        if numbers.next().is_some() {
+            return Err(invalid("expected major.minor.patch"));
+        }
+        let pre = match pre {
+            Some(pre) => pre
+                .split('.')
+                .map(|identifier| {
+                    if !is_valid_identifier(identifier) {
+                        return Err(invalid("invalid pre-release identifier"));
+                    }
+                    if identifier.bytes().all(|b| b.is_ascii_digit()) {
+                        parse_numeric(identifier)
+                            .map(Identifier::Numeric)
+                            .ok_or_else(|| invalid("invalid numeric pre-release identifier"))
+                    } else {
+                        Ok(Identifier::AlphaNumeric(identifier.to_owned()))
+                    }
+                })
+                .collect::<Result<_>>()?,
+            None => Vec::new(),
+        };
+        let build = match build {
+            Some(build) => build
+                .split('.')
+                .map(|identifier| {
+                    is_valid_identifier(identifier)
+                        .then(|| identifier.to_owned())
+                        .ok_or_else(|| invalid("invalid build metadata identifier"))
+                })
+                .collect::<Result<_>>()?,
+            None => Vec::new(),
+        };
+        Ok(Self {
+            major,
+            minor,
+            patch,
+            pre,
+            build,
+        })
+    }
+}
+
+/// Parses numeric identifier. Leading zeros are not allowed.
+fn parse_numeric(number: &str) -> Option<u64> {
+    if number.is_empty()
+        || !number.bytes().all(|b| b.is_ascii_digit())
+        || (number.len() > 1 && number.starts_with('0'))
+    {
+        return None;
+    }
+    number.parse().ok()
+}
+
+fn is_valid_identifier(identifier: &str) -> bool {
+    !identifier.is_empty()
+        && identifier
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_semver_precedence() {
+        let versions = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.0.1",
+            "1.1.0",
+            "2.0.0",
+        ];
+        let parsed: Vec<SemVer> = versions.iter().map(|v| v.parse().unwrap()).collect();
+        for pair in parsed.windows(2) {
+            assert!(pair[1].is_newer_than(&pair[0]), "{} > {}", pair[1], pair[0]);
+        }
+        let with_build: SemVer = "1.0.0+build.5".parse().unwrap();
+        assert!(with_build.is_same_as(&"1.0.0".parse().unwrap()));
+        assert_eq!(with_build.to_string(), "1.0.0+build.5");
+        for invalid in [
+            "1.0", "1.0.0.0", "01.0.0", "1.0.0-", "1.0.0-01", "1.0.0+", "v1.0.0",
+        ] {
+            assert!(invalid.parse::<SemVer>().is_err(), "{}", invalid);
+        }
+    }
+}
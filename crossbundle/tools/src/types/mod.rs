@@ -3,9 +3,13 @@ mod android;
 #[cfg(feature = "apple")]
 mod apple;
 mod common;
+#[cfg(feature = "windows")]
+mod windows;
 
 #[cfg(feature = "android")]
 pub use android::*;
 #[cfg(feature = "apple")]
 pub use apple::*;
 pub use common::*;
+#[cfg(feature = "windows")]
+pub use windows::*;
@@ -0,0 +1,263 @@
+use super::WindowsTarget;
+use crate::error::*;
+use std::path::{Path, PathBuf};
+
+/// File name of the package manifest in the root of the package layout.
+pub const APPX_MANIFEST_FILE: &str = "AppxManifest.xml";
+/// Windows 10 1809, the first version that installs MSIX packages.
+pub const DEFAULT_MIN_VERSION: &str = "10.0.17763.0";
+/// Windows 11 22H2.
+pub const DEFAULT_MAX_VERSION_TESTED: &str = "10.0.22621.0";
+/// Logos referenced by the manifest: file name in `Images` without extension, width and
+/// height in pixels at 100% scale.
+pub const APPX_LOGOS: [(&str, u32, u32); 4] = [
+    ("StoreLogo", 50, 50),
+    ("Square44x44Logo", 44, 44),
+    ("Square150x150Logo", 150, 150),
+    ("Wide310x150Logo", 310, 150),
+];
+/// Capabilities declared in the `uap` namespace. Other capabilities are declared in the
+/// foundation namespace, `makeappx` rejects the package if the namespace is wrong.
+pub const UAP_CAPABILITIES: [&str; 14] = [
+    "appointments",
+    "blockedChatMessages",
+    "chat",
+    "contacts",
+    "enterpriseAuthentication",
+    "musicLibrary",
+    "objects3D",
+    "phoneCall",
+    "picturesLibrary",
+    "removableStorage",
+    "sharedUserCertificates",
+    "userAccountInformation",
+    "videosLibrary",
+    "voipCall",
+];
+
+/// `AppxManifest.xml` of the MSIX package of a desktop application. The application runs
+/// with full trust like a regular Win32 executable.
+///
+/// More details: https://learn.microsoft.com/en-us/uwp/schemas/appxpackage/uapmanifestschema/element-package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppxManifest {
+    /// Package identity name, e.g. `com.studio.game`. Microsoft Store packages use the
+    /// name reserved in Partner Center.
+    pub identity_name: String,
+    /// Subject of the signing certificate, e.g. `CN=Studio, O=Studio, C=US`.
+    pub publisher: String,
+    /// Four-part package version, e.g. `1.2.0.0`.
+    pub version: String,
+    pub architecture: WindowsTarget,
+    pub display_name: String,
+    pub publisher_display_name: String,
+    pub description: String,
+    /// File name of the executable in the root of the package.
+    pub executable: String,
+    /// Background color of the tiles, e.g. `#1E1E1E`. `transparent` by default.
+    pub background_color: String,
+    /// Minimum Windows version. [`DEFAULT_MIN_VERSION`] by default.
+    pub min_version: String,
+    /// Maximum Windows version the package was tested on.
+    /// [`DEFAULT_MAX_VERSION_TESTED`] by default.
+    pub max_version_tested: String,
+    /// General capabilities, e.g. `internetClient` or `picturesLibrary`. Capabilities
+    /// from [`UAP_CAPABILITIES`] are declared in the `uap` namespace.
+    pub capabilities: Vec<String>,
+    /// Device capabilities, e.g. `microphone` or `webcam`.
+    pub device_capabilities: Vec<String>,
+}
+
+impl AppxManifest {
+    /// Creates the manifest with default tile color and Windows versions. The display name
+    /// is used as the publisher display name and the description.
+    pub fn new(
+        identity_name: &str,
+        publisher: &str,
+        version: &str,
+        architecture: WindowsTarget,
+        display_name: &str,
+        executable: &str,
+    ) -> Self {
+        Self {
+            identity_name: identity_name.to_owned(),
+            publisher: publisher.to_owned(),
+            version: version.to_owned(),
+            architecture,
+            display_name: display_name.to_owned(),
+            publisher_display_name: display_name.to_owned(),
+            description: display_name.to_owned(),
+            executable: executable.to_owned(),
+            background_color: "transparent".to_owned(),
+            min_version: DEFAULT_MIN_VERSION.to_owned(),
+            max_version_tested: DEFAULT_MAX_VERSION_TESTED.to_owned(),
+            capabilities: Vec::new(),
+            device_capabilities: Vec::new(),
+        }
+    }
+
+    /// Checks the identity name and the publisher, which `makeappx` rejects with
+    /// unclear schema errors.
+    pub fn validate(&self) -> Result<()> {
+        let name = &self.identity_name;
+        if !(3..=50).contains(&name.len())
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+        {
+            return Err(WindowsError::InvalidIdentityName(name.clone()).into());
+        }
+        if !self.publisher.starts_with("CN=") {
+            return Err(WindowsError::InvalidPublisher(self.publisher.clone()).into());
+        }
+        Ok(())
+    }
+
+    /// Returns the manifest in XML format.
+    pub fn to_xml(&self) -> String {
+        // The schema requires foundation capabilities before `uap` ones
+        let (uap, foundation): (Vec<&String>, Vec<&String>) = self
+            .capabilities
+            .iter()
+            .partition(|capability| UAP_CAPABILITIES.contains(&capability.as_str()));
+        let mut capabilities = String::new();
+        for capability in foundation {
+            capabilities.push_str(&format!(
+                "    <Capability Name=\"{}\" />\n",
+                xml_escape(capability)
+            ));
+        }
+        for capability in uap {
+            capabilities.push_str(&format!(
+                "    <uap:Capability Name=\"{}\" />\n",
+                xml_escape(capability)
+            ));
+        }
+        capabilities.push_str("    <rescap:Capability Name=\"runFullTrust\" />\n");
+        for capability in &self.device_capabilities {
+            capabilities.push_str(&format!(
+                "    <DeviceCapability Name=\"{}\" />\n",
+                xml_escape(capability)
+            ));
+        }
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<Package
+  xmlns="http://schemas.microsoft.com/appx/manifest/foundation/windows10"
+  xmlns:uap="http://schemas.microsoft.com/appx/manifest/uap/windows10"
+  xmlns:rescap="http://schemas.microsoft.com/appx/manifest/foundation/windows10/restrictedcapabilities"
+  IgnorableNamespaces="uap rescap">
+  <Identity Name="{name}" Publisher="{publisher}" Version="{version}" ProcessorArchitecture="{architecture}" />
+  <Properties>
+    <DisplayName>{display_name}</DisplayName>
+    <PublisherDisplayName>{publisher_display_name}</PublisherDisplayName>
+    <Logo>Images\StoreLogo.png</Logo>
+  </Properties>
+  <Dependencies>
+    <TargetDeviceFamily Name="Windows.Desktop" MinVersion="{min_version}" MaxVersionTested="{max_version_tested}" />
+  </Dependencies>
+  <Resources>
+    <Resource Language="en-us" />
+  </Resources>
+  <Applications>
+    <Application Id="App" Executable="{executable}" EntryPoint="Windows.FullTrustApplication">
+      <uap:VisualElements DisplayName="{display_name}" Description="{description}" BackgroundColor="{background_color}" Square150x150Logo="Images\Square150x150Logo.png" Square44x44Logo="Images\Square44x44Logo.png">
+        <uap:DefaultTile Wide310x150Logo="Images\Wide310x150Logo.png" />
+      </uap:VisualElements>
+    </Application>
+  </Applications>
+  <Capabilities>
+{capabilities}  </Capabilities>
+</Package>
+"#,
+            name = xml_escape(&self.identity_name),
+            publisher = xml_escape(&self.publisher),
+            version = xml_escape(&self.version),
+            architecture = self.architecture.appx_architecture(),
+            display_name = xml_escape(&self.display_name),
+            publisher_display_name = xml_escape(&self.publisher_display_name),
+            min_version = xml_escape(&self.min_version),
+            max_version_tested = xml_escape(&self.max_version_tested),
+            executable = xml_escape(&self.executable),
+            description = xml_escape(&self.description),
+            background_color = xml_escape(&self.background_color),
+            capabilities = capabilities,
+        )
+    }
+
+    /// Saves `AppxManifest.xml` into the package layout directory.
+    pub fn save(&self, layout_dir: &Path) -> Result<PathBuf> {
+        let path = layout_dir.join(APPX_MANIFEST_FILE);
+        std::fs::write(&path, self.to_xml())?;
+        Ok(path)
+    }
+}
+
+/// Converts the cargo package version like `1.2.3` or `1.2.3-rc.1` into the four-part
+/// MSIX version `1.2.3.0`. Pre-release and build metadata are dropped.
+pub fn appx_version(version: &str) -> Result<String> {
+    let invalid = || WindowsError::InvalidPackageVersion(version.to_owned());
+    let core = version.split(|c| c == '-' || c == '+').next().unwrap_or("");
+    let mut numbers = core
+        .split('.')
+        .map(|number| number.parse::<u16>().map_err(|_| invalid()))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    if numbers.len() > 4 {
+        return Err(invalid().into());
+    }
+    numbers.resize(4, 0);
+    Ok(numbers
+        .iter()
+        .map(|number| number.to_string())
+        .collect::<Vec<_>>()
+        .join("."))
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_appx_manifest() {
+        let mut manifest = AppxManifest::new(
+            "com.crossbow.game",
+            "CN=Studio",
+            &appx_version("1.2.3-rc.1").unwrap(),
+            WindowsTarget::X86_64,
+            "Rock & Roll",
+            "game.exe",
+        );
+        manifest.capabilities.push("internetClient".to_owned());
+        manifest.validate().unwrap();
+        let xml = manifest.to_xml();
+        assert!(xml.contains(r#"<Identity Name="com.crossbow.game" Publisher="CN=Studio" Version="1.2.3.0" ProcessorArchitecture="x64" />"#));
+        assert!(xml.contains("<DisplayName>Rock &amp; Roll</DisplayName>"));
+        assert!(xml.contains(r#"<Capability Name="internetClient" />"#));
+        assert!(xml.contains(r#"<rescap:Capability Name="runFullTrust" />"#));
+
+        manifest.capabilities = vec!["picturesLibrary".to_owned(), "internetClient".to_owned()];
+        manifest.device_capabilities.push("webcam".to_owned());
+        let xml = manifest.to_xml();
+        assert!(xml.contains(
+            r#"    <Capability Name="internetClient" />
+    <uap:Capability Name="picturesLibrary" />
+    <rescap:Capability Name="runFullTrust" />
+    <DeviceCapability Name="webcam" />
+"#
+        ));
+        assert!(!xml.contains(r#"<Capability Name="picturesLibrary" />"#));
+
+        manifest.identity_name = "com.crossbow.my_game".to_owned();
+        assert!(manifest.validate().is_err());
+        assert!(appx_version("70000.0.0").is_err());
+        assert_eq!(appx_version("2.1").unwrap(), "2.1.0.0");
+    }
+}
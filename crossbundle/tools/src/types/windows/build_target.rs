@@ -0,0 +1,52 @@
+use crate::{error::WindowsError, types::IntoRustTriple};
+use serde::{Deserialize, Serialize};
+
+/// Windows Target. Packages are built with the MSVC toolchain.
+///
+/// More details: https://doc.rust-lang.org/nightly/rustc/platform-support.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum WindowsTarget {
+    #[serde(rename = "x86_64-pc-windows-msvc")]
+    X86_64,
+    #[serde(rename = "aarch64-pc-windows-msvc")]
+    Aarch64,
+}
+
+impl WindowsTarget {
+    /// Returns the target of the host architecture.
+    pub fn host() -> Self {
+        match cfg!(target_arch = "aarch64") {
+            true => Self::Aarch64,
+            false => Self::X86_64,
+        }
+    }
+
+    /// Returns `ProcessorArchitecture` of the MSIX package identity.
+    pub fn appx_architecture(&self) -> &'static str {
+        match self {
+            Self::X86_64 => "x64",
+            Self::Aarch64 => "arm64",
+        }
+    }
+}
+
+impl IntoRustTriple for WindowsTarget {
+    fn rust_triple(&self) -> &'static str {
+        match self {
+            Self::X86_64 => "x86_64-pc-windows-msvc",
+            Self::Aarch64 => "aarch64-pc-windows-msvc",
+        }
+    }
+}
+
+impl std::str::FromStr for WindowsTarget {
+    type Err = WindowsError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "x86_64-pc-windows-msvc" => Ok(Self::X86_64),
+            "aarch64-pc-windows-msvc" => Ok(Self::Aarch64),
+            _ => Err(WindowsError::InvalidBuildTarget(s.to_owned())),
+        }
+    }
+}
@@ -0,0 +1,5 @@
+mod appx_manifest;
+mod build_target;
+
+pub use appx_manifest::*;
+pub use build_target::*;
@@ -1,7 +1,7 @@
 #![cfg(feature = "android")]
 
 use crossbundle_tools::{
-    commands::{android::*, gen_minimal_project},
+    commands::{android::*, gen_minimal_project, MinimalProject, MinimalProjectTemplate},
     types::*,
 };
 
@@ -12,7 +12,9 @@ fn add_bevy_libs() {
     let project_path = tempdir.path();
 
     // Assigns configuration for project
-    let bevy_package_name = gen_minimal_project(project_path, false).unwrap();
+    let bevy_package_name = MinimalProject::new(MinimalProjectTemplate::BevySprite)
+        .generate(project_path)
+        .unwrap();
 
     // Assign needed configuration to compile rust for android with bevy
     let sdk = AndroidSdk::from_env().unwrap();
@@ -24,6 +26,14 @@ fn add_bevy_libs() {
     let app_wrapper_for_bevy = AppWrapper::NdkGlue;
 
     // Compile rust code for android with bevy engine
+    set_rust_compile_env(
+        &ndk,
+        build_target,
+        project_path,
+        profile,
+        target_sdk_version,
+    )
+    .unwrap();
     rust_compile(
         &ndk,
         build_target,
@@ -35,6 +45,8 @@ fn add_bevy_libs() {
         target_sdk_version,
         &bevy_lib_name,
         app_wrapper_for_bevy,
+        None,
+        false,
     )
     .unwrap();
     println!("rust was compiled for bevy example");
@@ -93,6 +105,14 @@ fn add_quad_libs() {
     let app_wrapper_for_quad = AppWrapper::Quad;
 
     // Compile rust code for android with bevy engine
+    set_rust_compile_env(
+        &ndk,
+        build_target,
+        project_path,
+        profile,
+        target_sdk_version,
+    )
+    .unwrap();
     rust_compile(
         &ndk,
         build_target,
@@ -104,6 +124,8 @@ fn add_quad_libs() {
         target_sdk_version,
         &quad_lib_name,
         app_wrapper_for_quad,
+        None,
+        false,
     )
     .unwrap();
     println!("rust was compiled for quad example");
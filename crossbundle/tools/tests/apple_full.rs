@@ -81,6 +81,7 @@ fn test_apple_full() {
         false,
         false,
         &[],
+        false,
     )
     .unwrap();
     let out_dir = dir
@@ -94,7 +95,7 @@ fn test_apple_full() {
 
     // Generate Info.plist
     let properties = get_minimal_info_plist(&name);
-    save_info_plist(&app_dir, &properties, false).unwrap();
+    save_info_plist(&app_dir, &properties, &Default::default(), false).unwrap();
 
     // Sign bundle
     codesign(&app_dir, true, None, None).unwrap();
@@ -0,0 +1,172 @@
+//! Hermetic Android SDK/NDK layout with fake tool binaries. Every fake tool records its
+//! invocation into a JSON lines log, so tests can verify exact arguments passed to the
+//! tools without a real SDK installed.
+
+#![allow(dead_code)]
+
+use crossbundle_tools::types::{AndroidNdk, AndroidSdk};
+use serde::Deserialize;
+use std::{
+    fs::{create_dir_all, File},
+    io::Write,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
+
+pub const FAKE_BUILD_TOOLS_VERSION: &str = "31.0.0";
+pub const FAKE_NDK_VERSION: &str = "23.1.7779620";
+pub const FAKE_PLATFORM: u32 = 31;
+
+/// Recorded invocation of a fake tool.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Invocation {
+    pub tool: String,
+    pub args: Vec<String>,
+}
+
+/// Shell script that appends `{"tool": ..., "args": [...]}` line into the log, creates
+/// files passed with `-o`/`-F` flags and prints `<tool>.stdout` file if it exists.
+const FAKE_TOOL_SCRIPT: &str = r#"#!/bin/sh
+escape() {
+    printf '%s' "$1" | sed -e 's/\\/\\\\/g' -e 's/"/\\"/g'
+}
+{
+    printf '{"tool":"%s","args":[' "$(basename "$0")"
+    sep=""
+    for arg in "$@"; do
+        printf '%s"%s"' "$sep" "$(escape "$arg")"
+        sep=","
+    done
+    printf ']}\n'
+} >> "{log}"
+prev=""
+for arg in "$@"; do
+    case "$prev" in
+        -o|-F) mkdir -p "$(dirname "$arg")" && touch "$arg" ;;
+    esac
+    prev="$arg"
+done
+if [ -f "$0.stdout" ]; then
+    cat "$0.stdout"
+fi
+"#;
+
+/// Temporary SDK and NDK with fake `aapt`, `aapt2`, `zipalign`, `apksigner`, `adb`,
+/// `clang`, `llvm-readelf` and `llvm-strip` tools.
+pub struct FakeSdk {
+    dir: TempDir,
+    log: PathBuf,
+}
+
+impl FakeSdk {
+    pub fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("invocations.jsonl");
+        let fake_sdk = Self { dir, log };
+
+        let build_tools = fake_sdk
+            .sdk_path()
+            .join("build-tools")
+            .join(FAKE_BUILD_TOOLS_VERSION);
+        for tool in ["aapt", "aapt2", "zipalign", "apksigner"] {
+            fake_sdk.add_tool(&build_tools, tool);
+        }
+        fake_sdk.add_tool(&fake_sdk.sdk_path().join("platform-tools"), "adb");
+        let platform = fake_sdk
+            .sdk_path()
+            .join("platforms")
+            .join(format!("android-{}", FAKE_PLATFORM));
+        create_dir_all(&platform).unwrap();
+        File::create(platform.join("android.jar")).unwrap();
+
+        let ndk_path = fake_sdk.ndk_path();
+        create_dir_all(&ndk_path).unwrap();
+        std::fs::write(
+            ndk_path.join("source.properties"),
+            format!(
+                "Pkg.Desc = Android NDK\nPkg.Revision = {}\n",
+                FAKE_NDK_VERSION
+            ),
+        )
+        .unwrap();
+        let host = if cfg!(target_os = "macos") {
+            "darwin-x86_64"
+        } else {
+            "linux-x86_64"
+        };
+        let toolchain_bin = ndk_path
+            .join("toolchains")
+            .join("llvm")
+            .join("prebuilt")
+            .join(host)
+            .join("bin");
+        for tool in ["clang", "clang++", "llvm-readelf", "llvm-strip"] {
+            fake_sdk.add_tool(&toolchain_bin, tool);
+        }
+        fake_sdk
+    }
+
+    pub fn sdk_path(&self) -> PathBuf {
+        self.dir.path().join("sdk")
+    }
+
+    pub fn ndk_path(&self) -> PathBuf {
+        self.sdk_path().join("ndk").join(FAKE_NDK_VERSION)
+    }
+
+    pub fn sdk(&self) -> AndroidSdk {
+        AndroidSdk::from_path(self.sdk_path()).unwrap()
+    }
+
+    pub fn ndk(&self) -> AndroidNdk {
+        AndroidNdk::from_path(self.ndk_path()).unwrap()
+    }
+
+    /// Directory for test artifacts that is removed together with the fake SDK.
+    pub fn work_dir(&self) -> PathBuf {
+        let work_dir = self.dir.path().join("work");
+        create_dir_all(&work_dir).unwrap();
+        work_dir
+    }
+
+    /// Sets output printed by the fake tool on every invocation.
+    pub fn set_stdout(&self, tool_path: &Path, stdout: &str) {
+        let mut file_name = tool_path.file_name().unwrap().to_owned();
+        file_name.push(".stdout");
+        std::fs::write(tool_path.with_file_name(file_name), stdout).unwrap();
+    }
+
+    /// Returns all recorded invocations in order.
+    pub fn invocations(&self) -> Vec<Invocation> {
+        let log = match std::fs::read_to_string(&self.log) {
+            Ok(log) => log,
+            Err(_) => return Vec::new(),
+        };
+        log.lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    /// Returns arguments of the last invocation of given tool.
+    pub fn last_args(&self, tool: &str) -> Vec<String> {
+        self.invocations()
+            .into_iter()
+            .rev()
+            .find(|invocation| invocation.tool == tool)
+            .unwrap_or_else(|| panic!("{} wasn't invoked", tool))
+            .args
+    }
+
+    pub fn add_tool(&self, dir: &Path, name: &str) -> PathBuf {
+        create_dir_all(dir).unwrap();
+        let path = dir.join(name);
+        let script = FAKE_TOOL_SCRIPT.replace("{log}", self.log.to_str().unwrap());
+        File::create(&path)
+            .unwrap()
+            .write_all(script.as_bytes())
+            .unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+}
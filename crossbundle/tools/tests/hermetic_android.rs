@@ -0,0 +1,226 @@
+#![cfg(all(unix, feature = "android"))]
+
+mod fake_tools;
+
+use android_tools::java_tools::Key;
+use crossbundle_tools::{commands::android::*, types::*};
+use fake_tools::*;
+
+fn args(args: &[&dyn AsRef<std::ffi::OsStr>]) -> Vec<String> {
+    args.iter()
+        .map(|arg| arg.as_ref().to_string_lossy().into_owned())
+        .collect()
+}
+
+#[test]
+fn native_apk_pipeline() {
+    let fake_sdk = FakeSdk::new();
+    let sdk = fake_sdk.sdk();
+    let work_dir = fake_sdk.work_dir();
+    let build_dir = work_dir.join("build");
+    let manifest_path = work_dir.join("AndroidManifest.xml");
+
+    let unaligned_apk = gen_unaligned_apk(
+        &sdk,
+        &work_dir,
+        &build_dir,
+        &manifest_path,
+        &None,
+        &None,
+        "example",
+        FAKE_PLATFORM,
+    )
+    .unwrap();
+    assert_eq!(
+        fake_sdk.last_args("aapt"),
+        args(&[
+            &"package",
+            &"-f",
+            &"-F",
+            &unaligned_apk,
+            &"-M",
+            &manifest_path,
+            &"-I",
+            &sdk.android_jar(FAKE_PLATFORM).unwrap(),
+        ])
+    );
+
+    let aligned_apk = build_dir.join("example.apk");
+    ZipAlign::new(&unaligned_apk, &aligned_apk)
+        .force(true)
+        .page_size(PAGE_SIZE_16KB)
+        .run(&sdk)
+        .unwrap();
+    assert_eq!(
+        fake_sdk.last_args("zipalign"),
+        args(&[
+            &"-f",
+            &"-p",
+            &"-P",
+            &"16",
+            &"4",
+            &unaligned_apk,
+            &aligned_apk
+        ])
+    );
+
+    let key_path = work_dir.join("debug.keystore");
+    let key = Key {
+        key_path: key_path.clone(),
+        key_pass: "android".to_owned(),
+        key_alias: "androiddebugkey".to_owned(),
+    };
+    sign_apk(&sdk, &aligned_apk, key).unwrap();
+    assert_eq!(
+        fake_sdk.last_args("apksigner"),
+        args(&[
+            &"sign",
+            &"--ks",
+            &key_path,
+            &"--ks-pass",
+            &"pass:android",
+            &aligned_apk
+        ])
+    );
+
+    install_apk(&sdk, &aligned_apk, None).unwrap();
+    start_app(
+        &sdk,
+        "com.crossbow.example",
+        "android.app.NativeActivity",
+        Some("emulator-5554"),
+    )
+    .unwrap();
+    let adb_invocations: Vec<_> = fake_sdk
+        .invocations()
+        .into_iter()
+        .filter(|invocation| invocation.tool == "adb")
+        .map(|invocation| invocation.args)
+        .collect();
+    assert_eq!(
+        adb_invocations,
+        vec![
+            args(&[&"install", &"-r", &aligned_apk]),
+            args(&[
+                &"-s",
+                &"emulator-5554",
+                &"shell",
+                &"am",
+                &"start",
+                &"-a",
+                &"android.intent.action.MAIN",
+                &"-n",
+                &"com.crossbow.example/android.app.NativeActivity"
+            ]),
+        ]
+    );
+}
+
+#[test]
+fn adb_output_is_parsed() {
+    let fake_sdk = FakeSdk::new();
+    let sdk = fake_sdk.sdk();
+    fake_sdk.set_stdout(
+        &fake_sdk.sdk_path().join("platform-tools").join("adb"),
+        "arm64-v8a\n",
+    );
+    assert_eq!(detect_abi(&sdk).unwrap(), AndroidTarget::Aarch64);
+    assert_eq!(
+        fake_sdk.last_args("adb"),
+        args(&[&"shell", &"getprop", &"ro.product.cpu.abi"])
+    );
+}
+
+#[test]
+fn app_pid_is_captured() {
+    let fake_sdk = FakeSdk::new();
+    let sdk = fake_sdk.sdk();
+    fake_sdk.set_stdout(
+        &fake_sdk.sdk_path().join("platform-tools").join("adb"),
+        "4242\n",
+    );
+    let pid = wait_for_app_pid(
+        &sdk,
+        "com.crossbow.example",
+        Some("emulator-5554"),
+        std::time::Duration::from_secs(1),
+    )
+    .unwrap();
+    assert_eq!(pid, 4242);
+    assert_eq!(
+        fake_sdk.last_args("adb"),
+        args(&[
+            &"-s",
+            &"emulator-5554",
+            &"shell",
+            &"pidof",
+            &"-s",
+            &"com.crossbow.example"
+        ])
+    );
+}
+
+#[test]
+fn ndk_toolchain_is_resolved() {
+    let fake_sdk = FakeSdk::new();
+    let ndk = fake_sdk.ndk();
+    assert_eq!(ndk.build_tag(), 7779620);
+    assert_eq!(ndk.major_version(), 23);
+    assert_eq!(ndk.version().to_string(), "23.1.7779620");
+    assert!(ndk.require_version("23").is_ok());
+    assert!(ndk.require_version("25.1.8937393").is_err());
+
+    let (clang, clang_pp) = ndk.clang(AndroidTarget::Aarch64, FAKE_PLATFORM).unwrap();
+    assert!(clang.ends_with("clang"));
+    assert!(clang_pp.ends_with("clang++"));
+
+    let readelf = ndk
+        .toolchain_bin("readelf", AndroidTarget::Aarch64)
+        .unwrap();
+    fake_sdk.set_stdout(
+        &readelf,
+        " 0x0000000000000001 (NEEDED)             Shared library: [libc++_shared.so]\n 0x0000000000000001 (NEEDED)             Shared library: [liblog.so]\n",
+    );
+    let lib_path = fake_sdk.work_dir().join("libexample.so");
+    let libs = readelf_list_shared_libs(&readelf, &lib_path).unwrap();
+    assert_eq!(libs, vec!["libc++_shared.so", "liblog.so"]);
+    assert_eq!(
+        fake_sdk.last_args("llvm-readelf"),
+        args(&[&"-d", &lib_path])
+    );
+}
+
+#[test]
+fn ndk_versions_are_discovered() {
+    let fake_sdk = FakeSdk::new();
+    for version in ["9.0.1", "21.4.7075529"] {
+        std::fs::create_dir_all(fake_sdk.sdk_path().join("ndk").join(version)).unwrap();
+    }
+    let versions = AndroidNdk::installed_versions(&fake_sdk.sdk_path())
+        .unwrap()
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect::<Vec<_>>();
+    assert_eq!(versions, ["9.0.1", "21.4.7075529", FAKE_NDK_VERSION]);
+    assert!(AndroidNdk::from_env_with_version(&fake_sdk.sdk_path(), Some("26.0.0")).is_err());
+    let ndk =
+        AndroidNdk::from_env_with_version(&fake_sdk.sdk_path(), Some(FAKE_NDK_VERSION)).unwrap();
+    assert_eq!(ndk.ndk_path(), fake_sdk.ndk_path());
+}
+
+#[test]
+fn ndk_llvm_binutils_are_preferred_since_r23() {
+    let fake_sdk = FakeSdk::new();
+    let ndk = fake_sdk.ndk();
+    let toolchain_bin = ndk
+        .toolchain_bin("readelf", AndroidTarget::Armv7)
+        .unwrap()
+        .parent()
+        .unwrap()
+        .to_owned();
+    // Leftover GNU binutils are ignored by NDK r23 and newer
+    fake_sdk.add_tool(&toolchain_bin, "arm-linux-androideabi-strip");
+    let strip = ndk.toolchain_bin("strip", AndroidTarget::Armv7).unwrap();
+    assert!(strip.ends_with("llvm-strip"));
+    assert!(ndk.toolchain_bin("objcopy", AndroidTarget::Armv7).is_err());
+}
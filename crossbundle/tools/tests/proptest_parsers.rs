@@ -0,0 +1,83 @@
+//! Property tests checking that parsers of versions and tools output never panic on
+//! malformed input.
+
+use crossbundle_tools::types::{SemVer, Version};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn version_from_semver_doesnt_panic(version in ".*") {
+        let _ = Version::from_semver(&version);
+        let _ = version.parse::<SemVer>();
+    }
+
+    #[test]
+    fn version_from_semver_roundtrip(
+        major in 0u8..,
+        minor in 0u8..,
+        patch in 0u8..,
+        suffix in "(-[a-z][a-z0-9]{0,4}(\\.[1-9][0-9]{0,2})?)?(\\+[a-z0-9]{1,8})?",
+    ) {
+        let version = Version::from_semver(&format!("{}.{}.{}{}", major, minor, patch, suffix)).unwrap();
+        prop_assert_eq!(version, Version::new(major, minor, patch));
+    }
+
+    #[test]
+    fn semver_display_roundtrip(version in "(0|[1-9][0-9]{0,3})\\.(0|[1-9][0-9]{0,3})\\.(0|[1-9][0-9]{0,3})(-[a-z][a-z0-9-]{0,4}(\\.(0|[1-9][0-9]{0,2}))?)?(\\+[a-z0-9]{1,8})?") {
+        let parsed: SemVer = version.parse().unwrap();
+        prop_assert_eq!(parsed.to_string(), version);
+    }
+
+    #[test]
+    fn semver_precedence_is_antisymmetric(a in "[0-3]\\.[0-3]\\.[0-3](-(alpha|beta|rc)(\\.[1-3])?)?", b in "[0-3]\\.[0-3]\\.[0-3](-(alpha|beta|rc)(\\.[1-3])?)?") {
+        let a: SemVer = a.parse().unwrap();
+        let b: SemVer = b.parse().unwrap();
+        prop_assert_eq!(a.cmp_precedence(&b), b.cmp_precedence(&a).reverse());
+        prop_assert!(!(a.is_newer_than(&b) && b.is_newer_than(&a)));
+    }
+
+    #[test]
+    fn version_ordering_matches_version_code(
+        a in (0u8.., 0u8.., 0u8..),
+        b in (0u8.., 0u8.., 0u8..),
+    ) {
+        let a = Version::new(a.0, a.1, a.2);
+        let b = Version::new(b.0, b.1, b.2);
+        prop_assert_eq!(a.cmp(&b), a.to_code(1).cmp(&b.to_code(1)));
+    }
+}
+
+#[cfg(feature = "android")]
+mod android {
+    use crossbundle_tools::{commands::android::*, types::AndroidNdk};
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn ndk_build_tag_doesnt_panic(source_properties in ".*") {
+            let _ = AndroidNdk::parse_build_tag(&source_properties);
+        }
+
+        #[test]
+        fn ndk_build_tag_parses_revision(
+            major in 0u32..100,
+            minor in 0u32..10,
+            build_tag in any::<u32>(),
+            beta in "(-beta[0-9])?",
+        ) {
+            let source_properties = format!(
+                "Pkg.Desc = Android NDK\nPkg.Revision = {}.{}.{}{}\n",
+                major, minor, build_tag, beta
+            );
+            prop_assert_eq!(AndroidNdk::parse_build_tag(&source_properties).unwrap(), build_tag);
+            prop_assert_eq!(AndroidNdk::parse_major_version(&source_properties).unwrap(), major);
+        }
+
+        #[test]
+        fn tools_output_parsers_dont_panic(output in "(.{0,40}\n){0,10}") {
+            parse_keystore_entries(&output);
+            parse_certificate_info(&output);
+            parse_adb_devices(&format!("List of devices attached\n{}", output));
+        }
+    }
+}
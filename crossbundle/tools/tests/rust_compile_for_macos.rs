@@ -20,6 +20,7 @@ fn test_compile_apple() {
         false,
         false,
         &[],
+        false,
     )
     .unwrap();
 }
@@ -0,0 +1,314 @@
+//! Snapshot tests for generated manifests, plists, entitlements, exported Gradle projects
+//! and app bundles. Snapshots are stored in `tests/snapshots`. Run tests with
+//! `UPDATE_SNAPSHOTS=1` to create new snapshots and accept intended changes.
+
+use std::path::Path;
+
+/// Returns sorted paths of the files in `dir` relative to `root` with `/` separators.
+#[allow(dead_code)]
+fn walk_files(root: &Path, dir: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(walk_files(root, &path));
+        } else {
+            let relative = path.strip_prefix(root).unwrap();
+            let components: Vec<_> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            files.push(components.join("/"));
+        }
+    }
+    files.sort();
+    files
+}
+
+fn assert_snapshot(name: &str, actual: &str) {
+    assert_snapshot_with(name, actual, |expected, actual| expected == actual);
+}
+
+/// Compares the snapshot with `matches`. Fails if the snapshot is missing, unless
+/// `UPDATE_SNAPSHOTS` is set.
+fn assert_snapshot_with(name: &str, actual: &str, matches: impl Fn(&str, &str) -> bool) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots")
+        .join(format!("{}.snap", name));
+    let actual = actual.replace("\r\n", "\n");
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = match std::fs::read_to_string(&path) {
+        Ok(expected) => expected.replace("\r\n", "\n"),
+        Err(_) => panic!(
+            "Snapshot `{}` is missing.\n{}\nRun tests with UPDATE_SNAPSHOTS=1 to create it",
+            name, actual
+        ),
+    };
+    if !matches(&expected, &actual) {
+        panic!(
+            "Snapshot `{}` doesn't match.\n--- expected\n{}\n+++ actual\n{}\nRun tests with UPDATE_SNAPSHOTS=1 to accept the changes",
+            name, expected, actual
+        );
+    }
+}
+
+#[cfg(feature = "android")]
+mod android {
+    use super::{assert_snapshot, assert_snapshot_with};
+    use crossbundle_tools::{
+        commands::android::*,
+        types::{android_manifest::*, *},
+    };
+
+    fn manifest_snapshot(
+        name: &str,
+        mut manifest: AndroidManifest,
+        strategy: AndroidStrategy,
+    ) -> AndroidManifest {
+        update_android_manifest_with_default(
+            &mut manifest,
+            Some("Snapshot".to_owned()),
+            "snapshot-example",
+            strategy,
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let manifest_path = save_android_manifest(dir.path(), &manifest).unwrap();
+        // Compared as parsed manifests, the order of XML attributes doesn't matter
+        assert_snapshot_with(
+            name,
+            &std::fs::read_to_string(manifest_path).unwrap(),
+            |expected, actual| {
+                from_str::<AndroidManifest>(expected).unwrap() == from_str(actual).unwrap()
+            },
+        );
+        manifest
+    }
+
+    #[test]
+    fn android_manifest_matrix() {
+        for (strategy, strategy_name) in [
+            (AndroidStrategy::NativeApk, "native_apk"),
+            (AndroidStrategy::NativeAab, "native_aab"),
+            (AndroidStrategy::GradleApk, "gradle_apk"),
+        ] {
+            manifest_snapshot(
+                &format!("android_manifest_default_{}", strategy_name),
+                AndroidManifest::default(),
+                strategy,
+            );
+            let custom = AndroidManifest {
+                package: "com.example.custom".to_owned(),
+                version_name: Some("2.1.0".to_owned()),
+                version_code: Some(42),
+                uses_sdk: Some(UsesSdk {
+                    min_sdk_version: Some(24),
+                    target_sdk_version: Some(33),
+                    max_sdk_version: None,
+                }),
+                ..Default::default()
+            };
+            manifest_snapshot(
+                &format!("android_manifest_custom_{}", strategy_name),
+                custom,
+                strategy,
+            );
+        }
+    }
+
+    #[test]
+    fn android_manifest_components() {
+        let mut manifest = AndroidManifest::default();
+        manifest.application.service = vec![
+            Service {
+                name: "com.crossbow.game.MessagingService".to_owned(),
+                intent_filter: vec![IntentFilter {
+                    action: vec![Action {
+                        name: Some("com.google.firebase.MESSAGING_EVENT".to_owned()),
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            // Deserialized like the metadata of `Cargo.toml`
+            toml::from_str(
+                r#"
+                name = "com.crossbow.game.DownloadService"
+                foreground_service_type = "dataSync"
+                "#,
+            )
+            .unwrap(),
+        ];
+        manifest.application.receiver = vec![Receiver {
+            name: "com.crossbow.game.BootReceiver".to_owned(),
+            intent_filter: vec![IntentFilter {
+                action: vec![Action {
+                    name: Some("android.intent.action.BOOT_COMPLETED".to_owned()),
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }];
+        manifest.application.provider = vec![Provider {
+            name: "androidx.core.content.FileProvider".to_owned(),
+            grant_uri_permissions: Some(true),
+            ..Default::default()
+        }];
+        let manifest = manifest_snapshot(
+            "android_manifest_components_gradle_apk",
+            manifest,
+            AndroidStrategy::GradleApk,
+        );
+        let exported: Vec<_> = manifest
+            .application
+            .service
+            .iter()
+            .map(|service| service.exported)
+            .chain(manifest.application.receiver.iter().map(|r| r.exported))
+            .chain(manifest.application.provider.iter().map(|p| p.exported))
+            .collect();
+        assert_eq!(exported, [Some(true), Some(false), Some(true), Some(false)]);
+        assert!(manifest
+            .uses_permission
+            .iter()
+            .any(|p| p.name.as_deref() == Some("android.permission.FOREGROUND_SERVICE")));
+        let file_provider_paths = &manifest.application.provider[0].meta_data[0];
+        assert_eq!(
+            file_provider_paths.name.as_deref(),
+            Some("android.support.FILE_PROVIDER_PATHS")
+        );
+        assert_eq!(
+            file_provider_paths.resource.as_deref(),
+            Some(DEFAULT_FILE_PROVIDER_PATHS)
+        );
+    }
+
+    #[test]
+    fn gradle_project_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let plugins: AndroidGradlePlugins = toml::from_str(
+            r#"
+            plugins_remote = ["com.example:plugin:1.0.0"]
+            plugins_maven_repos = ["https://maven.example.com"]
+            plugins_local_projects = [{ include = ":plugin" }]
+            "#,
+        )
+        .unwrap();
+        let gradle_project_path = gen_gradle_project(
+            "com.crossbow.snapshot",
+            3,
+            "0.3.0",
+            dir.path(),
+            &None,
+            &None,
+            &plugins,
+        )
+        .unwrap();
+        let files = walk_files(&gradle_project_path, &gradle_project_path);
+        assert_snapshot("gradle_project_files", &files.join("\n"));
+        assert_snapshot(
+            "gradle_project_properties",
+            &std::fs::read_to_string(gradle_project_path.join("gradle.properties")).unwrap(),
+        );
+        assert_snapshot(
+            "gradle_project_settings",
+            &std::fs::read_to_string(gradle_project_path.join("settings.gradle")).unwrap(),
+        );
+    }
+}
+
+#[cfg(feature = "apple")]
+mod apple {
+    use super::{assert_snapshot, walk_files};
+    use crossbundle_tools::{
+        commands::apple::*,
+        types::{apple_bundle::prelude::*, *},
+    };
+
+    fn info_plist_snapshot(name: &str, mut properties: InfoPlist, app_name: Option<String>) {
+        update_info_plist_with_default(&mut properties, "snapshot-example", app_name);
+        let dir = tempfile::tempdir().unwrap();
+        save_info_plist(dir.path(), &properties, &Default::default(), false).unwrap();
+        assert_snapshot(
+            name,
+            &std::fs::read_to_string(dir.path().join("Info.plist")).unwrap(),
+        );
+    }
+
+    #[test]
+    fn info_plist_matrix() {
+        info_plist_snapshot("info_plist_default", InfoPlist::default(), None);
+        info_plist_snapshot(
+            "info_plist_app_name",
+            InfoPlist::default(),
+            Some("Snapshot".to_owned()),
+        );
+        let custom = InfoPlist {
+            identification: Identification {
+                bundle_identifier: "com.example.custom".to_owned(),
+                ..Default::default()
+            },
+            bundle_version: BundleVersion {
+                bundle_version: Some("42".to_owned()),
+                bundle_short_version_string: Some("2.1.0".to_owned()),
+                ..Default::default()
+            },
+            styling: Styling {
+                requires_full_screen: Some(true),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        info_plist_snapshot("info_plist_custom", custom, None);
+    }
+
+    #[test]
+    fn entitlements() {
+        let dir = tempfile::tempdir().unwrap();
+        let xcent_path = gen_xcent(
+            dir.path(),
+            "snapshot-example",
+            "TEAMID1234",
+            "com.crossbow.snapshot_example",
+            false,
+        )
+        .unwrap();
+        assert_snapshot(
+            "entitlements_xcent",
+            &std::fs::read_to_string(xcent_path).unwrap(),
+        );
+    }
+
+    #[test]
+    fn app_bundle_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let assets_dir = dir.path().join("assets");
+        std::fs::create_dir_all(assets_dir.join("models")).unwrap();
+        std::fs::write(assets_dir.join("models").join("hero.glb"), "").unwrap();
+        let resources_dir = dir.path().join("resources");
+        std::fs::create_dir_all(resources_dir.join("en.lproj")).unwrap();
+        std::fs::write(
+            resources_dir.join("en.lproj").join("Localizable.strings"),
+            "",
+        )
+        .unwrap();
+        let app_path = gen_apple_app_folder(
+            &dir.path().join("target"),
+            "snapshot-example",
+            Some(assets_dir),
+            Some(resources_dir),
+        )
+        .unwrap();
+        let mut properties = InfoPlist::default();
+        update_info_plist_with_default(&mut properties, "snapshot-example", None);
+        save_info_plist(&app_path, &properties, &Default::default(), false).unwrap();
+        assert_snapshot(
+            "app_bundle_files",
+            &walk_files(&app_path, &app_path).join("\n"),
+        );
+    }
+}
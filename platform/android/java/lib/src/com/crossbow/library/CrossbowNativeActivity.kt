@@ -30,6 +30,8 @@ open class CrossbowNativeActivity : NativeActivity(), CrossbowHost {
 
 	override fun onCreate(savedInstanceState: Bundle?) {
 		super.onCreate(savedInstanceState)
+        // Before the content is shown, so it never appears on screenshots
+        WindowUtil.applySecureWindow(this)
 
         val frame = FrameLayout(this)
         frame.setId(CONTENT_VIEW_ID)
@@ -0,0 +1,90 @@
+@file:Suppress("DEPRECATION")
+
+package com.crossbow.library
+
+import android.app.Activity
+import android.content.pm.PackageManager
+import android.os.Build
+import android.view.View
+import android.view.WindowManager
+import androidx.annotation.Keep
+
+/**
+ * This class includes utility functions for the window of the Crossbow activity.
+ * The native code calls them from the game thread, so window changes are posted to
+ * the UI thread: the view hierarchy throws if it's modified from another thread.
+ */
+@Keep
+object WindowUtil {
+    private const val SECURE_WINDOW_META_DATA = "crossbow.secure_window"
+    private const val IMMERSIVE_SYSTEM_UI_FLAGS = (View.SYSTEM_UI_FLAG_LAYOUT_STABLE
+            or View.SYSTEM_UI_FLAG_LAYOUT_HIDE_NAVIGATION
+            or View.SYSTEM_UI_FLAG_LAYOUT_FULLSCREEN
+            or View.SYSTEM_UI_FLAG_HIDE_NAVIGATION
+            or View.SYSTEM_UI_FLAG_FULLSCREEN
+            or View.SYSTEM_UI_FLAG_IMMERSIVE_STICKY)
+
+    /**
+     * Hides or shows the status and navigation bars.
+     * @param activity the caller activity for this method.
+     * @param immersive "true" to hide the bars until the user swipes from the edge of the screen.
+     * @param cutout "true" to draw into the display cutout area on Android 9.0 and newer.
+     */
+    @JvmStatic
+    fun setImmersive(activity: Activity, immersive: Boolean, cutout: Boolean) {
+        activity.runOnUiThread {
+            val window = activity.window
+            if (immersive) {
+                window.addFlags(WindowManager.LayoutParams.FLAG_FULLSCREEN)
+                window.decorView.systemUiVisibility = IMMERSIVE_SYSTEM_UI_FLAGS
+            } else {
+                window.clearFlags(WindowManager.LayoutParams.FLAG_FULLSCREEN)
+                window.decorView.systemUiVisibility = 0
+            }
+            if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.P) {
+                val attributes = window.attributes
+                attributes.layoutInDisplayCutoutMode = if (immersive && cutout) {
+                    WindowManager.LayoutParams.LAYOUT_IN_DISPLAY_CUTOUT_MODE_SHORT_EDGES
+                } else {
+                    WindowManager.LayoutParams.LAYOUT_IN_DISPLAY_CUTOUT_MODE_DEFAULT
+                }
+                window.attributes = attributes
+            }
+        }
+    }
+
+    /**
+     * Sets or clears FLAG_SECURE of the window.
+     * @param activity the caller activity for this method.
+     * @param secure "true" to hide the window on screenshots, screen recordings and non-secure displays.
+     */
+    @JvmStatic
+    fun setSecure(activity: Activity, secure: Boolean) {
+        activity.runOnUiThread {
+            if (secure) {
+                activity.window.addFlags(WindowManager.LayoutParams.FLAG_SECURE)
+            } else {
+                activity.window.clearFlags(WindowManager.LayoutParams.FLAG_SECURE)
+            }
+        }
+    }
+
+    /**
+     * Sets FLAG_SECURE if the activity has `crossbow.secure_window` meta-data, which the
+     * build adds for `secure_window = true`. Must be invoked on the UI thread.
+     * @param activity the caller activity for this method.
+     * @return true/false. "true" if the window is secure.
+     */
+    @JvmStatic
+    fun applySecureWindow(activity: Activity): Boolean {
+        val info = activity.packageManager.getActivityInfo(
+            activity.componentName,
+            PackageManager.GET_META_DATA
+        )
+        val secure = info.metaData?.getBoolean(SECURE_WINDOW_META_DATA, false) ?: false
+        if (secure) {
+            activity.window.addFlags(WindowManager.LayoutParams.FLAG_SECURE)
+        }
+        return secure
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{error::*, utils::jstring_to_string};
+use jni::{
+    objects::{JObject, JValue},
+    JNIEnv,
+};
+
+/// Status of the download from `DownloadManager.COLUMN_STATUS`.
+///
+/// More details: https://developer.android.com/reference/android/app/DownloadManager
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DownloadStatus {
+    Pending,
+    Running,
+    /// Waits for the network or retries after an error. Resumed by the system.
+    Paused,
+    Successful,
+    Failed,
+}
+
+impl From<i32> for DownloadStatus {
+    fn from(status: i32) -> Self {
+        match status {
+            1 => Self::Pending,
+            2 => Self::Running,
+            4 => Self::Paused,
+            8 => Self::Successful,
+            _ => Self::Failed,
+        }
+    }
+}
+
+/// Row of the `DownloadManager` query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadInfo {
+    pub id: i64,
+    pub status: DownloadStatus,
+    pub downloaded_bytes: i64,
+    /// `-1` until the size is known.
+    pub total_bytes: i64,
+    /// `file://` URI of the downloaded file.
+    pub local_uri: Option<String>,
+    /// Code of the error or the pause reason, e.g. `1006` for `ERROR_INSUFFICIENT_SPACE`.
+    pub reason: i32,
+}
+
+/// Enqueues the download into the system `DownloadManager`. The file is saved into
+/// `file_name` of the app-specific external files directory. Downloads continue when
+/// the application is in the background or killed, and are resumed by the system after
+/// network errors. Returns the download ID.
+pub fn enqueue_download(
+    url: &str,
+    file_name: &str,
+    title: Option<&str>,
+    wifi_only: bool,
+) -> Result<i64> {
+    let (ctx, vm) = crate::get_java_vm()?;
+    let jnienv = vm.attach_current_thread_as_daemon()?;
+    let context = JObject::from(ctx.context() as jni::sys::jobject);
+
+    let uri = jnienv
+        .call_static_method(
+            "android/net/Uri",
+            "parse",
+            "(Ljava/lang/String;)Landroid/net/Uri;",
+            &[jnienv.new_string(url)?.into()],
+        )?
+        .l()?;
+    let request = jnienv.new_object(
+        "android/app/DownloadManager$Request",
+        "(Landroid/net/Uri;)V",
+        &[uri.into()],
+    )?;
+    jnienv.call_method(
+        request,
+        "setDestinationInExternalFilesDir",
+        "(Landroid/content/Context;Ljava/lang/String;Ljava/lang/String;)Landroid/app/DownloadManager$Request;",
+        &[
+            context.into(),
+            JObject::null().into(),
+            jnienv.new_string(file_name)?.into(),
+        ],
+    )?;
+    if let Some(title) = title {
+        jnienv.call_method(
+            request,
+            "setTitle",
+            "(Ljava/lang/CharSequence;)Landroid/app/DownloadManager$Request;",
+            &[jnienv.new_string(title)?.into()],
+        )?;
+    }
+    jnienv.call_method(
+        request,
+        "setAllowedOverMetered",
+        "(Z)Landroid/app/DownloadManager$Request;",
+        &[JValue::Bool((!wifi_only).into())],
+    )?;
+
+    let id = jnienv
+        .call_method(
+            download_manager(&jnienv, context)?,
+            "enqueue",
+            "(Landroid/app/DownloadManager$Request;)J",
+            &[request.into()],
+        )?
+        .j()?;
+    jnienv.exception_check()?;
+    Ok(id)
+}
+
+/// Returns the state of the download or `None` if it was removed.
+pub fn query_download(id: i64) -> Result<Option<DownloadInfo>> {
+    let (ctx, vm) = crate::get_java_vm()?;
+    let jnienv = vm.attach_current_thread_as_daemon()?;
+    let context = JObject::from(ctx.context() as jni::sys::jobject);
+
+    let ids = jnienv.new_long_array(1)?;
+    jnienv.set_long_array_region(ids, 0, &[id])?;
+    let query = jnienv.new_object("android/app/DownloadManager$Query", "()V", &[])?;
+    jnienv.call_method(
+        query,
+        "setFilterById",
+        "([J)Landroid/app/DownloadManager$Query;",
+        &[JObject::from(ids).into()],
+    )?;
+    let cursor = jnienv
+        .call_method(
+            download_manager(&jnienv, context)?,
+            "query",
+            "(Landroid/app/DownloadManager$Query;)Landroid/database/Cursor;",
+            &[query.into()],
+        )?
+        .l()?;
+    if cursor.is_null() {
+        return Ok(None);
+    }
+    let info = if jnienv.call_method(cursor, "moveToFirst", "()Z", &[])?.z()? {
+        let local_uri = cursor_value(&jnienv, cursor, "local_uri", "getString")?.l()?;
+        let local_uri = match local_uri.is_null() {
+            true => None,
+            false => Some(jstring_to_string(&jnienv, local_uri.into())?),
+        };
+        Some(DownloadInfo {
+            id,
+            status: cursor_value(&jnienv, cursor, "status", "getInt")?
+                .i()?
+                .into(),
+            downloaded_bytes: cursor_value(&jnienv, cursor, "bytes_so_far", "getLong")?.j()?,
+            total_bytes: cursor_value(&jnienv, cursor, "total_size", "getLong")?.j()?,
+            local_uri,
+            reason: cursor_value(&jnienv, cursor, "reason", "getInt")?.i()?,
+        })
+    } else {
+        None
+    };
+    jnienv.call_method(cursor, "close", "()V", &[])?;
+    jnienv.exception_check()?;
+    Ok(info)
+}
+
+/// Cancels the download and deletes the downloaded file.
+pub fn remove_download(id: i64) -> Result<()> {
+    let (ctx, vm) = crate::get_java_vm()?;
+    let jnienv = vm.attach_current_thread_as_daemon()?;
+    let context = JObject::from(ctx.context() as jni::sys::jobject);
+    let ids = jnienv.new_long_array(1)?;
+    jnienv.set_long_array_region(ids, 0, &[id])?;
+    jnienv.call_method(
+        download_manager(&jnienv, context)?,
+        "remove",
+        "([J)I",
+        &[JObject::from(ids).into()],
+    )?;
+    jnienv.exception_check()?;
+    Ok(())
+}
+
+fn download_manager<'a>(jnienv: &JNIEnv<'a>, context: JObject<'a>) -> Result<JObject<'a>> {
+    Ok(jnienv
+        .call_method(
+            context,
+            "getSystemService",
+            "(Ljava/lang/String;)Ljava/lang/Object;",
+            &[jnienv.new_string("download")?.into()],
+        )?
+        .l()?)
+}
+
+/// Returns the column value of the current cursor row with `getInt`, `getLong` or
+/// `getString`.
+fn cursor_value<'a>(
+    jnienv: &JNIEnv<'a>,
+    cursor: JObject<'a>,
+    column: &str,
+    getter: &str,
+) -> Result<JValue<'a>> {
+    let index = jnienv
+        .call_method(
+            cursor,
+            "getColumnIndex",
+            "(Ljava/lang/String;)I",
+            &[jnienv.new_string(column)?.into()],
+        )?
+        .i()?;
+    let signature = match getter {
+        "getInt" => "(I)I",
+        "getLong" => "(I)J",
+        _ => "(I)Ljava/lang/String;",
+    };
+    Ok(jnienv.call_method(cursor, getter, signature, &[JValue::Int(index)])?)
+}
@@ -17,6 +17,8 @@ pub enum AndroidError {
     UnsupportedJniRustType(String),
     /// Wrong JNI Rust Type
     WrongJniRustType,
+    /// Failed to start the {0} sensor
+    SensorUnavailable(&'static str),
     /// Rust Jni library error: {0:?}
     Jni(#[from] jni::errors::Error),
     /// Anyhow library errors: {0:?}
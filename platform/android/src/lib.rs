@@ -9,11 +9,15 @@ pub(crate) mod utils;
 #[cfg(feature = "android")]
 mod crossbow;
 #[cfg(feature = "android")]
+pub mod download;
+#[cfg(feature = "android")]
 pub mod error;
 #[cfg(feature = "android")]
 pub mod permission;
 #[cfg(feature = "android")]
 pub mod plugin;
+#[cfg(feature = "android")]
+pub mod window;
 
 #[cfg(feature = "android")]
 pub use crossbow::*;
@@ -0,0 +1,361 @@
+//! Window state and hinge posture for foldables, split-screen and freeform windows,
+//! immersive fullscreen, picture-in-picture and screen capture protection.
+
+use crate::error::*;
+use jni::{
+    objects::{JClass, JObject, JValue},
+    JNIEnv,
+};
+use std::os::raw::{c_int, c_void};
+
+/// `Sensor.TYPE_HINGE_ANGLE`, available since Android 11.
+const SENSOR_TYPE_HINGE_ANGLE: c_int = 36;
+/// `ALOOPER_PREPARE_ALLOW_NON_CALLBACKS`.
+const ALOOPER_PREPARE_ALLOW_NON_CALLBACKS: c_int = 1;
+/// Helper of the Crossbow Java library that changes the window on the UI thread.
+const WINDOW_UTIL_CLASS: &str = "com.crossbow.library.WindowUtil";
+
+/// Size and mode of the activity window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WindowState {
+    /// Width of the window in pixels.
+    pub width: i32,
+    /// Height of the window in pixels.
+    pub height: i32,
+    /// The activity shares the screen in split-screen or freeform mode. Always `false`
+    /// before Android 7.0.
+    pub in_multi_window: bool,
+}
+
+/// Returns the current size and mode of the activity window. The size changes when the
+/// device is folded or unfolded and when the window is resized, poll it after
+/// `ContentRectChanged` and `ConfigChanged` events.
+pub fn window_state() -> Result<WindowState> {
+    let (ctx, vm) = crate::get_java_vm()?;
+    let jnienv = vm.attach_current_thread_as_daemon()?;
+    let activity = JObject::from(ctx.context() as jni::sys::jobject);
+
+    let window = jnienv
+        .call_method(activity, "getWindow", "()Landroid/view/Window;", &[])?
+        .l()?;
+    let decor_view = jnienv
+        .call_method(window, "getDecorView", "()Landroid/view/View;", &[])?
+        .l()?;
+    let width = jnienv
+        .call_method(decor_view, "getWidth", "()I", &[])?
+        .i()?;
+    let height = jnienv
+        .call_method(decor_view, "getHeight", "()I", &[])?
+        .i()?;
+    let in_multi_window = match sdk_int(&jnienv)? >= 24 {
+        true => jnienv
+            .call_method(activity, "isInMultiWindowMode", "()Z", &[])?
+            .z()?,
+        false => false,
+    };
+    Ok(WindowState {
+        width,
+        height,
+        in_multi_window,
+    })
+}
+
+/// Hides the status and navigation bars until the user swipes from the edge of the
+/// screen and lays the game out behind them. With `cutout` the game also draws into the
+/// display cutout area on Android 9.0 and newer, otherwise the cutout is letterboxed.
+///
+/// The change is posted to the UI thread, so the bars are hidden shortly after the call
+/// returns. Call it again after the activity is resumed, the system may show the bars
+/// meanwhile. Requires the Crossbow Java library of the Gradle build strategy.
+pub fn enter_immersive(cutout: bool) -> Result<()> {
+    set_immersive(true, cutout)
+}
+
+/// Shows the status and navigation bars hidden by [`enter_immersive`].
+pub fn exit_immersive() -> Result<()> {
+    set_immersive(false, false)
+}
+
+fn set_immersive(immersive: bool, cutout: bool) -> Result<()> {
+    let (ctx, vm) = crate::get_java_vm()?;
+    let jnienv = vm.attach_current_thread_as_daemon()?;
+    let activity = JObject::from(ctx.context() as jni::sys::jobject);
+
+    call_window_util(
+        &jnienv,
+        activity,
+        "setImmersive",
+        "(Landroid/app/Activity;ZZ)V",
+        &[
+            JValue::Object(activity),
+            JValue::Bool(immersive as jni::sys::jboolean),
+            JValue::Bool(cutout as jni::sys::jboolean),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Shrinks the game into a picture-in-picture window with the aspect ratio of the
+/// window or the given `(width, height)` ratio. Returns `false` before Android 8.0 or if
+/// the system refused, e.g. `picture_in_picture` is not enabled in the manifest.
+pub fn enter_picture_in_picture(aspect_ratio: Option<(i32, i32)>) -> Result<bool> {
+    let (ctx, vm) = crate::get_java_vm()?;
+    let jnienv = vm.attach_current_thread_as_daemon()?;
+    let activity = JObject::from(ctx.context() as jni::sys::jobject);
+
+    if sdk_int(&jnienv)? < 26 {
+        return Ok(false);
+    }
+    let builder = jnienv.new_object("android/app/PictureInPictureParams$Builder", "()V", &[])?;
+    if let Some((width, height)) = aspect_ratio {
+        let rational = jnienv.new_object(
+            "android/util/Rational",
+            "(II)V",
+            &[JValue::Int(width), JValue::Int(height)],
+        )?;
+        jnienv.call_method(
+            builder,
+            "setAspectRatio",
+            "(Landroid/util/Rational;)Landroid/app/PictureInPictureParams$Builder;",
+            &[JValue::Object(rational)],
+        )?;
+    }
+    let params = jnienv
+        .call_method(
+            builder,
+            "build",
+            "()Landroid/app/PictureInPictureParams;",
+            &[],
+        )?
+        .l()?;
+    // Throws if the activity doesn't support picture-in-picture or the ratio is too extreme
+    match jnienv.call_method(
+        activity,
+        "enterPictureInPictureMode",
+        "(Landroid/app/PictureInPictureParams;)Z",
+        &[JValue::Object(params)],
+    ) {
+        Ok(entered) => Ok(entered.z()?),
+        Err(jni::errors::Error::JavaException) => {
+            jnienv.exception_clear()?;
+            Ok(false)
+        }
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Returns `true` while the game is shown in a picture-in-picture window. Pause the
+/// gameplay and hide the controls meanwhile.
+pub fn is_in_picture_in_picture() -> Result<bool> {
+    let (ctx, vm) = crate::get_java_vm()?;
+    let jnienv = vm.attach_current_thread_as_daemon()?;
+    let activity = JObject::from(ctx.context() as jni::sys::jobject);
+
+    if sdk_int(&jnienv)? < 24 {
+        return Ok(false);
+    }
+    Ok(jnienv
+        .call_method(activity, "isInPictureInPictureMode", "()Z", &[])?
+        .z()?)
+}
+
+/// Sets or clears `FLAG_SECURE` of the window. Secure windows are black on screenshots,
+/// screen recordings and non-secure displays, and are hidden in the recent apps.
+///
+/// With `secure_window = true` in the metadata the flag is already set when the activity
+/// is created, use it to toggle the flag at runtime. The change is posted to the UI
+/// thread. Requires the Crossbow Java library of the Gradle build strategy.
+pub fn set_secure(secure: bool) -> Result<()> {
+    let (ctx, vm) = crate::get_java_vm()?;
+    let jnienv = vm.attach_current_thread_as_daemon()?;
+    let activity = JObject::from(ctx.context() as jni::sys::jobject);
+
+    call_window_util(
+        &jnienv,
+        activity,
+        "setSecure",
+        "(Landroid/app/Activity;Z)V",
+        &[
+            JValue::Object(activity),
+            JValue::Bool(secure as jni::sys::jboolean),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Calls the static method of `com.crossbow.library.WindowUtil`, which posts window
+/// changes to the UI thread. The class is loaded with the class loader of the activity,
+/// `FindClass` on the game thread only sees the system classes.
+fn call_window_util<'a>(
+    jnienv: &JNIEnv<'a>,
+    activity: JObject<'a>,
+    name: &str,
+    sig: &str,
+    args: &[JValue<'a>],
+) -> Result<JValue<'a>> {
+    let result = jnienv
+        .call_method(activity, "getClassLoader", "()Ljava/lang/ClassLoader;", &[])
+        .and_then(|class_loader| {
+            let class_name = jnienv.new_string(WINDOW_UTIL_CLASS)?;
+            jnienv.call_method(
+                class_loader.l()?,
+                "loadClass",
+                "(Ljava/lang/String;)Ljava/lang/Class;",
+                &[JValue::Object(class_name.into())],
+            )
+        })
+        .and_then(|class| jnienv.call_static_method(JClass::from(class.l()?), name, sig, args));
+    if let Err(jni::errors::Error::JavaException) = result {
+        // Log and clear the exception, otherwise the next JNI call on this thread aborts
+        jnienv.exception_describe()?;
+        jnienv.exception_clear()?;
+    }
+    Ok(result?)
+}
+
+fn sdk_int(jnienv: &JNIEnv) -> Result<i32> {
+    Ok(jnienv
+        .get_static_field("android/os/Build$VERSION", "SDK_INT", "I")?
+        .i()?)
+}
+
+/// Posture of the foldable device derived from the hinge angle, like `FoldingFeature`
+/// state of Jetpack WindowManager.
+///
+/// More details: https://developer.android.com/guide/topics/large-screens/make-apps-fold-aware
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Posture {
+    /// The device is folded, the inner screen is not visible.
+    Closed,
+    /// The device is partially folded like a book or a laptop, e.g. tabletop mode.
+    HalfOpened,
+    /// The device is completely unfolded.
+    Flat,
+}
+
+impl Posture {
+    /// Returns the posture for the hinge angle in degrees: `0` is closed, `180` is flat.
+    pub fn from_hinge_angle(angle: f32) -> Self {
+        if angle < 30.0 {
+            Self::Closed
+        } else if angle <= 150.0 {
+            Self::HalfOpened
+        } else {
+            Self::Flat
+        }
+    }
+}
+
+/// Hinge angle sensor of foldable devices. Create it on the thread that polls it.
+///
+/// ```no_run
+/// # fn main() -> crossbow_android::error::Result<()> {
+/// use crossbow_android::window::{HingeSensor, Posture};
+///
+/// if let Some(mut hinge) = HingeSensor::new()? {
+///     // Every frame
+///     if let Some(angle) = hinge.poll() {
+///         let tabletop = Posture::from_hinge_angle(angle) == Posture::HalfOpened;
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct HingeSensor {
+    manager: *mut c_void,
+    queue: *mut c_void,
+}
+
+impl HingeSensor {
+    /// Starts receiving hinge angle events. Returns `None` if the device has no hinge
+    /// sensor, e.g. it's not foldable or runs Android 10 or older.
+    pub fn new() -> Result<Option<Self>> {
+        unsafe {
+            let manager = ASensorManager_getInstance();
+            if manager.is_null() {
+                return Ok(None);
+            }
+            let sensor = ASensorManager_getDefaultSensor(manager, SENSOR_TYPE_HINGE_ANGLE);
+            if sensor.is_null() {
+                return Ok(None);
+            }
+            // Events are read without callbacks, the looper only wakes the queue up
+            let looper = ALooper_prepare(ALOOPER_PREPARE_ALLOW_NON_CALLBACKS);
+            let queue = ASensorManager_createEventQueue(
+                manager,
+                looper,
+                0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            );
+            if queue.is_null() {
+                return Err(AndroidError::SensorUnavailable("hinge angle"));
+            }
+            if ASensorEventQueue_enableSensor(queue, sensor) < 0 {
+                ASensorManager_destroyEventQueue(manager, queue);
+                return Err(AndroidError::SensorUnavailable("hinge angle"));
+            }
+            Ok(Some(Self { manager, queue }))
+        }
+    }
+
+    /// Returns the latest hinge angle in degrees received since the last call, or
+    /// `None` if the angle didn't change. Doesn't block.
+    pub fn poll(&mut self) -> Option<f32> {
+        let mut events = [ASensorEvent::default(); 8];
+        let mut angle = None;
+        loop {
+            let count = unsafe {
+                ASensorEventQueue_getEvents(self.queue, events.as_mut_ptr(), events.len())
+            };
+            if count <= 0 {
+                return angle;
+            }
+            angle = Some(events[count as usize - 1].data[0]);
+        }
+    }
+}
+
+impl Drop for HingeSensor {
+    fn drop(&mut self) {
+        unsafe {
+            ASensorManager_destroyEventQueue(self.manager, self.queue);
+        }
+    }
+}
+
+/// `ASensorEvent` from `android/sensor.h`. The data union is read as floats.
+#[repr(C)]
+#[derive(Copy, Clone, Default)]
+#[allow(dead_code)]
+struct ASensorEvent {
+    version: i32,
+    sensor: i32,
+    sensor_type: i32,
+    reserved0: i32,
+    timestamp: i64,
+    data: [f32; 16],
+    flags: u32,
+    reserved1: [i32; 3],
+}
+
+#[link(name = "android")]
+extern "C" {
+    fn ASensorManager_getInstance() -> *mut c_void;
+    fn ASensorManager_getDefaultSensor(manager: *mut c_void, sensor_type: c_int) -> *const c_void;
+    fn ASensorManager_createEventQueue(
+        manager: *mut c_void,
+        looper: *mut c_void,
+        ident: c_int,
+        callback: *mut c_void,
+        data: *mut c_void,
+    ) -> *mut c_void;
+    fn ASensorManager_destroyEventQueue(manager: *mut c_void, queue: *mut c_void) -> c_int;
+    fn ASensorEventQueue_enableSensor(queue: *mut c_void, sensor: *const c_void) -> c_int;
+    fn ASensorEventQueue_getEvents(
+        queue: *mut c_void,
+        events: *mut ASensorEvent,
+        count: usize,
+    ) -> isize;
+    fn ALooper_prepare(opts: c_int) -> *mut c_void;
+}
@@ -1,6 +1,7 @@
 //! Emits linker flags depending on platforms and features.
 
 fn main() {
+    println!("cargo:rustc-link-lib=framework=UIKit");
     #[cfg(feature = "eventkit")]
     println!("cargo:rustc-link-lib=framework=EventKit");
     #[cfg(feature = "avfoundation")]
@@ -0,0 +1,77 @@
+use cocoa_foundation::{
+    base::{id, nil},
+    foundation::NSString,
+};
+use objc::{
+    class, msg_send,
+    runtime::{BOOL, YES},
+    sel, sel_impl,
+};
+
+/// `Info.plist` key generated by `secure_window = true` in the metadata of the game.
+const SECURE_WINDOW_INFO_PLIST_KEY: &str = "CrossbowSecureWindow";
+
+/// Returns `true` while the screen is recorded, mirrored with AirPlay or captured by
+/// QuickTime. iOS doesn't allow blocking the capture, hide sensitive content instead.
+///
+/// More details: https://developer.apple.com/documentation/uikit/uiscreen/2921651-iscaptured
+pub fn is_screen_captured() -> bool {
+    let captured: BOOL = unsafe {
+        let screen: id = msg_send![class!(UIScreen), mainScreen];
+        msg_send![screen, isCaptured]
+    };
+    captured == YES
+}
+
+/// Returns `true` if `secure_window` is enabled in the metadata of the game.
+pub fn secure_window_requested() -> bool {
+    let requested: BOOL = unsafe {
+        let key = NSString::alloc(nil).init_str(SECURE_WINDOW_INFO_PLIST_KEY);
+        let bundle: id = msg_send![class!(NSBundle), mainBundle];
+        let value: id = msg_send![bundle, objectForInfoDictionaryKey: key];
+        if value == nil {
+            return false;
+        }
+        msg_send![value, boolValue]
+    };
+    requested == YES
+}
+
+/// Calls `handler` with [`is_screen_captured`] every time the screen recording or
+/// mirroring starts or stops. The handler is called on the main thread.
+pub fn observe_screen_capture<F>(handler: F)
+where
+    F: Fn(bool) + Send + Sync + 'static,
+{
+    add_observer("UIScreenCapturedDidChangeNotification", move || {
+        handler(is_screen_captured())
+    });
+}
+
+/// Calls `handler` after the user took a screenshot. The screenshot can't be prevented,
+/// e.g. report it to the anti-cheat service.
+pub fn observe_screenshots<F>(handler: F)
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    add_observer("UIApplicationUserDidTakeScreenshotNotification", handler);
+}
+
+fn add_observer<F>(notification_name: &str, handler: F)
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    let block = block::ConcreteBlock::new(move |_notification: id| handler());
+    unsafe {
+        // Values of UIKit notification names are their names
+        let name = NSString::alloc(nil).init_str(notification_name);
+        let center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+        let _: id = msg_send![
+            center,
+            addObserverForName: name
+            object: nil
+            queue: nil
+            usingBlock: block.copy()
+        ];
+    }
+}
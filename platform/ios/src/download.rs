@@ -0,0 +1,294 @@
+use crate::error::*;
+use cocoa_foundation::{
+    base::{id, nil},
+    foundation::{NSString, NSUInteger},
+};
+use objc::{
+    class,
+    declare::ClassDecl,
+    msg_send,
+    runtime::{Class, Object, Sel},
+    sel, sel_impl,
+};
+use std::{
+    collections::BTreeMap,
+    ffi::CStr,
+    os::raw::c_char,
+    path::PathBuf,
+    sync::{Mutex, Once},
+};
+
+/// Identifier of the background `NSURLSession`. Downloads of the previous launches are
+/// reattached when the session with the same identifier is created.
+const SESSION_IDENTIFIER: &str = "com.crossbow.background-downloads";
+
+/// Event of the background download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadEvent {
+    Progress {
+        id: u64,
+        downloaded_bytes: i64,
+        /// `-1` until the size is known.
+        total_bytes: i64,
+    },
+    /// The file was moved to `path` in the downloads directory.
+    Completed { id: u64, path: PathBuf },
+    /// The download failed. It can be continued with `resume_data` if the server
+    /// supports it.
+    Failed {
+        id: u64,
+        reason: String,
+        resume_data: Option<Vec<u8>>,
+    },
+    /// The download was paused with `pause_download`.
+    Paused { id: u64, resume_data: Vec<u8> },
+}
+
+static EVENTS: Mutex<Vec<DownloadEvent>> = Mutex::new(Vec::new());
+/// `NSURLSession` pointer.
+static SESSION: Mutex<Option<usize>> = Mutex::new(None);
+/// Retained `NSURLSessionDownloadTask` pointers of the current launch by task ID.
+static TASKS: Mutex<BTreeMap<u64, usize>> = Mutex::new(BTreeMap::new());
+
+/// Returns the directory completed downloads are moved to:
+/// `Library/Application Support/Downloads` of the application container.
+pub fn downloads_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_default();
+    PathBuf::from(home)
+        .join("Library")
+        .join("Application Support")
+        .join("Downloads")
+}
+
+/// Starts the download in the background `NSURLSession`. The system continues it when
+/// the application is suspended or terminated. Returns the task ID.
+pub fn start_download(url: &str, file_name: &str, wifi_only: bool) -> Result<u64> {
+    let session = background_session();
+    unsafe {
+        let url_string = NSString::alloc(nil).init_str(url);
+        let ns_url: id = msg_send![class!(NSURL), URLWithString: url_string];
+        if ns_url == nil {
+            return Err(IosError::InvalidDownloadUrl(url.to_owned()));
+        }
+        let request: id = msg_send![class!(NSMutableURLRequest), requestWithURL: ns_url];
+        let _: () = msg_send![request, setAllowsCellularAccess: !wifi_only];
+        let task: id = msg_send![session, downloadTaskWithRequest: request];
+        Ok(start_task(task, file_name))
+    }
+}
+
+/// Continues the failed or paused download with its resume data.
+pub fn resume_download(resume_data: &[u8], file_name: &str) -> Result<u64> {
+    let session = background_session();
+    unsafe {
+        let data: id = msg_send![
+            class!(NSData),
+            dataWithBytes: resume_data.as_ptr()
+            length: resume_data.len() as NSUInteger
+        ];
+        let task: id = msg_send![session, downloadTaskWithResumeData: data];
+        if task == nil {
+            return Err(IosError::InvalidResumeData);
+        }
+        Ok(start_task(task, file_name))
+    }
+}
+
+/// Pauses the download started in the current launch. `DownloadEvent::Paused` with the
+/// resume data is emitted when the task is cancelled. Returns `false` if the task is
+/// not found.
+pub fn pause_download(id: u64) -> bool {
+    let task = match TASKS.lock().unwrap().remove(&id) {
+        Some(task) => task as id,
+        None => return false,
+    };
+    let block = block::ConcreteBlock::new(move |data: id| {
+        if let Some(resume_data) = unsafe { data_to_vec(data) } {
+            push_event(DownloadEvent::Paused { id, resume_data });
+        }
+    });
+    unsafe {
+        let _: () = msg_send![task, cancelByProducingResumeData: block.copy()];
+        let _: () = msg_send![task, release];
+    }
+    true
+}
+
+/// Cancels the download started in the current launch. Returns `false` if the task is
+/// not found.
+pub fn cancel_download(id: u64) -> bool {
+    let task = match TASKS.lock().unwrap().remove(&id) {
+        Some(task) => task as id,
+        None => return false,
+    };
+    unsafe {
+        let _: () = msg_send![task, cancel];
+        let _: () = msg_send![task, release];
+    }
+    true
+}
+
+/// Returns events of the downloads since the last call. Creates the background session,
+/// so events of downloads finished while the application was terminated are delivered.
+pub fn poll_download_events() -> Vec<DownloadEvent> {
+    background_session();
+    std::mem::take(&mut *EVENTS.lock().unwrap())
+}
+
+unsafe fn start_task(task: id, file_name: &str) -> u64 {
+    // The file name is kept in the task, so it's known in the next launches too
+    let description = NSString::alloc(nil).init_str(file_name);
+    let _: () = msg_send![task, setTaskDescription: description];
+    let _: () = msg_send![task, resume];
+    let _: id = msg_send![task, retain];
+    let id: NSUInteger = msg_send![task, taskIdentifier];
+    TASKS.lock().unwrap().insert(id as u64, task as usize);
+    id as u64
+}
+
+/// Returns the background session, creating it on the first call.
+fn background_session() -> id {
+    let mut session = SESSION.lock().unwrap();
+    if let Some(session) = *session {
+        return session as id;
+    }
+    unsafe {
+        let identifier = NSString::alloc(nil).init_str(SESSION_IDENTIFIER);
+        let configuration: id = msg_send![
+            class!(NSURLSessionConfiguration),
+            backgroundSessionConfigurationWithIdentifier: identifier
+        ];
+        let _: () = msg_send![configuration, setSessionSendsLaunchEvents: true];
+        let delegate: id = msg_send![delegate_class(), new];
+        let new_session: id = msg_send![
+            class!(NSURLSession),
+            sessionWithConfiguration: configuration
+            delegate: delegate
+            delegateQueue: nil
+        ];
+        let _: id = msg_send![new_session, retain];
+        *session = Some(new_session as usize);
+        new_session
+    }
+}
+
+/// Declares `CrossbowDownloadDelegate` class implementing `NSURLSessionDownloadDelegate`.
+fn delegate_class() -> &'static Class {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        let mut decl = ClassDecl::new("CrossbowDownloadDelegate", class!(NSObject)).unwrap();
+        unsafe {
+            decl.add_method(
+                sel!(URLSession:downloadTask:didFinishDownloadingToURL:),
+                did_finish_downloading as extern "C" fn(&Object, Sel, id, id, id),
+            );
+            decl.add_method(
+                sel!(URLSession:downloadTask:didWriteData:totalBytesWritten:totalBytesExpectedToWrite:),
+                did_write_data as extern "C" fn(&Object, Sel, id, id, i64, i64, i64),
+            );
+            decl.add_method(
+                sel!(URLSession:task:didCompleteWithError:),
+                did_complete as extern "C" fn(&Object, Sel, id, id, id),
+            );
+        }
+        decl.register();
+    });
+    Class::get("CrossbowDownloadDelegate").unwrap()
+}
+
+extern "C" fn did_finish_downloading(_: &Object, _: Sel, _session: id, task: id, location: id) {
+    unsafe {
+        let id = task_id(task);
+        let description: id = msg_send![task, taskDescription];
+        let file_name = nsstring_to_string(description);
+        let location: id = msg_send![location, path];
+        let location = nsstring_to_string(location);
+        // The temporary file is removed when the method returns
+        let path = downloads_dir().join(file_name);
+        let moved = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| {
+                std::fs::rename(&location, &path)
+                    .or_else(|_| std::fs::copy(&location, &path).map(|_| ()))
+            });
+        match moved {
+            Ok(()) => push_event(DownloadEvent::Completed { id, path }),
+            Err(error) => push_event(DownloadEvent::Failed {
+                id,
+                reason: error.to_string(),
+                resume_data: None,
+            }),
+        }
+    }
+}
+
+extern "C" fn did_write_data(
+    _: &Object,
+    _: Sel,
+    _session: id,
+    task: id,
+    _written: i64,
+    downloaded_bytes: i64,
+    total_bytes: i64,
+) {
+    let id = unsafe { task_id(task) };
+    push_event(DownloadEvent::Progress {
+        id,
+        downloaded_bytes,
+        total_bytes,
+    });
+}
+
+extern "C" fn did_complete(_: &Object, _: Sel, _session: id, task: id, error: id) {
+    unsafe {
+        let id = task_id(task);
+        if let Some(task) = TASKS.lock().unwrap().remove(&id) {
+            let _: () = msg_send![task as id, release];
+        }
+        if error == nil {
+            return;
+        }
+        // Paused downloads are reported by `pause_download`
+        let code: isize = msg_send![error, code];
+        const NSURL_ERROR_CANCELLED: isize = -999;
+        if code == NSURL_ERROR_CANCELLED {
+            return;
+        }
+        let description: id = msg_send![error, localizedDescription];
+        let user_info: id = msg_send![error, userInfo];
+        let key = NSString::alloc(nil).init_str("NSURLSessionDownloadTaskResumeData");
+        let resume_data: id = msg_send![user_info, objectForKey: key];
+        push_event(DownloadEvent::Failed {
+            id,
+            reason: nsstring_to_string(description),
+            resume_data: data_to_vec(resume_data),
+        });
+    }
+}
+
+fn push_event(event: DownloadEvent) {
+    EVENTS.lock().unwrap().push(event);
+}
+
+unsafe fn task_id(task: id) -> u64 {
+    let id: NSUInteger = msg_send![task, taskIdentifier];
+    id as u64
+}
+
+unsafe fn nsstring_to_string(string: id) -> String {
+    if string == nil {
+        return String::new();
+    }
+    let utf8: *const c_char = msg_send![string, UTF8String];
+    CStr::from_ptr(utf8).to_string_lossy().into_owned()
+}
+
+unsafe fn data_to_vec(data: id) -> Option<Vec<u8>> {
+    if data == nil {
+        return None;
+    }
+    let length: NSUInteger = msg_send![data, length];
+    let bytes: *const u8 = msg_send![data, bytes];
+    Some(std::slice::from_raw_parts(bytes, length as usize).to_vec())
+}
@@ -7,6 +7,10 @@ pub type Result<T> = std::result::Result<T, IosError>;
 /// Permissions error type.
 #[derive(Display, Debug, Error)]
 pub enum IosError {
+    /// Invalid download URL: {0}
+    InvalidDownloadUrl(String),
+    /// Resume data of the download is invalid or expired
+    InvalidResumeData,
     /// Anyhow library errors: {0:?}
     Anyhow(#[from] anyhow::Error),
 }
@@ -1,2 +1,5 @@
+pub mod capture;
+pub mod download;
 pub mod error;
 pub mod permission;
+pub mod thermal;
@@ -0,0 +1,76 @@
+use cocoa_foundation::{
+    base::{id, nil},
+    foundation::{NSInteger, NSString},
+};
+use objc::{class, msg_send, sel, sel_impl};
+
+/// NSProcessInfoThermalState.
+///
+/// Thermal state of the device. The system reduces CPU and GPU performance on `Serious`
+/// and `Critical` states, so games should lower their workload.
+///
+/// More details: https://developer.apple.com/documentation/foundation/nsprocessinfothermalstate
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ThermalState {
+    /// The thermal state is within normal limits.
+    Nominal,
+    /// The thermal state is slightly elevated.
+    Fair,
+    /// The thermal state is high, performance is reduced.
+    Serious,
+    /// The thermal state is significantly impacting the performance of the system and
+    /// the device needs to cool down.
+    Critical,
+}
+
+impl ThermalState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Nominal => "nominal",
+            Self::Fair => "fair",
+            Self::Serious => "serious",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl From<NSInteger> for ThermalState {
+    fn from(state: NSInteger) -> Self {
+        match state {
+            0 => Self::Nominal,
+            1 => Self::Fair,
+            2 => Self::Serious,
+            _ => Self::Critical,
+        }
+    }
+}
+
+/// Returns the current thermal state of the device.
+pub fn thermal_state() -> ThermalState {
+    let state: NSInteger = unsafe {
+        let process_info: id = msg_send![class!(NSProcessInfo), processInfo];
+        msg_send![process_info, thermalState]
+    };
+    ThermalState::from(state)
+}
+
+/// Calls `handler` with the new state every time the thermal state changes. The handler
+/// is called on the thread that changed the state.
+pub fn observe_thermal_state<F>(handler: F)
+where
+    F: Fn(ThermalState) + Send + Sync + 'static,
+{
+    let block = block::ConcreteBlock::new(move |_notification: id| handler(thermal_state()));
+    unsafe {
+        // The value of `NSProcessInfoThermalStateDidChangeNotification` is its name
+        let name = NSString::alloc(nil).init_str("NSProcessInfoThermalStateDidChangeNotification");
+        let center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+        let _: id = msg_send![
+            center,
+            addObserverForName: name
+            object: nil
+            queue: nil
+            usingBlock: block.copy()
+        ];
+    }
+}
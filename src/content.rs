@@ -0,0 +1,272 @@
+use crate::error::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+/// Name of the content manifest in the root of the bundled assets.
+pub const CONTENT_MANIFEST_NAME: &str = "crossbow-content.json";
+
+/// Paths, sizes and hashes of the content files. Generated by `crossbundle build` into
+/// the bundled assets if `content_manifest` is enabled in the metadata, or with
+/// `ContentManifest::from_dir` for downloaded content.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ContentManifest {
+    pub files: Vec<ContentEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ContentEntry {
+    /// Path relative to the content root with `/` separators.
+    pub path: String,
+    pub size: u64,
+    /// Hex encoded SHA-256 of the file.
+    pub sha256: String,
+}
+
+/// Reason the content file failed the verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentProblem {
+    Missing,
+    SizeMismatch { expected: u64, actual: u64 },
+    HashMismatch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentFailure {
+    pub path: String,
+    pub problem: ContentProblem,
+}
+
+/// Result of the content verification.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentReport {
+    /// Number of the verified files.
+    pub checked: usize,
+    pub failures: Vec<ContentFailure>,
+}
+
+impl ContentReport {
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Passes every failed file to `repair`, e.g. to download it again with
+    /// `BackgroundDownloader`. Stops on the first error.
+    pub fn repair<F>(&self, repair: F) -> Result<()>
+    where
+        F: FnMut(&ContentFailure) -> Result<()>,
+    {
+        self.failures.iter().try_for_each(repair)
+    }
+}
+
+impl ContentManifest {
+    /// Generates the manifest of all files in `dir`. The manifest itself is skipped.
+    pub fn from_dir(dir: &Path) -> Result<Self> {
+        let mut paths = Vec::new();
+        collect_files(dir, &mut paths)?;
+        paths.sort();
+        let mut files = Vec::with_capacity(paths.len());
+        for path in paths {
+            let relative = path
+                .strip_prefix(dir)
+                .unwrap()
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if relative == CONTENT_MANIFEST_NAME {
+                continue;
+            }
+            let data = std::fs::read(&path)?;
+            files.push(ContentEntry {
+                path: relative,
+                size: data.len() as u64,
+                sha256: sha256_hex(&data),
+            });
+        }
+        Ok(Self { files })
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|error| CrossbowError::InvalidContentManifest(error.to_string()))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Writes the manifest into `CONTENT_MANIFEST_NAME` file of `dir`.
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        std::fs::write(dir.join(CONTENT_MANIFEST_NAME), self.to_json())?;
+        Ok(())
+    }
+
+    /// Loads the manifest generated by `crossbundle build` from the bundled assets.
+    pub fn load_bundled() -> Result<Self> {
+        let data =
+            read_bundled_asset(CONTENT_MANIFEST_NAME).map_err(|error| match error.kind() {
+                std::io::ErrorKind::NotFound => CrossbowError::ContentManifestNotFound,
+                _ => error.into(),
+            })?;
+        Self::from_json(&String::from_utf8_lossy(&data))
+    }
+
+    /// Returns SHA-256 of the manifest. It changes with any content file.
+    pub fn fingerprint(&self) -> String {
+        sha256_hex(self.to_json().as_bytes())
+    }
+
+    /// Verifies the files read with `read` by their paths.
+    pub fn verify_with<F>(&self, mut read: F) -> ContentReport
+    where
+        F: FnMut(&str) -> std::io::Result<Vec<u8>>,
+    {
+        let mut report = ContentReport::default();
+        for entry in &self.files {
+            report.checked += 1;
+            let problem = match read(&entry.path) {
+                Err(_) => ContentProblem::Missing,
+                Ok(data) if data.len() as u64 != entry.size => ContentProblem::SizeMismatch {
+                    expected: entry.size,
+                    actual: data.len() as u64,
+                },
+                Ok(data) if sha256_hex(&data) != entry.sha256 => ContentProblem::HashMismatch,
+                Ok(_) => continue,
+            };
+            report.failures.push(ContentFailure {
+                path: entry.path.clone(),
+                problem,
+            });
+        }
+        report
+    }
+
+    /// Verifies the files in `dir`, e.g. the content downloaded after installation.
+    pub fn verify_dir(&self, dir: &Path) -> ContentReport {
+        self.verify_with(|path| std::fs::read(dir.join(path)))
+    }
+
+    /// Verifies the bundled assets.
+    pub fn verify_bundled(&self) -> ContentReport {
+        self.verify_with(read_bundled_asset)
+    }
+}
+
+/// Verifies the bundled assets on the first run and after every update of the content.
+/// The fingerprint of the verified manifest is saved into `data_dir`, so the assets are
+/// not hashed on every launch. Returns `None` if the content was already verified.
+pub fn verify_bundled_content_once(data_dir: &Path) -> Result<Option<ContentReport>> {
+    let manifest = ContentManifest::load_bundled()?;
+    let fingerprint = manifest.fingerprint();
+    let marker_path = data_dir.join(".crossbow-content-verified");
+    if std::fs::read_to_string(&marker_path).ok().as_deref() == Some(fingerprint.as_str()) {
+        return Ok(None);
+    }
+    let report = manifest.verify_bundled();
+    if report.is_ok() {
+        std::fs::create_dir_all(data_dir)?;
+        std::fs::write(marker_path, fingerprint)?;
+    }
+    Ok(Some(report))
+}
+
+/// Reads the file from the bundled assets: the APK assets on **Android** and the
+/// `assets` directory of the application bundle on other platforms.
+pub fn read_bundled_asset(path: &str) -> std::io::Result<Vec<u8>> {
+    #[cfg(all(target_os = "android", feature = "android"))]
+    {
+        let path = std::ffi::CString::new(path)
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidInput, error))?;
+        let mut asset = ndk_glue::native_activity()
+            .asset_manager()
+            .open(&path)
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))?;
+        let mut data = Vec::new();
+        asset.read_to_end(&mut data)?;
+        Ok(data)
+    }
+    #[cfg(not(all(target_os = "android", feature = "android")))]
+    {
+        let mut data = Vec::new();
+        std::fs::File::open(bundled_assets_dir()?.join(path))?.read_to_end(&mut data)?;
+        Ok(data)
+    }
+}
+
+/// `assets` directory next to the executable, or in `Contents/Resources` of macOS apps.
+#[cfg(not(all(target_os = "android", feature = "android")))]
+fn bundled_assets_dir() -> std::io::Result<PathBuf> {
+    let exe_dir = std::env::current_exe()?
+        .parent()
+        .map(Path::to_owned)
+        .unwrap_or_default();
+    let macos_assets = exe_dir.join("../Resources/assets");
+    match macos_assets.exists() {
+        true => Ok(macos_assets),
+        false => Ok(exe_dir.join("assets")),
+    }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_content() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("levels")).unwrap();
+        std::fs::write(dir.path().join("levels/1.ron"), "level").unwrap();
+        std::fs::write(dir.path().join("music.ogg"), "music").unwrap();
+        let manifest = ContentManifest::from_dir(dir.path()).unwrap();
+        manifest.save(dir.path()).unwrap();
+        assert_eq!(
+            ContentManifest::from_dir(dir.path()).unwrap(),
+            manifest,
+            "the manifest must not list itself"
+        );
+        assert_eq!(manifest.files[0].path, "levels/1.ron");
+        assert!(manifest.verify_dir(dir.path()).is_ok());
+
+        std::fs::write(dir.path().join("levels/1.ron"), "LEVEL").unwrap();
+        std::fs::remove_file(dir.path().join("music.ogg")).unwrap();
+        let report = manifest.verify_dir(dir.path());
+        assert_eq!(report.checked, 2);
+        assert_eq!(
+            report.failures,
+            vec![
+                ContentFailure {
+                    path: "levels/1.ron".to_owned(),
+                    problem: ContentProblem::HashMismatch,
+                },
+                ContentFailure {
+                    path: "music.ogg".to_owned(),
+                    problem: ContentProblem::Missing,
+                },
+            ]
+        );
+    }
+}
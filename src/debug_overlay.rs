@@ -0,0 +1,286 @@
+//! Debug overlay with FPS, frame time graph, memory usage, build id and the tail of the
+//! log. Crossbow doesn't depend on any engine, so the overlay collects the data and the
+//! game draws [`DebugOverlay::lines`] and [`DebugOverlay::frame_times`] with its own
+//! renderer while the overlay is visible.
+//!
+//! The overlay is toggled with `overlay on|off|toggle` console commands of the
+//! development channel, e.g. by `crossbundle run --debug-overlay` or
+//! `crossbundle dev overlay`.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// Number of frames shown on the frame time graph.
+pub const FRAME_TIME_HISTORY: usize = 120;
+/// Number of log lines shown on the overlay.
+pub const LOG_TAIL_LINES: usize = 8;
+
+/// Debug overlay state. Clones share the state, so one clone can be moved into the
+/// command handler of the development channel.
+///
+/// ```no_run
+/// # fn main() -> crossbow::error::Result<()> {
+/// use crossbow::{DebugOverlay, DevChannel, DEFAULT_DEV_CHANNEL_PORT};
+///
+/// let overlay = DebugOverlay::new(env!("CARGO_PKG_VERSION"));
+/// let handler = overlay.clone();
+/// let root = "/data/data/com.crossbow.game/files/dev";
+/// # let token = "secret";
+/// let _channel = DevChannel::start(DEFAULT_DEV_CHANNEL_PORT, token, root, move |command| {
+///     handler
+///         .handle_command(command)
+///         .unwrap_or_else(|| format!("unknown command: {}", command))
+/// })?;
+/// loop {
+///     overlay.frame();
+///     if overlay.is_visible() {
+///         for line in overlay.lines() {
+///             // Draw the line
+///         }
+///     }
+/// #   break;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct DebugOverlay {
+    visible: Arc<AtomicBool>,
+    build_id: Arc<str>,
+    state: Arc<Mutex<OverlayState>>,
+}
+
+#[derive(Default)]
+struct OverlayState {
+    last_frame: Option<Instant>,
+    frame_times: VecDeque<Duration>,
+    log_tail: VecDeque<String>,
+}
+
+impl DebugOverlay {
+    /// Creates the hidden overlay. `build_id` is shown to tell builds apart, e.g. the
+    /// package version or the commit hash.
+    pub fn new(build_id: impl Into<String>) -> Self {
+        Self {
+            visible: Arc::new(AtomicBool::new(false)),
+            build_id: build_id.into().into(),
+            state: Arc::new(Mutex::new(OverlayState::default())),
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible.load(Ordering::Relaxed)
+    }
+
+    pub fn set_visible(&self, visible: bool) {
+        self.visible.store(visible, Ordering::Relaxed);
+    }
+
+    /// Toggles the overlay and returns whether it's visible now.
+    pub fn toggle(&self) -> bool {
+        !self.visible.fetch_xor(true, Ordering::Relaxed)
+    }
+
+    /// Marks the beginning of the new frame. Call it once per frame.
+    pub fn frame(&self) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        if let Some(last_frame) = state.last_frame.replace(now) {
+            if state.frame_times.len() == FRAME_TIME_HISTORY {
+                state.frame_times.pop_front();
+            }
+            state.frame_times.push_back(now - last_frame);
+        }
+    }
+
+    /// Times of the last frames for the frame time graph, the oldest first.
+    pub fn frame_times(&self) -> Vec<Duration> {
+        self.state
+            .lock()
+            .unwrap()
+            .frame_times
+            .iter()
+            .copied()
+            .collect()
+    }
+
+    /// Average frames per second over the frame time history.
+    pub fn fps(&self) -> f32 {
+        let state = self.state.lock().unwrap();
+        let total: Duration = state.frame_times.iter().sum();
+        if total.is_zero() {
+            return 0.0;
+        }
+        state.frame_times.len() as f32 / total.as_secs_f32()
+    }
+
+    /// Adds the line to the log tail shown on the overlay.
+    pub fn log(&self, line: impl Into<String>) {
+        let mut state = self.state.lock().unwrap();
+        if state.log_tail.len() == LOG_TAIL_LINES {
+            state.log_tail.pop_front();
+        }
+        state.log_tail.push_back(line.into());
+    }
+
+    /// Text lines of the overlay: FPS and frame time, memory usage, build id and the log
+    /// tail.
+    pub fn lines(&self) -> Vec<String> {
+        let fps = self.fps();
+        let frame_time = if fps > 0.0 { 1000.0 / fps } else { 0.0 };
+        let mut lines = vec![format!("FPS: {:.1} ({:.1} ms)", fps, frame_time)];
+        if let Some(memory) = resident_memory() {
+            lines.push(format!(
+                "Memory: {:.1} MB",
+                memory as f64 / (1024.0 * 1024.0)
+            ));
+        }
+        if let Some(thermal) = thermal_state() {
+            lines.push(format!("Thermal: {}", thermal));
+        }
+        lines.push(format!("Build: {}", self.build_id));
+        lines.extend(self.state.lock().unwrap().log_tail.iter().cloned());
+        lines
+    }
+
+    /// Handles `overlay on`, `overlay off`, `overlay toggle`, `overlay status` and
+    /// `overlay stats` console commands. Returns `None` for other commands.
+    ///
+    /// `overlay stats` returns `fps=<fps> frame_ms=<ms> [thermal=<state>]` line sampled by
+    /// `crossbundle soak`. The frame rate is counted while the overlay is hidden too.
+    pub fn handle_command(&self, command: &str) -> Option<String> {
+        let mut words = command.split_whitespace();
+        if words.next() != Some("overlay") {
+            return None;
+        }
+        let visible = match words.next().unwrap_or("toggle") {
+            "on" => {
+                self.set_visible(true);
+                true
+            }
+            "off" => {
+                self.set_visible(false);
+                false
+            }
+            "toggle" => self.toggle(),
+            "status" => return Some(self.lines().join("\n")),
+            "stats" => return Some(self.stats()),
+            other => return Some(format!("unknown overlay command `{}`", other)),
+        };
+        Some(format!(
+            "overlay is {}",
+            if visible { "visible" } else { "hidden" }
+        ))
+    }
+
+    /// Machine-readable frame rate and thermal state of the device.
+    pub fn stats(&self) -> String {
+        let fps = self.fps();
+        let frame_time = if fps > 0.0 { 1000.0 / fps } else { 0.0 };
+        let mut stats = format!("fps={:.2} frame_ms={:.2}", fps, frame_time);
+        if let Some(thermal) = thermal_state() {
+            stats.push_str(&format!(" thermal={}", thermal));
+        }
+        stats
+    }
+}
+
+/// Returns thermal state of the device reported by the system: `nominal`, `fair`,
+/// `serious` or `critical`. Only supported on iOS, the thermal status of Android devices
+/// is read by `crossbundle soak` with `dumpsys thermalservice`.
+pub fn thermal_state() -> Option<&'static str> {
+    #[cfg(all(target_os = "ios", feature = "ios"))]
+    return Some(crate::ios::thermal::thermal_state().as_str());
+    #[cfg(not(all(target_os = "ios", feature = "ios")))]
+    None
+}
+
+/// Returns resident memory of the process in bytes. Only supported on Android and Linux.
+pub fn resident_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_times() {
+        let overlay = DebugOverlay::new("1.0.0");
+        assert_eq!(overlay.fps(), 0.0);
+        // The first frame only starts the measurement
+        overlay.frame();
+        assert!(overlay.frame_times().is_empty());
+        overlay.frame();
+        assert_eq!(overlay.frame_times().len(), 1);
+
+        overlay.state.lock().unwrap().frame_times = (1..=FRAME_TIME_HISTORY as u64)
+            .map(Duration::from_millis)
+            .collect();
+        overlay.frame();
+        // The oldest frame is dropped from the full history
+        let frame_times = overlay.frame_times();
+        assert_eq!(frame_times.len(), FRAME_TIME_HISTORY);
+        assert_eq!(frame_times[0], Duration::from_millis(2));
+
+        let overlay = DebugOverlay::new("1.0.0");
+        overlay.state.lock().unwrap().frame_times =
+            VecDeque::from(vec![Duration::from_millis(10), Duration::from_millis(30)]);
+        assert!((overlay.fps() - 50.0).abs() < 0.01);
+        assert!(overlay.stats().starts_with("fps=50.00 frame_ms=20.00"));
+    }
+
+    #[test]
+    fn test_log_tail() {
+        let overlay = DebugOverlay::new("1.0.0");
+        for i in 0..LOG_TAIL_LINES + 2 {
+            overlay.log(format!("line {}", i));
+        }
+        let lines = overlay.lines();
+        assert_eq!(lines[0], "FPS: 0.0 (0.0 ms)");
+        assert!(lines.contains(&"Build: 1.0.0".to_owned()));
+        let tail = &lines[lines.len() - LOG_TAIL_LINES..];
+        assert_eq!(tail[0], "line 2");
+        assert_eq!(
+            tail[LOG_TAIL_LINES - 1],
+            format!("line {}", LOG_TAIL_LINES + 1)
+        );
+        assert!(!lines.contains(&"line 1".to_owned()));
+    }
+
+    #[test]
+    fn test_handle_command() {
+        let overlay = DebugOverlay::new("1.0.0");
+        assert_eq!(overlay.handle_command("reload"), None);
+        assert_eq!(
+            overlay.handle_command("overlay").as_deref(),
+            Some("overlay is visible")
+        );
+        assert!(overlay.is_visible());
+        assert_eq!(
+            overlay.handle_command("overlay off").as_deref(),
+            Some("overlay is hidden")
+        );
+        assert_eq!(
+            overlay.handle_command("overlay on").as_deref(),
+            Some("overlay is visible")
+        );
+        assert_eq!(
+            overlay.handle_command("overlay blink").as_deref(),
+            Some("unknown overlay command `blink`")
+        );
+        assert!(overlay
+            .handle_command("overlay status")
+            .unwrap()
+            .contains("Build: 1.0.0"));
+    }
+}
@@ -0,0 +1,289 @@
+//! Development channel between the running game and `crossbundle dev` command. The CLI
+//! pushes assets, runs console commands and pulls files like profiling dumps while the
+//! game is running.
+//!
+//! The channel listens on `127.0.0.1` only. On Android the CLI reaches it through
+//! `adb forward`, the iOS simulator shares the network with the host.
+//!
+//! Protocol: every connection sends the session token line `TOKEN <token>\n`, one request
+//! and receives one response. Requests are a header line followed by an optional body:
+//!
+//! * `PUSH <length> <path>\n<length bytes>` — saves the file into the channel root;
+//! * `PULL <path>\n` — reads the file from the channel root;
+//! * `CMD <command>\n` — runs the console command;
+//! * `PING\n` — checks that the channel is started.
+//!
+//! Responses are `OK <length>\n<length bytes>` or `ERR <message>\n`. Paths are relative to
+//! the channel root and can't contain `..`. Requests with another token are rejected, so
+//! other applications on the device can't push files or run commands.
+
+use crate::error::{CrossbowError, Result};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Port used by `crossbundle dev` by default.
+pub const DEFAULT_DEV_CHANNEL_PORT: u16 = 8741;
+
+/// Connections that don't send or read data for this time are closed.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+type CommandHandler = dyn Fn(&str) -> String + Send + Sync;
+
+/// Development channel server running in a background thread. Every connection is
+/// handled in its own thread. Enable it only in development builds.
+///
+/// ```no_run
+/// # fn main() -> crossbow::error::Result<()> {
+/// use crossbow::{DevChannel, DEFAULT_DEV_CHANNEL_PORT};
+///
+/// let root = "/data/data/com.crossbow.game/files/dev";
+/// # let token = "secret";
+/// // `crossbundle dev` reads the same token from `CROSSBOW_DEV_TOKEN` of its environment
+/// let channel = DevChannel::start(DEFAULT_DEV_CHANNEL_PORT, token, root, |command| {
+///     format!("unknown command: {}", command)
+/// })?;
+/// // Load assets through `pushed_file` to pick up pushed versions
+/// let level = channel.pushed_file("levels/1.ron");
+/// # Ok(())
+/// # }
+/// ```
+pub struct DevChannel {
+    local_addr: SocketAddr,
+    root: PathBuf,
+    changed_files: Arc<Mutex<Vec<PathBuf>>>,
+}
+
+impl DevChannel {
+    /// Starts the channel on `127.0.0.1:<port>`. Only requests with the session `token`
+    /// are accepted. Pushed files are saved into `root` and pulled files are read from
+    /// it. Console commands are passed to `handler`, its result is sent back to the CLI.
+    pub fn start<F>(port: u16, token: &str, root: impl Into<PathBuf>, handler: F) -> Result<Self>
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        if token.trim().is_empty() {
+            return Err(CrossbowError::EmptyDevChannelToken);
+        }
+        let root = root.into();
+        std::fs::create_dir_all(&root)?;
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        let local_addr = listener.local_addr()?;
+        let changed_files = Arc::new(Mutex::new(Vec::new()));
+        let server = Arc::new(Server {
+            root: root.clone(),
+            token: token.to_owned(),
+            changed_files: changed_files.clone(),
+            handler: Box::new(handler),
+        });
+        std::thread::Builder::new()
+            .name("crossbow-dev-channel".to_owned())
+            .spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let server = server.clone();
+                    // A stalled client must not block other connections, and errors of a
+                    // single connection must not stop the channel
+                    std::thread::spawn(move || server.handle_connection(stream).ok());
+                }
+            })?;
+        Ok(Self {
+            local_addr,
+            root,
+            changed_files,
+        })
+    }
+
+    /// Address the channel listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Directory of pushed and pulled files.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns path of the pushed file if it exists.
+    pub fn pushed_file(&self, path: &str) -> Option<PathBuf> {
+        resolve_path(&self.root, path).filter(|path| path.is_file())
+    }
+
+    /// Returns paths of the files pushed since the last call, e.g. to reload them.
+    pub fn take_changed_files(&self) -> Vec<PathBuf> {
+        std::mem::take(&mut *self.changed_files.lock().unwrap())
+    }
+}
+
+struct Server {
+    root: PathBuf,
+    token: String,
+    changed_files: Arc<Mutex<Vec<PathBuf>>>,
+    handler: Box<CommandHandler>,
+}
+
+impl Server {
+    fn handle_connection(&self, stream: TcpStream) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+        stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut token = String::new();
+        reader.read_line(&mut token)?;
+        let response = match token.trim_end_matches('\n').strip_prefix("TOKEN ") {
+            Some(token) if tokens_match(token, &self.token) => {
+                let mut header = String::new();
+                reader.read_line(&mut header)?;
+                self.handle_request(header.trim_end_matches('\n'), &mut reader)
+            }
+            _ => Err("invalid session token".to_owned()),
+        };
+        let mut stream = stream;
+        match response {
+            Ok(body) => {
+                writeln!(stream, "OK {}", body.len())?;
+                stream.write_all(&body)?;
+            }
+            Err(message) => writeln!(stream, "ERR {}", message.replace('\n', " "))?,
+        }
+        stream.flush()
+    }
+
+    fn handle_request(
+        &self,
+        header: &str,
+        reader: &mut impl Read,
+    ) -> std::result::Result<Vec<u8>, String> {
+        let (method, args) = header.split_once(' ').unwrap_or((header, ""));
+        match method {
+            "PUSH" => {
+                let (length, path) = args
+                    .split_once(' ')
+                    .ok_or_else(|| "PUSH requires length and path".to_owned())?;
+                let length: u64 = length.parse().map_err(|_| "invalid length".to_owned())?;
+                let target = self.resolve(path)?;
+                let mut body = Vec::new();
+                reader
+                    .take(length)
+                    .read_to_end(&mut body)
+                    .map_err(|e| e.to_string())?;
+                if (body.len() as u64) < length {
+                    return Err("unexpected end of the file".to_owned());
+                }
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                std::fs::write(&target, body).map_err(|e| e.to_string())?;
+                self.changed_files.lock().unwrap().push(target);
+                Ok(Vec::new())
+            }
+            "PULL" => std::fs::read(self.resolve(args)?).map_err(|e| e.to_string()),
+            "CMD" => Ok((self.handler)(args).into_bytes()),
+            "PING" => Ok(Vec::new()),
+            _ => Err(format!("unknown request `{}`", method)),
+        }
+    }
+
+    fn resolve(&self, path: &str) -> std::result::Result<PathBuf, String> {
+        resolve_path(&self.root, path).ok_or_else(|| format!("invalid path `{}`", path))
+    }
+}
+
+/// Compares the tokens in constant time, so the token can't be guessed by the response
+/// time.
+fn tokens_match(received: &str, expected: &str) -> bool {
+    received.len() == expected.len()
+        && received
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Joins the relative path to the root. Returns `None` for empty, absolute and parent
+/// paths.
+fn resolve_path(root: &Path, path: &str) -> Option<PathBuf> {
+    let path = Path::new(path);
+    let is_relative = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if path.as_os_str().is_empty() || !is_relative {
+        return None;
+    }
+    Some(root.join(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(channel: &DevChannel, token: &str, request: &[u8]) -> String {
+        let mut stream = TcpStream::connect(channel.local_addr()).unwrap();
+        // Written at once, so rejected requests are read before the connection is closed
+        let mut data = format!("TOKEN {}\n", token).into_bytes();
+        data.extend_from_slice(request);
+        stream.write_all(&data).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        let root = Path::new("dev");
+        assert_eq!(
+            resolve_path(root, "levels/1.ron"),
+            Some(root.join("levels").join("1.ron"))
+        );
+        for path in ["", "../secret", "levels/../../secret", "/etc/passwd", "./a"] {
+            assert_eq!(resolve_path(root, path), None, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_dev_channel() {
+        let dir = tempfile::tempdir().unwrap();
+        let channel = DevChannel::start(0, "secret", dir.path(), |command| {
+            format!("ran {}", command)
+        })
+        .unwrap();
+        // Idle connections don't block other requests
+        let _idle = TcpStream::connect(channel.local_addr()).unwrap();
+
+        assert_eq!(
+            request(&channel, "secret", b"PUSH 5 levels/1.ron\nhello"),
+            "OK 0\n"
+        );
+        assert_eq!(
+            channel.pushed_file("levels/1.ron"),
+            Some(dir.path().join("levels").join("1.ron"))
+        );
+        assert_eq!(
+            channel.take_changed_files(),
+            vec![dir.path().join("levels").join("1.ron")]
+        );
+        assert_eq!(
+            request(&channel, "secret", b"PULL levels/1.ron\n"),
+            "OK 5\nhello"
+        );
+        assert_eq!(
+            request(&channel, "secret", b"PULL ../secret\n"),
+            "ERR invalid path `../secret`\n"
+        );
+        assert_eq!(
+            request(&channel, "secret", b"CMD reload\n"),
+            "OK 10\nran reload"
+        );
+        assert_eq!(
+            request(&channel, "guess", b"PUSH 5 levels/1.ron\nworld"),
+            "ERR invalid session token\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("levels").join("1.ron")).unwrap(),
+            "hello"
+        );
+        assert!(DevChannel::start(0, "", dir.path(), |_| String::new()).is_err());
+    }
+}
@@ -0,0 +1,345 @@
+use crate::error::*;
+#[cfg(all(target_os = "android", feature = "android"))]
+use crossbow_android::download as platform;
+#[cfg(all(target_os = "ios", feature = "ios"))]
+use crossbow_ios::download as platform;
+#[cfg(all(target_os = "android", feature = "android"))]
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// ID of the download assigned by the platform downloader.
+pub type DownloadId = u64;
+
+/// Content downloaded after the installation, e.g. from your CDN.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadRequest {
+    pub url: String,
+    /// Name of the file in the downloads directory. Can contain subdirectories.
+    pub file_name: String,
+    /// Title of the system notification. Used only on **Android**.
+    pub title: Option<String>,
+    /// Don't download over cellular and metered networks.
+    pub wifi_only: bool,
+}
+
+impl DownloadRequest {
+    pub fn new(url: &str, file_name: &str) -> Self {
+        Self {
+            url: url.to_owned(),
+            file_name: file_name.to_owned(),
+            title: None,
+            wifi_only: false,
+        }
+    }
+
+    pub fn title(&mut self, title: &str) -> &mut Self {
+        self.title = Some(title.to_owned());
+        self
+    }
+
+    pub fn wifi_only(&mut self, wifi_only: bool) -> &mut Self {
+        self.wifi_only = wifi_only;
+        self
+    }
+}
+
+/// Event of the background download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadEvent {
+    Progress {
+        id: DownloadId,
+        downloaded_bytes: i64,
+        /// `-1` until the size is known.
+        total_bytes: i64,
+    },
+    /// The download is paused. On **Android** the system resumes it when the network is
+    /// available. On **iOS** it's continued with `BackgroundDownloader::resume`.
+    Paused {
+        id: DownloadId,
+        resume_data: Option<Vec<u8>>,
+    },
+    Completed {
+        id: DownloadId,
+        path: PathBuf,
+    },
+    /// The download failed. On **iOS** it can be continued with `resume_data` if the
+    /// server supports range requests.
+    Failed {
+        id: DownloadId,
+        reason: String,
+        resume_data: Option<Vec<u8>>,
+    },
+}
+
+/// Downloads files with the platform background downloader: `DownloadManager` on
+/// **Android** and background `NSURLSession` on **iOS**. Downloads continue when the
+/// application is in the background or terminated.
+///
+/// Platforms: **Android / iOS**.
+///
+/// Required Permissions for **Android**:
+/// * **android.permission.INTERNET**
+#[derive(Debug, Default)]
+pub struct BackgroundDownloader {
+    /// Last status and downloaded bytes of the tracked downloads.
+    #[cfg(all(target_os = "android", feature = "android"))]
+    tracked: BTreeMap<DownloadId, (platform::DownloadStatus, i64)>,
+}
+
+impl BackgroundDownloader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts the download and returns its ID. Persist the ID if the application must
+    /// get events of the download after restart.
+    pub fn start(&mut self, request: &DownloadRequest) -> Result<DownloadId> {
+        #[cfg(all(target_os = "android", feature = "android"))]
+        {
+            let id = platform::enqueue_download(
+                &request.url,
+                &request.file_name,
+                request.title.as_deref(),
+                request.wifi_only,
+            )? as DownloadId;
+            self.track(id);
+            Ok(id)
+        }
+        #[cfg(all(target_os = "ios", feature = "ios"))]
+        {
+            Ok(platform::start_download(
+                &request.url,
+                &request.file_name,
+                request.wifi_only,
+            )?)
+        }
+        #[cfg(not(any(
+            all(target_os = "android", feature = "android"),
+            all(target_os = "ios", feature = "ios")
+        )))]
+        {
+            let _ = request;
+            Err(CrossbowError::UnsupportedDownloadOperation("start"))
+        }
+    }
+
+    /// Reattaches the download started in the previous launch. Required only on
+    /// **Android**, the background session reattaches its downloads on **iOS**.
+    pub fn track(&mut self, id: DownloadId) {
+        #[cfg(all(target_os = "android", feature = "android"))]
+        self.tracked
+            .insert(id, (platform::DownloadStatus::Pending, -1));
+        #[cfg(not(all(target_os = "android", feature = "android")))]
+        let _ = id;
+    }
+
+    /// Continues the download with the resume data of `DownloadEvent::Paused` or
+    /// `DownloadEvent::Failed`. Returns ID of the new download.
+    ///
+    /// Platforms: **iOS**.
+    pub fn resume(&mut self, resume_data: &[u8], file_name: &str) -> Result<DownloadId> {
+        #[cfg(all(target_os = "ios", feature = "ios"))]
+        {
+            Ok(platform::resume_download(resume_data, file_name)?)
+        }
+        #[cfg(not(all(target_os = "ios", feature = "ios")))]
+        {
+            let _ = (resume_data, file_name);
+            Err(CrossbowError::UnsupportedDownloadOperation("resume"))
+        }
+    }
+
+    /// Pauses the download. `DownloadEvent::Paused` with the resume data is emitted.
+    ///
+    /// Platforms: **iOS**.
+    pub fn pause(&mut self, id: DownloadId) -> Result<bool> {
+        #[cfg(all(target_os = "ios", feature = "ios"))]
+        {
+            Ok(platform::pause_download(id))
+        }
+        #[cfg(not(all(target_os = "ios", feature = "ios")))]
+        {
+            let _ = id;
+            Err(CrossbowError::UnsupportedDownloadOperation("pause"))
+        }
+    }
+
+    /// Cancels the download. Partially downloaded file is removed.
+    pub fn cancel(&mut self, id: DownloadId) -> Result<()> {
+        #[cfg(all(target_os = "android", feature = "android"))]
+        {
+            self.tracked.remove(&id);
+            Ok(platform::remove_download(id as i64)?)
+        }
+        #[cfg(all(target_os = "ios", feature = "ios"))]
+        {
+            platform::cancel_download(id);
+            Ok(())
+        }
+        #[cfg(not(any(
+            all(target_os = "android", feature = "android"),
+            all(target_os = "ios", feature = "ios")
+        )))]
+        {
+            let _ = id;
+            Err(CrossbowError::UnsupportedDownloadOperation("cancel"))
+        }
+    }
+
+    /// Returns events of the downloads since the last call. Call it once a frame or on
+    /// a timer.
+    pub fn poll_events(&mut self) -> Result<Vec<DownloadEvent>> {
+        #[cfg(all(target_os = "android", feature = "android"))]
+        {
+            self.poll_android_events()
+        }
+        #[cfg(all(target_os = "ios", feature = "ios"))]
+        {
+            Ok(platform::poll_download_events()
+                .into_iter()
+                .map(Into::into)
+                .collect())
+        }
+        #[cfg(not(any(
+            all(target_os = "android", feature = "android"),
+            all(target_os = "ios", feature = "ios")
+        )))]
+        {
+            Ok(Vec::new())
+        }
+    }
+
+    /// `DownloadManager` has no callbacks for the progress, so the tracked downloads are
+    /// queried and compared with their last state.
+    #[cfg(all(target_os = "android", feature = "android"))]
+    fn poll_android_events(&mut self) -> Result<Vec<DownloadEvent>> {
+        use platform::DownloadStatus;
+        let mut events = Vec::new();
+        let ids: Vec<DownloadId> = self.tracked.keys().copied().collect();
+        for id in ids {
+            let info = match platform::query_download(id as i64)? {
+                Some(info) => info,
+                None => {
+                    self.tracked.remove(&id);
+                    events.push(DownloadEvent::Failed {
+                        id,
+                        reason: "the download was removed".to_owned(),
+                        resume_data: None,
+                    });
+                    continue;
+                }
+            };
+            let (status, downloaded_bytes) = self.tracked[&id];
+            match info.status {
+                DownloadStatus::Successful => {
+                    self.tracked.remove(&id);
+                    let path = info
+                        .local_uri
+                        .as_deref()
+                        .map(uri_to_path)
+                        .unwrap_or_default();
+                    events.push(DownloadEvent::Completed { id, path });
+                    continue;
+                }
+                DownloadStatus::Failed => {
+                    self.tracked.remove(&id);
+                    events.push(DownloadEvent::Failed {
+                        id,
+                        reason: format!("DownloadManager error {}", info.reason),
+                        resume_data: None,
+                    });
+                    continue;
+                }
+                DownloadStatus::Paused if status != DownloadStatus::Paused => {
+                    events.push(DownloadEvent::Paused {
+                        id,
+                        resume_data: None,
+                    });
+                }
+                _ if info.downloaded_bytes != downloaded_bytes => {
+                    events.push(DownloadEvent::Progress {
+                        id,
+                        downloaded_bytes: info.downloaded_bytes,
+                        total_bytes: info.total_bytes,
+                    });
+                }
+                _ => {}
+            }
+            self.tracked
+                .insert(id, (info.status, info.downloaded_bytes));
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(all(target_os = "ios", feature = "ios"))]
+impl From<platform::DownloadEvent> for DownloadEvent {
+    fn from(event: platform::DownloadEvent) -> Self {
+        match event {
+            platform::DownloadEvent::Progress {
+                id,
+                downloaded_bytes,
+                total_bytes,
+            } => Self::Progress {
+                id,
+                downloaded_bytes,
+                total_bytes,
+            },
+            platform::DownloadEvent::Paused { id, resume_data } => Self::Paused {
+                id,
+                resume_data: Some(resume_data),
+            },
+            platform::DownloadEvent::Completed { id, path } => Self::Completed { id, path },
+            platform::DownloadEvent::Failed {
+                id,
+                reason,
+                resume_data,
+            } => Self::Failed {
+                id,
+                reason,
+                resume_data,
+            },
+        }
+    }
+}
+
+/// Converts `file://` URI of `DownloadManager` into the path.
+#[cfg(any(test, all(target_os = "android", feature = "android")))]
+fn uri_to_path(uri: &str) -> PathBuf {
+    let encoded = uri.strip_prefix("file://").unwrap_or(uri).as_bytes();
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        let hex = encoded
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (encoded[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    PathBuf::from(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri_to_path() {
+        assert_eq!(
+            uri_to_path(
+                "file:///storage/emulated/0/Android/data/com.crossbow.game/files/Level%202.pak"
+            ),
+            PathBuf::from("/storage/emulated/0/Android/data/com.crossbow.game/files/Level 2.pak")
+        );
+        assert_eq!(uri_to_path("/data/100%"), PathBuf::from("/data/100%"));
+    }
+}
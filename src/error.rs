@@ -13,6 +13,20 @@ pub enum CrossbowError {
     /// Ios errors
     #[cfg(all(target_os = "ios", feature = "ios"))]
     IosError(#[from] crate::ios::error::IosError),
+    /// Content manifest is not found in the bundled assets. Enable `content_manifest` in the metadata
+    ContentManifestNotFound,
+    /// Invalid content manifest: {0}
+    InvalidContentManifest(String),
+    /// Background downloads do not support `{0}` on this platform
+    UnsupportedDownloadOperation(&'static str),
+    /// Development channel session token must not be empty
+    #[cfg(feature = "dev-channel")]
+    EmptyDevChannelToken,
+    /// Failed to hot reload the game logic library: {0}
+    #[cfg(feature = "hot-reload")]
+    HotReloadFailed(String),
+    /// IO error: {0:?}
+    Io(#[from] std::io::Error),
     /// Anyhow library errors
     Anyhow(#[from] anyhow::Error),
 }
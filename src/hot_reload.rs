@@ -0,0 +1,130 @@
+//! Hot reload of the game logic for development builds. The game logic is built as a
+//! separate `cdylib` crate, `crossbundle dev hot-reload` pushes its new versions into
+//! the `hot-reload` directory of the development channel and the game loads them
+//! without restarting.
+//!
+//! The library exposes `#[no_mangle] pub extern "C"` functions and keeps no state of
+//! its own: all game state lives in the host application and is passed to the
+//! functions, so it survives the reload. Old versions are never unloaded, pointers to
+//! their functions and statics stay valid.
+//!
+//! Works on Android devices and the iOS simulator. iOS devices refuse to load libraries
+//! that are not signed into the application bundle.
+
+use crate::{error::*, DevChannel};
+use libloading::Library;
+use std::path::{Path, PathBuf};
+
+/// Directory of the channel root the libraries are pushed to.
+pub const HOT_RELOAD_DIR: &str = "hot-reload";
+
+/// Loaded versions of the game logic library.
+///
+/// ```no_run
+/// # fn main() -> crossbow::error::Result<()> {
+/// use crossbow::{DevChannel, HotReload, DEFAULT_DEV_CHANNEL_PORT};
+///
+/// # struct State;
+/// # let mut state = State;
+/// # let token = "secret";
+/// let channel = DevChannel::start(DEFAULT_DEV_CHANNEL_PORT, token, "dev", |command| {
+///     format!("unknown command: {}", command)
+/// })?;
+/// let mut logic = HotReload::new();
+/// // Every frame
+/// logic.update(&channel, &channel.take_changed_files())?;
+/// if logic.version() > 0 {
+///     let update = unsafe { logic.symbol::<extern "C" fn(&mut State)>("update")? };
+///     update(&mut state);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct HotReload {
+    libraries: Vec<(PathBuf, Library)>,
+}
+
+impl HotReload {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the newest library among `changed_files` of the channel that were pushed
+    /// into the `hot-reload` directory. Returns `true` if a new version was loaded.
+    pub fn update(&mut self, channel: &DevChannel, changed_files: &[PathBuf]) -> Result<bool> {
+        let path = match newest_library(&channel.root().join(HOT_RELOAD_DIR), changed_files) {
+            Some(path) => path,
+            None => return Ok(false),
+        };
+        self.load(&path)?;
+        Ok(true)
+    }
+
+    /// Loads the library from `path` and makes it the current version.
+    pub fn load(&mut self, path: &Path) -> Result<()> {
+        // Safety: initialization routines of the game logic library are trusted, the
+        // library is built from the game sources by `crossbundle dev hot-reload`
+        let library = unsafe { Library::new(path) }
+            .map_err(|error| CrossbowError::HotReloadFailed(error.to_string()))?;
+        self.libraries.push((path.to_owned(), library));
+        Ok(())
+    }
+
+    /// Number of the loaded versions. Zero if nothing was pushed yet.
+    pub fn version(&self) -> usize {
+        self.libraries.len()
+    }
+
+    /// Path of the current version.
+    pub fn current_path(&self) -> Option<&Path> {
+        self.libraries.last().map(|(path, _)| path.as_path())
+    }
+
+    /// Returns the symbol of the current version, e.g. `extern "C" fn(&mut State)`.
+    ///
+    /// # Safety
+    ///
+    /// `T` must match the type of the exported symbol.
+    pub unsafe fn symbol<T: Copy>(&self, name: &str) -> Result<T> {
+        let (path, library) = self.libraries.last().ok_or_else(|| {
+            CrossbowError::HotReloadFailed("game logic library is not loaded".to_owned())
+        })?;
+        library
+            .get::<T>(name.as_bytes())
+            .map(|symbol| *symbol)
+            .map_err(|error| {
+                CrossbowError::HotReloadFailed(format!("{}: {}", path.display(), error))
+            })
+    }
+}
+
+/// Returns the last pushed library in `dir`. Files are pushed in the order of their
+/// versions, so the last changed file is the newest one.
+fn newest_library(dir: &Path, changed_files: &[PathBuf]) -> Option<PathBuf> {
+    changed_files
+        .iter()
+        .rev()
+        .find(|path| path.parent() == Some(dir))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newest_library() {
+        let dir = Path::new("dev").join(HOT_RELOAD_DIR);
+        let changed_files = vec![
+            dir.join("libgame_logic-1.so"),
+            dir.join("libgame_logic-2.so"),
+            Path::new("dev/levels/1.ron").to_owned(),
+        ];
+        assert_eq!(
+            newest_library(&dir, &changed_files),
+            Some(dir.join("libgame_logic-2.so"))
+        );
+        assert_eq!(newest_library(&dir, &changed_files[2..]), None);
+    }
+}
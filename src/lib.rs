@@ -7,7 +7,23 @@ pub use crossbow_android as android;
 #[cfg(all(target_os = "ios", feature = "ios"))]
 pub use crossbow_ios as ios;
 
+mod content;
+#[cfg(feature = "debug-overlay")]
+mod debug_overlay;
+#[cfg(feature = "dev-channel")]
+mod dev_channel;
+mod download;
 pub mod error;
+#[cfg(feature = "hot-reload")]
+mod hot_reload;
 mod permission;
 
+pub use content::*;
+#[cfg(feature = "debug-overlay")]
+pub use debug_overlay::*;
+#[cfg(feature = "dev-channel")]
+pub use dev_channel::*;
+pub use download::*;
+#[cfg(feature = "hot-reload")]
+pub use hot_reload::*;
 pub use permission::*;